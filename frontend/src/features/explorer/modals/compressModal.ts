@@ -2,6 +2,7 @@ import { invoke } from '@/shared/lib/tauri'
 import { writable, get } from 'svelte/store'
 import { getErrorMessage } from '@/shared/lib/error'
 import type { Entry } from '../model/types'
+import type { ArchiveFormat } from '../services/settings.service'
 
 type ActivityApi = {
   start: (label: string, eventName: string, onCancel?: () => void) => Promise<void>
@@ -41,7 +42,13 @@ export const createCompressModal = (deps: Deps) => {
 
   const close = () => state.set({ open: false, targets: [], error: '' })
 
-  const confirm = async (name: string, level: number) => {
+  const confirm = async (
+    name: string,
+    level: number,
+    format: ArchiveFormat = 'zip',
+    password?: string,
+    volumeSize?: number,
+  ) => {
     const current = get(state)
     if (!current.open || current.targets.length === 0 || busy) {
       close()
@@ -53,12 +60,19 @@ export const createCompressModal = (deps: Deps) => {
     const progressEvent = `compress-progress-${Date.now()}-${Math.random().toString(16).slice(2)}`
     try {
       await activityApi.start('Compressing…', progressEvent, () => activityApi.requestCancel(progressEvent))
-      const base = (name || '').trim().replace(/\.zip$/i, '')
-      const finalName = base.length > 0 ? `${base}.zip` : 'Archive.zip'
-      const dest = await invoke<string>('compress_entries', {
+      const extension = `.${format}`
+      const trimmed = (name || '').trim()
+      const base = trimmed.toLowerCase().endsWith(extension)
+        ? trimmed.slice(0, -extension.length)
+        : trimmed
+      const finalName = `${base.length > 0 ? base : 'Archive'}${extension}`
+      const { destination: dest } = await invoke<{ destination: string }>('compress_entries', {
         paths,
         name: finalName,
         level: lvl,
+        format,
+        password: password || undefined,
+        volumeSize: volumeSize || undefined,
         progressEvent,
       })
       if (reloadCurrent) {
@@ -1,4 +1,37 @@
 import { invoke } from '@/shared/lib/tauri'
 
+export type JobKind = 'paste' | 'transfer' | 'compress' | 'extract' | 'trash' | 'delete'
+
+export type JobStatus = 'queued' | 'running' | 'paused' | 'failed' | 'done'
+
+export type JobInfo = {
+  id: number
+  kind: JobKind
+  status: JobStatus
+  target: string
+  items: number
+  progressEvent: string | null
+  devices: string[]
+  bytes: number
+  total: number
+  bandwidthLimit: number | null
+  error: string | null
+}
+
+/** Emitted with the full job list whenever a job changes. */
+export const JOBS_CHANGED_EVENT = 'jobs-changed'
+
 export const cancelTask = (id: string) =>
   invoke<void>('cancel_task', { id })
+
+export const listJobs = () => invoke<JobInfo[]>('list_jobs')
+
+export const pauseJob = (id: number) => invoke<void>('pause_job', { id })
+
+export const resumeJob = (id: number) => invoke<void>('resume_job', { id })
+
+export const moveJob = (id: number, position: number) =>
+  invoke<void>('move_job', { id, position })
+
+export const setJobBandwidthLimit = (id: number, limit: number | null) =>
+  invoke<void>('set_job_bandwidth_limit', { id, limit })
@@ -1,4 +1,18 @@
 import { invoke } from '@/shared/lib/tauri'
+import type {
+  ConflictPolicy,
+  PreserveAttr,
+  UnpreservedAttr,
+  VerifyMode,
+  VerifyReport,
+} from './files.service'
+
+export type PasteResult = {
+  created: string[]
+  skipped: string[]
+  unpreserved: UnpreservedAttr[]
+  verify: VerifyReport | null
+}
 
 export const copyPathsToSystemClipboard = (paths: string[], mode: 'copy' | 'cut' = 'copy') =>
   invoke<void>('copy_paths_to_system_clipboard', { paths, mode })
@@ -11,9 +25,22 @@ export const clearSystemClipboard = () =>
 
 export const pasteClipboardCmd = (
   dest: string,
-  policy: 'rename' | 'overwrite' = 'rename',
+  policy: ConflictPolicy = 'rename',
   progressEvent?: string,
-) => invoke<void>('paste_clipboard_cmd', { dest, policy, progressEvent })
+  preserve?: PreserveAttr[],
+  decisions?: Record<string, ConflictPolicy>,
+  verify?: VerifyMode,
+  password?: string,
+) =>
+  invoke<PasteResult>('paste_clipboard_cmd', {
+    dest,
+    policy,
+    decisions,
+    progressEvent,
+    preserve,
+    verify,
+    password,
+  })
 
 export const pasteClipboardPreview = (dest: string) =>
   invoke<{ src: string; target: string; is_dir: boolean }[]>('paste_clipboard_preview', { dest })
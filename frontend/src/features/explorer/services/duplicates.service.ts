@@ -1,6 +1,6 @@
 import { invoke } from '@/shared/lib/tauri'
 
-export type DuplicateScanPhase = 'collecting' | 'comparing' | 'done'
+export type DuplicateScanPhase = 'collecting' | 'comparing' | 'prefiltering' | 'hashing' | 'done'
 
 export type DuplicateScanProgress = {
   phase: DuplicateScanPhase
@@ -17,5 +17,32 @@ export type DuplicateScanProgress = {
 export const checkDuplicatesStream = (args: {
   targetPath: string
   startPath: string
+  includeIgnored?: boolean
   progressEvent: string
 }) => invoke<void>('check_duplicates_stream', args)
+
+export type DuplicateGroup = {
+  size: number
+  hash: string
+  paths: string[]
+  reclaimableBytes: number
+}
+
+export type DuplicateGroupsProgress = {
+  phase: DuplicateScanPhase
+  percent: number
+  scannedFiles: number
+  candidateFiles: number
+  hashedFiles: number
+  groupCount: number
+  reclaimableBytes: number
+  done: boolean
+  error?: string | null
+  groups?: DuplicateGroup[] | null
+}
+
+export const findAllDuplicatesStream = (args: {
+  rootPath: string
+  includeIgnored?: boolean
+  progressEvent: string
+}) => invoke<void>('find_all_duplicates_stream', args)
@@ -107,10 +107,55 @@ export const canExtractPaths = (paths: string[]) => {
   return invoke<boolean>('can_extract_paths', { paths })
 }
 
+export type PreserveAttr = 'times' | 'mode' | 'xattr' | 'acl'
+
+export type ConflictPolicy =
+  | 'rename'
+  | 'overwrite'
+  | 'skip'
+  | 'overwrite_if_newer'
+  | 'overwrite_if_larger'
+
+export type VerifyMode = 'checksum' | 'checksum_retry'
+
+export type VerifyReport = {
+  verified: number
+  mismatches: { source: string; target: string; reason: string; retried: boolean }[]
+}
+
+export type UnpreservedAttr = {
+  attribute: PreserveAttr
+  reason: string
+  count: number
+  path: string
+}
+
+export type ExtractOptions = {
+  destination?: 'new_folder' | 'here'
+  conflict?: ConflictPolicy
+  symlinks?: 'skip' | 'restore'
+}
+
+export type ExtractedEntry = {
+  entry: string
+  outcome: 'written' | 'overwritten' | 'renamed' | 'skipped' | 'symlink_restored'
+  path?: string | null
+  reason?: string | null
+}
+
 export type ExtractResult = {
   destination: string
   skipped_symlinks: number
   skipped_entries: number
+  unpreserved: UnpreservedAttr[]
+  entries: ExtractedEntry[]
+}
+
+export type ExtractMembersResult = {
+  extracted: string[]
+  skipped_symlinks: number
+  skipped_entries: number
+  unpreserved: UnpreservedAttr[]
 }
 
 export type ExtractBatchItem = {
@@ -120,16 +165,67 @@ export type ExtractBatchItem = {
   error?: string | null
 }
 
-export const extractArchive = (path: string, progressEvent?: string) => {
+export const extractArchive = (
+  path: string,
+  progressEvent?: string,
+  preserve?: PreserveAttr[],
+  options?: ExtractOptions,
+) => {
   if (isCloudPath(path)) {
     throw new Error('Archive extraction is not supported for cloud entries yet')
   }
-  return invoke<ExtractResult>('extract_archive', { path, progressEvent })
+  return invoke<ExtractResult>('extract_archive', { path, progressEvent, preserve, options })
 }
 
-export const extractArchives = (paths: string[], progressEvent?: string) => {
+export const extractArchiveWithPassword = (
+  path: string,
+  password: string,
+  progressEvent?: string,
+  preserve?: PreserveAttr[],
+  options?: ExtractOptions,
+) => {
+  if (isCloudPath(path)) {
+    throw new Error('Archive extraction is not supported for cloud entries yet')
+  }
+  return invoke<ExtractResult>('extract_archive_with_password', {
+    path,
+    password,
+    progressEvent,
+    preserve,
+    options,
+  })
+}
+
+export const extractArchiveMembers = (
+  path: string,
+  members: string[],
+  dest: string,
+  options: { password?: string; progressEvent?: string; preserve?: PreserveAttr[] } = {},
+) => {
+  if (isCloudPath(path) || isCloudPath(dest)) {
+    throw new Error('Archive extraction is not supported for cloud entries yet')
+  }
+  return invoke<ExtractMembersResult>('extract_archive_members', {
+    path,
+    members,
+    dest,
+    ...options,
+  })
+}
+
+export const extractArchives = (
+  paths: string[],
+  progressEvent?: string,
+  preserve?: PreserveAttr[],
+  options?: ExtractOptions,
+) => {
   if (paths.some(isCloudPath)) {
     throw new Error('Archive extraction is not supported for cloud entries yet')
   }
-  return invoke<ExtractBatchItem[]>('extract_archives', { paths, progressEvent })
+  return invoke<ExtractBatchItem[]>('extract_archives', {
+    paths,
+    progressEvent,
+    preserve,
+    options,
+  })
 }
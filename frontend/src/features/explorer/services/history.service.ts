@@ -5,3 +5,35 @@ export const undoAction = () =>
 
 export const redoAction = () =>
   invoke<void>('redo_action')
+
+export type UndoHistoryItem = {
+  id: number
+  kind:
+    | 'rename'
+    | 'move'
+    | 'copy'
+    | 'create'
+    | 'delete'
+    | 'set_hidden'
+    | 'create_folder'
+    | 'set_permissions'
+    | 'set_ownership'
+    | 'batch'
+  recordedAt: number
+  paths: string[]
+  actionCount: number
+  backupBytes: number
+  undone: boolean
+}
+
+export const listUndoHistory = () =>
+  invoke<UndoHistoryItem[]>('list_undo_history')
+
+export const undoToHistoryEntry = (id: number) =>
+  invoke<number>('undo_to_history_entry', { id })
+
+export const redoToHistoryEntry = (id: number) =>
+  invoke<number>('redo_to_history_entry', { id })
+
+export const undoHistoryEntry = (id: number) =>
+  invoke<void>('undo_history_entry', { id })
@@ -1,11 +1,17 @@
 import { invoke } from '@/shared/lib/tauri'
 import type { Listing, ListingFacets, Partition, SortField, SortDirection } from '../model/types'
 
-export type FacetScope = 'dir' | 'recent' | 'starred' | 'trash'
+export type FacetScope = 'dir' | 'recent' | 'starred' | 'trash' | 'archive' | `saved:${number}`
 
 export const listDir = (path: string | undefined, sort: { field: SortField; direction: SortDirection }) =>
   invoke<Listing>('list_dir', { path, sort })
 
+export const listArchiveEntries = (
+  path: string,
+  sort: { field: SortField; direction: SortDirection } | null,
+  password?: string,
+) => invoke<Listing>('list_archive_entries', { path, sort, password })
+
 export const listRecent = (sort: { field: SortField; direction: SortDirection } | null) =>
   invoke<Listing>('list_recent', { sort })
 
@@ -31,5 +37,7 @@ export const searchStream = (args: {
   path: string
   query: string
   sort: { field: SortField; direction: SortDirection }
+  includeIgnored?: boolean
+  searchArchives?: boolean
   progressEvent: string
 }) => invoke<void>('search_stream', args)
@@ -0,0 +1,39 @@
+import { invoke } from '@/shared/lib/tauri'
+import type { Listing, SortField, SortDirection } from '../model/types'
+
+export type SavedSearchOptions = { includeIgnored: boolean; searchArchives: boolean }
+
+export type SavedSearch = {
+  id: number
+  name: string
+  query: string
+  root: string
+  options: SavedSearchOptions
+}
+
+export const listSavedSearches = () =>
+  invoke<SavedSearch[]>('list_saved_searches')
+
+export const addSavedSearch = (args: {
+  name: string
+  query: string
+  root: string
+  options?: SavedSearchOptions
+}) => invoke<SavedSearch>('add_saved_search', args)
+
+export const updateSavedSearch = (args: {
+  id: number
+  name: string
+  query: string
+  root: string
+  options?: SavedSearchOptions
+}) => invoke<SavedSearch>('update_saved_search', args)
+
+export const removeSavedSearch = (id: number) =>
+  invoke<void>('remove_saved_search', { id })
+
+export const listSavedSearch = (
+  id: number,
+  sort: { field: SortField; direction: SortDirection } | null,
+  cancelId?: string,
+) => invoke<Listing>('list_saved_search', { id, sort, cancelId })
@@ -59,6 +59,13 @@ export const loadArchiveLevel = () => invoke<number | null>('load_archive_level'
 export const storeArchiveLevel = (value: number) =>
   invoke<void>('store_archive_level', { value })
 
+export type ArchiveFormat = 'zip' | 'tar' | 'tar.gz' | 'tar.bz2' | 'tar.xz' | 'tar.zst' | '7z'
+
+export const loadArchiveFormat = () => invoke<ArchiveFormat | null>('load_archive_format')
+
+export const storeArchiveFormat = (value: ArchiveFormat) =>
+  invoke<void>('store_archive_format', { value })
+
 export const loadOpenDestAfterExtract = () => invoke<boolean | null>('load_open_dest_after_extract')
 
 export const storeOpenDestAfterExtract = (value: boolean) =>
@@ -100,6 +107,17 @@ export const loadMountsPollMs = () => invoke<number | null>('load_mounts_poll_ms
 export const storeMountsPollMs = (value: number) =>
   invoke<void>('store_mounts_poll_ms', { value })
 
+export const loadParallelJobsPerDevice = () =>
+  invoke<number | null>('load_parallel_jobs_per_device')
+
+export const storeParallelJobsPerDevice = (value: number) =>
+  invoke<void>('store_parallel_jobs_per_device', { value })
+
+export const loadBandwidthLimit = () => invoke<number | null>('load_bandwidth_limit')
+
+export const storeBandwidthLimit = (value: number) =>
+  invoke<void>('store_bandwidth_limit', { value })
+
 export const loadDoubleClickMs = () => invoke<number | null>('load_double_click_ms')
 
 export const storeDoubleClickMs = (value: number) =>
@@ -1,4 +1,11 @@
 import { invoke } from '@/shared/lib/tauri'
+import type {
+  ConflictPolicy,
+  PreserveAttr,
+  UnpreservedAttr,
+  VerifyMode,
+  VerifyReport,
+} from './files.service'
 
 export type MixedTransferConflictInfo = {
   src: string
@@ -11,6 +18,25 @@ export type MixedTransferWriteOptions = {
   overwrite?: boolean
   prechecked?: boolean
   progressEvent?: string
+  preserve?: PreserveAttr[]
+}
+
+export type MixedTransferBatchOptions = MixedTransferWriteOptions & {
+  policy?: ConflictPolicy
+  decisions?: Record<string, ConflictPolicy>
+  verify?: VerifyMode
+}
+
+export type MixedTransferResult = {
+  created: string[]
+  skipped: string[]
+  unpreserved: UnpreservedAttr[]
+  verify: VerifyReport | null
+}
+
+export type MixedTransferEntryResult = {
+  created: string
+  unpreserved: UnpreservedAttr[]
 }
 
 export const previewMixedTransferConflicts = (sources: string[], destDir: string) =>
@@ -19,27 +45,34 @@ export const previewMixedTransferConflicts = (sources: string[], destDir: string
 export const copyMixedEntries = (
   sources: string[],
   destDir: string,
-  options?: MixedTransferWriteOptions,
+  options?: MixedTransferBatchOptions,
 ) =>
-  invoke<string[]>('copy_mixed_entries', {
+  invoke<MixedTransferResult>('copy_mixed_entries', {
     sources,
     destDir,
     overwrite: options?.overwrite ?? false,
     prechecked: options?.prechecked ?? false,
     progressEvent: options?.progressEvent,
+    preserve: options?.preserve,
+    policy: options?.policy,
+    decisions: options?.decisions,
+    verify: options?.verify,
   })
 
 export const moveMixedEntries = (
   sources: string[],
   destDir: string,
-  options?: MixedTransferWriteOptions,
+  options?: MixedTransferBatchOptions,
 ) =>
-  invoke<string[]>('move_mixed_entries', {
+  invoke<MixedTransferResult>('move_mixed_entries', {
     sources,
     destDir,
     overwrite: options?.overwrite ?? false,
     prechecked: options?.prechecked ?? false,
     progressEvent: options?.progressEvent,
+    preserve: options?.preserve,
+    policy: options?.policy,
+    decisions: options?.decisions,
   })
 
 export const copyMixedEntryTo = (
@@ -47,12 +80,13 @@ export const copyMixedEntryTo = (
   dst: string,
   options?: MixedTransferWriteOptions,
 ) =>
-  invoke<string>('copy_mixed_entry_to', {
+  invoke<MixedTransferEntryResult>('copy_mixed_entry_to', {
     src,
     dst,
     overwrite: options?.overwrite ?? false,
     prechecked: options?.prechecked ?? false,
     progressEvent: options?.progressEvent,
+    preserve: options?.preserve,
   })
 
 export const moveMixedEntryTo = (
@@ -60,10 +94,45 @@ export const moveMixedEntryTo = (
   dst: string,
   options?: MixedTransferWriteOptions,
 ) =>
-  invoke<string>('move_mixed_entry_to', {
+  invoke<MixedTransferEntryResult>('move_mixed_entry_to', {
     src,
     dst,
     overwrite: options?.overwrite ?? false,
     prechecked: options?.prechecked ?? false,
     progressEvent: options?.progressEvent,
+    preserve: options?.preserve,
   })
+
+export type InterruptedTransfer = {
+  id: string
+  op: 'copy' | 'move'
+  kind: 'paste' | 'mixed'
+  sources: string[]
+  dest: string
+  startedAt: number
+  completed: number
+  total: number
+  current: string | null
+}
+
+export type ResumeOutcome = {
+  created: string[]
+  skipped: string[]
+  verify: VerifyReport | null
+}
+
+export type RollbackOutcome = {
+  kept: string[]
+}
+
+export const listInterruptedTransfers = () =>
+  invoke<InterruptedTransfer[]>('list_interrupted_transfers')
+
+export const resumeInterruptedTransfer = (id: string, progressEvent?: string) =>
+  invoke<ResumeOutcome>('resume_interrupted_transfer', { id, progressEvent })
+
+export const rollbackInterruptedTransfer = (id: string) =>
+  invoke<RollbackOutcome>('rollback_interrupted_transfer', { id })
+
+export const discardInterruptedTransfer = (id: string) =>
+  invoke<void>('discard_interrupted_transfer', { id })
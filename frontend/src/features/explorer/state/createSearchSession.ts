@@ -8,6 +8,7 @@ import { appendEntries } from './entryMutations'
 import {
   createSearchProgressEventName,
   mapSearchProgressChunkEntries,
+  rankSearchEntries,
   resolveSearchFinalEntries,
   type SearchProgressPayload,
 } from './searchRuntimeHelpers'
@@ -46,6 +47,7 @@ export const createSearchSession = (deps: Deps) => {
     const runId = deps.getSearchRunId()
 
     let buffer: Entry[] = []
+    const scores = new Map<string, number>()
     let raf: number | null = null
     let stop: (() => void) | null = null
     let cleaned = false
@@ -129,8 +131,9 @@ export const createSearchSession = (deps: Deps) => {
             bufferedEntries: buffer,
           })
           buffer = []
-          // Keep streamed order on completion; sorting remains a manual UI action.
-          deps.entries.set(finalEntries)
+          // Keep streamed order on completion, except that fuzzy terms rank by relevance;
+          // sorting remains a manual UI action.
+          deps.entries.set(rankSearchEntries(finalEntries, scores))
           if (evt.payload.facets) {
             deps.columnFacets.set(evt.payload.facets)
           }
@@ -141,6 +144,9 @@ export const createSearchSession = (deps: Deps) => {
           return
         }
 
+        for (const { path, score } of evt.payload.scores ?? []) {
+          scores.set(path, score)
+        }
         const chunkEntries = mapSearchProgressChunkEntries(evt.payload.entries)
         if (chunkEntries.length > 0) {
           buffer.push(...chunkEntries)
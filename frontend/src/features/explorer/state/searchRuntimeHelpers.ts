@@ -2,11 +2,24 @@ import type { Entry, ListingFacets } from '../model/types'
 import { appendEntries } from './entryMutations'
 import { mapNameLower } from './helpers'
 
+export type SearchContentMatch = {
+  path: string
+  line: number
+  snippet: string
+}
+
+export type SearchScore = {
+  path: string
+  score: number
+}
+
 export type SearchProgressPayload = {
   entries: Entry[]
   done: boolean
   error?: string
   facets?: ListingFacets
+  content_matches?: SearchContentMatch[]
+  scores?: SearchScore[]
 }
 
 export const createSearchProgressEventName = () =>
@@ -27,3 +40,13 @@ export const resolveSearchFinalEntries = (params: {
   return appendEntries(params.currentEntries, params.bufferedEntries)
 }
 
+
+/** Orders entries best fuzzy score first; unscored entries keep their streamed order after them. */
+export const rankSearchEntries = (entries: Entry[], scores: Map<string, number>): Entry[] => {
+  if (scores.size === 0) {
+    return entries
+  }
+  return [...entries].sort(
+    (a, b) => (scores.get(b.path) ?? -1) - (scores.get(a.path) ?? -1),
+  )
+}
@@ -0,0 +1,207 @@
+//! Pasting members of archives that are browsed as folders. Members are read-only, so they
+//! can only be copied, and every pasted member is a new path the undo stack can take away.
+
+use super::{
+    error::{ClipboardError, ClipboardErrorCode, ClipboardResult},
+    ops::{backup_existing_target, metadata_if_exists_nofollow},
+    rename_candidate,
+};
+use crate::{
+    commands::decompress::{self, ArchiveMember, ArchiveScanBudget, MemberTarget},
+    fs_utils::{ConflictPolicies, ConflictPolicy, EntryFacts, MetadataPreserver},
+    undo::{temp_backup_path, Action},
+};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+    time::{Duration, SystemTime},
+};
+
+const MAX_RENAME_ATTEMPTS: usize = 50;
+
+#[derive(Clone, Debug)]
+pub(super) struct ArchiveSource {
+    /// The `archive://` path the member was copied as.
+    pub(super) uri: String,
+    archive: PathBuf,
+    member: String,
+}
+
+impl ArchiveSource {
+    pub(super) fn parse(uri: &str) -> ClipboardResult<Self> {
+        let (archive, member) = decompress::resolve_archive_uri(uri)?;
+        if member.is_empty() {
+            return Err(ClipboardError::invalid_input(
+                "Copy the archive file itself to copy all of its contents",
+            ));
+        }
+        Ok(Self {
+            uri: uri.to_string(),
+            archive,
+            member,
+        })
+    }
+
+    pub(super) fn name(&self) -> &str {
+        self.member.rsplit('/').next().unwrap_or(&self.member)
+    }
+
+    /// The archive file the member is read from.
+    pub(super) fn archive(&self) -> &Path {
+        &self.archive
+    }
+}
+
+/// Copies `sources` into `dest`, reading each archive once for all of its members. Undo
+/// actions for replaced and created paths are appended to `performed` as they happen, and
+/// members the conflict policy keeps out are appended to `skipped`. `password` opens
+/// encrypted zip and 7z archives.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn paste_archive_members(
+    sources: &[ArchiveSource],
+    dest: &Path,
+    policies: &ConflictPolicies,
+    performed: &mut Vec<Action>,
+    skipped: &mut Vec<String>,
+    preserve: &MetadataPreserver,
+    password: Option<&str>,
+    cancel: Option<&AtomicBool>,
+) -> ClipboardResult<Vec<PathBuf>> {
+    let mut created = Vec::with_capacity(sources.len());
+    let mut claimed = HashSet::new();
+    for group in sources.chunk_by(|a, b| a.archive == b.archive) {
+        let mut targets = Vec::with_capacity(group.len());
+        let mut paths = Vec::with_capacity(group.len());
+        let mut listing = None;
+        for source in group {
+            let base = dest.join(source.name());
+            let policy = policies.for_source(&source.uri);
+            if matches!(
+                policy,
+                ConflictPolicy::OverwriteIfNewer | ConflictPolicy::OverwriteIfLarger
+            ) {
+                if let Some(existing) = metadata_if_exists_nofollow(&base)? {
+                    let facts = member_facts(source, &mut listing, password, cancel)?;
+                    if !policy.replaces(&facts, &EntryFacts::from_metadata(&existing)) {
+                        skipped.push(source.uri.clone());
+                        continue;
+                    }
+                }
+            }
+            let Some(target) = pick_target(&base, policy, &mut claimed, performed)? else {
+                skipped.push(source.uri.clone());
+                continue;
+            };
+            let target_name = target
+                .file_name()
+                .ok_or_else(|| ClipboardError::invalid_input("Invalid archive member name"))?
+                .to_os_string();
+            targets.push(MemberTarget {
+                member: PathBuf::from(&source.member),
+                target_name,
+            });
+            paths.push(target);
+        }
+        if targets.is_empty() {
+            continue;
+        }
+        decompress::copy_archive_members(
+            &group[0].archive,
+            &targets,
+            dest,
+            preserve,
+            cancel,
+            password,
+        )?;
+        for path in paths {
+            performed.push(Action::Create {
+                backup: temp_backup_path(&path),
+                path: path.clone(),
+            });
+            created.push(path);
+        }
+    }
+    Ok(created)
+}
+
+/// Size and modification time of `source` as the archive records them. The member list is
+/// read once per archive, and only when a conditional policy needs it.
+fn member_facts(
+    source: &ArchiveSource,
+    listing: &mut Option<Vec<ArchiveMember>>,
+    password: Option<&str>,
+    cancel: Option<&AtomicBool>,
+) -> ClipboardResult<EntryFacts> {
+    if listing.is_none() {
+        *listing = Some(decompress::list_archive_members(
+            &source.archive,
+            password,
+            &ArchiveScanBudget::new(),
+            cancel,
+        )?);
+    }
+    let member = listing
+        .iter()
+        .flatten()
+        .find(|member| member.path == source.member);
+    // Folders that only exist as a prefix of deeper members have no record of their own.
+    Ok(EntryFacts {
+        is_dir: member.is_none_or(|m| m.is_dir),
+        size: member.map_or(0, |m| m.size),
+        modified: member
+            .and_then(|m| m.mtime)
+            .and_then(|secs| u64::try_from(secs).ok())
+            .map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs)),
+    })
+}
+
+/// Chooses where a member named like `base` goes, or None when it is skipped. Renamed
+/// targets skip both existing paths and names already handed to earlier members of the same
+/// paste. Conditional policies were already checked by the caller and replace from here on.
+fn pick_target(
+    base: &Path,
+    policy: ConflictPolicy,
+    claimed: &mut HashSet<PathBuf>,
+    performed: &mut Vec<Action>,
+) -> ClipboardResult<Option<PathBuf>> {
+    match policy {
+        ConflictPolicy::Rename => {
+            for attempt in 0..=MAX_RENAME_ATTEMPTS {
+                let candidate = rename_candidate(base, attempt);
+                if !claimed.contains(&candidate)
+                    && metadata_if_exists_nofollow(&candidate)?.is_none()
+                {
+                    claimed.insert(candidate.clone());
+                    return Ok(Some(candidate));
+                }
+            }
+            Err(ClipboardError::new(
+                ClipboardErrorCode::DestinationExists,
+                format!("No free name for {}", base.display()),
+            ))
+        }
+        ConflictPolicy::Skip => {
+            if claimed.contains(base) || metadata_if_exists_nofollow(base)?.is_some() {
+                return Ok(None);
+            }
+            claimed.insert(base.to_path_buf());
+            Ok(Some(base.to_path_buf()))
+        }
+        ConflictPolicy::Overwrite
+        | ConflictPolicy::OverwriteIfNewer
+        | ConflictPolicy::OverwriteIfLarger => {
+            if let Some(meta) = metadata_if_exists_nofollow(base)? {
+                if meta.file_type().is_symlink() {
+                    return Err(ClipboardError::new(
+                        ClipboardErrorCode::SymlinkUnsupported,
+                        "Refusing to overwrite symlinks",
+                    ));
+                }
+                backup_existing_target(base, performed)?;
+            }
+            claimed.insert(base.to_path_buf());
+            Ok(Some(base.to_path_buf()))
+        }
+    }
+}
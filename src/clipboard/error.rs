@@ -127,6 +127,21 @@ impl From<crate::undo::UndoError> for ClipboardError {
     }
 }
 
+impl From<crate::errors::api_error::ApiError> for ClipboardError {
+    fn from(error: crate::errors::api_error::ApiError) -> Self {
+        let code = match error.code.as_str() {
+            "invalid_input" | "invalid_path" | "unsupported_archive" => {
+                ClipboardErrorCode::InvalidInput
+            }
+            "not_found" => ClipboardErrorCode::NotFound,
+            "symlink_unsupported" => ClipboardErrorCode::SymlinkUnsupported,
+            "cancelled" => ClipboardErrorCode::Cancelled,
+            _ => ClipboardErrorCode::IoError,
+        };
+        Self::new(code, error.message)
+    }
+}
+
 pub(crate) type ClipboardResult<T> = Result<T, ClipboardError>;
 
 pub(crate) fn map_api_result<T>(result: ClipboardResult<T>) -> ApiResult<T> {
@@ -1,26 +1,36 @@
 use crate::{
+    commands::decompress,
     errors::api_error::ApiResult,
-    fs_utils::sanitize_path_follow,
+    fs_utils::{
+        rename_candidate_name, sanitize_path_follow, ConflictPolicies, ConflictPolicy, EntryFacts,
+        FileVerifier, MetadataPreserver, PreserveAttr, PreserveOptions, UnpreservedAttr,
+        VerifyMode, VerifyReport,
+    },
     runtime_lifecycle,
-    tasks::CancelState,
+    tasks::{run_job, CancelState, JobKind, JobSpec},
+    transfer_journal::{TransferJournal, TransferOp, TransferPlan},
     undo::{run_actions, Action, Direction, UndoState},
 };
+mod archive_members;
 mod clipboard_size;
 mod drop_mode;
 mod error;
 mod ops;
+mod resume;
 #[cfg(test)]
 mod tests;
 
+use archive_members::{paste_archive_members, ArchiveSource};
 use clipboard_size::estimate_total_size;
 use error::{map_api_result, ClipboardError, ClipboardErrorCode, ClipboardResult};
 use once_cell::sync::Lazy;
 use serde::Serialize;
 use std::{
+    collections::HashMap,
     fs,
     io::ErrorKind,
     path::{Path, PathBuf},
-    sync::Mutex,
+    sync::{atomic::AtomicBool, Mutex},
 };
 
 #[cfg(test)]
@@ -29,10 +39,11 @@ use drop_mode::resolve_drop_clipboard_mode_impl;
 use ops::copy_file_best_effort;
 use ops::{
     backup_existing_target, copy_entry, is_destination_exists_error, merge_dir,
-    metadata_if_exists_nofollow, move_entry, transfer_cancelled,
+    metadata_if_exists_nofollow, move_entry, pause_checkpoint, transfer_cancelled,
 };
 
 pub use drop_mode::resolve_drop_clipboard_mode;
+pub(crate) use resume::{resume_paste_job, rollback_paste_job};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum ClipboardMode {
@@ -43,15 +54,11 @@ enum ClipboardMode {
 #[derive(Clone)]
 struct ClipboardState {
     entries: Vec<PathBuf>,
+    /// Members of archives browsed as folders; these can only be copied.
+    archive_members: Vec<ArchiveSource>,
     mode: ClipboardMode,
 }
 
-#[derive(Clone, Copy)]
-enum ConflictPolicy {
-    Rename,
-    Overwrite,
-}
-
 #[derive(Serialize, Clone, Copy)]
 pub(crate) struct CopyProgressPayload {
     bytes: u64,
@@ -59,6 +66,18 @@ pub(crate) struct CopyProgressPayload {
     finished: bool,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasteResult {
+    pub created: Vec<String>,
+    /// Sources left in place because the conflict policy kept the existing item.
+    pub skipped: Vec<String>,
+    /// Requested attributes that could not be carried over to the pasted items.
+    pub unpreserved: Vec<UnpreservedAttr>,
+    /// Checksum results, present when verification was requested.
+    pub verify: Option<VerifyReport>,
+}
+
 #[derive(serde::Serialize)]
 pub struct ConflictInfo {
     pub src: String,
@@ -91,14 +110,50 @@ where
 }
 
 fn policy_from_str(policy: &str) -> ClipboardResult<ConflictPolicy> {
-    match policy.to_lowercase().as_str() {
-        "overwrite" => Ok(ConflictPolicy::Overwrite),
-        "rename" => Ok(ConflictPolicy::Rename),
-        other => Err(ClipboardError::new(
+    ConflictPolicy::parse(policy).ok_or_else(|| {
+        ClipboardError::new(
             ClipboardErrorCode::InvalidMode,
-            format!("Invalid conflict policy: {}", other),
-        )),
+            format!("Invalid conflict policy: {}", policy.to_lowercase()),
+        )
+    })
+}
+
+/// Combines the paste-wide `policy` with the per-item `decisions` the frontend collected from
+/// `paste_clipboard_preview`, keyed by the reported `src`.
+fn conflict_policies(
+    policy: Option<String>,
+    decisions: Option<HashMap<String, String>>,
+) -> ClipboardResult<ConflictPolicies> {
+    let default = policy
+        .map(|p| policy_from_str(&p))
+        .transpose()?
+        .unwrap_or(ConflictPolicy::Rename);
+    let items = decisions
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(src, p)| Ok((src, policy_from_str(&p)?)))
+        .collect::<ClipboardResult<HashMap<_, _>>>()?;
+    Ok(ConflictPolicies::new(default, items))
+}
+
+/// Undoes what a failed paste already did and describes the failure.
+fn failed_paste_error(performed: &[Action], src: &str, err: ClipboardError) -> ClipboardError {
+    if !performed.is_empty() {
+        let mut rollback = performed.to_vec();
+        if let Err(rb_err) = run_actions(&mut rollback, Direction::Backward) {
+            return ClipboardError::new(
+                ClipboardErrorCode::RollbackFailed,
+                format!(
+                    "Paste failed for {}: {}; rollback also failed: {}",
+                    src, err, rb_err
+                ),
+            );
+        }
     }
+    ClipboardError::new(
+        ClipboardErrorCode::IoError,
+        format!("Paste failed for {}: {}", src, err),
+    )
 }
 
 fn rename_candidate(base: &Path, idx: usize) -> PathBuf {
@@ -106,21 +161,8 @@ fn rename_candidate(base: &Path, idx: usize) -> PathBuf {
         return base.to_path_buf();
     }
     let parent = base.parent().unwrap_or_else(|| Path::new("."));
-    let original = base
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("item")
-        .to_string();
-    let (stem, ext) = original
-        .rsplit_once('.')
-        .map(|(s, e)| (s.to_string(), Some(e.to_string())))
-        .unwrap_or_else(|| (original.clone(), None));
-
-    let candidate_name = match &ext {
-        Some(ext) => format!("{stem}-{idx}.{ext}"),
-        None => format!("{stem}-{idx}"),
-    };
-    parent.join(candidate_name)
+    let original = base.file_name().and_then(|n| n.to_str()).unwrap_or("item");
+    parent.join(rename_candidate_name(original, idx))
 }
 
 fn current_clipboard() -> Option<ClipboardState> {
@@ -152,8 +194,18 @@ fn set_clipboard_impl(paths: Vec<String>, mode: String) -> ClipboardResult<()> {
     };
 
     let mut entries = Vec::new();
+    let mut archive_members = Vec::new();
     for p in paths {
         reject_cloud_clipboard_path(&p, "set")?;
+        if decompress::is_archive_uri(&p) {
+            if parsed_mode == ClipboardMode::Cut {
+                return Err(ClipboardError::invalid_input(
+                    "Archive members are read-only and can only be copied",
+                ));
+            }
+            archive_members.push(ArchiveSource::parse(&p)?);
+            continue;
+        }
         let meta = fs::symlink_metadata(&p).map_err(|e| {
             ClipboardError::new(
                 ClipboardErrorCode::NotFound,
@@ -173,6 +225,7 @@ fn set_clipboard_impl(paths: Vec<String>, mode: String) -> ClipboardResult<()> {
     let mut guard = CLIPBOARD.lock().unwrap();
     *guard = Some(ClipboardState {
         entries,
+        archive_members,
         mode: parsed_mode,
     });
     Ok(())
@@ -214,49 +267,165 @@ fn paste_clipboard_preview_impl(dest: String) -> ClipboardResult<Vec<ConflictInf
             is_dir,
         });
     }
+    for member in state.archive_members.iter() {
+        let target = dest.join(member.name());
+        conflicts.push(ConflictInfo {
+            src: member.uri.clone(),
+            target: target.to_string_lossy().to_string(),
+            exists: target.exists(),
+            is_dir: target.is_dir(),
+        });
+    }
     Ok(conflicts.into_iter().filter(|c| c.exists).collect())
 }
 
+/// `password` opens the encrypted zip or 7z archives that pasted archive members come from.
+/// It is not logged, stored, or put on the job.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn paste_clipboard_cmd(
     app: tauri::AppHandle,
     dest: String,
     policy: Option<String>,
+    decisions: Option<HashMap<String, String>>,
     undo: tauri::State<'_, UndoState>,
     cancel: tauri::State<'_, CancelState>,
     progress_event: Option<String>,
-) -> ApiResult<Vec<String>> {
+    preserve: Option<Vec<PreserveAttr>>,
+    verify: Option<VerifyMode>,
+    password: Option<String>,
+) -> ApiResult<PasteResult> {
     let undo_inner = undo.clone_inner();
     let cancel_state = cancel.inner().clone();
     let app_handle = app.clone();
-    let join_result = tauri::async_runtime::spawn_blocking(move || {
-        paste_clipboard_impl(
-            app_handle,
-            dest,
-            policy,
-            undo_inner,
-            cancel_state,
-            progress_event,
-        )
-    })
-    .await;
-    match join_result {
-        Ok(result) => map_api_result(result),
-        Err(e) => map_api_result(Err(ClipboardError::new(
-            ClipboardErrorCode::TaskFailed,
-            format!("Paste task failed: {e}"),
-        ))),
+    let job = paste_job(&dest, &progress_event);
+    let work = async move {
+        tauri::async_runtime::spawn_blocking(move || {
+            paste_clipboard_impl(
+                app_handle,
+                dest,
+                policy,
+                decisions,
+                undo_inner,
+                cancel_state,
+                progress_event,
+                preserve,
+                verify,
+                password,
+            )
+        })
+        .await
+        .unwrap_or_else(|e| {
+            Err(ClipboardError::new(
+                ClipboardErrorCode::TaskFailed,
+                format!("Paste task failed: {e}"),
+            ))
+        })
+    };
+    map_api_result(run_job(&app, job, ClipboardError::cancelled, work).await)
+}
+
+/// The queued job for pasting the current clipboard into `dest`.
+fn paste_job(dest: &str, progress_event: &Option<String>) -> JobSpec {
+    let sources = current_clipboard()
+        .map(|state| {
+            state
+                .entries
+                .iter()
+                .map(|path| path.to_string_lossy().to_string())
+                .chain(
+                    state
+                        .archive_members
+                        .iter()
+                        .map(|member| member.archive().to_string_lossy().to_string()),
+                )
+                .collect()
+        })
+        .unwrap_or_default();
+    JobSpec::new(JobKind::Paste, dest, sources, progress_event.clone())
+}
+
+/// What one paste run works with, whether it is a new paste or a resumed one.
+struct PasteRun<'a> {
+    app: &'a tauri::AppHandle,
+    dest: &'a Path,
+    mode: ClipboardMode,
+    policies: &'a ConflictPolicies,
+    preserve: &'a MetadataPreserver,
+    verify: &'a FileVerifier,
+    journal: &'a TransferJournal,
+    progress_event: Option<&'a str>,
+    cancel: Option<&'a AtomicBool>,
+    total_bytes: Option<u64>,
+    total_items: u64,
+}
+
+/// What a paste run has done so far.
+#[derive(Default)]
+struct PasteTally {
+    created: Vec<String>,
+    skipped: Vec<String>,
+    performed: Vec<Action>,
+    done_items: u64,
+}
+
+impl PasteRun<'_> {
+    /// Whether the app is exiting under a journaled paste, which then keeps what it wrote for
+    /// a resume on the next start instead of rolling it back.
+    fn interrupted(&self) -> bool {
+        self.journal.is_active() && runtime_lifecycle::is_shutting_down(self.app)
+    }
+
+    fn failed(&self, tally: &PasteTally, src: &str, err: ClipboardError) -> ClipboardError {
+        if self.interrupted() {
+            return ClipboardError::cancelled();
+        }
+        failed_paste_error(&tally.performed, src, err)
+    }
+
+    fn item_done(&self, tally: &mut PasteTally) {
+        tally.done_items = tally.done_items.saturating_add(1);
+        self.journal
+            .item_done(&tally.performed, &tally.created, &tally.skipped);
+        if self.total_bytes.is_none() {
+            if let Some(evt) = self.progress_event {
+                let _ = runtime_lifecycle::emit_if_running(
+                    self.app,
+                    evt,
+                    CopyProgressPayload {
+                        bytes: tally.done_items,
+                        total: self.total_items,
+                        finished: false,
+                    },
+                );
+            }
+        }
+    }
+}
+
+/// Keeps the journal of a paste the app interrupted by exiting, so it is offered for resume on
+/// the next start, and removes it otherwise.
+fn close_journal(journal: TransferJournal, interrupted: bool, tally: &PasteTally) {
+    if interrupted {
+        journal.suspend(&tally.performed);
+    } else {
+        journal.finish();
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn paste_clipboard_impl(
     app: tauri::AppHandle,
     dest: String,
     policy: Option<String>,
+    decisions: Option<HashMap<String, String>>,
     undo_inner: std::sync::Arc<std::sync::Mutex<crate::undo::UndoManager>>,
     cancel_state: CancelState,
     progress_event: Option<String>,
-) -> ClipboardResult<Vec<String>> {
+    preserve: Option<Vec<PreserveAttr>>,
+    verify: Option<VerifyMode>,
+    password: Option<String>,
+) -> ClipboardResult<PasteResult> {
     if runtime_lifecycle::is_shutting_down(&app) {
         return Err(ClipboardError::cancelled());
     }
@@ -265,10 +434,18 @@ fn paste_clipboard_impl(
     let state = current_clipboard().ok_or_else(|| {
         ClipboardError::new(ClipboardErrorCode::ClipboardEmpty, "Clipboard is empty")
     })?;
-    let policy = policy
-        .map(|p| policy_from_str(&p))
-        .transpose()?
-        .unwrap_or(ConflictPolicy::Rename);
+    let policies = conflict_policies(policy, decisions)?;
+    // A move keeps the item as it was unless the caller says otherwise.
+    let preserve_options = PreserveOptions::from_request(
+        preserve,
+        match state.mode {
+            ClipboardMode::Copy => PreserveOptions::NONE,
+            ClipboardMode::Cut => PreserveOptions::ALL,
+        },
+    );
+    let preserve = MetadataPreserver::new(preserve_options);
+    let verify_mode = verify;
+    let verify = FileVerifier::new(verify_mode);
 
     let cancel_guard = progress_event
         .as_ref()
@@ -277,29 +454,144 @@ fn paste_clipboard_impl(
         .map_err(ClipboardError::from)?;
     let cancel_flag = cancel_guard.as_ref().map(|g| g.token());
 
-    let total_items = state.entries.len() as u64;
+    let total_items = (state.entries.len() + state.archive_members.len()) as u64;
     let total_bytes = progress_event
         .as_ref()
         .map(|evt| estimate_total_size(&state.entries, evt, &app));
-    let mut done_items: u64 = 0;
     if let (Some(evt), Some(total)) = (progress_event.as_ref(), total_bytes) {
         let _ = runtime_lifecycle::emit_if_running(
             &app,
             evt,
             CopyProgressPayload {
-                bytes: done_items,
+                bytes: 0,
                 total,
                 finished: false,
             },
         );
     }
 
-    let mut created = Vec::new();
-    let mut performed: Vec<Action> = Vec::with_capacity(state.entries.len() * 4);
-    for src in state.entries.iter() {
+    // Archive members are extracted again from scratch, so only the local items are journaled.
+    let journal = if state.entries.is_empty() {
+        TransferJournal::disabled()
+    } else {
+        TransferJournal::begin(
+            transfer_op(state.mode),
+            TransferPlan::Paste {
+                sources: state.entries.clone(),
+                dest: dest.clone(),
+                policies: policies.clone(),
+                preserve: preserve_options,
+                verify: verify_mode,
+            },
+        )
+    };
+    let run = PasteRun {
+        app: &app,
+        dest: &dest,
+        mode: state.mode,
+        policies: &policies,
+        preserve: &preserve,
+        verify: &verify,
+        journal: &journal,
+        progress_event: progress_event.as_deref(),
+        cancel: cancel_flag.as_deref(),
+        total_bytes,
+        total_items,
+    };
+    let mut tally = PasteTally::default();
+    let pasted = paste_entries(&run, &state.entries, &mut tally);
+    let interrupted = pasted.is_err() && run.interrupted();
+    close_journal(journal, interrupted, &tally);
+    pasted?;
+
+    if !state.archive_members.is_empty() {
         if transfer_cancelled(cancel_flag.as_deref(), Some(&app)) {
             return Err(ClipboardError::cancelled());
         }
+        match paste_archive_members(
+            &state.archive_members,
+            &dest,
+            &policies,
+            &mut tally.performed,
+            &mut tally.skipped,
+            &preserve,
+            password.as_deref(),
+            cancel_flag.as_deref(),
+        ) {
+            Ok(paths) => {
+                tally.done_items = tally.done_items.saturating_add(paths.len() as u64);
+                tally
+                    .created
+                    .extend(paths.iter().map(|p| p.to_string_lossy().to_string()));
+            }
+            Err(err) => return Err(failed_paste_error(&tally.performed, "archive members", err)),
+        }
+    }
+
+    if let Some(evt) = progress_event.as_ref() {
+        let _ = runtime_lifecycle::emit_if_running(
+            &app,
+            evt,
+            CopyProgressPayload {
+                bytes: total_bytes.unwrap_or(tally.done_items),
+                total: total_bytes.unwrap_or(total_items),
+                finished: true,
+            },
+        );
+    }
+
+    record_paste(&undo_inner, tally.performed);
+
+    if let ClipboardMode::Cut = state.mode {
+        let mut guard = CLIPBOARD.lock().unwrap();
+        *guard = None;
+    }
+
+    Ok(PasteResult {
+        created: tally.created,
+        skipped: tally.skipped,
+        unpreserved: preserve.into_report(),
+        verify: verify.into_report(),
+    })
+}
+
+fn transfer_op(mode: ClipboardMode) -> TransferOp {
+    match mode {
+        ClipboardMode::Copy => TransferOp::Copy,
+        ClipboardMode::Cut => TransferOp::Move,
+    }
+}
+
+/// Records a finished paste as one undo step.
+fn record_paste(
+    undo_inner: &std::sync::Mutex<crate::undo::UndoManager>,
+    mut performed: Vec<Action>,
+) {
+    if performed.is_empty() {
+        return;
+    }
+    let recorded = if performed.len() == 1 {
+        performed.pop().unwrap()
+    } else {
+        Action::Batch(performed)
+    };
+    if let Ok(mut mgr) = undo_inner.lock() {
+        mgr.record_applied(recorded);
+    }
+}
+
+/// Pastes `sources` into the run's destination one after another.
+fn paste_entries(
+    run: &PasteRun<'_>,
+    sources: &[PathBuf],
+    tally: &mut PasteTally,
+) -> ClipboardResult<()> {
+    let app = Some(run.app);
+    for src in sources.iter() {
+        pause_checkpoint(app, run.progress_event);
+        if transfer_cancelled(run.cancel, app) {
+            return Err(ClipboardError::cancelled());
+        }
         let src_meta = match fs::symlink_metadata(src) {
             Ok(meta) => meta,
             Err(e) if e.kind() == ErrorKind::NotFound => {
@@ -325,81 +617,87 @@ fn paste_clipboard_impl(
         let name = src
             .file_name()
             .ok_or_else(|| ClipboardError::invalid_input("Invalid source path"))?;
-        let target_base = dest.join(name);
+        let target_base = run.dest.join(name);
+        let policy = run.policies.for_path(src);
         let mut rename_attempt = 0usize;
-        let mut target = match policy {
-            ConflictPolicy::Rename => rename_candidate(&target_base, rename_attempt),
-            ConflictPolicy::Overwrite => target_base.clone(),
-        };
+        let mut target = target_base.clone();
 
-        if matches!(policy, ConflictPolicy::Overwrite) {
+        if policy != ConflictPolicy::Rename {
             if let Some(target_meta) = metadata_if_exists_nofollow(&target)? {
-                if target_meta.file_type().is_symlink() {
-                    return Err(ClipboardError::new(
-                        ClipboardErrorCode::SymlinkUnsupported,
-                        "Refusing to overwrite symlinks",
-                    ));
-                }
                 // If both are dirs, merge instead of deleting target (Windows Explorer behavior).
-                if src_meta.is_dir() && target_meta.is_dir() {
-                    merge_dir(
+                if src_meta.is_dir() && target_meta.is_dir() && policy.merges_folders() {
+                    let kept = merge_dir(
                         src,
                         &target,
-                        state.mode,
-                        &mut performed,
-                        Some(&app),
-                        progress_event.as_deref(),
-                        cancel_flag.as_deref(),
+                        run.mode,
+                        run.policies,
+                        &mut tally.performed,
+                        run.preserve,
+                        run.verify,
+                        run.journal,
+                        app,
+                        run.progress_event,
+                        run.cancel,
                     )?;
-                    created.push(target.to_string_lossy().to_string());
+                    tally
+                        .skipped
+                        .extend(kept.iter().map(|p| p.to_string_lossy().to_string()));
+                    tally.created.push(target.to_string_lossy().to_string());
+                    run.item_done(tally);
+                    continue;
+                }
+                let src_facts = EntryFacts::from_metadata(&src_meta);
+                if !policy.replaces(&src_facts, &EntryFacts::from_metadata(&target_meta)) {
+                    tally.skipped.push(src.to_string_lossy().to_string());
+                    run.item_done(tally);
                     continue;
                 }
+                if target_meta.file_type().is_symlink() {
+                    return Err(ClipboardError::new(
+                        ClipboardErrorCode::SymlinkUnsupported,
+                        "Refusing to overwrite symlinks",
+                    ));
+                }
                 // Prevent deleting parent/ancestor of the source.
                 if src.starts_with(&target) {
                     return Err(ClipboardError::invalid_input(
                         "Cannot overwrite a parent directory of the source item",
                     ));
                 }
-                backup_existing_target(&target, &mut performed)?;
+                backup_existing_target(&target, &mut tally.performed)?;
+                run.journal.record_actions(&tally.performed);
             }
         }
 
-        loop {
-            let result = match state.mode {
+        let source_moved = loop {
+            run.journal
+                .begin_item(&src.to_string_lossy(), &target.to_string_lossy(), true);
+            let result = match run.mode {
                 ClipboardMode::Copy => copy_entry(
                     src,
                     &target,
-                    Some(&app),
-                    progress_event.as_deref(),
-                    cancel_flag.as_deref(),
-                ),
+                    run.preserve,
+                    run.verify,
+                    run.journal,
+                    app,
+                    run.progress_event,
+                    run.cancel,
+                )
+                .map(|_| false),
                 ClipboardMode::Cut => move_entry(
                     src,
                     &target,
-                    Some(&app),
-                    progress_event.as_deref(),
-                    cancel_flag.as_deref(),
+                    run.preserve,
+                    run.verify,
+                    run.journal,
+                    app,
+                    run.progress_event,
+                    run.cancel,
                 ),
             };
 
             match result {
-                Ok(_) => {
-                    done_items = done_items.saturating_add(1);
-                    if total_bytes.is_none() {
-                        if let Some(evt) = progress_event.as_ref() {
-                            let _ = runtime_lifecycle::emit_if_running(
-                                &app,
-                                evt,
-                                CopyProgressPayload {
-                                    bytes: done_items,
-                                    total: total_items,
-                                    finished: false,
-                                },
-                            );
-                        }
-                    }
-                    break;
-                }
+                Ok(moved) => break moved,
                 Err(err) => {
                     if matches!(policy, ConflictPolicy::Rename)
                         && is_destination_exists_error(&err)
@@ -409,67 +707,26 @@ fn paste_clipboard_impl(
                         target = rename_candidate(&target_base, rename_attempt);
                         continue;
                     }
-                    if !performed.is_empty() {
-                        let mut rollback = performed.clone();
-                        if let Err(rb_err) = run_actions(&mut rollback, Direction::Backward) {
-                            return Err(ClipboardError::new(
-                                ClipboardErrorCode::RollbackFailed,
-                                format!(
-                                    "Paste failed for {:?}: {}; rollback also failed: {}",
-                                    src, err, rb_err
-                                ),
-                            ));
-                        }
-                    }
-                    return Err(ClipboardError::new(
-                        ClipboardErrorCode::IoError,
-                        format!("Paste failed for {:?}: {}", src, err),
-                    ));
+                    return Err(run.failed(tally, &format!("{:?}", src), err));
                 }
             }
-        }
-
-        let action = match state.mode {
-            ClipboardMode::Copy => Action::Copy {
-                from: src.clone(),
-                to: target.clone(),
-            },
-            ClipboardMode::Cut => Action::Move {
-                from: src.clone(),
-                to: target.clone(),
-            },
         };
-        performed.push(action);
-        created.push(target.to_string_lossy().to_string());
-    }
-
-    if let Some(evt) = progress_event.as_ref() {
-        let _ = runtime_lifecycle::emit_if_running(
-            &app,
-            evt,
-            CopyProgressPayload {
-                bytes: total_bytes.unwrap_or(done_items),
-                total: total_bytes.unwrap_or(total_items),
-                finished: true,
-            },
-        );
-    }
 
-    if !performed.is_empty() {
-        let recorded = if performed.len() == 1 {
-            performed.pop().unwrap()
-        } else {
-            Action::Batch(performed)
-        };
-        if let Ok(mut mgr) = undo_inner.lock() {
-            mgr.record_applied(recorded);
-        }
+        tally
+            .performed
+            .push(pasted_action(src, &target, source_moved));
+        tally.created.push(target.to_string_lossy().to_string());
+        run.item_done(tally);
     }
+    Ok(())
+}
 
-    if let ClipboardMode::Cut = state.mode {
-        let mut guard = CLIPBOARD.lock().unwrap();
-        *guard = None;
+/// A cut whose copy failed verification kept its source, so undo only removes the copy.
+fn pasted_action(src: &Path, target: &Path, source_moved: bool) -> Action {
+    let (from, to) = (src.to_path_buf(), target.to_path_buf());
+    if source_moved {
+        Action::Move { from, to }
+    } else {
+        Action::Copy { from, to }
     }
-
-    Ok(created)
 }
@@ -1,5 +1,10 @@
 use crate::{
-    runtime_lifecycle,
+    fs_utils::{
+        bandwidth::RateLimiter, compare_files, copy_file_fast, ConflictPolicies, ConflictPolicy,
+        EntryFacts, FastCopy, FileVerifier, MetadataPreserver,
+    },
+    runtime_lifecycle, tasks,
+    transfer_journal::{JournalEntry, PartialFile, TransferJournal},
     undo::{move_with_fallback, temp_backup_path, Action},
 };
 #[cfg(not(target_os = "windows"))]
@@ -8,16 +13,18 @@ use std::io::BufRead;
 use std::process::Command;
 use std::{
     fs,
-    io::{ErrorKind, Read, Write},
-    path::Path,
+    io::{ErrorKind, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
     sync::atomic::AtomicBool,
 };
 
 use super::{
     error::{ClipboardError, ClipboardErrorCode, ClipboardResult},
-    ClipboardMode, CopyProgressPayload,
+    rename_candidate, ClipboardMode, CopyProgressPayload,
 };
 
+const MAX_RENAME_ATTEMPTS: usize = 50;
+
 fn ensure_not_child(src: &Path, dest: &Path) -> ClipboardResult<()> {
     if dest.starts_with(src) {
         return Err(ClipboardError::invalid_input(
@@ -39,6 +46,13 @@ pub(super) fn transfer_cancelled(
             .unwrap_or(false)
 }
 
+/// Holds a copy between chunks or entries while its job is paused.
+pub(super) fn pause_checkpoint(app: Option<&tauri::AppHandle>, progress_event: Option<&str>) {
+    if let (Some(app), Some(event)) = (app, progress_event) {
+        tasks::pause_checkpoint(app, event);
+    }
+}
+
 fn emit_copy_progress(
     app: Option<&tauri::AppHandle>,
     event: Option<&str>,
@@ -49,9 +63,13 @@ fn emit_copy_progress(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn copy_dir(
     src: &Path,
     dest: &Path,
+    preserve: &MetadataPreserver,
+    verify: &FileVerifier,
+    journal: &TransferJournal,
     app: Option<&tauri::AppHandle>,
     progress_event: Option<&str>,
     cancel: Option<&AtomicBool>,
@@ -88,6 +106,8 @@ fn copy_dir(
                 format!("Failed to read metadata: {e}"),
             )
         })?;
+        pause_checkpoint(app, progress_event);
+
         if transfer_cancelled(cancel, app) {
             return Err(ClipboardError::cancelled());
         }
@@ -100,11 +120,31 @@ fn copy_dir(
         let target = dest.join(entry.file_name());
         if meta.is_dir() {
             ensure_not_child(&path, &target)?;
-            copy_dir(&path, &target, app, progress_event, cancel)?;
+            copy_dir(
+                &path,
+                &target,
+                preserve,
+                verify,
+                journal,
+                app,
+                progress_event,
+                cancel,
+            )?;
         } else {
-            copy_file_best_effort(&path, &target, app, progress_event, cancel, None)?;
+            copy_file_best_effort(
+                &path,
+                &target,
+                preserve,
+                verify,
+                journal,
+                app,
+                progress_event,
+                cancel,
+                None,
+            )?;
         }
     }
+    preserve.copy_from(src, dest);
     Ok(())
 }
 
@@ -130,15 +170,22 @@ pub(super) fn backup_existing_target(
     Ok(())
 }
 
+/// Merges `src` into the existing folder `dest`, resolving every conflict inside with
+/// `policies`. Returns the sources that were kept back; a cut leaves their folders in place.
+#[allow(clippy::too_many_arguments)]
 pub(super) fn merge_dir(
     src: &Path,
     dest: &Path,
     mode: ClipboardMode,
+    policies: &ConflictPolicies,
     actions: &mut Vec<Action>,
+    preserve: &MetadataPreserver,
+    verify: &FileVerifier,
+    journal: &TransferJournal,
     app: Option<&tauri::AppHandle>,
     progress_event: Option<&str>,
     cancel: Option<&AtomicBool>,
-) -> ClipboardResult<()> {
+) -> ClipboardResult<Vec<PathBuf>> {
     // Ensure both exist and are directories.
     let src_meta = fs::symlink_metadata(src).map_err(|e| {
         ClipboardError::new(
@@ -159,6 +206,8 @@ pub(super) fn merge_dir(
         ));
     }
 
+    let mut skipped = Vec::new();
+    let mut kept_unverified = false;
     for entry in fs::read_dir(src).map_err(|e| {
         ClipboardError::new(
             ClipboardErrorCode::IoError,
@@ -184,72 +233,115 @@ pub(super) fn merge_dir(
                 "Refusing to copy symlinks",
             ));
         }
+        pause_checkpoint(app, progress_event);
+
         if transfer_cancelled(cancel, app) {
             return Err(ClipboardError::cancelled());
         }
-        let target = dest.join(entry.file_name());
-        let target_meta = metadata_if_exists_nofollow(&target)?;
-        if meta.is_dir() {
-            if matches!(target_meta, Some(ref m) if m.file_type().is_symlink()) {
-                return Err(ClipboardError::new(
-                    ClipboardErrorCode::SymlinkUnsupported,
-                    "Refusing to overwrite symlinks",
-                ));
+        // Finished before the paste was interrupted and resumed.
+        if journal.is_done(&path) {
+            continue;
+        }
+        let mut target = dest.join(entry.file_name());
+        if let Some(target_meta) = metadata_if_exists_nofollow(&target)? {
+            let policy = policies.for_path(&path);
+            if meta.is_dir() && target_meta.is_dir() && policy.merges_folders() {
+                skipped.extend(merge_dir(
+                    &path,
+                    &target,
+                    mode,
+                    policies,
+                    actions,
+                    preserve,
+                    verify,
+                    journal,
+                    app,
+                    progress_event,
+                    cancel,
+                )?);
+                continue;
             }
-            if matches!(target_meta, Some(ref m) if m.is_dir()) {
-                merge_dir(&path, &target, mode, actions, app, progress_event, cancel)?;
-            } else {
-                if target_meta.is_some() {
-                    backup_existing_target(&target, actions)?;
+            if policy == ConflictPolicy::Rename {
+                target = free_rename_target(&target)?;
+            } else if policy.replaces(
+                &EntryFacts::from_metadata(&meta),
+                &EntryFacts::from_metadata(&target_meta),
+            ) {
+                if target_meta.file_type().is_symlink() {
+                    return Err(ClipboardError::new(
+                        ClipboardErrorCode::SymlinkUnsupported,
+                        "Refusing to overwrite symlinks",
+                    ));
                 }
-                match mode {
-                    ClipboardMode::Copy => {
-                        copy_dir(&path, &target, app, progress_event, cancel)?;
-                        actions.push(Action::Copy {
-                            from: path.clone(),
-                            to: target.clone(),
-                        });
-                    }
-                    ClipboardMode::Cut => {
-                        move_entry(&path, &target, app, progress_event, cancel)?;
-                        actions.push(Action::Move {
-                            from: path.clone(),
-                            to: target.clone(),
-                        });
-                    }
-                }
-            }
-        } else {
-            if matches!(target_meta, Some(ref m) if m.file_type().is_symlink()) {
-                return Err(ClipboardError::new(
-                    ClipboardErrorCode::SymlinkUnsupported,
-                    "Refusing to overwrite symlinks",
-                ));
-            }
-            if target_meta.is_some() {
                 backup_existing_target(&target, actions)?;
+                journal.record_actions(actions);
+            } else {
+                skipped.push(path);
+                continue;
             }
-            match mode {
-                ClipboardMode::Copy => {
+        }
+        journal.begin_entry(&path, &target);
+        match mode {
+            ClipboardMode::Copy => {
+                if meta.is_dir() {
+                    copy_dir(
+                        &path,
+                        &target,
+                        preserve,
+                        verify,
+                        journal,
+                        app,
+                        progress_event,
+                        cancel,
+                    )?;
+                } else {
                     let hint = Some(meta.len());
-                    copy_file_best_effort(&path, &target, app, progress_event, cancel, hint)?;
-                    actions.push(Action::Copy {
+                    copy_file_best_effort(
+                        &path,
+                        &target,
+                        preserve,
+                        verify,
+                        journal,
+                        app,
+                        progress_event,
+                        cancel,
+                        hint,
+                    )?;
+                }
+                actions.push(Action::Copy {
+                    from: path.clone(),
+                    to: target.clone(),
+                });
+            }
+            ClipboardMode::Cut => {
+                if move_entry(
+                    &path,
+                    &target,
+                    preserve,
+                    verify,
+                    journal,
+                    app,
+                    progress_event,
+                    cancel,
+                )? {
+                    actions.push(Action::Move {
                         from: path.clone(),
                         to: target.clone(),
                     });
-                }
-                ClipboardMode::Cut => {
-                    move_entry(&path, &target, app, progress_event, cancel)?;
-                    actions.push(Action::Move {
+                } else {
+                    kept_unverified = true;
+                    actions.push(Action::Copy {
                         from: path.clone(),
                         to: target.clone(),
                     });
                 }
             }
         }
+        journal.end_entry(actions);
     }
 
-    if let ClipboardMode::Cut = mode {
+    // Items kept back still live in the source folder, so it stays where it is.
+    if mode == ClipboardMode::Cut && skipped.is_empty() && !kept_unverified {
         // Remove source directory but keep an empty backup so undo can recreate it
         // before moving items back.
         let backup = temp_backup_path(src);
@@ -277,13 +369,32 @@ pub(super) fn merge_dir(
             path: src.to_path_buf(),
             backup,
         });
+        journal.record_actions(actions);
     }
-    Ok(())
+    Ok(skipped)
 }
 
+/// The first `name-N` sibling of `target` that does not exist yet.
+fn free_rename_target(target: &Path) -> ClipboardResult<PathBuf> {
+    for attempt in 1..=MAX_RENAME_ATTEMPTS {
+        let candidate = rename_candidate(target, attempt);
+        if metadata_if_exists_nofollow(&candidate)?.is_none() {
+            return Ok(candidate);
+        }
+    }
+    Err(ClipboardError::new(
+        ClipboardErrorCode::DestinationExists,
+        format!("No free name for {}", target.display()),
+    ))
+}
+
+#[allow(clippy::too_many_arguments)]
 pub(super) fn copy_entry(
     src: &Path,
     dest: &Path,
+    preserve: &MetadataPreserver,
+    verify: &FileVerifier,
+    journal: &TransferJournal,
     app: Option<&tauri::AppHandle>,
     progress_event: Option<&str>,
     cancel: Option<&AtomicBool>,
@@ -302,20 +413,154 @@ pub(super) fn copy_entry(
     }
     if meta.is_dir() {
         ensure_not_child(src, dest)?;
-        copy_dir(src, dest, app, progress_event, cancel)
+        copy_dir(
+            src,
+            dest,
+            preserve,
+            verify,
+            journal,
+            app,
+            progress_event,
+            cancel,
+        )
     } else {
+        pause_checkpoint(app, progress_event);
+
         if transfer_cancelled(cancel, app) {
             return Err(ClipboardError::cancelled());
         }
         let size_hint = Some(meta.len());
-        copy_file_best_effort(src, dest, app, progress_event, cancel, size_hint)?;
+        copy_file_best_effort(
+            src,
+            dest,
+            preserve,
+            verify,
+            journal,
+            app,
+            progress_event,
+            cancel,
+            size_hint,
+        )?;
         Ok(())
     }
 }
 
+/// Copies one file and, when `verify` asks for it, checks the copy against the source,
+/// copying it once more before reporting a mismatch if the mode retries.
+#[allow(clippy::too_many_arguments)]
 pub(super) fn copy_file_best_effort(
     src: &Path,
     dest: &Path,
+    preserve: &MetadataPreserver,
+    verify: &FileVerifier,
+    journal: &TransferJournal,
+    app: Option<&tauri::AppHandle>,
+    progress_event: Option<&str>,
+    cancel: Option<&AtomicBool>,
+    total_hint: Option<u64>,
+) -> ClipboardResult<u64> {
+    copy_file_from(
+        src,
+        dest,
+        0,
+        preserve,
+        verify,
+        journal,
+        app,
+        progress_event,
+        cancel,
+        total_hint,
+    )
+}
+
+/// Like [`copy_file_best_effort`], but a non-zero `offset` continues a copy that already holds
+/// that many bytes of `src`. A retry after a failed check always starts over.
+#[allow(clippy::too_many_arguments)]
+fn copy_file_from(
+    src: &Path,
+    dest: &Path,
+    offset: u64,
+    preserve: &MetadataPreserver,
+    verify: &FileVerifier,
+    journal: &TransferJournal,
+    app: Option<&tauri::AppHandle>,
+    progress_event: Option<&str>,
+    cancel: Option<&AtomicBool>,
+    total_hint: Option<u64>,
+) -> ClipboardResult<u64> {
+    let done = copy_file_once(
+        src,
+        dest,
+        offset,
+        preserve,
+        journal,
+        app,
+        progress_event,
+        cancel,
+        total_hint,
+    )?;
+    let Some(mode) = verify.mode() else {
+        return Ok(done);
+    };
+    let Some(reason) = verify_copy(src, dest, app, cancel)? else {
+        verify.record_match();
+        return Ok(done);
+    };
+    let src_label = src.to_string_lossy();
+    let dest_label = dest.to_string_lossy();
+    if !mode.retries() {
+        verify.record_mismatch(&src_label, &dest_label, reason, false);
+        return Ok(done);
+    }
+    fs::remove_file(dest).map_err(|e| {
+        ClipboardError::new(
+            ClipboardErrorCode::IoError,
+            format!("Failed to remove mismatched copy: {e}"),
+        )
+    })?;
+    let done = copy_file_once(
+        src,
+        dest,
+        0,
+        preserve,
+        journal,
+        app,
+        progress_event,
+        cancel,
+        total_hint,
+    )?;
+    match verify_copy(src, dest, app, cancel)? {
+        None => verify.record_match(),
+        Some(reason) => verify.record_mismatch(&src_label, &dest_label, reason, true),
+    }
+    Ok(done)
+}
+
+fn verify_copy(
+    src: &Path,
+    dest: &Path,
+    app: Option<&tauri::AppHandle>,
+    cancel: Option<&AtomicBool>,
+) -> ClipboardResult<Option<String>> {
+    compare_files(src, dest, &|| transfer_cancelled(cancel, app)).map_err(|e| {
+        if e.kind() == ErrorKind::Interrupted {
+            ClipboardError::cancelled()
+        } else {
+            ClipboardError::new(
+                ClipboardErrorCode::IoError,
+                format!("Verification failed: {e}"),
+            )
+        }
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn copy_file_once(
+    src: &Path,
+    dest: &Path,
+    offset: u64,
+    preserve: &MetadataPreserver,
+    journal: &TransferJournal,
     app: Option<&tauri::AppHandle>,
     progress_event: Option<&str>,
     cancel: Option<&AtomicBool>,
@@ -323,91 +568,150 @@ pub(super) fn copy_file_best_effort(
 ) -> ClipboardResult<u64> {
     #[cfg(not(target_os = "windows"))]
     {
-        if is_gvfs_path(src) || is_gvfs_path(dest) {
+        if offset == 0 && (is_gvfs_path(src) || is_gvfs_path(dest)) {
             if let Some(app) = app {
                 if let Some(bytes) =
                     try_gio_copy_progress(src, dest, app, progress_event, cancel, total_hint)?
                 {
+                    preserve.copy_from(src, dest);
                     return Ok(bytes);
                 }
             }
         }
     }
 
-    // Fallback: manual chunked copy with progress
     let mut reader = fs::File::open(src).map_err(|e| {
         ClipboardError::new(
             ClipboardErrorCode::IoError,
             format!("Failed to open source for copy: {e}"),
         )
     })?;
-    let mut writer = fs::OpenOptions::new()
-        .write(true)
-        .create_new(true)
-        .open(dest)
-        .map_err(|e| {
-            if e.kind() == ErrorKind::AlreadyExists {
-                ClipboardError::new(
-                    ClipboardErrorCode::DestinationExists,
-                    format!("Destination already exists: {}", dest.display()),
-                )
-            } else {
-                ClipboardError::new(
-                    ClipboardErrorCode::IoError,
-                    format!("Failed to open target for copy: {e}"),
-                )
-            }
-        })?;
+    let mut writer = if offset == 0 {
+        fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(dest)
+            .map_err(|e| {
+                if e.kind() == ErrorKind::AlreadyExists {
+                    ClipboardError::new(
+                        ClipboardErrorCode::DestinationExists,
+                        format!("Destination already exists: {}", dest.display()),
+                    )
+                } else {
+                    ClipboardError::new(
+                        ClipboardErrorCode::IoError,
+                        format!("Failed to open target for copy: {e}"),
+                    )
+                }
+            })?
+    } else {
+        reopen_partial_copy(&mut reader, dest, offset)?
+    };
+    // Only a journaled copy needs the source's identity, to tell later whether it can resume.
+    let src_meta = if journal.is_active() {
+        reader.metadata().ok()
+    } else {
+        None
+    };
 
-    let mut buf = vec![0u8; 512 * 1024];
-    let mut done: u64 = 0;
     let total =
         total_hint.or_else(|| progress_event.and_then(|_| fs::metadata(src).ok().map(|m| m.len())));
     let mut last_emit = 0u64;
     let mut last_time = std::time::Instant::now();
-    loop {
-        if transfer_cancelled(cancel, app) {
-            let _ = fs::remove_file(dest);
+    let mut report_progress = |done: u64| {
+        if progress_event.is_none() {
+            return;
+        }
+        if done.saturating_sub(last_emit) >= 64 * 1024
+            || last_time.elapsed() >= std::time::Duration::from_millis(200)
+        {
             emit_copy_progress(
                 app,
                 progress_event,
                 CopyProgressPayload {
                     bytes: done,
-                    total: total.unwrap_or(done),
-                    finished: true,
+                    total: total.unwrap_or(0),
+                    finished: false,
                 },
             );
-            return Err(ClipboardError::cancelled());
+            last_emit = done;
+            last_time = std::time::Instant::now();
         }
-        let n = reader.read(&mut buf).map_err(|e| {
-            ClipboardError::new(ClipboardErrorCode::IoError, format!("Read failed: {e}"))
-        })?;
-        if n == 0 {
-            break;
+    };
+
+    let mut limiter = RateLimiter::new(progress_event);
+    // Reflinks and in-kernel copies first; they also keep sparse files sparse. A continued
+    // copy appends from the offset, which a reflink of the whole file cannot do. The limit
+    // paces the data actually moved, so skipped holes and shared extents cost nothing.
+    let fast = if offset == 0 {
+        copy_file_fast(&reader, &writer, |done, moved| {
+            report_progress(done);
+            if let Some(meta) = src_meta.as_ref() {
+                journal.file_progress(src, meta, dest, &writer, done);
+            }
+            limiter.throttle(moved, || transfer_cancelled(cancel, app));
+            pause_checkpoint(app, progress_event);
+            !transfer_cancelled(cancel, app)
+        })
+        .map_err(|e| {
+            ClipboardError::new(ClipboardErrorCode::IoError, format!("Copy failed: {e}"))
+        })?
+    } else {
+        FastCopy::Unsupported
+    };
+    let done = match fast {
+        FastCopy::Done(bytes) => bytes,
+        FastCopy::Cancelled(bytes) => {
+            let kept = keep_for_resume(journal, app, src, src_meta.as_ref(), dest, &writer, bytes);
+            return Err(abort_cancelled_copy(
+                dest,
+                kept,
+                app,
+                progress_event,
+                bytes,
+                total,
+            ));
         }
-        writer.write_all(&buf[..n]).map_err(|e| {
-            ClipboardError::new(ClipboardErrorCode::IoError, format!("Write failed: {e}"))
-        })?;
-        done = done.saturating_add(n as u64);
-        if progress_event.is_some() {
-            let elapsed = last_time.elapsed();
-            if done.saturating_sub(last_emit) >= 64 * 1024
-                || elapsed >= std::time::Duration::from_millis(200)
-            {
-                emit_copy_progress(
-                    app,
-                    progress_event,
-                    CopyProgressPayload {
-                        bytes: done,
-                        total: total.unwrap_or(0),
-                        finished: false,
-                    },
-                );
-                last_emit = done;
-                last_time = std::time::Instant::now();
+        // Fallback: manual chunked copy with progress
+        FastCopy::Unsupported => {
+            let mut buf = vec![0u8; 512 * 1024];
+            let mut done: u64 = offset;
+            loop {
+                pause_checkpoint(app, progress_event);
+                if transfer_cancelled(cancel, app) {
+                    let kept =
+                        keep_for_resume(journal, app, src, src_meta.as_ref(), dest, &writer, done);
+                    return Err(abort_cancelled_copy(
+                        dest,
+                        kept,
+                        app,
+                        progress_event,
+                        done,
+                        total,
+                    ));
+                }
+                let n = reader.read(&mut buf).map_err(|e| {
+                    ClipboardError::new(ClipboardErrorCode::IoError, format!("Read failed: {e}"))
+                })?;
+                if n == 0 {
+                    break;
+                }
+                writer.write_all(&buf[..n]).map_err(|e| {
+                    ClipboardError::new(ClipboardErrorCode::IoError, format!("Write failed: {e}"))
+                })?;
+                done = done.saturating_add(n as u64);
+                report_progress(done);
+                if let Some(meta) = src_meta.as_ref() {
+                    journal.file_progress(src, meta, dest, &writer, done);
+                }
+                limiter.throttle(done - offset, || transfer_cancelled(cancel, app));
             }
+            done
         }
-    }
+    };
+    // Close the target first so no later write moves the copied times.
+    drop(writer);
+    preserve.copy_from(src, dest);
     emit_copy_progress(
         app,
         progress_event,
@@ -420,6 +724,69 @@ pub(super) fn copy_file_best_effort(
     Ok(done)
 }
 
+/// Opens a copy that already holds the first `offset` bytes of the source, dropping anything
+/// written after them, and moves both files to that position.
+fn reopen_partial_copy(
+    reader: &mut fs::File,
+    dest: &Path,
+    offset: u64,
+) -> ClipboardResult<fs::File> {
+    let reopen = || -> std::io::Result<fs::File> {
+        let mut writer = fs::OpenOptions::new().write(true).open(dest)?;
+        writer.set_len(offset)?;
+        writer.seek(SeekFrom::Start(offset))?;
+        reader.seek(SeekFrom::Start(offset))?;
+        Ok(writer)
+    };
+    reopen().map_err(|e| {
+        ClipboardError::new(
+            ClipboardErrorCode::IoError,
+            format!("Failed to continue partial copy {}: {e}", dest.display()),
+        )
+    })
+}
+
+/// A copy cut short by the app shutting down keeps what it wrote once the journal has saved
+/// the offset, so the job can continue it on the next start.
+fn keep_for_resume(
+    journal: &TransferJournal,
+    app: Option<&tauri::AppHandle>,
+    src: &Path,
+    src_meta: Option<&fs::Metadata>,
+    dest: &Path,
+    writer: &fs::File,
+    written: u64,
+) -> bool {
+    let (Some(meta), Some(app)) = (src_meta, app) else {
+        return false;
+    };
+    runtime_lifecycle::is_shutting_down(app)
+        && journal.file_interrupted(src, meta, dest, writer, written)
+}
+
+fn abort_cancelled_copy(
+    dest: &Path,
+    keep_partial: bool,
+    app: Option<&tauri::AppHandle>,
+    progress_event: Option<&str>,
+    done: u64,
+    total: Option<u64>,
+) -> ClipboardError {
+    if !keep_partial {
+        let _ = fs::remove_file(dest);
+    }
+    emit_copy_progress(
+        app,
+        progress_event,
+        CopyProgressPayload {
+            bytes: done,
+            total: total.unwrap_or(done),
+            finished: true,
+        },
+    );
+    ClipboardError::cancelled()
+}
+
 #[cfg(not(target_os = "windows"))]
 fn try_gio_copy_progress(
     src: &Path,
@@ -442,10 +809,12 @@ fn try_gio_copy_progress(
     let stdout = child.stdout.take();
     let mut total_seen: Option<u64> = total_hint;
     let mut last_bytes: u64 = 0;
+    let mut limiter = RateLimiter::new(progress_event);
 
     if let Some(out) = stdout {
         let reader = std::io::BufReader::new(out);
         for line in reader.lines().map_while(Result::ok) {
+            pause_checkpoint(Some(app), progress_event);
             if transfer_cancelled(cancel, Some(app)) {
                 let _ = child.kill();
                 let _ = child.wait();
@@ -472,6 +841,9 @@ fn try_gio_copy_progress(
                         },
                     );
                 }
+                pace_gio_copy(&child, &mut limiter, last_bytes, || {
+                    transfer_cancelled(cancel, Some(app))
+                });
             }
         }
     }
@@ -500,12 +872,34 @@ fn try_gio_copy_progress(
     Ok(None)
 }
 
+/// `gio copy` has no rate option, so while it is ahead of the bandwidth limit it is stopped
+/// with `SIGSTOP` and continued once the limit allows it.
+#[cfg(not(target_os = "windows"))]
+fn pace_gio_copy(
+    child: &std::process::Child,
+    limiter: &mut RateLimiter<'_>,
+    done: u64,
+    cancelled: impl Fn() -> bool,
+) {
+    if limiter.pending(done).is_zero() {
+        return;
+    }
+    let pid = child.id() as libc::pid_t;
+    unsafe {
+        libc::kill(pid, libc::SIGSTOP);
+    }
+    limiter.throttle(done, cancelled);
+    unsafe {
+        libc::kill(pid, libc::SIGCONT);
+    }
+}
+
 #[cfg(not(target_os = "windows"))]
 fn is_gvfs_path(path: &Path) -> bool {
     path.to_string_lossy().to_lowercase().contains("/gvfs/")
 }
 
-fn delete_entry_path(path: &Path) -> ClipboardResult<()> {
+pub(super) fn delete_entry_path(path: &Path) -> ClipboardResult<()> {
     let meta = fs::symlink_metadata(path).map_err(|e| {
         ClipboardError::new(
             ClipboardErrorCode::IoError,
@@ -529,13 +923,19 @@ fn delete_entry_path(path: &Path) -> ClipboardResult<()> {
     }
 }
 
+/// Moves `src` to `dest`, falling back to copy and delete across filesystems. Returns whether
+/// the source is gone; a copy that failed verification leaves the source in place.
+#[allow(clippy::too_many_arguments)]
 pub(super) fn move_entry(
     src: &Path,
     dest: &Path,
+    preserve: &MetadataPreserver,
+    verify: &FileVerifier,
+    journal: &TransferJournal,
     app: Option<&tauri::AppHandle>,
     progress_event: Option<&str>,
     cancel: Option<&AtomicBool>,
-) -> ClipboardResult<()> {
+) -> ClipboardResult<bool> {
     ensure_not_child(src, dest)?;
     if metadata_if_exists_nofollow(dest)?.is_some() {
         return Err(ClipboardError::new(
@@ -544,12 +944,175 @@ pub(super) fn move_entry(
         ));
     }
     match fs::rename(src, dest) {
-        Ok(_) => Ok(()),
+        Ok(_) => Ok(true),
         Err(_) => {
-            copy_entry(src, dest, app, progress_event, cancel)?;
-            delete_entry_path(src)
+            let mismatches = verify.mismatch_count();
+            copy_entry(
+                src,
+                dest,
+                preserve,
+                verify,
+                journal,
+                app,
+                progress_event,
+                cancel,
+            )?;
+            if verify.mismatch_count() > mismatches {
+                return Ok(false);
+            }
+            journal.entry_copied();
+            delete_entry_path(src)?;
+            Ok(true)
+        }
+    }
+}
+
+/// Finishes `entry`, which a paste was writing when the app stopped. Everything below its
+/// target was written by that paste, so files that already have their source's size are kept,
+/// `partial` is continued from its offset while that is safe, and the rest is copied again.
+/// A cut then removes the source; returns whether the source is gone.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn resume_entry(
+    entry: &JournalEntry,
+    partial: Option<&PartialFile>,
+    mode: ClipboardMode,
+    preserve: &MetadataPreserver,
+    verify: &FileVerifier,
+    journal: &TransferJournal,
+    app: Option<&tauri::AppHandle>,
+    progress_event: Option<&str>,
+    cancel: Option<&AtomicBool>,
+) -> ClipboardResult<bool> {
+    let src = Path::new(&entry.src);
+    let dest = Path::new(&entry.target);
+    let src_exists = metadata_if_exists_nofollow(src)?.is_some();
+    if mode == ClipboardMode::Cut && !src_exists && entry.copied {
+        return Ok(true);
+    }
+    if mode == ClipboardMode::Cut && metadata_if_exists_nofollow(dest)?.is_none() {
+        return move_entry(
+            src,
+            dest,
+            preserve,
+            verify,
+            journal,
+            app,
+            progress_event,
+            cancel,
+        );
+    }
+    let mismatches = verify.mismatch_count();
+    if !entry.copied {
+        resume_tree(
+            src,
+            dest,
+            partial,
+            preserve,
+            verify,
+            journal,
+            app,
+            progress_event,
+            cancel,
+        )?;
+    }
+    if mode == ClipboardMode::Copy || verify.mismatch_count() > mismatches {
+        return Ok(false);
+    }
+    journal.entry_copied();
+    delete_entry_path(src)?;
+    Ok(true)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn resume_tree(
+    src: &Path,
+    dest: &Path,
+    partial: Option<&PartialFile>,
+    preserve: &MetadataPreserver,
+    verify: &FileVerifier,
+    journal: &TransferJournal,
+    app: Option<&tauri::AppHandle>,
+    progress_event: Option<&str>,
+    cancel: Option<&AtomicBool>,
+) -> ClipboardResult<()> {
+    pause_checkpoint(app, progress_event);
+    if transfer_cancelled(cancel, app) {
+        return Err(ClipboardError::cancelled());
+    }
+    let meta = fs::symlink_metadata(src).map_err(|e| {
+        ClipboardError::new(
+            ClipboardErrorCode::IoError,
+            format!("Failed to read metadata: {e}"),
+        )
+    })?;
+    if meta.file_type().is_symlink() {
+        return Err(ClipboardError::new(
+            ClipboardErrorCode::SymlinkUnsupported,
+            "Refusing to copy symlinks",
+        ));
+    }
+    if meta.is_dir() {
+        match fs::create_dir(dest) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+            Err(e) => {
+                return Err(ClipboardError::new(
+                    ClipboardErrorCode::IoError,
+                    format!("Failed to create dir {:?}: {e}", dest),
+                ))
+            }
         }
+        for entry in fs::read_dir(src).map_err(|e| {
+            ClipboardError::new(
+                ClipboardErrorCode::IoError,
+                format!("Failed to read dir {:?}: {e}", src),
+            )
+        })? {
+            let entry = entry.map_err(|e| {
+                ClipboardError::new(
+                    ClipboardErrorCode::IoError,
+                    format!("Failed to read dir entry: {e}"),
+                )
+            })?;
+            resume_tree(
+                &entry.path(),
+                &dest.join(entry.file_name()),
+                partial,
+                preserve,
+                verify,
+                journal,
+                app,
+                progress_event,
+                cancel,
+            )?;
+        }
+        preserve.copy_from(src, dest);
+        return Ok(());
     }
+
+    let partial = partial.filter(|p| p.dest == dest);
+    let mut offset = 0;
+    if let Some(existing) = metadata_if_exists_nofollow(dest)? {
+        match partial {
+            Some(p) if p.src == src && p.resumable() => offset = p.offset,
+            // Finished before the interruption; only the partial file can be incomplete.
+            None if existing.is_file() && existing.len() == meta.len() => return Ok(()),
+            _ => delete_entry_path(dest)?,
+        }
+    }
+    copy_file_from(
+        src,
+        dest,
+        offset,
+        preserve,
+        verify,
+        journal,
+        app,
+        progress_event,
+        cancel,
+        Some(meta.len()),
+    )?;
+    Ok(())
 }
 
 pub(super) fn metadata_if_exists_nofollow(path: &Path) -> ClipboardResult<Option<fs::Metadata>> {
@@ -0,0 +1,209 @@
+//! Resume and rollback of pastes that a previous run left in its transfer journal.
+
+use super::ops::{delete_entry_path, metadata_if_exists_nofollow, resume_entry};
+use super::*;
+use crate::transfer_journal::{
+    JournalEntry, PartialFile, ResumeOutcome, RollbackOutcome, TransferJob,
+};
+use crate::undo::move_with_fallback;
+
+pub(crate) async fn resume_paste_job(
+    app: tauri::AppHandle,
+    job: TransferJob,
+    undo_inner: std::sync::Arc<std::sync::Mutex<crate::undo::UndoManager>>,
+    cancel_state: CancelState,
+    progress_event: Option<String>,
+) -> ApiResult<ResumeOutcome> {
+    let (sources, dest) = job.plan.paths();
+    let spec = JobSpec::new(JobKind::Paste, dest, sources, progress_event.clone());
+    let app_handle = app.clone();
+    let work = async move {
+        tauri::async_runtime::spawn_blocking(move || {
+            resume_paste_impl(app_handle, job, undo_inner, cancel_state, progress_event)
+        })
+        .await
+        .unwrap_or_else(|e| {
+            Err(ClipboardError::new(
+                ClipboardErrorCode::TaskFailed,
+                format!("Resume task failed: {e}"),
+            ))
+        })
+    };
+    map_api_result(run_job(&app, spec, ClipboardError::cancelled, work).await)
+}
+
+pub(crate) fn rollback_paste_job(job: &TransferJob) -> ApiResult<RollbackOutcome> {
+    map_api_result(rollback_paste_impl(job))
+}
+
+fn clipboard_mode(op: TransferOp) -> ClipboardMode {
+    match op {
+        TransferOp::Copy => ClipboardMode::Copy,
+        TransferOp::Move => ClipboardMode::Cut,
+    }
+}
+
+fn resume_paste_impl(
+    app: tauri::AppHandle,
+    job: TransferJob,
+    undo_inner: std::sync::Arc<std::sync::Mutex<crate::undo::UndoManager>>,
+    cancel_state: CancelState,
+    progress_event: Option<String>,
+) -> ClipboardResult<ResumeOutcome> {
+    if runtime_lifecycle::is_shutting_down(&app) {
+        return Err(ClipboardError::cancelled());
+    }
+    let TransferPlan::Paste {
+        sources,
+        dest,
+        policies,
+        preserve,
+        verify,
+    } = job.plan.clone()
+    else {
+        return Err(ClipboardError::invalid_input(
+            "Interrupted transfer is not a paste",
+        ));
+    };
+    let mode = clipboard_mode(job.op);
+    let preserve = MetadataPreserver::new(preserve);
+    let verify = FileVerifier::new(verify);
+
+    let cancel_guard = progress_event
+        .as_ref()
+        .map(|id| cancel_state.register(id.clone()))
+        .transpose()
+        .map_err(ClipboardError::from)?;
+    let cancel_flag = cancel_guard.as_ref().map(|g| g.token());
+
+    let mut tally = PasteTally {
+        created: job.created.clone(),
+        skipped: job.skipped.clone(),
+        performed: job.performed.clone(),
+        done_items: job.next as u64,
+    };
+    let next = job.next.min(sources.len());
+    let entry = job.entry.clone();
+    let partial = job.partial.clone();
+    let journal = TransferJournal::resume(job);
+    let run = PasteRun {
+        app: &app,
+        dest: &dest,
+        mode,
+        policies: &policies,
+        preserve: &preserve,
+        verify: &verify,
+        journal: &journal,
+        progress_event: progress_event.as_deref(),
+        cancel: cancel_flag.as_deref(),
+        total_bytes: None,
+        total_items: sources.len() as u64,
+    };
+    let resumed = resume_paste_entries(
+        &run,
+        &sources,
+        next,
+        entry.as_ref(),
+        partial.as_ref(),
+        &mut tally,
+    );
+    let interrupted = resumed.is_err() && run.interrupted();
+    close_journal(journal, interrupted, &tally);
+    resumed?;
+
+    if let Some(evt) = progress_event.as_ref() {
+        let _ = runtime_lifecycle::emit_if_running(
+            &app,
+            evt,
+            CopyProgressPayload {
+                bytes: tally.done_items,
+                total: sources.len() as u64,
+                finished: true,
+            },
+        );
+    }
+    record_paste(&undo_inner, tally.performed);
+    Ok(ResumeOutcome {
+        created: tally.created,
+        skipped: tally.skipped,
+        verify: verify.into_report(),
+    })
+}
+
+/// Finishes the item that was being written, then pastes the sources from `next` on. An item
+/// inside a merged folder is skipped when that merge runs again.
+fn resume_paste_entries(
+    run: &PasteRun<'_>,
+    sources: &[PathBuf],
+    mut next: usize,
+    entry: Option<&JournalEntry>,
+    partial: Option<&PartialFile>,
+    tally: &mut PasteTally,
+) -> ClipboardResult<()> {
+    if let Some(entry) = entry {
+        run.journal
+            .begin_item(&entry.src, &entry.target, entry.fresh);
+        let moved = resume_entry(
+            entry,
+            partial,
+            run.mode,
+            run.preserve,
+            run.verify,
+            run.journal,
+            Some(run.app),
+            run.progress_event,
+            run.cancel,
+        )
+        .map_err(|err| run.failed(tally, &entry.src, err))?;
+        let src = PathBuf::from(&entry.src);
+        let target = PathBuf::from(&entry.target);
+        tally.performed.push(pasted_action(&src, &target, moved));
+        if sources.get(next) == Some(&src) {
+            tally.created.push(entry.target.clone());
+            run.item_done(tally);
+            next += 1;
+        } else {
+            run.journal.end_entry(&tally.performed);
+        }
+    }
+    // A cut may have finished moving the item before the journal recorded it.
+    if run.mode == ClipboardMode::Cut
+        && sources
+            .get(next)
+            .is_some_and(|src| matches!(metadata_if_exists_nofollow(src), Ok(None)))
+    {
+        run.item_done(tally);
+        next += 1;
+    }
+    paste_entries(run, &sources[next..], tally)
+}
+
+/// Undoes an interrupted paste: the item being written is removed, or moved back when a move
+/// had already taken it, and the finished items are undone like a failed paste.
+fn rollback_paste_impl(job: &TransferJob) -> ClipboardResult<RollbackOutcome> {
+    let mut outcome = RollbackOutcome::default();
+    if let Some(entry) = job.entry.as_ref() {
+        let src = Path::new(&entry.src);
+        let target = Path::new(&entry.target);
+        let src_exists = metadata_if_exists_nofollow(src)?.is_some();
+        let target_exists = metadata_if_exists_nofollow(target)?.is_some();
+        if target_exists {
+            if job.op == TransferOp::Move && !src_exists {
+                move_with_fallback(target, src).map_err(ClipboardError::from)?;
+            } else if entry.copied {
+                // Part of the source is already gone; the full copy is all that is left.
+                outcome.kept.push(entry.target.clone());
+            } else {
+                delete_entry_path(target)?;
+            }
+        }
+    }
+    let mut performed = job.performed.clone();
+    run_actions(&mut performed, Direction::Backward).map_err(|e| {
+        ClipboardError::new(
+            ClipboardErrorCode::RollbackFailed,
+            format!("Rollback of interrupted paste failed: {e}"),
+        )
+    })?;
+    Ok(outcome)
+}
@@ -1,4 +1,5 @@
 use super::*;
+use crate::transfer_journal::{JournalEntry, PartialFile};
 use std::env;
 use std::fs;
 use std::io::Write;
@@ -37,6 +38,10 @@ fn write_file(path: &Path, content: &[u8]) {
     f.write_all(content).unwrap();
 }
 
+fn overwrite_policies() -> ConflictPolicies {
+    ConflictPolicies::new(ConflictPolicy::Overwrite, HashMap::new())
+}
+
 #[test]
 fn merge_copy_can_undo_without_touching_existing() {
     let _ = ensure_undo_dir();
@@ -54,7 +59,11 @@ fn merge_copy_can_undo_without_touching_existing() {
         &src,
         &dest,
         ClipboardMode::Copy,
+        &overwrite_policies(),
         &mut actions,
+        &MetadataPreserver::disabled(),
+        &FileVerifier::disabled(),
+        &TransferJournal::disabled(),
         None,
         None,
         None,
@@ -91,7 +100,11 @@ fn merge_cut_undo_restores_source_and_target() {
         &src,
         &dest,
         ClipboardMode::Cut,
+        &overwrite_policies(),
         &mut actions,
+        &MetadataPreserver::disabled(),
+        &FileVerifier::disabled(),
+        &TransferJournal::disabled(),
         None,
         None,
         None,
@@ -111,6 +124,81 @@ fn merge_cut_undo_restores_source_and_target() {
     let _ = fs::remove_dir_all(&base);
 }
 
+#[test]
+fn merge_applies_skip_and_newer_policies_and_keeps_cut_source_with_leftovers() {
+    let _ = ensure_undo_dir();
+    let base = uniq_path("merge-policies");
+    let src = base.join("src");
+    let dest = base.join("dest");
+    write_file(&src.join("keep.txt"), b"incoming keep");
+    write_file(&src.join("newer.txt"), b"incoming newer");
+    write_file(&src.join("fresh.txt"), b"incoming fresh");
+    write_file(&dest.join("keep.txt"), b"existing keep");
+    write_file(&dest.join("newer.txt"), b"existing newer");
+    let old = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+    fs::File::options()
+        .write(true)
+        .open(dest.join("newer.txt"))
+        .unwrap()
+        .set_modified(old)
+        .unwrap();
+
+    let policies = ConflictPolicies::new(
+        ConflictPolicy::OverwriteIfNewer,
+        HashMap::from([(
+            src.join("keep.txt").to_string_lossy().to_string(),
+            ConflictPolicy::Skip,
+        )]),
+    );
+    let mut actions = Vec::new();
+    let skipped = merge_dir(
+        &src,
+        &dest,
+        ClipboardMode::Cut,
+        &policies,
+        &mut actions,
+        &MetadataPreserver::disabled(),
+        &FileVerifier::disabled(),
+        &TransferJournal::disabled(),
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+
+    assert_eq!(skipped, vec![src.join("keep.txt")]);
+    assert_eq!(fs::read(dest.join("keep.txt")).unwrap(), b"existing keep");
+    assert_eq!(fs::read(dest.join("newer.txt")).unwrap(), b"incoming newer");
+    assert_eq!(fs::read(dest.join("fresh.txt")).unwrap(), b"incoming fresh");
+    assert!(src.join("keep.txt").exists());
+    assert!(!src.join("fresh.txt").exists());
+
+    run_actions(&mut actions, Direction::Backward).unwrap();
+    assert_eq!(fs::read(dest.join("newer.txt")).unwrap(), b"existing newer");
+    assert_eq!(fs::read(src.join("newer.txt")).unwrap(), b"incoming newer");
+    assert!(!dest.join("fresh.txt").exists());
+
+    let _ = fs::remove_dir_all(&base);
+}
+
+#[test]
+fn conflict_policies_parse_decisions_and_reject_unknown_values() {
+    let policies = conflict_policies(
+        Some("skip".to_string()),
+        Some(HashMap::from([(
+            "/a/b.txt".to_string(),
+            "overwrite_if_larger".to_string(),
+        )])),
+    )
+    .unwrap();
+    assert_eq!(
+        policies.for_source("/a/b.txt"),
+        ConflictPolicy::OverwriteIfLarger
+    );
+    assert_eq!(policies.for_source("/a/c.txt"), ConflictPolicy::Skip);
+    assert!(conflict_policies(Some("merge".to_string()), None).is_err());
+}
+
 #[test]
 fn copy_file_best_effort_does_not_overwrite_existing_target() {
     let base = uniq_path("copy-no-overwrite");
@@ -120,7 +208,18 @@ fn copy_file_best_effort_does_not_overwrite_existing_target() {
     write_file(&src, b"new-content");
     write_file(&dest, b"old-content");
 
-    let err = copy_file_best_effort(&src, &dest, None, None, None, None).unwrap_err();
+    let err = copy_file_best_effort(
+        &src,
+        &dest,
+        &MetadataPreserver::disabled(),
+        &FileVerifier::disabled(),
+        &TransferJournal::disabled(),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap_err();
     assert!(is_destination_exists_error(&err), "unexpected error: {err}");
     assert_eq!(
         fs::read(&dest).unwrap(),
@@ -140,7 +239,17 @@ fn move_entry_does_not_overwrite_existing_target() {
     write_file(&src, b"new-content");
     write_file(&dest, b"old-content");
 
-    let err = move_entry(&src, &dest, None, None, None).unwrap_err();
+    let err = move_entry(
+        &src,
+        &dest,
+        &MetadataPreserver::disabled(),
+        &FileVerifier::disabled(),
+        &TransferJournal::disabled(),
+        None,
+        None,
+        None,
+    )
+    .unwrap_err();
     assert!(is_destination_exists_error(&err), "unexpected error: {err}");
     assert_eq!(
         fs::read(&dest).unwrap(),
@@ -212,6 +321,86 @@ fn resolve_drop_mode_defaults_to_cut_on_same_filesystem() {
     let _ = fs::remove_dir_all(&base);
 }
 
+#[test]
+fn copy_entry_preserves_requested_times_and_reports_nothing_missing() {
+    let base = uniq_path("preserve-times");
+    let src = base.join("src");
+    fs::create_dir_all(src.join("nested")).unwrap();
+    write_file(&src.join("nested").join("a.txt"), b"a");
+    let old = SystemTime::UNIX_EPOCH + Duration::from_secs(1_500_000_000);
+    for path in [
+        src.join("nested").join("a.txt"),
+        src.join("nested"),
+        src.clone(),
+    ] {
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .or_else(|_| fs::File::open(&path))
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+    }
+    let dest = base.join("dest");
+
+    let preserve = MetadataPreserver::new(PreserveOptions::from_attrs(&[
+        PreserveAttr::Times,
+        PreserveAttr::Mode,
+    ]));
+    copy_entry(
+        &src,
+        &dest,
+        &preserve,
+        &FileVerifier::disabled(),
+        &TransferJournal::disabled(),
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+
+    for rel in ["nested/a.txt", "nested", ""] {
+        let copied = fs::metadata(dest.join(rel)).unwrap();
+        assert_eq!(copied.modified().unwrap(), old, "mtime of {rel:?}");
+    }
+    assert!(preserve.into_report().is_empty());
+
+    let _ = fs::remove_dir_all(&base);
+}
+
+#[test]
+fn verified_copy_counts_every_checked_file() {
+    let base = uniq_path("verify-copy");
+    let src = base.join("src");
+    fs::create_dir_all(src.join("nested")).unwrap();
+    write_file(&src.join("a.txt"), b"first");
+    write_file(&src.join("nested").join("b.txt"), b"second");
+    let dest = base.join("dest");
+
+    let verify = FileVerifier::new(Some(VerifyMode::ChecksumRetry));
+    copy_entry(
+        &src,
+        &dest,
+        &MetadataPreserver::disabled(),
+        &verify,
+        &TransferJournal::disabled(),
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let report = verify.into_report().expect("verification was requested");
+    assert_eq!(report.verified, 2);
+    assert!(report.mismatches.is_empty());
+    assert_eq!(
+        fs::read(dest.join("nested").join("b.txt")).unwrap(),
+        b"second"
+    );
+
+    let _ = fs::remove_dir_all(&base);
+}
+
 #[test]
 fn copy_file_best_effort_fails_when_source_is_missing() {
     let base = uniq_path("copy-missing-source");
@@ -219,7 +408,18 @@ fn copy_file_best_effort_fails_when_source_is_missing() {
     let src = base.join("missing.txt");
     let dest = base.join("dest.txt");
 
-    let err = copy_file_best_effort(&src, &dest, None, None, None, None).unwrap_err();
+    let err = copy_file_best_effort(
+        &src,
+        &dest,
+        &MetadataPreserver::disabled(),
+        &FileVerifier::disabled(),
+        &TransferJournal::disabled(),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap_err();
     assert_eq!(err.code(), ClipboardErrorCode::IoError);
     assert!(
         !dest.exists(),
@@ -236,7 +436,17 @@ fn move_entry_fails_when_source_is_missing() {
     let src = base.join("missing.txt");
     let dest = base.join("dest.txt");
 
-    let err = move_entry(&src, &dest, None, None, None).unwrap_err();
+    let err = move_entry(
+        &src,
+        &dest,
+        &MetadataPreserver::disabled(),
+        &FileVerifier::disabled(),
+        &TransferJournal::disabled(),
+        None,
+        None,
+        None,
+    )
+    .unwrap_err();
     assert_eq!(err.code(), ClipboardErrorCode::IoError);
     assert!(
         !dest.exists(),
@@ -254,7 +464,17 @@ fn move_entry_keeps_source_when_destination_parent_disappears() {
     write_file(&src, b"data");
     let dest = base.join("missing").join("dest.txt");
 
-    let err = move_entry(&src, &dest, None, None, None).unwrap_err();
+    let err = move_entry(
+        &src,
+        &dest,
+        &MetadataPreserver::disabled(),
+        &FileVerifier::disabled(),
+        &TransferJournal::disabled(),
+        None,
+        None,
+        None,
+    )
+    .unwrap_err();
     assert_eq!(err.code(), ClipboardErrorCode::IoError);
     assert!(src.exists(), "source should remain when move fails");
     assert!(!dest.exists(), "destination should not be created");
@@ -275,7 +495,18 @@ fn copy_file_best_effort_fails_when_destination_dir_is_read_only() {
     fs::set_permissions(&dest_dir, Permissions::from_mode(0o555)).unwrap();
     let dest = dest_dir.join("out.txt");
 
-    let err = copy_file_best_effort(&src, &dest, None, None, None, None).unwrap_err();
+    let err = copy_file_best_effort(
+        &src,
+        &dest,
+        &MetadataPreserver::disabled(),
+        &FileVerifier::disabled(),
+        &TransferJournal::disabled(),
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap_err();
     assert_eq!(err.code(), ClipboardErrorCode::IoError);
     assert!(src.exists(), "source should remain");
     assert!(!dest.exists(), "destination should not be created");
@@ -297,7 +528,17 @@ fn move_entry_fails_when_destination_dir_is_read_only_and_keeps_source() {
     fs::set_permissions(&dest_dir, Permissions::from_mode(0o555)).unwrap();
     let dest = dest_dir.join("out.txt");
 
-    let err = move_entry(&src, &dest, None, None, None).unwrap_err();
+    let err = move_entry(
+        &src,
+        &dest,
+        &MetadataPreserver::disabled(),
+        &FileVerifier::disabled(),
+        &TransferJournal::disabled(),
+        None,
+        None,
+        None,
+    )
+    .unwrap_err();
     assert_eq!(err.code(), ClipboardErrorCode::IoError);
     assert!(src.exists(), "source should remain on permission failure");
     assert!(!dest.exists(), "destination should not be created");
@@ -317,10 +558,111 @@ fn copy_entry_rejects_symlink_source_no_follow() {
     symlink(&real_src, &link_src).unwrap();
     let dest = base.join("dest.txt");
 
-    let err = copy_entry(&link_src, &dest, None, None, None).unwrap_err();
+    let err = copy_entry(
+        &link_src,
+        &dest,
+        &MetadataPreserver::disabled(),
+        &FileVerifier::disabled(),
+        &TransferJournal::disabled(),
+        None,
+        None,
+        None,
+    )
+    .unwrap_err();
     assert_eq!(err.code(), ClipboardErrorCode::SymlinkUnsupported);
     assert!(!dest.exists(), "destination should not be created");
     assert!(real_src.exists(), "real source should remain unchanged");
 
     let _ = fs::remove_dir_all(&base);
 }
+
+#[test]
+fn archive_members_paste_renames_on_conflict_and_undoes() {
+    let _ = ensure_undo_dir();
+    let base = uniq_path("archive-paste");
+    let dest = base.join("dest");
+    fs::create_dir_all(&dest).unwrap();
+    write_file(&dest.join("a.txt"), b"existing");
+
+    let archive = base.join("bundle.zip");
+    let mut zip = zip::ZipWriter::new(fs::File::create(&archive).unwrap());
+    zip.start_file("docs/a.txt", zip::write::SimpleFileOptions::default())
+        .unwrap();
+    zip.write_all(b"from archive").unwrap();
+    zip.finish().unwrap();
+
+    let uri = format!("archive://{}!/docs/a.txt", archive.display());
+    let sources = vec![ArchiveSource::parse(&uri).unwrap()];
+    let mut actions = Vec::new();
+    let created = paste_archive_members(
+        &sources,
+        &dest,
+        &ConflictPolicies::default(),
+        &mut actions,
+        &mut Vec::new(),
+        &MetadataPreserver::disabled(),
+        None,
+        None,
+    )
+    .unwrap();
+
+    assert_eq!(created, vec![dest.join("a-1.txt")]);
+    assert_eq!(fs::read(dest.join("a.txt")).unwrap(), b"existing");
+    assert_eq!(fs::read(dest.join("a-1.txt")).unwrap(), b"from archive");
+
+    run_actions(&mut actions, Direction::Backward).unwrap();
+    assert!(!dest.join("a-1.txt").exists());
+    assert!(dest.join("a.txt").exists());
+
+    assert!(set_clipboard_impl(vec![uri], "cut".to_string()).is_err());
+    let _ = fs::remove_dir_all(&base);
+}
+
+#[test]
+fn resume_entry_continues_partial_file_and_keeps_finished_ones() {
+    let base = uniq_path("resume-entry");
+    let src = base.join("src");
+    let dest = base.join("dest");
+    write_file(&src.join("done.txt"), b"finished");
+    write_file(&src.join("partial.bin"), &[1u8; 40]);
+    write_file(&src.join("missing.txt"), b"missing");
+    // Marker bytes show which parts were written before the interruption.
+    write_file(&dest.join("done.txt"), b"FINISHED");
+    write_file(&dest.join("partial.bin"), &[9u8; 25]);
+
+    let src_meta = fs::metadata(src.join("partial.bin")).unwrap();
+    let partial = PartialFile {
+        src: src.join("partial.bin"),
+        dest: dest.join("partial.bin"),
+        offset: 10,
+        src_len: src_meta.len(),
+        src_modified: src_meta.modified().ok(),
+    };
+    let entry = JournalEntry {
+        src: src.to_string_lossy().to_string(),
+        target: dest.to_string_lossy().to_string(),
+        fresh: true,
+        copied: false,
+    };
+    let moved = ops::resume_entry(
+        &entry,
+        Some(&partial),
+        ClipboardMode::Copy,
+        &MetadataPreserver::disabled(),
+        &FileVerifier::disabled(),
+        &TransferJournal::disabled(),
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+
+    assert!(!moved);
+    assert_eq!(fs::read(dest.join("done.txt")).unwrap(), b"FINISHED");
+    assert_eq!(fs::read(dest.join("missing.txt")).unwrap(), b"missing");
+    let mut expected = vec![9u8; 10];
+    expected.extend_from_slice(&[1u8; 30]);
+    assert_eq!(fs::read(dest.join("partial.bin")).unwrap(), expected);
+    assert!(src.join("partial.bin").exists());
+    let _ = fs::remove_dir_all(&base);
+}
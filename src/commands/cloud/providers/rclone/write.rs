@@ -105,7 +105,8 @@ impl RcloneCloudProvider {
             .run_capture_text_with_cancel(
                 RcloneCommandSpec::new(RcloneSubcommand::CopyTo)
                     .arg(local_src.as_os_str())
-                    .arg(dst.to_rclone_remote_spec()),
+                    .arg(dst.to_rclone_remote_spec())
+                    .bwlimit(Some(progress_group)),
                 cancel,
             )
             .map_err(|error| map_rclone_error_for_remote(dst.remote(), error))?;
@@ -232,7 +233,8 @@ impl RcloneCloudProvider {
             .run_capture_text_with_cancel(
                 RcloneCommandSpec::new(RcloneSubcommand::CopyTo)
                     .arg(src.to_rclone_remote_spec())
-                    .arg(local_dest.as_os_str()),
+                    .arg(local_dest.as_os_str())
+                    .bwlimit(None),
                 cancel,
             )
             .map_err(|error| map_rclone_error_for_remote(src.remote(), error))?;
@@ -1,6 +1,7 @@
 use std::{
     collections::HashMap,
     ffi::{OsStr, OsString},
+    path::PathBuf,
     process::{Child, Command, ExitStatus, Output, Stdio},
     sync::{
         atomic::{AtomicBool, AtomicU64, Ordering},
@@ -11,6 +12,9 @@ use std::{
 use tracing::{debug, warn};
 use wait_timeout::ChildExt;
 
+use super::rclone_rc;
+use crate::fs_utils::bandwidth;
+
 mod output;
 use output::{scrub_log_text, truncate_failure_output};
 
@@ -39,6 +43,7 @@ pub enum RcloneSubcommand {
     Rmdir,
     MoveTo,
     CopyTo,
+    HashSum,
 }
 
 impl RcloneSubcommand {
@@ -56,6 +61,7 @@ impl RcloneSubcommand {
             Self::Rmdir => "rmdir",
             Self::MoveTo => "moveto",
             Self::CopyTo => "copyto",
+            Self::HashSum => "hashsum",
         }
     }
 
@@ -69,6 +75,8 @@ impl RcloneSubcommand {
             Self::DeleteFile | Self::Rmdir => Duration::from_secs(120),
             Self::Purge => Duration::from_secs(300),
             Self::MoveTo | Self::CopyTo => Duration::from_secs(300),
+            // Remotes without the hash type are read back in full.
+            Self::HashSum => Duration::from_secs(600),
         }
     }
 }
@@ -78,6 +86,14 @@ impl RcloneSubcommand {
 pub struct RcloneCommandSpec {
     subcommand: RcloneSubcommand,
     args: Vec<OsString>,
+    bwlimit: Option<BwlimitSource>,
+}
+
+/// Where a transfer's bandwidth limit comes from, and the limit it started with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BwlimitSource {
+    event: Option<String>,
+    initial: Option<u64>,
 }
 
 impl RcloneCommandSpec {
@@ -86,6 +102,7 @@ impl RcloneCommandSpec {
         Self {
             subcommand,
             args: Vec::new(),
+            bwlimit: None,
         }
     }
 
@@ -95,6 +112,23 @@ impl RcloneCommandSpec {
         self
     }
 
+    /// Starts a transfer reporting on `event` at its bandwidth limit, passed as `--bwlimit`, and
+    /// keeps it at that limit while it runs: the process serves rc on a socket of its own, and
+    /// a changed limit is pushed to it through `core/bwlimit`.
+    pub fn bwlimit(mut self, event: Option<&str>) -> Self {
+        let initial = bandwidth::limit_for(event);
+        self.bwlimit = Some(BwlimitSource {
+            event: event.map(str::to_string),
+            initial,
+        });
+        match initial {
+            Some(limit) => self
+                .arg("--bwlimit")
+                .arg(bandwidth::rclone_rate(Some(limit))),
+            None => self,
+        }
+    }
+
     #[allow(dead_code)]
     pub fn argv(&self) -> Vec<OsString> {
         let mut argv = Vec::with_capacity(2 + self.args.len());
@@ -316,15 +350,30 @@ impl RcloneCli {
         let timeout = subcommand.default_timeout();
         let started = Instant::now();
         debug!(command = subcommand.as_str(), "running rclone command");
+        let bwlimit = spec.bwlimit.clone();
         let mut command = self.command(spec);
+        let mut live_bwlimit = bwlimit.and_then(LiveBwlimit::new);
+        if let Some(live) = &live_bwlimit {
+            command
+                .arg("--rc")
+                .arg("--rc-no-auth")
+                .arg("--rc-addr")
+                .arg(format!("unix://{}", live.socket_path.display()));
+        }
         command.stdout(Stdio::piped());
         command.stderr(Stdio::piped());
         let child = Arc::new(Mutex::new(Some(
             spawn_with_etxtbsy_retry(&mut command, subcommand).map_err(RcloneCliError::Io)?,
         )));
         let _registration = RunningChildRegistration::register(child.clone());
-        let output =
-            wait_for_child_output_or_cancel(&child, subcommand, timeout, started, cancel_token)?;
+        let output = wait_for_child_output_or_cancel(
+            &child,
+            subcommand,
+            timeout,
+            started,
+            cancel_token,
+            live_bwlimit.as_mut(),
+        )?;
         let elapsed_ms = started.elapsed().as_millis() as u64;
         let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
         let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
@@ -383,6 +432,43 @@ fn spawn_with_etxtbsy_retry(
     }
 }
 
+/// The rc socket of a running transfer and the bandwidth limit it was last given.
+struct LiveBwlimit {
+    event: Option<String>,
+    socket_path: PathBuf,
+    applied: Option<u64>,
+}
+
+impl LiveBwlimit {
+    fn new(source: BwlimitSource) -> Option<Self> {
+        let key = RCLONE_CHILD_KEY_SEQ.fetch_add(1, Ordering::SeqCst);
+        Some(Self {
+            socket_path: rclone_rc::cli_rc_socket_path(key)?,
+            event: source.event,
+            applied: source.initial,
+        })
+    }
+
+    /// Pushes the current limit if it changed. The socket shows up once rclone has started its
+    /// rc server; until then, and after a failed push, the next poll tries again.
+    fn sync(&mut self) {
+        let wanted = bandwidth::limit_for(self.event.as_deref());
+        if wanted == self.applied || !self.socket_path.exists() {
+            return;
+        }
+        match rclone_rc::push_cli_bwlimit(&self.socket_path, wanted) {
+            Ok(()) => self.applied = wanted,
+            Err(error) => debug!(%error, "failed to update rclone transfer bandwidth limit"),
+        }
+    }
+}
+
+impl Drop for LiveBwlimit {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.socket_path);
+    }
+}
+
 fn is_etxtbsy_error(error: &std::io::Error) -> bool {
     #[cfg(unix)]
     {
@@ -422,6 +508,7 @@ fn wait_for_child_output_or_cancel(
     timeout: Duration,
     started: Instant,
     cancel_token: Option<&AtomicBool>,
+    mut bwlimit: Option<&mut LiveBwlimit>,
 ) -> Result<Output, RcloneCliError> {
     let poll = Duration::from_millis(RCLONE_SHUTDOWN_POLL_SLICE_MS);
     loop {
@@ -464,6 +551,9 @@ fn wait_for_child_output_or_cancel(
             });
         }
 
+        if let Some(bwlimit) = bwlimit.as_deref_mut() {
+            bwlimit.sync();
+        }
         let remaining = timeout.saturating_sub(elapsed);
         let slice = remaining.min(poll);
         match child_wait_timeout(child, slice).map_err(RcloneCliError::Io)? {
@@ -538,8 +628,10 @@ fn child_wait_with_output(child: &SharedChild) -> std::io::Result<Output> {
 #[cfg(test)]
 mod tests {
     use super::{
-        scrub_log_text, truncate_failure_output, RcloneCli, RcloneCommandSpec, RcloneSubcommand,
+        scrub_log_text, truncate_failure_output, BwlimitSource, LiveBwlimit, RcloneCli,
+        RcloneCommandSpec, RcloneSubcommand,
     };
+    use crate::fs_utils::bandwidth;
     use std::ffi::OsString;
 
     #[test]
@@ -587,6 +679,72 @@ mod tests {
         );
     }
 
+    #[test]
+    fn bwlimit_starts_at_the_job_limit_and_remembers_the_job() {
+        let event = "rclone-cli-bwlimit-spec";
+        bandwidth::set_job_limit(event, Some(2048));
+        let spec = RcloneCommandSpec::new(RcloneSubcommand::CopyTo)
+            .arg("a")
+            .arg("remote:b")
+            .bwlimit(Some(event));
+        bandwidth::set_job_limit(event, None);
+        assert_eq!(
+            spec.argv(),
+            vec![
+                OsString::from("copyto"),
+                OsString::from("a"),
+                OsString::from("remote:b"),
+                OsString::from("--bwlimit"),
+                OsString::from("2048B"),
+            ]
+        );
+        assert_eq!(
+            spec.bwlimit,
+            Some(BwlimitSource {
+                event: Some(event.to_string()),
+                initial: Some(2048),
+            })
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn live_bwlimit_pushes_a_changed_limit_to_the_transfer() {
+        use crate::fs_utils::unique_temp_dir;
+        use std::io::{Read, Write};
+        use std::os::unix::net::UnixListener;
+
+        let dir = unique_temp_dir("rclone-live-bwlimit");
+        let socket_path = dir.join("cli.sock");
+        let listener = UnixListener::bind(&socket_path).unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 4096];
+            let read = stream.read(&mut request).unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n{}")
+                .unwrap();
+            String::from_utf8_lossy(&request[..read]).into_owned()
+        });
+
+        let event = "rclone-cli-bwlimit-live";
+        bandwidth::set_job_limit(event, Some(4096));
+        let mut live = LiveBwlimit {
+            event: Some(event.to_string()),
+            socket_path,
+            applied: Some(2048),
+        };
+        live.sync();
+        bandwidth::set_job_limit(event, None);
+
+        assert_eq!(live.applied, Some(4096));
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /core/bwlimit "), "{request}");
+        assert!(request.contains("\"4096B\""), "{request}");
+        drop(live);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn config_dump_builds_two_word_subcommand() {
         let spec = RcloneCommandSpec::new(RcloneSubcommand::ConfigDump);
@@ -605,6 +763,7 @@ mod tests {
         assert_eq!(RcloneSubcommand::Rmdir.default_timeout().as_secs(), 120);
         assert_eq!(RcloneSubcommand::Purge.default_timeout().as_secs(), 300);
         assert_eq!(RcloneSubcommand::CopyTo.default_timeout().as_secs(), 300);
+        assert_eq!(RcloneSubcommand::HashSum.default_timeout().as_secs(), 600);
     }
 
     #[test]
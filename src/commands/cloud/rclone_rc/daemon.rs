@@ -4,6 +4,7 @@ use super::{
     RCLONE_RC_STARTUP_POLL_SLICE, RCLONE_RC_STARTUP_TIMEOUT, RCLONE_RC_STATE_DIR_NAME,
     RCLONE_RC_WRITE_ENABLE_ENV,
 };
+use crate::fs_utils::bandwidth;
 use serde_json::json;
 use std::{
     env,
@@ -22,6 +23,8 @@ pub(super) struct RcloneRcDaemon {
     pub(super) socket_path: PathBuf,
     pub(super) binary: OsString,
     pub(super) child: Child,
+    /// The bandwidth limit the daemon applies to every transfer it runs.
+    pub(super) bwlimit: Option<u64>,
 }
 
 #[derive(Debug, Default)]
@@ -135,6 +138,53 @@ pub fn health_snapshot() -> RcloneRcHealth {
     }
 }
 
+/// The bandwidth limit of the running daemon; `None` when no daemon runs.
+pub(super) fn daemon_bwlimit() -> Option<Option<u64>> {
+    let state = match rclone_rc_state().lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    state.daemon.as_ref().map(|daemon| daemon.bwlimit)
+}
+
+pub(super) fn record_daemon_bwlimit(limit: Option<u64>) {
+    let mut state = match rclone_rc_state().lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if let Some(daemon) = state.daemon.as_mut() {
+        daemon.bwlimit = limit;
+    }
+}
+
+/// A socket for the rc server of one rclone CLI transfer, so its bandwidth limit can change
+/// while it runs. `None` where no socket can be set up.
+pub(crate) fn cli_rc_socket_path(key: u64) -> Option<PathBuf> {
+    if !cfg!(unix) {
+        return None;
+    }
+    let state_dir = rc_state_dir_path().ok()?;
+    prepare_state_dir(&state_dir).ok()?;
+    let socket_path = state_dir.join(format!("cli-{}-{key}.sock", std::process::id()));
+    cleanup_stale_socket(&socket_path).ok()?;
+    Some(socket_path)
+}
+
+/// Sets the bandwidth limit of the rclone CLI transfer serving rc on `socket_path`.
+pub(crate) fn push_cli_bwlimit(
+    socket_path: &Path,
+    limit: Option<u64>,
+) -> Result<(), RcloneCliError> {
+    harden_and_validate_socket_path(socket_path).map_err(RcloneCliError::Io)?;
+    run_rc_command_via_socket(
+        socket_path,
+        RcloneRcMethod::CoreBwlimit,
+        json!({ "rate": bandwidth::rclone_rate(limit) }),
+        RCLONE_RC_NOOP_TIMEOUT,
+    )?;
+    Ok(())
+}
+
 pub(super) fn parse_rc_toggle_value(value: &str) -> Option<bool> {
     let normalized = value.trim().to_ascii_lowercase();
     match normalized.as_str() {
@@ -187,6 +237,7 @@ pub(super) fn spawn_daemon(binary: &OsString) -> Result<RcloneRcDaemon, RcloneCl
     let socket_path = state_dir.join(format!("rcd-{}.sock", std::process::id()));
     cleanup_stale_socket(&socket_path).map_err(RcloneCliError::Io)?;
 
+    let bwlimit = bandwidth::shared_limit();
     let mut child = Command::new(binary)
         .arg("rcd")
         .arg("--rc-no-auth")
@@ -196,6 +247,8 @@ pub(super) fn spawn_daemon(binary: &OsString) -> Result<RcloneRcDaemon, RcloneCl
         .arg("5m")
         .arg("--rc-server-write-timeout")
         .arg("5m")
+        .arg("--bwlimit")
+        .arg(bandwidth::rclone_rate(bwlimit))
         .stdin(Stdio::null())
         .stdout(Stdio::null())
         .stderr(Stdio::null())
@@ -249,6 +302,7 @@ pub(super) fn spawn_daemon(binary: &OsString) -> Result<RcloneRcDaemon, RcloneCl
         socket_path,
         binary: binary.clone(),
         child,
+        bwlimit,
     })
 }
 
@@ -437,6 +491,7 @@ mod tests {
             socket_path: socket_path.clone(),
             binary: "rclone".into(),
             child,
+            bwlimit: None,
         };
 
         kill_daemon(&mut daemon).expect("kill daemon");
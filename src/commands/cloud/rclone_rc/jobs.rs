@@ -110,6 +110,7 @@ impl RcloneRcClient {
         payload_obj.insert("_async".to_string(), Value::Bool(true));
         if let Some(group) = group {
             payload_obj.insert("_group".to_string(), Value::String(group.to_string()));
+            self.sync_bwlimit();
         }
 
         let kickoff = self.run_method(method, payload)?;
@@ -142,6 +143,7 @@ impl RcloneRcClient {
             }
 
             if let Some(group) = group {
+                self.sync_bwlimit();
                 if let Ok(stats) = self.core_stats(Some(group), true) {
                     on_progress(stats);
                 }
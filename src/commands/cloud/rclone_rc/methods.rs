@@ -1,6 +1,9 @@
+use super::daemon::{daemon_bwlimit, record_daemon_bwlimit};
 use super::{RcloneCliError, RcloneRcClient, RcloneRcMethod};
+use crate::fs_utils::bandwidth;
 use serde_json::{json, Value};
 use std::sync::atomic::AtomicBool;
+use tracing::debug;
 
 pub(crate) struct RcCopyFileToLocalProgressSpec<'a> {
     pub src_fs: &'a str,
@@ -33,6 +36,20 @@ impl RcloneRcClient {
         self.run_method(RcloneRcMethod::CoreStatsDelete, json!({ "group": group }))
     }
 
+    /// Brings the daemon's bandwidth limit in line with the running jobs. The daemon holds one
+    /// limit for all of its transfers, so the strictest job limit applies to each of them.
+    pub(super) fn sync_bwlimit(&self) {
+        let wanted = bandwidth::shared_limit();
+        if daemon_bwlimit() == Some(wanted) {
+            return;
+        }
+        let rate = bandwidth::rclone_rate(wanted);
+        match self.run_method(RcloneRcMethod::CoreBwlimit, json!({ "rate": rate })) {
+            Ok(_) => record_daemon_bwlimit(wanted),
+            Err(error) => debug!(%error, rate, "failed to update rclone rc bandwidth limit"),
+        }
+    }
+
     pub fn list_remotes(&self) -> Result<Value, RcloneCliError> {
         self.run_method(RcloneRcMethod::ConfigListRemotes, json!({}))
     }
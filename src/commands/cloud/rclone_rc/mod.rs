@@ -10,6 +10,7 @@ pub(crate) use daemon::reset_state_for_tests;
 pub use daemon::{
     begin_shutdown_and_kill_daemon, health_snapshot, reset_backend_state, RcloneRcHealth,
 };
+pub(crate) use daemon::{cli_rc_socket_path, push_cli_bwlimit};
 use daemon::{rc_read_enabled, rc_write_enabled, should_recycle_daemon_after_error};
 #[cfg(test)]
 use jobs::ForcedAsyncStatusErrorState;
@@ -45,6 +46,7 @@ pub enum RcloneRcMethod {
     CoreNoop,
     CoreStats,
     CoreStatsDelete,
+    CoreBwlimit,
     ConfigListRemotes,
     ConfigDump,
     OperationsList,
@@ -65,6 +67,7 @@ impl RcloneRcMethod {
             Self::CoreNoop => "rc/noop",
             Self::CoreStats => "core/stats",
             Self::CoreStatsDelete => "core/stats-delete",
+            Self::CoreBwlimit => "core/bwlimit",
             Self::ConfigListRemotes => "config/listremotes",
             Self::ConfigDump => "config/dump",
             Self::OperationsList => "operations/list",
@@ -86,6 +89,7 @@ fn method_timeout(method: RcloneRcMethod) -> Duration {
         RcloneRcMethod::CoreNoop
         | RcloneRcMethod::CoreStats
         | RcloneRcMethod::CoreStatsDelete
+        | RcloneRcMethod::CoreBwlimit
         | RcloneRcMethod::ConfigListRemotes
         | RcloneRcMethod::ConfigDump
         | RcloneRcMethod::OperationsList
@@ -107,6 +111,7 @@ fn method_is_retry_safe(method: RcloneRcMethod) -> bool {
         RcloneRcMethod::CoreNoop
             | RcloneRcMethod::CoreStats
             | RcloneRcMethod::CoreStatsDelete
+            | RcloneRcMethod::CoreBwlimit
             | RcloneRcMethod::ConfigListRemotes
             | RcloneRcMethod::ConfigDump
             | RcloneRcMethod::JobStatus
@@ -153,6 +158,7 @@ fn allowlisted_method_from_name(method_name: &str) -> Option<RcloneRcMethod> {
         "rc/noop" => Some(RcloneRcMethod::CoreNoop),
         "core/stats" => Some(RcloneRcMethod::CoreStats),
         "core/stats-delete" => Some(RcloneRcMethod::CoreStatsDelete),
+        "core/bwlimit" => Some(RcloneRcMethod::CoreBwlimit),
         "config/listremotes" => Some(RcloneRcMethod::ConfigListRemotes),
         "config/dump" => Some(RcloneRcMethod::ConfigDump),
         "operations/list" => Some(RcloneRcMethod::OperationsList),
@@ -257,6 +263,7 @@ impl RcloneRcClient {
             | RcloneRcMethod::OperationsRmdir
             | RcloneRcMethod::OperationsCopyFile
             | RcloneRcMethod::OperationsMoveFile
+            | RcloneRcMethod::CoreBwlimit
             | RcloneRcMethod::JobStatus
             | RcloneRcMethod::JobStop => self.is_write_enabled(),
         };
@@ -340,6 +347,7 @@ mod tests {
         );
         assert_eq!(RcloneRcMethod::JobStatus.as_str(), "job/status");
         assert_eq!(RcloneRcMethod::JobStop.as_str(), "job/stop");
+        assert_eq!(RcloneRcMethod::CoreBwlimit.as_str(), "core/bwlimit");
     }
 
     #[test]
@@ -360,6 +368,10 @@ mod tests {
             allowlisted_method_from_name("job/stop"),
             Some(RcloneRcMethod::JobStop)
         );
+        assert_eq!(
+            allowlisted_method_from_name("core/bwlimit"),
+            Some(RcloneRcMethod::CoreBwlimit)
+        );
         assert_eq!(allowlisted_method_from_name("../rc/noop"), None);
         assert_eq!(allowlisted_method_from_name("rc/noop?x=1"), None);
         assert_eq!(allowlisted_method_from_name("sync/copy"), None);
@@ -403,6 +415,7 @@ mod tests {
         assert!(method_is_retry_safe(RcloneRcMethod::ConfigDump));
         assert!(method_is_retry_safe(RcloneRcMethod::JobStatus));
         assert!(method_is_retry_safe(RcloneRcMethod::JobStop));
+        assert!(method_is_retry_safe(RcloneRcMethod::CoreBwlimit));
         assert!(!method_is_retry_safe(RcloneRcMethod::OperationsList));
         assert!(!method_is_retry_safe(RcloneRcMethod::OperationsMkdir));
         assert!(!method_is_retry_safe(RcloneRcMethod::OperationsDeleteFile));
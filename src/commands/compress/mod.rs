@@ -1,6 +1,6 @@
 use std::{
     fs::{self, File},
-    io::{self, BufReader, BufWriter, Read, Write},
+    io::{self, Read, Write},
     path::{Path, PathBuf},
     sync::{
         atomic::{AtomicBool, AtomicU64, Ordering},
@@ -9,26 +9,29 @@ use std::{
     time::{SystemTime, UNIX_EPOCH},
 };
 
-use chrono::{DateTime as ChronoDateTime, Datelike, Local, Timelike};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use walkdir::WalkDir;
-use zip::{write::SimpleFileOptions, CompressionMethod, DateTime as ZipDateTime, ZipWriter};
 
 use crate::errors::api_error::ApiResult;
+use crate::fs_utils::{MetadataPreserver, PreserveAttr, PreserveOptions, UnpreservedAttr};
 use crate::undo::{temp_backup_path, Action, UndoState};
 use crate::{
     runtime_lifecycle,
-    tasks::{CancelGuard, CancelState},
+    tasks::{self, run_job, CancelGuard, CancelState, JobKind, JobSpec},
 };
 use error::{map_api_result, CompressError, CompressErrorCode, CompressResult};
 
 mod error;
 mod pathing;
+mod seven_z_format;
+mod tar_format;
+mod volumes;
+mod zip_format;
 use pathing::{destination_path, ensure_same_parent, resolve_input_path};
+use volumes::{volume_path, VolumeWriter, MIN_VOLUME_SIZE};
 
 const CHUNK: usize = 4 * 1024 * 1024;
 const FILE_READ_BUF: usize = 256 * 1024;
-const ZIP64_LIMIT: u64 = 0xFFFF_FFFF;
 const COMPRESS_CANCEL_CHECK_INTERVAL_BYTES: u64 = 16 * 1024 * 1024; // 16 MiB
 
 #[derive(Debug, Clone)]
@@ -37,10 +40,17 @@ struct EntryMeta {
     rel_path: PathBuf,
     kind: EntryKind,
     mode: Option<u32>,
-    modified: Option<ZipDateTime>,
+    modified: Option<SystemTime>,
     size: u64,
 }
 
+impl EntryMeta {
+    /// The entry's path inside the archive, with `/` separators.
+    fn archive_name(&self) -> String {
+        self.rel_path.to_string_lossy().replace('\\', "/")
+    }
+}
+
 #[derive(Debug, Clone)]
 enum EntryKind {
     File { precompressed: bool },
@@ -59,19 +69,6 @@ fn metadata_mode(_meta: &fs::Metadata) -> Option<u32> {
     None
 }
 
-fn system_time_to_zip_datetime(time: SystemTime) -> Option<ZipDateTime> {
-    let dt: ChronoDateTime<Local> = time.into();
-    ZipDateTime::from_date_and_time(
-        dt.year() as u16,
-        dt.month() as u8,
-        dt.day() as u8,
-        dt.hour() as u8,
-        dt.minute() as u8,
-        dt.second() as u8,
-    )
-    .ok()
-}
-
 fn current_millis() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -79,75 +76,6 @@ fn current_millis() -> u64 {
         .unwrap_or(0)
 }
 
-fn add_path_to_zip(
-    zip: &mut ZipWriter<BufWriter<File>>,
-    entry: &EntryMeta,
-    deflated_opts: &SimpleFileOptions,
-    stored_opts: &SimpleFileOptions,
-    progress: Option<&ProgressEmitter>,
-    cancel: Option<&AtomicBool>,
-    buf: &mut [u8],
-) -> CompressResult<()> {
-    check_cancel(cancel).map_err(|e| {
-        CompressError::from_external_message(map_copy_err("Compression cancelled", e))
-    })?;
-    let mut rel_name = entry.rel_path.to_string_lossy().replace('\\', "/");
-    match &entry.kind {
-        EntryKind::Dir => {
-            if !rel_name.ends_with('/') {
-                rel_name.push('/');
-            }
-            let opts = with_entry_metadata(*stored_opts, entry);
-            zip.add_directory(rel_name, opts).map_err(|e| {
-                CompressError::from_external_message(format!("Failed to add directory to zip: {e}"))
-            })?;
-        }
-        EntryKind::Symlink { target } => {
-            if rel_name.ends_with('/') {
-                rel_name.pop();
-            }
-            let target = target.to_string_lossy().replace('\\', "/");
-            let opts = with_entry_metadata(*stored_opts, entry);
-            zip.add_symlink(rel_name, target, opts).map_err(|e| {
-                CompressError::from_external_message(format!("Failed to add symlink to zip: {e}"))
-            })?;
-        }
-        EntryKind::File { precompressed } => {
-            let mut base_opts = if *precompressed {
-                *stored_opts
-            } else {
-                *deflated_opts
-            };
-            if entry.size >= ZIP64_LIMIT {
-                base_opts = base_opts.large_file(true);
-            }
-            let opts = with_entry_metadata(base_opts, entry);
-            zip.start_file(rel_name, opts).map_err(|e| {
-                CompressError::from_external_message(format!("Failed to start zip entry: {e}"))
-            })?;
-            let file = File::open(&entry.path).map_err(|e| {
-                CompressError::from_external_message(format!("Failed to open file: {e}"))
-            })?;
-            let mut reader = BufReader::with_capacity(FILE_READ_BUF, file);
-            copy_with_progress(&mut reader, zip, progress, cancel, buf).map_err(|e| {
-                CompressError::from_external_message(map_copy_err("Failed to write file to zip", e))
-            })?;
-        }
-    }
-    Ok(())
-}
-
-fn with_entry_metadata(base: SimpleFileOptions, entry: &EntryMeta) -> SimpleFileOptions {
-    let mut opts = base;
-    if let Some(mode) = entry.mode {
-        opts = opts.unix_permissions(mode);
-    }
-    if let Some(modified) = entry.modified {
-        opts = opts.last_modified_time(modified);
-    }
-    opts
-}
-
 fn is_precompressed(path: &Path) -> bool {
     matches!(
         path
@@ -202,7 +130,7 @@ fn collect_entries(
             total_size = total_size.saturating_add(meta.len());
         }
 
-        let modified = meta.modified().ok().and_then(system_time_to_zip_datetime);
+        let modified = meta.modified().ok();
         let mode = metadata_mode(&meta);
         let kind = if file_type.is_dir() {
             EntryKind::Dir
@@ -252,6 +180,87 @@ fn collect_entries(
     Ok((out, total_size))
 }
 
+/// Archive formats `compress_entries` can write, named by their file extension.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArchiveFormat {
+    #[default]
+    #[serde(rename = "zip")]
+    Zip,
+    #[serde(rename = "tar")]
+    Tar,
+    #[serde(rename = "tar.gz")]
+    TarGz,
+    #[serde(rename = "tar.bz2")]
+    TarBz2,
+    #[serde(rename = "tar.xz")]
+    TarXz,
+    #[serde(rename = "tar.zst")]
+    TarZst,
+    #[serde(rename = "7z")]
+    SevenZ,
+}
+
+impl ArchiveFormat {
+    const ALL: [Self; 7] = [
+        Self::Zip,
+        Self::Tar,
+        Self::TarGz,
+        Self::TarBz2,
+        Self::TarXz,
+        Self::TarZst,
+        Self::SevenZ,
+    ];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Zip => "zip",
+            Self::Tar => "tar",
+            Self::TarGz => "tar.gz",
+            Self::TarBz2 => "tar.bz2",
+            Self::TarXz => "tar.xz",
+            Self::TarZst => "tar.zst",
+            Self::SevenZ => "7z",
+        }
+    }
+
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|format| format.extension() == extension)
+    }
+
+    /// Zip and 7z can be encrypted with AES-256; tar has no encryption of its own.
+    pub fn supports_password(self) -> bool {
+        matches!(self, Self::Zip | Self::SevenZ)
+    }
+
+    /// Zip and 7z can be split into `.001`, `.002`, ... volumes, which extraction joins again.
+    pub fn supports_volumes(self) -> bool {
+        matches!(self, Self::Zip | Self::SevenZ)
+    }
+}
+
+/// What each archive writer needs next to the entries.
+struct ArchiveWriteContext<'a> {
+    /// 0 (store) to 9 (smallest).
+    level: u32,
+    preserve: &'a MetadataPreserver,
+    progress: Option<&'a ProgressEmitter>,
+    cancel: Option<&'a AtomicBool>,
+    /// Encrypts the archive with AES-256 when set.
+    password: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+pub struct CompressOutcome {
+    /// The archive, or its first volume when it was split.
+    pub destination: String,
+    /// Every volume in order when the archive was split, empty otherwise.
+    pub volumes: Vec<String>,
+    /// Requested attributes that the archive could not store.
+    pub unpreserved: Vec<UnpreservedAttr>,
+}
+
 #[derive(Serialize, Clone, Copy)]
 struct CompressProgressPayload {
     bytes: u64,
@@ -331,6 +340,13 @@ impl ProgressEmitter {
     }
 }
 
+/// Holds the compression between chunks or entries while its job is paused.
+fn pause_checkpoint(progress: Option<&ProgressEmitter>) {
+    if let Some(p) = progress {
+        tasks::pause_checkpoint(&p.app, &p.event);
+    }
+}
+
 fn is_cancelled(cancel: Option<&AtomicBool>) -> bool {
     cancel.map(|c| c.load(Ordering::Relaxed)).unwrap_or(false)
 }
@@ -352,6 +368,7 @@ fn map_copy_err(context: &str, err: io::Error) -> String {
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn compress_entries(
     app: tauri::AppHandle,
     cancel: tauri::State<'_, CancelState>,
@@ -359,22 +376,51 @@ pub async fn compress_entries(
     paths: Vec<String>,
     name: Option<String>,
     level: Option<u32>,
+    format: Option<ArchiveFormat>,
+    password: Option<String>,
+    volume_size: Option<u64>,
     progress_event: Option<String>,
-) -> ApiResult<String> {
+    preserve: Option<Vec<PreserveAttr>>,
+) -> ApiResult<CompressOutcome> {
+    // Archives have always carried times and permission bits.
+    let default = PreserveOptions::from_attrs(&[PreserveAttr::Times, PreserveAttr::Mode]);
+    // The archive is written next to the items it packs.
+    let target = paths
+        .first()
+        .and_then(|path| Path::new(path).parent())
+        .map(|parent| parent.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let job = JobSpec::new(
+        JobKind::Compress,
+        target,
+        paths.clone(),
+        progress_event.clone(),
+    );
+    let work = compress_entries_impl(
+        app.clone(),
+        cancel.inner().clone(),
+        undo.inner().clone(),
+        paths,
+        name,
+        level,
+        format.unwrap_or_default(),
+        password,
+        volume_size,
+        progress_event,
+        PreserveOptions::from_request(preserve, default),
+    );
     map_api_result(
-        compress_entries_impl(
-            app,
-            cancel.inner().clone(),
-            undo.inner().clone(),
-            paths,
-            name,
-            level,
-            progress_event,
+        run_job(
+            &app,
+            job,
+            || CompressError::new(CompressErrorCode::Cancelled, "Compression cancelled"),
+            work,
         )
         .await,
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn compress_entries_impl(
     app: tauri::AppHandle,
     cancel_state: CancelState,
@@ -382,8 +428,12 @@ async fn compress_entries_impl(
     paths: Vec<String>,
     name: Option<String>,
     level: Option<u32>,
+    format: ArchiveFormat,
+    password: Option<String>,
+    volume_size: Option<u64>,
     progress_event: Option<String>,
-) -> CompressResult<String> {
+    preserve: PreserveOptions,
+) -> CompressResult<CompressOutcome> {
     let task = tauri::async_runtime::spawn_blocking(move || {
         do_compress(
             app,
@@ -392,7 +442,11 @@ async fn compress_entries_impl(
             paths,
             name,
             level,
+            format,
+            password.as_deref(),
+            volume_size,
             progress_event,
+            preserve,
         )
     });
     match task.await {
@@ -404,6 +458,7 @@ async fn compress_entries_impl(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn do_compress(
     app: tauri::AppHandle,
     cancel_state: CancelState,
@@ -411,11 +466,46 @@ fn do_compress(
     paths: Vec<String>,
     name: Option<String>,
     level: Option<u32>,
+    format: ArchiveFormat,
+    password: Option<&str>,
+    volume_size: Option<u64>,
     progress_event: Option<String>,
-) -> CompressResult<String> {
+    preserve: PreserveOptions,
+) -> CompressResult<CompressOutcome> {
     if paths.is_empty() {
         return Err(CompressError::from_external_message("Nothing to compress"));
     }
+    if let Some(size) = volume_size {
+        if !format.supports_volumes() {
+            return Err(CompressError::new(
+                CompressErrorCode::InvalidInput,
+                format!(
+                    "{} archives cannot be split into volumes",
+                    format.extension()
+                ),
+            ));
+        }
+        if size < MIN_VOLUME_SIZE {
+            return Err(CompressError::new(
+                CompressErrorCode::InvalidInput,
+                format!("Volumes must be at least {} KiB", MIN_VOLUME_SIZE / 1024),
+            ));
+        }
+    }
+    if let Some(password) = password {
+        if password.is_empty() {
+            return Err(CompressError::new(
+                CompressErrorCode::InvalidInput,
+                "Archive password cannot be empty",
+            ));
+        }
+        if !format.supports_password() {
+            return Err(CompressError::new(
+                CompressErrorCode::InvalidInput,
+                format!("{} archives cannot be encrypted", format.extension()),
+            ));
+        }
+    }
     let mut resolved: Vec<PathBuf> = Vec::new();
     for raw in paths {
         let pb = resolve_input_path(&raw)?;
@@ -436,14 +526,20 @@ fn do_compress(
     let dest_name = name.unwrap_or(suggested);
     let lvl = level.unwrap_or(6).min(9);
     let mut dest_idx = 0usize;
-    let (dest, file) = loop {
-        let candidate = destination_path(&parent, &dest_name, dest_idx)?;
-        match File::options()
-            .write(true)
-            .create_new(true)
-            .open(&candidate)
-        {
-            Ok(f) => break (candidate, f),
+    let mut output = loop {
+        let candidate = destination_path(&parent, &dest_name, format.extension(), dest_idx)?;
+        // A split archive is named by its first volume; the rest follow as it grows.
+        let first = match volume_size {
+            Some(_) => volume_path(&candidate, 1),
+            None => candidate.clone(),
+        };
+        match File::options().write(true).create_new(true).open(&first) {
+            Ok(f) => {
+                break match volume_size {
+                    Some(size) => VolumeWriter::split(candidate, f, size),
+                    None => VolumeWriter::single(candidate, f),
+                }
+            }
             Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
                 dest_idx = dest_idx.saturating_add(1);
                 continue;
@@ -456,8 +552,6 @@ fn do_compress(
         }
     };
 
-    let mut writer = ZipWriter::new(BufWriter::with_capacity(CHUNK, file));
-
     let store_precompressed = lvl == 0;
     let (entries, total_size) = collect_entries(&parent, &resolved, store_precompressed)?;
     if entries.is_empty() {
@@ -478,82 +572,78 @@ fn do_compress(
             )
         })?;
     let cancel_token = cancel_guard.as_ref().map(|c| c.token());
-    let mut cleanup = CompressionCleanup::new(dest.clone());
-    let mut buf = vec![0u8; CHUNK];
-
-    let method = if lvl == 0 {
-        CompressionMethod::Stored
-    } else {
-        CompressionMethod::Deflated
-    };
-    let level_opt = if method == CompressionMethod::Stored {
-        None
-    } else {
-        Some(lvl as i64)
-    };
-
-    let deflated_opts = SimpleFileOptions::default()
-        .compression_method(method)
-        .compression_level(level_opt);
-
-    let stored_opts = SimpleFileOptions::default()
-        .compression_method(CompressionMethod::Stored)
-        .compression_level(None);
+    let mut cleanup = CompressionCleanup::new(output.paths().to_vec());
+    let preserve = MetadataPreserver::new(preserve);
 
     let mut entries = entries;
-    if entries.is_empty() {
-        return Err(CompressError::from_external_message("Nothing to compress"));
-    }
     entries.sort_by(|a, b| a.rel_path.cmp(&b.rel_path));
 
-    let result: CompressResult<()> = (|| {
-        for entry in &entries {
-            check_cancel(cancel_token.as_deref()).map_err(|e| {
-                CompressError::from_external_message(map_copy_err("Compression cancelled", e))
-            })?;
-            add_path_to_zip(
-                &mut writer,
-                entry,
-                &deflated_opts,
-                &stored_opts,
-                progress.as_ref(),
-                cancel_token.as_deref(),
-                &mut buf,
-            )?;
-        }
-
-        writer.finish().map_err(|e| {
-            CompressError::from_external_message(format!("Failed to finalize zip: {e}"))
-        })?;
-
+    let ctx = ArchiveWriteContext {
+        level: lvl,
+        preserve: &preserve,
+        progress: progress.as_ref(),
+        cancel: cancel_token.as_deref(),
+        password,
+    };
+    let result = match format {
+        ArchiveFormat::Zip => zip_format::write_zip(&mut output, &entries, &ctx),
+        ArchiveFormat::SevenZ => seven_z_format::write_7z(&mut output, &entries, &ctx),
+        _ => tar_format::write_tar(&mut output, format, &entries, &ctx),
+    };
+    // Volumes after the first were created while writing.
+    let written = output.into_paths();
+    cleanup.paths.clone_from(&written);
+    if result.is_ok() {
         if let Some(p) = progress.as_ref() {
             p.finish();
         }
-        Ok(())
-    })();
+    }
 
     match result {
         Ok(_) => {
             cleanup.disarm();
-            let backup = temp_backup_path(&dest);
-            let _ = undo.record_applied(Action::Create {
-                path: dest.clone(),
-                backup,
-            });
-            Ok(dest.to_string_lossy().into_owned())
+            let mut actions = written
+                .iter()
+                .map(|path| Action::Create {
+                    path: path.clone(),
+                    backup: temp_backup_path(path),
+                })
+                .collect::<Vec<_>>();
+            let action = if actions.len() == 1 {
+                actions.remove(0)
+            } else {
+                Action::Batch(actions)
+            };
+            let _ = undo.record_applied(action);
+            let volumes = if volume_size.is_some() {
+                written
+                    .iter()
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            Ok(CompressOutcome {
+                destination: written[0].to_string_lossy().into_owned(),
+                volumes,
+                unpreserved: preserve.into_report(),
+            })
         }
         Err(e) => Err(e),
     }
 }
 
 struct CompressionCleanup {
-    dest: PathBuf,
+    paths: Vec<PathBuf>,
     active: bool,
 }
 
 impl CompressionCleanup {
-    fn new(dest: PathBuf) -> Self {
-        Self { dest, active: true }
+    fn new(paths: Vec<PathBuf>) -> Self {
+        Self {
+            paths,
+            active: true,
+        }
     }
 
     fn disarm(&mut self) {
@@ -564,8 +654,50 @@ impl CompressionCleanup {
 impl Drop for CompressionCleanup {
     fn drop(&mut self) {
         if self.active {
-            let _ = fs::remove_file(&self.dest);
+            for path in &self.paths {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+/// Feeds file data to an archive writer that pulls it, counting it into the progress and
+/// checking for cancellation like [`copy_with_progress`].
+struct ProgressReader<'a, R> {
+    inner: R,
+    progress: Option<&'a ProgressEmitter>,
+    cancel: Option<&'a AtomicBool>,
+    since_cancel_check: u64,
+}
+
+impl<'a, R: Read> ProgressReader<'a, R> {
+    fn new(
+        inner: R,
+        progress: Option<&'a ProgressEmitter>,
+        cancel: Option<&'a AtomicBool>,
+    ) -> Self {
+        Self {
+            inner,
+            progress,
+            cancel,
+            since_cancel_check: COMPRESS_CANCEL_CHECK_INTERVAL_BYTES,
+        }
+    }
+}
+
+impl<R: Read> Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.since_cancel_check >= COMPRESS_CANCEL_CHECK_INTERVAL_BYTES {
+            pause_checkpoint(self.progress);
+            check_cancel(self.cancel)?;
+            self.since_cancel_check = 0;
+        }
+        let n = self.inner.read(buf)?;
+        self.since_cancel_check = self.since_cancel_check.saturating_add(n as u64);
+        if let Some(p) = self.progress {
+            p.add(n as u64);
         }
+        Ok(n)
     }
 }
 
@@ -580,6 +712,7 @@ fn copy_with_progress<R: Read, W: Write>(
     let mut since_cancel_check = COMPRESS_CANCEL_CHECK_INTERVAL_BYTES;
     loop {
         if since_cancel_check >= COMPRESS_CANCEL_CHECK_INTERVAL_BYTES {
+            pause_checkpoint(progress);
             check_cancel(cancel)?;
             since_cancel_check = 0;
         }
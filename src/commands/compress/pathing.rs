@@ -58,22 +58,25 @@ fn safe_name(name: &str) -> CompressResult<String> {
     Ok(name.to_string())
 }
 
-pub(super) fn destination_path(parent: &Path, name: &str, idx: usize) -> CompressResult<PathBuf> {
-    let mut base = safe_name(name)?;
-    let lower = base.to_lowercase();
-    let has_zip = lower.ends_with(".zip");
-    if !has_zip {
-        base.push_str(".zip");
-    }
-    let stem = if has_zip {
-        base[..base.len() - 4].to_string()
-    } else {
-        base.trim_end_matches(".zip").to_string()
-    };
+/// `<name> (<idx>).<extension>` in `parent`, without doubling an extension `name` already has.
+pub(super) fn destination_path(
+    parent: &Path,
+    name: &str,
+    extension: &str,
+    idx: usize,
+) -> CompressResult<PathBuf> {
+    let base = safe_name(name)?;
+    let dotted = format!(".{extension}");
+    let stem = base
+        .len()
+        .checked_sub(dotted.len())
+        .and_then(|split| base.get(split..).map(|tail| (split, tail)))
+        .filter(|(_, tail)| tail.eq_ignore_ascii_case(&dotted))
+        .map_or(base.as_str(), |(split, _)| &base[..split]);
     let suffix = if idx == 0 {
         String::new()
     } else {
         format!(" ({idx})")
     };
-    Ok(parent.join(format!("{stem}{suffix}.zip")))
+    Ok(parent.join(format!("{stem}{suffix}{dotted}")))
 }
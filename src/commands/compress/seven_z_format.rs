@@ -0,0 +1,122 @@
+use std::{
+    fs::File,
+    io::{BufReader, Seek, Write},
+};
+
+use sevenz_rust2::{
+    encoder_options::{AesEncoderOptions, Lzma2Options},
+    ArchiveEntry, ArchiveWriter, Password,
+};
+
+use super::{
+    check_cancel, is_cancelled, map_copy_err, pause_checkpoint, ArchiveWriteContext, CompressError,
+    CompressResult, EntryKind, EntryMeta, ProgressReader, FILE_READ_BUF,
+};
+
+const FILE_ATTRIBUTE_DIRECTORY: u32 = 0x10;
+const FILE_ATTRIBUTE_ARCHIVE: u32 = 0x20;
+/// Set by 7-Zip and p7zip when the high 16 bits of the attributes hold a unix mode.
+const FILE_ATTRIBUTE_UNIX_EXTENSION: u32 = 0x8000;
+const UNIX_SYMLINK_MODE: u32 = 0o120777;
+
+/// Writes a 7z archive with LZMA2. Modes are stored the way 7-Zip stores them on unix, and a
+/// symlink becomes an entry holding its target with a symlink mode, which 7-Zip restores as a
+/// link. Extended attributes and ACLs cannot be stored. With a password, contents and the header
+/// with the entry names are encrypted with AES-256.
+pub(super) fn write_7z<W: Write + Seek>(
+    output: W,
+    entries: &[EntryMeta],
+    ctx: &ArchiveWriteContext<'_>,
+) -> CompressResult<()> {
+    let mut writer = ArchiveWriter::new(output)
+        .map_err(|e| CompressError::from_external_message(format!("Failed to create 7z: {e}")))?;
+    let lzma2 = Lzma2Options::from_level(ctx.level).into();
+    match ctx.password {
+        Some(password) => {
+            writer.set_content_methods(vec![
+                AesEncoderOptions::new(Password::from(password)).into(),
+                lzma2,
+            ]);
+            writer.set_encrypt_header(true);
+        }
+        None => {
+            writer.set_content_methods(vec![lzma2]);
+        }
+    }
+    let options = ctx.preserve.options();
+    for entry in entries {
+        pause_checkpoint(ctx.progress);
+        check_cancel(ctx.cancel).map_err(|e| {
+            CompressError::from_external_message(map_copy_err("Compression cancelled", e))
+        })?;
+        ctx.preserve
+            .record_unstorable(&entry.path, "7z archives cannot store them");
+        let name = entry.archive_name();
+        let pushed = match &entry.kind {
+            EntryKind::Dir => {
+                let mut item = ArchiveEntry::from_path(&entry.path, name);
+                set_metadata(
+                    &mut item,
+                    entry.mode.filter(|_| options.mode),
+                    options.times,
+                );
+                writer.push_archive_entry::<&[u8]>(item, None)
+            }
+            EntryKind::Symlink { target } => {
+                let mut item = ArchiveEntry::new_file(&name);
+                set_metadata(&mut item, Some(UNIX_SYMLINK_MODE), false);
+                let target = target.to_string_lossy().replace('\\', "/");
+                writer.push_archive_entry(item, Some(target.as_bytes()))
+            }
+            EntryKind::File { .. } => {
+                let file = File::open(&entry.path).map_err(|e| {
+                    CompressError::from_external_message(format!("Failed to open file: {e}"))
+                })?;
+                let mut item = ArchiveEntry::from_path(&entry.path, name);
+                set_metadata(
+                    &mut item,
+                    entry.mode.filter(|_| options.mode),
+                    options.times,
+                );
+                let reader = BufReader::with_capacity(FILE_READ_BUF, file);
+                writer.push_archive_entry(
+                    item,
+                    Some(ProgressReader::new(reader, ctx.progress, ctx.cancel)),
+                )
+            }
+        };
+        if let Err(e) = pushed {
+            if is_cancelled(ctx.cancel) {
+                return Err(CompressError::from_external_message(
+                    "Compression cancelled",
+                ));
+            }
+            return Err(CompressError::from_external_message(format!(
+                "Failed to write entry to 7z: {e}"
+            )));
+        }
+    }
+    writer
+        .finish()
+        .map_err(|e| CompressError::from_external_message(format!("Failed to finalize 7z: {e}")))?;
+    Ok(())
+}
+
+/// Stores `mode` in the attributes and drops the times `from_path` read when they were not
+/// requested.
+fn set_metadata(item: &mut ArchiveEntry, mode: Option<u32>, times: bool) {
+    if !times {
+        item.has_creation_date = false;
+        item.has_last_modified_date = false;
+        item.has_access_date = false;
+    }
+    if let Some(mode) = mode {
+        let kind = if item.is_directory {
+            FILE_ATTRIBUTE_DIRECTORY
+        } else {
+            FILE_ATTRIBUTE_ARCHIVE
+        };
+        item.has_windows_attributes = true;
+        item.windows_attributes = kind | FILE_ATTRIBUTE_UNIX_EXTENSION | ((mode & 0xFFFF) << 16);
+    }
+}
@@ -0,0 +1,287 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bzip2::write::BzEncoder;
+use flate2::write::GzEncoder;
+use tar::{Builder, EntryType, Header};
+use xz2::write::XzEncoder;
+use zstd::stream::write::Encoder as ZstdEncoder;
+
+use super::{
+    check_cancel, map_copy_err, pause_checkpoint, ArchiveFormat, ArchiveWriteContext,
+    CompressError, CompressResult, EntryKind, EntryMeta, ProgressReader, CHUNK, FILE_READ_BUF,
+};
+use crate::fs_utils::PreserveAttr;
+
+/// PAX records GNU tar and bsdtar use for extended attributes, ACLs included.
+const PAX_XATTR_PREFIX: &str = "SCHILY.xattr.";
+const DEFAULT_FILE_MODE: u32 = 0o644;
+const DEFAULT_DIR_MODE: u32 = 0o755;
+const SYMLINK_MODE: u32 = 0o777;
+
+/// The compression around a tar stream. Each encoder has to be finished for its trailer.
+enum TarEncoder<W: Write> {
+    Plain(W),
+    Gz(GzEncoder<W>),
+    Bz2(BzEncoder<W>),
+    Xz(XzEncoder<W>),
+    Zstd(ZstdEncoder<'static, W>),
+}
+
+impl<W: Write> TarEncoder<W> {
+    /// `level` is the 0-9 archive level; each codec gets the matching level of its own range.
+    fn new(inner: W, format: ArchiveFormat, level: u32) -> io::Result<Self> {
+        Ok(match format {
+            ArchiveFormat::TarGz => {
+                Self::Gz(GzEncoder::new(inner, flate2::Compression::new(level)))
+            }
+            ArchiveFormat::TarBz2 => Self::Bz2(BzEncoder::new(
+                inner,
+                bzip2::Compression::new(level.clamp(1, 9)),
+            )),
+            ArchiveFormat::TarXz => Self::Xz(XzEncoder::new(inner, level)),
+            ArchiveFormat::TarZst => {
+                Self::Zstd(ZstdEncoder::new(inner, (level * 2 + 1).min(19) as i32)?)
+            }
+            _ => Self::Plain(inner),
+        })
+    }
+
+    fn finish(self) -> io::Result<W> {
+        match self {
+            Self::Plain(inner) => Ok(inner),
+            Self::Gz(encoder) => encoder.finish(),
+            Self::Bz2(encoder) => encoder.finish(),
+            Self::Xz(encoder) => encoder.finish(),
+            Self::Zstd(encoder) => encoder.finish(),
+        }
+    }
+}
+
+impl<W: Write> Write for TarEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(inner) => inner.write(buf),
+            Self::Gz(encoder) => encoder.write(buf),
+            Self::Bz2(encoder) => encoder.write(buf),
+            Self::Xz(encoder) => encoder.write(buf),
+            Self::Zstd(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Plain(inner) => inner.flush(),
+            Self::Gz(encoder) => encoder.flush(),
+            Self::Bz2(encoder) => encoder.flush(),
+            Self::Xz(encoder) => encoder.flush(),
+            Self::Zstd(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// Writes a plain or compressed tar. Symlinks stay symlinks; modes, times, extended attributes
+/// and ACLs are stored when requested, the last two as PAX records.
+pub(super) fn write_tar<W: Write>(
+    output: W,
+    format: ArchiveFormat,
+    entries: &[EntryMeta],
+    ctx: &ArchiveWriteContext<'_>,
+) -> CompressResult<()> {
+    let encoder = TarEncoder::new(BufWriter::with_capacity(CHUNK, output), format, ctx.level)
+        .map_err(|e| {
+            CompressError::from_external_message(format!("Failed to create encoder: {e}"))
+        })?;
+    let mut builder = Builder::new(encoder);
+    let archived_at = SystemTime::now();
+    for entry in entries {
+        pause_checkpoint(ctx.progress);
+        check_cancel(ctx.cancel).map_err(|e| {
+            CompressError::from_external_message(map_copy_err("Compression cancelled", e))
+        })?;
+        append_entry(&mut builder, entry, ctx, archived_at)?;
+    }
+    let encoder = builder.into_inner().map_err(|e| {
+        CompressError::from_external_message(format!("Failed to finalize tar: {e}"))
+    })?;
+    encoder
+        .finish()
+        .and_then(|mut writer| writer.flush())
+        .map_err(|e| {
+            CompressError::from_external_message(format!("Failed to finalize tar: {e}"))
+        })?;
+    Ok(())
+}
+
+fn append_entry<W: Write>(
+    builder: &mut Builder<W>,
+    entry: &EntryMeta,
+    ctx: &ArchiveWriteContext<'_>,
+    archived_at: SystemTime,
+) -> CompressResult<()> {
+    let options = ctx.preserve.options();
+    let name = entry.archive_name();
+    let mut header = Header::new_gnu();
+    let modified = entry
+        .modified
+        .filter(|_| options.times)
+        .unwrap_or(archived_at);
+    header.set_mtime(
+        modified
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+    );
+    let default_mode = match entry.kind {
+        EntryKind::Dir => DEFAULT_DIR_MODE,
+        EntryKind::Symlink { .. } => SYMLINK_MODE,
+        EntryKind::File { .. } => DEFAULT_FILE_MODE,
+    };
+    header.set_mode(
+        entry
+            .mode
+            .filter(|_| options.mode)
+            .map_or(default_mode, |mode| mode & 0o7777),
+    );
+
+    // Attributes of a symlink would be read from its target.
+    if !matches!(entry.kind, EntryKind::Symlink { .. }) {
+        append_xattrs(builder, entry, ctx)?;
+    }
+
+    let appended = match &entry.kind {
+        EntryKind::Dir => {
+            header.set_entry_type(EntryType::Directory);
+            header.set_size(0);
+            builder.append_data(&mut header, format!("{name}/"), io::empty())
+        }
+        EntryKind::Symlink { target } => {
+            header.set_entry_type(EntryType::Symlink);
+            header.set_size(0);
+            builder.append_link(&mut header, &name, target)
+        }
+        EntryKind::File { .. } => {
+            let file = File::open(&entry.path).map_err(|e| {
+                CompressError::from_external_message(format!("Failed to open file: {e}"))
+            })?;
+            // The header has to carry the size that is actually written.
+            let size = file.metadata().map(|meta| meta.len()).map_err(|e| {
+                CompressError::from_external_message(format!("Failed to read metadata: {e}"))
+            })?;
+            header.set_entry_type(EntryType::Regular);
+            header.set_size(size);
+            let reader = BufReader::with_capacity(FILE_READ_BUF, file).take(size);
+            builder.append_data(
+                &mut header,
+                &name,
+                ProgressReader::new(reader, ctx.progress, ctx.cancel),
+            )
+        }
+    };
+    appended.map_err(|e| {
+        CompressError::from_external_message(map_copy_err("Failed to write entry to tar", e))
+    })
+}
+
+fn append_xattrs<W: Write>(
+    builder: &mut Builder<W>,
+    entry: &EntryMeta,
+    ctx: &ArchiveWriteContext<'_>,
+) -> CompressResult<()> {
+    let mut records = Vec::new();
+    for (name, value) in ctx.preserve.read_xattrs(&entry.path) {
+        match String::from_utf8(name) {
+            Ok(name) => records.push((format!("{PAX_XATTR_PREFIX}{name}"), value)),
+            Err(_) => ctx.preserve.record(
+                &entry.path,
+                PreserveAttr::Xattr,
+                "tar archives cannot store attribute names that are not UTF-8",
+            ),
+        }
+    }
+    if records.is_empty() {
+        return Ok(());
+    }
+    builder
+        .append_pax_extensions(
+            records
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_slice())),
+        )
+        .map_err(|e| {
+            CompressError::from_external_message(format!("Failed to write tar attributes: {e}"))
+        })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::commands::compress::collect_entries;
+    use crate::fs_utils::{unique_temp_dir, MetadataPreserver, PreserveOptions};
+    use std::os::unix::fs::{symlink, PermissionsExt};
+    use std::path::PathBuf;
+
+    #[test]
+    fn tar_variants_keep_modes_and_symlinks() {
+        let dir = unique_temp_dir("tar");
+        let root = dir.join("docs");
+        std::fs::create_dir(&root).unwrap();
+        let script = root.join("run.sh");
+        std::fs::write(&script, b"echo hi").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o750)).unwrap();
+        symlink("run.sh", root.join("link")).unwrap();
+
+        let (mut entries, total) = collect_entries(&dir, &[root], false).unwrap();
+        entries.sort_by(|a, b| a.rel_path.cmp(&b.rel_path));
+        assert_eq!(total, 7);
+        let preserve = MetadataPreserver::new(PreserveOptions::from_attrs(&[PreserveAttr::Mode]));
+        let ctx = ArchiveWriteContext {
+            level: 6,
+            preserve: &preserve,
+            progress: None,
+            cancel: None,
+            password: None,
+        };
+
+        for format in [
+            ArchiveFormat::Tar,
+            ArchiveFormat::TarGz,
+            ArchiveFormat::TarZst,
+        ] {
+            let dest = dir.join(format!("out.{}", format.extension()));
+            write_tar(File::create(&dest).unwrap(), format, &entries, &ctx).unwrap();
+
+            let file = File::open(&dest).unwrap();
+            let reader: Box<dyn Read> = match format {
+                ArchiveFormat::TarGz => Box::new(flate2::read::GzDecoder::new(file)),
+                ArchiveFormat::TarZst => Box::new(zstd::stream::read::Decoder::new(file).unwrap()),
+                _ => Box::new(file),
+            };
+            let mut archive = tar::Archive::new(reader);
+            let mut seen = Vec::new();
+            for member in archive.entries().unwrap() {
+                let member = member.unwrap();
+                let header = member.header();
+                let path = member.path().unwrap().to_string_lossy().into_owned();
+                let path = path.trim_end_matches('/').to_string();
+                match path.as_str() {
+                    "docs/run.sh" => assert_eq!(header.mode().unwrap(), 0o750),
+                    "docs/link" => {
+                        assert_eq!(header.entry_type(), EntryType::Symlink);
+                        assert_eq!(
+                            member.link_name().unwrap().unwrap(),
+                            PathBuf::from("run.sh")
+                        );
+                    }
+                    _ => {}
+                }
+                seen.push(path);
+            }
+            assert_eq!(seen, vec!["docs", "docs/link", "docs/run.sh"]);
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,196 @@
+use std::{
+    fs::File,
+    io::{self, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+/// Smallest volume size accepted, so a typo cannot produce a folder of thousands of volumes.
+pub(super) const MIN_VOLUME_SIZE: u64 = 64 * 1024;
+
+/// `<archive>.001`, `<archive>.002`, ... as 7-Zip names the volumes it splits.
+pub(super) fn volume_path(archive: &Path, index: usize) -> PathBuf {
+    let mut name = archive.as_os_str().to_os_string();
+    name.push(format!(".{index:03}"));
+    PathBuf::from(name)
+}
+
+/// The archive file, or the volumes it is split into. Writers seek back to patch headers, so
+/// each write lands in whichever volume holds its offset; later volumes are created as the
+/// archive grows into them.
+pub(super) struct VolumeWriter {
+    base: PathBuf,
+    volume_size: u64,
+    volumes: Vec<File>,
+    paths: Vec<PathBuf>,
+    pos: u64,
+    len: u64,
+    /// The volume whose file cursor was last moved, and where that cursor is.
+    cursor: Option<(usize, u64)>,
+}
+
+impl VolumeWriter {
+    /// The archive written to `path` as it is.
+    pub(super) fn single(path: PathBuf, file: File) -> Self {
+        Self {
+            base: path.clone(),
+            volume_size: u64::MAX,
+            volumes: vec![file],
+            paths: vec![path],
+            pos: 0,
+            len: 0,
+            cursor: None,
+        }
+    }
+
+    /// The archive `base` cut into volumes of `volume_size` bytes; `first` is its `.001`.
+    pub(super) fn split(base: PathBuf, first: File, volume_size: u64) -> Self {
+        Self {
+            paths: vec![volume_path(&base, 1)],
+            base,
+            volume_size: volume_size.max(1),
+            volumes: vec![first],
+            pos: 0,
+            len: 0,
+            cursor: None,
+        }
+    }
+
+    /// Every file created so far, in order.
+    pub(super) fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    pub(super) fn into_paths(self) -> Vec<PathBuf> {
+        self.paths
+    }
+
+    fn volume(&mut self, idx: usize) -> io::Result<&mut File> {
+        while self.volumes.len() <= idx {
+            // Only full volumes may come before another one.
+            if let Some(last) = self.volumes.last() {
+                if last.metadata()?.len() < self.volume_size {
+                    last.set_len(self.volume_size)?;
+                }
+            }
+            let path = volume_path(&self.base, self.volumes.len() + 1);
+            let file = File::options()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+                .map_err(|e| {
+                    io::Error::new(
+                        e.kind(),
+                        format!(
+                            "{}: {e}",
+                            path.file_name().unwrap_or_default().to_string_lossy()
+                        ),
+                    )
+                })?;
+            self.paths.push(path);
+            self.volumes.push(file);
+        }
+        Ok(&mut self.volumes[idx])
+    }
+}
+
+impl Write for VolumeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let idx = usize::try_from(self.pos / self.volume_size)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too many volumes"))?;
+        let offset = self.pos % self.volume_size;
+        let room = (self.volume_size - offset).min(buf.len() as u64) as usize;
+        let moved = self.cursor != Some((idx, offset));
+        let file = self.volume(idx)?;
+        if moved {
+            file.seek(SeekFrom::Start(offset))?;
+        }
+        let n = file.write(&buf[..room])?;
+        self.cursor = Some((idx, offset + n as u64));
+        self.pos += n as u64;
+        self.len = self.len.max(self.pos);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.volumes.iter_mut().try_for_each(|file| file.flush())
+    }
+}
+
+impl Seek for VolumeWriter {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(delta) => self.len.checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.pos.checked_add_signed(delta),
+        };
+        self.pos = target.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "seek before the start of the archive",
+            )
+        })?;
+        Ok(self.pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs_utils::unique_temp_dir;
+
+    #[test]
+    fn splits_into_fixed_size_volumes_and_patches_across_them() {
+        let dir = unique_temp_dir("volumes");
+        let base = dir.join("out.7z");
+        let first = File::create(volume_path(&base, 1)).unwrap();
+        let mut writer = VolumeWriter::split(base.clone(), first, 100);
+
+        let bytes = (0..=255u8).cycle().take(250).collect::<Vec<_>>();
+        writer.write_all(&bytes).unwrap();
+        // A header patched at the start and one straddling the first cut.
+        writer.seek(SeekFrom::Start(0)).unwrap();
+        writer.write_all(b"HEAD").unwrap();
+        writer.seek(SeekFrom::End(-152)).unwrap();
+        writer.write_all(b"SPAN").unwrap();
+        writer.flush().unwrap();
+
+        let paths = writer.into_paths();
+        assert_eq!(
+            paths,
+            (1..=3)
+                .map(|idx| volume_path(&base, idx))
+                .collect::<Vec<_>>()
+        );
+        let sizes = paths
+            .iter()
+            .map(|path| std::fs::metadata(path).unwrap().len())
+            .collect::<Vec<_>>();
+        assert_eq!(sizes, vec![100, 100, 50]);
+        let joined = paths
+            .iter()
+            .flat_map(|path| std::fs::read(path).unwrap())
+            .collect::<Vec<_>>();
+        let mut expected = bytes;
+        expected[..4].copy_from_slice(b"HEAD");
+        expected[98..102].copy_from_slice(b"SPAN");
+        assert_eq!(joined, expected);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn refuses_to_overwrite_a_later_volume() {
+        let dir = unique_temp_dir("volumes-taken");
+        let base = dir.join("out.zip");
+        std::fs::write(volume_path(&base, 2), b"keep").unwrap();
+        let first = File::create(volume_path(&base, 1)).unwrap();
+        let mut writer = VolumeWriter::split(base.clone(), first, 4);
+        let err = writer.write_all(b"12345678").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert!(err.to_string().starts_with("out.zip.002: "));
+        assert_eq!(std::fs::read(volume_path(&base, 2)).unwrap(), b"keep");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,163 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Seek, Write},
+    time::SystemTime,
+};
+
+use chrono::{DateTime as ChronoDateTime, Datelike, Local, Timelike};
+use zip::{
+    write::SimpleFileOptions, AesMode, CompressionMethod, DateTime as ZipDateTime, ZipWriter,
+};
+
+use super::{
+    check_cancel, copy_with_progress, map_copy_err, pause_checkpoint, ArchiveWriteContext,
+    CompressError, CompressResult, EntryKind, EntryMeta, CHUNK, FILE_READ_BUF,
+};
+use crate::fs_utils::PreserveOptions;
+
+const ZIP64_LIMIT: u64 = 0xFFFF_FFFF;
+
+/// Writes a zip with deflate. With a password, file contents are encrypted with AES-256 (WinZip
+/// AE-2); names and symlink targets stay readable, as zip cannot hide them.
+pub(super) fn write_zip<W: Write + Seek>(
+    output: W,
+    entries: &[EntryMeta],
+    ctx: &ArchiveWriteContext<'_>,
+) -> CompressResult<()> {
+    let mut writer = ZipWriter::new(BufWriter::with_capacity(CHUNK, output));
+    let mut buf = vec![0u8; CHUNK];
+
+    let method = if ctx.level == 0 {
+        CompressionMethod::Stored
+    } else {
+        CompressionMethod::Deflated
+    };
+    let level_opt = if method == CompressionMethod::Stored {
+        None
+    } else {
+        Some(ctx.level as i64)
+    };
+
+    let deflated_opts = SimpleFileOptions::default()
+        .compression_method(method)
+        .compression_level(level_opt);
+
+    let stored_opts = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Stored)
+        .compression_level(None);
+
+    for entry in entries {
+        pause_checkpoint(ctx.progress);
+        check_cancel(ctx.cancel).map_err(|e| {
+            CompressError::from_external_message(map_copy_err("Compression cancelled", e))
+        })?;
+        add_path_to_zip(
+            &mut writer,
+            entry,
+            &deflated_opts,
+            &stored_opts,
+            ctx,
+            &mut buf,
+        )?;
+    }
+
+    writer.finish().map_err(|e| {
+        CompressError::from_external_message(format!("Failed to finalize zip: {e}"))
+    })?;
+    Ok(())
+}
+
+fn system_time_to_zip_datetime(time: SystemTime) -> Option<ZipDateTime> {
+    let dt: ChronoDateTime<Local> = time.into();
+    ZipDateTime::from_date_and_time(
+        dt.year() as u16,
+        dt.month() as u8,
+        dt.day() as u8,
+        dt.hour() as u8,
+        dt.minute() as u8,
+        dt.second() as u8,
+    )
+    .ok()
+}
+
+fn add_path_to_zip<W: Write + Seek>(
+    zip: &mut ZipWriter<BufWriter<W>>,
+    entry: &EntryMeta,
+    deflated_opts: &SimpleFileOptions,
+    stored_opts: &SimpleFileOptions,
+    ctx: &ArchiveWriteContext<'_>,
+    buf: &mut [u8],
+) -> CompressResult<()> {
+    check_cancel(ctx.cancel).map_err(|e| {
+        CompressError::from_external_message(map_copy_err("Compression cancelled", e))
+    })?;
+    ctx.preserve
+        .record_unstorable(&entry.path, "zip archives cannot store them");
+    let mut rel_name = entry.archive_name();
+    match &entry.kind {
+        EntryKind::Dir => {
+            if !rel_name.ends_with('/') {
+                rel_name.push('/');
+            }
+            let opts = with_entry_metadata(*stored_opts, entry, ctx.preserve.options());
+            zip.add_directory(rel_name, opts).map_err(|e| {
+                CompressError::from_external_message(format!("Failed to add directory to zip: {e}"))
+            })?;
+        }
+        EntryKind::Symlink { target } => {
+            if rel_name.ends_with('/') {
+                rel_name.pop();
+            }
+            let target = target.to_string_lossy().replace('\\', "/");
+            let opts = with_entry_metadata(*stored_opts, entry, ctx.preserve.options());
+            zip.add_symlink(rel_name, target, opts).map_err(|e| {
+                CompressError::from_external_message(format!("Failed to add symlink to zip: {e}"))
+            })?;
+        }
+        EntryKind::File { precompressed } => {
+            let mut base_opts = if *precompressed {
+                *stored_opts
+            } else {
+                *deflated_opts
+            };
+            if entry.size >= ZIP64_LIMIT {
+                base_opts = base_opts.large_file(true);
+            }
+            let opts = with_entry_metadata(base_opts, entry, ctx.preserve.options());
+            let opts = match ctx.password {
+                Some(password) => opts.with_aes_encryption(AesMode::Aes256, password),
+                None => opts,
+            };
+            zip.start_file(rel_name, opts).map_err(|e| {
+                CompressError::from_external_message(format!("Failed to start zip entry: {e}"))
+            })?;
+            let file = File::open(&entry.path).map_err(|e| {
+                CompressError::from_external_message(format!("Failed to open file: {e}"))
+            })?;
+            let mut reader = BufReader::with_capacity(FILE_READ_BUF, file);
+            copy_with_progress(&mut reader, zip, ctx.progress, ctx.cancel, buf).map_err(|e| {
+                CompressError::from_external_message(map_copy_err("Failed to write file to zip", e))
+            })?;
+        }
+    }
+    Ok(())
+}
+
+fn with_entry_metadata(
+    base: SimpleFileOptions,
+    entry: &EntryMeta,
+    preserve: PreserveOptions,
+) -> SimpleFileOptions {
+    let mut opts = base;
+    if let Some(mode) = entry.mode.filter(|_| preserve.mode) {
+        opts = opts.unix_permissions(mode);
+    }
+    if let Some(modified) = entry
+        .modified
+        .filter(|_| preserve.times)
+        .and_then(system_time_to_zip_datetime)
+    {
+        opts = opts.last_modified_time(modified);
+    }
+    opts
+}
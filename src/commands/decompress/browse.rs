@@ -0,0 +1,443 @@
+//! Archives as read-only folders: one directory level of an archive at a time, and copying
+//! selected members out of it with the same guardrails as a full extraction.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+};
+
+use super::error::{map_api_result, DecompressError, DecompressErrorCode, DecompressResult};
+use super::members::{
+    archive_member_uri, list_members, member_entry, ArchiveMember, ARCHIVE_URI_SCHEME,
+    ARCHIVE_URI_SEPARATOR,
+};
+use super::rar_format::{extract_rar, parse_rar_entries};
+use super::seven_z_format::extract_7z;
+use super::tar_format::{extract_tar, open_tar_reader};
+use super::util::{
+    CreatedPaths, EntryLayout, ExtractBudget, ExtractOptions, MemberTarget, Placement,
+    ProgressEmitter, SkipStats, EXTRACT_TOTAL_BYTES_CAP, EXTRACT_TOTAL_ENTRIES_CAP,
+};
+use super::zip_format::extract_zip;
+use super::{detect_archive, extract_budget_for, ArchiveKind};
+use crate::commands::listing::DirListing;
+use crate::errors::api_error::ApiResult;
+use crate::fs_utils::{sanitize_path_follow, MetadataPreserver};
+use crate::sorting::{sort_entries, SortSpec};
+
+pub(crate) fn is_archive_uri(path: &str) -> bool {
+    path.starts_with(ARCHIVE_URI_SCHEME)
+}
+
+/// Splits `archive://<archive>!/<member>` into the archive path and a clean member path,
+/// which is empty for the archive root. A bare `archive://<archive>` also opens the root.
+fn parse_archive_uri(uri: &str) -> Option<(&str, String)> {
+    let rest = uri.strip_prefix(ARCHIVE_URI_SCHEME)?;
+    let (archive, member) = rest.split_once(ARCHIVE_URI_SEPARATOR).unwrap_or((rest, ""));
+    if archive.is_empty() {
+        return None;
+    }
+    let mut parts = Vec::new();
+    for part in member.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => return None,
+            part => parts.push(part),
+        }
+    }
+    Some((archive, parts.join("/")))
+}
+
+fn invalid_uri(uri: &str) -> DecompressError {
+    DecompressError::new(
+        DecompressErrorCode::InvalidPath,
+        format!("Invalid archive path: {uri}"),
+    )
+}
+
+/// Resolves the archive named by `uri` to a regular file on disk.
+pub(crate) fn resolve_archive_uri(uri: &str) -> ApiResult<(PathBuf, String)> {
+    map_api_result(resolve_uri(uri))
+}
+
+fn resolve_uri(uri: &str) -> DecompressResult<(PathBuf, String)> {
+    let (archive, member) = parse_archive_uri(uri).ok_or_else(|| invalid_uri(uri))?;
+    let archive = sanitize_path_follow(archive, true).map_err(DecompressError::from)?;
+    if !archive.is_file() {
+        return Err(DecompressError::new(
+            DecompressErrorCode::NotFound,
+            format!("Archive not found: {}", archive.display()),
+        ));
+    }
+    Ok((archive, member))
+}
+
+/// `password` is only needed for a 7z archive whose names are encrypted; without it such an
+/// archive fails with `password_required`.
+#[tauri::command]
+pub async fn list_archive_entries(
+    path: String,
+    sort: Option<SortSpec>,
+    password: Option<String>,
+) -> ApiResult<DirListing> {
+    let task = tauri::async_runtime::spawn_blocking(move || {
+        list_archive_dir_sync(&path, sort, password.as_deref())
+    });
+    match task.await {
+        Ok(result) => result,
+        Err(error) => map_api_result(Err(DecompressError::new(
+            DecompressErrorCode::TaskFailed,
+            format!("Archive listing task failed: {error}"),
+        ))),
+    }
+}
+
+/// Lists the folder an `archive://` path points at, like `list_dir` does for disk folders.
+pub(crate) fn list_archive_dir_sync(
+    uri: &str,
+    sort: Option<SortSpec>,
+    password: Option<&str>,
+) -> ApiResult<DirListing> {
+    let mut listing = map_api_result(list_archive_dir(uri, password))?;
+    sort_entries(&mut listing.entries, sort);
+    Ok(listing)
+}
+
+fn list_archive_dir(uri: &str, password: Option<&str>) -> DecompressResult<DirListing> {
+    let (archive, dir) = resolve_uri(uri)?;
+    let budget = ExtractBudget::new(EXTRACT_TOTAL_BYTES_CAP, EXTRACT_TOTAL_ENTRIES_CAP);
+    let members = list_members(&archive, password, &budget, None)?;
+    let children = dir_children(&members, &dir).ok_or_else(|| {
+        DecompressError::new(
+            DecompressErrorCode::NotFound,
+            format!("Folder not found in archive: {dir}"),
+        )
+    })?;
+    Ok(DirListing {
+        current: archive_member_uri(&archive, &dir),
+        entries: children
+            .iter()
+            .map(|member| member_entry(&archive, member))
+            .collect(),
+    })
+}
+
+/// Direct children of `dir` (empty for the root). Folders that only exist as the prefix of
+/// deeper members are synthesized. Returns None when nothing in the archive lives below `dir`.
+fn dir_children(members: &[ArchiveMember], dir: &str) -> Option<Vec<ArchiveMember>> {
+    let mut found = dir.is_empty();
+    let mut children: BTreeMap<&str, ArchiveMember> = BTreeMap::new();
+    for member in members {
+        let rest = if dir.is_empty() {
+            Some(member.path.as_str())
+        } else {
+            member
+                .path
+                .strip_prefix(dir)
+                .and_then(|rest| rest.strip_prefix('/'))
+        };
+        let Some(rest) = rest else {
+            found |= member.is_dir && member.path == dir;
+            continue;
+        };
+        found = true;
+        match rest.split_once('/') {
+            Some((name, _)) => {
+                children.entry(name).or_insert_with(|| ArchiveMember {
+                    path: member.path[..member.path.len() - rest.len() + name.len()].to_string(),
+                    is_dir: true,
+                    size: 0,
+                    mtime: None,
+                });
+            }
+            None => {
+                // A recorded member beats a folder synthesized from its descendants.
+                children.insert(rest, member.clone());
+            }
+        }
+    }
+    found.then(|| children.into_values().collect())
+}
+
+/// Writes `targets` of `archive` into `dest_dir`, within the extraction entry and byte caps
+/// and the free-space reserve. Anything written is removed again when the copy fails.
+/// `password` opens encrypted zip and 7z archives.
+pub(crate) fn copy_archive_members(
+    archive: &Path,
+    targets: &[MemberTarget],
+    dest_dir: &Path,
+    preserve: &MetadataPreserver,
+    cancel: Option<&AtomicBool>,
+    password: Option<&str>,
+) -> ApiResult<()> {
+    map_api_result(
+        extract_members(archive, targets, dest_dir, preserve, None, cancel, password).map(|_| ()),
+    )
+}
+
+/// `copy_archive_members` with progress, for `extract_archive_members`.
+/// Returns what was skipped on the way.
+pub(super) fn extract_members(
+    archive: &Path,
+    targets: &[MemberTarget],
+    dest_dir: &Path,
+    preserve: &MetadataPreserver,
+    progress: Option<&ProgressEmitter>,
+    cancel: Option<&AtomicBool>,
+    password: Option<&str>,
+) -> DecompressResult<SkipStats> {
+    let (budget, _) = extract_budget_for(dest_dir)?;
+    let layout = EntryLayout::Members(targets);
+    let stats = SkipStats::default();
+    let mut created = CreatedPaths::default();
+    // Callers pick free target names, so the default policy only meets duplicate entries.
+    let mut placement = Placement::new(dest_dir, &ExtractOptions::default());
+    match detect_archive(archive)? {
+        ArchiveKind::Zip => extract_zip(
+            archive,
+            dest_dir,
+            layout,
+            &mut placement,
+            &stats,
+            preserve,
+            progress,
+            &mut created,
+            cancel,
+            &budget,
+            password,
+        )?,
+        kind @ (ArchiveKind::Tar
+        | ArchiveKind::TarGz
+        | ArchiveKind::TarBz2
+        | ArchiveKind::TarXz
+        | ArchiveKind::TarZstd) => {
+            if let Some(reader) = open_tar_reader(archive, kind, "copy")? {
+                extract_tar(
+                    reader,
+                    dest_dir,
+                    layout,
+                    &mut placement,
+                    &stats,
+                    preserve,
+                    progress,
+                    &mut created,
+                    cancel,
+                    &budget,
+                )?;
+            }
+        }
+        ArchiveKind::SevenZ => extract_7z(
+            archive,
+            dest_dir,
+            layout,
+            &mut placement,
+            &stats,
+            preserve,
+            progress,
+            &mut created,
+            cancel,
+            &budget,
+            password,
+        )?,
+        ArchiveKind::Rar => extract_rar(
+            parse_rar_entries(archive)?,
+            dest_dir,
+            layout,
+            &mut placement,
+            &stats,
+            preserve,
+            progress,
+            &mut created,
+            cancel,
+            &budget,
+        )?,
+        ArchiveKind::Gz | ArchiveKind::Bz2 | ArchiveKind::Xz | ArchiveKind::Zstd => {
+            return Err(DecompressError::new(
+                DecompressErrorCode::UnsupportedArchive,
+                "Single-file compressed streams have no members to copy",
+            ));
+        }
+    }
+    for target in targets {
+        if fs::symlink_metadata(dest_dir.join(&target.target_name)).is_err() {
+            // Dropping `created` removes whatever the other targets already wrote.
+            return Err(DecompressError::new(
+                DecompressErrorCode::NotFound,
+                format!("Archive member not found: {}", target.member.display()),
+            ));
+        }
+    }
+    created.disarm();
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{copy_archive_members, dir_children, list_archive_dir_sync, parse_archive_uri};
+    use crate::commands::decompress::members::ArchiveMember;
+    use crate::commands::decompress::util::MemberTarget;
+    use crate::fs_utils::{unique_temp_dir, MetadataPreserver};
+    use std::{
+        fs::{self, File},
+        io::Write,
+        path::PathBuf,
+    };
+    use zip::{write::SimpleFileOptions, AesMode, ZipWriter};
+
+    fn member(path: &str, is_dir: bool) -> ArchiveMember {
+        ArchiveMember {
+            path: path.to_string(),
+            is_dir,
+            size: 0,
+            mtime: None,
+        }
+    }
+
+    #[test]
+    fn parses_archive_uris() {
+        assert_eq!(
+            parse_archive_uri("archive:///tmp/a.zip!/docs//x/"),
+            Some(("/tmp/a.zip", "docs/x".to_string()))
+        );
+        assert_eq!(
+            parse_archive_uri("archive:///tmp/a.zip"),
+            Some(("/tmp/a.zip", String::new()))
+        );
+        assert_eq!(parse_archive_uri("archive:///tmp/a.zip!/../etc"), None);
+        assert_eq!(parse_archive_uri("/tmp/a.zip"), None);
+    }
+
+    #[test]
+    fn dir_children_synthesizes_missing_folders() {
+        let members = [
+            member("docs/a.txt", false),
+            member("docs/sub/b.txt", false),
+            member("readme.md", false),
+            member("empty", true),
+        ];
+        let names = |dir: &str| {
+            dir_children(&members, dir).map(|children| {
+                children
+                    .into_iter()
+                    .map(|m| (m.path, m.is_dir))
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(
+            names(""),
+            Some(vec![
+                ("docs".to_string(), true),
+                ("empty".to_string(), true),
+                ("readme.md".to_string(), false),
+            ])
+        );
+        assert_eq!(
+            names("docs"),
+            Some(vec![
+                ("docs/a.txt".to_string(), false),
+                ("docs/sub".to_string(), true),
+            ])
+        );
+        assert_eq!(names("empty"), Some(Vec::new()));
+        assert_eq!(names("readme.md"), None);
+        assert_eq!(names("missing"), None);
+    }
+
+    #[test]
+    fn lists_folders_and_copies_members_out() {
+        let root = unique_temp_dir("copy");
+        let archive = root.join("bundle.zip");
+        let mut zip = ZipWriter::new(File::create(&archive).expect("create zip"));
+        for (name, body) in [("docs/a.txt", "alpha"), ("docs/sub/b.txt", "beta")] {
+            zip.start_file(name, SimpleFileOptions::default())
+                .expect("start file");
+            zip.write_all(body.as_bytes()).expect("write member");
+        }
+        zip.finish().expect("finish zip");
+
+        let uri = format!("archive://{}!/docs", archive.display());
+        let listing = list_archive_dir_sync(&uri, None, None).expect("list docs");
+        assert_eq!(listing.current, uri);
+        let names: Vec<_> = listing.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["a.txt", "sub"]);
+        assert!(listing.entries.iter().all(|e| e.read_only));
+
+        let dest = root.join("out");
+        fs::create_dir_all(&dest).expect("create dest");
+        let targets = [
+            MemberTarget {
+                member: PathBuf::from("docs/a.txt"),
+                target_name: "copy.txt".into(),
+            },
+            MemberTarget {
+                member: PathBuf::from("docs/sub"),
+                target_name: "sub".into(),
+            },
+        ];
+        copy_archive_members(
+            &archive,
+            &targets,
+            &dest,
+            &MetadataPreserver::disabled(),
+            None,
+            None,
+        )
+        .expect("copy out");
+        assert_eq!(fs::read_to_string(dest.join("copy.txt")).unwrap(), "alpha");
+        assert_eq!(fs::read_to_string(dest.join("sub/b.txt")).unwrap(), "beta");
+
+        let missing = [MemberTarget {
+            member: PathBuf::from("docs/nope.txt"),
+            target_name: "nope.txt".into(),
+        }];
+        assert!(copy_archive_members(
+            &archive,
+            &missing,
+            &dest,
+            &MetadataPreserver::disabled(),
+            None,
+            None,
+        )
+        .is_err());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn copies_members_of_an_encrypted_zip_with_its_password() {
+        let root = unique_temp_dir("copy-encrypted");
+        let archive = root.join("secret.zip");
+        let mut zip = ZipWriter::new(File::create(&archive).expect("create zip"));
+        zip.start_file(
+            "notes.txt",
+            SimpleFileOptions::default().with_aes_encryption(AesMode::Aes256, "hunter2"),
+        )
+        .expect("start file");
+        zip.write_all(b"top secret").expect("write member");
+        zip.finish().expect("finish zip");
+
+        let dest = root.join("out");
+        fs::create_dir_all(&dest).expect("create dest");
+        let targets = [MemberTarget {
+            member: PathBuf::from("notes.txt"),
+            target_name: "notes.txt".into(),
+        }];
+        let copy = |password| {
+            copy_archive_members(
+                &archive,
+                &targets,
+                &dest,
+                &MetadataPreserver::disabled(),
+                None,
+                password,
+            )
+        };
+        let missing = copy(None).expect_err("password required");
+        assert_eq!(missing.code, "password_required");
+        assert!(!dest.join("notes.txt").exists());
+        copy(Some("hunter2")).expect("copy with password");
+        assert_eq!(fs::read(dest.join("notes.txt")).unwrap(), b"top secret");
+        let _ = fs::remove_dir_all(&root);
+    }
+}
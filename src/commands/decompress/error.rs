@@ -21,6 +21,10 @@ pub(super) enum DecompressErrorCode {
     DiskSpaceExceeded,
     ArchiveTooLarge,
     UnsupportedArchive,
+    MissingVolume,
+    PasswordRequired,
+    InvalidPassword,
+    EncryptedRarUnsupported,
     Cancelled,
     ExtractFailed,
     TaskFailed,
@@ -41,6 +45,10 @@ impl ErrorCode for DecompressErrorCode {
             Self::DiskSpaceExceeded => "disk_space_exceeded",
             Self::ArchiveTooLarge => "archive_too_large",
             Self::UnsupportedArchive => "unsupported_archive",
+            Self::MissingVolume => "missing_volume",
+            Self::PasswordRequired => "password_required",
+            Self::InvalidPassword => "invalid_password",
+            Self::EncryptedRarUnsupported => "encrypted_rar_unsupported",
             Self::Cancelled => "cancelled",
             Self::ExtractFailed => "extract_failed",
             Self::TaskFailed => "task_failed",
@@ -68,6 +76,31 @@ impl DecompressError {
         let code = classify_external_code(&message);
         Self::new(code, message)
     }
+
+    /// The archive holds encrypted entries and no password was given. The UI asks for one and
+    /// retries with `extract_archive_with_password`.
+    pub(super) fn password_required() -> Self {
+        Self::new(
+            DecompressErrorCode::PasswordRequired,
+            "Archive is encrypted, a password is required",
+        )
+    }
+
+    pub(super) fn invalid_password() -> Self {
+        Self::new(
+            DecompressErrorCode::InvalidPassword,
+            "Wrong password for encrypted archive",
+        )
+    }
+
+    /// The rar archive is encrypted, and rar-stream cannot decrypt it with or without a
+    /// password. Unlike `password_required`, asking for a password does not help here.
+    pub(super) fn encrypted_rar_unsupported() -> Self {
+        Self::new(
+            DecompressErrorCode::EncryptedRarUnsupported,
+            "Encrypted RAR archives cannot be decrypted, extract it with another tool",
+        )
+    }
 }
 
 impl fmt::Display for DecompressError {
@@ -163,6 +196,18 @@ const DECOMPRESS_CLASSIFICATION_RULES: &[(DecompressErrorCode, &[&str])] = &[
         DecompressErrorCode::TaskFailed,
         &["extraction task failed", "batch extraction task failed"],
     ),
+    (
+        DecompressErrorCode::EncryptedRarUnsupported,
+        &["encrypted rar archives cannot be decrypted"],
+    ),
+    (
+        DecompressErrorCode::PasswordRequired,
+        &["password is required", "password required"],
+    ),
+    (
+        DecompressErrorCode::InvalidPassword,
+        &["wrong password", "invalid password"],
+    ),
     (
         DecompressErrorCode::PathNotAbsolute,
         COMMON_PATH_NOT_ABSOLUTE_PATTERNS,
@@ -234,4 +279,16 @@ mod tests {
         let decompress: DecompressError = fs_error.into();
         assert_eq!(decompress.code, DecompressErrorCode::PermissionDenied);
     }
+
+    #[test]
+    fn password_errors_survive_reclassification() {
+        for error in [
+            DecompressError::password_required(),
+            DecompressError::invalid_password(),
+        ] {
+            let wrapped =
+                DecompressError::from_external_message(format!("Failed to extract 7z: {error}"));
+            assert_eq!(wrapped.code, error.code);
+        }
+    }
 }
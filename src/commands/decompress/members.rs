@@ -0,0 +1,422 @@
+//! Read-only member listings, so archives can be looked into without extracting them.
+//!
+//! Members are addressed as `archive://<archive path>!/<member path>`. Every listed member
+//! counts against an `ExtractBudget`, the same guardrail extraction uses against archive
+//! bombs.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+};
+
+use chrono::{DateTime, Local, NaiveDate};
+use tar::Archive;
+
+use super::error::{map_api_result, DecompressError, DecompressErrorCode, DecompressResult};
+use super::rar_format::parse_rar_entries;
+use super::seven_z_format::open_7z;
+use super::tar_format::open_tar_reader;
+use super::util::{
+    check_cancel, clean_relative_path, map_copy_err, ExtractBudget, EXTRACT_TOTAL_BYTES_CAP,
+    EXTRACT_TOTAL_ENTRIES_CAP,
+};
+use super::volumes::VolumeName;
+use super::zip_format::open_zip;
+use super::{detect_archive, ArchiveKind};
+use crate::entry::{EntryCapabilities, FsEntry};
+use crate::errors::api_error::ApiResult;
+use crate::icons::icon_id_for_virtual_entry;
+
+pub(crate) const ARCHIVE_URI_SCHEME: &str = "archive://";
+/// Separates the archive's own path from the member path inside an `archive://` URI.
+pub(crate) const ARCHIVE_URI_SEPARATOR: &str = "!/";
+
+const BROWSABLE_SUFFIXES: &[&str] = &[
+    ".zip", ".tar", ".tar.gz", ".tgz", ".tar.bz2", ".tbz2", ".tar.xz", ".txz", ".tar.zst", ".tzst",
+    ".7z", ".rar",
+];
+
+#[derive(Clone)]
+pub(crate) struct ArchiveMember {
+    /// Clean `/`-separated path relative to the archive root.
+    pub(crate) path: String,
+    pub(crate) is_dir: bool,
+    pub(crate) size: u64,
+    /// Seconds since the Unix epoch, for formats that record it.
+    pub(crate) mtime: Option<i64>,
+}
+
+/// Entry and byte allowance shared by every archive a caller lists. Skipping over tar member
+/// data still decompresses it, so those bytes count too.
+#[derive(Clone)]
+pub(crate) struct ArchiveScanBudget(ExtractBudget);
+
+impl ArchiveScanBudget {
+    pub(crate) fn new() -> Self {
+        Self::with_limits(EXTRACT_TOTAL_BYTES_CAP, EXTRACT_TOTAL_ENTRIES_CAP)
+    }
+
+    fn with_limits(max_total_bytes: u64, max_total_entries: u64) -> Self {
+        Self(ExtractBudget::new(max_total_bytes, max_total_entries))
+    }
+}
+
+/// Cheap name check used before opening a file to sniff its format.
+pub(crate) fn has_browsable_archive_name(name: &str) -> bool {
+    // Of a split set, only the first volume opens.
+    if let Some(volume) = VolumeName::parse(name) {
+        return volume.index == 1;
+    }
+    let lower = name.to_lowercase();
+    BROWSABLE_SUFFIXES
+        .iter()
+        .any(|suffix| lower.ends_with(suffix) && lower.len() > suffix.len())
+}
+
+pub(crate) fn archive_member_uri(archive: &Path, member: &str) -> String {
+    format!(
+        "{ARCHIVE_URI_SCHEME}{}{ARCHIVE_URI_SEPARATOR}{member}",
+        archive.display()
+    )
+}
+
+/// Lists the members of a zip, tar (plain, gz, bz2, xz or zstd), 7z or rar archive.
+/// `password` is only needed for a 7z archive whose names are encrypted.
+pub(crate) fn list_archive_members(
+    path: &Path,
+    password: Option<&str>,
+    budget: &ArchiveScanBudget,
+    cancel: Option<&AtomicBool>,
+) -> ApiResult<Vec<ArchiveMember>> {
+    map_api_result(list_members(path, password, &budget.0, cancel))
+}
+
+/// Builds the read-only row shown for `member` of `archive`.
+pub(crate) fn member_entry(archive: &Path, member: &ArchiveMember) -> FsEntry {
+    let name = member
+        .path
+        .rsplit('/')
+        .next()
+        .unwrap_or(&member.path)
+        .to_string();
+    let ext = if member.is_dir {
+        None
+    } else {
+        Path::new(&name)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|s| s.to_string())
+    };
+    FsEntry {
+        path: archive_member_uri(archive, &member.path),
+        kind: if member.is_dir { "dir" } else { "file" }.to_string(),
+        ext,
+        size: (!member.is_dir).then_some(member.size),
+        items: None,
+        modified: member
+            .mtime
+            .and_then(|secs| DateTime::from_timestamp(secs, 0))
+            .map(|t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()),
+        original_path: None,
+        trash_id: None,
+        icon_id: icon_id_for_virtual_entry(&name, member.is_dir),
+        starred: false,
+        hidden: name.starts_with('.'),
+        network: false,
+        read_only: true,
+        read_denied: false,
+        capabilities: Some(EntryCapabilities {
+            can_list: member.is_dir,
+            can_mkdir: false,
+            can_delete: false,
+            can_rename: false,
+            can_move: false,
+            can_copy: true,
+            can_trash: false,
+            can_undo: false,
+            can_permissions: false,
+        }),
+        name,
+    }
+}
+
+pub(super) fn list_members(
+    path: &Path,
+    password: Option<&str>,
+    budget: &ExtractBudget,
+    cancel: Option<&AtomicBool>,
+) -> DecompressResult<Vec<ArchiveMember>> {
+    let mut members = Vec::new();
+    match detect_archive(path)? {
+        ArchiveKind::Zip => list_zip_members(path, budget, cancel, &mut members)?,
+        kind @ (ArchiveKind::Tar
+        | ArchiveKind::TarGz
+        | ArchiveKind::TarBz2
+        | ArchiveKind::TarXz
+        | ArchiveKind::TarZstd) => list_tar_members(path, kind, budget, cancel, &mut members)?,
+        ArchiveKind::SevenZ => list_7z_members(path, password, budget, cancel, &mut members)?,
+        ArchiveKind::Rar => list_rar_members(path, budget, cancel, &mut members)?,
+        ArchiveKind::Gz | ArchiveKind::Bz2 | ArchiveKind::Xz | ArchiveKind::Zstd => {
+            return Err(DecompressError::new(
+                DecompressErrorCode::UnsupportedArchive,
+                "Single-file compressed streams have no members to list",
+            ));
+        }
+    }
+    Ok(members)
+}
+
+fn reserve_member(budget: &ExtractBudget, cancel: Option<&AtomicBool>) -> DecompressResult<()> {
+    check_cancel(cancel).map_err(|e| map_copy_err("Archive listing cancelled", e))?;
+    budget
+        .reserve_entry(1)
+        .map_err(|e| map_copy_err("Archive entry cap exceeded", e))?;
+    Ok(())
+}
+
+/// Normalizes a raw member name, dropping absolute and traversing paths like extraction does.
+fn member_path(raw: &Path) -> Option<String> {
+    let clean = clean_relative_path(raw).ok()?;
+    let parts: Vec<String> = clean
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+fn zip_mtime(value: zip::DateTime) -> Option<i64> {
+    NaiveDate::from_ymd_opt(
+        value.year().into(),
+        value.month().into(),
+        value.day().into(),
+    )?
+    .and_hms_opt(
+        value.hour().into(),
+        value.minute().into(),
+        value.second().into(),
+    )?
+    .and_local_timezone(Local)
+    .earliest()
+    .map(|t| t.timestamp())
+}
+
+fn list_zip_members(
+    path: &Path,
+    budget: &ExtractBudget,
+    cancel: Option<&AtomicBool>,
+    out: &mut Vec<ArchiveMember>,
+) -> DecompressResult<()> {
+    let mut archive = open_zip(path)?;
+    for i in 0..archive.len() {
+        reserve_member(budget, cancel)?;
+        // Raw access reads headers only, so encrypted members list without a password.
+        let entry = archive
+            .by_index_raw(i)
+            .map_err(|e| format!("Failed to read zip entry {i}: {e}"))?;
+        let is_symlink = entry
+            .unix_mode()
+            .is_some_and(|mode| (mode & 0o170000) == 0o120000);
+        if is_symlink {
+            continue;
+        }
+        let Some(member) = entry.enclosed_name().and_then(|p| member_path(&p)) else {
+            continue;
+        };
+        let is_dir = entry.is_dir() || entry.name().ends_with('/');
+        out.push(ArchiveMember {
+            path: member,
+            is_dir,
+            size: if is_dir { 0 } else { entry.size() },
+            mtime: entry.last_modified().and_then(zip_mtime),
+        });
+    }
+    Ok(())
+}
+
+fn list_tar_members(
+    path: &Path,
+    kind: ArchiveKind,
+    budget: &ExtractBudget,
+    cancel: Option<&AtomicBool>,
+    out: &mut Vec<ArchiveMember>,
+) -> DecompressResult<()> {
+    let Some(reader) = open_tar_reader(path, kind, "listing")? else {
+        return Ok(());
+    };
+    let mut archive = Archive::new(reader);
+    for entry_result in archive.entries().map_err(|e| {
+        DecompressError::from_external_message(format!("Failed to iterate tar: {e}"))
+    })? {
+        reserve_member(budget, cancel)?;
+        let entry = entry_result.map_err(|e| {
+            DecompressError::from_external_message(format!("Failed to read tar entry: {e}"))
+        })?;
+        let header = entry.header();
+        let entry_type = header.entry_type();
+        let size = header.size().unwrap_or(0);
+        budget
+            .reserve_bytes(size)
+            .map_err(|e| map_copy_err("Archive size cap exceeded", e))?;
+        if !(entry_type.is_dir() || entry_type.is_file()) {
+            continue;
+        }
+        let raw_path: PathBuf = match entry.path() {
+            Ok(p) => p.into_owned(),
+            Err(_) => continue,
+        };
+        let Some(member) = member_path(&raw_path) else {
+            continue;
+        };
+        out.push(ArchiveMember {
+            path: member,
+            is_dir: entry_type.is_dir(),
+            size: if entry_type.is_dir() { 0 } else { size },
+            mtime: header
+                .mtime()
+                .ok()
+                .and_then(|secs| i64::try_from(secs).ok()),
+        });
+    }
+    Ok(())
+}
+
+fn list_7z_members(
+    path: &Path,
+    password: Option<&str>,
+    budget: &ExtractBudget,
+    cancel: Option<&AtomicBool>,
+    out: &mut Vec<ArchiveMember>,
+) -> DecompressResult<()> {
+    let archive = open_7z(path, password)?;
+    for entry in archive.files {
+        reserve_member(budget, cancel)?;
+        if entry.is_anti_item {
+            continue;
+        }
+        let Some(member) = member_path(Path::new(&entry.name)) else {
+            continue;
+        };
+        out.push(ArchiveMember {
+            path: member,
+            is_dir: entry.is_directory,
+            size: if entry.is_directory { 0 } else { entry.size },
+            mtime: None,
+        });
+    }
+    Ok(())
+}
+
+fn list_rar_members(
+    path: &Path,
+    budget: &ExtractBudget,
+    cancel: Option<&AtomicBool>,
+    out: &mut Vec<ArchiveMember>,
+) -> DecompressResult<()> {
+    for entry in parse_rar_entries(path)? {
+        reserve_member(budget, cancel)?;
+        let normalized = entry.name.replace('\\', "/");
+        let is_dir = normalized.ends_with('/');
+        let Some(member) = member_path(Path::new(&normalized)) else {
+            continue;
+        };
+        out.push(ArchiveMember {
+            path: member,
+            is_dir,
+            size: if is_dir { 0 } else { entry.length },
+            mtime: None,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        archive_member_uri, has_browsable_archive_name, list_archive_members, ArchiveScanBudget,
+    };
+    use crate::fs_utils::unique_temp_dir;
+    use std::{
+        fs::{self, File},
+        io::Write,
+        path::PathBuf,
+    };
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    fn write_zip(path: &PathBuf, names: &[&str]) {
+        let mut zip = ZipWriter::new(File::create(path).expect("create zip"));
+        for name in names {
+            if let Some(dir) = name.strip_suffix('/') {
+                zip.add_directory(dir, SimpleFileOptions::default())
+                    .expect("add dir");
+            } else {
+                zip.start_file(*name, SimpleFileOptions::default())
+                    .expect("start file");
+                zip.write_all(b"payload").expect("write");
+            }
+        }
+        zip.finish().expect("finish zip");
+    }
+
+    #[test]
+    fn lists_zip_and_tar_members() {
+        let root = unique_temp_dir("list");
+        let zip_path = root.join("bundle.zip");
+        write_zip(&zip_path, &["docs/", "docs/readme.md", "../escape.txt"]);
+        let members = list_archive_members(&zip_path, None, &ArchiveScanBudget::new(), None)
+            .expect("list zip");
+        let listed: Vec<(&str, bool)> = members
+            .iter()
+            .map(|m| (m.path.as_str(), m.is_dir))
+            .collect();
+        assert_eq!(listed, [("docs", true), ("docs/readme.md", false)]);
+        assert_eq!(members[1].size, 7);
+
+        let tar_path = root.join("bundle.tar");
+        let mut builder = tar::Builder::new(File::create(&tar_path).expect("create tar"));
+        let mut header = tar::Header::new_gnu();
+        header.set_size(3);
+        header.set_mode(0o644);
+        header.set_mtime(1_700_000_000);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "src/main.rs", &b"abc"[..])
+            .expect("append");
+        builder.finish().expect("finish tar");
+        drop(builder);
+        let members = list_archive_members(&tar_path, None, &ArchiveScanBudget::new(), None)
+            .expect("list tar");
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].path, "src/main.rs");
+        assert_eq!(members[0].mtime, Some(1_700_000_000));
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn shared_budget_stops_listing_past_entry_cap() {
+        let root = unique_temp_dir("budget");
+        let zip_path = root.join("many.zip");
+        write_zip(&zip_path, &["a.txt", "b.txt", "c.txt"]);
+        let budget = ArchiveScanBudget::with_limits(u64::MAX, 4);
+        assert!(list_archive_members(&zip_path, None, &budget, None).is_ok());
+        let err = list_archive_members(&zip_path, None, &budget, None)
+            .err()
+            .expect("second listing exceeds the shared cap");
+        assert!(err.message.contains("entry cap"));
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn builds_archive_uris_and_recognizes_names() {
+        assert_eq!(
+            archive_member_uri(std::path::Path::new("/tmp/a.zip"), "docs/readme.md"),
+            "archive:///tmp/a.zip!/docs/readme.md"
+        );
+        assert!(has_browsable_archive_name("Backup.TAR.GZ"));
+        assert!(has_browsable_archive_name("photos.7z"));
+        assert!(!has_browsable_archive_name("notes.gz"));
+        assert!(!has_browsable_archive_name(".zip"));
+        assert!(has_browsable_archive_name("photos.zip.001"));
+        assert!(!has_browsable_archive_name("backup.part2.rar"));
+    }
+}
@@ -1,14 +1,19 @@
+mod browse;
 mod error;
+mod members;
 mod rar_format;
+mod selective;
 mod seven_z_format;
 mod tar_format;
 mod util;
+mod volumes;
 mod zip_format;
 
 use std::{
+    collections::HashSet,
     fs,
     fs::File,
-    io::{BufReader, BufWriter, Read, Seek, SeekFrom},
+    io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write},
     path::{Path, PathBuf},
     sync::{atomic::AtomicBool, Arc, Mutex},
 };
@@ -21,8 +26,11 @@ use xz2::read::XzDecoder;
 use zstd::stream::read::Decoder as ZstdDecoder;
 
 use crate::errors::api_error::ApiResult;
-use crate::fs_utils::{check_no_symlink_components, sanitize_path_follow, sanitize_path_nofollow};
-use crate::tasks::{CancelGuard, CancelState};
+use crate::fs_utils::{
+    check_no_symlink_components, sanitize_path_follow, sanitize_path_nofollow, ConflictPolicy,
+    EntryFacts, MetadataPreserver, PreserveAttr, PreserveOptions, UnpreservedAttr,
+};
+use crate::tasks::{run_job, CancelGuard, CancelState, JobKind, JobSpec};
 use crate::undo::{temp_backup_path, Action, UndoState};
 use error::{
     is_cancelled_error, map_api_result, DecompressError, DecompressErrorCode, DecompressResult,
@@ -36,12 +44,25 @@ use tar_format::{extract_tar_with_reader, single_root_in_tar, tar_uncompressed_t
 use util::{
     available_disk_bytes, copy_with_progress, create_unique_dir_nofollow,
     effective_extract_bytes_cap, ensure_dir_nofollow, map_copy_err, map_io, open_buffered_file,
-    open_unique_file, strip_known_suffixes, CreatedPaths, DiskSpaceGuard, ExtractBudget,
+    strip_known_suffixes, CreatedPaths, DiskSpaceGuard, EntryLayout, ExtractBudget, Placement,
     ProgressEmitter, SkipStats, CHUNK, EXTRACT_DISK_CHECK_INTERVAL_BYTES,
     EXTRACT_MIN_FREE_DISK_RESERVE, EXTRACT_TOTAL_BYTES_CAP, EXTRACT_TOTAL_ENTRIES_CAP,
 };
+use volumes::{check_first_volume, split_volume_name};
 use zip_format::{extract_zip, single_root_in_zip, zip_uncompressed_total};
 
+pub use browse::list_archive_entries;
+pub(crate) use browse::{
+    copy_archive_members, is_archive_uri, list_archive_dir_sync, resolve_archive_uri,
+};
+pub(crate) use members::{
+    archive_member_uri, has_browsable_archive_name, list_archive_members, member_entry,
+    ArchiveMember, ArchiveScanBudget,
+};
+pub use selective::extract_archive_members;
+pub(crate) use util::MemberTarget;
+pub use util::{EntryOutcome, ExtractDestination, ExtractOptions, ExtractedEntry, SymlinkPolicy};
+
 #[derive(Debug, Clone, Copy)]
 enum ArchiveKind {
     Zip,
@@ -58,11 +79,22 @@ enum ArchiveKind {
     Zstd,
 }
 
+impl ArchiveKind {
+    /// Compressed single files, which decompress to one file rather than a folder.
+    fn is_single_stream(self) -> bool {
+        matches!(self, Self::Gz | Self::Bz2 | Self::Xz | Self::Zstd)
+    }
+}
+
 #[derive(Serialize)]
 pub struct ExtractResult {
     pub destination: String,
     pub skipped_symlinks: usize,
     pub skipped_entries: usize,
+    /// Requested attributes that could not be restored on the extracted entries.
+    pub unpreserved: Vec<UnpreservedAttr>,
+    /// What happened to each file and symlink entry.
+    pub entries: Vec<ExtractedEntry>,
 }
 
 #[derive(Serialize)]
@@ -105,25 +137,60 @@ pub async fn extract_archive(
     undo: tauri::State<'_, UndoState>,
     path: String,
     progress_event: Option<String>,
+    preserve: Option<Vec<PreserveAttr>>,
+    options: Option<ExtractOptions>,
 ) -> ApiResult<ExtractResult> {
-    map_api_result(
-        extract_archive_impl(
-            app,
-            cancel.inner().clone(),
-            undo.inner().clone(),
-            path,
-            progress_event,
-        )
-        .await,
-    )
+    let job = extract_job(std::slice::from_ref(&path), &progress_event);
+    let work = extract_archive_impl(
+        app.clone(),
+        cancel.inner().clone(),
+        undo.inner().clone(),
+        path,
+        None,
+        progress_event,
+        PreserveOptions::from_request(preserve, PreserveOptions::NONE),
+        options.unwrap_or_default(),
+    );
+    map_api_result(run_job(&app, job, extraction_cancelled, work).await)
+}
+
+/// Retries an extraction that failed with `password_required` or `invalid_password`. The
+/// password only lives for this extraction: it is not logged, stored, or put on the job.
+#[tauri::command]
+pub async fn extract_archive_with_password(
+    app: tauri::AppHandle,
+    cancel: tauri::State<'_, CancelState>,
+    undo: tauri::State<'_, UndoState>,
+    path: String,
+    password: String,
+    progress_event: Option<String>,
+    preserve: Option<Vec<PreserveAttr>>,
+    options: Option<ExtractOptions>,
+) -> ApiResult<ExtractResult> {
+    let job = extract_job(std::slice::from_ref(&path), &progress_event);
+    let work = extract_archive_impl(
+        app.clone(),
+        cancel.inner().clone(),
+        undo.inner().clone(),
+        path,
+        Some(password),
+        progress_event,
+        PreserveOptions::from_request(preserve, PreserveOptions::NONE),
+        options.unwrap_or_default(),
+    );
+    map_api_result(run_job(&app, job, extraction_cancelled, work).await)
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn extract_archive_impl(
     app: tauri::AppHandle,
     cancel_state: CancelState,
     undo_state: UndoState,
     path: String,
+    password: Option<String>,
     progress_event: Option<String>,
+    preserve: PreserveOptions,
+    options: ExtractOptions,
 ) -> DecompressResult<ExtractResult> {
     let task = tauri::async_runtime::spawn_blocking(move || -> DecompressResult<ExtractResult> {
         do_extract(
@@ -131,7 +198,10 @@ async fn extract_archive_impl(
             cancel_state,
             undo_state,
             path,
+            password.as_deref(),
             progress_event,
+            preserve,
+            options,
             None,
             None,
             None,
@@ -153,25 +223,49 @@ pub async fn extract_archives(
     undo: tauri::State<'_, UndoState>,
     paths: Vec<String>,
     progress_event: Option<String>,
+    preserve: Option<Vec<PreserveAttr>>,
+    options: Option<ExtractOptions>,
 ) -> ApiResult<Vec<ExtractBatchItem>> {
-    map_api_result(
-        extract_archives_impl(
-            app,
-            cancel.inner().clone(),
-            undo.inner().clone(),
-            paths,
-            progress_event,
-        )
-        .await,
+    let job = extract_job(&paths, &progress_event);
+    let work = extract_archives_impl(
+        app.clone(),
+        cancel.inner().clone(),
+        undo.inner().clone(),
+        paths,
+        progress_event,
+        PreserveOptions::from_request(preserve, PreserveOptions::NONE),
+        options.unwrap_or_default(),
+    );
+    map_api_result(run_job(&app, job, extraction_cancelled, work).await)
+}
+
+/// Archives are extracted next to themselves, so the first one's folder is the target.
+fn extract_job(paths: &[String], progress_event: &Option<String>) -> JobSpec {
+    let target = paths
+        .first()
+        .and_then(|path| Path::new(path).parent())
+        .map(|parent| parent.to_string_lossy().to_string())
+        .unwrap_or_default();
+    JobSpec::new(
+        JobKind::Extract,
+        target,
+        paths.to_vec(),
+        progress_event.clone(),
     )
 }
 
+fn extraction_cancelled() -> DecompressError {
+    DecompressError::new(DecompressErrorCode::Cancelled, "Extraction cancelled")
+}
+
 async fn extract_archives_impl(
     app: tauri::AppHandle,
     cancel_state: CancelState,
     undo_state: UndoState,
     paths: Vec<String>,
     progress_event: Option<String>,
+    preserve: PreserveOptions,
+    options: ExtractOptions,
 ) -> DecompressResult<Vec<ExtractBatchItem>> {
     if paths.is_empty() {
         return Ok(Vec::new());
@@ -214,7 +308,10 @@ async fn extract_archives_impl(
                     cancel_state.clone(),
                     undo_state.clone(),
                     path,
+                    None,
                     progress_event.clone(), // only used for cancel registration in single mode
+                    preserve,
+                    options,
                     batch_token.clone(),
                     shared_progress.clone(),
                     Some(batch_actions.clone()),
@@ -277,30 +374,15 @@ fn do_extract(
     cancel_state: CancelState,
     undo: UndoState,
     path: String,
+    password: Option<&str>,
     progress_event: Option<String>,
+    preserve: PreserveOptions,
+    options: ExtractOptions,
     shared_cancel: Option<Arc<AtomicBool>>,
     shared_progress: Option<ProgressEmitter>,
     batch_actions: Option<Arc<Mutex<Vec<Action>>>>,
 ) -> DecompressResult<ExtractResult> {
-    let nofollow = sanitize_path_nofollow(&path, true).map_err(DecompressError::from)?;
-    let meta = fs::symlink_metadata(&nofollow).map_err(|e| {
-        DecompressError::from_external_message(format!("Failed to read archive metadata: {e}"))
-    })?;
-    if meta.file_type().is_symlink() {
-        return Err(DecompressError::from_external_message(
-            "Symlink archives are not supported",
-        ));
-    }
-
-    let archive_path = sanitize_path_follow(&path, true).map_err(DecompressError::from)?;
-    check_no_symlink_components(&archive_path).map_err(DecompressError::from)?;
-
-    if !archive_path.is_file() {
-        return Err(DecompressError::from_external_message(
-            "Only files can be extracted",
-        ));
-    }
-
+    let (archive_path, meta) = resolve_archive_path(&path)?;
     let parent = archive_path.parent().ok_or_else(|| {
         DecompressError::from_external_message("Cannot extract archive at filesystem root")
     })?;
@@ -311,7 +393,9 @@ fn do_extract(
         ArchiveKind::Zip => zip_uncompressed_total(&archive_path).unwrap_or(meta.len()),
         ArchiveKind::Tar => tar_uncompressed_total(&archive_path).unwrap_or(meta.len()),
         ArchiveKind::TarGz => gzip_uncompressed_size(&archive_path).unwrap_or(meta.len()),
-        ArchiveKind::SevenZ => sevenz_uncompressed_total(&archive_path).unwrap_or(meta.len()),
+        ArchiveKind::SevenZ => {
+            sevenz_uncompressed_total(&archive_path, password).unwrap_or(meta.len())
+        }
         ArchiveKind::Rar => {
             let entries = parse_rar_entries(&archive_path)?;
             let total = rar_uncompressed_total_from_entries(&entries).unwrap_or(meta.len());
@@ -322,26 +406,7 @@ fn do_extract(
         _ => meta.len(),
     }
     .max(1);
-    let available_bytes = available_disk_bytes(parent)?;
-    let effective_bytes_cap = effective_extract_bytes_cap(
-        EXTRACT_TOTAL_BYTES_CAP,
-        available_bytes,
-        EXTRACT_MIN_FREE_DISK_RESERVE,
-    );
-    if effective_bytes_cap == 0 {
-        return Err(DecompressError::from_external_message(format!(
-            "Insufficient free disk space in {} (available: {} bytes, required reserve: {} bytes)",
-            parent.display(),
-            available_bytes,
-            EXTRACT_MIN_FREE_DISK_RESERVE
-        )));
-    }
-    let budget = ExtractBudget::new(effective_bytes_cap, EXTRACT_TOTAL_ENTRIES_CAP)
-        .with_disk_guard(DiskSpaceGuard::new(
-            parent.to_path_buf(),
-            EXTRACT_MIN_FREE_DISK_RESERVE,
-            EXTRACT_DISK_CHECK_INTERVAL_BYTES,
-        ));
+    let (budget, available_bytes) = extract_budget_for(parent)?;
     if total_hint > budget.max_total_bytes() {
         return Err(DecompressError::from_external_message(format!(
             "Archive exceeds extraction size cap ({} bytes > {} bytes, available disk: {} bytes)",
@@ -381,30 +446,38 @@ fn do_extract(
     let cancel_token = cancel_token_arc.as_deref();
 
     let stats = SkipStats::default();
+    let preserve = MetadataPreserver::new(preserve);
     let destination = match kind {
         ArchiveKind::Zip => {
-            let (dest_dir, strip) = choose_destination_dir(&archive_path, kind)?;
-            created.record_dir(dest_dir.clone());
+            let (dest_dir, strip) =
+                prepare_destination(&archive_path, kind, password, &options, &mut created)?;
+            let mut placement = Placement::new(&dest_dir, &options);
             extract_zip(
                 &archive_path,
                 &dest_dir,
-                strip.as_deref(),
+                EntryLayout::Tree(strip.as_deref()),
+                &mut placement,
                 &stats,
+                &preserve,
                 progress.as_ref(),
                 &mut created,
                 cancel_token,
                 &budget,
+                password,
             )?;
             dest_dir
         }
         ArchiveKind::Tar => {
-            let (dest_dir, strip) = choose_destination_dir(&archive_path, kind)?;
-            created.record_dir(dest_dir.clone());
+            let (dest_dir, strip) =
+                prepare_destination(&archive_path, kind, password, &options, &mut created)?;
+            let mut placement = Placement::new(&dest_dir, &options);
             extract_tar_with_reader(
                 &archive_path,
                 &dest_dir,
-                strip.as_deref(),
+                EntryLayout::Tree(strip.as_deref()),
+                &mut placement,
                 &stats,
+                &preserve,
                 progress.as_ref(),
                 &mut created,
                 cancel_token,
@@ -414,13 +487,16 @@ fn do_extract(
             dest_dir
         }
         ArchiveKind::TarGz => {
-            let (dest_dir, strip) = choose_destination_dir(&archive_path, kind)?;
-            created.record_dir(dest_dir.clone());
+            let (dest_dir, strip) =
+                prepare_destination(&archive_path, kind, password, &options, &mut created)?;
+            let mut placement = Placement::new(&dest_dir, &options);
             extract_tar_with_reader(
                 &archive_path,
                 &dest_dir,
-                strip.as_deref(),
+                EntryLayout::Tree(strip.as_deref()),
+                &mut placement,
                 &stats,
+                &preserve,
                 progress.as_ref(),
                 &mut created,
                 cancel_token,
@@ -430,13 +506,16 @@ fn do_extract(
             dest_dir
         }
         ArchiveKind::TarBz2 => {
-            let (dest_dir, strip) = choose_destination_dir(&archive_path, kind)?;
-            created.record_dir(dest_dir.clone());
+            let (dest_dir, strip) =
+                prepare_destination(&archive_path, kind, password, &options, &mut created)?;
+            let mut placement = Placement::new(&dest_dir, &options);
             extract_tar_with_reader(
                 &archive_path,
                 &dest_dir,
-                strip.as_deref(),
+                EntryLayout::Tree(strip.as_deref()),
+                &mut placement,
                 &stats,
+                &preserve,
                 progress.as_ref(),
                 &mut created,
                 cancel_token,
@@ -446,13 +525,16 @@ fn do_extract(
             dest_dir
         }
         ArchiveKind::TarXz => {
-            let (dest_dir, strip) = choose_destination_dir(&archive_path, kind)?;
-            created.record_dir(dest_dir.clone());
+            let (dest_dir, strip) =
+                prepare_destination(&archive_path, kind, password, &options, &mut created)?;
+            let mut placement = Placement::new(&dest_dir, &options);
             extract_tar_with_reader(
                 &archive_path,
                 &dest_dir,
-                strip.as_deref(),
+                EntryLayout::Tree(strip.as_deref()),
+                &mut placement,
                 &stats,
+                &preserve,
                 progress.as_ref(),
                 &mut created,
                 cancel_token,
@@ -462,13 +544,16 @@ fn do_extract(
             dest_dir
         }
         ArchiveKind::TarZstd => {
-            let (dest_dir, strip) = choose_destination_dir(&archive_path, kind)?;
-            created.record_dir(dest_dir.clone());
+            let (dest_dir, strip) =
+                prepare_destination(&archive_path, kind, password, &options, &mut created)?;
+            let mut placement = Placement::new(&dest_dir, &options);
             extract_tar_with_reader(
                 &archive_path,
                 &dest_dir,
-                strip.as_deref(),
+                EntryLayout::Tree(strip.as_deref()),
+                &mut placement,
                 &stats,
+                &preserve,
                 progress.as_ref(),
                 &mut created,
                 cancel_token,
@@ -486,17 +571,21 @@ fn do_extract(
             dest_dir
         }
         ArchiveKind::SevenZ => {
-            let (dest_dir, strip) = choose_destination_dir(&archive_path, kind)?;
-            created.record_dir(dest_dir.clone());
+            let (dest_dir, strip) =
+                prepare_destination(&archive_path, kind, password, &options, &mut created)?;
+            let mut placement = Placement::new(&dest_dir, &options);
             extract_7z(
                 &archive_path,
                 &dest_dir,
-                strip.as_deref(),
+                EntryLayout::Tree(strip.as_deref()),
+                &mut placement,
                 &stats,
+                &preserve,
                 progress.as_ref(),
                 &mut created,
                 cancel_token,
                 &budget,
+                password,
             )?;
             dest_dir
         }
@@ -505,13 +594,16 @@ fn do_extract(
                 Some(v) => v,
                 None => parse_rar_entries(&archive_path)?,
             };
-            let (dest_dir, strip) = choose_destination_dir(&archive_path, kind)?;
-            created.record_dir(dest_dir.clone());
+            let (dest_dir, strip) =
+                prepare_destination(&archive_path, kind, password, &options, &mut created)?;
+            let mut placement = Placement::new(&dest_dir, &options);
             extract_rar(
                 entries,
                 &dest_dir,
-                strip.as_deref(),
+                EntryLayout::Tree(strip.as_deref()),
+                &mut placement,
                 &stats,
+                &preserve,
                 progress.as_ref(),
                 &mut created,
                 cancel_token,
@@ -522,6 +614,9 @@ fn do_extract(
         ArchiveKind::Gz => decompress_single_with_reader(
             &archive_path,
             parent,
+            &options,
+            &stats,
+            &preserve,
             progress.as_ref(),
             &mut created,
             cancel_token,
@@ -531,6 +626,9 @@ fn do_extract(
         ArchiveKind::Bz2 => decompress_single_with_reader(
             &archive_path,
             parent,
+            &options,
+            &stats,
+            &preserve,
             progress.as_ref(),
             &mut created,
             cancel_token,
@@ -540,6 +638,9 @@ fn do_extract(
         ArchiveKind::Xz => decompress_single_with_reader(
             &archive_path,
             parent,
+            &options,
+            &stats,
+            &preserve,
             progress.as_ref(),
             &mut created,
             cancel_token,
@@ -549,6 +650,9 @@ fn do_extract(
         ArchiveKind::Zstd => decompress_single_with_reader(
             &archive_path,
             parent,
+            &options,
+            &stats,
+            &preserve,
             progress.as_ref(),
             &mut created,
             cancel_token,
@@ -572,29 +676,123 @@ fn do_extract(
     }
     created.disarm();
 
-    let backup = temp_backup_path(&destination);
-    let action = Action::Create {
-        path: destination.clone(),
-        backup,
-    };
+    // A new folder is undone as a whole; what went into an existing folder is undone item by
+    // item, bringing back whatever was overwritten.
+    let mut actions =
+        if options.destination == ExtractDestination::NewFolder && !kind.is_single_stream() {
+            vec![Action::Create {
+                path: destination.clone(),
+                backup: temp_backup_path(&destination),
+            }]
+        } else {
+            merged_undo_actions(&created)
+        };
     if let Some(list) = batch_actions.as_ref() {
         if let Ok(mut guard) = list.lock() {
-            guard.push(action);
+            guard.extend(actions);
         }
-    } else {
-        let _ = undo.record_applied(action);
+    } else if actions.len() == 1 {
+        let _ = undo.record_applied(actions.remove(0));
+    } else if !actions.is_empty() {
+        let _ = undo.record_applied(Action::Batch(actions));
     }
 
     Ok(ExtractResult {
         destination: destination.to_string_lossy().into_owned(),
         skipped_symlinks: stats.symlinks.load(std::sync::atomic::Ordering::Relaxed),
         skipped_entries: stats.unsupported.load(std::sync::atomic::Ordering::Relaxed),
+        unpreserved: preserve.into_report(),
+        entries: stats.take_outcomes(),
     })
 }
 
+/// Undo steps for an extraction into an existing folder: each overwritten item comes back from
+/// its backup, and each top-level file or folder the extraction created is removed.
+fn merged_undo_actions(created: &CreatedPaths) -> Vec<Action> {
+    let mut actions: Vec<Action> = created
+        .replaced
+        .iter()
+        .map(|(path, backup)| Action::Delete {
+            path: path.clone(),
+            backup: backup.clone(),
+        })
+        .collect();
+    let created_dirs: HashSet<&Path> = created.dirs.iter().map(PathBuf::as_path).collect();
+    let inside_created_dir = |path: &Path| {
+        path.ancestors()
+            .skip(1)
+            .any(|dir| created_dirs.contains(dir))
+    };
+    for path in created.dirs.iter().chain(&created.files) {
+        if !inside_created_dir(path) {
+            actions.push(Action::Create {
+                path: path.clone(),
+                backup: temp_backup_path(path),
+            });
+        }
+    }
+    actions
+}
+
+/// Resolves an archive to extract, refusing symlinks and anything but regular files.
+fn resolve_archive_path(path: &str) -> DecompressResult<(PathBuf, fs::Metadata)> {
+    let nofollow = sanitize_path_nofollow(path, true).map_err(DecompressError::from)?;
+    let meta = fs::symlink_metadata(&nofollow).map_err(|e| {
+        DecompressError::from_external_message(format!("Failed to read archive metadata: {e}"))
+    })?;
+    if meta.file_type().is_symlink() {
+        return Err(DecompressError::from_external_message(
+            "Symlink archives are not supported",
+        ));
+    }
+
+    let archive_path = sanitize_path_follow(path, true).map_err(DecompressError::from)?;
+    check_no_symlink_components(&archive_path).map_err(DecompressError::from)?;
+
+    if !archive_path.is_file() {
+        return Err(DecompressError::from_external_message(
+            "Only files can be extracted",
+        ));
+    }
+    Ok((archive_path, meta))
+}
+
+/// Builds the extraction budget for writing into `dest`, capped so the free-space reserve
+/// survives. Also returns the free space seen, for error messages.
+fn extract_budget_for(dest: &Path) -> DecompressResult<(ExtractBudget, u64)> {
+    let available_bytes = available_disk_bytes(dest)?;
+    let effective_bytes_cap = effective_extract_bytes_cap(
+        EXTRACT_TOTAL_BYTES_CAP,
+        available_bytes,
+        EXTRACT_MIN_FREE_DISK_RESERVE,
+    );
+    if effective_bytes_cap == 0 {
+        return Err(DecompressError::from_external_message(format!(
+            "Insufficient free disk space in {} (available: {} bytes, required reserve: {} bytes)",
+            dest.display(),
+            available_bytes,
+            EXTRACT_MIN_FREE_DISK_RESERVE
+        )));
+    }
+    let budget = ExtractBudget::new(effective_bytes_cap, EXTRACT_TOTAL_ENTRIES_CAP)
+        .with_disk_guard(DiskSpaceGuard::new(
+            dest.to_path_buf(),
+            EXTRACT_MIN_FREE_DISK_RESERVE,
+            EXTRACT_DISK_CHECK_INTERVAL_BYTES,
+        ));
+    Ok((budget, available_bytes))
+}
+
 fn archive_root_name(path: &Path) -> String {
-    path.file_name()
-        .and_then(|s| s.to_str())
+    // `photos.zip.001` and `photos.part1.rar` extract to `photos`, like `photos.zip`.
+    let name = match split_volume_name(path) {
+        Some(volume) => Some(volume.archive_name()),
+        None => path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .map(str::to_string),
+    };
+    name.as_deref()
         .map(strip_known_suffixes)
         .map(|s| {
             if s.is_empty() {
@@ -619,6 +817,10 @@ fn prepare_output_dir(archive_path: &Path) -> DecompressResult<PathBuf> {
 }
 
 fn detect_archive(path: &Path) -> DecompressResult<ArchiveKind> {
+    // Only the first volume of a split set starts the archive; its number says the format.
+    if let Some(volume) = check_first_volume(path)? {
+        return Ok(volume.kind);
+    }
     let mut f = File::open(path)
         .map_err(map_io("open archive for detection"))
         .map_err(DecompressError::from_external_message)?;
@@ -707,9 +909,35 @@ fn has_suffix(name: &str, suffixes: &[&str]) -> bool {
     suffixes.iter().any(|s| name.ends_with(s))
 }
 
+/// The folder entries are written to, and the single root folder to strip from their paths.
+/// A new folder is recorded so a failed extraction removes it whole; extracting here writes
+/// into the archive's own folder with paths kept as they are.
+fn prepare_destination(
+    archive_path: &Path,
+    kind: ArchiveKind,
+    password: Option<&str>,
+    options: &ExtractOptions,
+    created: &mut CreatedPaths,
+) -> DecompressResult<(PathBuf, Option<PathBuf>)> {
+    match options.destination {
+        ExtractDestination::Here => {
+            let parent = archive_path.parent().ok_or_else(|| {
+                DecompressError::from_external_message("Cannot extract archive at filesystem root")
+            })?;
+            Ok((parent.to_path_buf(), None))
+        }
+        ExtractDestination::NewFolder => {
+            let (dest_dir, strip) = choose_destination_dir(archive_path, kind, password)?;
+            created.record_dir(dest_dir.clone());
+            Ok((dest_dir, strip))
+        }
+    }
+}
+
 fn choose_destination_dir(
     archive_path: &Path,
     kind: ArchiveKind,
+    password: Option<&str>,
 ) -> DecompressResult<(PathBuf, Option<PathBuf>)> {
     let parent = archive_path.parent().ok_or_else(|| {
         DecompressError::from_external_message("Cannot extract archive at filesystem root")
@@ -722,7 +950,7 @@ fn choose_destination_dir(
         | ArchiveKind::TarBz2
         | ArchiveKind::TarXz
         | ArchiveKind::TarZstd => single_root_in_tar(archive_path, kind)?,
-        ArchiveKind::SevenZ => single_root_in_7z(archive_path)?,
+        ArchiveKind::SevenZ => single_root_in_7z(archive_path, password)?,
         ArchiveKind::Rar => single_root_in_rar(archive_path)?,
         _ => None,
     };
@@ -737,9 +965,13 @@ fn choose_destination_dir(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn decompress_single_with_reader<F>(
     archive_path: &Path,
     parent: &Path,
+    options: &ExtractOptions,
+    stats: &SkipStats,
+    preserve: &MetadataPreserver,
     progress: Option<&ProgressEmitter>,
     created: &mut CreatedPaths,
     cancel: Option<&AtomicBool>,
@@ -751,38 +983,60 @@ where
 {
     let reader = open_buffered_file(archive_path, "open compressed file")?;
     let reader = wrap(reader)?;
-    decompress_single(
+    let dest_path = single_output_path(archive_path, parent);
+    // A lone decompressed file takes the next free name unless a policy says otherwise.
+    let options = ExtractOptions {
+        conflict: Some(options.conflict.unwrap_or(ConflictPolicy::Rename)),
+        ..*options
+    };
+    let placement = Placement::new(parent, &options);
+    let written = decompress_single(
         reader,
         archive_path,
-        parent,
+        &dest_path,
+        &placement,
+        stats,
         progress,
         created,
         cancel,
         budget,
-    )
+    )?;
+    let Some(written) = written else {
+        // Skipped: the file already there stays the destination.
+        return Ok(dest_path);
+    };
+    // Like gunzip, the output takes over the compressed file's own metadata.
+    preserve.copy_from(archive_path, &written);
+    Ok(written)
 }
 
+fn single_output_path(archive: &Path, parent: &Path) -> PathBuf {
+    let mut dest_name = archive
+        .file_name()
+        .and_then(|s| s.to_str())
+        .map(strip_known_suffixes)
+        .unwrap_or_else(|| "extracted".to_string());
+    if dest_name.is_empty() {
+        dest_name = "extracted".to_string();
+    }
+    parent.join(dest_name)
+}
+
+#[allow(clippy::too_many_arguments)]
 fn decompress_single<R: Read>(
     mut reader: R,
     archive: &Path,
-    parent: &Path,
+    dest_path: &Path,
+    placement: &Placement,
+    stats: &SkipStats,
     progress: Option<&ProgressEmitter>,
     created: &mut CreatedPaths,
     cancel: Option<&AtomicBool>,
     budget: &ExtractBudget,
-) -> DecompressResult<PathBuf> {
+) -> DecompressResult<Option<PathBuf>> {
     budget.reserve_entry(1).map_err(|e| {
         DecompressError::from_external_message(map_copy_err("Extraction entry cap exceeded", e))
     })?;
-    let mut dest_name = archive
-        .file_name()
-        .and_then(|s| s.to_str())
-        .map(strip_known_suffixes)
-        .unwrap_or_else(|| "extracted".to_string());
-    if dest_name.is_empty() {
-        dest_name = "extracted".to_string();
-    }
-    let dest_path = parent.join(dest_name);
     if let Some(parent_dir) = dest_path.parent() {
         let created_dirs = ensure_dir_nofollow(parent_dir).map_err(|e| {
             DecompressError::from_external_message(format!(
@@ -794,14 +1048,29 @@ fn decompress_single<R: Read>(
             created.record_dir(dir);
         }
     }
-    let (file, dest_path) = open_unique_file(&dest_path)?;
-    created.record_file(dest_path.clone());
+    // The decompressed size is not known up front, so `overwrite_if_larger` keeps what is there.
+    let incoming = EntryFacts {
+        is_dir: false,
+        size: 0,
+        modified: fs::metadata(archive).and_then(|meta| meta.modified()).ok(),
+    };
+    let name = dest_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let Some((file, dest_path)) =
+        placement.open_file(&name, dest_path, &incoming, stats, created)?
+    else {
+        return Ok(None);
+    };
     let mut out = BufWriter::with_capacity(CHUNK, file);
     let mut buf = vec![0u8; CHUNK];
-    copy_with_progress(&mut reader, &mut out, progress, cancel, budget, &mut buf).map_err(|e| {
-        DecompressError::from_external_message(map_copy_err("write decompressed file", e))
-    })?;
-    Ok(dest_path)
+    copy_with_progress(&mut reader, &mut out, progress, cancel, budget, &mut buf)
+        .and_then(|_| out.flush())
+        .map_err(|e| {
+            DecompressError::from_external_message(map_copy_err("write decompressed file", e))
+        })?;
+    Ok(Some(dest_path))
 }
 
 fn gzip_uncompressed_size(path: &Path) -> DecompressResult<u64> {
@@ -840,7 +1109,7 @@ fn estimate_total_hint(path: &Path) -> DecompressResult<u64> {
         ArchiveKind::Zip => zip_uncompressed_total(path).unwrap_or(meta.len()),
         ArchiveKind::Tar => tar_uncompressed_total(path).unwrap_or(meta.len()),
         ArchiveKind::TarGz => gzip_uncompressed_size(path).unwrap_or(meta.len()),
-        ArchiveKind::SevenZ => sevenz_uncompressed_total(path).unwrap_or(meta.len()),
+        ArchiveKind::SevenZ => sevenz_uncompressed_total(path, None).unwrap_or(meta.len()),
         ArchiveKind::Rar => {
             let entries = parse_rar_entries(path)?;
             let total = rar_uncompressed_total_from_entries(&entries).unwrap_or(meta.len());
@@ -867,6 +1136,8 @@ mod tests {
             destination: format!("/tmp/{path}"),
             skipped_symlinks: 0,
             skipped_entries: 0,
+            unpreserved: Vec::new(),
+            entries: Vec::new(),
         }
     }
 
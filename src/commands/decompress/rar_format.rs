@@ -1,21 +1,56 @@
 use std::{
-    io::{BufWriter, Write},
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
     path::{Path, PathBuf},
     sync::{atomic::AtomicBool, Arc},
 };
 
 use rar_stream::{
-    InnerFile as RarInnerFile, LocalFileMedia as RarLocalFileMedia,
+    FileMedia as RarFileMedia, InnerFile as RarInnerFile, LocalFileMedia as RarLocalFileMedia,
     ParseOptions as RarParseOptions, RarFilesPackage, ReadInterval as RarReadInterval,
 };
 use tauri::async_runtime;
 
 use super::error::{DecompressError, DecompressResult};
 use super::util::{
-    check_cancel, clean_relative_path, ensure_dir_nofollow, first_component, map_copy_err,
-    open_unique_file, path_exists_nofollow, CreatedPaths, ExtractBudget, ProgressEmitter,
-    SkipStats, CHUNK, EXTRACT_TOTAL_ENTRIES_CAP,
+    check_cancel, clean_relative_path, ensure_dir_nofollow, first_component, map_copy_err, map_io,
+    pause_checkpoint, record_unread_metadata, CreatedPaths, EntryLayout, ExtractBudget, Placement,
+    ProgressEmitter, SkipStats, CHUNK, EXTRACT_TOTAL_ENTRIES_CAP,
 };
+use super::volumes::{archive_volumes, missing_volume_after};
+use crate::fs_utils::{EntryFacts, MetadataPreserver};
+
+const RAR4_SIGNATURE: &[u8] = b"Rar!\x1a\x07\x00";
+const RAR5_SIGNATURE: &[u8] = b"Rar!\x1a\x07\x01\x00";
+const RAR4_MAIN_BLOCK: u8 = 0x73;
+const RAR4_FILE_BLOCK: u8 = 0x74;
+const RAR4_END_BLOCK: u8 = 0x7b;
+/// End block flag: the archive goes on in the next volume.
+const RAR4_END_NEXT_VOLUME: u16 = 0x0001;
+/// Main block flag: every block header after it is encrypted.
+const RAR4_MAIN_PASSWORD: u16 = 0x0080;
+const RAR4_FILE_ENCRYPTED: u16 = 0x0004;
+const RAR4_FILE_LARGE: u16 = 0x0100;
+const RAR4_LONG_BLOCK: u16 = 0x8000;
+const RAR4_HIGH_PACK_SIZE_OFFSET: u64 = 32;
+const RAR5_FILE_HEADER: u64 = 2;
+const RAR5_ENCRYPTION_HEADER: u64 = 4;
+const RAR5_END_HEADER: u64 = 5;
+const RAR5_HAS_EXTRA: u64 = 0x1;
+const RAR5_HAS_DATA: u64 = 0x2;
+const RAR5_FILE_ENCRYPTION_RECORD: u64 = 0x1;
+const RAR5_END_NEXT_VOLUME: u64 = 0x1;
+
+/// What walking the block headers of a RAR volume came to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RarHeaders {
+    /// The headers or an entry are encrypted; nothing after that was read.
+    Encrypted,
+    /// The end block, which says whether the archive goes on in a next volume.
+    End { next_volume: bool },
+    /// The headers ran out before an end block, or were not recognized.
+    Unknown,
+}
 
 pub(super) fn single_root_in_rar(path: &Path) -> DecompressResult<Option<PathBuf>> {
     let entries = parse_rar_entries(path)?;
@@ -62,14 +97,17 @@ pub(super) fn single_root_in_rar(path: &Path) -> DecompressResult<Option<PathBuf
 pub(super) fn extract_rar(
     entries: Vec<RarInnerFile>,
     dest_dir: &Path,
-    strip_prefix: Option<&Path>,
+    layout: EntryLayout<'_>,
+    placement: &mut Placement,
     stats: &SkipStats,
+    preserve: &MetadataPreserver,
     progress: Option<&ProgressEmitter>,
     created: &mut CreatedPaths,
     cancel: Option<&AtomicBool>,
     budget: &ExtractBudget,
 ) -> DecompressResult<()> {
     for entry in entries {
+        pause_checkpoint(progress);
         check_cancel(cancel).map_err(|e| map_copy_err("Extraction cancelled", e))?;
         budget
             .reserve_entry(1)
@@ -78,13 +116,6 @@ pub(super) fn extract_rar(
         let normalized = raw_name.replace('\\', "/");
         let raw_path = Path::new(&normalized).to_path_buf();
 
-        // rar-stream lacks a complete decoder for compressed entries; abort instead of writing corrupted data.
-        if entry.is_compressed() {
-            return Err(DecompressError::from_external_message(format!(
-                "RAR entry uses unsupported compression method: {raw_name}"
-            )));
-        }
-
         let clean_rel = match clean_relative_path(&raw_path) {
             Ok(p) => p,
             Err(err) => {
@@ -92,17 +123,20 @@ pub(super) fn extract_rar(
                 continue;
             }
         };
-        let clean_rel = if let Some(prefix) = strip_prefix {
-            match clean_rel.strip_prefix(prefix) {
-                Ok(stripped) => stripped.to_path_buf(),
-                Err(_) => clean_rel,
-            }
-        } else {
-            clean_rel
+        let Some(clean_rel) = layout.place(clean_rel) else {
+            continue;
         };
         if clean_rel.as_os_str().is_empty() {
             continue;
         }
+
+        // rar-stream lacks a complete decoder for compressed entries; abort instead of writing corrupted data.
+        if entry.is_compressed() {
+            return Err(DecompressError::from_external_message(format!(
+                "RAR entry uses unsupported compression method: {raw_name}"
+            )));
+        }
+
         let dest_path = dest_dir.join(clean_rel);
         let is_dir = normalized.ends_with('/') || normalized.ends_with('\\');
 
@@ -120,20 +154,6 @@ pub(super) fn extract_rar(
             continue;
         }
 
-        match path_exists_nofollow(&dest_path) {
-            Ok(true) => {
-                if let Some(p) = progress {
-                    p.add(entry.length.max(1));
-                }
-                continue;
-            }
-            Ok(false) => {}
-            Err(e) => {
-                stats.skip_unsupported(&raw_name, &format!("stat destination failed: {e}"));
-                continue;
-            }
-        }
-
         if let Some(parent) = dest_path.parent() {
             match ensure_dir_nofollow(parent) {
                 Ok(created_dirs) => {
@@ -148,8 +168,21 @@ pub(super) fn extract_rar(
             }
         }
 
-        let (file, dest_actual) = open_unique_file(&dest_path)?;
-        created.record_file(dest_actual);
+        // Times are not read from rar entries, so `overwrite_if_newer` keeps what is there.
+        let incoming = EntryFacts {
+            is_dir: false,
+            size: entry.length,
+            modified: None,
+        };
+        let Some((file, dest_actual)) =
+            placement.open_file(&raw_name, &dest_path, &incoming, stats, created)?
+        else {
+            if let Some(p) = progress {
+                p.add(entry.length.max(1));
+            }
+            continue;
+        };
+        record_unread_metadata(preserve, &dest_actual, "rar");
         let mut out = BufWriter::with_capacity(CHUNK, file);
         write_rar_entry_streaming(&entry, &raw_name, &mut out, progress, cancel, budget)?;
     }
@@ -168,6 +201,7 @@ fn write_rar_entry_streaming(
     let chunk_len = CHUNK as u64;
 
     while start < entry.length {
+        pause_checkpoint(progress);
         check_cancel(cancel).map_err(|e| map_copy_err("Extraction cancelled", e))?;
         let end = (start.saturating_add(chunk_len).saturating_sub(1)).min(entry.length - 1);
         let data = async_runtime::block_on(entry.read_range(RarReadInterval { start, end }))
@@ -192,14 +226,35 @@ fn write_rar_entry_streaming(
     Ok(())
 }
 
+/// Parses a RAR archive, with all its volumes when `path` is the first of a `.partN.rar` set.
 pub(super) fn parse_rar_entries(path: &Path) -> DecompressResult<Vec<RarInnerFile>> {
-    let path_str = path
-        .to_str()
-        .ok_or_else(|| "Archive path is not valid UTF-8".to_string())?;
-    let media = Arc::new(
-        RarLocalFileMedia::new(path_str).map_err(|e| format!("Failed to open rar archive: {e}"))?,
-    );
-    let package = RarFilesPackage::new(vec![media]);
+    let volumes = archive_volumes(path)?;
+    let first = scan_rar_volume(path)?;
+    // rar-stream cannot decrypt, and would hand back ciphertext for a stored entry, so a
+    // password would not help either.
+    if first == RarHeaders::Encrypted {
+        return Err(DecompressError::encrypted_rar_unsupported());
+    }
+    let last_volume = &volumes[volumes.len() - 1];
+    let last = if volumes.len() > 1 {
+        scan_rar_volume(last_volume)?
+    } else {
+        first
+    };
+    if last == (RarHeaders::End { next_volume: true }) {
+        return Err(missing_volume_after(last_volume));
+    }
+    let mut medias: Vec<Arc<dyn RarFileMedia>> = Vec::with_capacity(volumes.len());
+    for volume in &volumes {
+        let volume_str = volume
+            .to_str()
+            .ok_or_else(|| "Archive path is not valid UTF-8".to_string())?;
+        medias.push(Arc::new(
+            RarLocalFileMedia::new(volume_str)
+                .map_err(|e| format!("Failed to open rar archive: {e}"))?,
+        ));
+    }
+    let package = RarFilesPackage::new(medias);
     let entries = async_runtime::block_on(async move {
         package
             .parse(RarParseOptions::default())
@@ -227,3 +282,279 @@ pub(super) fn rar_uncompressed_total_from_entries(
     }
     Ok(total)
 }
+
+fn scan_rar_volume(path: &Path) -> DecompressResult<RarHeaders> {
+    let mut reader = BufReader::new(File::open(path).map_err(map_io("open rar archive"))?);
+    Ok(scan_rar_headers(&mut reader).map_err(map_io("read rar headers"))?)
+}
+
+/// Walks the block headers of one volume, up to its end block or the first sign of
+/// encryption. Anything this does not recognize, a self-extracting archive say, is left to the
+/// parser.
+fn scan_rar_headers<R: Read + Seek>(reader: &mut R) -> io::Result<RarHeaders> {
+    let mut signature = [0u8; 8];
+    let read = read_fully(reader, &mut signature)?;
+    let signature = &signature[..read];
+    if signature.starts_with(RAR5_SIGNATURE) {
+        scan_rar5_headers(reader)
+    } else if signature.starts_with(RAR4_SIGNATURE) {
+        reader.seek(SeekFrom::Start(RAR4_SIGNATURE.len() as u64))?;
+        scan_rar4_headers(reader)
+    } else {
+        Ok(RarHeaders::Unknown)
+    }
+}
+
+fn scan_rar4_headers<R: Read + Seek>(reader: &mut R) -> io::Result<RarHeaders> {
+    let mut head = [0u8; 7];
+    loop {
+        let block_start = reader.stream_position()?;
+        if read_fully(reader, &mut head)? < head.len() {
+            return Ok(RarHeaders::Unknown);
+        }
+        let kind = head[2];
+        let flags = u16::from_le_bytes([head[3], head[4]]);
+        let head_size = u64::from(u16::from_le_bytes([head[5], head[6]]));
+        if head_size < head.len() as u64 {
+            return Ok(RarHeaders::Unknown);
+        }
+        match kind {
+            RAR4_MAIN_BLOCK if flags & RAR4_MAIN_PASSWORD != 0 => return Ok(RarHeaders::Encrypted),
+            RAR4_FILE_BLOCK if flags & RAR4_FILE_ENCRYPTED != 0 => {
+                return Ok(RarHeaders::Encrypted)
+            }
+            RAR4_END_BLOCK => {
+                return Ok(RarHeaders::End {
+                    next_volume: flags & RAR4_END_NEXT_VOLUME != 0,
+                })
+            }
+            _ => {}
+        }
+        let mut data_size = 0u64;
+        if flags & RAR4_LONG_BLOCK != 0 {
+            data_size = u64::from(read_u32_le(reader)?);
+            if kind == RAR4_FILE_BLOCK && flags & RAR4_FILE_LARGE != 0 {
+                reader.seek(SeekFrom::Start(block_start + RAR4_HIGH_PACK_SIZE_OFFSET))?;
+                data_size |= u64::from(read_u32_le(reader)?) << 32;
+            }
+        }
+        reader.seek(SeekFrom::Start(
+            block_start
+                .saturating_add(head_size)
+                .saturating_add(data_size),
+        ))?;
+    }
+}
+
+fn scan_rar5_headers<R: Read + Seek>(reader: &mut R) -> io::Result<RarHeaders> {
+    let mut crc = [0u8; 4];
+    loop {
+        if read_fully(reader, &mut crc)? < crc.len() {
+            return Ok(RarHeaders::Unknown);
+        }
+        let header_size = read_vint(reader)?;
+        let header_start = reader.stream_position()?;
+        let header_type = read_vint(reader)?;
+        let flags = read_vint(reader)?;
+        if header_type == RAR5_ENCRYPTION_HEADER {
+            return Ok(RarHeaders::Encrypted);
+        }
+        if header_size == 0 {
+            return Ok(RarHeaders::Unknown);
+        }
+        let extra_size = if flags & RAR5_HAS_EXTRA != 0 {
+            read_vint(reader)?
+        } else {
+            0
+        };
+        let data_size = if flags & RAR5_HAS_DATA != 0 {
+            read_vint(reader)?
+        } else {
+            0
+        };
+        if header_type == RAR5_END_HEADER {
+            let end_flags = read_vint(reader)?;
+            return Ok(RarHeaders::End {
+                next_volume: end_flags & RAR5_END_NEXT_VOLUME != 0,
+            });
+        }
+        let header_end = header_start.saturating_add(header_size);
+        if header_type == RAR5_FILE_HEADER && extra_size > 0 {
+            let Some(extra_start) = header_end.checked_sub(extra_size) else {
+                return Ok(RarHeaders::Unknown);
+            };
+            if rar5_extra_has_encryption(reader, extra_start, header_end)? {
+                return Ok(RarHeaders::Encrypted);
+            }
+        }
+        reader.seek(SeekFrom::Start(header_end.saturating_add(data_size)))?;
+    }
+}
+
+fn rar5_extra_has_encryption<R: Read + Seek>(
+    reader: &mut R,
+    mut record_start: u64,
+    extra_end: u64,
+) -> io::Result<bool> {
+    while record_start < extra_end {
+        reader.seek(SeekFrom::Start(record_start))?;
+        let record_size = read_vint(reader)?;
+        let record_body = reader.stream_position()?;
+        if read_vint(reader)? == RAR5_FILE_ENCRYPTION_RECORD {
+            return Ok(true);
+        }
+        if record_size == 0 {
+            break;
+        }
+        record_start = record_body.saturating_add(record_size);
+    }
+    Ok(false)
+}
+
+/// RAR5 variable-length integer: seven bits per byte, low bits first.
+fn read_vint<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0u8; 1];
+        reader.read_exact(&mut byte)?;
+        value |= u64::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "rar header integer is too long",
+    ))
+}
+
+fn read_u32_le<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+/// Reads until `buf` is full or the input ends, returning how much was read.
+fn read_fully<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::domain::DomainError;
+    use crate::fs_utils::unique_temp_dir;
+    use std::io::Cursor;
+
+    fn rar5_header(header_type: u8, flags: u8, body: &[u8]) -> Vec<u8> {
+        let mut header = vec![header_type, flags];
+        header.extend_from_slice(body);
+        let mut block = vec![0u8; 4];
+        block.push(header.len() as u8);
+        block.extend_from_slice(&header);
+        block
+    }
+
+    fn rar5_archive(file_extra: &[u8]) -> Vec<u8> {
+        let mut bytes = RAR5_SIGNATURE.to_vec();
+        bytes.extend(rar5_header(1, 0, &[0]));
+        // Extra area size, data size, then file flags, unpacked size, attributes,
+        // compression info, host OS and a one-byte name.
+        let mut file_body = vec![file_extra.len() as u8, 3, 0, 3, 0, 0, 1, 1, b'a'];
+        file_body.extend_from_slice(file_extra);
+        let mut flags = RAR5_HAS_DATA as u8;
+        if file_extra.is_empty() {
+            file_body.remove(0);
+        } else {
+            flags |= RAR5_HAS_EXTRA as u8;
+        }
+        bytes.extend(rar5_header(RAR5_FILE_HEADER as u8, flags, &file_body));
+        bytes.extend_from_slice(b"abc");
+        bytes.extend(rar5_header(RAR5_END_HEADER as u8, 0, &[0]));
+        bytes
+    }
+
+    fn rar4_archive(main_flags: u16, file_flags: u16) -> Vec<u8> {
+        let mut bytes = RAR4_SIGNATURE.to_vec();
+        bytes.extend_from_slice(&[0, 0, RAR4_MAIN_BLOCK]);
+        bytes.extend_from_slice(&main_flags.to_le_bytes());
+        bytes.extend_from_slice(&13u16.to_le_bytes());
+        bytes.extend_from_slice(&[0; 6]);
+        let mut file = vec![0, 0, RAR4_FILE_BLOCK];
+        file.extend_from_slice(&(file_flags | RAR4_LONG_BLOCK).to_le_bytes());
+        file.extend_from_slice(&33u16.to_le_bytes());
+        file.extend_from_slice(&3u32.to_le_bytes());
+        file.resize(26, 0);
+        file.extend_from_slice(&1u16.to_le_bytes());
+        file.extend_from_slice(&[0; 4]);
+        file.push(b'a');
+        bytes.extend(file);
+        bytes.extend_from_slice(b"abc");
+        bytes.extend_from_slice(&[0, 0, RAR4_END_BLOCK, 0, 0, 7, 0]);
+        bytes
+    }
+
+    fn scan(bytes: Vec<u8>) -> RarHeaders {
+        scan_rar_headers(&mut Cursor::new(bytes)).expect("scan headers")
+    }
+
+    fn encrypted(bytes: Vec<u8>) -> bool {
+        scan(bytes) == RarHeaders::Encrypted
+    }
+
+    #[test]
+    fn detects_encrypted_rar5_entries_and_headers() {
+        assert!(!encrypted(rar5_archive(&[])));
+        // An encryption record: size, type and a stub body.
+        assert!(encrypted(rar5_archive(&[2, 1, 0])));
+        // A record of another type is skipped.
+        assert!(!encrypted(rar5_archive(&[2, 3, 0])));
+
+        let mut header_encrypted = RAR5_SIGNATURE.to_vec();
+        header_encrypted.extend(rar5_header(RAR5_ENCRYPTION_HEADER as u8, 0, &[0, 0]));
+        assert!(encrypted(header_encrypted));
+    }
+
+    #[test]
+    fn detects_encrypted_rar4_entries_and_headers() {
+        assert!(!encrypted(rar4_archive(0, 0)));
+        assert!(encrypted(rar4_archive(RAR4_MAIN_PASSWORD, 0)));
+        assert!(encrypted(rar4_archive(0, RAR4_FILE_ENCRYPTED)));
+        assert!(!encrypted(b"not a rar archive".to_vec()));
+    }
+
+    #[test]
+    fn reads_the_next_volume_flag_from_the_end_block() {
+        let last = RarHeaders::End { next_volume: false };
+        let continued = RarHeaders::End { next_volume: true };
+        assert_eq!(scan(rar5_archive(&[])), last);
+        let mut rar5 = rar5_archive(&[]);
+        *rar5.last_mut().unwrap() = RAR5_END_NEXT_VOLUME as u8;
+        assert_eq!(scan(rar5), continued);
+
+        assert_eq!(scan(rar4_archive(0, 0)), last);
+        let mut rar4 = rar4_archive(0, 0);
+        let flags = rar4.len() - 4;
+        rar4[flags] = RAR4_END_NEXT_VOLUME as u8;
+        assert_eq!(scan(rar4), continued);
+    }
+
+    #[test]
+    fn encrypted_rar_fails_with_its_own_code_rather_than_asking_for_a_password() {
+        let dir = unique_temp_dir("rar-encrypted");
+        let path = dir.join("secret.rar");
+        std::fs::write(&path, rar4_archive(0, RAR4_FILE_ENCRYPTED)).unwrap();
+        let err = parse_rar_entries(&path).unwrap_err();
+        assert_eq!(err.code_str(), "encrypted_rar_unsupported");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,410 @@
+//! Extracting chosen members of an archive, files or whole folders, instead of all of it.
+
+use std::{
+    collections::HashSet,
+    ffi::{OsStr, OsString},
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use serde::Serialize;
+
+use super::browse::extract_members;
+use super::error::{map_api_result, DecompressError, DecompressErrorCode, DecompressResult};
+use super::members::list_members;
+use super::util::{
+    clean_relative_path, path_exists_nofollow, ExtractBudget, MemberTarget, ProgressEmitter,
+    EXTRACT_TOTAL_BYTES_CAP, EXTRACT_TOTAL_ENTRIES_CAP,
+};
+use super::{extraction_cancelled, resolve_archive_path};
+use crate::errors::api_error::ApiResult;
+use crate::fs_utils::{
+    check_no_symlink_components, sanitize_path_follow, MetadataPreserver, PreserveAttr,
+    PreserveOptions, UnpreservedAttr,
+};
+use crate::tasks::{run_job, CancelGuard, CancelState, JobKind, JobSpec};
+use crate::undo::{temp_backup_path, Action, UndoState};
+
+#[derive(Serialize)]
+pub struct ExtractMembersResult {
+    /// Where each selected member was written, in archive path order. A member whose name is
+    /// taken in the destination gets a numbered name.
+    pub extracted: Vec<String>,
+    pub skipped_symlinks: usize,
+    pub skipped_entries: usize,
+    /// Requested attributes that could not be restored on the extracted entries.
+    pub unpreserved: Vec<UnpreservedAttr>,
+}
+
+/// Extracts only `members` of the archive at `path` into the folder `dest`. A member is a
+/// `/`-separated path as `list_archive_entries` reports it; a folder brings its contents along.
+/// `password` opens encrypted zip and 7z archives and is never logged or stored.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn extract_archive_members(
+    app: tauri::AppHandle,
+    cancel: tauri::State<'_, CancelState>,
+    undo: tauri::State<'_, UndoState>,
+    path: String,
+    members: Vec<String>,
+    dest: String,
+    password: Option<String>,
+    progress_event: Option<String>,
+    preserve: Option<Vec<PreserveAttr>>,
+) -> ApiResult<ExtractMembersResult> {
+    let job = JobSpec::new(
+        JobKind::Extract,
+        dest.clone(),
+        vec![path.clone()],
+        progress_event.clone(),
+    );
+    let cancel_state = cancel.inner().clone();
+    let undo_state = undo.inner().clone();
+    let preserve = PreserveOptions::from_request(preserve, PreserveOptions::NONE);
+    let task_app = app.clone();
+    let work = async move {
+        let task = tauri::async_runtime::spawn_blocking(move || {
+            do_extract_members(
+                task_app,
+                cancel_state,
+                undo_state,
+                path,
+                members,
+                dest,
+                password.as_deref(),
+                progress_event,
+                preserve,
+            )
+        });
+        match task.await {
+            Ok(result) => result,
+            Err(error) => Err(DecompressError::new(
+                DecompressErrorCode::TaskFailed,
+                format!("Extraction task failed: {error}"),
+            )),
+        }
+    };
+    map_api_result(run_job(&app, job, extraction_cancelled, work).await)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn do_extract_members(
+    app: tauri::AppHandle,
+    cancel_state: CancelState,
+    undo: UndoState,
+    path: String,
+    members: Vec<String>,
+    dest: String,
+    password: Option<&str>,
+    progress_event: Option<String>,
+    preserve: PreserveOptions,
+) -> DecompressResult<ExtractMembersResult> {
+    let (archive_path, _) = resolve_archive_path(&path)?;
+    let dest_dir = resolve_destination_dir(&dest)?;
+    let targets = plan_targets(&dest_dir, selected_members(&members)?)?;
+
+    let cancel_guard: Option<CancelGuard> = progress_event
+        .as_ref()
+        .map(|evt| cancel_state.register(evt.clone()))
+        .transpose()
+        .map_err(|error| {
+            DecompressError::new(
+                DecompressErrorCode::TaskFailed,
+                format!("Failed to register cancel: {error}"),
+            )
+        })?;
+    let cancel_token = cancel_guard.as_ref().map(|guard| guard.token());
+    let cancel = cancel_token.as_deref();
+    let progress = progress_event.as_ref().map(|evt| {
+        let total = selected_size(&archive_path, &targets, password, cancel);
+        ProgressEmitter::new(app.clone(), evt.clone(), total)
+    });
+
+    let preserve = MetadataPreserver::new(preserve);
+    let stats = extract_members(
+        &archive_path,
+        &targets,
+        &dest_dir,
+        &preserve,
+        progress.as_ref(),
+        cancel,
+        password,
+    )?;
+    if let Some(progress) = progress.as_ref() {
+        progress.finish();
+    }
+
+    let extracted: Vec<PathBuf> = targets
+        .iter()
+        .map(|target| dest_dir.join(&target.target_name))
+        .collect();
+    let mut actions: Vec<Action> = extracted
+        .iter()
+        .map(|path| Action::Create {
+            path: path.clone(),
+            backup: temp_backup_path(path),
+        })
+        .collect();
+    let action = if actions.len() == 1 {
+        actions.remove(0)
+    } else {
+        Action::Batch(actions)
+    };
+    let _ = undo.record_applied(action);
+
+    Ok(ExtractMembersResult {
+        extracted: extracted
+            .iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect(),
+        skipped_symlinks: stats.symlinks.load(Ordering::Relaxed),
+        skipped_entries: stats.unsupported.load(Ordering::Relaxed),
+        unpreserved: preserve.into_report(),
+    })
+}
+
+fn resolve_destination_dir(dest: &str) -> DecompressResult<PathBuf> {
+    let dest_dir = sanitize_path_follow(dest, true).map_err(DecompressError::from)?;
+    check_no_symlink_components(&dest_dir).map_err(DecompressError::from)?;
+    let is_dir = fs::metadata(&dest_dir)
+        .map(|meta| meta.is_dir())
+        .map_err(|e| {
+            DecompressError::from_external_message(format!(
+                "Failed to read destination metadata: {e}"
+            ))
+        })?;
+    if !is_dir {
+        return Err(DecompressError::new(
+            DecompressErrorCode::InvalidInput,
+            "Extraction destination must be a folder",
+        ));
+    }
+    Ok(dest_dir)
+}
+
+/// Cleans the requested member paths with the same traversal checks extraction applies to
+/// entry names, and drops members already covered by a selected folder.
+fn selected_members(raw: &[String]) -> DecompressResult<Vec<PathBuf>> {
+    if raw.is_empty() {
+        return Err(DecompressError::new(
+            DecompressErrorCode::InvalidInput,
+            "No archive members selected",
+        ));
+    }
+    let mut members = Vec::with_capacity(raw.len());
+    for name in raw {
+        let normalized = name.replace('\\', "/");
+        let member = clean_relative_path(Path::new(&normalized)).map_err(|_| {
+            DecompressError::new(
+                DecompressErrorCode::InvalidPath,
+                format!("Invalid archive member path: {name}"),
+            )
+        })?;
+        if member.as_os_str().is_empty() {
+            return Err(DecompressError::new(
+                DecompressErrorCode::InvalidInput,
+                "Archive member path is empty; extract the archive to get all of it",
+            ));
+        }
+        members.push(member);
+    }
+    members.sort();
+    let mut kept: Vec<PathBuf> = Vec::with_capacity(members.len());
+    for member in members {
+        if !kept.iter().any(|folder| member.starts_with(folder)) {
+            kept.push(member);
+        }
+    }
+    Ok(kept)
+}
+
+/// Names each member after its last component, numbered when the destination or an earlier
+/// member already has that name.
+fn plan_targets(dest_dir: &Path, members: Vec<PathBuf>) -> DecompressResult<Vec<MemberTarget>> {
+    let mut claimed: HashSet<OsString> = HashSet::new();
+    let mut targets = Vec::with_capacity(members.len());
+    for member in members {
+        let name = member.file_name().ok_or_else(|| {
+            DecompressError::new(
+                DecompressErrorCode::InvalidPath,
+                format!("Invalid archive member path: {}", member.display()),
+            )
+        })?;
+        let target_name = free_name(dest_dir, name, &claimed)?;
+        claimed.insert(target_name.clone());
+        targets.push(MemberTarget {
+            member,
+            target_name,
+        });
+    }
+    Ok(targets)
+}
+
+fn free_name(
+    dest_dir: &Path,
+    name: &OsStr,
+    claimed: &HashSet<OsString>,
+) -> DecompressResult<OsString> {
+    let path = Path::new(name);
+    let stem = path.file_stem().unwrap_or(name).to_string_lossy();
+    let mut candidate = name.to_os_string();
+    let mut idx = 0usize;
+    loop {
+        if !claimed.contains(&candidate) && !path_exists_nofollow(&dest_dir.join(&candidate))? {
+            return Ok(candidate);
+        }
+        idx = idx.saturating_add(1);
+        candidate = match path.extension() {
+            Some(ext) => format!("{stem}-{idx}.{}", ext.to_string_lossy()).into(),
+            None => format!("{stem}-{idx}").into(),
+        };
+    }
+}
+
+/// Bytes the selected members hold, for progress. Progress falls back to a total of one byte
+/// when the archive cannot be listed up front.
+fn selected_size(
+    archive: &Path,
+    targets: &[MemberTarget],
+    password: Option<&str>,
+    cancel: Option<&AtomicBool>,
+) -> u64 {
+    let budget = ExtractBudget::new(EXTRACT_TOTAL_BYTES_CAP, EXTRACT_TOTAL_ENTRIES_CAP);
+    let Ok(members) = list_members(archive, password, &budget, cancel) else {
+        return 1;
+    };
+    members
+        .iter()
+        .filter(|member| !member.is_dir)
+        .filter(|member| {
+            let path = Path::new(&member.path);
+            targets
+                .iter()
+                .any(|target| path.starts_with(&target.member))
+        })
+        .fold(0u64, |total, member| total.saturating_add(member.size))
+        .max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{plan_targets, selected_members};
+    use crate::commands::decompress::browse::extract_members;
+    use crate::errors::domain::DomainError;
+    use crate::fs_utils::{unique_temp_dir, MetadataPreserver};
+    use std::{
+        ffi::OsString,
+        fs::{self, File},
+        path::PathBuf,
+    };
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    #[test]
+    fn members_are_cleaned_and_folders_cover_their_contents() {
+        let members = selected_members(&strings(&[
+            "src/lib.rs",
+            "src/",
+            "./docs\\a.txt",
+            "src/x/y",
+        ]))
+        .expect("members");
+        assert_eq!(
+            members,
+            vec![PathBuf::from("docs/a.txt"), PathBuf::from("src")]
+        );
+
+        for bad in ["../etc/passwd", "/etc/passwd", "docs/../../x"] {
+            let error = selected_members(&strings(&[bad])).expect_err("traversal rejected");
+            assert_eq!(error.code_str(), "invalid_path");
+        }
+        assert_eq!(
+            selected_members(&[]).expect_err("empty").code_str(),
+            "invalid_input"
+        );
+        assert_eq!(
+            selected_members(&strings(&["./"]))
+                .expect_err("root")
+                .code_str(),
+            "invalid_input"
+        );
+    }
+
+    #[test]
+    fn targets_get_free_names_in_the_destination() {
+        let dest = unique_temp_dir("names");
+        File::create(dest.join("report.txt")).expect("existing file");
+        let targets = plan_targets(
+            &dest,
+            vec![
+                PathBuf::from("a/report.txt"),
+                PathBuf::from("b/report.txt"),
+                PathBuf::from("src"),
+            ],
+        )
+        .expect("targets");
+        let names: Vec<OsString> = targets.into_iter().map(|t| t.target_name).collect();
+        assert_eq!(names, vec!["report-1.txt", "report-2.txt", "src"]);
+        let _ = fs::remove_dir_all(dest);
+    }
+
+    #[test]
+    fn extracts_a_tar_subtree_and_rolls_back_on_missing_member() {
+        let root = unique_temp_dir("tar");
+        let src = root.join("src");
+        fs::create_dir_all(src.join("docs/deep")).expect("create tree");
+        fs::write(src.join("docs/deep/a.txt"), b"a").expect("write a");
+        fs::write(src.join("docs/b.txt"), b"b").expect("write b");
+        fs::write(src.join("other.txt"), b"other").expect("write other");
+        let archive = root.join("sample.tar");
+        let mut builder = tar::Builder::new(File::create(&archive).expect("create tar"));
+        builder.append_dir_all("pkg", &src).expect("append tree");
+        builder.finish().expect("finish tar");
+        drop(builder);
+
+        let dest = root.join("out");
+        fs::create_dir_all(&dest).expect("create destination");
+        let targets = plan_targets(
+            &dest,
+            selected_members(&strings(&["pkg/docs/deep", "pkg/docs/b.txt"])).expect("members"),
+        )
+        .expect("targets");
+        extract_members(
+            &archive,
+            &targets,
+            &dest,
+            &MetadataPreserver::disabled(),
+            None,
+            None,
+            None,
+        )
+        .expect("extract members");
+        assert_eq!(fs::read(dest.join("deep/a.txt")).expect("read a"), b"a");
+        assert_eq!(fs::read(dest.join("b.txt")).expect("read b"), b"b");
+        assert!(!dest.join("other.txt").exists());
+
+        let rollback = root.join("rollback");
+        fs::create_dir_all(&rollback).expect("create rollback destination");
+        let targets = plan_targets(
+            &rollback,
+            selected_members(&strings(&["pkg/other.txt", "pkg/missing.txt"])).expect("members"),
+        )
+        .expect("targets");
+        let error = extract_members(
+            &archive,
+            &targets,
+            &rollback,
+            &MetadataPreserver::disabled(),
+            None,
+            None,
+            None,
+        )
+        .expect_err("missing member");
+        assert_eq!(error.code_str(), "not_found");
+        assert_eq!(fs::read_dir(&rollback).expect("list").count(), 0);
+        let _ = fs::remove_dir_all(root);
+    }
+}
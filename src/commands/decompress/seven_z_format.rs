@@ -1,24 +1,53 @@
 use std::{
     borrow::Cow,
-    io::{self, BufWriter},
+    io::{self, BufWriter, Read},
     path::{Path, PathBuf},
     sync::atomic::AtomicBool,
 };
 
 use sevenz_rust2::{
-    decompress_file_with_extract_fn, Archive as SevenZArchive, Error as SevenZError,
+    decompress_with_extract_fn_and_password, Archive as SevenZArchive, ArchiveEntry,
+    Error as SevenZError, Password,
 };
 
 use super::error::{DecompressError, DecompressResult};
 use super::util::{
     clean_relative_path, copy_with_progress, ensure_dir_nofollow, first_component, is_cancelled,
-    open_unique_file, path_exists_nofollow, CreatedPaths, ExtractBudget, ProgressEmitter,
-    SkipStats, CHUNK, EXTRACT_TOTAL_ENTRIES_CAP,
+    pause_checkpoint, record_unread_metadata, CreatedPaths, EntryLayout, ExtractBudget, Placement,
+    ProgressEmitter, SkipStats, CHUNK, EXTRACT_TOTAL_ENTRIES_CAP, SYMLINK_TARGET_MAX,
 };
+use super::volumes::VolumeReader;
 use crate::errors::domain::DomainError;
+use crate::fs_utils::{EntryFacts, MetadataPreserver};
 
-pub(super) fn single_root_in_7z(path: &Path) -> DecompressResult<Option<PathBuf>> {
-    let archive = SevenZArchive::open(path).map_err(|e| format!("Failed to read 7z: {e}"))?;
+/// Set by 7-Zip and p7zip when the high 16 bits of the attributes hold a unix mode.
+const FILE_ATTRIBUTE_UNIX_EXTENSION: u32 = 0x8000;
+
+/// Opens the archive headers, which 7-Zip encrypts along with the data when asked to. A split
+/// set is read on through its later volumes.
+pub(super) fn open_7z(path: &Path, password: Option<&str>) -> DecompressResult<SevenZArchive> {
+    SevenZArchive::read(&mut VolumeReader::open(path)?, &seven_z_password(password))
+        .map_err(|e| map_7z_error("Failed to read 7z", e))
+}
+
+fn seven_z_password(password: Option<&str>) -> Password {
+    password.map_or_else(Password::empty, Password::from)
+}
+
+/// A missing or wrong password gets its own error, so the UI can ask for one.
+fn map_7z_error(context: &str, error: SevenZError) -> DecompressError {
+    match error {
+        SevenZError::PasswordRequired => DecompressError::password_required(),
+        SevenZError::MaybeBadPassword(_) => DecompressError::invalid_password(),
+        error => DecompressError::from_external_message(format!("{context}: {error}")),
+    }
+}
+
+pub(super) fn single_root_in_7z(
+    path: &Path,
+    password: Option<&str>,
+) -> DecompressResult<Option<PathBuf>> {
+    let archive = open_7z(path, password)?;
     let mut root: Option<PathBuf> = None;
     let mut entries_seen = 0u64;
     for entry in archive.files {
@@ -62,18 +91,25 @@ pub(super) fn single_root_in_7z(path: &Path) -> DecompressResult<Option<PathBuf>
 pub(super) fn extract_7z(
     archive_path: &Path,
     dest_dir: &Path,
-    strip_prefix: Option<&Path>,
+    layout: EntryLayout<'_>,
+    placement: &mut Placement,
     stats: &SkipStats,
+    preserve: &MetadataPreserver,
     progress: Option<&ProgressEmitter>,
     created: &mut CreatedPaths,
     cancel: Option<&AtomicBool>,
     budget: &ExtractBudget,
+    password: Option<&str>,
 ) -> DecompressResult<()> {
     let mut buf = vec![0u8; CHUNK];
-    decompress_file_with_extract_fn(archive_path, dest_dir, |entry, reader, _dest_path| {
+    let reader = VolumeReader::open(archive_path)?;
+    let password = seven_z_password(password);
+    let skip_error = |e| SevenZError::Io(e, Cow::Borrowed("Failed to skip 7z entry"));
+    decompress_with_extract_fn_and_password(reader, dest_dir, password, |entry, reader, _| {
         budget
             .reserve_entry(1)
             .map_err(|e| SevenZError::Io(e, Cow::Borrowed("Extraction entry cap exceeded")))?;
+        pause_checkpoint(progress);
         if is_cancelled(cancel) {
             return Err(SevenZError::Io(
                 io::Error::new(io::ErrorKind::Interrupted, "cancelled"),
@@ -94,19 +130,33 @@ pub(super) fn extract_7z(
                 return Ok(true);
             }
         };
-        let clean_rel = if let Some(prefix) = strip_prefix {
-            match clean_rel.strip_prefix(prefix) {
-                Ok(stripped) => stripped.to_path_buf(),
-                Err(_) => clean_rel,
-            }
-        } else {
-            clean_rel
+        let Some(clean_rel) = layout.place(clean_rel) else {
+            // Solid blocks decode members in order, so skipped members are still read through.
+            copy_with_progress(reader, io::sink(), None, cancel, budget, &mut buf)
+                .map_err(skip_error)?;
+            return Ok(true);
         };
         if clean_rel.as_os_str().is_empty() {
             return Ok(true);
         }
         let dest_path = dest_dir.join(clean_rel);
 
+        if is_symlink_entry(entry) {
+            let mut target = String::new();
+            if placement.restores_symlinks() {
+                (&mut *reader)
+                    .take(SYMLINK_TARGET_MAX)
+                    .read_to_string(&mut target)
+                    .map_err(|e| {
+                        SevenZError::Io(e, Cow::Borrowed("Failed to read 7z link target"))
+                    })?;
+            }
+            copy_with_progress(reader, io::sink(), None, cancel, budget, &mut buf)
+                .map_err(skip_error)?;
+            placement.add_symlink(&raw_name, dest_path, PathBuf::from(target), stats);
+            return Ok(true);
+        }
+
         if entry.is_directory {
             match ensure_dir_nofollow(&dest_path) {
                 Ok(created_dirs) => {
@@ -135,38 +185,26 @@ pub(super) fn extract_7z(
             }
         }
 
-        if !entry.has_stream {
-            match path_exists_nofollow(&dest_path) {
-                Ok(true) => return Ok(true),
-                Ok(false) => {}
-                Err(e) => {
-                    stats.skip_unsupported(&raw_name, &format!("stat destination failed: {e}"));
-                    return Ok(true);
-                }
+        // Times are not read from 7z entries, so `overwrite_if_newer` keeps what is there.
+        let incoming = EntryFacts {
+            is_dir: false,
+            size: entry.size,
+            modified: None,
+        };
+        let opened = placement
+            .open_file(&raw_name, &dest_path, &incoming, stats, created)
+            .map_err(|e| SevenZError::Other(Cow::Owned(e.message().to_owned())))?;
+        let Some((file, dest_actual)) = opened else {
+            if entry.has_stream {
+                copy_with_progress(reader, io::sink(), progress, cancel, budget, &mut buf)
+                    .map_err(skip_error)?;
             }
-            let (_file, dest_actual) = open_unique_file(&dest_path)
-                .map_err(|e| SevenZError::Other(Cow::Owned(e.message().to_owned())))?;
-            created.record_file(dest_actual);
+            return Ok(true);
+        };
+        record_unread_metadata(preserve, &dest_actual, "7z");
+        if !entry.has_stream {
             return Ok(true);
         }
-
-        match path_exists_nofollow(&dest_path) {
-            Ok(true) => {
-                if let Some(p) = progress {
-                    p.add(entry.size.max(1));
-                }
-                return Ok(true);
-            }
-            Ok(false) => {}
-            Err(e) => {
-                stats.skip_unsupported(&raw_name, &format!("stat destination failed: {e}"));
-                return Ok(true);
-            }
-        }
-
-        let (file, dest_actual) = open_unique_file(&dest_path)
-            .map_err(|e| SevenZError::Other(Cow::Owned(e.message().to_owned())))?;
-        created.record_file(dest_actual);
         let mut out = BufWriter::with_capacity(CHUNK, file);
         copy_with_progress(reader, &mut out, progress, cancel, budget, &mut buf).map_err(|e| {
             SevenZError::Io(
@@ -176,12 +214,24 @@ pub(super) fn extract_7z(
         })?;
         Ok(true)
     })
-    .map_err(|e| DecompressError::from_external_message(format!("Failed to extract 7z: {e}")))
+    .map_err(|e| map_7z_error("Failed to extract 7z", e))?;
+    placement.restore_symlinks(stats, created)
+}
+
+/// 7-Zip on unix stores a symlink as a file holding its target, with a symlink mode in the
+/// unix bits of the attributes.
+fn is_symlink_entry(entry: &ArchiveEntry) -> bool {
+    let attributes = entry.windows_attributes;
+    entry.has_windows_attributes
+        && attributes & FILE_ATTRIBUTE_UNIX_EXTENSION != 0
+        && (attributes >> 16) & 0o170000 == 0o120000
 }
 
-pub(super) fn sevenz_uncompressed_total(path: &Path) -> DecompressResult<u64> {
-    let archive =
-        SevenZArchive::open(path).map_err(|e| format!("Failed to read 7z for total size: {e}"))?;
+pub(super) fn sevenz_uncompressed_total(
+    path: &Path,
+    password: Option<&str>,
+) -> DecompressResult<u64> {
+    let archive = open_7z(path, password)?;
     let mut total = 0u64;
     let mut entries_seen = 0u64;
     for entry in archive.files {
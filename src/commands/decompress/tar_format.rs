@@ -1,30 +1,40 @@
 use std::{
     fs::File,
-    io::{BufReader, BufWriter, Read},
+    io::{BufReader, BufWriter, Read, Write},
     path::{Path, PathBuf},
     sync::atomic::AtomicBool,
+    time::{Duration, UNIX_EPOCH},
 };
 
 use bzip2::read::BzDecoder;
 use flate2::read::GzDecoder;
-use tar::Archive;
+use tar::{Archive, Entry};
 use xz2::read::XzDecoder;
 use zstd::stream::read::Decoder as ZstdDecoder;
 
 use super::error::{DecompressError, DecompressResult};
 use super::util::{
     check_cancel, clean_relative_path, copy_with_progress, ensure_dir_nofollow, first_component,
-    map_copy_err, open_buffered_file, open_unique_file, path_exists_nofollow, CreatedPaths,
-    ExtractBudget, ProgressEmitter, SkipStats, CHUNK, EXTRACT_TOTAL_ENTRIES_CAP,
+    map_copy_err, open_buffered_file, pause_checkpoint, preserve_dir_metadata, CreatedPaths,
+    EntryLayout, ExtractBudget, Placement, ProgressEmitter, SkipStats, CHUNK,
+    EXTRACT_TOTAL_ENTRIES_CAP,
 };
 use super::ArchiveKind;
+use crate::fs_utils::{EntryFacts, MetadataPreserver, PreserveAttr, StoredMetadata};
 
-pub(super) fn single_root_in_tar(
+/// PAX records GNU tar and bsdtar use for extended attributes, ACLs included.
+const PAX_XATTR_PREFIX: &[u8] = b"SCHILY.xattr.";
+/// PAX records holding ACLs in their textual form, which are not restored.
+const PAX_TEXT_ACL_PREFIX: &[u8] = b"SCHILY.acl.";
+
+/// Opens a plain or compressed tar for streaming. Returns `None` for non-tar kinds.
+pub(super) fn open_tar_reader(
     path: &Path,
     kind: ArchiveKind,
-) -> DecompressResult<Option<PathBuf>> {
+    context: &str,
+) -> DecompressResult<Option<Box<dyn Read>>> {
     let file = File::open(path).map_err(|e| {
-        DecompressError::from_external_message(format!("Failed to open tar for root: {e}"))
+        DecompressError::from_external_message(format!("Failed to open tar for {context}: {e}"))
     })?;
     let reader = BufReader::with_capacity(CHUNK, file);
     let reader: Box<dyn Read> = match kind {
@@ -37,6 +47,16 @@ pub(super) fn single_root_in_tar(
         })?),
         _ => return Ok(None),
     };
+    Ok(Some(reader))
+}
+
+pub(super) fn single_root_in_tar(
+    path: &Path,
+    kind: ArchiveKind,
+) -> DecompressResult<Option<PathBuf>> {
+    let Some(reader) = open_tar_reader(path, kind, "root")? else {
+        return Ok(None);
+    };
     let mut archive = Archive::new(reader);
     let mut root: Option<PathBuf> = None;
     let mut entries_seen = 0u64;
@@ -88,8 +108,10 @@ pub(super) fn single_root_in_tar(
 pub(super) fn extract_tar_with_reader<F>(
     archive_path: &Path,
     dest_dir: &Path,
-    strip_prefix: Option<&Path>,
+    layout: EntryLayout<'_>,
+    placement: &mut Placement,
     stats: &SkipStats,
+    preserve: &MetadataPreserver,
     progress: Option<&ProgressEmitter>,
     created: &mut CreatedPaths,
     cancel: Option<&AtomicBool>,
@@ -102,14 +124,7 @@ where
     let reader = open_buffered_file(archive_path, "open tar")?;
     let reader = wrap(reader)?;
     extract_tar(
-        reader,
-        dest_dir,
-        strip_prefix,
-        stats,
-        progress,
-        created,
-        cancel,
-        budget,
+        reader, dest_dir, layout, placement, stats, preserve, progress, created, cancel, budget,
     )?;
     Ok(())
 }
@@ -118,8 +133,10 @@ where
 pub(super) fn extract_tar<R: Read>(
     reader: R,
     dest_dir: &Path,
-    strip_prefix: Option<&Path>,
+    layout: EntryLayout<'_>,
+    placement: &mut Placement,
     stats: &SkipStats,
+    preserve: &MetadataPreserver,
     progress: Option<&ProgressEmitter>,
     created: &mut CreatedPaths,
     cancel: Option<&AtomicBool>,
@@ -127,9 +144,11 @@ pub(super) fn extract_tar<R: Read>(
 ) -> DecompressResult<()> {
     let mut archive = Archive::new(reader);
     let mut buf = vec![0u8; CHUNK];
+    let mut dir_metadata = Vec::new();
     for entry_result in archive.entries().map_err(|e| {
         DecompressError::from_external_message(format!("Failed to iterate tar: {e}"))
     })? {
+        pause_checkpoint(progress);
         check_cancel(cancel).map_err(|e| {
             DecompressError::from_external_message(map_copy_err("Extraction cancelled", e))
         })?;
@@ -148,11 +167,11 @@ pub(super) fn extract_tar<R: Read>(
             .into_owned();
         let raw_str = raw_path.to_string_lossy().to_string();
 
-        if entry_type.is_symlink() || entry_type.is_hard_link() {
-            stats.skip_symlink(&raw_str);
+        if entry_type.is_hard_link() {
+            stats.skip_symlink(&raw_str, "hard links are not restored");
             continue;
         }
-        if !(entry_type.is_dir() || entry_type.is_file()) {
+        if !(entry_type.is_dir() || entry_type.is_file() || entry_type.is_symlink()) {
             stats.skip_unsupported(&raw_str, "unsupported type");
             continue;
         }
@@ -167,13 +186,8 @@ pub(super) fn extract_tar<R: Read>(
             continue;
         }
 
-        let clean_rel = if let Some(prefix) = strip_prefix {
-            match clean_rel.strip_prefix(prefix) {
-                Ok(stripped) => stripped.to_path_buf(),
-                Err(_) => clean_rel,
-            }
-        } else {
-            clean_rel
+        let Some(clean_rel) = layout.place(clean_rel) else {
+            continue;
         };
 
         if clean_rel.as_os_str().is_empty() {
@@ -181,12 +195,24 @@ pub(super) fn extract_tar<R: Read>(
         }
 
         let dest_path = dest_dir.join(clean_rel);
+        if entry_type.is_symlink() {
+            match entry.link_name() {
+                Ok(Some(target)) => {
+                    let target = target.into_owned();
+                    placement.add_symlink(&raw_str, dest_path, target, stats);
+                }
+                _ => stats.skip_symlink(&raw_str, "missing link target"),
+            }
+            continue;
+        }
+        let stored = tar_stored_metadata(&mut entry, &dest_path, preserve);
         if entry_type.is_dir() {
             match ensure_dir_nofollow(&dest_path) {
                 Ok(created_dirs) => {
                     for dir in created_dirs {
                         created.record_dir(dir);
                     }
+                    dir_metadata.push((dest_path, stored));
                 }
                 Err(e) => {
                     stats.skip_unsupported(&raw_str, &format!("create dir failed: {e}"));
@@ -194,21 +220,6 @@ pub(super) fn extract_tar<R: Read>(
             }
             continue;
         }
-        match path_exists_nofollow(&dest_path) {
-            Ok(true) => {
-                if let Some(p) = progress {
-                    let sz = entry.size();
-                    p.add(sz);
-                }
-                continue;
-            }
-            Ok(false) => {}
-            Err(e) => {
-                stats.skip_unsupported(&raw_str, &format!("stat destination failed: {e}"));
-                continue;
-            }
-        }
-
         if let Some(parent) = dest_path.parent() {
             match ensure_dir_nofollow(parent) {
                 Ok(created_dirs) => {
@@ -222,16 +233,77 @@ pub(super) fn extract_tar<R: Read>(
                 }
             }
         }
-        let (file, actual_path) = open_unique_file(&dest_path)?;
-        created.record_file(actual_path);
+        let incoming = EntryFacts {
+            is_dir: false,
+            size: entry.size(),
+            modified: entry
+                .header()
+                .mtime()
+                .ok()
+                .map(|secs| UNIX_EPOCH + Duration::from_secs(secs)),
+        };
+        let Some((file, actual_path)) =
+            placement.open_file(&raw_str, &dest_path, &incoming, stats, created)?
+        else {
+            if let Some(p) = progress {
+                p.add(entry.size());
+            }
+            continue;
+        };
         let mut out = BufWriter::with_capacity(CHUNK, file);
-        copy_with_progress(&mut entry, &mut out, progress, cancel, budget, &mut buf).map_err(
-            |e| DecompressError::from_external_message(map_copy_err("write tar entry", e)),
-        )?;
+        copy_with_progress(&mut entry, &mut out, progress, cancel, budget, &mut buf)
+            .and_then(|_| out.flush())
+            .map_err(|e| {
+                DecompressError::from_external_message(map_copy_err("write tar entry", e))
+            })?;
+        drop(out);
+        preserve.apply_stored(&actual_path, &stored);
     }
+    placement.restore_symlinks(stats, created)?;
+    preserve_dir_metadata(preserve, dir_metadata);
     Ok(())
 }
 
+/// Mode, mtime and PAX extended attributes of a tar entry, read only when asked for.
+fn tar_stored_metadata<R: Read>(
+    entry: &mut Entry<'_, R>,
+    dest_path: &Path,
+    preserve: &MetadataPreserver,
+) -> StoredMetadata {
+    let options = preserve.options();
+    if options.is_empty() {
+        return StoredMetadata::default();
+    }
+    let header = entry.header();
+    let mut stored = StoredMetadata {
+        mode: header.mode().ok(),
+        modified: header
+            .mtime()
+            .ok()
+            .map(|secs| UNIX_EPOCH + Duration::from_secs(secs)),
+        xattrs: Vec::new(),
+    };
+    if options.xattr || options.acl {
+        if let Ok(Some(extensions)) = entry.pax_extensions() {
+            for extension in extensions.flatten() {
+                let key = extension.key_bytes();
+                if let Some(name) = key.strip_prefix(PAX_XATTR_PREFIX) {
+                    stored
+                        .xattrs
+                        .push((name.to_vec(), extension.value_bytes().to_vec()));
+                } else if key.starts_with(PAX_TEXT_ACL_PREFIX) {
+                    preserve.record(
+                        dest_path,
+                        PreserveAttr::Acl,
+                        "textual tar ACLs are not restored",
+                    );
+                }
+            }
+        }
+    }
+    stored
+}
+
 pub(super) fn tar_uncompressed_total(path: &Path) -> DecompressResult<u64> {
     let file = File::open(path).map_err(|e| {
         DecompressError::from_external_message(format!("Failed to open tar for total: {e}"))
@@ -1,16 +1,19 @@
 use std::{
+    ffi::OsString,
     fs,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{
         atomic::{AtomicUsize, Ordering},
-        Arc,
+        Arc, Mutex,
     },
 };
 
-use crate::fs_utils::debug_log;
+use crate::fs_utils::{debug_log, MetadataPreserver, PreserveAttr, StoredMetadata};
+use crate::undo::move_with_fallback;
 
 mod budget;
 mod path_ops;
+mod placement;
 mod stream_io;
 
 pub(super) const CHUNK: usize = 4 * 1024 * 1024;
@@ -18,40 +21,139 @@ pub(super) const EXTRACT_TOTAL_BYTES_CAP: u64 = 100_000_000_000; // 100 GB
 pub(super) const EXTRACT_TOTAL_ENTRIES_CAP: u64 = 2_000_000; // 2 million entries
 pub(super) const EXTRACT_MIN_FREE_DISK_RESERVE: u64 = 1_073_741_824; // 1 GiB
 pub(super) const EXTRACT_DISK_CHECK_INTERVAL_BYTES: u64 = 256 * 1024 * 1024; // 256 MiB
+pub(super) const SYMLINK_TARGET_MAX: u64 = 4096; // PATH_MAX
 
 pub(super) use budget::{
     available_disk_bytes, effective_extract_bytes_cap, DiskSpaceGuard, ExtractBudget,
 };
 pub(super) use path_ops::{
     clean_relative_path, create_unique_dir_nofollow, ensure_dir_nofollow, first_component,
-    open_unique_file, path_exists_nofollow, strip_known_suffixes,
+    numbered_path, open_unique_file, path_exists_nofollow, strip_known_suffixes,
+};
+pub(super) use placement::Placement;
+pub use placement::{
+    EntryOutcome, ExtractDestination, ExtractOptions, ExtractedEntry, SymlinkPolicy,
 };
 pub(super) use stream_io::{
     check_cancel, copy_with_progress, is_cancelled, map_copy_err, map_io, open_buffered_file,
-    ProgressEmitter,
+    pause_checkpoint, ProgressEmitter,
 };
 
+/// Skip counters plus the outcome of every file and symlink entry.
 #[derive(Default, Clone)]
 pub(super) struct SkipStats {
     pub(super) symlinks: Arc<AtomicUsize>,
     pub(super) unsupported: Arc<AtomicUsize>,
+    outcomes: Arc<Mutex<Vec<ExtractedEntry>>>,
 }
 
 impl SkipStats {
-    pub(super) fn skip_symlink(&self, path: &str) {
+    pub(super) fn skip_symlink(&self, path: &str, reason: &str) {
         self.symlinks.fetch_add(1, Ordering::Relaxed);
-        debug_log(&format!("Skipping symlink entry while extracting: {path}"));
+        debug_log(&format!("Skipping symlink entry {path}: {reason}"));
+        self.push(path, EntryOutcome::Skipped, None, Some(reason));
     }
 
     pub(super) fn skip_unsupported(&self, path: &str, reason: &str) {
         self.unsupported.fetch_add(1, Ordering::Relaxed);
         debug_log(&format!("Skipping unsupported entry {path}: {reason}"));
+        self.push(path, EntryOutcome::Skipped, None, Some(reason));
+    }
+
+    /// An entry left out because its path was taken; not counted as unsupported.
+    pub(super) fn skip_existing(&self, path: &str, reason: &str) {
+        self.push(path, EntryOutcome::Skipped, None, Some(reason));
+    }
+
+    pub(super) fn record(&self, path: &str, outcome: EntryOutcome, dest: &Path) {
+        let dest = dest.to_string_lossy();
+        self.push(path, outcome, Some(dest.as_ref()), None);
+    }
+
+    pub(super) fn take_outcomes(&self) -> Vec<ExtractedEntry> {
+        self.outcomes
+            .lock()
+            .map(|mut outcomes| std::mem::take(&mut *outcomes))
+            .unwrap_or_default()
+    }
+
+    fn push(&self, path: &str, outcome: EntryOutcome, dest: Option<&str>, reason: Option<&str>) {
+        if let Ok(mut outcomes) = self.outcomes.lock() {
+            outcomes.push(ExtractedEntry {
+                entry: path.to_string(),
+                outcome,
+                path: dest.map(str::to_string),
+                reason: reason.map(str::to_string),
+            });
+        }
     }
 }
 
+/// An archive member to write out, and the name it gets in the destination directory.
+pub(crate) struct MemberTarget {
+    /// Clean relative path of the member inside the archive.
+    pub(crate) member: PathBuf,
+    pub(crate) target_name: OsString,
+}
+
+/// Decides where each archive entry lands below the destination directory.
+#[derive(Clone, Copy)]
+pub(super) enum EntryLayout<'a> {
+    /// Every entry at its archive path, minus the single root folder when one is given.
+    Tree(Option<&'a Path>),
+    /// Only the selected members and their contents, each under its target name.
+    Members(&'a [MemberTarget]),
+}
+
+impl EntryLayout<'_> {
+    /// Maps a clean archive path to its path below the destination, or None to skip the entry.
+    pub(super) fn place(self, clean_rel: PathBuf) -> Option<PathBuf> {
+        match self {
+            Self::Tree(Some(prefix)) => match clean_rel.strip_prefix(prefix) {
+                Ok(stripped) => Some(stripped.to_path_buf()),
+                Err(_) => Some(clean_rel),
+            },
+            Self::Tree(None) => Some(clean_rel),
+            Self::Members(targets) => targets.iter().find_map(|target| {
+                let rest = clean_rel.strip_prefix(&target.member).ok()?;
+                let placed = PathBuf::from(&target.target_name);
+                Some(if rest.as_os_str().is_empty() {
+                    placed
+                } else {
+                    placed.join(rest)
+                })
+            }),
+        }
+    }
+}
+
+/// Applies stored directory metadata once all entries are written, deepest directories first
+/// so creating children does not move a parent's restored times again.
+pub(super) fn preserve_dir_metadata(
+    preserve: &MetadataPreserver,
+    mut dirs: Vec<(PathBuf, StoredMetadata)>,
+) {
+    if preserve.options().is_empty() {
+        return;
+    }
+    dirs.sort_by_key(|(path, _)| std::cmp::Reverse(path.components().count()));
+    for (path, stored) in &dirs {
+        preserve.apply_stored(path, stored);
+    }
+}
+
+/// Reports times and mode as dropped for formats whose entry metadata is not read back.
+pub(super) fn record_unread_metadata(preserve: &MetadataPreserver, path: &Path, format: &str) {
+    let reason = format!("not read from {format} archives");
+    preserve.record(path, PreserveAttr::Times, reason.as_str());
+    preserve.record(path, PreserveAttr::Mode, reason);
+}
+
 pub(super) struct CreatedPaths {
     pub(super) files: Vec<PathBuf>,
     pub(super) dirs: Vec<PathBuf>,
+    /// Items that were overwritten, with the undo backup each was moved to.
+    pub(super) replaced: Vec<(PathBuf, PathBuf)>,
     active: bool,
 }
 
@@ -60,6 +162,7 @@ impl Default for CreatedPaths {
         Self {
             files: Vec::new(),
             dirs: Vec::new(),
+            replaced: Vec::new(),
             active: true,
         }
     }
@@ -74,6 +177,10 @@ impl CreatedPaths {
         self.dirs.push(path);
     }
 
+    pub(super) fn record_replaced(&mut self, path: PathBuf, backup: PathBuf) {
+        self.replaced.push((path, backup));
+    }
+
     pub(super) fn disarm(&mut self) {
         self.active = false;
     }
@@ -91,16 +198,23 @@ impl Drop for CreatedPaths {
         for dir in self.dirs.iter().rev() {
             let _ = fs::remove_dir_all(dir);
         }
+        // Then put back what was overwritten.
+        for (path, backup) in self.replaced.iter().rev() {
+            let _ = move_with_fallback(backup, path);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{copy_with_progress, open_unique_file, CreatedPaths, ExtractBudget, CHUNK};
+    use super::{
+        copy_with_progress, open_unique_file, CreatedPaths, EntryLayout, ExtractBudget,
+        MemberTarget, CHUNK,
+    };
     use crate::errors::domain::DomainError;
     use std::fs;
     use std::io::{self, Read};
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
     use std::sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
@@ -353,4 +467,39 @@ mod tests {
         assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
         assert_eq!(writer.data.len(), CHUNK);
     }
+
+    #[test]
+    fn entry_layout_places_tree_and_selected_members() {
+        let strip = Path::new("root");
+        assert_eq!(
+            EntryLayout::Tree(Some(strip)).place(PathBuf::from("root/a.txt")),
+            Some(PathBuf::from("a.txt"))
+        );
+        assert_eq!(
+            EntryLayout::Tree(None).place(PathBuf::from("root/a.txt")),
+            Some(PathBuf::from("root/a.txt"))
+        );
+
+        let targets = [
+            MemberTarget {
+                member: PathBuf::from("docs/report.txt"),
+                target_name: "report-1.txt".into(),
+            },
+            MemberTarget {
+                member: PathBuf::from("src"),
+                target_name: "src".into(),
+            },
+        ];
+        let layout = EntryLayout::Members(&targets);
+        assert_eq!(
+            layout.place(PathBuf::from("docs/report.txt")),
+            Some(PathBuf::from("report-1.txt"))
+        );
+        assert_eq!(
+            layout.place(PathBuf::from("src/lib/mod.rs")),
+            Some(PathBuf::from("src/lib/mod.rs"))
+        );
+        assert_eq!(layout.place(PathBuf::from("docs/other.txt")), None);
+        assert_eq!(layout.place(PathBuf::from("srcs/x")), None);
+    }
 }
@@ -15,8 +15,6 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use crate::fs_utils::unique_path;
-
 use crate::commands::decompress::error::{DecompressError, DecompressResult};
 
 pub(crate) fn clean_relative_path(path: &Path) -> DecompressResult<PathBuf> {
@@ -415,6 +413,8 @@ pub(crate) fn open_unique_file(dest_path: &Path) -> DecompressResult<(File, Path
         .map_err(|e| format!("Failed to resolve path {}: {e}", dest_path.display()))?;
     #[cfg(not(all(unix, target_os = "linux")))]
     let mut candidate = dest_path.to_path_buf();
+    let base = candidate.clone();
+    let mut idx = 0usize;
     loop {
         #[cfg(all(unix, target_os = "linux"))]
         let create_result = open_unique_file_nofollow(&candidate);
@@ -427,7 +427,8 @@ pub(crate) fn open_unique_file(dest_path: &Path) -> DecompressResult<(File, Path
         match create_result {
             Ok(f) => return Ok((f, candidate)),
             Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
-                candidate = unique_path(&candidate);
+                idx = idx.saturating_add(1);
+                candidate = numbered_path(&base, idx);
                 continue;
             }
             Err(e) => {
@@ -437,6 +438,20 @@ pub(crate) fn open_unique_file(dest_path: &Path) -> DecompressResult<(File, Path
     }
 }
 
+/// `path` with `-idx` after its stem. Unlike `unique_path` this does not probe the disk, so a
+/// dangling symlink in the way cannot make it hand back the taken name again.
+pub(crate) fn numbered_path(path: &Path, idx: usize) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "item".to_string());
+    let mut candidate = path.with_file_name(format!("{stem}-{idx}"));
+    if let Some(ext) = path.extension() {
+        candidate.set_extension(ext);
+    }
+    candidate
+}
+
 pub(crate) fn strip_known_suffixes(name: &str) -> String {
     let lower = name.to_lowercase();
     for suffix in [
@@ -0,0 +1,461 @@
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    ensure_dir_nofollow, numbered_path, open_unique_file, path_exists_nofollow, CreatedPaths,
+    SkipStats,
+};
+use crate::commands::decompress::error::DecompressResult;
+use crate::fs_utils::{ConflictPolicy, EntryFacts};
+use crate::undo::{move_with_fallback, temp_backup_path};
+
+/// Where an archive is extracted to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtractDestination {
+    /// A fresh folder next to the archive, named after its single root folder or itself.
+    #[default]
+    NewFolder,
+    /// Straight into the archive's folder, merged with what is already there.
+    Here,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymlinkPolicy {
+    #[default]
+    Skip,
+    /// Recreate symlinks whose target stays inside the destination; the rest are skipped.
+    Restore,
+}
+
+/// How an extraction treats the destination. By default an archive goes into a new folder and
+/// symlinks are not restored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ExtractOptions {
+    pub destination: ExtractDestination,
+    /// For entries whose path is taken. When unset, archive entries are skipped and a single
+    /// decompressed file takes the next free name.
+    pub conflict: Option<ConflictPolicy>,
+    pub symlinks: SymlinkPolicy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryOutcome {
+    Written,
+    /// Written in place of an item that was already there; undo brings that item back.
+    Overwritten,
+    /// Written under the next free `name-N` name because the path was taken.
+    Renamed,
+    /// Not written; the reason says why.
+    Skipped,
+    SymlinkRestored,
+}
+
+/// What happened to one file or symlink entry of the archive.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExtractedEntry {
+    /// Path of the entry inside the archive.
+    pub entry: String,
+    pub outcome: EntryOutcome,
+    /// Where the entry was written, when it was.
+    pub path: Option<String>,
+    pub reason: Option<String>,
+}
+
+struct PendingLink {
+    entry: String,
+    path: PathBuf,
+    target: PathBuf,
+}
+
+/// Applies the conflict and symlink policies below one destination root.
+pub(crate) struct Placement {
+    root: PathBuf,
+    conflict: ConflictPolicy,
+    symlinks: SymlinkPolicy,
+    pending_links: Vec<PendingLink>,
+    restored_links: HashSet<PathBuf>,
+}
+
+impl Placement {
+    pub(crate) fn new(root: &Path, options: &ExtractOptions) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            conflict: options.conflict.unwrap_or(ConflictPolicy::Skip),
+            symlinks: options.symlinks,
+            pending_links: Vec::new(),
+            restored_links: HashSet::new(),
+        }
+    }
+
+    /// Whether symlink entries are worth reading; skipped ones never are.
+    pub(crate) fn restores_symlinks(&self) -> bool {
+        self.symlinks == SymlinkPolicy::Restore
+    }
+
+    /// Creates the file for an entry at `dest_path`, whose parent has to exist already. When the
+    /// path is taken the conflict policy decides; `None` means the entry is skipped and its
+    /// outcome is recorded.
+    pub(crate) fn open_file(
+        &self,
+        entry: &str,
+        dest_path: &Path,
+        incoming: &EntryFacts,
+        stats: &SkipStats,
+        created: &mut CreatedPaths,
+    ) -> DecompressResult<Option<(fs::File, PathBuf)>> {
+        let Some(outcome) = self.claim(entry, dest_path, incoming, stats, created)? else {
+            return Ok(None);
+        };
+        let (file, actual) = open_unique_file(dest_path)?;
+        created.record_file(actual.clone());
+        // Someone may have taken the path between the check and the exclusive create.
+        let outcome = if actual != dest_path && outcome == EntryOutcome::Written {
+            EntryOutcome::Renamed
+        } else {
+            outcome
+        };
+        stats.record(entry, outcome, &actual);
+        Ok(Some((file, actual)))
+    }
+
+    /// Queues a symlink entry. Links are only created by `restore_symlinks`, after every file
+    /// and folder is in place, so no entry is ever written through one.
+    pub(crate) fn add_symlink(
+        &mut self,
+        entry: &str,
+        dest_path: PathBuf,
+        target: PathBuf,
+        stats: &SkipStats,
+    ) {
+        if !self.restores_symlinks() {
+            stats.skip_symlink(entry, "symlinks are not restored");
+            return;
+        }
+        self.pending_links.push(PendingLink {
+            entry: entry.to_string(),
+            path: dest_path,
+            target,
+        });
+    }
+
+    /// Creates the queued symlinks whose targets stay inside the destination root.
+    pub(crate) fn restore_symlinks(
+        &mut self,
+        stats: &SkipStats,
+        created: &mut CreatedPaths,
+    ) -> DecompressResult<()> {
+        for link in std::mem::take(&mut self.pending_links) {
+            if let Err(reason) = self.check_link_target(&link.path, &link.target) {
+                stats.skip_symlink(&link.entry, &reason);
+                continue;
+            }
+            if let Some(parent) = link.path.parent() {
+                match ensure_dir_nofollow(parent) {
+                    Ok(created_dirs) => {
+                        for dir in created_dirs {
+                            created.record_dir(dir);
+                        }
+                    }
+                    Err(e) => {
+                        stats.skip_unsupported(&link.entry, &format!("create parent failed: {e}"));
+                        continue;
+                    }
+                }
+            }
+            let incoming = EntryFacts {
+                is_dir: false,
+                size: 0,
+                modified: None,
+            };
+            if self
+                .claim(&link.entry, &link.path, &incoming, stats, created)?
+                .is_none()
+            {
+                continue;
+            }
+            match create_symlink(&link.target, &link.path) {
+                Ok(actual) => {
+                    created.record_file(actual.clone());
+                    stats.record(&link.entry, EntryOutcome::SymlinkRestored, &actual);
+                    self.restored_links.insert(actual);
+                }
+                Err(e) => {
+                    stats.skip_symlink(&link.entry, &format!("create symlink failed: {e}"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the conflict policy when `dest_path` is taken. Returns how the entry will land,
+    /// or `None` after recording why it is skipped.
+    fn claim(
+        &self,
+        entry: &str,
+        dest_path: &Path,
+        incoming: &EntryFacts,
+        stats: &SkipStats,
+        created: &mut CreatedPaths,
+    ) -> DecompressResult<Option<EntryOutcome>> {
+        match path_exists_nofollow(dest_path) {
+            Ok(false) => return Ok(Some(EntryOutcome::Written)),
+            Ok(true) => {}
+            Err(e) => {
+                stats.skip_unsupported(entry, &format!("stat destination failed: {e}"));
+                return Ok(None);
+            }
+        }
+        let existing = match fs::symlink_metadata(dest_path) {
+            Ok(meta) => meta,
+            Err(e) => {
+                stats.skip_unsupported(entry, &format!("stat destination failed: {e}"));
+                return Ok(None);
+            }
+        };
+        match self.conflict {
+            ConflictPolicy::Rename => Ok(Some(EntryOutcome::Renamed)),
+            ConflictPolicy::Skip => {
+                stats.skip_existing(entry, "destination already exists");
+                Ok(None)
+            }
+            policy => {
+                if existing.is_dir() {
+                    stats.skip_existing(entry, "a folder is in the way");
+                    return Ok(None);
+                }
+                if !policy.replaces(incoming, &EntryFacts::from_metadata(&existing)) {
+                    stats.skip_existing(entry, "existing item kept");
+                    return Ok(None);
+                }
+                set_aside(dest_path, created)?;
+                Ok(Some(EntryOutcome::Overwritten))
+            }
+        }
+    }
+
+    /// A link may only point inside the root. Every `..` has to leave a real folder, and no
+    /// folder on the way may be a symlink this extraction did not restore, so the link cannot
+    /// reach out through one.
+    fn check_link_target(&self, link: &Path, target: &Path) -> Result<(), String> {
+        if target.as_os_str().is_empty() {
+            return Err("symlink has an empty target".to_string());
+        }
+        let rel_parent = link
+            .parent()
+            .and_then(|parent| parent.strip_prefix(&self.root).ok())
+            .ok_or_else(|| "symlink lies outside the destination".to_string())?;
+        let mut resolved = PathBuf::new();
+        for component in rel_parent.components().chain(target.components()) {
+            match component {
+                Component::Normal(part) => {
+                    resolved.push(part);
+                    self.check_link_component(&resolved)?;
+                }
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    let left = self.root.join(&resolved);
+                    if !fs::symlink_metadata(&left).is_ok_and(|meta| meta.is_dir()) {
+                        return Err(
+                            "symlink target steps out of a symlink or missing folder".to_string()
+                        );
+                    }
+                    if !resolved.pop() {
+                        return Err("symlink target leaves the destination".to_string());
+                    }
+                }
+                Component::RootDir | Component::Prefix(_) => {
+                    return Err("symlink target is absolute".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_link_component(&self, rel: &Path) -> Result<(), String> {
+        let path = self.root.join(rel);
+        match fs::symlink_metadata(&path) {
+            Ok(meta) if meta.file_type().is_symlink() && !self.restored_links.contains(&path) => {
+                Err("symlink target passes through a symlink outside the archive".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Moves an existing item out of the way before it is overwritten. Items this extraction wrote
+/// itself are simply removed; anything else goes to an undo backup that a rollback restores.
+fn set_aside(path: &Path, created: &mut CreatedPaths) -> DecompressResult<()> {
+    if created.files.iter().any(|file| file == path) {
+        return fs::remove_file(path)
+            .map_err(|e| format!("Failed to replace {}: {e}", path.display()).into());
+    }
+    let backup = temp_backup_path(path);
+    if let Some(parent) = backup.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create backup folder {}: {e}", parent.display()))?;
+    }
+    move_with_fallback(path, &backup)
+        .map_err(|e| format!("Failed to move {} aside: {e}", path.display()))?;
+    created.record_replaced(path.to_path_buf(), backup);
+    Ok(())
+}
+
+/// Creates the link at `path`, or at the next free name when the path got taken meanwhile.
+#[cfg(unix)]
+fn create_symlink(target: &Path, path: &Path) -> io::Result<PathBuf> {
+    let mut candidate = path.to_path_buf();
+    let mut idx = 0usize;
+    loop {
+        match std::os::unix::fs::symlink(target, &candidate) {
+            Ok(()) => return Ok(candidate),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                idx = idx.saturating_add(1);
+                candidate = numbered_path(path, idx);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn create_symlink(_target: &Path, _path: &Path) -> io::Result<PathBuf> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "symlinks cannot be restored on this platform",
+    ))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::fs_utils::unique_temp_dir;
+
+    fn file_facts(size: u64) -> EntryFacts {
+        EntryFacts {
+            is_dir: false,
+            size,
+            modified: None,
+        }
+    }
+
+    fn options(conflict: ConflictPolicy) -> ExtractOptions {
+        ExtractOptions {
+            destination: ExtractDestination::Here,
+            conflict: Some(conflict),
+            symlinks: SymlinkPolicy::Restore,
+        }
+    }
+
+    #[test]
+    fn conflict_policies_skip_rename_or_overwrite_with_rollback() {
+        let root = unique_temp_dir("conflict");
+        let taken = root.join("notes.txt");
+        fs::write(&taken, b"old").expect("seed existing file");
+        let stats = SkipStats::default();
+
+        let skip = Placement::new(&root, &options(ConflictPolicy::Skip));
+        let mut created = CreatedPaths::default();
+        let opened = skip
+            .open_file("notes.txt", &taken, &file_facts(3), &stats, &mut created)
+            .expect("skip");
+        assert!(opened.is_none());
+
+        let rename = Placement::new(&root, &options(ConflictPolicy::Rename));
+        let (_, renamed) = rename
+            .open_file("notes.txt", &taken, &file_facts(3), &stats, &mut created)
+            .expect("rename")
+            .expect("renamed file");
+        assert_ne!(renamed, taken);
+        created.disarm();
+
+        let larger = Placement::new(&root, &options(ConflictPolicy::OverwriteIfLarger));
+        let mut created = CreatedPaths::default();
+        assert!(larger
+            .open_file("notes.txt", &taken, &file_facts(2), &stats, &mut created)
+            .expect("smaller entry")
+            .is_none());
+        {
+            let mut created = CreatedPaths::default();
+            let (mut file, path) = larger
+                .open_file("notes.txt", &taken, &file_facts(9), &stats, &mut created)
+                .expect("overwrite")
+                .expect("overwritten file");
+            assert_eq!(path, taken);
+            io::Write::write_all(&mut file, b"new stuff").expect("write");
+            assert_eq!(created.replaced.len(), 1);
+        }
+        assert_eq!(
+            fs::read(&taken).expect("read restored"),
+            b"old",
+            "rollback brings the overwritten file back"
+        );
+
+        let outcomes: Vec<_> = stats
+            .take_outcomes()
+            .into_iter()
+            .map(|entry| entry.outcome)
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                EntryOutcome::Skipped,
+                EntryOutcome::Renamed,
+                EntryOutcome::Skipped,
+                EntryOutcome::Overwritten,
+            ]
+        );
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn only_symlinks_that_stay_inside_the_root_are_restored() {
+        let base = unique_temp_dir("symlinks");
+        let root = base.join("out");
+        fs::create_dir_all(root.join("docs")).expect("create docs");
+        fs::write(root.join("docs").join("a.txt"), b"a").expect("write target");
+        std::os::unix::fs::symlink(&base, root.join("escape")).expect("seed outside link");
+
+        let stats = SkipStats::default();
+        let mut placement = Placement::new(&root, &options(ConflictPolicy::Skip));
+        let mut created = CreatedPaths::default();
+        for (name, target) in [
+            ("docs/inside", "a.txt"),
+            ("up", "docs/../docs/a.txt"),
+            ("docs/chain", "../up"),
+            ("outside", "../secret"),
+            ("absolute", "/etc/passwd"),
+            ("through", "escape/x"),
+            ("via-missing", "gone/../../x"),
+        ] {
+            placement.add_symlink(name, root.join(name), PathBuf::from(target), &stats);
+        }
+        placement
+            .restore_symlinks(&stats, &mut created)
+            .expect("restore symlinks");
+        created.disarm();
+
+        let restored: Vec<_> = stats
+            .take_outcomes()
+            .into_iter()
+            .filter(|entry| entry.outcome == EntryOutcome::SymlinkRestored)
+            .map(|entry| entry.entry)
+            .collect();
+        assert_eq!(restored, vec!["docs/inside", "up", "docs/chain"]);
+        assert_eq!(
+            fs::read(root.join("docs").join("chain")).expect("read through chain"),
+            b"a"
+        );
+        assert!(fs::symlink_metadata(root.join("outside")).is_err());
+        assert_eq!(stats.symlinks.load(std::sync::atomic::Ordering::Relaxed), 4);
+        let _ = fs::remove_dir_all(base);
+    }
+}
@@ -1,6 +1,6 @@
 use super::{ExtractBudget, CHUNK};
 use crate::commands::decompress::error::DecompressResult;
-use crate::runtime_lifecycle;
+use crate::{runtime_lifecycle, tasks};
 use serde::Serialize;
 use std::{
     fs::File,
@@ -94,6 +94,13 @@ impl ProgressEmitter {
     }
 }
 
+/// Holds the extraction between chunks or entries while its job is paused.
+pub(crate) fn pause_checkpoint(progress: Option<&ProgressEmitter>) {
+    if let Some(p) = progress {
+        tasks::pause_checkpoint(&p.app, &p.event);
+    }
+}
+
 pub(crate) fn is_cancelled(cancel: Option<&AtomicBool>) -> bool {
     cancel.map(|c| c.load(Ordering::Relaxed)).unwrap_or(false)
 }
@@ -130,6 +137,7 @@ pub(crate) fn copy_with_progress<R: Read, W: Write>(
     let mut since_cancel_check = EXTRACT_CANCEL_CHECK_INTERVAL_BYTES;
     loop {
         if since_cancel_check >= EXTRACT_CANCEL_CHECK_INTERVAL_BYTES {
+            pause_checkpoint(progress);
             check_cancel(cancel)?;
             since_cancel_check = 0;
         }
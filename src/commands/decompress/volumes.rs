@@ -0,0 +1,442 @@
+use std::{
+    fs::{self, File},
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use super::error::{DecompressError, DecompressErrorCode, DecompressResult};
+use super::util::map_io;
+use super::ArchiveKind;
+
+/// The zip end of central directory record, which a cut-off split archive lacks.
+const ZIP_EOCD_SIGNATURE: &[u8] = b"PK\x05\x06";
+/// Fixed part of the end of central directory record plus the longest comment.
+const ZIP_EOCD_SEARCH: u64 = 22 + 0xFFFF;
+const SEVEN_Z_SIGNATURE: &[u8] = &[0x37, 0x7A, 0xBC, 0xAF, 0x27, 0x1C];
+const SEVEN_Z_START_HEADER_LEN: u64 = 32;
+
+/// One volume of a split set: `<name>.zip.001` and `<name>.7z.001` as 7-Zip and `split` cut
+/// them, or `<name>.part1.rar` as WinRAR names its volumes.
+#[derive(Debug, Clone)]
+pub(super) struct VolumeName {
+    /// The name up to the volume number, `.part` included for RAR.
+    prefix: String,
+    /// The name after the volume number: `.rar` for RAR, empty otherwise.
+    suffix: String,
+    pub(super) index: u32,
+    /// Zero-padded digits every volume of the set shares.
+    width: usize,
+    pub(super) kind: ArchiveKind,
+}
+
+impl VolumeName {
+    pub(super) fn parse(name: &str) -> Option<Self> {
+        Self::parse_numbered(name).or_else(|| Self::parse_rar_part(name))
+    }
+
+    fn parse_numbered(name: &str) -> Option<Self> {
+        let (head, digits) = name.rsplit_once('.')?;
+        if digits.len() < 3 {
+            return None;
+        }
+        let lower = head.to_lowercase();
+        let kind = [(".zip", ArchiveKind::Zip), (".7z", ArchiveKind::SevenZ)]
+            .into_iter()
+            .find(|(ext, _)| lower.ends_with(ext) && lower.len() > ext.len())
+            .map(|(_, kind)| kind)?;
+        Self::with_number(format!("{head}."), String::new(), digits, kind)
+    }
+
+    fn parse_rar_part(name: &str) -> Option<Self> {
+        let split = name.len().checked_sub(".rar".len())?;
+        let (stem, suffix) = (name.get(..split)?, &name[split..]);
+        if !suffix.eq_ignore_ascii_case(".rar") {
+            return None;
+        }
+        let (base, part) = stem.rsplit_once('.')?;
+        let digits = part.get("part".len()..)?;
+        if base.is_empty() || !part[.."part".len()].eq_ignore_ascii_case("part") {
+            return None;
+        }
+        Self::with_number(
+            format!("{base}.{}", &part[.."part".len()]),
+            suffix.to_string(),
+            digits,
+            ArchiveKind::Rar,
+        )
+    }
+
+    fn with_number(
+        prefix: String,
+        suffix: String,
+        digits: &str,
+        kind: ArchiveKind,
+    ) -> Option<Self> {
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let index = digits.parse().ok().filter(|&index| index > 0)?;
+        Some(Self {
+            prefix,
+            suffix,
+            index,
+            width: digits.len(),
+            kind,
+        })
+    }
+
+    /// The file name of volume `index` of the same set.
+    pub(super) fn volume(&self, index: u32) -> String {
+        format!(
+            "{}{index:0width$}{}",
+            self.prefix,
+            self.suffix,
+            width = self.width
+        )
+    }
+
+    /// The archive's name without the volume number, `photos.zip` for `photos.zip.001` and
+    /// `photos.rar` for `photos.part1.rar`.
+    pub(super) fn archive_name(&self) -> String {
+        match self.kind {
+            ArchiveKind::Rar => {
+                let base = &self.prefix[..self.prefix.len() - ".part".len()];
+                format!("{base}{}", self.suffix)
+            }
+            _ => self.prefix[..self.prefix.len() - 1].to_string(),
+        }
+    }
+}
+
+pub(super) fn split_volume_name(path: &Path) -> Option<VolumeName> {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .and_then(VolumeName::parse)
+}
+
+/// Refuses any volume but the first, which is the only one that starts the archive.
+pub(super) fn check_first_volume(path: &Path) -> DecompressResult<Option<VolumeName>> {
+    let Some(name) = split_volume_name(path) else {
+        return Ok(None);
+    };
+    if name.index != 1 {
+        return Err(DecompressError::new(
+            DecompressErrorCode::InvalidInput,
+            format!(
+                "{} is volume {} of a split archive; open {} instead",
+                name.volume(name.index),
+                name.index,
+                name.volume(1)
+            ),
+        ));
+    }
+    Ok(Some(name))
+}
+
+/// The volumes of the archive at `path` in order: the file alone, or every volume of the split
+/// set it starts. A gap in the numbering is reported by the names that are missing; a missing
+/// last volume can only be told from the archive itself, see [`VolumeReader::open`].
+pub(super) fn archive_volumes(path: &Path) -> DecompressResult<Vec<PathBuf>> {
+    let Some(name) = check_first_volume(path)? else {
+        return Ok(vec![path.to_path_buf()]);
+    };
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let mut volumes = vec![path.to_path_buf()];
+    loop {
+        let next = dir.join(name.volume(volumes.len() as u32 + 1));
+        if !fs::symlink_metadata(&next).is_ok_and(|meta| meta.is_file()) {
+            break;
+        }
+        volumes.push(next);
+    }
+    let found = volumes.len() as u32;
+    let last = fs::read_dir(dir)
+        .map_err(map_io("list archive volumes"))?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().to_str().and_then(VolumeName::parse))
+        .filter(|other| other.prefix == name.prefix && other.suffix == name.suffix)
+        .map(|other| other.index)
+        .max()
+        .unwrap_or(found);
+    if last > found {
+        let missing = (found + 1..last)
+            .map(|index| name.volume(index))
+            .filter(|missing| !dir.join(missing).exists())
+            .collect::<Vec<_>>();
+        return Err(missing_volumes(&missing));
+    }
+    Ok(volumes)
+}
+
+fn missing_volumes(names: &[String]) -> DecompressError {
+    DecompressError::new(
+        DecompressErrorCode::MissingVolume,
+        format!("Split archive is incomplete, missing {}", names.join(", ")),
+    )
+}
+
+/// The set ends at `last` although its archive continues past it.
+pub(super) fn missing_volume_after(last: &Path) -> DecompressError {
+    match split_volume_name(last) {
+        Some(name) => missing_volumes(&[name.volume(name.index + 1)]),
+        None => DecompressError::new(
+            DecompressErrorCode::MissingVolume,
+            format!(
+                "Split archive is incomplete, the volume after {} was not found",
+                last.file_name().unwrap_or_default().to_string_lossy()
+            ),
+        ),
+    }
+}
+
+struct Volume {
+    path: PathBuf,
+    start: u64,
+    len: u64,
+}
+
+/// Reads the volumes of a split archive as the one seekable stream they were cut from. Only one
+/// volume is open at a time.
+pub(super) struct VolumeReader {
+    volumes: Vec<Volume>,
+    len: u64,
+    pos: u64,
+    /// The open volume's index, and where its file cursor is.
+    open: Option<(usize, File, u64)>,
+}
+
+impl VolumeReader {
+    /// Opens the archive at `path`, joined with the volumes after it when it starts a split
+    /// set. A set whose last volumes are missing is refused here, as zip and 7z only notice
+    /// once they are deep into the archive.
+    pub(super) fn open(path: &Path) -> DecompressResult<Self> {
+        let mut reader = Self::from_paths(archive_volumes(path)?)?;
+        if let Some(name) = split_volume_name(path) {
+            if !reader
+                .is_complete(name.kind)
+                .map_err(map_io("read archive volumes"))?
+            {
+                let last = &reader.volumes[reader.volumes.len() - 1].path;
+                return Err(missing_volume_after(last));
+            }
+        }
+        Ok(reader)
+    }
+
+    fn from_paths(paths: Vec<PathBuf>) -> DecompressResult<Self> {
+        let mut volumes = Vec::with_capacity(paths.len());
+        let mut start = 0u64;
+        for path in paths {
+            let len = fs::metadata(&path)
+                .map_err(map_io("read archive volume metadata"))?
+                .len();
+            volumes.push(Volume { path, start, len });
+            start = start.saturating_add(len);
+        }
+        Ok(Self {
+            volumes,
+            len: start,
+            pos: 0,
+            open: None,
+        })
+    }
+
+    /// Whether the archive's end is within the volumes found. Formats without a fixed end are
+    /// taken as complete.
+    fn is_complete(&mut self, kind: ArchiveKind) -> io::Result<bool> {
+        let complete = match kind {
+            ArchiveKind::Zip => {
+                let tail = self.len.min(ZIP_EOCD_SEARCH);
+                self.seek(SeekFrom::Start(self.len - tail))?;
+                let mut buf = Vec::with_capacity(tail as usize);
+                self.by_ref().take(tail).read_to_end(&mut buf)?;
+                buf.windows(ZIP_EOCD_SIGNATURE.len())
+                    .any(|window| window == ZIP_EOCD_SIGNATURE)
+            }
+            ArchiveKind::SevenZ => {
+                let mut header = [0u8; SEVEN_Z_START_HEADER_LEN as usize];
+                self.seek(SeekFrom::Start(0))?;
+                self.read_exact(&mut header)?;
+                let field = |at: usize| {
+                    let mut bytes = [0u8; 8];
+                    bytes.copy_from_slice(&header[at..at + 8]);
+                    u64::from_le_bytes(bytes)
+                };
+                !header.starts_with(SEVEN_Z_SIGNATURE)
+                    || SEVEN_Z_START_HEADER_LEN
+                        .saturating_add(field(12))
+                        .saturating_add(field(20))
+                        <= self.len
+            }
+            _ => true,
+        };
+        self.seek(SeekFrom::Start(0))?;
+        Ok(complete)
+    }
+}
+
+impl Read for VolumeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.pos >= self.len {
+            return Ok(0);
+        }
+        // The first volume that ends past the position; empty volumes are passed over.
+        let idx = self
+            .volumes
+            .partition_point(|volume| volume.start + volume.len <= self.pos);
+        let volume = &self.volumes[idx];
+        let offset = self.pos - volume.start;
+        let (file, at) = match &mut self.open {
+            Some((open, file, at)) if *open == idx => (file, at),
+            slot => {
+                let file = File::open(&volume.path)?;
+                let (_, file, at) = slot.insert((idx, file, 0));
+                (file, at)
+            }
+        };
+        if *at != offset {
+            file.seek(SeekFrom::Start(offset))?;
+            *at = offset;
+        }
+        let want = buf.len().min((volume.len - offset) as usize);
+        let n = file.read(&mut buf[..want])?;
+        if n == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("{} is shorter than it was", volume.path.display()),
+            ));
+        }
+        *at += n as u64;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for VolumeReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(delta) => self.len.checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.pos.checked_add_signed(delta),
+        };
+        self.pos = target.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "seek before the start of the archive",
+            )
+        })?;
+        Ok(self.pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::domain::DomainError;
+    use crate::fs_utils::unique_temp_dir;
+
+    /// Cuts `bytes` into `<name>.001`, `<name>.002`, ... of `size` bytes each.
+    fn write_split(dir: &Path, name: &str, bytes: &[u8], size: usize) -> PathBuf {
+        for (idx, chunk) in bytes.chunks(size).enumerate() {
+            fs::write(dir.join(format!("{name}.{:03}", idx + 1)), chunk).expect("write volume");
+        }
+        dir.join(format!("{name}.001"))
+    }
+
+    #[test]
+    fn parses_split_and_rar_part_names() {
+        let zip = VolumeName::parse("Photos.ZIP.001").expect("zip volume");
+        assert_eq!(
+            (zip.index, zip.volume(12)),
+            (1, "Photos.ZIP.012".to_string())
+        );
+        assert_eq!(zip.archive_name(), "Photos.ZIP");
+        let rar = VolumeName::parse("backup.Part03.rar").expect("rar volume");
+        assert!(matches!(rar.kind, ArchiveKind::Rar));
+        assert_eq!(
+            (rar.index, rar.volume(1)),
+            (3, "backup.Part01.rar".to_string())
+        );
+        assert_eq!(rar.archive_name(), "backup.rar");
+        assert!(VolumeName::parse("notes.txt.001").is_none());
+        assert!(VolumeName::parse("data.7z.01").is_none());
+        assert!(VolumeName::parse("data.7z.000").is_none());
+        assert!(VolumeName::parse("report.rar").is_none());
+        assert!(VolumeName::parse(".part1.rar").is_none());
+    }
+
+    #[test]
+    fn reads_and_seeks_across_volumes() {
+        let dir = unique_temp_dir("reader");
+        let bytes = (0..=255u8).cycle().take(1000).collect::<Vec<_>>();
+        let first = write_split(&dir, "data.7z", &bytes, 300);
+
+        let mut reader = VolumeReader::from_paths(archive_volumes(&first).unwrap()).unwrap();
+        assert_eq!(reader.seek(SeekFrom::End(0)).unwrap(), 1000);
+        reader.rewind().unwrap();
+        let mut all = Vec::new();
+        reader.read_to_end(&mut all).unwrap();
+        assert_eq!(all, bytes);
+
+        let mut window = [0u8; 20];
+        reader.seek(SeekFrom::Start(290)).unwrap();
+        reader.read_exact(&mut window).unwrap();
+        assert_eq!(window, bytes[290..310]);
+        reader.seek(SeekFrom::End(-5)).unwrap();
+        reader.read_exact(&mut window[..5]).unwrap();
+        assert_eq!(window[..5], bytes[995..]);
+        reader.seek(SeekFrom::Current(-700)).unwrap();
+        reader.read_exact(&mut window).unwrap();
+        assert_eq!(window, bytes[300..320]);
+        assert!(reader.seek(SeekFrom::Current(-1000)).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn names_missing_volumes() {
+        let dir = unique_temp_dir("missing");
+        let first = write_split(&dir, "data.zip", &[7u8; 1000], 200);
+        fs::remove_file(dir.join("data.zip.002")).unwrap();
+        fs::remove_file(dir.join("data.zip.004")).unwrap();
+        let err = archive_volumes(&first).unwrap_err();
+        assert_eq!(err.code_str(), "missing_volume");
+        assert!(err
+            .message()
+            .ends_with("missing data.zip.002, data.zip.004"));
+
+        let err = archive_volumes(&dir.join("data.zip.003")).unwrap_err();
+        assert_eq!(err.code_str(), "invalid_input");
+        assert!(err.message().contains("open data.zip.001"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn refuses_a_split_zip_cut_short() {
+        let mut zip = zip::ZipWriter::new(io::Cursor::new(Vec::new()));
+        let stored = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        zip.start_file("notes.txt", stored).unwrap();
+        io::Write::write_all(&mut zip, &[b'x'; 600]).unwrap();
+        let bytes = zip.finish().unwrap().into_inner();
+
+        let dir = unique_temp_dir("zip");
+        let first = write_split(&dir, "notes.zip", &bytes, 256);
+        let mut archive = zip::ZipArchive::new(VolumeReader::open(&first).unwrap()).unwrap();
+        let mut text = String::new();
+        archive
+            .by_name("notes.txt")
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        assert_eq!(text.len(), 600);
+
+        let last = bytes.len().div_ceil(256);
+        fs::remove_file(dir.join(format!("notes.zip.{last:03}"))).unwrap();
+        let err = VolumeReader::open(&first).err().expect("incomplete set");
+        assert_eq!(err.code_str(), "missing_volume");
+        assert!(err
+            .message()
+            .ends_with(&format!("missing notes.zip.{last:03}")));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,28 +1,37 @@
 use std::{
-    fs::File,
-    io::BufWriter,
+    io::{BufWriter, Read, Write},
     path::{Path, PathBuf},
     sync::atomic::AtomicBool,
+    time::SystemTime,
 };
 
-use zip::ZipArchive;
+use chrono::{Local, NaiveDate};
+use zip::{result::ZipError, DateTime as ZipDateTime, ZipArchive};
 
 use super::error::{DecompressError, DecompressResult};
 use super::util::{
     check_cancel, clean_relative_path, copy_with_progress, ensure_dir_nofollow, first_component,
-    map_copy_err, map_io, open_unique_file, path_exists_nofollow, CreatedPaths, ExtractBudget,
-    ProgressEmitter, SkipStats, CHUNK, EXTRACT_TOTAL_ENTRIES_CAP,
+    map_copy_err, pause_checkpoint, preserve_dir_metadata, CreatedPaths, EntryLayout,
+    ExtractBudget, Placement, ProgressEmitter, SkipStats, CHUNK, EXTRACT_TOTAL_ENTRIES_CAP,
+    SYMLINK_TARGET_MAX,
 };
-use crate::fs_utils::debug_log;
+use super::volumes::VolumeReader;
+use crate::fs_utils::{debug_log, EntryFacts, MetadataPreserver, StoredMetadata};
+
+/// Opens a zip, reading on through the volumes after it when it starts a split set.
+pub(super) fn open_zip(path: &Path) -> DecompressResult<ZipArchive<VolumeReader>> {
+    ZipArchive::new(VolumeReader::open(path)?)
+        .map_err(|e| DecompressError::from_external_message(format!("Failed to read zip: {e}")))
+}
 
 pub(super) fn single_root_in_zip(path: &Path) -> DecompressResult<Option<PathBuf>> {
-    let mut archive = ZipArchive::new(File::open(path).map_err(map_io("open zip for root"))?)
-        .map_err(|e| format!("Failed to read zip: {e}"))?;
+    let mut archive = open_zip(path)?;
     let mut root: Option<PathBuf> = None;
     let mut entries_seen = 0u64;
     for i in 0..archive.len() {
+        // Raw access reads headers only, so encrypted entries need no password here.
         let entry = archive
-            .by_index(i)
+            .by_index_raw(i)
             .map_err(|e| format!("Failed to read zip entry {i}: {e}"))?;
         entries_seen = entries_seen.saturating_add(1);
         if entries_seen > EXTRACT_TOTAL_ENTRIES_CAP {
@@ -70,21 +79,26 @@ pub(super) fn single_root_in_zip(path: &Path) -> DecompressResult<Option<PathBuf
 pub(super) fn extract_zip(
     path: &Path,
     dest_dir: &Path,
-    strip_prefix: Option<&Path>,
+    layout: EntryLayout<'_>,
+    placement: &mut Placement,
     stats: &SkipStats,
+    preserve: &MetadataPreserver,
     progress: Option<&ProgressEmitter>,
     created: &mut CreatedPaths,
     cancel: Option<&AtomicBool>,
     budget: &ExtractBudget,
+    password: Option<&str>,
 ) -> DecompressResult<()> {
-    let mut archive = ZipArchive::new(File::open(path).map_err(map_io("open zip"))?)
-        .map_err(|e| format!("Failed to read zip: {e}"))?;
+    let mut archive = open_zip(path)?;
     let mut buf = vec![0u8; CHUNK];
+    let mut dir_metadata = Vec::new();
 
     for i in 0..archive.len() {
-        let mut entry = archive
-            .by_index(i)
-            .map_err(|e| format!("Failed to read zip entry {i}: {e}"))?;
+        let mut entry = match password {
+            Some(password) => archive.by_index_decrypt(i, password.as_bytes()),
+            None => archive.by_index(i),
+        }
+        .map_err(|e| zip_entry_error(i, e))?;
         budget
             .reserve_entry(1)
             .map_err(|e| map_copy_err("Extraction entry cap exceeded", e))?;
@@ -104,14 +118,10 @@ pub(super) fn extract_zip(
                 continue;
             }
         };
-        let clean_rel = if let Some(prefix) = strip_prefix {
-            match clean_rel.strip_prefix(prefix) {
-                Ok(stripped) => stripped.to_path_buf(),
-                Err(_) => clean_rel,
-            }
-        } else {
-            clean_rel
+        let Some(clean_rel) = layout.place(clean_rel) else {
+            continue;
         };
+        pause_checkpoint(progress);
         check_cancel(cancel).map_err(|e| map_copy_err("Extraction cancelled", e))?;
         if clean_rel.as_os_str().is_empty() {
             continue;
@@ -120,11 +130,29 @@ pub(super) fn extract_zip(
             .unix_mode()
             .map(|mode| (mode & 0o170000) == 0o120000)
             .unwrap_or(false);
+        let dest_path = dest_dir.join(clean_rel);
         if is_symlink {
-            stats.skip_symlink(&raw_name);
+            // The entry's data is the link target.
+            if placement.restores_symlinks() {
+                let mut target = String::new();
+                if let Err(e) = (&mut entry)
+                    .take(SYMLINK_TARGET_MAX)
+                    .read_to_string(&mut target)
+                {
+                    stats.skip_symlink(&raw_name, &format!("read link target failed: {e}"));
+                    continue;
+                }
+                placement.add_symlink(&raw_name, dest_path, PathBuf::from(target), stats);
+            } else {
+                stats.skip_symlink(&raw_name, "symlinks are not restored");
+            }
             continue;
         }
-        let dest_path = dest_dir.join(clean_rel);
+        let stored = StoredMetadata {
+            mode: entry.unix_mode(),
+            modified: entry.last_modified().and_then(zip_datetime_to_system_time),
+            xattrs: Vec::new(),
+        };
         if entry.is_dir() || raw_name.ends_with('/') {
             match ensure_dir_nofollow(&dest_path) {
                 Ok(created_dirs) => {
@@ -137,22 +165,9 @@ pub(super) fn extract_zip(
                     continue;
                 }
             }
+            dir_metadata.push((dest_path, stored));
             continue;
         }
-        match path_exists_nofollow(&dest_path) {
-            Ok(true) => {
-                if let Some(p) = progress {
-                    let inc = entry.compressed_size().max(1);
-                    p.add(inc);
-                }
-                continue;
-            }
-            Ok(false) => {}
-            Err(e) => {
-                stats.skip_unsupported(&raw_name, &format!("stat destination failed: {e}"));
-                continue;
-            }
-        }
         if let Some(parent) = dest_path.parent() {
             match ensure_dir_nofollow(parent) {
                 Ok(created_dirs) => {
@@ -166,27 +181,61 @@ pub(super) fn extract_zip(
                 }
             }
         }
-        let (file, actual_path) = open_unique_file(&dest_path)?;
-        created.record_file(actual_path);
+        let incoming = EntryFacts {
+            is_dir: false,
+            size: entry.size(),
+            modified: stored.modified,
+        };
+        let Some((file, actual_path)) =
+            placement.open_file(&raw_name, &dest_path, &incoming, stats, created)?
+        else {
+            if let Some(p) = progress {
+                p.add(entry.compressed_size().max(1));
+            }
+            continue;
+        };
         let mut out = BufWriter::with_capacity(CHUNK, file);
         if let Err(e) = copy_with_progress(&mut entry, &mut out, progress, cancel, budget, &mut buf)
+            .and_then(|_| out.flush())
         {
             let msg = map_copy_err(&format!("Failed to write zip entry {raw_name}"), e);
             return Err(DecompressError::from_external_message(msg));
         }
+        drop(out);
+        preserve.apply_stored(&actual_path, &stored);
     }
+    placement.restore_symlinks(stats, created)?;
+    preserve_dir_metadata(preserve, dir_metadata);
 
     Ok(())
 }
 
+fn zip_entry_error(index: usize, error: ZipError) -> DecompressError {
+    match error {
+        ZipError::UnsupportedArchive(message) if message == ZipError::PASSWORD_REQUIRED => {
+            DecompressError::password_required()
+        }
+        ZipError::InvalidPassword => DecompressError::invalid_password(),
+        error => format!("Failed to read zip entry {index}: {error}").into(),
+    }
+}
+
+/// Zip times are local wall-clock times without a zone.
+fn zip_datetime_to_system_time(dt: ZipDateTime) -> Option<SystemTime> {
+    let naive = NaiveDate::from_ymd_opt(dt.year().into(), dt.month().into(), dt.day().into())?
+        .and_hms_opt(dt.hour().into(), dt.minute().into(), dt.second().into())?;
+    let local = naive.and_local_timezone(Local).earliest()?;
+    Some(local.into())
+}
+
 pub(super) fn zip_uncompressed_total(path: &Path) -> DecompressResult<u64> {
-    let mut archive = ZipArchive::new(File::open(path).map_err(map_io("open zip for total"))?)
-        .map_err(|e| format!("Failed to read zip: {e}"))?;
+    let mut archive = open_zip(path)?;
     let mut total = 0u64;
     let mut entries_seen = 0u64;
     for i in 0..archive.len() {
+        // Raw access reads headers only, so encrypted entries need no password here.
         let entry = archive
-            .by_index(i)
+            .by_index_raw(i)
             .map_err(|e| format!("Failed to read zip entry {i}: {e}"))?;
         entries_seen = entries_seen.saturating_add(1);
         if entries_seen > EXTRACT_TOTAL_ENTRIES_CAP {
@@ -207,14 +256,19 @@ pub(super) fn zip_uncompressed_total(path: &Path) -> DecompressResult<u64> {
 #[cfg(test)]
 mod tests {
     use super::{extract_zip, single_root_in_zip, zip_uncompressed_total};
-    use crate::commands::decompress::util::{CreatedPaths, ExtractBudget, SkipStats};
+    use crate::commands::decompress::util::{
+        CreatedPaths, EntryLayout, EntryOutcome, ExtractBudget, ExtractDestination, ExtractOptions,
+        Placement, SkipStats, SymlinkPolicy,
+    };
+    use crate::errors::domain::DomainError;
+    use crate::fs_utils::{ConflictPolicy, MetadataPreserver};
     use std::{
         fs::{self, File},
         io::Write,
         path::{Path, PathBuf},
         time::{SystemTime, UNIX_EPOCH},
     };
-    use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+    use zip::{write::SimpleFileOptions, AesMode, CompressionMethod, ZipArchive, ZipWriter};
 
     fn unique_temp_dir(label: &str) -> PathBuf {
         let unique = format!(
@@ -272,12 +326,15 @@ mod tests {
         extract_zip(
             &zip_path,
             &dest_dir,
-            None,
+            EntryLayout::Tree(None),
+            &mut Placement::new(&dest_dir, &ExtractOptions::default()),
             &stats,
+            &MetadataPreserver::disabled(),
             None,
             &mut created,
             None,
             &budget,
+            None,
         )
         .expect("extract zip64/stored archive");
 
@@ -288,4 +345,121 @@ mod tests {
         created.disarm();
         let _ = fs::remove_dir_all(root);
     }
+
+    #[test]
+    fn encrypted_zip_asks_for_password_and_rejects_a_wrong_one() {
+        let root = unique_temp_dir("encrypted");
+        let zip_path = root.join("secret.zip");
+        let mut zip = ZipWriter::new(File::create(&zip_path).expect("create zip file"));
+        zip.start_file(
+            "secret/notes.txt",
+            SimpleFileOptions::default().with_aes_encryption(AesMode::Aes256, "hunter2"),
+        )
+        .expect("start encrypted entry");
+        zip.write_all(b"top secret").expect("write entry");
+        zip.finish().expect("finish zip");
+
+        assert_eq!(
+            single_root_in_zip(&zip_path).expect("single root"),
+            Some(PathBuf::from("secret"))
+        );
+        let extract = |dest: &Path, password: Option<&str>| {
+            fs::create_dir_all(dest).expect("create destination");
+            let mut created = CreatedPaths::default();
+            let result = extract_zip(
+                &zip_path,
+                dest,
+                EntryLayout::Tree(None),
+                &mut Placement::new(dest, &ExtractOptions::default()),
+                &SkipStats::default(),
+                &MetadataPreserver::disabled(),
+                None,
+                &mut created,
+                None,
+                &ExtractBudget::new(10_000_000, 1000),
+                password,
+            );
+            created.disarm();
+            result
+        };
+
+        let missing = extract(&root.join("none"), None).expect_err("password required");
+        assert_eq!(missing.code_str(), "password_required");
+        let wrong = extract(&root.join("wrong"), Some("letmein")).expect_err("wrong password");
+        assert_eq!(wrong.code_str(), "invalid_password");
+        assert!(!wrong.to_string().contains("letmein"));
+
+        let dest = root.join("right");
+        extract(&dest, Some("hunter2")).expect("extract with password");
+        assert_eq!(
+            fs::read(dest.join("secret").join("notes.txt")).expect("read extracted"),
+            b"top secret"
+        );
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn extract_here_overwrites_and_restores_contained_symlinks() {
+        let root = unique_temp_dir("here");
+        let zip_path = root.join("update.zip");
+        let mut zip = ZipWriter::new(File::create(&zip_path).expect("create zip file"));
+        zip.start_file("notes.txt", SimpleFileOptions::default())
+            .expect("start entry");
+        zip.write_all(b"new").expect("write entry");
+        zip.add_symlink("latest", "notes.txt", SimpleFileOptions::default())
+            .expect("add contained symlink");
+        zip.add_symlink("escape", "../outside", SimpleFileOptions::default())
+            .expect("add escaping symlink");
+        zip.finish().expect("finish zip");
+        fs::write(root.join("notes.txt"), b"old").expect("seed existing file");
+
+        let options = ExtractOptions {
+            destination: ExtractDestination::Here,
+            conflict: Some(ConflictPolicy::Overwrite),
+            symlinks: SymlinkPolicy::Restore,
+        };
+        let stats = SkipStats::default();
+        let mut created = CreatedPaths::default();
+        extract_zip(
+            &zip_path,
+            &root,
+            EntryLayout::Tree(None),
+            &mut Placement::new(&root, &options),
+            &stats,
+            &MetadataPreserver::disabled(),
+            None,
+            &mut created,
+            None,
+            &ExtractBudget::new(10_000_000, 1000),
+            None,
+        )
+        .expect("extract here");
+        created.disarm();
+
+        assert_eq!(fs::read(root.join("notes.txt")).expect("read"), b"new");
+        assert_eq!(
+            fs::read_link(root.join("latest")).expect("restored link"),
+            PathBuf::from("notes.txt")
+        );
+        assert!(fs::symlink_metadata(root.join("escape")).is_err());
+        assert_eq!(created.replaced.len(), 1);
+        let outcomes: Vec<_> = stats
+            .take_outcomes()
+            .into_iter()
+            .map(|entry| (entry.entry, entry.outcome))
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                ("notes.txt".to_string(), EntryOutcome::Overwritten),
+                ("latest".to_string(), EntryOutcome::SymlinkRestored),
+                ("escape".to_string(), EntryOutcome::Skipped),
+            ]
+        );
+        for (_, backup) in &created.replaced {
+            let _ = fs::remove_file(backup);
+        }
+        let _ = fs::remove_dir_all(root);
+    }
 }
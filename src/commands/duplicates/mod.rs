@@ -1,14 +1,18 @@
 //! Duplicate detection commands.
 //!
-//! Current strategy:
+//! Single-file strategy (`check_duplicates*`):
 //! 1) coarse filter by byte length
 //! 2) byte-for-byte comparison for same-length candidates (early exit on mismatch)
+//!
+//! Whole-tree grouping (`find_all_duplicates_stream`) hashes instead; see `tree`.
 
 mod scan;
+mod tree;
 
 use crate::{
     commands::fs::expand_path,
     errors::api_error::ApiResult,
+    exclusions::ExclusionRules,
     fs_utils::{check_no_symlink_components, sanitize_path_follow},
     runtime_lifecycle,
     tasks::CancelState,
@@ -30,6 +34,8 @@ struct DuplicateScanInput {
 pub enum DuplicateScanPhase {
     Collecting,
     Comparing,
+    Prefiltering,
+    Hashing,
     Done,
 }
 
@@ -47,17 +53,49 @@ pub struct DuplicateScanProgress {
     pub duplicates: Option<Vec<String>>,
 }
 
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    pub size: u64,
+    /// Hex BLAKE3 digest of the shared contents.
+    pub hash: String,
+    pub paths: Vec<String>,
+    pub reclaimable_bytes: u64,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroupsProgress {
+    pub phase: DuplicateScanPhase,
+    pub percent: u8,
+    pub scanned_files: u64,
+    pub candidate_files: u64,
+    pub hashed_files: u64,
+    pub group_count: u64,
+    pub reclaimable_bytes: u64,
+    pub done: bool,
+    pub error: Option<String>,
+    pub groups: Option<Vec<DuplicateGroup>>,
+}
+
 #[tauri::command]
-pub async fn check_duplicates(target_path: String, start_path: String) -> ApiResult<Vec<String>> {
-    map_api_result(check_duplicates_impl(target_path, start_path).await)
+pub async fn check_duplicates(
+    target_path: String,
+    start_path: String,
+    include_ignored: Option<bool>,
+) -> ApiResult<Vec<String>> {
+    map_api_result(
+        check_duplicates_impl(target_path, start_path, include_ignored.unwrap_or(true)).await,
+    )
 }
 
 async fn check_duplicates_impl(
     target_path: String,
     start_path: String,
+    include_ignored: bool,
 ) -> DuplicatesResult<Vec<String>> {
     let task = tauri::async_runtime::spawn_blocking(move || {
-        check_duplicates_sync(target_path, start_path)
+        check_duplicates_sync(target_path, start_path, include_ignored)
     });
     match task.await {
         Ok(result) => result,
@@ -74,6 +112,7 @@ pub fn check_duplicates_stream(
     cancel: tauri::State<'_, CancelState>,
     target_path: String,
     start_path: String,
+    include_ignored: Option<bool>,
     progress_event: Option<String>,
 ) -> ApiResult<()> {
     map_api_result(check_duplicates_stream_impl(
@@ -81,6 +120,7 @@ pub fn check_duplicates_stream(
         cancel.inner().clone(),
         target_path,
         start_path,
+        include_ignored.unwrap_or(true),
         progress_event,
     ))
 }
@@ -90,6 +130,7 @@ fn check_duplicates_stream_impl(
     cancel_state: CancelState,
     target_path: String,
     start_path: String,
+    include_ignored: bool,
     progress_event: Option<String>,
 ) -> DuplicatesResult<()> {
     let progress_event = progress_event
@@ -117,10 +158,12 @@ fn check_duplicates_stream_impl(
             }
         };
 
+        let exclusions = (!include_ignored).then(|| ExclusionRules::load(&input.start));
         let outcome = scan::find_identical_files_with_progress(
             &input.target,
             &input.start,
             input.target_len,
+            exclusions.as_ref(),
             Some(cancel_token.as_ref()),
             |progress| {
                 if progress_cancel.load(Ordering::Relaxed)
@@ -155,9 +198,102 @@ fn check_duplicates_stream_impl(
     Ok(())
 }
 
-fn check_duplicates_sync(target_path: String, start_path: String) -> DuplicatesResult<Vec<String>> {
+#[tauri::command]
+pub fn find_all_duplicates_stream(
+    app: tauri::AppHandle,
+    cancel: tauri::State<'_, CancelState>,
+    root_path: String,
+    include_ignored: Option<bool>,
+    progress_event: Option<String>,
+) -> ApiResult<()> {
+    map_api_result(find_all_duplicates_stream_impl(
+        app,
+        cancel.inner().clone(),
+        root_path,
+        include_ignored.unwrap_or(true),
+        progress_event,
+    ))
+}
+
+fn find_all_duplicates_stream_impl(
+    app: tauri::AppHandle,
+    cancel_state: CancelState,
+    root_path: String,
+    include_ignored: bool,
+    progress_event: Option<String>,
+) -> DuplicatesResult<()> {
+    let progress_event = progress_event
+        .ok_or_else(|| DuplicatesError::invalid_input("progress_event is required"))?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let send = |payload: DuplicateGroupsProgress| {
+            let _ = runtime_lifecycle::emit_if_running(&app, &progress_event, payload);
+        };
+        let cancel_guard = match cancel_state.register(progress_event.clone()) {
+            Ok(guard) => guard,
+            Err(err) => {
+                send(groups_error_payload(err.to_string()));
+                return;
+            }
+        };
+        let cancel_token = cancel_guard.token();
+        let progress_cancel = cancel_token.clone();
+
+        let root = match validate_start_dir(root_path) {
+            Ok(root) => root,
+            Err(err) => {
+                send(groups_error_payload(err.to_string()));
+                return;
+            }
+        };
+
+        let exclusions = (!include_ignored).then(|| ExclusionRules::load(&root));
+        let outcome = tree::find_duplicate_sets(
+            &root,
+            exclusions.as_ref(),
+            Some(cancel_token.as_ref()),
+            |progress| {
+                if progress_cancel.load(Ordering::Relaxed)
+                    || runtime_lifecycle::is_shutting_down(&app)
+                {
+                    return;
+                }
+                send(groups_progress_payload(progress, false, None, None));
+            },
+        );
+
+        if cancel_token.load(Ordering::Relaxed) || runtime_lifecycle::is_shutting_down(&app) {
+            return;
+        }
+
+        match outcome {
+            tree::TreeScanResult::Completed { groups, progress } => {
+                let groups = groups.into_iter().map(to_duplicate_group).collect();
+                send(groups_progress_payload(progress, true, None, Some(groups)));
+            }
+            tree::TreeScanResult::Cancelled => {}
+            tree::TreeScanResult::Failed(err) => {
+                send(groups_error_payload(err.to_string()));
+            }
+        }
+    });
+
+    Ok(())
+}
+
+fn check_duplicates_sync(
+    target_path: String,
+    start_path: String,
+    include_ignored: bool,
+) -> DuplicatesResult<Vec<String>> {
     let input = validate_scan_input(target_path, start_path)?;
-    let matches = scan::find_identical_files(&input.target, &input.start, input.target_len)?;
+    let exclusions = (!include_ignored).then(|| ExclusionRules::load(&input.start));
+    let matches = scan::find_identical_files(
+        &input.target,
+        &input.start,
+        input.target_len,
+        exclusions.as_ref(),
+    )?;
     Ok(to_string_paths(matches))
 }
 
@@ -180,6 +316,16 @@ fn validate_scan_input(
         return Err(DuplicatesError::invalid_input("Target must be a file"));
     }
 
+    let start = validate_start_dir(start_path)?;
+
+    Ok(DuplicateScanInput {
+        target,
+        start,
+        target_len: target_meta.len(),
+    })
+}
+
+fn validate_start_dir(start_path: String) -> DuplicatesResult<PathBuf> {
     let start_expanded = expand_path(Some(start_path)).map_err(DuplicatesError::from)?;
     let start = sanitize_path_follow(&start_expanded.to_string_lossy(), false)
         .map_err(DuplicatesError::from)?;
@@ -199,11 +345,7 @@ fn validate_scan_input(
         ));
     }
 
-    Ok(DuplicateScanInput {
-        target,
-        start,
-        target_len: target_meta.len(),
-    })
+    Ok(start)
 }
 
 fn to_string_paths(paths: Vec<PathBuf>) -> Vec<String> {
@@ -217,6 +359,8 @@ fn map_phase(phase: scan::ScanPhase) -> DuplicateScanPhase {
     match phase {
         scan::ScanPhase::Collecting => DuplicateScanPhase::Collecting,
         scan::ScanPhase::Comparing => DuplicateScanPhase::Comparing,
+        scan::ScanPhase::Prefiltering => DuplicateScanPhase::Prefiltering,
+        scan::ScanPhase::Hashing => DuplicateScanPhase::Hashing,
         scan::ScanPhase::Done => DuplicateScanPhase::Done,
     }
 }
@@ -253,3 +397,47 @@ fn error_payload(error: String) -> DuplicateScanProgress {
         duplicates: None,
     }
 }
+
+fn to_duplicate_group(set: tree::DuplicateSet) -> DuplicateGroup {
+    DuplicateGroup {
+        size: set.size,
+        hash: set.hash.to_hex().to_string(),
+        reclaimable_bytes: set.reclaimable_bytes(),
+        paths: to_string_paths(set.paths),
+    }
+}
+
+fn groups_progress_payload(
+    progress: tree::TreeScanProgress,
+    done: bool,
+    error: Option<String>,
+    groups: Option<Vec<DuplicateGroup>>,
+) -> DuplicateGroupsProgress {
+    DuplicateGroupsProgress {
+        phase: map_phase(progress.phase),
+        percent: progress.percent,
+        scanned_files: progress.scanned_files,
+        candidate_files: progress.candidate_files,
+        hashed_files: progress.hashed_files,
+        group_count: progress.group_count,
+        reclaimable_bytes: progress.reclaimable_bytes,
+        done,
+        error,
+        groups,
+    }
+}
+
+fn groups_error_payload(error: String) -> DuplicateGroupsProgress {
+    DuplicateGroupsProgress {
+        phase: DuplicateScanPhase::Done,
+        percent: 100,
+        scanned_files: 0,
+        candidate_files: 0,
+        hashed_files: 0,
+        group_count: 0,
+        reclaimable_bytes: 0,
+        done: true,
+        error: Some(error),
+        groups: None,
+    }
+}
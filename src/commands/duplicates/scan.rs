@@ -1,4 +1,5 @@
 use super::error::{DuplicatesError, DuplicatesResult};
+use crate::exclusions::ExclusionRules;
 use std::{
     fs::{self, File},
     io::{BufReader, Read},
@@ -9,12 +10,12 @@ use tracing::{debug, warn};
 
 const COMPARE_BUF_SIZE: usize = 64 * 1024;
 const COLLECT_PHASE_PERCENT: u8 = 40;
-const COLLECT_PROGRESS_INTERVAL: u64 = 512;
-const COLLECT_CANCEL_CHECK_INTERVAL: u64 = 256;
-const MAX_SCANNED_FILES: u64 = 2_000_000;
+pub(super) const COLLECT_PROGRESS_INTERVAL: u64 = 512;
+pub(super) const COLLECT_CANCEL_CHECK_INTERVAL: u64 = 256;
+pub(super) const MAX_SCANNED_FILES: u64 = 2_000_000;
 const MAX_CANDIDATE_FILES: u64 = 100_000;
 
-fn log_walk_error(context: &str, path: &Path, err: &std::io::Error) {
+pub(super) fn log_walk_error(context: &str, path: &Path, err: &std::io::Error) {
     if err.kind() == std::io::ErrorKind::PermissionDenied {
         debug!("{context}: path={} err={}", path.display(), err);
     } else {
@@ -26,8 +27,9 @@ pub(super) fn find_identical_files(
     target: &Path,
     start: &Path,
     target_len: u64,
+    exclusions: Option<&ExclusionRules>,
 ) -> DuplicatesResult<Vec<PathBuf>> {
-    match find_identical_files_with_progress(target, start, target_len, None, |_| {}) {
+    match find_identical_files_with_progress(target, start, target_len, exclusions, None, |_| {}) {
         ScanResult::Completed { matches, .. } => Ok(matches),
         ScanResult::Cancelled => Err(DuplicatesError::from_external_message(
             "Duplicate scan cancelled",
@@ -40,6 +42,8 @@ pub(super) fn find_identical_files(
 pub(super) enum ScanPhase {
     Collecting,
     Comparing,
+    Prefiltering,
+    Hashing,
     Done,
 }
 
@@ -112,17 +116,24 @@ pub(super) fn find_identical_files_with_progress(
     target: &Path,
     start: &Path,
     target_len: u64,
+    exclusions: Option<&ExclusionRules>,
     cancel_token: Option<&AtomicBool>,
     mut on_progress: impl FnMut(ScanProgress),
 ) -> ScanResult {
     on_progress(ScanProgress::collecting(0, 0, 0));
 
-    let (candidates, scanned_files) =
-        match collect_same_size_files(target, start, target_len, cancel_token, &mut on_progress) {
-            Ok(result) => result,
-            Err(CollectAbort::Cancelled) => return ScanResult::Cancelled,
-            Err(CollectAbort::Failed(err)) => return ScanResult::Failed(err),
-        };
+    let (candidates, scanned_files) = match collect_same_size_files(
+        target,
+        start,
+        target_len,
+        exclusions,
+        cancel_token,
+        &mut on_progress,
+    ) {
+        Ok(result) => result,
+        Err(CollectAbort::Cancelled) => return ScanResult::Cancelled,
+        Err(CollectAbort::Failed(err)) => return ScanResult::Failed(err),
+    };
     let candidate_files = candidates.len() as u64;
 
     if is_cancelled(cancel_token) {
@@ -198,6 +209,7 @@ fn collect_same_size_files(
     target: &Path,
     start: &Path,
     target_len: u64,
+    exclusions: Option<&ExclusionRules>,
     cancel_token: Option<&AtomicBool>,
     on_progress: &mut impl FnMut(ScanProgress),
 ) -> Result<(Vec<PathBuf>, u64), CollectAbort> {
@@ -205,6 +217,7 @@ fn collect_same_size_files(
         target,
         start,
         target_len,
+        exclusions,
         cancel_token,
         on_progress,
         MAX_SCANNED_FILES,
@@ -216,6 +229,7 @@ fn collect_same_size_files_with_limits(
     target: &Path,
     start: &Path,
     target_len: u64,
+    exclusions: Option<&ExclusionRules>,
     cancel_token: Option<&AtomicBool>,
     on_progress: &mut impl FnMut(ScanProgress),
     max_scanned_files: u64,
@@ -275,6 +289,9 @@ fn collect_same_size_files_with_limits(
             }
 
             let path = item.path();
+            if exclusions.is_some_and(|rules| rules.is_excluded(&path, file_type.is_dir())) {
+                continue;
+            }
             if file_type.is_dir() {
                 stack.push(path);
                 continue;
@@ -358,7 +375,7 @@ fn files_equal(left: &Path, right: &Path) -> std::io::Result<bool> {
     }
 }
 
-fn is_cancelled(cancel_token: Option<&AtomicBool>) -> bool {
+pub(super) fn is_cancelled(cancel_token: Option<&AtomicBool>) -> bool {
     cancel_token
         .map(|token| token.load(Ordering::Relaxed))
         .unwrap_or(false)
@@ -421,8 +438,16 @@ mod tests {
         write_file(&base.join("b.bin"), b"same-size");
 
         let mut progress = |_p: ScanProgress| {};
-        let res =
-            collect_same_size_files_with_limits(&target, &base, 9, None, &mut progress, 100, 1);
+        let res = collect_same_size_files_with_limits(
+            &target,
+            &base,
+            9,
+            None,
+            None,
+            &mut progress,
+            100,
+            1,
+        );
         match res {
             Err(CollectAbort::Failed(err)) => {
                 assert!(
@@ -446,8 +471,16 @@ mod tests {
         write_file(&base.join("b.bin"), b"b");
 
         let mut progress = |_p: ScanProgress| {};
-        let res =
-            collect_same_size_files_with_limits(&target, &base, 1, None, &mut progress, 1, 100);
+        let res = collect_same_size_files_with_limits(
+            &target,
+            &base,
+            1,
+            None,
+            None,
+            &mut progress,
+            1,
+            100,
+        );
         match res {
             Err(CollectAbort::Failed(err)) => {
                 assert!(
@@ -460,4 +493,24 @@ mod tests {
 
         let _ = fs::remove_dir_all(&base);
     }
+
+    #[test]
+    fn find_identical_files_skips_excluded_directories() {
+        let base = uniq_path("exclusions");
+        let target = base.join("target.bin");
+        write_file(&target, b"payload");
+        write_file(&base.join("copy.bin"), b"payload");
+        write_file(&base.join("node_modules").join("copy.bin"), b"payload");
+        write_file(&base.join(".gitignore"), b"build/\n");
+        write_file(&base.join("build").join("copy.bin"), b"payload");
+
+        let rules = ExclusionRules::new(&base, &["node_modules/".to_string()]);
+        let matches = find_identical_files(&target, &base, 7, Some(&rules)).unwrap();
+        assert_eq!(matches, vec![base.join("copy.bin")]);
+
+        let all = find_identical_files(&target, &base, 7, None).unwrap();
+        assert_eq!(all.len(), 3);
+
+        let _ = fs::remove_dir_all(&base);
+    }
 }
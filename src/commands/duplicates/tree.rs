@@ -0,0 +1,449 @@
+//! Whole-tree duplicate grouping for `find_all_duplicates_stream`.
+//!
+//! Strategy:
+//! 1) bucket every non-empty regular file by byte length
+//! 2) split buckets by a BLAKE3 hash of each file's first 64 KiB
+//! 3) confirm what is left with a full BLAKE3 hash
+
+use super::error::{DuplicatesError, DuplicatesResult};
+use super::scan::{
+    is_cancelled, log_walk_error, ScanPhase, COLLECT_CANCEL_CHECK_INTERVAL,
+    COLLECT_PROGRESS_INTERVAL, MAX_SCANNED_FILES,
+};
+use crate::exclusions::ExclusionRules;
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+};
+use tracing::warn;
+
+const PARTIAL_HASH_BYTES: u64 = 64 * 1024;
+const HASH_BUF_SIZE: usize = 256 * 1024;
+const HASH_PROGRESS_INTERVAL: u64 = 64;
+const COLLECT_PHASE_END: u8 = 30;
+const PREFILTER_PHASE_END: u8 = 60;
+
+#[derive(Debug, Clone, Copy)]
+pub(super) struct TreeScanProgress {
+    pub phase: ScanPhase,
+    pub percent: u8,
+    pub scanned_files: u64,
+    pub candidate_files: u64,
+    pub hashed_files: u64,
+    pub group_count: u64,
+    pub reclaimable_bytes: u64,
+}
+
+impl TreeScanProgress {
+    fn new(phase: ScanPhase, percent: u8) -> Self {
+        Self {
+            phase,
+            percent,
+            scanned_files: 0,
+            candidate_files: 0,
+            hashed_files: 0,
+            group_count: 0,
+            reclaimable_bytes: 0,
+        }
+    }
+}
+
+/// Files with identical contents.
+#[derive(Debug)]
+pub(super) struct DuplicateSet {
+    pub size: u64,
+    pub hash: blake3::Hash,
+    /// Sorted, at least two entries.
+    pub paths: Vec<PathBuf>,
+}
+
+impl DuplicateSet {
+    /// Bytes freed by keeping a single copy.
+    pub(super) fn reclaimable_bytes(&self) -> u64 {
+        self.size
+            .saturating_mul(self.paths.len().saturating_sub(1) as u64)
+    }
+}
+
+pub(super) enum TreeScanResult {
+    Completed {
+        groups: Vec<DuplicateSet>,
+        progress: TreeScanProgress,
+    },
+    Cancelled,
+    Failed(DuplicatesError),
+}
+
+pub(super) fn find_duplicate_sets(
+    root: &Path,
+    exclusions: Option<&ExclusionRules>,
+    cancel_token: Option<&AtomicBool>,
+    mut on_progress: impl FnMut(TreeScanProgress),
+) -> TreeScanResult {
+    let mut progress = TreeScanProgress::new(ScanPhase::Collecting, 0);
+    on_progress(progress);
+
+    let buckets = match collect_size_buckets(
+        root,
+        exclusions,
+        cancel_token,
+        MAX_SCANNED_FILES,
+        &mut progress,
+        &mut on_progress,
+    ) {
+        Ok(buckets) => buckets,
+        Err(_) if is_cancelled(cancel_token) => return TreeScanResult::Cancelled,
+        Err(err) => return TreeScanResult::Failed(err),
+    };
+
+    progress.phase = ScanPhase::Prefiltering;
+    progress.percent = COLLECT_PHASE_END;
+    progress.candidate_files = buckets.iter().map(|(_, paths)| paths.len() as u64).sum();
+    on_progress(progress);
+
+    let mut groups = Vec::new();
+    let mut to_confirm = Vec::new();
+    let mut prefiltered = 0u64;
+    for (size, paths) in buckets {
+        let count = paths.len() as u64;
+        let Some(split) = split_by_hash(paths, Some(PARTIAL_HASH_BYTES), cancel_token) else {
+            return TreeScanResult::Cancelled;
+        };
+        for (hash, paths) in split {
+            if size <= PARTIAL_HASH_BYTES {
+                // The prefix hash already covered the whole file.
+                groups.push(DuplicateSet { size, hash, paths });
+            } else {
+                to_confirm.push((size, paths));
+            }
+        }
+        prefiltered = prefiltered.saturating_add(count);
+        progress.percent = span_percent(
+            prefiltered,
+            progress.candidate_files,
+            COLLECT_PHASE_END,
+            PREFILTER_PHASE_END,
+        );
+        record_groups(&mut progress, &groups);
+        on_progress(progress);
+    }
+
+    progress.phase = ScanPhase::Hashing;
+    progress.percent = PREFILTER_PHASE_END;
+    on_progress(progress);
+
+    let total_bytes: u64 = to_confirm
+        .iter()
+        .map(|(size, paths)| size.saturating_mul(paths.len() as u64))
+        .sum();
+    let mut hashed_bytes = 0u64;
+    let mut since_progress = 0u64;
+    for (size, paths) in to_confirm {
+        let count = paths.len() as u64;
+        let Some(split) = split_by_hash(paths, None, cancel_token) else {
+            return TreeScanResult::Cancelled;
+        };
+        groups.extend(
+            split
+                .into_iter()
+                .map(|(hash, paths)| DuplicateSet { size, hash, paths }),
+        );
+        hashed_bytes = hashed_bytes.saturating_add(size.saturating_mul(count));
+        progress.hashed_files = progress.hashed_files.saturating_add(count);
+        since_progress = since_progress.saturating_add(count);
+        if since_progress >= HASH_PROGRESS_INTERVAL {
+            since_progress = 0;
+            progress.percent =
+                span_percent(hashed_bytes, total_bytes, PREFILTER_PHASE_END, 100).min(99);
+            record_groups(&mut progress, &groups);
+            on_progress(progress);
+        }
+    }
+
+    groups.sort_by(|a, b| {
+        b.reclaimable_bytes()
+            .cmp(&a.reclaimable_bytes())
+            .then_with(|| a.paths.cmp(&b.paths))
+    });
+    progress.phase = ScanPhase::Done;
+    progress.percent = 100;
+    record_groups(&mut progress, &groups);
+    on_progress(progress);
+    TreeScanResult::Completed { groups, progress }
+}
+
+fn record_groups(progress: &mut TreeScanProgress, groups: &[DuplicateSet]) {
+    progress.group_count = groups.len() as u64;
+    progress.reclaimable_bytes = groups.iter().map(DuplicateSet::reclaimable_bytes).sum();
+}
+
+/// Walks `root` and returns the sizes shared by at least two files, with those files.
+/// Hard links to an already seen file are skipped: deleting them reclaims nothing.
+fn collect_size_buckets(
+    root: &Path,
+    exclusions: Option<&ExclusionRules>,
+    cancel_token: Option<&AtomicBool>,
+    max_scanned_files: u64,
+    progress: &mut TreeScanProgress,
+    on_progress: &mut impl FnMut(TreeScanProgress),
+) -> DuplicatesResult<Vec<(u64, Vec<PathBuf>)>> {
+    let mut stack = vec![root.to_path_buf()];
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    let mut seen_inodes = HashSet::new();
+    let mut processed_entries = 0u64;
+    let mut discovered_entries = 0u64;
+    let mut since_progress = 0u64;
+    let mut since_cancel_check = 0u64;
+
+    while let Some(dir) = stack.pop() {
+        let iter = match fs::read_dir(&dir) {
+            Ok(iter) => iter,
+            Err(err) => {
+                log_walk_error("duplicate tree read_dir failed", &dir, &err);
+                continue;
+            }
+        };
+
+        for item in iter {
+            discovered_entries = discovered_entries.saturating_add(1);
+            processed_entries = processed_entries.saturating_add(1);
+            since_progress = since_progress.saturating_add(1);
+            since_cancel_check = since_cancel_check.saturating_add(1);
+            if since_cancel_check >= COLLECT_CANCEL_CHECK_INTERVAL {
+                since_cancel_check = 0;
+                if is_cancelled(cancel_token) {
+                    return Err(DuplicatesError::from_external_message(
+                        "Duplicate scan cancelled",
+                    ));
+                }
+            }
+
+            let item = match item {
+                Ok(item) => item,
+                Err(err) => {
+                    log_walk_error("duplicate tree read_dir entry failed", &dir, &err);
+                    continue;
+                }
+            };
+            let file_type = match item.file_type() {
+                Ok(file_type) => file_type,
+                Err(err) => {
+                    log_walk_error("duplicate tree file_type failed", &item.path(), &err);
+                    continue;
+                }
+            };
+            if file_type.is_symlink() {
+                continue;
+            }
+            let path = item.path();
+            if exclusions.is_some_and(|rules| rules.is_excluded(&path, file_type.is_dir())) {
+                continue;
+            }
+            if file_type.is_dir() {
+                stack.push(path);
+                continue;
+            }
+            if !file_type.is_file() {
+                continue;
+            }
+            let meta = match item.metadata() {
+                Ok(meta) => meta,
+                Err(err) => {
+                    log_walk_error("duplicate tree metadata failed", &path, &err);
+                    continue;
+                }
+            };
+
+            progress.scanned_files = progress.scanned_files.saturating_add(1);
+            if progress.scanned_files > max_scanned_files {
+                return Err(DuplicatesError::from_external_message(format!(
+                    "Duplicate scan aborted: scanned file limit exceeded ({} > {})",
+                    progress.scanned_files, max_scanned_files
+                )));
+            }
+            if meta.len() > 0 && first_link(&meta, &mut seen_inodes) {
+                by_size.entry(meta.len()).or_default().push(path);
+            }
+
+            if since_progress >= COLLECT_PROGRESS_INTERVAL {
+                since_progress = 0;
+                progress.percent =
+                    span_percent(processed_entries, discovered_entries, 0, COLLECT_PHASE_END)
+                        .max(progress.percent);
+                on_progress(*progress);
+            }
+        }
+    }
+
+    let mut buckets: Vec<_> = by_size
+        .into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .collect();
+    buckets.sort_by_key(|(size, _)| *size);
+    Ok(buckets)
+}
+
+#[cfg(unix)]
+fn first_link(meta: &fs::Metadata, seen: &mut HashSet<(u64, u64)>) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    meta.nlink() <= 1 || seen.insert((meta.dev(), meta.ino()))
+}
+
+#[cfg(not(unix))]
+fn first_link(_meta: &fs::Metadata, _seen: &mut HashSet<(u64, u64)>) -> bool {
+    true
+}
+
+/// Groups same-size `paths` by the hash of their first `limit` bytes (whole file for None),
+/// keeping only groups with two or more files. Returns None when cancelled.
+fn split_by_hash(
+    paths: Vec<PathBuf>,
+    limit: Option<u64>,
+    cancel_token: Option<&AtomicBool>,
+) -> Option<Vec<(blake3::Hash, Vec<PathBuf>)>> {
+    let mut by_hash: HashMap<blake3::Hash, Vec<PathBuf>> = HashMap::new();
+    for path in paths {
+        match hash_file(&path, limit, cancel_token) {
+            Ok(hash) => by_hash.entry(hash).or_default().push(path),
+            Err(_) if is_cancelled(cancel_token) => return None,
+            Err(err) => warn!("duplicate hash failed: path={} err={}", path.display(), err),
+        }
+    }
+    let mut groups: Vec<_> = by_hash
+        .into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .map(|(hash, mut paths)| {
+            paths.sort();
+            (hash, paths)
+        })
+        .collect();
+    groups.sort_by(|a, b| a.1.cmp(&b.1));
+    Some(groups)
+}
+
+fn hash_file(
+    path: &Path,
+    limit: Option<u64>,
+    cancel_token: Option<&AtomicBool>,
+) -> io::Result<blake3::Hash> {
+    let mut reader = File::open(path)?.take(limit.unwrap_or(u64::MAX));
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = vec![0u8; HASH_BUF_SIZE];
+    loop {
+        if is_cancelled(cancel_token) {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled"));
+        }
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            return Ok(hasher.finalize());
+        }
+        hasher.update(&buf[..n]);
+    }
+}
+
+/// Maps `done / total` onto the `from..=to` percent range of a phase.
+fn span_percent(done: u64, total: u64, from: u8, to: u8) -> u8 {
+    if total == 0 {
+        return to;
+    }
+    let span = u64::from(to.saturating_sub(from));
+    let offset = done.saturating_mul(span).saturating_div(total).min(span);
+    from.saturating_add(offset as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{io::Write, time::Duration};
+
+    fn uniq_path(label: &str) -> PathBuf {
+        let ts = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or(Duration::from_secs(0))
+            .as_nanos();
+        std::env::temp_dir().join(format!("browsey-dup-tree-{label}-{ts}"))
+    }
+
+    fn write_file(path: &Path, content: &[u8]) {
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let mut f = fs::File::create(path).unwrap();
+        f.write_all(content).unwrap();
+    }
+
+    fn scan(root: &Path) -> Vec<DuplicateSet> {
+        match find_duplicate_sets(root, None, None, |_| {}) {
+            TreeScanResult::Completed { groups, .. } => groups,
+            _ => panic!("expected completed scan"),
+        }
+    }
+
+    #[test]
+    fn groups_identical_files_and_reports_reclaimable_bytes() {
+        let base = uniq_path("groups");
+        write_file(&base.join("a.txt"), b"hello");
+        write_file(&base.join("nested/b.txt"), b"hello");
+        write_file(&base.join("nested/deeper/c.txt"), b"hello");
+        write_file(&base.join("other.txt"), b"world");
+        write_file(&base.join("empty-1"), b"");
+        write_file(&base.join("empty-2"), b"");
+
+        // Same size and same first 64 KiB, different tail: only the full hash tells apart.
+        let mut big = vec![7u8; (PARTIAL_HASH_BYTES as usize) + 10];
+        write_file(&base.join("big-1.bin"), &big);
+        write_file(&base.join("big-2.bin"), &big);
+        *big.last_mut().unwrap() = 8;
+        write_file(&base.join("big-3.bin"), &big);
+
+        let groups = scan(&base);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(
+            groups[0].paths,
+            vec![base.join("big-1.bin"), base.join("big-2.bin")]
+        );
+        assert_eq!(groups[0].reclaimable_bytes(), PARTIAL_HASH_BYTES + 10);
+        assert_eq!(
+            groups[1].paths,
+            vec![
+                base.join("a.txt"),
+                base.join("nested/b.txt"),
+                base.join("nested/deeper/c.txt"),
+            ]
+        );
+        assert_eq!(groups[1].reclaimable_bytes(), 10);
+        assert_eq!(groups[1].hash, blake3::hash(b"hello"));
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hard_links_are_not_reported_as_duplicates() {
+        let base = uniq_path("hardlinks");
+        write_file(&base.join("a.txt"), b"linked");
+        fs::hard_link(base.join("a.txt"), base.join("b.txt")).unwrap();
+        assert!(scan(&base).is_empty());
+
+        write_file(&base.join("c.txt"), b"linked");
+        let groups = scan(&base);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].paths.len(), 2);
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn cancelled_scan_stops() {
+        let base = uniq_path("cancel");
+        write_file(&base.join("a.txt"), b"same");
+        write_file(&base.join("b.txt"), b"same");
+        let cancel = AtomicBool::new(true);
+        let result = find_duplicate_sets(&base, None, Some(&cancel), |_| {});
+        assert!(matches!(result, TreeScanResult::Cancelled));
+        let _ = fs::remove_dir_all(&base);
+    }
+}
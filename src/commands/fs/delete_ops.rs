@@ -8,6 +8,7 @@ use crate::{
         ensure_no_symlink_components_existing_prefix,
     },
     runtime_lifecycle,
+    tasks::{run_job, JobKind, JobSpec},
     undo::{
         assert_path_snapshot, move_with_fallback, run_actions, snapshot_existing_path,
         temp_backup_path, Action, Direction,
@@ -201,7 +202,22 @@ pub async fn delete_entries(
     undo: tauri::State<'_, UndoState>,
     cancel: tauri::State<'_, CancelState>,
 ) -> ApiResult<()> {
-    map_api_result(delete_entries_impl(app, paths, progress_event, undo, cancel).await)
+    let job = JobSpec::new(
+        JobKind::Delete,
+        paths.first().cloned().unwrap_or_default(),
+        paths.clone(),
+        progress_event.clone(),
+    );
+    let work = delete_entries_impl(app.clone(), paths, progress_event, undo, cancel);
+    map_api_result(
+        run_job(
+            &app,
+            job,
+            || FsError::new(FsErrorCode::Cancelled, "Delete cancelled"),
+            work,
+        )
+        .await,
+    )
 }
 
 async fn delete_entries_impl(
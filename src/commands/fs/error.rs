@@ -380,6 +380,7 @@ impl From<crate::undo::UndoError> for FsError {
             | crate::undo::UndoErrorCode::SnapshotMismatch
             | crate::undo::UndoErrorCode::UndoUnavailable
             | crate::undo::UndoErrorCode::RedoUnavailable
+            | crate::undo::UndoErrorCode::HistoryConflict
             | crate::undo::UndoErrorCode::LockFailed
             | crate::undo::UndoErrorCode::IoError => FsErrorCode::UnknownError,
         };
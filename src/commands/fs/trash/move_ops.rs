@@ -11,6 +11,7 @@ use crate::{
     errors::api_error::ApiResult,
     fs_utils::{check_no_symlink_components, sanitize_path_nofollow},
     runtime_lifecycle,
+    tasks::{run_job, JobKind, JobSpec},
     undo::{
         assert_path_snapshot, copy_entry as undo_copy_entry, delete_entry_path as undo_delete_path,
         run_actions, snapshot_existing_path, temp_backup_path, Action, Direction, PathSnapshot,
@@ -268,7 +269,22 @@ pub async fn move_to_trash_many(
     cancel: tauri::State<'_, CancelState>,
     progress_event: Option<String>,
 ) -> ApiResult<()> {
-    map_api_result(move_to_trash_many_impl(paths, app, undo, cancel, progress_event).await)
+    let job = JobSpec::new(
+        JobKind::Trash,
+        paths.first().cloned().unwrap_or_default(),
+        paths.clone(),
+        progress_event.clone(),
+    );
+    let work = move_to_trash_many_impl(paths, app.clone(), undo, cancel, progress_event);
+    map_api_result(
+        run_job(
+            &app,
+            job,
+            || FsError::new(FsErrorCode::Cancelled, "Move to trash cancelled"),
+            work,
+        )
+        .await,
+    )
 }
 
 async fn move_to_trash_many_impl(
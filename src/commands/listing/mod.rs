@@ -1,6 +1,9 @@
 //! Directory listing and watcher wiring.
 
-use crate::{entry::FsEntry, errors::api_error::ApiResult, sorting::SortSpec, watcher::WatchState};
+use crate::{
+    commands::decompress, entry::FsEntry, errors::api_error::ApiResult, sorting::SortSpec,
+    watcher::WatchState,
+};
 use chrono::{Local, NaiveDateTime};
 use error::{map_api_result, ListingError, ListingErrorCode, ListingResult};
 use serde::Serialize;
@@ -153,6 +156,34 @@ pub fn build_listing_facets_with_hidden(
     builder.finish()
 }
 
+/// Facet ids `entry` falls into, in the same form [`ListingFacetBuilder::finish`] emits them,
+/// so a selected facet can be reused as a search term.
+pub fn entry_facet_ids(entry: &FsEntry, now: NaiveDateTime) -> Vec<String> {
+    let mut ids = Vec::with_capacity(4);
+    let name_key = entry.name.to_lowercase();
+    ids.push(bucket_name(name_key.as_str()).0.to_string());
+    ids.push(format!("type:{}", entry_type_label(entry)));
+    if let Some(modified) = &entry.modified {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(modified, "%Y-%m-%d %H:%M") {
+            ids.push(format!("modified:{}", bucket_modified(dt, now).0));
+        }
+    }
+    if entry.kind == "file" {
+        if let Some(size) = entry.size {
+            ids.push(format!("size:{}", bucket_size(size).0));
+        }
+    }
+    ids
+}
+
+/// Whether `label` is one of the fixed size facet labels (ASCII case-insensitive).
+pub fn is_size_facet_label(label: &str) -> bool {
+    SIZE_BUCKETS
+        .iter()
+        .any(|(_, known)| known.eq_ignore_ascii_case(label))
+        || SIZE_BUCKET_OVERFLOW.eq_ignore_ascii_case(label)
+}
+
 fn name_filter_label(id: &str) -> &'static str {
     match id {
         "name:a-f" => "A–F",
@@ -231,30 +262,34 @@ fn bucket_modified(dt: NaiveDateTime, now: NaiveDateTime) -> (String, i64) {
     (label, years * 365)
 }
 
+const KB: u64 = 1024;
+const MB: u64 = 1024 * KB;
+const GB: u64 = 1024 * MB;
+const TB: u64 = 1024 * GB;
+
+const SIZE_BUCKETS: [(u64, &str); 9] = [
+    (10 * KB, "0–10 KB"),
+    (100 * KB, "10–100 KB"),
+    (MB, "100 KB–1 MB"),
+    (10 * MB, "1–10 MB"),
+    (100 * MB, "10–100 MB"),
+    (GB, "100 MB–1 GB"),
+    (10 * GB, "1–10 GB"),
+    (100 * GB, "10–100 GB"),
+    (TB, "100 GB–1 TB"),
+];
+const SIZE_BUCKET_OVERFLOW: &str = "Over 1 TB";
+
 fn bucket_size(size: u64) -> (String, i64) {
-    const KB: u64 = 1024;
-    const MB: u64 = 1024 * KB;
-    const GB: u64 = 1024 * MB;
-    const TB: u64 = 1024 * GB;
-
-    let buckets = [
-        (10 * KB, "0–10 KB"),
-        (100 * KB, "10–100 KB"),
-        (MB, "100 KB–1 MB"),
-        (10 * MB, "1–10 MB"),
-        (100 * MB, "10–100 MB"),
-        (GB, "100 MB–1 GB"),
-        (10 * GB, "1–10 GB"),
-        (100 * GB, "10–100 GB"),
-        (TB, "100 GB–1 TB"),
-    ];
-
-    for (limit, label) in buckets.iter() {
+    for (limit, label) in SIZE_BUCKETS.iter() {
         if size <= *limit {
             return (label.to_string(), *limit as i64);
         }
     }
-    ("Over 1 TB".to_string(), (size / TB) as i64 * (TB as i64))
+    (
+        SIZE_BUCKET_OVERFLOW.to_string(),
+        (size / TB) as i64 * (TB as i64),
+    )
 }
 
 fn bucket_name(value: &str) -> (&'static str, i64) {
@@ -296,7 +331,13 @@ async fn list_dir_impl(
             return cloud::list_cloud_dir(raw_path, sort, app).await;
         }
     }
-    let task = tauri::async_runtime::spawn_blocking(move || local::list_dir_sync(path, sort, app));
+    let task = tauri::async_runtime::spawn_blocking(move || {
+        if let Some(uri) = path.as_deref().filter(|p| decompress::is_archive_uri(p)) {
+            return decompress::list_archive_dir_sync(uri, sort, None)
+                .map_err(cloud::listing_error_from_api);
+        }
+        local::list_dir_sync(path, sort, app)
+    });
     match task.await {
         Ok(result) => result,
         Err(error) => Err(ListingError::new(
@@ -350,7 +391,7 @@ pub fn watch_dir(
     app: tauri::AppHandle,
 ) -> ApiResult<()> {
     if let Some(raw_path) = path.as_deref() {
-        if cloud::is_cloud_path(raw_path) {
+        if cloud::is_cloud_path(raw_path) || decompress::is_archive_uri(raw_path) {
             return map_api_result(state.replace(None).map_err(ListingError::from).map(|_| ()));
         }
     }
@@ -360,7 +401,7 @@ pub fn watch_dir(
 #[cfg(test)]
 mod tests {
     use super::cloud::fs_entry_from_cloud_entry;
-    use super::{bucket_modified, bucket_name, bucket_size};
+    use super::{bucket_modified, bucket_name, bucket_size, entry_facet_ids};
     use crate::{
         commands::cloud::types::{CloudCapabilities, CloudEntry, CloudEntryKind},
         icons::icon_ids::{
@@ -434,6 +475,22 @@ mod tests {
         assert_eq!(bucket_name("_tmp").0, "name:other");
     }
 
+    #[test]
+    fn entry_facet_ids_match_builder_ids() {
+        let mut entry = fs_entry_from_cloud_entry(cloud_entry("Report.PDF", CloudEntryKind::File));
+        entry.size = Some(2 * 1024 * 1024);
+        let ids = entry_facet_ids(&entry, parse_dt("2026-03-05 10:00"));
+        assert_eq!(
+            ids,
+            vec![
+                "name:m-r".to_string(),
+                "type:pdf".to_string(),
+                "modified:Yesterday".to_string(),
+                "size:1–10 MB".to_string(),
+            ]
+        );
+    }
+
     fn cloud_entry(name: &str, kind: CloudEntryKind) -> CloudEntry {
         let path = match kind {
             CloudEntryKind::Dir => format!("rclone://work/docs/{name}"),
@@ -1,5 +1,8 @@
 use super::{cloud, local, ListingError, ListingErrorCode, ListingResult};
-use crate::entry::FsEntry;
+use crate::{
+    commands::{decompress, saved_searches},
+    entry::FsEntry,
+};
 
 pub(super) fn list_scope_entries(
     scope: &str,
@@ -17,6 +20,24 @@ pub(super) fn list_scope_entries(
         "trash" => Ok(crate::commands::fs::list_trash(None)
             .map_err(cloud::listing_error_from_api)?
             .entries),
+        "archive" => {
+            let uri = path.ok_or_else(|| {
+                ListingError::new(ListingErrorCode::InvalidInput, "Archive scope needs a path")
+            })?;
+            Ok(decompress::list_archive_dir_sync(&uri, None, None)
+                .map_err(cloud::listing_error_from_api)?
+                .entries)
+        }
+        s if s.starts_with(saved_searches::SAVED_SCOPE_PREFIX) => {
+            let id = saved_searches::parse_saved_scope(s).ok_or_else(|| {
+                ListingError::new(
+                    ListingErrorCode::InvalidInput,
+                    format!("Invalid saved search scope: {scope}"),
+                )
+            })?;
+            saved_searches::saved_search_facet_entries(&app, id)
+                .map_err(cloud::listing_error_from_api)
+        }
         _ => Err(ListingError::new(
             ListingErrorCode::UnsupportedScope,
             format!("Unsupported facet scope: {scope}"),
@@ -17,6 +17,7 @@ pub mod network;
 pub mod open_with;
 pub mod permissions;
 pub mod rename;
+pub mod saved_searches;
 pub mod search;
 pub mod settings;
 pub mod system_clipboard;
@@ -26,7 +27,9 @@ pub mod transfer;
 pub use crate::clipboard::{
     paste_clipboard_cmd, paste_clipboard_preview, resolve_drop_clipboard_mode, set_clipboard_cmd,
 };
-pub use crate::tasks::{cancel_task, CancelState};
+pub use crate::tasks::{
+    cancel_task, list_jobs, move_job, pause_job, resume_job, set_job_bandwidth_limit, CancelState,
+};
 pub use about::about_info;
 pub use bookmarks::{add_bookmark, clear_bookmarks, get_bookmarks, remove_bookmark};
 pub use cloud::{
@@ -38,8 +41,11 @@ pub use cloud::{
 };
 pub use compress::compress_entries;
 pub use console::open_console;
-pub use decompress::{can_extract_paths, extract_archive, extract_archives};
-pub use duplicates::{check_duplicates, check_duplicates_stream};
+pub use decompress::{
+    can_extract_paths, extract_archive, extract_archive_members, extract_archive_with_password,
+    extract_archives, list_archive_entries,
+};
+pub use duplicates::{check_duplicates, check_duplicates_stream, find_all_duplicates_stream};
 pub use entry_metadata::{entry_extra_metadata_cmd, entry_kind_cmd, entry_times_cmd};
 pub use file_types::detect_new_file_type;
 pub use fs::{
@@ -64,19 +70,26 @@ pub use permissions::{
     maybe_run_ownership_helper_from_args, set_ownership, set_permissions,
 };
 pub use rename::{preview_rename_entries, rename_entries, rename_entry};
-pub use search::search_stream;
+pub use saved_searches::{
+    add_saved_search, list_saved_search, list_saved_searches, remove_saved_search,
+    update_saved_search,
+};
+pub use search::{rebuild_search_index, search_index_status, search_stream};
 pub use settings::{
-    load_archive_level, load_archive_name, load_cloud_enabled, load_cloud_thumbs,
-    load_confirm_delete, load_default_view, load_density, load_double_click_ms, load_ffmpeg_path,
-    load_folders_first, load_hardware_acceleration, load_hidden_files_last, load_high_contrast,
-    load_log_level, load_mounts_poll_ms, load_open_dest_after_extract, load_rclone_path,
-    load_saved_column_widths, load_scrollbar_width, load_show_hidden, load_sort_direction,
-    load_sort_field, load_start_dir, load_thumb_cache_mb, load_video_thumbs, store_archive_level,
-    store_archive_name, store_cloud_enabled, store_cloud_thumbs, store_column_widths,
+    load_archive_format, load_archive_level, load_archive_name, load_bandwidth_limit,
+    load_cloud_enabled, load_cloud_thumbs, load_confirm_delete, load_default_view, load_density,
+    load_double_click_ms, load_exclude_patterns, load_ffmpeg_path, load_folders_first,
+    load_hardware_acceleration, load_hidden_files_last, load_high_contrast, load_log_level,
+    load_mounts_poll_ms, load_open_dest_after_extract, load_parallel_jobs_per_device,
+    load_rclone_path, load_saved_column_widths, load_scrollbar_width, load_search_index_roots,
+    load_show_hidden, load_sort_direction, load_sort_field, load_start_dir, load_thumb_cache_mb,
+    load_video_thumbs, store_archive_format, store_archive_level, store_archive_name,
+    store_bandwidth_limit, store_cloud_enabled, store_cloud_thumbs, store_column_widths,
     store_confirm_delete, store_default_view, store_density, store_double_click_ms,
-    store_ffmpeg_path, store_folders_first, store_hardware_acceleration, store_hidden_files_last,
-    store_high_contrast, store_log_level, store_mounts_poll_ms, store_open_dest_after_extract,
-    store_rclone_path, store_scrollbar_width, store_show_hidden, store_sort_direction,
+    store_exclude_patterns, store_ffmpeg_path, store_folders_first, store_hardware_acceleration,
+    store_hidden_files_last, store_high_contrast, store_log_level, store_mounts_poll_ms,
+    store_open_dest_after_extract, store_parallel_jobs_per_device, store_rclone_path,
+    store_scrollbar_width, store_search_index_roots, store_show_hidden, store_sort_direction,
     store_sort_field, store_start_dir, store_thumb_cache_mb, store_video_thumbs,
 };
 pub use system_clipboard::clear_system_clipboard;
@@ -6,6 +6,7 @@ use tracing::{debug, warn};
 
 use crate::errors::api_error::{ApiError, ApiResult};
 use crate::fs_utils::{check_no_symlink_components, sanitize_path_nofollow};
+use crate::undo::{run_actions, Action, Direction, UndoState};
 
 mod error;
 mod ownership;
@@ -38,6 +39,40 @@ pub(super) fn ensure_absolute_path(raw: &str) -> PermissionsResult<()> {
     }
 }
 
+/// Reverts the changes a failed batch already applied, using the undo engine so that a
+/// revert that fails part way leaves the batch fully applied rather than half reverted.
+pub(super) fn rollback_applied(applied: &[Action], err: PermissionsError) -> PermissionsError {
+    if applied.is_empty() {
+        return err;
+    }
+    let mut rollback = applied.to_vec();
+    match run_actions(&mut rollback, Direction::Backward) {
+        Ok(()) => err,
+        Err(rollback_err) => {
+            warn!(error = %rollback_err, "permissions rollback failed");
+            PermissionsError::new(
+                PermissionsErrorCode::RollbackFailed,
+                format!("{err}; rollback failed ({rollback_err}). System may be partially changed"),
+            )
+        }
+    }
+}
+
+/// Records the changes of one successful batch as a single undo entry.
+pub(super) fn record_applied(undo: Option<&UndoState>, mut applied: Vec<Action>) {
+    let Some(undo) = undo else {
+        return;
+    };
+    let action = match applied.len() {
+        0 => return,
+        1 => applied.pop().unwrap(),
+        _ => Action::Batch(applied),
+    };
+    if let Err(err) = undo.record_applied(action) {
+        warn!(error = %err, "failed to record permissions change for undo");
+    }
+}
+
 #[derive(serde::Serialize, Clone, PartialEq, Eq)]
 pub struct AccessBits {
     pub read: bool,
@@ -454,6 +489,7 @@ pub fn get_permissions_batch(paths: Vec<String>) -> ApiResult<PermissionsBatchRe
 #[tauri::command]
 #[allow(clippy::too_many_arguments)]
 pub fn set_permissions(
+    undo: tauri::State<'_, UndoState>,
     path: Option<String>,
     paths: Option<Vec<String>>,
     #[allow(non_snake_case)] readOnly: Option<bool>,
@@ -469,6 +505,7 @@ pub fn set_permissions(
         _ => return map_api_result(Err(PermissionsError::invalid_input("No paths provided"))),
     };
     map_api_result(set_permissions_batch(
+        Some(undo.inner()),
         targets,
         readOnly.or(read_only),
         executable,
@@ -480,6 +517,7 @@ pub fn set_permissions(
 
 #[tauri::command]
 pub fn set_ownership(
+    undo: tauri::State<'_, UndoState>,
     path: Option<String>,
     paths: Option<Vec<String>>,
     owner: Option<String>,
@@ -490,7 +528,12 @@ pub fn set_ownership(
         (_, Some(single)) => vec![single],
         _ => return map_api_result(Err(PermissionsError::invalid_input("No paths provided"))),
     };
-    map_api_result(set_ownership_batch(targets, owner, group))
+    map_api_result(set_ownership_batch(
+        Some(undo.inner()),
+        targets,
+        owner,
+        group,
+    ))
 }
 
 #[tauri::command]
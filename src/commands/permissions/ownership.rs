@@ -3,6 +3,7 @@ use std::collections::HashSet;
 #[cfg(not(unix))]
 use super::error::{PermissionsError, PermissionsErrorCode};
 use super::{error::PermissionsResult, PermissionInfo, OWNERSHIP_HELPER_FLAG};
+use crate::undo::UndoState;
 
 #[cfg(unix)]
 mod unix;
@@ -97,20 +98,22 @@ pub(super) fn list_ownership_principals(
 
 #[cfg(unix)]
 pub(super) fn set_ownership_batch(
+    undo: Option<&UndoState>,
     paths: Vec<String>,
     owner: Option<String>,
     group: Option<String>,
 ) -> PermissionsResult<PermissionInfo> {
-    unix::set_ownership_batch(paths, owner, group)
+    unix::set_ownership_batch(undo, paths, owner, group)
 }
 
 #[cfg(not(unix))]
 pub(super) fn set_ownership_batch(
+    undo: Option<&UndoState>,
     paths: Vec<String>,
     owner: Option<String>,
     group: Option<String>,
 ) -> PermissionsResult<PermissionInfo> {
-    let _ = (paths, owner, group);
+    let _ = (undo, paths, owner, group);
     Err(PermissionsError::new(
         PermissionsErrorCode::UnsupportedPlatform,
         "Ownership changes are not supported on this platform",
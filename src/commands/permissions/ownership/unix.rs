@@ -3,49 +3,21 @@ use std::fs;
 use std::os::unix::fs::MetadataExt;
 use std::process::{Command, Stdio};
 use std::sync::Mutex;
-use tracing::{debug, warn};
+use tracing::debug;
 
 use crate::{
     fs_utils::{check_no_symlink_components, sanitize_path_nofollow},
-    undo::{apply_ownership, ownership_snapshot, set_ownership_nofollow},
+    undo::{apply_ownership, ownership_snapshot, set_ownership_nofollow, Action, UndoState},
 };
 
 use super::super::{
     ensure_absolute_path,
     error::{PermissionsError, PermissionsErrorCode, PermissionsResult},
-    permission_info_fallback, refresh_permissions_after_apply, PermissionInfo,
-    OWNERSHIP_HELPER_FLAG,
+    permission_info_fallback, record_applied, refresh_permissions_after_apply, rollback_applied,
+    PermissionInfo, OWNERSHIP_HELPER_FLAG,
 };
 use super::{filter_principal_names, normalize_principal_spec, OwnershipPrincipalKind};
 
-#[derive(Clone)]
-struct OwnershipRollback {
-    path: std::path::PathBuf,
-    before: crate::undo::OwnershipSnapshot,
-}
-
-fn rollback_ownership_actions(actions: &[OwnershipRollback]) -> PermissionsResult<()> {
-    if actions.is_empty() {
-        return Ok(());
-    }
-    let mut errors: Vec<String> = Vec::new();
-    for action in actions.iter().rev() {
-        if let Err(err) = apply_ownership(&action.path, &action.before) {
-            errors.push(format!("{}: {err}", action.path.display()));
-        }
-    }
-    if errors.is_empty() {
-        Ok(())
-    } else {
-        let joined = errors.join("; ");
-        warn!(error = %joined, "ownership rollback failed");
-        Err(PermissionsError::new(
-            PermissionsErrorCode::RollbackFailed,
-            joined,
-        ))
-    }
-}
-
 #[derive(serde::Serialize, serde::Deserialize)]
 struct OwnershipHelperRequest {
     paths: Vec<String>,
@@ -312,7 +284,10 @@ struct OwnershipTarget {
     gid_update: Option<u32>,
 }
 
+/// Changes made directly are recorded in `undo` as one entry. Changes made through the
+/// pkexec helper are not: undoing them would need the same elevation again.
 fn set_ownership_batch_impl(
+    undo: Option<&UndoState>,
     paths: Vec<String>,
     owner: Option<String>,
     group: Option<String>,
@@ -366,7 +341,7 @@ fn set_ownership_batch_impl(
         });
     }
 
-    let mut rollbacks: Vec<OwnershipRollback> = Vec::with_capacity(targets.len());
+    let mut applied: Vec<Action> = Vec::with_capacity(targets.len());
     let mut escalated = false;
 
     for target in &targets {
@@ -422,24 +397,17 @@ fn set_ownership_batch_impl(
                 },
             };
             if after.uid != target.before.uid || after.gid != target.before.gid {
-                rollbacks.push(OwnershipRollback {
+                applied.push(Action::SetOwnership {
                     path: target.target.clone(),
                     before: target.before.clone(),
+                    after,
                 });
             }
             Ok(())
         })();
 
         if let Err(err) = apply_result {
-            if let Err(rollback_err) = rollback_ownership_actions(&rollbacks) {
-                return Err(PermissionsError::new(
-                    PermissionsErrorCode::RollbackFailed,
-                    format!(
-                        "{err}; rollback failed ({rollback_err}). System may be partially changed"
-                    ),
-                ));
-            }
-            return Err(err);
+            return Err(rollback_applied(&applied, err));
         }
         if escalated {
             break;
@@ -459,7 +427,9 @@ fn set_ownership_batch_impl(
         }
         changed
     } else {
-        !rollbacks.is_empty()
+        let changed = !applied.is_empty();
+        record_applied(undo, applied);
+        changed
     };
 
     if let Some(path) = first_path {
@@ -470,11 +440,12 @@ fn set_ownership_batch_impl(
 }
 
 pub(super) fn set_ownership_batch(
+    undo: Option<&UndoState>,
     paths: Vec<String>,
     owner: Option<String>,
     group: Option<String>,
 ) -> PermissionsResult<PermissionInfo> {
-    set_ownership_batch_impl(paths, owner, group, true)
+    set_ownership_batch_impl(undo, paths, owner, group, true)
 }
 
 pub(super) fn run_ownership_helper_from_stdin() -> PermissionsResult<()> {
@@ -494,7 +465,7 @@ pub(super) fn run_ownership_helper_from_stdin() -> PermissionsResult<()> {
             format!("Invalid helper input: {e}"),
         )
     })?;
-    set_ownership_batch_impl(request.paths, request.owner, request.group, false).map(|_| ())
+    set_ownership_batch_impl(None, request.paths, request.owner, request.group, false).map(|_| ())
 }
 
 #[cfg(test)]
@@ -517,7 +488,7 @@ mod tests {
     }
 
     #[test]
-    fn rollback_ownership_actions_reports_partial_failure() {
+    fn rollback_applied_reports_partial_failure() {
         let path = temp_path("owner-rollback-partial-ok");
         let missing = temp_path("owner-rollback-partial-missing");
         fs::write(&path, b"test").expect("write test file");
@@ -525,18 +496,21 @@ mod tests {
         let expected_uid = before.uid;
         let expected_gid = before.gid;
 
-        let actions = vec![
-            OwnershipRollback {
-                path: path.clone(),
+        let applied = vec![
+            Action::SetOwnership {
+                path: missing.clone(),
                 before: before.clone(),
+                after: before.clone(),
             },
-            OwnershipRollback {
-                path: missing.clone(),
-                before,
+            Action::SetOwnership {
+                path: path.clone(),
+                before: before.clone(),
+                after: before,
             },
         ];
 
-        let err = rollback_ownership_actions(&actions).expect_err("rollback should fail");
+        let err = rollback_applied(&applied, PermissionsError::invalid_input("later failure"));
+        assert_eq!(err.code_enum(), PermissionsErrorCode::RollbackFailed);
         assert!(err.to_string().contains(missing.to_string_lossy().as_ref()));
 
         let after = fs::symlink_metadata(&path).expect("metadata after");
@@ -1,14 +1,13 @@
 use std::fs::{self, Permissions};
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
-use std::path::PathBuf;
 use tracing::{debug, warn};
 
 #[cfg(target_os = "linux")]
 use crate::undo::set_unix_mode_nofollow;
 use crate::{
     fs_utils::{check_no_symlink_components, sanitize_path_nofollow},
-    undo::{apply_permissions, permissions_snapshot},
+    undo::{apply_permissions, permissions_snapshot, Action, UndoState},
 };
 
 #[cfg(target_os = "windows")]
@@ -18,39 +17,13 @@ use super::AccessBits;
 use super::{
     ensure_absolute_path,
     error::{PermissionsError, PermissionsErrorCode, PermissionsResult},
-    permission_info_fallback, refresh_permissions_after_apply, AccessUpdate, PermissionInfo,
+    permission_info_fallback, record_applied, refresh_permissions_after_apply, rollback_applied,
+    AccessUpdate, PermissionInfo,
 };
 
-#[derive(Clone)]
-struct PermissionRollback {
-    path: PathBuf,
-    before: crate::undo::PermissionsSnapshot,
-}
-
-fn rollback_permissions_actions(actions: &[PermissionRollback]) -> PermissionsResult<()> {
-    if actions.is_empty() {
-        return Ok(());
-    }
-    let mut errors: Vec<String> = Vec::new();
-    for action in actions.iter().rev() {
-        if let Err(err) = apply_permissions(&action.path, &action.before) {
-            errors.push(format!("{}: {err}", action.path.display()));
-        }
-    }
-    if errors.is_empty() {
-        Ok(())
-    } else {
-        let joined = errors.join("; ");
-        warn!(error = %joined, "permissions rollback failed");
-        Err(PermissionsError::new(
-            PermissionsErrorCode::RollbackFailed,
-            joined,
-        ))
-    }
-}
-
 #[cfg(unix)]
 pub(super) fn set_permissions_batch(
+    undo: Option<&UndoState>,
     paths: Vec<String>,
     read_only: Option<bool>,
     executable: Option<bool>,
@@ -66,7 +39,7 @@ pub(super) fn set_permissions_batch(
     }
 
     let first_path = paths.first().cloned();
-    let mut rollbacks: Vec<PermissionRollback> = Vec::with_capacity(paths.len());
+    let mut applied: Vec<Action> = Vec::with_capacity(paths.len());
 
     for path in paths {
         let owner_update = owner.clone();
@@ -214,8 +187,8 @@ pub(super) fn set_permissions_batch(
                         )
                     })?;
                 }
-                match permissions_snapshot(&target) {
-                    Ok(_) => {}
+                let after = match permissions_snapshot(&target) {
+                    Ok(after) => after,
                     Err(snapshot_err) => match apply_permissions(&target, &before) {
                         Ok(()) => {
                             return Err(PermissionsError::new(
@@ -238,10 +211,11 @@ pub(super) fn set_permissions_batch(
                             ));
                         }
                     },
-                }
-                rollbacks.push(PermissionRollback {
+                };
+                applied.push(Action::SetPermissions {
                     path: target.clone(),
                     before,
+                    after,
                 });
             }
             Ok(())
@@ -249,19 +223,12 @@ pub(super) fn set_permissions_batch(
 
         if let Err(err) = apply_result {
             warn!(path = %path, error = %err, "set_permissions failed");
-            if let Err(rollback_err) = rollback_permissions_actions(&rollbacks) {
-                return Err(PermissionsError::new(
-                    PermissionsErrorCode::RollbackFailed,
-                    format!(
-                        "{err}; rollback failed ({rollback_err}). System may be partially changed"
-                    ),
-                ));
-            }
-            return Err(err);
+            return Err(rollback_applied(&applied, err));
         }
     }
 
-    let changed_any = !rollbacks.is_empty();
+    let changed_any = !applied.is_empty();
+    record_applied(undo, applied);
 
     if let Some(path) = first_path {
         return refresh_permissions_after_apply(path, changed_any);
@@ -272,6 +239,7 @@ pub(super) fn set_permissions_batch(
 
 #[cfg(target_os = "windows")]
 pub(super) fn set_permissions_batch(
+    undo: Option<&UndoState>,
     paths: Vec<String>,
     read_only: Option<bool>,
     executable: Option<bool>,
@@ -287,7 +255,7 @@ pub(super) fn set_permissions_batch(
     }
 
     let first_path = paths.first().cloned();
-    let mut rollbacks: Vec<PermissionRollback> = Vec::with_capacity(paths.len());
+    let mut applied: Vec<Action> = Vec::with_capacity(paths.len());
 
     for path in paths {
         let owner_update = owner.clone();
@@ -395,8 +363,8 @@ pub(super) fn set_permissions_batch(
             }
 
             if changed {
-                match permissions_snapshot(&target) {
-                    Ok(_) => {}
+                let after = match permissions_snapshot(&target) {
+                    Ok(after) => after,
                     Err(snapshot_err) => match apply_permissions(&target, &before) {
                         Ok(()) => {
                             return Err(PermissionsError::new(
@@ -419,10 +387,11 @@ pub(super) fn set_permissions_batch(
                             ));
                         }
                     },
-                }
-                rollbacks.push(PermissionRollback {
+                };
+                applied.push(Action::SetPermissions {
                     path: target.clone(),
                     before,
+                    after,
                 });
             }
             Ok(())
@@ -430,19 +399,12 @@ pub(super) fn set_permissions_batch(
 
         if let Err(err) = apply_result {
             warn!(path = %path, error = %err, "set_permissions failed");
-            if let Err(rollback_err) = rollback_permissions_actions(&rollbacks) {
-                return Err(PermissionsError::new(
-                    PermissionsErrorCode::RollbackFailed,
-                    format!(
-                        "{err}; rollback failed ({rollback_err}). System may be partially changed"
-                    ),
-                ));
-            }
-            return Err(err);
+            return Err(rollback_applied(&applied, err));
         }
     }
 
-    let changed_any = !rollbacks.is_empty();
+    let changed_any = !applied.is_empty();
+    record_applied(undo, applied);
 
     if let Some(path) = first_path {
         return refresh_permissions_after_apply(path, changed_any);
@@ -453,6 +415,7 @@ pub(super) fn set_permissions_batch(
 
 #[cfg(not(any(unix, target_os = "windows")))]
 pub(super) fn set_permissions_batch(
+    undo: Option<&UndoState>,
     paths: Vec<String>,
     read_only: Option<bool>,
     executable: Option<bool>,
@@ -496,30 +459,59 @@ mod tests {
     }
 
     #[test]
-    fn rollback_permissions_actions_reports_partial_failure() {
+    fn rollback_applied_keeps_batch_consistent_on_partial_failure() {
         let path = temp_path("perm-rollback-partial-ok");
         let missing = temp_path("perm-rollback-partial-missing");
         fs::write(&path, b"test").unwrap();
         fs::set_permissions(&path, PermissionsExt::from_mode(0o600)).unwrap();
         let before = permissions_snapshot(&path).unwrap();
         fs::set_permissions(&path, PermissionsExt::from_mode(0o640)).unwrap();
+        let after = permissions_snapshot(&path).unwrap();
 
-        let actions = vec![
-            PermissionRollback {
-                path: path.clone(),
+        let applied = vec![
+            Action::SetPermissions {
+                path: missing.clone(),
                 before: before.clone(),
+                after: after.clone(),
             },
-            PermissionRollback {
-                path: missing.clone(),
+            Action::SetPermissions {
+                path: path.clone(),
                 before,
+                after,
             },
         ];
 
-        let err = rollback_permissions_actions(&actions).unwrap_err();
+        let err = rollback_applied(&applied, PermissionsError::invalid_input("later failure"));
+        assert_eq!(err.code_enum(), PermissionsErrorCode::RollbackFailed);
+        assert!(err.to_string().contains("later failure"));
         assert!(err.to_string().contains(missing.to_string_lossy().as_ref()));
 
-        let restored_mode = fs::metadata(&path).unwrap().permissions().mode() & 0o777;
-        assert_eq!(restored_mode, 0o600);
+        // The restored entry is re-applied so the batch is not left half reverted.
+        let mode = fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o640);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn rollback_applied_restores_every_entry() {
+        let path = temp_path("perm-rollback-ok");
+        fs::write(&path, b"test").unwrap();
+        fs::set_permissions(&path, PermissionsExt::from_mode(0o600)).unwrap();
+        let before = permissions_snapshot(&path).unwrap();
+        fs::set_permissions(&path, PermissionsExt::from_mode(0o640)).unwrap();
+        let after = permissions_snapshot(&path).unwrap();
+
+        let applied = vec![Action::SetPermissions {
+            path: path.clone(),
+            before,
+            after,
+        }];
+        let err = rollback_applied(&applied, PermissionsError::invalid_input("later failure"));
+        assert_eq!(err.code_enum(), PermissionsErrorCode::InvalidInput);
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o600);
 
         let _ = fs::remove_file(&path);
     }
@@ -27,6 +27,7 @@ fn read_only_toggle_does_not_grant_world_write() {
     fs::set_permissions(&path, PermissionsExt::from_mode(0o664)).unwrap();
 
     set_permissions_batch(
+        None,
         vec![path.to_string_lossy().to_string()],
         Some(true),
         None,
@@ -39,6 +40,7 @@ fn read_only_toggle_does_not_grant_world_write() {
     assert_eq!(after_ro & 0o222, 0o020); // only owner write cleared
 
     set_permissions_batch(
+        None,
         vec![path.to_string_lossy().to_string()],
         Some(false),
         None,
@@ -60,6 +62,7 @@ fn executable_toggle_sets_owner_only() {
     fs::set_permissions(&path, PermissionsExt::from_mode(0o654)).unwrap(); // owner no exec, group exec
 
     set_permissions_batch(
+        None,
         vec![path.to_string_lossy().to_string()],
         None,
         Some(true),
@@ -72,6 +75,7 @@ fn executable_toggle_sets_owner_only() {
     assert_eq!(after_exec & 0o111, 0o110); // owner + existing group preserved
 
     set_permissions_batch(
+        None,
         vec![path.to_string_lossy().to_string()],
         None,
         Some(false),
@@ -94,6 +98,7 @@ fn owner_group_other_bits_update() {
 
     // Enable other read + owner exec without reintroducing world write.
     set_permissions_batch(
+        None,
         vec![path.to_string_lossy().to_string()],
         None,
         None,
@@ -112,6 +117,7 @@ fn owner_group_other_bits_update() {
     assert_eq!(mode & 0o001, 0);
 
     set_permissions_batch(
+        None,
         vec![path.to_string_lossy().to_string()],
         None,
         None,
@@ -132,7 +138,8 @@ fn owner_group_other_bits_update() {
 fn set_ownership_requires_owner_or_group() {
     let path = temp_file("owner-empty");
     fs::write(&path, b"test").unwrap();
-    let err = match set_ownership_batch(vec![path.to_string_lossy().to_string()], None, None) {
+    let err = match set_ownership_batch(None, vec![path.to_string_lossy().to_string()], None, None)
+    {
         Ok(_) => panic!("set_ownership_batch should fail without owner/group"),
         Err(err) => err,
     };
@@ -147,6 +154,7 @@ fn set_ownership_rejects_unknown_principals() {
     let path = temp_file("owner-unknown");
     fs::write(&path, b"test").unwrap();
     let err = match set_ownership_batch(
+        None,
         vec![path.to_string_lossy().to_string()],
         Some("browsey-user-does-not-exist".into()),
         None,
@@ -157,6 +165,7 @@ fn set_ownership_rejects_unknown_principals() {
     assert!(err.to_string().contains("User not found"));
 
     let err = match set_ownership_batch(
+        None,
         vec![path.to_string_lossy().to_string()],
         None,
         Some("browsey-group-does-not-exist".into()),
@@ -177,6 +186,7 @@ fn set_ownership_noop_with_current_ids_succeeds() {
     let gid = meta.gid();
 
     let info = set_ownership_batch(
+        None,
         vec![path.to_string_lossy().to_string()],
         Some(uid.to_string()),
         Some(gid.to_string()),
@@ -189,6 +199,7 @@ fn set_ownership_noop_with_current_ids_succeeds() {
 #[test]
 fn set_permissions_rejects_relative_path() {
     let err = match set_permissions_batch(
+        None,
         vec!["relative-path.txt".into()],
         Some(true),
         None,
@@ -222,6 +233,7 @@ fn set_permissions_rejects_symlink_components() {
     symlink(&real_dir, &link_dir).unwrap();
 
     let err = match set_permissions_batch(
+        None,
         vec![via_link_path.to_string_lossy().to_string()],
         Some(true),
         None,
@@ -295,6 +307,7 @@ fn set_permissions_rolls_back_when_later_target_fails_validation() {
 
     let before_mode = fs::metadata(&first_path).unwrap().permissions().mode() & 0o777;
     let err = match set_permissions_batch(
+        None,
         vec![
             first_path.to_string_lossy().to_string(),
             via_link_path.to_string_lossy().to_string(),
@@ -327,6 +340,7 @@ fn set_permissions_noop_returns_actual_state() {
     let before_mode = fs::metadata(&path).unwrap().permissions().mode() & 0o777;
 
     let info = set_permissions_batch(
+        None,
         vec![path.to_string_lossy().to_string()],
         Some(false), // owner write already set, so this is a no-op
         None,
@@ -354,6 +368,7 @@ fn set_ownership_rejects_relative_path() {
     let uid = fs::symlink_metadata(&path).unwrap().uid();
 
     let err = match set_ownership_batch(
+        None,
         vec!["relative-owner-path".into()],
         Some(uid.to_string()),
         None,
@@ -387,10 +402,55 @@ fn refresh_permissions_after_apply_errors_when_no_change() {
 }
 
 #[test]
-fn set_permissions_does_not_record_undo_history() {
+fn set_permissions_records_undo_that_restores_previous_mode() {
+    let path_a = temp_file("perm-undo-a");
+    let path_b = temp_file("perm-undo-b");
+    fs::write(&path_a, b"a").unwrap();
+    fs::write(&path_b, b"b").unwrap();
+    fs::set_permissions(&path_a, PermissionsExt::from_mode(0o640)).unwrap();
+    fs::set_permissions(&path_b, PermissionsExt::from_mode(0o604)).unwrap();
+
+    let undo = UndoState::default();
+    set_permissions_batch(
+        Some(&undo),
+        vec![
+            path_a.to_string_lossy().to_string(),
+            path_b.to_string_lossy().to_string(),
+        ],
+        None,
+        Some(true),
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    let mode = |path: &std::path::Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+    assert_eq!(mode(&path_a), 0o740);
+    assert_eq!(mode(&path_b), 0o704);
+
+    let history = undo.history().unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].kind, "batch");
+    assert_eq!(history[0].action_count, 2);
+
+    undo.undo().unwrap();
+    assert_eq!(mode(&path_a), 0o640);
+    assert_eq!(mode(&path_b), 0o604);
+
+    undo.redo().unwrap();
+    assert_eq!(mode(&path_a), 0o740);
+    assert_eq!(mode(&path_b), 0o704);
+
+    let _ = fs::remove_file(&path_a);
+    let _ = fs::remove_file(&path_b);
+}
+
+#[test]
+fn set_permissions_without_change_does_not_record_undo_history() {
     let src = temp_file("perm-undo-src");
     let dst = temp_file("perm-undo-dst");
     fs::write(&src, b"undo-test").unwrap();
+    fs::set_permissions(&src, PermissionsExt::from_mode(0o444)).unwrap();
     let _ = fs::remove_file(&dst);
 
     let undo = UndoState::default();
@@ -399,10 +459,9 @@ fn set_permissions_does_not_record_undo_history() {
         to: dst.clone(),
     })
     .unwrap();
-    assert!(!src.exists());
-    assert!(dst.exists());
 
     set_permissions_batch(
+        Some(&undo),
         vec![dst.to_string_lossy().to_string()],
         Some(true),
         None,
@@ -424,7 +483,7 @@ fn set_permissions_does_not_record_undo_history() {
 }
 
 #[test]
-fn set_ownership_does_not_record_undo_history() {
+fn set_ownership_without_change_does_not_record_undo_history() {
     let src = temp_file("owner-undo-src");
     let dst = temp_file("owner-undo-dst");
     fs::write(&src, b"undo-test").unwrap();
@@ -441,6 +500,7 @@ fn set_ownership_does_not_record_undo_history() {
 
     let meta = fs::symlink_metadata(&dst).unwrap();
     set_ownership_batch(
+        Some(&undo),
         vec![dst.to_string_lossy().to_string()],
         Some(meta.uid().to_string()),
         Some(meta.gid().to_string()),
@@ -0,0 +1,371 @@
+//! Saved search CRUD commands and their listing as virtual folders.
+
+use crate::commands::listing::DirListing;
+use crate::db::{self, SavedSearchRow};
+use crate::entry::FsEntry;
+use crate::errors::api_error::ApiResult;
+use crate::runtime_lifecycle;
+use crate::sorting::{sort_entries, SortSpec};
+use crate::tasks::CancelState;
+use error::{map_api_result, SavedSearchError, SavedSearchErrorCode, SavedSearchResult};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+mod error;
+
+/// Prefix of the listing scope that opens a saved search, e.g. `saved:3`.
+pub const SAVED_SCOPE_PREFIX: &str = "saved:";
+
+/// How long the matches of a listed saved search keep answering facet requests for it.
+const LAST_RESULTS_TTL: Duration = Duration::from_secs(60);
+
+fn map_db_open_error(error: crate::db::DbError) -> SavedSearchError {
+    SavedSearchError::new(SavedSearchErrorCode::DatabaseOpenFailed, error.to_string())
+}
+
+fn map_db_read_error(error: crate::db::DbError) -> SavedSearchError {
+    SavedSearchError::new(
+        SavedSearchErrorCode::SavedSearchesReadFailed,
+        error.to_string(),
+    )
+}
+
+fn map_db_write_error(error: crate::db::DbError) -> SavedSearchError {
+    SavedSearchError::new(
+        SavedSearchErrorCode::SavedSearchesWriteFailed,
+        error.to_string(),
+    )
+}
+
+fn not_found(id: i64) -> SavedSearchError {
+    SavedSearchError::new(
+        SavedSearchErrorCode::NotFound,
+        format!("Saved search {id} not found"),
+    )
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SavedSearchOptions {
+    pub include_ignored: bool,
+    pub search_archives: bool,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct SavedSearch {
+    pub id: i64,
+    pub name: String,
+    pub query: String,
+    pub root: String,
+    pub options: SavedSearchOptions,
+}
+
+impl From<SavedSearchRow> for SavedSearch {
+    fn from(row: SavedSearchRow) -> Self {
+        // Options written by a newer build may carry fields we do not know; missing or
+        // unreadable options fall back to defaults rather than hiding the search.
+        let options = serde_json::from_str(&row.options).unwrap_or_default();
+        Self {
+            id: row.id,
+            name: row.name,
+            query: row.query,
+            root: row.root,
+            options,
+        }
+    }
+}
+
+/// Parses the id out of a `saved:<id>` scope. Returns None for other scopes.
+pub fn parse_saved_scope(scope: &str) -> Option<i64> {
+    scope.strip_prefix(SAVED_SCOPE_PREFIX)?.trim().parse().ok()
+}
+
+fn validated_row(
+    id: i64,
+    name: String,
+    query: String,
+    root: String,
+    options: Option<SavedSearchOptions>,
+) -> SavedSearchResult<SavedSearchRow> {
+    let name = name.trim().to_string();
+    let query = query.trim().to_string();
+    let root = root.trim().to_string();
+    for (field, value) in [("name", &name), ("query", &query), ("root", &root)] {
+        if value.is_empty() {
+            return Err(SavedSearchError::new(
+                SavedSearchErrorCode::InvalidInput,
+                format!("Saved search {field} cannot be empty"),
+            ));
+        }
+    }
+    let options = serde_json::to_string(&options.unwrap_or_default()).map_err(|error| {
+        SavedSearchError::new(SavedSearchErrorCode::InvalidInput, error.to_string())
+    })?;
+    Ok(SavedSearchRow {
+        id,
+        name,
+        query,
+        root,
+        options,
+    })
+}
+
+#[tauri::command]
+pub fn list_saved_searches() -> ApiResult<Vec<SavedSearch>> {
+    map_api_result(list_saved_searches_impl())
+}
+
+fn list_saved_searches_impl() -> SavedSearchResult<Vec<SavedSearch>> {
+    let conn = db::open().map_err(map_db_open_error)?;
+    let rows = db::list_saved_searches(&conn).map_err(map_db_read_error)?;
+    Ok(rows.into_iter().map(SavedSearch::from).collect())
+}
+
+#[tauri::command]
+pub fn add_saved_search(
+    name: String,
+    query: String,
+    root: String,
+    options: Option<SavedSearchOptions>,
+) -> ApiResult<SavedSearch> {
+    map_api_result(add_saved_search_impl(name, query, root, options))
+}
+
+fn add_saved_search_impl(
+    name: String,
+    query: String,
+    root: String,
+    options: Option<SavedSearchOptions>,
+) -> SavedSearchResult<SavedSearch> {
+    let mut row = validated_row(0, name, query, root, options)?;
+    let conn = db::open().map_err(map_db_open_error)?;
+    row.id = db::insert_saved_search(&conn, &row.name, &row.query, &row.root, &row.options)
+        .map_err(map_db_write_error)?;
+    Ok(row.into())
+}
+
+#[tauri::command]
+pub fn update_saved_search(
+    id: i64,
+    name: String,
+    query: String,
+    root: String,
+    options: Option<SavedSearchOptions>,
+) -> ApiResult<SavedSearch> {
+    map_api_result(update_saved_search_impl(id, name, query, root, options))
+}
+
+fn update_saved_search_impl(
+    id: i64,
+    name: String,
+    query: String,
+    root: String,
+    options: Option<SavedSearchOptions>,
+) -> SavedSearchResult<SavedSearch> {
+    let row = validated_row(id, name, query, root, options)?;
+    let conn = db::open().map_err(map_db_open_error)?;
+    if !db::update_saved_search(&conn, &row).map_err(map_db_write_error)? {
+        return Err(not_found(id));
+    }
+    Ok(row.into())
+}
+
+#[tauri::command]
+pub fn remove_saved_search(id: i64) -> ApiResult<()> {
+    map_api_result(remove_saved_search_impl(id))
+}
+
+fn remove_saved_search_impl(id: i64) -> SavedSearchResult<()> {
+    let conn = db::open().map_err(map_db_open_error)?;
+    if !db::delete_saved_search(&conn, id).map_err(map_db_write_error)? {
+        return Err(not_found(id));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_saved_search(
+    app: tauri::AppHandle,
+    cancel: tauri::State<'_, CancelState>,
+    id: i64,
+    sort: Option<SortSpec>,
+    cancel_id: Option<String>,
+) -> ApiResult<DirListing> {
+    let cancel_state = cancel.inner().clone();
+    let task = tauri::async_runtime::spawn_blocking(move || {
+        list_saved_search_sync(&app, &cancel_state, id, sort, cancel_id)
+    });
+    match task.await {
+        Ok(result) => result,
+        Err(error) => map_api_result(Err(SavedSearchError::new(
+            SavedSearchErrorCode::TaskFailed,
+            format!("saved search task panicked: {error}"),
+        ))),
+    }
+}
+
+/// Runs saved search `id` to completion and lists its matches like a folder named after it.
+/// `cancel_id` registers the run with the cancel registry so `cancel_task` can stop it.
+fn list_saved_search_sync(
+    app: &tauri::AppHandle,
+    cancel_state: &CancelState,
+    id: i64,
+    sort: Option<SortSpec>,
+    cancel_id: Option<String>,
+) -> ApiResult<DirListing> {
+    let saved = map_api_result(load_saved_search(id))?;
+    let cancel_guard = map_api_result(
+        cancel_id
+            .map(|id| cancel_state.register(id))
+            .transpose()
+            .map_err(|error| {
+                SavedSearchError::new(SavedSearchErrorCode::TaskFailed, error.to_string())
+            }),
+    )?;
+    let cancel = cancel_guard
+        .as_ref()
+        .map(|guard| guard.token())
+        .unwrap_or_default();
+    let mut entries = run_saved_search(app, &saved, &cancel)?;
+    sort_entries(&mut entries, sort);
+    Ok(DirListing {
+        current: saved.name,
+        entries,
+    })
+}
+
+/// The matches of saved search `id` for its facets. A listing of the same search from the
+/// last minute is reused, so opening the facets does not run the search a second time.
+pub fn saved_search_facet_entries(app: &tauri::AppHandle, id: i64) -> ApiResult<Vec<FsEntry>> {
+    let saved = map_api_result(load_saved_search(id))?;
+    match last_results_for(&saved) {
+        Some(entries) => Ok(entries),
+        None => run_saved_search(app, &saved, &AtomicBool::new(false)),
+    }
+}
+
+fn run_saved_search(
+    app: &tauri::AppHandle,
+    saved: &SavedSearch,
+    cancel: &AtomicBool,
+) -> ApiResult<Vec<FsEntry>> {
+    let should_stop = || cancel.load(Ordering::Relaxed) || runtime_lifecycle::is_shutting_down(app);
+    let entries = crate::commands::search::collect_search_entries(
+        Some(saved.root.clone()),
+        &saved.query,
+        saved.options.include_ignored,
+        saved.options.search_archives,
+        cancel,
+        &should_stop,
+    )?;
+    remember_results(saved, &entries);
+    Ok(entries)
+}
+
+struct LastResults {
+    search: SavedSearch,
+    at: Instant,
+    entries: Vec<FsEntry>,
+}
+
+fn last_results() -> &'static Mutex<Option<LastResults>> {
+    static LAST: OnceLock<Mutex<Option<LastResults>>> = OnceLock::new();
+    LAST.get_or_init(|| Mutex::new(None))
+}
+
+fn remember_results(search: &SavedSearch, entries: &[FsEntry]) {
+    if let Ok(mut last) = last_results().lock() {
+        *last = Some(LastResults {
+            search: search.clone(),
+            at: Instant::now(),
+            entries: entries.to_vec(),
+        });
+    }
+}
+
+/// The remembered matches of `search`, unless it was edited since or they are too old.
+fn last_results_for(search: &SavedSearch) -> Option<Vec<FsEntry>> {
+    let last = last_results().lock().ok()?;
+    last.as_ref()
+        .filter(|last| last.search == *search && last.at.elapsed() < LAST_RESULTS_TTL)
+        .map(|last| last.entries.clone())
+}
+
+fn load_saved_search(id: i64) -> SavedSearchResult<SavedSearch> {
+    let conn = db::open().map_err(map_db_open_error)?;
+    db::get_saved_search(&conn, id)
+        .map_err(map_db_read_error)?
+        .map(SavedSearch::from)
+        .ok_or_else(|| not_found(id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_saved_scope_reads_numeric_ids_only() {
+        assert_eq!(parse_saved_scope("saved:12"), Some(12));
+        assert_eq!(parse_saved_scope("saved:abc"), None);
+        assert_eq!(parse_saved_scope("saved:"), None);
+        assert_eq!(parse_saved_scope("starred"), None);
+    }
+
+    #[test]
+    fn unreadable_options_fall_back_to_defaults() {
+        let saved = SavedSearch::from(SavedSearchRow {
+            id: 1,
+            name: "All".to_string(),
+            query: "a".to_string(),
+            root: "/".to_string(),
+            options: "not json".to_string(),
+        });
+        assert_eq!(saved.options, SavedSearchOptions::default());
+    }
+
+    #[test]
+    fn validated_row_rejects_blank_fields() {
+        let error = validated_row(0, " ".into(), "a".into(), "/".into(), None).unwrap_err();
+        assert!(error.to_string().contains("name"));
+        let row = validated_row(
+            0,
+            " Docs ".into(),
+            "ext:pdf".into(),
+            "/home".into(),
+            Some(SavedSearchOptions {
+                include_ignored: true,
+                search_archives: false,
+            }),
+        )
+        .expect("valid row");
+        assert_eq!(row.name, "Docs");
+        assert_eq!(
+            row.options,
+            r#"{"includeIgnored":true,"searchArchives":false}"#
+        );
+    }
+
+    #[test]
+    fn remembered_results_only_answer_the_same_search() {
+        let saved = SavedSearch {
+            id: 7,
+            name: "Reports".to_string(),
+            query: "ext:pdf".to_string(),
+            root: "/srv/remembered".to_string(),
+            options: SavedSearchOptions::default(),
+        };
+        remember_results(&saved, &[]);
+        assert_eq!(
+            last_results_for(&saved).map(|entries| entries.len()),
+            Some(0)
+        );
+
+        let edited = SavedSearch {
+            query: "ext:doc".to_string(),
+            ..saved.clone()
+        };
+        assert!(last_results_for(&edited).is_none());
+    }
+}
@@ -0,0 +1,67 @@
+use crate::errors::{
+    api_error::ApiResult,
+    domain::{self, DomainError, ErrorCode},
+};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum SavedSearchErrorCode {
+    DatabaseOpenFailed,
+    SavedSearchesReadFailed,
+    SavedSearchesWriteFailed,
+    InvalidInput,
+    NotFound,
+    TaskFailed,
+}
+
+impl ErrorCode for SavedSearchErrorCode {
+    fn as_code_str(self) -> &'static str {
+        match self {
+            Self::DatabaseOpenFailed => "database_open_failed",
+            Self::SavedSearchesReadFailed => "saved_searches_read_failed",
+            Self::SavedSearchesWriteFailed => "saved_searches_write_failed",
+            Self::InvalidInput => "invalid_input",
+            Self::NotFound => "not_found",
+            Self::TaskFailed => "task_failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct SavedSearchError {
+    code: SavedSearchErrorCode,
+    message: String,
+}
+
+impl SavedSearchError {
+    pub(super) fn new(code: SavedSearchErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for SavedSearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for SavedSearchError {}
+
+impl DomainError for SavedSearchError {
+    fn code_str(&self) -> &'static str {
+        self.code.as_code_str()
+    }
+
+    fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+pub(super) type SavedSearchResult<T> = Result<T, SavedSearchError>;
+
+pub(super) fn map_api_result<T>(result: SavedSearchResult<T>) -> ApiResult<T> {
+    domain::map_api_result(result)
+}
@@ -0,0 +1,247 @@
+//! Streaming file-content matching for `content:` query predicates.
+
+use super::query::{content_matchers, ContentMatchMode, ContentMatcher, Expr};
+use regex::bytes::{Regex, RegexBuilder};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Files above this size are never opened for content matching.
+pub(super) const CONTENT_MAX_FILE_BYTES: u64 = 64 * 1024 * 1024;
+const READ_BUFFER_BYTES: usize = 64 * 1024;
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+const SNIPPET_MAX_CHARS: usize = 160;
+const SNIPPET_LEAD_CHARS: usize = 40;
+const CANCEL_CHECK_LINES: u64 = 512;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ContentHit {
+    pub line: u64,
+    pub snippet: String,
+}
+
+/// Outcome of scanning one file for a `content:` pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum ContentScan {
+    /// The first matching line.
+    Found(ContentHit),
+    /// The file was read to the end, or is binary, and nothing matched.
+    NotFound,
+    /// The file was too large, could not be read or the scan was cancelled, so whether it
+    /// matches is not known.
+    Unknown,
+}
+
+pub(super) struct ContentSearcher {
+    patterns: Vec<(ContentMatcher, Regex)>,
+}
+
+impl ContentSearcher {
+    /// Compiles the `content:` predicates of `expr`; returns `None` when there are none.
+    pub(super) fn from_query(expr: &Expr) -> Result<Option<Self>, regex::Error> {
+        let matchers = content_matchers(expr);
+        if matchers.is_empty() {
+            return Ok(None);
+        }
+        let mut patterns = Vec::with_capacity(matchers.len());
+        for matcher in matchers {
+            let regex = compile(&matcher)?;
+            patterns.push((matcher, regex));
+        }
+        Ok(Some(Self { patterns }))
+    }
+
+    /// Scans `path` for the first line matching `matcher`. Binary files never match; files
+    /// over [`CONTENT_MAX_FILE_BYTES`], unreadable files and cancelled scans are unknown.
+    pub(super) fn probe(
+        &self,
+        path: &Path,
+        matcher: &ContentMatcher,
+        cancel: &AtomicBool,
+    ) -> ContentScan {
+        let Some(regex) = self
+            .patterns
+            .iter()
+            .find(|(candidate, _)| candidate == matcher)
+            .map(|(_, regex)| regex)
+        else {
+            return ContentScan::Unknown;
+        };
+        let Ok(file) = File::open(path) else {
+            return ContentScan::Unknown;
+        };
+        match file.metadata() {
+            Ok(meta) if meta.len() <= CONTENT_MAX_FILE_BYTES => {}
+            _ => return ContentScan::Unknown,
+        }
+        scan_reader(file.take(CONTENT_MAX_FILE_BYTES), regex, cancel)
+    }
+}
+
+fn compile(matcher: &ContentMatcher) -> Result<Regex, regex::Error> {
+    match matcher.mode {
+        ContentMatchMode::Substring => RegexBuilder::new(&regex::escape(&matcher.raw))
+            .case_insensitive(true)
+            .build(),
+        ContentMatchMode::Regex => RegexBuilder::new(&matcher.raw).build(),
+    }
+}
+
+fn scan_reader<R: Read>(reader: R, regex: &Regex, cancel: &AtomicBool) -> ContentScan {
+    let mut reader = BufReader::with_capacity(READ_BUFFER_BYTES, reader);
+    {
+        let Ok(head) = reader.fill_buf() else {
+            return ContentScan::Unknown;
+        };
+        let sniff = &head[..head.len().min(BINARY_SNIFF_BYTES)];
+        if sniff.contains(&0) {
+            return ContentScan::NotFound;
+        }
+    }
+
+    let mut line = Vec::new();
+    let mut line_no: u64 = 0;
+    loop {
+        line.clear();
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) => return ContentScan::NotFound,
+            Ok(_) => {}
+            Err(_) => return ContentScan::Unknown,
+        }
+        line_no += 1;
+        if line_no.is_multiple_of(CANCEL_CHECK_LINES) && cancel.load(Ordering::Relaxed) {
+            return ContentScan::Unknown;
+        }
+        if let Some(found) = regex.find(&line) {
+            return ContentScan::Found(ContentHit {
+                line: line_no,
+                snippet: snippet_around(&line, found.start()),
+            });
+        }
+    }
+}
+
+fn snippet_around(line: &[u8], match_start: usize) -> String {
+    let text = String::from_utf8_lossy(line);
+    let text = text.trim_end_matches(['\r', '\n']);
+    let lead_chars = String::from_utf8_lossy(&line[..match_start.min(line.len())])
+        .chars()
+        .count();
+    let start = lead_chars.saturating_sub(SNIPPET_LEAD_CHARS);
+    let mut snippet: String = text
+        .chars()
+        .skip(start)
+        .take(SNIPPET_MAX_CHARS)
+        .collect::<String>()
+        .trim()
+        .to_string();
+    if start > 0 {
+        snippet.insert(0, '…');
+    }
+    if text.chars().count() > start + SNIPPET_MAX_CHARS {
+        snippet.push('…');
+    }
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        compile, scan_reader, snippet_around, ContentHit, ContentScan, CANCEL_CHECK_LINES,
+    };
+    use crate::commands::search::query::{ContentMatchMode, ContentMatcher};
+    use std::io::{Cursor, Read};
+    use std::sync::atomic::AtomicBool;
+
+    fn matcher(raw: &str, mode: ContentMatchMode) -> ContentMatcher {
+        ContentMatcher {
+            raw: raw.to_string(),
+            mode,
+        }
+    }
+
+    #[test]
+    fn finds_first_matching_line_case_insensitively() {
+        let regex = compile(&matcher("todo", ContentMatchMode::Substring)).unwrap();
+        let data = b"first line\nsecond line\n  // TODO: fix me\nTODO again\n";
+        let hit = scan_reader(Cursor::new(&data[..]), &regex, &AtomicBool::new(false));
+        assert_eq!(
+            hit,
+            ContentScan::Found(ContentHit {
+                line: 3,
+                snippet: "// TODO: fix me".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn regex_mode_matches_pattern() {
+        let regex = compile(&matcher(r"fn\s+main", ContentMatchMode::Regex)).unwrap();
+        let data = b"use std::io;\n\nfn  main() {}\n";
+        let hit = scan_reader(Cursor::new(&data[..]), &regex, &AtomicBool::new(false));
+        assert!(matches!(
+            hit,
+            ContentScan::Found(ContentHit { line: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn substring_mode_escapes_regex_metacharacters() {
+        let regex = compile(&matcher("a.b(", ContentMatchMode::Substring)).unwrap();
+        let data = b"axb(\na.b(\n";
+        let hit = scan_reader(Cursor::new(&data[..]), &regex, &AtomicBool::new(false));
+        assert!(matches!(
+            hit,
+            ContentScan::Found(ContentHit { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn skips_binary_content() {
+        let regex = compile(&matcher("needle", ContentMatchMode::Substring)).unwrap();
+        let data = b"\x7fELF\x00\x00needle\n";
+        assert_eq!(
+            scan_reader(Cursor::new(&data[..]), &regex, &AtomicBool::new(false)),
+            ContentScan::NotFound
+        );
+    }
+
+    #[test]
+    fn failed_reads_and_cancelled_scans_are_unknown() {
+        struct Failing;
+        impl Read for Failing {
+            fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("device gone"))
+            }
+        }
+        let regex = compile(&matcher("needle", ContentMatchMode::Substring)).unwrap();
+        assert_eq!(
+            scan_reader(Failing, &regex, &AtomicBool::new(false)),
+            ContentScan::Unknown
+        );
+
+        let data = "hay\n".repeat(CANCEL_CHECK_LINES as usize + 1);
+        assert_eq!(
+            scan_reader(Cursor::new(data.as_bytes()), &regex, &AtomicBool::new(true)),
+            ContentScan::Unknown
+        );
+        assert_eq!(
+            scan_reader(
+                Cursor::new(data.as_bytes()),
+                &regex,
+                &AtomicBool::new(false)
+            ),
+            ContentScan::NotFound
+        );
+    }
+
+    #[test]
+    fn snippet_is_trimmed_around_long_lines() {
+        let line = format!("{}needle{}", "x".repeat(100), "y".repeat(300));
+        let snippet = snippet_around(line.as_bytes(), 100);
+        assert!(snippet.starts_with('…'));
+        assert!(snippet.ends_with('…'));
+        assert!(snippet.contains("needle"));
+    }
+}
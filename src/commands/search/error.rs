@@ -13,6 +13,7 @@ pub(super) enum SearchErrorCode {
     DatabaseOpenFailed,
     DatabaseReadFailed,
     TaskFailed,
+    Cancelled,
     UnknownError,
 }
 
@@ -26,6 +27,7 @@ impl ErrorCode for SearchErrorCode {
             Self::DatabaseOpenFailed => "database_open_failed",
             Self::DatabaseReadFailed => "database_read_failed",
             Self::TaskFailed => "task_failed",
+            Self::Cancelled => "cancelled",
             Self::UnknownError => "unknown_error",
         }
     }
@@ -81,6 +83,20 @@ impl From<crate::commands::fs::FsError> for SearchError {
     }
 }
 
+impl From<crate::db::DbError> for SearchError {
+    fn from(error: crate::db::DbError) -> Self {
+        use crate::db::DbErrorCode;
+        let code = match error.code() {
+            DbErrorCode::OpenFailed
+            | DbErrorCode::DataDirUnavailable
+            | DbErrorCode::PermissionDenied
+            | DbErrorCode::ReadOnlyFilesystem => SearchErrorCode::DatabaseOpenFailed,
+            _ => SearchErrorCode::DatabaseReadFailed,
+        };
+        Self::new(code, error.to_string())
+    }
+}
+
 pub(super) type SearchResult<T> = Result<T, SearchError>;
 
 pub(super) fn map_api_result<T>(result: SearchResult<T>) -> ApiResult<T> {
@@ -0,0 +1,607 @@
+//! Background filename index that answers `search_stream` for configured roots.
+//!
+//! Roots come from the `searchIndexRoots` setting. A single indexer thread scans them at
+//! startup, whenever the roots change or a rebuild is requested, and again once a scan is
+//! older than [`RESCAN_INTERVAL`]. Searches below a root whose first scan has completed read
+//! their candidates from the index; everything else falls back to the live walk. Directories
+//! that changed since they were indexed are listed live, so entries added after a scan are
+//! found before the next one.
+
+use super::{
+    error::{map_api_result, SearchError, SearchErrorCode, SearchResult},
+    walker::{evaluate_path, walk_parallel_skipping, NoProbe, SearchWalk, WalkHit},
+    SearchIndexRootStatus,
+};
+use crate::{
+    commands::search::query::{matches_query, needs_probe},
+    db::{
+        self,
+        index::{IndexRootStatus, IndexedEntry},
+    },
+    entry::FsEntry,
+    errors::api_error::ApiResult,
+    icons::icon_id_for_virtual_entry,
+    runtime_lifecycle,
+};
+use chrono::{DateTime, Local};
+use once_cell::sync::OnceCell;
+use rusqlite::Connection;
+use std::collections::{HashSet, VecDeque};
+use std::fs::{self, Metadata};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, SyncSender};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, warn};
+use walkdir::WalkDir;
+
+/// Completed scans older than this are refreshed by the idle loop.
+const RESCAN_INTERVAL: Duration = Duration::from_secs(30 * 60);
+const IDLE_POLL: Duration = Duration::from_secs(60);
+const INDEX_WRITE_BATCH: usize = 2048;
+
+enum IndexerCommand {
+    RootsChanged,
+    Rebuild(Option<String>),
+}
+
+static INDEXER: OnceCell<Mutex<Sender<IndexerCommand>>> = OnceCell::new();
+static SCANNING_ROOT: Mutex<Option<String>> = Mutex::new(None);
+
+/// Starts the indexer thread. Later calls are no-ops.
+pub fn start_search_indexer(app: tauri::AppHandle) {
+    let (tx, rx) = mpsc::channel();
+    if INDEXER.set(Mutex::new(tx)).is_err() {
+        return;
+    }
+    let spawned = std::thread::Builder::new()
+        .name("search-indexer".to_string())
+        .spawn(move || run_indexer(app, rx));
+    if let Err(error) = spawned {
+        warn!(%error, "failed to start search indexer");
+    }
+}
+
+fn send_command(command: IndexerCommand) -> bool {
+    INDEXER
+        .get()
+        .and_then(|tx| tx.lock().ok())
+        .is_some_and(|tx| tx.send(command).is_ok())
+}
+
+/// Tells the indexer to pick up a new `searchIndexRoots` setting.
+pub fn notify_search_index_roots_changed() {
+    send_command(IndexerCommand::RootsChanged);
+}
+
+fn configured_roots() -> SearchResult<Vec<String>> {
+    let conn = db::open()?;
+    Ok(db::load_search_index_roots(&conn)?)
+}
+
+#[tauri::command]
+pub fn rebuild_search_index(root: Option<String>) -> ApiResult<()> {
+    map_api_result((|| -> SearchResult<()> {
+        if let Some(root) = root.as_deref() {
+            if !configured_roots()?
+                .iter()
+                .any(|configured| configured == root)
+            {
+                return Err(SearchError::new(
+                    SearchErrorCode::InvalidInput,
+                    "Path is not an indexed search root",
+                ));
+            }
+        }
+        if !send_command(IndexerCommand::Rebuild(root)) {
+            return Err(SearchError::new(
+                SearchErrorCode::TaskFailed,
+                "Search indexer is not running",
+            ));
+        }
+        Ok(())
+    })())
+}
+
+#[tauri::command]
+pub fn search_index_status() -> ApiResult<Vec<SearchIndexRootStatus>> {
+    map_api_result((|| -> SearchResult<Vec<SearchIndexRootStatus>> {
+        let roots = configured_roots()?;
+        let statuses = db::index::root_statuses(&db::index::open_index()?)?;
+        let scanning = SCANNING_ROOT.lock().ok().and_then(|root| root.clone());
+        Ok(roots
+            .into_iter()
+            .map(|root| {
+                let status = statuses.iter().find(|status| status.root == root);
+                SearchIndexRootStatus {
+                    ready: status.is_some_and(IndexRootStatus::is_ready),
+                    scanning: scanning.as_deref() == Some(root.as_str()),
+                    entry_count: status.map(|status| status.entry_count).unwrap_or(0),
+                    last_scan_completed: status.and_then(|status| status.last_scan_completed),
+                    root,
+                }
+            })
+            .collect())
+    })())
+}
+
+fn run_indexer(app: tauri::AppHandle, commands: Receiver<IndexerCommand>) {
+    let mut pending: VecDeque<String> = due_roots(false).into();
+    loop {
+        while let Some(root) = pending.pop_front() {
+            if runtime_lifecycle::is_shutting_down(&app) {
+                return;
+            }
+            scan_root(&app, &root);
+        }
+        let due = match commands.recv_timeout(IDLE_POLL) {
+            Ok(IndexerCommand::RootsChanged) | Err(RecvTimeoutError::Timeout) => due_roots(false),
+            Ok(IndexerCommand::Rebuild(None)) => due_roots(true),
+            Ok(IndexerCommand::Rebuild(Some(root))) => vec![root],
+            Err(RecvTimeoutError::Disconnected) => return,
+        };
+        for root in due {
+            if !pending.contains(&root) {
+                pending.push_back(root);
+            }
+        }
+    }
+}
+
+/// Drops index data for roots that are no longer configured and returns the roots that need
+/// a scan.
+fn due_roots(force: bool) -> Vec<String> {
+    let result = (|| -> SearchResult<Vec<String>> {
+        let roots = configured_roots()?;
+        let mut conn = db::index::open_index()?;
+        db::index::retain_roots(&mut conn, &roots)?;
+        let statuses = db::index::root_statuses(&conn)?;
+        Ok(select_due_roots(&roots, &statuses, now_secs(), force))
+    })();
+    result.unwrap_or_else(|error| {
+        warn!(%error, "failed to plan search index scans");
+        Vec::new()
+    })
+}
+
+fn select_due_roots(
+    roots: &[String],
+    statuses: &[IndexRootStatus],
+    now: i64,
+    force: bool,
+) -> Vec<String> {
+    roots
+        .iter()
+        .filter(|root| {
+            if force {
+                return true;
+            }
+            let completed = statuses
+                .iter()
+                .find(|status| &status.root == *root)
+                .and_then(|status| status.last_scan_completed);
+            match completed {
+                Some(at) => now.saturating_sub(at) >= RESCAN_INTERVAL.as_secs() as i64,
+                None => true,
+            }
+        })
+        .cloned()
+        .collect()
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+fn scan_root(app: &tauri::AppHandle, root: &str) {
+    let Some(_activity_guard) = runtime_lifecycle::try_enter_background_job_from_app(app) else {
+        return;
+    };
+    if let Ok(mut scanning) = SCANNING_ROOT.lock() {
+        *scanning = Some(root.to_string());
+    }
+    let result = db::index::open_index().and_then(|mut conn| {
+        scan_into(&mut conn, Path::new(root), &|| {
+            runtime_lifecycle::is_shutting_down(app)
+        })
+    });
+    if let Ok(mut scanning) = SCANNING_ROOT.lock() {
+        *scanning = None;
+    }
+    match result {
+        Ok(Some(count)) => debug!(root, count, "search index scan finished"),
+        Ok(None) => debug!(root, "search index scan skipped or interrupted"),
+        Err(error) => warn!(%error, root, "search index scan failed"),
+    }
+}
+
+/// Walks `root` into the index. Returns `None` when the root is not a directory or the scan
+/// was interrupted, in which case the previous scan stays in place.
+fn scan_into(
+    conn: &mut Connection,
+    root: &Path,
+    should_stop: &dyn Fn() -> bool,
+) -> db::DbResult<Option<u64>> {
+    if !root.is_dir() {
+        return Ok(None);
+    }
+    let key = root.to_string_lossy();
+    let scan_id = db::index::begin_scan(conn, &key)?;
+    let mut batch = Vec::with_capacity(INDEX_WRITE_BATCH);
+    for entry in WalkDir::new(root)
+        .follow_links(false)
+        .min_depth(1)
+        .into_iter()
+        .filter_map(Result::ok)
+    {
+        if should_stop() {
+            return Ok(None);
+        }
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        batch.push(indexed_entry(entry.path(), &meta));
+        if batch.len() >= INDEX_WRITE_BATCH {
+            db::index::upsert_entries(conn, &key, scan_id, &batch)?;
+            batch.clear();
+        }
+    }
+    if !batch.is_empty() {
+        db::index::upsert_entries(conn, &key, scan_id, &batch)?;
+    }
+    db::index::finish_scan(conn, &key, scan_id).map(Some)
+}
+
+fn indexed_entry(path: &Path, meta: &Metadata) -> IndexedEntry {
+    let file_type = meta.file_type();
+    let kind = if file_type.is_symlink() {
+        "link"
+    } else if file_type.is_dir() {
+        "dir"
+    } else {
+        "file"
+    };
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    IndexedEntry {
+        path: path.to_string_lossy().into_owned(),
+        hidden: name.starts_with('.'),
+        name,
+        kind: kind.to_string(),
+        size: meta.is_file().then(|| meta.len()),
+        mtime: meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64),
+        read_only: meta.permissions().readonly(),
+    }
+}
+
+/// The index of a root whose first scan completed.
+pub(super) struct ReadyIndex {
+    conn: Connection,
+    status: IndexRootStatus,
+}
+
+/// Opens the index when `target` lies inside a configured root whose first scan completed.
+pub(super) fn open_for_target(target: &Path) -> Option<ReadyIndex> {
+    let roots = configured_roots().ok()?;
+    let root = roots.iter().find(|root| target.starts_with(root))?;
+    let conn = db::index::open_index().ok()?;
+    let status = db::index::root_status(&conn, root).ok()??;
+    status.is_ready().then_some(ReadyIndex { conn, status })
+}
+
+/// Answers a search below `target` from the index. Candidates are checked against the
+/// indexed metadata first and then re-read from disk, so deleted entries never surface and
+/// results carry current metadata. Entries the index lacks are then looked up in the
+/// directories that changed since they were indexed. Every path evaluated is recorded in
+/// `seen` first, so a caller falling back to a live walk after an error can skip them.
+pub(super) fn search_indexed(
+    walk: &SearchWalk<'_>,
+    index: &ReadyIndex,
+    target: &Path,
+    tx: &SyncSender<WalkHit>,
+    seen: &Mutex<HashSet<String>>,
+) -> db::DbResult<()> {
+    let prefilter = !needs_probe(walk.query);
+    let mut receiver_gone = false;
+    db::index::for_each_entry_under(
+        &index.conn,
+        &target.to_string_lossy(),
+        walk.simple_name_contains_needle_lc,
+        |row| {
+            if (walk.should_stop)() {
+                return false;
+            }
+            if prefilter
+                && !matches_query(&candidate_entry(&row), walk.query, walk.now, &mut NoProbe)
+            {
+                return true;
+            }
+            let path = Path::new(&row.path);
+            if walk
+                .exclusions
+                .is_some_and(|rules| rules.is_excluded_with_ancestors(path, row.kind == "dir"))
+            {
+                return true;
+            }
+            if !first_visit(seen, path) {
+                return true;
+            }
+            match evaluate_path(walk, path) {
+                Some(hit) => {
+                    receiver_gone = tx.send(hit).is_err();
+                    !receiver_gone
+                }
+                None => true,
+            }
+        },
+    )?;
+    if receiver_gone || (walk.should_stop)() {
+        return Ok(());
+    }
+    search_changed_dirs(walk, index, target, tx, seen)
+}
+
+/// Records `path` in `seen`, returning false when it was already there.
+fn first_visit(seen: &Mutex<HashSet<String>>, path: &Path) -> bool {
+    seen.lock()
+        .is_ok_and(|mut seen| seen.insert(path.to_string_lossy().to_string()))
+}
+
+/// Evaluates the entries of changed directories that the index does not know, walking the
+/// new directories among them live. `target` itself has no indexed mtime when it is the
+/// root, so it is always listed.
+fn search_changed_dirs(
+    walk: &SearchWalk<'_>,
+    index: &ReadyIndex,
+    target: &Path,
+    tx: &SyncSender<WalkHit>,
+    seen: &Mutex<HashSet<String>>,
+) -> db::DbResult<()> {
+    let scan_started = index.status.last_scan_started;
+    let mut changed = vec![target.to_path_buf()];
+    db::index::for_each_dir_under(&index.conn, &target.to_string_lossy(), |path, mtime| {
+        if (walk.should_stop)() {
+            return false;
+        }
+        if dir_changed(Path::new(&path), mtime, scan_started) {
+            changed.push(PathBuf::from(path));
+        }
+        true
+    })?;
+    for dir in changed {
+        let Ok(children) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for child in children.flatten() {
+            if (walk.should_stop)() {
+                return Ok(());
+            }
+            let path = child.path();
+            if db::index::contains_path(&index.conn, &path.to_string_lossy())? {
+                continue;
+            }
+            let Ok(file_type) = child.file_type() else {
+                continue;
+            };
+            if walk
+                .exclusions
+                .is_some_and(|rules| rules.is_excluded_with_ancestors(&path, file_type.is_dir()))
+            {
+                continue;
+            }
+            if first_visit(seen, &path) {
+                if let Some(hit) = evaluate_path(walk, &path) {
+                    if tx.send(hit).is_err() {
+                        return Ok(());
+                    }
+                }
+            }
+            if file_type.is_dir() && !file_type.is_symlink() {
+                walk_parallel_skipping(walk, path, tx.clone(), seen);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether a directory may hold entries its scan did not see: its mtime moved since, or it
+/// was modified once the scan had started, within the second mtimes are kept at. A directory
+/// that is gone holds nothing new.
+fn dir_changed(path: &Path, indexed_mtime: Option<i64>, scan_started: Option<i64>) -> bool {
+    let Some(current) = fs::symlink_metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+    else {
+        return false;
+    };
+    match indexed_mtime {
+        Some(indexed) => {
+            current != indexed || scan_started.is_some_and(|started| indexed >= started)
+        }
+        None => true,
+    }
+}
+
+/// Builds an entry from indexed metadata alone, good enough for evaluating queries that do
+/// not need the probe.
+fn candidate_entry(row: &IndexedEntry) -> FsEntry {
+    let is_dir = row.kind == "dir";
+    FsEntry {
+        name: row.name.clone(),
+        path: row.path.clone(),
+        kind: row.kind.clone(),
+        ext: Path::new(&row.name)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|s| s.to_string()),
+        size: row.size,
+        items: None,
+        modified: row
+            .mtime
+            .and_then(|secs| DateTime::from_timestamp(secs, 0))
+            .map(|t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()),
+        original_path: None,
+        trash_id: None,
+        icon_id: icon_id_for_virtual_entry(&row.name, is_dir),
+        starred: false,
+        hidden: row.hidden,
+        network: false,
+        read_only: row.read_only,
+        read_denied: false,
+        capabilities: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{scan_into, search_indexed, select_due_roots, ReadyIndex, RESCAN_INTERVAL};
+    use crate::commands::search::query::parse_query;
+    use crate::commands::search::walker::SearchWalk;
+    use crate::db::index::{open_index_at, root_statuses, IndexRootStatus};
+    use crate::fs_utils::unique_temp_dir;
+    use chrono::Local;
+    use std::collections::HashSet;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::AtomicBool;
+    use std::sync::{mpsc, Mutex};
+
+    fn indexed_matches(db: &Path, target: &Path, query: &str) -> Vec<String> {
+        let conn = open_index_at(db).expect("open index");
+        let status = root_statuses(&conn)
+            .expect("statuses")
+            .into_iter()
+            .find(|status| target.starts_with(&status.root))
+            .expect("indexed root");
+        let index = ReadyIndex { conn, status };
+        let expr = parse_query(query).expect("parse");
+        let star_set = HashSet::new();
+        let cancel = AtomicBool::new(false);
+        let should_stop = || false;
+        let needle = crate::commands::search::query::simple_name_contains_needle_lc(&expr);
+        let walk = SearchWalk {
+            query: &expr,
+            simple_name_contains_needle_lc: needle.as_deref(),
+            content_searcher: None,
+            exclusions: None,
+            star_set: &star_set,
+            now: Local::now().naive_local(),
+            cancel: &cancel,
+            should_stop: &should_stop,
+            archive_budget: None,
+        };
+        let (tx, rx) = mpsc::sync_channel(1024);
+        search_indexed(&walk, &index, target, &tx, &Mutex::new(HashSet::new())).expect("search");
+        drop(tx);
+        let mut paths: Vec<String> = rx.into_iter().map(|hit| hit.entry.path).collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn indexed_search_returns_live_matches_below_target() {
+        let dir = unique_temp_dir("search");
+        let root = dir.join("root");
+        fs::create_dir_all(root.join("docs").join("old")).expect("create dirs");
+        fs::write(root.join("docs").join("report.txt"), b"x").expect("write");
+        fs::write(root.join("docs").join("old").join("report.md"), b"x").expect("write");
+        fs::write(root.join("notes.txt"), b"x").expect("write");
+        let db = dir.join("index.db");
+
+        let mut conn = open_index_at(&db).expect("open index");
+        let count = scan_into(&mut conn, &root, &|| false).expect("scan");
+        assert_eq!(count, Some(5));
+
+        let docs = root.join("docs");
+        let hits = indexed_matches(&db, &docs, "report");
+        assert_eq!(hits.len(), 2);
+        assert_eq!(indexed_matches(&db, &root, "ext:txt").len(), 2);
+
+        fs::remove_file(docs.join("report.txt")).expect("remove");
+        let hits = indexed_matches(&db, &docs, "report");
+        assert_eq!(
+            hits.into_iter().collect::<Vec<_>>(),
+            vec![docs
+                .join("old")
+                .join("report.md")
+                .to_string_lossy()
+                .into_owned()]
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn indexed_search_finds_entries_added_after_the_scan() {
+        let dir = unique_temp_dir("search-fresh");
+        let root = dir.join("root");
+        let docs = root.join("docs");
+        fs::create_dir_all(&docs).expect("create dirs");
+        fs::write(docs.join("report.txt"), b"x").expect("write");
+        let db = dir.join("index.db");
+        let mut conn = open_index_at(&db).expect("open index");
+        assert_eq!(scan_into(&mut conn, &root, &|| false).unwrap(), Some(2));
+
+        fs::write(docs.join("report-new.txt"), b"x").expect("write");
+        fs::create_dir_all(root.join("fresh").join("nested")).expect("create dirs");
+        fs::write(root.join("fresh").join("nested").join("report.md"), b"x").expect("write");
+
+        let path = |p: PathBuf| p.to_string_lossy().into_owned();
+        let mut expected = vec![
+            path(docs.join("report-new.txt")),
+            path(docs.join("report.txt")),
+            path(root.join("fresh").join("nested").join("report.md")),
+        ];
+        expected.sort();
+        assert_eq!(indexed_matches(&db, &root, "report"), expected);
+        assert_eq!(
+            indexed_matches(&db, &docs, "new"),
+            vec![path(docs.join("report-new.txt"))]
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn interrupted_scan_keeps_previous_index() {
+        let dir = unique_temp_dir("interrupted");
+        let root = dir.join("root");
+        fs::create_dir_all(&root).expect("create root");
+        fs::write(root.join("a.txt"), b"x").expect("write");
+        let db = dir.join("index.db");
+        let mut conn = open_index_at(&db).expect("open index");
+        assert_eq!(scan_into(&mut conn, &root, &|| false).unwrap(), Some(1));
+        assert_eq!(scan_into(&mut conn, &root, &|| true).unwrap(), None);
+        assert_eq!(indexed_matches(&db, &root, "a.txt").len(), 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn due_roots_include_unscanned_and_stale_roots() {
+        let now = 10_000_000;
+        let fresh = now - 60;
+        let stale = now - RESCAN_INTERVAL.as_secs() as i64 - 1;
+        let status = |root: &str, completed: Option<i64>| IndexRootStatus {
+            root: root.to_string(),
+            last_scan_started: completed,
+            last_scan_completed: completed,
+            entry_count: 0,
+        };
+        let roots = vec!["/a".to_string(), "/b".to_string(), "/c".to_string()];
+        let statuses = vec![status("/a", Some(fresh)), status("/b", Some(stale))];
+        assert_eq!(
+            select_due_roots(&roots, &statuses, now, false),
+            vec!["/b".to_string(), "/c".to_string()]
+        );
+        assert_eq!(select_due_roots(&roots, &statuses, now, true), roots);
+    }
+}
@@ -1,15 +1,23 @@
 //! Streaming recursive search command that decorates entries with starred state.
 
+mod content;
 mod error;
+mod index;
 mod query;
 mod types;
+mod walker;
 mod worker;
 
-pub use types::SearchProgress;
+pub use index::{
+    notify_search_index_roots_changed, rebuild_search_index, search_index_status,
+    start_search_indexer,
+};
+pub use types::{SearchContentMatch, SearchIndexRootStatus, SearchProgress, SearchScore};
 
 use crate::errors::api_error::ApiResult;
-use crate::{sorting::SortSpec, tasks::CancelState};
+use crate::{entry::FsEntry, sorting::SortSpec, tasks::CancelState};
 use error::{map_api_result, SearchError, SearchErrorCode};
+use std::sync::atomic::AtomicBool;
 
 #[tauri::command]
 pub fn search_stream(
@@ -18,6 +26,8 @@ pub fn search_stream(
     path: Option<String>,
     query: String,
     _sort: Option<SortSpec>,
+    include_ignored: Option<bool>,
+    search_archives: Option<bool>,
     progress_event: Option<String>,
 ) -> ApiResult<()> {
     let progress_event = progress_event.ok_or_else(|| {
@@ -29,8 +39,36 @@ pub fn search_stream(
     };
     let cancel_state = cancel.inner().clone();
     tauri::async_runtime::spawn_blocking(move || {
-        worker::run_search_stream(app, cancel_state, path, query, progress_event);
+        worker::run_search_stream(
+            app,
+            cancel_state,
+            path,
+            query,
+            include_ignored.unwrap_or(false),
+            search_archives.unwrap_or(false),
+            progress_event,
+        );
     });
 
     map_api_result(Ok(()))
 }
+
+/// Runs a search to completion on the calling thread and returns every match. It fails as
+/// cancelled once `should_stop` fires; `cancel` also aborts content and archive reads.
+pub fn collect_search_entries(
+    path: Option<String>,
+    query: &str,
+    include_ignored: bool,
+    search_archives: bool,
+    cancel: &AtomicBool,
+    should_stop: &(dyn Fn() -> bool + Sync),
+) -> ApiResult<Vec<FsEntry>> {
+    map_api_result(worker::collect_search(
+        path,
+        query,
+        include_ignored,
+        search_archives,
+        cancel,
+        should_stop,
+    ))
+}
@@ -1,3 +1,5 @@
+use chrono::NaiveDateTime;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Expr {
     Predicate(Predicate),
@@ -12,6 +14,16 @@ pub enum Predicate {
         field: TextField,
         matcher: TextMatcher,
     },
+    Content(ContentMatcher),
+    Size(SizeRange),
+    Time {
+        field: TimeField,
+        range: TimeRange,
+    },
+    Ext(Vec<String>),
+    Kind(KindFilter),
+    /// A facet id as emitted by `ListingFacetBuilder`, lowercased (e.g. `size:1–10 mb`).
+    Facet(String),
     Hidden(bool),
     Readonly(bool),
 }
@@ -28,11 +40,80 @@ pub enum TextField {
 pub struct TextMatcher {
     pub raw: String,
     pub mode: TextMatchMode,
+    /// Set by the `case:` modifier; otherwise values are compared case-insensitively.
+    pub case_sensitive: bool,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TextMatchMode {
     Contains,
     Exact,
     Wildcard,
+    /// Compiled once at parse time, honoring `case_sensitive`.
+    Regex(TextRegex),
+    /// Characters of `raw` must appear in order; ranked by `eval::fuzzy_score`.
+    Fuzzy,
+}
+
+/// A compiled name/path regex. Compares by pattern so parsed queries stay comparable.
+#[derive(Debug, Clone)]
+pub struct TextRegex(pub regex::Regex);
+
+impl PartialEq for TextRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl Eq for TextRegex {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentMatcher {
+    pub raw: String,
+    pub mode: ContentMatchMode,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentMatchMode {
+    Substring,
+    Regex,
+}
+
+/// Inclusive byte bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeRange {
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeField {
+    Modified,
+    Created,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeRange {
+    /// Local wall-clock bounds, `start` inclusive and `end` exclusive.
+    Absolute {
+        start: Option<NaiveDateTime>,
+        end: Option<NaiveDateTime>,
+    },
+    /// Inclusive age bounds in seconds relative to evaluation time.
+    Age {
+        min_secs: Option<i64>,
+        max_secs: Option<i64>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KindFilter {
+    File,
+    Dir,
+    Link,
+    Image,
+    Video,
+    Audio,
+    Pdf,
+    Archive,
 }
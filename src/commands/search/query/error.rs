@@ -4,6 +4,8 @@ use std::fmt;
 pub struct QueryError {
     pub message: String,
     pub at: usize,
+    /// Byte offset just past the offending text; equal to `at` when only a position is known.
+    pub end: usize,
 }
 
 impl QueryError {
@@ -11,6 +13,15 @@ impl QueryError {
         Self {
             message: message.into(),
             at,
+            end: at,
+        }
+    }
+
+    pub fn spanning(message: impl Into<String>, start: usize, end: usize) -> Self {
+        Self {
+            message: message.into(),
+            at: start,
+            end: end.max(start),
         }
     }
 }
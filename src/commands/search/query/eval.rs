@@ -1,47 +1,285 @@
-use super::ast::{Expr, Predicate, TextField, TextMatchMode, TextMatcher};
-use crate::entry::FsEntry;
+use super::ast::{
+    ContentMatcher, Expr, KindFilter, Predicate, SizeRange, TextField, TextMatchMode, TextMatcher,
+    TimeField, TimeRange,
+};
+use crate::{commands::listing::entry_facet_ids, entry::FsEntry, metadata::classify_extension};
+use chrono::NaiveDateTime;
+use std::borrow::Cow;
 use std::path::Path;
 
-pub fn matches(entry: &FsEntry, expr: &Expr) -> bool {
+/// Three-valued result of a query term. A probe that cannot read a file answers `Unknown`,
+/// which never matches, not even under `NOT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Truth {
+    True,
+    False,
+    Unknown,
+}
+
+impl From<bool> for Truth {
+    fn from(value: bool) -> Self {
+        if value {
+            Self::True
+        } else {
+            Self::False
+        }
+    }
+}
+
+impl std::ops::Not for Truth {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        match self {
+            Self::True => Self::False,
+            Self::False => Self::True,
+            Self::Unknown => Self::Unknown,
+        }
+    }
+}
+
+/// Lazily supplies the parts of an entry that are not carried on `FsEntry`.
+pub trait EntryProbe {
+    /// Whether the bytes of the regular file match `matcher`; `Unknown` when they could not
+    /// be read.
+    fn content(&mut self, matcher: &ContentMatcher) -> Truth;
+    /// Creation time as local wall-clock time, when the filesystem records one.
+    fn created(&mut self) -> Option<NaiveDateTime>;
+}
+
+/// Evaluates `expr` against `entry`, with relative dates resolved against `now`. Terms that
+/// need the probe run after the cheaper metadata terms of the surrounding group.
+pub fn matches<P: EntryProbe>(
+    entry: &FsEntry,
+    expr: &Expr,
+    now: NaiveDateTime,
+    probe: &mut P,
+) -> bool {
+    eval(entry, expr, now, probe) == Truth::True
+}
+
+/// [`matches`] in three-valued logic: a group is decided by any term that settles it, and is
+/// `Unknown` when the rest leave it open.
+fn eval<P: EntryProbe>(entry: &FsEntry, expr: &Expr, now: NaiveDateTime, probe: &mut P) -> Truth {
     match expr {
-        Expr::Predicate(p) => matches_predicate(entry, p),
-        Expr::Not(inner) => !matches(entry, inner),
-        Expr::And(parts) => parts.iter().all(|part| matches(entry, part)),
-        Expr::Or(parts) => parts.iter().any(|part| matches(entry, part)),
+        Expr::Predicate(p) => matches_predicate(entry, p, now, probe),
+        Expr::Not(inner) => !eval(entry, inner, now, probe),
+        Expr::And(parts) => eval_group(entry, parts, now, probe, Truth::False),
+        Expr::Or(parts) => eval_group(entry, parts, now, probe, Truth::True),
     }
 }
 
-fn matches_predicate(entry: &FsEntry, pred: &Predicate) -> bool {
-    match pred {
+/// Evaluates `parts` until one yields `decisive`, metadata terms first.
+fn eval_group<P: EntryProbe>(
+    entry: &FsEntry,
+    parts: &[Expr],
+    now: NaiveDateTime,
+    probe: &mut P,
+    decisive: Truth,
+) -> Truth {
+    let ordered = parts
+        .iter()
+        .filter(|part| !needs_probe(part))
+        .chain(parts.iter().filter(|part| needs_probe(part)));
+    let mut result = !decisive;
+    for part in ordered {
+        match eval(entry, part, now, probe) {
+            Truth::Unknown => result = Truth::Unknown,
+            value if value == decisive => return decisive,
+            _ => {}
+        }
+    }
+    result
+}
+
+pub fn needs_probe(expr: &Expr) -> bool {
+    match expr {
+        Expr::Predicate(Predicate::Content(_))
+        | Expr::Predicate(Predicate::Time {
+            field: TimeField::Created,
+            ..
+        }) => true,
+        Expr::Predicate(_) => false,
+        Expr::Not(inner) => needs_probe(inner),
+        Expr::And(parts) | Expr::Or(parts) => parts.iter().any(needs_probe),
+    }
+}
+
+fn matches_predicate<P: EntryProbe>(
+    entry: &FsEntry,
+    pred: &Predicate,
+    now: NaiveDateTime,
+    probe: &mut P,
+) -> Truth {
+    let holds = match pred {
+        Predicate::Content(matcher) if entry.kind == "file" => return probe.content(matcher),
+        Predicate::Content(_) => false,
+        Predicate::Size(range) => entry.kind == "file" && match_size(entry.size, range),
+        Predicate::Time { field, range } => {
+            let value = match field {
+                TimeField::Modified => entry
+                    .modified
+                    .as_deref()
+                    .and_then(|raw| NaiveDateTime::parse_from_str(raw, "%Y-%m-%d %H:%M").ok()),
+                TimeField::Created => probe.created(),
+            };
+            value.is_some_and(|value| match_time(value, range, now))
+        }
+        Predicate::Ext(exts) => {
+            entry.kind != "dir"
+                && entry
+                    .ext
+                    .as_deref()
+                    .is_some_and(|ext| exts.iter().any(|want| want.eq_ignore_ascii_case(ext)))
+        }
+        Predicate::Kind(kind) => match_kind(entry, *kind),
+        Predicate::Facet(id) => entry_facet_ids(entry, now)
+            .iter()
+            .any(|candidate| candidate.to_lowercase() == *id),
         Predicate::Hidden(v) => entry.hidden == *v,
         Predicate::Readonly(v) => entry.read_only == *v,
-        Predicate::Text { field, matcher } => match field {
-            TextField::Name => match_text(&entry.name, matcher),
-            TextField::Filename => {
-                (entry.kind == "file" || entry.kind == "link") && match_text(&entry.name, matcher)
-            }
-            TextField::Folder => {
-                let parent = Path::new(&entry.path)
-                    .parent()
-                    .map(|p| p.to_string_lossy().into_owned())
-                    .unwrap_or_default();
-                match_text(&parent, matcher)
-            }
-            TextField::Path => match_text(&entry.path, matcher),
-        },
+        Predicate::Text { field, matcher } => {
+            text_subject(entry, *field).is_some_and(|value| match_text(&value, matcher))
+        }
+    };
+    holds.into()
+}
+
+/// The part of `entry` a text field matches against; None when the field does not apply.
+fn text_subject(entry: &FsEntry, field: TextField) -> Option<Cow<'_, str>> {
+    match field {
+        TextField::Name => Some(Cow::Borrowed(entry.name.as_str())),
+        TextField::Filename => (entry.kind == "file" || entry.kind == "link")
+            .then_some(Cow::Borrowed(entry.name.as_str())),
+        TextField::Folder => Some(Cow::Owned(
+            Path::new(&entry.path)
+                .parent()
+                .map(|p| p.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        )),
+        TextField::Path => Some(Cow::Borrowed(entry.path.as_str())),
+    }
+}
+
+/// Best score among the fuzzy terms `entry` satisfies, used to rank results. Terms under
+/// `NOT` never contribute; None when the query has no matching fuzzy terms.
+pub fn fuzzy_rank(entry: &FsEntry, expr: &Expr) -> Option<u32> {
+    match expr {
+        Expr::Predicate(Predicate::Text { field, matcher })
+            if matcher.mode == TextMatchMode::Fuzzy =>
+        {
+            let value = text_subject(entry, *field)?;
+            fuzzy_score(
+                &fold_case(&value, matcher.case_sensitive),
+                &fold_case(&matcher.raw, matcher.case_sensitive),
+            )
+        }
+        Expr::Predicate(_) | Expr::Not(_) => None,
+        Expr::And(parts) | Expr::Or(parts) => parts
+            .iter()
+            .filter_map(|part| fuzzy_rank(entry, part))
+            .max(),
+    }
+}
+
+fn match_size(size: Option<u64>, range: &SizeRange) -> bool {
+    let Some(size) = size else {
+        return false;
+    };
+    range.min.is_none_or(|min| size >= min) && range.max.is_none_or(|max| size <= max)
+}
+
+fn match_time(value: NaiveDateTime, range: &TimeRange, now: NaiveDateTime) -> bool {
+    match *range {
+        TimeRange::Absolute { start, end } => {
+            start.is_none_or(|start| value >= start) && end.is_none_or(|end| value < end)
+        }
+        TimeRange::Age { min_secs, max_secs } => {
+            let age = (now - value).num_seconds();
+            min_secs.is_none_or(|min| age >= min) && max_secs.is_none_or(|max| age <= max)
+        }
+    }
+}
+
+fn match_kind(entry: &FsEntry, kind: KindFilter) -> bool {
+    let media = match kind {
+        KindFilter::File => return entry.kind == "file",
+        KindFilter::Dir => return entry.kind == "dir",
+        KindFilter::Link => return entry.kind == "link",
+        KindFilter::Image => "image",
+        KindFilter::Video => "video",
+        KindFilter::Audio => "audio",
+        KindFilter::Pdf => "pdf",
+        KindFilter::Archive => "archive",
+    };
+    entry.kind == "file"
+        && entry
+            .ext
+            .as_deref()
+            .is_some_and(|ext| classify_extension(ext) == media)
+}
+
+fn fold_case(value: &str, case_sensitive: bool) -> Cow<'_, str> {
+    if case_sensitive {
+        Cow::Borrowed(value)
+    } else {
+        Cow::Owned(value.to_lowercase())
     }
 }
 
 fn match_text(value: &str, matcher: &TextMatcher) -> bool {
-    let value_lc = value.to_lowercase();
-    let needle_lc = matcher.raw.to_lowercase();
+    if let TextMatchMode::Regex(regex) = &matcher.mode {
+        return regex.0.is_match(value);
+    }
+    let value = fold_case(value, matcher.case_sensitive);
+    let needle = fold_case(&matcher.raw, matcher.case_sensitive);
     match matcher.mode {
-        TextMatchMode::Contains => value_lc.contains(&needle_lc),
-        TextMatchMode::Exact => value_lc == needle_lc,
-        TextMatchMode::Wildcard => wildcard_match(&value_lc, &needle_lc),
+        TextMatchMode::Contains => value.contains(needle.as_ref()),
+        TextMatchMode::Exact => value == needle,
+        TextMatchMode::Wildcard => wildcard_match(&value, &needle),
+        TextMatchMode::Fuzzy => fuzzy_score(&value, &needle).is_some(),
+        TextMatchMode::Regex(_) => false,
     }
 }
 
+const FUZZY_MATCH: i64 = 16;
+const FUZZY_WORD_START_BONUS: i64 = 24;
+const FUZZY_CONSECUTIVE_BONUS: i64 = 32;
+const FUZZY_MAX_GAP_PENALTY: i64 = 16;
+
+/// Scores `needle` as an in-order subsequence of `value`, or None when it is not one.
+/// Consecutive characters and characters at word starts score extra; gaps and leftover
+/// characters cost a little, so tighter and shorter matches rank first.
+fn fuzzy_score(value: &str, needle: &str) -> Option<u32> {
+    let mut wanted = needle.chars().peekable();
+    let mut score = 0i64;
+    let mut last_match: Option<usize> = None;
+    let mut prev: Option<char> = None;
+    for (idx, ch) in value.chars().enumerate() {
+        if wanted.peek() == Some(&ch) {
+            wanted.next();
+            score += FUZZY_MATCH;
+            if prev.is_none_or(|p| !p.is_alphanumeric() || (p.is_lowercase() && ch.is_uppercase()))
+            {
+                score += FUZZY_WORD_START_BONUS;
+            }
+            score -= match last_match {
+                Some(last) if last + 1 == idx => -FUZZY_CONSECUTIVE_BONUS,
+                Some(last) => ((idx - last - 1) as i64).min(FUZZY_MAX_GAP_PENALTY),
+                None => (idx as i64).min(FUZZY_MAX_GAP_PENALTY),
+            };
+            last_match = Some(idx);
+        }
+        prev = Some(ch);
+    }
+    if wanted.peek().is_some() {
+        return None;
+    }
+    let leftover = value.chars().count().saturating_sub(needle.chars().count()) as i64;
+    score -= leftover.min(FUZZY_MAX_GAP_PENALTY * 2) / 4;
+    Some(score.max(0) as u32)
+}
+
 fn wildcard_match(value: &str, pattern: &str) -> bool {
     let s: Vec<char> = value.chars().collect();
     let p: Vec<char> = pattern.chars().collect();
@@ -77,11 +315,50 @@ fn wildcard_match(value: &str, pattern: &str) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::matches;
+    use super::{fuzzy_rank, matches, EntryProbe, Truth};
     use crate::commands::search::query::ast::{
-        Expr, Predicate, TextField, TextMatchMode, TextMatcher,
+        ContentMatchMode, ContentMatcher, Expr, KindFilter, Predicate, SizeRange, TextField,
+        TextMatchMode, TextMatcher, TimeField, TimeRange,
     };
+    use crate::commands::search::query::parse_query;
     use crate::entry::FsEntry;
+    use chrono::NaiveDateTime;
+
+    struct CountingProbe {
+        content_result: Truth,
+        created: Option<NaiveDateTime>,
+        probes: usize,
+    }
+
+    impl EntryProbe for CountingProbe {
+        fn content(&mut self, _matcher: &ContentMatcher) -> Truth {
+            self.probes += 1;
+            self.content_result
+        }
+
+        fn created(&mut self) -> Option<NaiveDateTime> {
+            self.probes += 1;
+            self.created
+        }
+    }
+
+    impl Default for CountingProbe {
+        fn default() -> Self {
+            Self {
+                content_result: Truth::False,
+                created: None,
+                probes: 0,
+            }
+        }
+    }
+
+    fn dt(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").expect("valid test datetime")
+    }
+
+    fn now() -> NaiveDateTime {
+        dt("2026-03-10 12:00")
+    }
 
     fn sample_entry(name: &str, path: &str, kind: &str) -> FsEntry {
         FsEntry {
@@ -113,10 +390,11 @@ mod tests {
             matcher: TextMatcher {
                 raw: "*.rs".into(),
                 mode: TextMatchMode::Wildcard,
+                case_sensitive: false,
             },
         });
-        assert!(!matches(&dir, &expr));
-        assert!(matches(&file, &expr));
+        assert!(!matches(&dir, &expr, now(), &mut CountingProbe::default()));
+        assert!(matches(&file, &expr, now(), &mut CountingProbe::default()));
     }
 
     #[test]
@@ -127,9 +405,10 @@ mod tests {
             matcher: TextMatcher {
                 raw: "projects".into(),
                 mode: TextMatchMode::Contains,
+                case_sensitive: false,
             },
         });
-        assert!(matches(&file, &expr));
+        assert!(matches(&file, &expr, now(), &mut CountingProbe::default()));
     }
 
     #[test]
@@ -140,8 +419,171 @@ mod tests {
             matcher: TextMatcher {
                 raw: "main.rs".into(),
                 mode: TextMatchMode::Exact,
+                case_sensitive: false,
             },
         });
-        assert!(matches(&file, &expr));
+        assert!(matches(&file, &expr, now(), &mut CountingProbe::default()));
+    }
+
+    #[test]
+    fn case_modifier_and_regex_respect_case() {
+        let file = sample_entry("README.md", "/tmp/README.md", "file");
+        let lower = parse_query("case:readme").unwrap();
+        let upper = parse_query("case:README").unwrap();
+        assert!(!matches(
+            &file,
+            &lower,
+            now(),
+            &mut CountingProbe::default()
+        ));
+        assert!(matches(&file, &upper, now(), &mut CountingProbe::default()));
+
+        let regex = parse_query(r"/^read.*\.MD$/").unwrap();
+        assert!(matches(&file, &regex, now(), &mut CountingProbe::default()));
+        let strict = parse_query(r"case:/^read/").unwrap();
+        assert!(!matches(
+            &file,
+            &strict,
+            now(),
+            &mut CountingProbe::default()
+        ));
+    }
+
+    #[test]
+    fn fuzzy_terms_match_subsequences_and_rank_tighter_matches_higher() {
+        let expr = parse_query("~mrs").unwrap();
+        let tight = sample_entry("main.rs", "/tmp/main.rs", "file");
+        let loose = sample_entry(
+            "my_report_summary.txt",
+            "/tmp/my_report_summary.txt",
+            "file",
+        );
+        let miss = sample_entry("notes.txt", "/tmp/notes.txt", "file");
+        assert!(matches(&tight, &expr, now(), &mut CountingProbe::default()));
+        assert!(matches(&loose, &expr, now(), &mut CountingProbe::default()));
+        assert!(!matches(&miss, &expr, now(), &mut CountingProbe::default()));
+
+        let tight_score = fuzzy_rank(&tight, &expr).expect("tight score");
+        let loose_score = fuzzy_rank(&loose, &expr).expect("loose score");
+        assert!(tight_score > loose_score, "{tight_score} <= {loose_score}");
+        assert_eq!(fuzzy_rank(&miss, &expr), None);
+        assert_eq!(fuzzy_rank(&tight, &parse_query("NOT ~xyz").unwrap()), None);
+    }
+
+    #[test]
+    fn content_predicate_runs_after_metadata_terms_and_only_for_files() {
+        let dir = sample_entry("notes", "/tmp/notes", "dir");
+        let file = sample_entry("notes.txt", "/tmp/notes.txt", "file");
+        let content = Expr::Predicate(Predicate::Content(ContentMatcher {
+            raw: "todo".into(),
+            mode: ContentMatchMode::Substring,
+        }));
+        let expr = Expr::And(vec![
+            content,
+            Expr::Predicate(Predicate::Text {
+                field: TextField::Name,
+                matcher: TextMatcher {
+                    raw: "*.txt".into(),
+                    mode: TextMatchMode::Wildcard,
+                    case_sensitive: false,
+                },
+            }),
+        ]);
+
+        let mut probe = CountingProbe {
+            content_result: Truth::True,
+            ..CountingProbe::default()
+        };
+        assert!(!matches(&dir, &expr, now(), &mut probe));
+        assert_eq!(probe.probes, 0);
+
+        assert!(matches(&file, &expr, now(), &mut probe));
+        assert_eq!(probe.probes, 1);
+    }
+
+    #[test]
+    fn unknown_content_matches_neither_the_term_nor_its_negation() {
+        let mut file = sample_entry("notes.txt", "/tmp/notes.txt", "file");
+        file.ext = Some("txt".into());
+        let unknown = || CountingProbe {
+            content_result: Truth::Unknown,
+            ..CountingProbe::default()
+        };
+        for query in [
+            "content:todo",
+            "NOT content:todo",
+            "NOT (content:todo OR ext:pdf)",
+        ] {
+            let expr = parse_query(query).expect("parse");
+            assert!(!matches(&file, &expr, now(), &mut unknown()), "{query}");
+        }
+        // Terms that settle the group do not depend on the unread content.
+        for query in ["NOT (content:todo AND ext:pdf)", "content:todo OR ext:txt"] {
+            let expr = parse_query(query).expect("parse");
+            assert!(matches(&file, &expr, now(), &mut unknown()), "{query}");
+        }
+    }
+
+    #[test]
+    fn matches_size_kind_and_ext_predicates() {
+        let mut file = sample_entry("report.PDF", "/tmp/report.PDF", "file");
+        file.ext = Some("PDF".into());
+        file.size = Some(12 * 1024 * 1024);
+        let dir = sample_entry("big", "/tmp/big", "dir");
+        let big = Expr::Predicate(Predicate::Size(SizeRange {
+            min: Some(10 * 1024 * 1024),
+            max: None,
+        }));
+        assert!(matches(&file, &big, now(), &mut CountingProbe::default()));
+        assert!(!matches(&dir, &big, now(), &mut CountingProbe::default()));
+
+        let pdf = Expr::Predicate(Predicate::Kind(KindFilter::Pdf));
+        assert!(matches(&file, &pdf, now(), &mut CountingProbe::default()));
+        let ext = Expr::Predicate(Predicate::Ext(vec!["pdf".into()]));
+        assert!(matches(&file, &ext, now(), &mut CountingProbe::default()));
+    }
+
+    #[test]
+    fn matches_modified_ages_and_created_via_probe() {
+        let mut file = sample_entry("notes.txt", "/tmp/notes.txt", "file");
+        file.modified = Some("2026-03-05 12:00".into());
+        let within_week = Expr::Predicate(Predicate::Time {
+            field: TimeField::Modified,
+            range: TimeRange::Age {
+                min_secs: None,
+                max_secs: Some(7 * 86_400),
+            },
+        });
+        assert!(matches(
+            &file,
+            &within_week,
+            now(),
+            &mut CountingProbe::default()
+        ));
+
+        let created_before = Expr::Predicate(Predicate::Time {
+            field: TimeField::Created,
+            range: TimeRange::Absolute {
+                start: None,
+                end: Some(dt("2026-01-01 00:00")),
+            },
+        });
+        let mut probe = CountingProbe {
+            created: Some(dt("2025-12-31 23:59")),
+            ..CountingProbe::default()
+        };
+        assert!(matches(&file, &created_before, now(), &mut probe));
+        assert_eq!(probe.probes, 1);
+    }
+
+    #[test]
+    fn matches_facet_ids() {
+        let mut file = sample_entry("Report.pdf", "/tmp/Report.pdf", "file");
+        file.ext = Some("pdf".into());
+        file.modified = Some("2026-03-10 09:00".into());
+        let today = Expr::Predicate(Predicate::Facet("modified:today".into()));
+        let pdf = Expr::Predicate(Predicate::Facet("type:pdf".into()));
+        assert!(matches(&file, &today, now(), &mut CountingProbe::default()));
+        assert!(matches(&file, &pdf, now(), &mut CountingProbe::default()));
     }
 }
@@ -83,6 +83,13 @@ pub fn lex(input: &str) -> Result<Vec<Token>, QueryError> {
         let mut j = i;
         while j < chars.len() {
             let (_, c) = chars[j];
+            if c == '/' && (j == i || chars[j - 1].1 == ':') {
+                if let Some(close) = regex_literal_end(&chars, j) {
+                    value.extend(chars[j..=close].iter().map(|(_, c)| *c));
+                    j = close + 1;
+                    continue;
+                }
+            }
             if c.is_whitespace() || c == '(' || c == ')' || c == '"' {
                 break;
             }
@@ -104,6 +111,29 @@ pub fn lex(input: &str) -> Result<Vec<Token>, QueryError> {
     Ok(out)
 }
 
+/// Finds the closing slash of a `/pattern/` literal starting at `open`, so regexes may contain
+/// spaces, quotes and parentheses. Only a slash followed by a token boundary closes the
+/// literal; anything else (e.g. `path:/home/user`) is lexed as a plain word.
+fn regex_literal_end(chars: &[(usize, char)], open: usize) -> Option<usize> {
+    let mut k = open + 1;
+    while k < chars.len() {
+        match chars[k].1 {
+            '\\' => k += 2,
+            '/' => {
+                let at_boundary = chars
+                    .get(k + 1)
+                    .is_none_or(|(_, next)| next.is_whitespace() || *next == ')');
+                if at_boundary && k > open + 1 {
+                    return Some(k);
+                }
+                k += 1;
+            }
+            _ => k += 1,
+        }
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::{lex, TokenKind};
@@ -127,6 +157,14 @@ mod tests {
         assert!(matches!(&toks[1].kind, TokenKind::Word(s) if s == "name:?.txt"));
     }
 
+    #[test]
+    fn keeps_regex_literals_in_one_word() {
+        let toks = lex(r#"(name:/(foo|bar) \d"/ OR path:/home/user)"#).unwrap();
+        assert!(matches!(&toks[1].kind, TokenKind::Word(s) if s == r#"name:/(foo|bar) \d"/"#));
+        assert!(matches!(&toks[3].kind, TokenKind::Word(s) if s == "path:/home/user"));
+        assert!(matches!(&toks[4].kind, TokenKind::RParen));
+    }
+
     #[test]
     fn errors_on_unclosed_quote() {
         let err = lex("\"foo").unwrap_err();
@@ -3,17 +3,49 @@ mod error;
 mod eval;
 mod lexer;
 mod parser;
+mod values;
 
 use self::ast::{Predicate, TextField, TextMatchMode};
-pub use ast::Expr;
+pub use ast::{ContentMatchMode, ContentMatcher, Expr};
 pub use error::QueryError;
+pub use eval::{fuzzy_rank, needs_probe, EntryProbe, Truth};
 
 pub fn parse_query(input: &str) -> Result<Expr, QueryError> {
     parser::parse(input)
 }
 
-pub fn matches_query(entry: &crate::entry::FsEntry, expr: &Expr) -> bool {
-    eval::matches(entry, expr)
+/// Evaluates the query with relative dates resolved against `now`; `probe` answers the terms
+/// that need more than the `FsEntry` (file contents, creation time).
+pub fn matches_query<P: EntryProbe>(
+    entry: &crate::entry::FsEntry,
+    expr: &Expr,
+    now: chrono::NaiveDateTime,
+    probe: &mut P,
+) -> bool {
+    eval::matches(entry, expr, now, probe)
+}
+
+/// Collects every `content:` matcher in the query so callers can compile them once.
+pub fn content_matchers(expr: &Expr) -> Vec<ContentMatcher> {
+    fn walk(expr: &Expr, out: &mut Vec<ContentMatcher>) {
+        match expr {
+            Expr::Predicate(Predicate::Content(matcher)) => {
+                if !out.contains(matcher) {
+                    out.push(matcher.clone());
+                }
+            }
+            Expr::Predicate(_) => {}
+            Expr::Not(inner) => walk(inner, out),
+            Expr::And(parts) | Expr::Or(parts) => {
+                for part in parts {
+                    walk(part, out);
+                }
+            }
+        }
+    }
+    let mut out = Vec::new();
+    walk(expr, &mut out);
+    out
 }
 
 /// Returns a lowercase needle when the query is exactly a simple `name contains` search.
@@ -1,8 +1,15 @@
 use super::{
-    ast::{Expr, Predicate, TextField, TextMatchMode, TextMatcher},
+    ast::{
+        ContentMatchMode, ContentMatcher, Expr, Predicate, TextField, TextMatchMode, TextMatcher,
+        TextRegex, TimeField,
+    },
     error::QueryError,
     lexer::{lex, Span, Token, TokenKind},
+    values::{
+        modified_facet_label, parse_ext_list, parse_kind, parse_size_range, parse_time_range,
+    },
 };
+use crate::commands::listing::is_size_facet_label;
 
 pub fn parse(input: &str) -> Result<Expr, QueryError> {
     let tokens = lex(input)?;
@@ -100,6 +107,7 @@ impl Parser {
                 matcher: TextMatcher {
                     raw: value,
                     mode: TextMatchMode::Exact,
+                    case_sensitive: false,
                 },
             }),
             TokenKind::Word(word) => self.parse_word_term(word, tok.span),
@@ -118,14 +126,103 @@ impl Parser {
                     exact = true;
                     rest = tail;
                 }
-                return self.parse_field_predicate(field, rest, exact, span.start);
+                let rest_at = span.start + (word.len() - rest.len());
+                return self.parse_field_predicate(field, rest, exact, span.start, rest_at);
             }
         }
 
-        let (mode, raw) = classify_text_value(word, false);
+        self.parse_text_predicate(TextField::Name, word, false, false, span.start, span.start)
+    }
+
+    /// Parses a name/path value, honoring the `case:` and `re:` modifiers, `/regex/`
+    /// literals and `~fuzzy` terms ahead of the plain contains/exact/wildcard forms.
+    fn parse_text_predicate(
+        &mut self,
+        field: TextField,
+        value: String,
+        exact: bool,
+        quoted: bool,
+        at: usize,
+        value_at: usize,
+    ) -> Result<Predicate, QueryError> {
+        let (modifiers, rest) = if quoted {
+            (TextModifiers::default(), value.as_str())
+        } else {
+            strip_text_modifiers(&value)
+        };
+        let mut value_at = value_at + (value.len() - rest.len());
+        let (mut value, mut exact, mut quoted) = (rest.to_string(), exact, quoted);
+        if value.is_empty() && modifiers != TextModifiers::default() {
+            // `case: "foo bar"` / `re:"a b"`: the value is the next token.
+            let next = self
+                .bump()
+                .ok_or_else(|| QueryError::new("Missing field value", at))?;
+            match next.kind {
+                TokenKind::Quoted(v) => {
+                    value = v;
+                    quoted = true;
+                    value_at = next.span.start + 1;
+                }
+                TokenKind::Word(v) => {
+                    value = v;
+                    value_at = next.span.start;
+                }
+                TokenKind::LParen | TokenKind::RParen => {
+                    return Err(QueryError::new("Missing field value", next.span.start));
+                }
+            }
+        }
+        if !quoted && modifiers != TextModifiers::default() {
+            // `case:=Foo`; a field's own `=` was already consumed by the caller.
+            if let Some(tail) = value.strip_prefix('=') {
+                value = tail.to_string();
+                exact = true;
+                value_at += 1;
+            }
+        }
+        let value_end = value_at + value.len();
+        let slash_literal = !quoted && is_slash_literal(&value);
+        // Names never contain '/', so a leading slash there can only be an unfinished regex.
+        let name_field = matches!(field, TextField::Name | TextField::Filename);
+
+        let mode = if modifiers.regex || (slash_literal && name_field && !exact) {
+            let pattern = if slash_literal {
+                &value[1..value.len() - 1]
+            } else {
+                value.as_str()
+            };
+            if pattern.is_empty() {
+                return Err(QueryError::spanning("Empty regex", value_at, value_end));
+            }
+            let regex = regex::RegexBuilder::new(pattern)
+                .case_insensitive(!modifiers.case_sensitive)
+                .build()
+                .map_err(|error| {
+                    QueryError::spanning(
+                        format!("Invalid regex ({})", first_error_line(&error)),
+                        value_at,
+                        value_end,
+                    )
+                })?;
+            value = pattern.to_string();
+            TextMatchMode::Regex(TextRegex(regex))
+        } else if name_field && !quoted && !exact && value.starts_with('/') {
+            return Err(QueryError::spanning("Unclosed regex", value_at, value_end));
+        } else if let Some(needle) = fuzzy_needle(&value).filter(|_| !quoted && !exact) {
+            value = needle.to_string();
+            TextMatchMode::Fuzzy
+        } else {
+            let (mode, raw) = classify_text_value(value, exact || quoted);
+            value = raw;
+            mode
+        };
         Ok(Predicate::Text {
-            field: TextField::Name,
-            matcher: TextMatcher { raw, mode },
+            field,
+            matcher: TextMatcher {
+                raw: value,
+                mode,
+                case_sensitive: modifiers.case_sensitive,
+            },
         })
     }
 
@@ -135,18 +232,19 @@ impl Parser {
         rest: &str,
         exact: bool,
         at: usize,
+        rest_at: usize,
     ) -> Result<Predicate, QueryError> {
-        let (value, value_exact, value_was_quoted) = if rest.is_empty() {
+        let (value, value_exact, value_was_quoted, value_at) = if rest.is_empty() {
             let next = self
                 .bump()
                 .ok_or_else(|| QueryError::new("Missing field value", at))?;
             match next.kind {
-                TokenKind::Quoted(v) => (v, exact, true),
+                TokenKind::Quoted(v) => (v, exact, true, next.span.start + 1),
                 TokenKind::Word(v) => {
                     if let Some(tail) = v.strip_prefix('=') {
-                        (tail.to_string(), true, false)
+                        (tail.to_string(), true, false, next.span.start + 1)
                     } else {
-                        (v, exact, false)
+                        (v, exact, false, next.span.start)
                     }
                 }
                 TokenKind::LParen | TokenKind::RParen => {
@@ -154,25 +252,75 @@ impl Parser {
                 }
             }
         } else {
-            (rest.to_string(), exact, false)
+            (rest.to_string(), exact, false, rest_at)
         };
 
         match field {
             FieldKey::Hidden => Ok(Predicate::Hidden(parse_bool(&value, at)?)),
             FieldKey::Readonly => Ok(Predicate::Readonly(parse_bool(&value, at)?)),
+            FieldKey::Content => Ok(Predicate::Content(parse_content_matcher(
+                value,
+                value_was_quoted,
+                at,
+            )?)),
+            FieldKey::Size => {
+                if is_size_facet_label(&value) {
+                    return Ok(Predicate::Facet(format!("size:{}", value.to_lowercase())));
+                }
+                Ok(Predicate::Size(parse_size_range(&value, value_at)?))
+            }
+            FieldKey::Modified | FieldKey::Created => {
+                let time_field = if matches!(field, FieldKey::Modified) {
+                    TimeField::Modified
+                } else {
+                    TimeField::Created
+                };
+                if time_field == TimeField::Modified {
+                    if let Some(label) = modified_facet_label(&value) {
+                        return Ok(Predicate::Facet(format!("modified:{label}")));
+                    }
+                }
+                Ok(Predicate::Time {
+                    field: time_field,
+                    range: parse_time_range(&value, value_at)?,
+                })
+            }
+            FieldKey::Ext => Ok(Predicate::Ext(parse_ext_list(&value, value_at)?)),
+            FieldKey::Kind => Ok(Predicate::Kind(parse_kind(&value, value_at)?)),
+            FieldKey::Type => {
+                let label = value.trim().trim_start_matches('.').to_lowercase();
+                if label.is_empty() {
+                    return Err(QueryError::new("Missing field value", at));
+                }
+                Ok(Predicate::Facet(format!("type:{label}")))
+            }
+            FieldKey::Facet => {
+                let id = value.trim().to_lowercase();
+                match id.split_once(':') {
+                    Some((dimension, label))
+                        if !label.is_empty()
+                            && matches!(dimension, "name" | "type" | "modified" | "size") =>
+                    {
+                        Ok(Predicate::Facet(id))
+                    }
+                    _ => Err(QueryError::new("Invalid facet id", value_at)),
+                }
+            }
             FieldKey::Name | FieldKey::Filename | FieldKey::Folder | FieldKey::Path => {
-                let (mode, raw) = classify_text_value(value, value_exact || value_was_quoted);
                 let text_field = match field {
                     FieldKey::Name => TextField::Name,
                     FieldKey::Filename => TextField::Filename,
                     FieldKey::Folder => TextField::Folder,
-                    FieldKey::Path => TextField::Path,
-                    FieldKey::Hidden | FieldKey::Readonly => unreachable!(),
+                    _ => TextField::Path,
                 };
-                Ok(Predicate::Text {
-                    field: text_field,
-                    matcher: TextMatcher { raw, mode },
-                })
+                self.parse_text_predicate(
+                    text_field,
+                    value,
+                    value_exact,
+                    value_was_quoted,
+                    at,
+                    value_at,
+                )
             }
         }
     }
@@ -222,6 +370,14 @@ enum FieldKey {
     Filename,
     Folder,
     Path,
+    Content,
+    Size,
+    Modified,
+    Created,
+    Ext,
+    Kind,
+    Type,
+    Facet,
     Hidden,
     Readonly,
 }
@@ -235,6 +391,22 @@ fn parse_field(value: &str) -> Option<FieldKey> {
         Some(FieldKey::Folder)
     } else if value.eq_ignore_ascii_case("path") {
         Some(FieldKey::Path)
+    } else if value.eq_ignore_ascii_case("content") {
+        Some(FieldKey::Content)
+    } else if value.eq_ignore_ascii_case("size") {
+        Some(FieldKey::Size)
+    } else if value.eq_ignore_ascii_case("modified") {
+        Some(FieldKey::Modified)
+    } else if value.eq_ignore_ascii_case("created") {
+        Some(FieldKey::Created)
+    } else if value.eq_ignore_ascii_case("ext") {
+        Some(FieldKey::Ext)
+    } else if value.eq_ignore_ascii_case("kind") {
+        Some(FieldKey::Kind)
+    } else if value.eq_ignore_ascii_case("type") {
+        Some(FieldKey::Type)
+    } else if value.eq_ignore_ascii_case("facet") {
+        Some(FieldKey::Facet)
     } else if value.eq_ignore_ascii_case("hidden") {
         Some(FieldKey::Hidden)
     } else if value.eq_ignore_ascii_case("readonly") {
@@ -254,6 +426,90 @@ fn parse_bool(raw: &str, at: usize) -> Result<bool, QueryError> {
     }
 }
 
+/// `content:/pattern/` is a regex; anything else (including quoted values) is a
+/// case-insensitive substring.
+fn parse_content_matcher(
+    value: String,
+    quoted: bool,
+    at: usize,
+) -> Result<ContentMatcher, QueryError> {
+    if value.is_empty() {
+        return Err(QueryError::new("Missing field value", at));
+    }
+    if !quoted && value.len() >= 2 && value.starts_with('/') && value.ends_with('/') {
+        let pattern = value[1..value.len() - 1].to_string();
+        if pattern.is_empty() {
+            return Err(QueryError::new("Empty content regex", at));
+        }
+        if let Err(error) = regex::bytes::Regex::new(&pattern) {
+            return Err(QueryError::new(
+                format!("Invalid content regex ({})", first_error_line(&error)),
+                at,
+            ));
+        }
+        return Ok(ContentMatcher {
+            raw: pattern,
+            mode: ContentMatchMode::Regex,
+        });
+    }
+    Ok(ContentMatcher {
+        raw: value,
+        mode: ContentMatchMode::Substring,
+    })
+}
+
+fn first_error_line(error: &regex::Error) -> String {
+    let text = error.to_string();
+    text.lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or("syntax error")
+        .trim()
+        .to_string()
+}
+
+/// Prefixes that adjust how a text value matches; they may be combined in any order.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+struct TextModifiers {
+    case_sensitive: bool,
+    regex: bool,
+}
+
+fn strip_text_modifiers(mut value: &str) -> (TextModifiers, &str) {
+    let mut modifiers = TextModifiers::default();
+    loop {
+        if let Some(rest) = strip_prefix_ignore_case(value, "case:") {
+            modifiers.case_sensitive = true;
+            value = rest;
+        } else if let Some(rest) = strip_prefix_ignore_case(value, "re:")
+            .or_else(|| strip_prefix_ignore_case(value, "regex:"))
+        {
+            modifiers.regex = true;
+            value = rest;
+        } else {
+            return (modifiers, value);
+        }
+    }
+}
+
+fn strip_prefix_ignore_case<'a>(value: &'a str, prefix: &str) -> Option<&'a str> {
+    value
+        .get(..prefix.len())
+        .filter(|head| head.eq_ignore_ascii_case(prefix))
+        .map(|_| &value[prefix.len()..])
+}
+
+fn is_slash_literal(value: &str) -> bool {
+    value.len() >= 2 && value.starts_with('/') && value.ends_with('/')
+}
+
+/// `~abc` is a fuzzy term; `~/...` stays a literal home-relative path.
+fn fuzzy_needle(value: &str) -> Option<&str> {
+    value
+        .strip_prefix('~')
+        .filter(|rest| !rest.is_empty() && !rest.starts_with('/'))
+}
+
 fn classify_text_value(value: String, exact: bool) -> (TextMatchMode, String) {
     if exact {
         return (TextMatchMode::Exact, value);
@@ -278,7 +534,10 @@ fn flatten_bool(parts: Vec<Expr>, is_and: bool) -> Expr {
 #[cfg(test)]
 mod tests {
     use super::parse;
-    use crate::commands::search::query::ast::{Expr, Predicate, TextField, TextMatchMode};
+    use crate::commands::search::query::ast::{
+        ContentMatchMode, Expr, KindFilter, Predicate, SizeRange, TextField, TextMatchMode,
+        TextMatcher, TimeField,
+    };
 
     #[test]
     fn parses_grouped_boolean_expression() {
@@ -343,4 +602,154 @@ mod tests {
         let err = parse("(name:foo").unwrap_err();
         assert!(err.message.contains("Unclosed group"));
     }
+
+    #[test]
+    fn parses_content_substring_and_regex() {
+        let expr = parse("content:\"todo list\"").unwrap();
+        match expr {
+            Expr::Predicate(Predicate::Content(matcher)) => {
+                assert_eq!(matcher.mode, ContentMatchMode::Substring);
+                assert_eq!(matcher.raw, "todo list");
+            }
+            _ => panic!("unexpected expr"),
+        }
+
+        let expr = parse("content:/fn\\s+main/").unwrap();
+        match expr {
+            Expr::Predicate(Predicate::Content(matcher)) => {
+                assert_eq!(matcher.mode, ContentMatchMode::Regex);
+                assert_eq!(matcher.raw, "fn\\s+main");
+            }
+            _ => panic!("unexpected expr"),
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_content_regex_with_position() {
+        let err = parse("name:foo content:/(unclosed/").unwrap_err();
+        assert!(err.message.contains("Invalid content regex"));
+        assert_eq!(err.at, 9);
+    }
+
+    fn text_matcher(expr: Expr) -> (TextField, TextMatcher) {
+        match expr {
+            Expr::Predicate(Predicate::Text { field, matcher }) => (field, matcher),
+            other => panic!("expected text predicate, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_regex_literals_and_re_modifier() {
+        let (field, matcher) = text_matcher(parse(r"/^foo\d+ (bar)$/").unwrap());
+        assert_eq!(field, TextField::Name);
+        assert!(matches!(matcher.mode, TextMatchMode::Regex(_)));
+        assert_eq!(matcher.raw, r"^foo\d+ (bar)$");
+
+        let (field, matcher) = text_matcher(parse("path:re:src/.*\\.rs$").unwrap());
+        assert_eq!(field, TextField::Path);
+        assert!(matches!(matcher.mode, TextMatchMode::Regex(_)));
+
+        // Slash-delimited values on path fields stay literal.
+        let (_, matcher) = text_matcher(parse("path:/home/").unwrap());
+        assert_eq!(matcher.mode, TextMatchMode::Contains);
+        assert_eq!(matcher.raw, "/home/");
+    }
+
+    #[test]
+    fn parses_fuzzy_and_case_modifiers() {
+        let (_, matcher) = text_matcher(parse("~mrs").unwrap());
+        assert_eq!(matcher.mode, TextMatchMode::Fuzzy);
+        assert_eq!(matcher.raw, "mrs");
+        assert!(!matcher.case_sensitive);
+
+        let (_, matcher) = text_matcher(parse("case:README").unwrap());
+        assert_eq!(matcher.mode, TextMatchMode::Contains);
+        assert!(matcher.case_sensitive);
+
+        let (field, matcher) = text_matcher(parse("filename:case:re: \"A B\"").unwrap());
+        assert_eq!(field, TextField::Filename);
+        assert!(matches!(matcher.mode, TextMatchMode::Regex(_)));
+        assert_eq!(matcher.raw, "A B");
+        assert!(matcher.case_sensitive);
+
+        let (_, matcher) = text_matcher(parse("path:~/docs").unwrap());
+        assert_eq!(matcher.mode, TextMatchMode::Contains);
+        assert_eq!(matcher.raw, "~/docs");
+    }
+
+    #[test]
+    fn reports_regex_errors_with_spans() {
+        let err = parse("name:foo /(unclosed/").unwrap_err();
+        assert!(err.message.contains("Invalid regex"));
+        assert_eq!((err.at, err.end), (9, 20));
+
+        let err = parse("name:/abc").unwrap_err();
+        assert!(err.message.contains("Unclosed regex"));
+        assert_eq!((err.at, err.end), (5, 9));
+
+        let err = parse("re:").unwrap_err();
+        assert!(err.message.contains("Missing field value"));
+    }
+
+    #[test]
+    fn parses_size_time_ext_and_kind_fields() {
+        let expr = parse("ext:pdf size:>10MB modified:7d kind:image").unwrap();
+        let Expr::And(parts) = expr else {
+            panic!("expected AND");
+        };
+        assert!(matches!(&parts[0], Expr::Predicate(Predicate::Ext(exts)) if exts == &["pdf"]));
+        assert!(matches!(
+            parts[1],
+            Expr::Predicate(Predicate::Size(SizeRange {
+                min: Some(_),
+                max: None
+            }))
+        ));
+        assert!(matches!(
+            parts[2],
+            Expr::Predicate(Predicate::Time {
+                field: TimeField::Modified,
+                ..
+            })
+        ));
+        assert!(matches!(
+            parts[3],
+            Expr::Predicate(Predicate::Kind(KindFilter::Image))
+        ));
+    }
+
+    #[test]
+    fn parses_facet_labels_as_facet_terms() {
+        assert_eq!(
+            parse("size:\"1–10 MB\"").unwrap(),
+            Expr::Predicate(Predicate::Facet("size:1–10 mb".into()))
+        );
+        assert_eq!(
+            parse("modified:Today").unwrap(),
+            Expr::Predicate(Predicate::Facet("modified:today".into()))
+        );
+        assert_eq!(
+            parse("type:PDF").unwrap(),
+            Expr::Predicate(Predicate::Facet("type:pdf".into()))
+        );
+        assert_eq!(
+            parse("facet:name:a-f").unwrap(),
+            Expr::Predicate(Predicate::Facet("name:a-f".into()))
+        );
+    }
+
+    #[test]
+    fn reports_value_positions_for_invalid_fields() {
+        let err = parse("name:foo size:>12QB").unwrap_err();
+        assert!(err.message.contains("Unknown size unit"));
+        assert_eq!(err.at, 17);
+
+        let err = parse("modified: yesterdayish").unwrap_err();
+        assert!(err.message.contains("Invalid date value"));
+        assert_eq!(err.at, 10);
+
+        let err = parse("kind:spreadsheet").unwrap_err();
+        assert!(err.message.contains("Unknown kind"));
+        assert_eq!(err.at, 5);
+    }
 }
@@ -0,0 +1,429 @@
+//! Value grammars for the `size:`, `modified:`/`created:`, `ext:` and `kind:` fields.
+
+use super::{
+    ast::{KindFilter, SizeRange, TimeRange},
+    error::QueryError,
+};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TimePoint {
+    Day(NaiveDate),
+    Minute(NaiveDateTime),
+    Age(i64),
+}
+
+/// Splits a leading comparison operator off `raw`, returning the operator, the remainder and
+/// the operator length so error positions can point at the remainder.
+fn split_comparison(raw: &str) -> (Comparison, &str, usize) {
+    for (prefix, cmp) in [
+        (">=", Comparison::Ge),
+        ("<=", Comparison::Le),
+        (">", Comparison::Gt),
+        ("<", Comparison::Lt),
+        ("=", Comparison::Eq),
+    ] {
+        if let Some(rest) = raw.strip_prefix(prefix) {
+            return (cmp, rest, prefix.len());
+        }
+    }
+    (Comparison::Eq, raw, 0)
+}
+
+/// Parses `10MB`, `>1.5G`, `<=512K`, `1K..5M`, `..4K` and `1G..` into inclusive byte bounds.
+/// Units are binary (`K` = 1024) to match the size facet buckets.
+pub fn parse_size_range(raw: &str, at: usize) -> Result<SizeRange, QueryError> {
+    if let Some((lo, hi)) = raw.split_once("..") {
+        let min = if lo.is_empty() {
+            None
+        } else {
+            Some(parse_size(lo, at)?)
+        };
+        let max = if hi.is_empty() {
+            None
+        } else {
+            Some(parse_size(hi, at + lo.len() + 2)?)
+        };
+        return match (min, max) {
+            (None, None) => Err(QueryError::new("Invalid size range", at)),
+            (Some(min), Some(max)) if min > max => {
+                Err(QueryError::new("Size range is reversed", at))
+            }
+            _ => Ok(SizeRange { min, max }),
+        };
+    }
+
+    let (cmp, rest, skip) = split_comparison(raw);
+    let value = parse_size(rest, at + skip)?;
+    Ok(match cmp {
+        Comparison::Gt => SizeRange {
+            min: Some(value.saturating_add(1)),
+            max: None,
+        },
+        Comparison::Ge => SizeRange {
+            min: Some(value),
+            max: None,
+        },
+        Comparison::Lt => {
+            let Some(max) = value.checked_sub(1) else {
+                return Err(QueryError::new("Size must be greater than zero", at + skip));
+            };
+            SizeRange {
+                min: None,
+                max: Some(max),
+            }
+        }
+        Comparison::Le => SizeRange {
+            min: None,
+            max: Some(value),
+        },
+        Comparison::Eq => SizeRange {
+            min: Some(value),
+            max: Some(value),
+        },
+    })
+}
+
+fn parse_size(raw: &str, at: usize) -> Result<u64, QueryError> {
+    let split = raw
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(raw.len());
+    let (number, unit) = raw.split_at(split);
+    if number.is_empty() {
+        return Err(QueryError::new("Invalid size value", at));
+    }
+    let multiplier: u64 = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        "t" | "tb" | "tib" => 1 << 40,
+        _ => return Err(QueryError::new("Unknown size unit", at + split)),
+    };
+    if number.contains('.') {
+        let value: f64 = number
+            .parse()
+            .map_err(|_| QueryError::new("Invalid size value", at))?;
+        let bytes = value * multiplier as f64;
+        if !bytes.is_finite() || bytes >= u64::MAX as f64 {
+            return Err(QueryError::new("Size value is too large", at));
+        }
+        return Ok(bytes.round() as u64);
+    }
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|value| value.checked_mul(multiplier))
+        .ok_or_else(|| QueryError::new("Size value is too large", at))
+}
+
+/// Parses absolute dates (`2025-01-01`, `2025-01-01T08:30`), ages (`36h`, `7d`, `2w`, `3mo`,
+/// `1y`), comparisons and `a..b` ranges.
+///
+/// Comparisons on absolute dates compare points in time (`<2025-01-01` is before that day).
+/// Comparisons on ages compare ages (`<7d` is newer than a week, `>7d` older); a bare age
+/// means "within the last ...".
+pub fn parse_time_range(raw: &str, at: usize) -> Result<TimeRange, QueryError> {
+    if let Some((lo, hi)) = raw.split_once("..") {
+        let hi_at = at + lo.len() + 2;
+        let lo = if lo.is_empty() {
+            None
+        } else {
+            Some(parse_time_point(lo, at)?)
+        };
+        let hi = if hi.is_empty() {
+            None
+        } else {
+            Some(parse_time_point(hi, hi_at)?)
+        };
+        return time_range_between(lo, hi, at);
+    }
+
+    let (cmp, rest, skip) = split_comparison(raw);
+    let point = parse_time_point(rest, at + skip)?;
+    Ok(match point {
+        TimePoint::Age(secs) => match cmp {
+            Comparison::Gt | Comparison::Ge => TimeRange::Age {
+                min_secs: Some(secs),
+                max_secs: None,
+            },
+            Comparison::Lt | Comparison::Le | Comparison::Eq => TimeRange::Age {
+                min_secs: None,
+                max_secs: Some(secs),
+            },
+        },
+        _ => {
+            let (start, end) = point_bounds(point);
+            match cmp {
+                Comparison::Lt => TimeRange::Absolute {
+                    start: None,
+                    end: Some(start),
+                },
+                Comparison::Le => TimeRange::Absolute {
+                    start: None,
+                    end: Some(end),
+                },
+                Comparison::Gt => TimeRange::Absolute {
+                    start: Some(end),
+                    end: None,
+                },
+                Comparison::Ge => TimeRange::Absolute {
+                    start: Some(start),
+                    end: None,
+                },
+                Comparison::Eq => TimeRange::Absolute {
+                    start: Some(start),
+                    end: Some(end),
+                },
+            }
+        }
+    })
+}
+
+fn time_range_between(
+    lo: Option<TimePoint>,
+    hi: Option<TimePoint>,
+    at: usize,
+) -> Result<TimeRange, QueryError> {
+    match (lo, hi) {
+        (None, None) => Err(QueryError::new("Invalid date range", at)),
+        (Some(TimePoint::Age(min)), Some(TimePoint::Age(max))) => {
+            if min > max {
+                return Err(QueryError::new("Date range is reversed", at));
+            }
+            Ok(TimeRange::Age {
+                min_secs: Some(min),
+                max_secs: Some(max),
+            })
+        }
+        (Some(TimePoint::Age(_)), _) | (_, Some(TimePoint::Age(_))) => {
+            Err(QueryError::new("Date range cannot mix dates and ages", at))
+        }
+        (lo, hi) => {
+            let start = lo.map(|point| point_bounds(point).0);
+            let end = hi.map(|point| point_bounds(point).1);
+            if let (Some(start), Some(end)) = (start, end) {
+                if start >= end {
+                    return Err(QueryError::new("Date range is reversed", at));
+                }
+            }
+            Ok(TimeRange::Absolute { start, end })
+        }
+    }
+}
+
+fn parse_time_point(raw: &str, at: usize) -> Result<TimePoint, QueryError> {
+    if let Ok(day) = NaiveDate::parse_from_str(raw, "%Y-%m-%d") {
+        return Ok(TimePoint::Day(day));
+    }
+    if let Ok(minute) = NaiveDateTime::parse_from_str(raw, "%Y-%m-%dT%H:%M") {
+        return Ok(TimePoint::Minute(minute));
+    }
+    let split = raw.find(|c: char| !c.is_ascii_digit()).unwrap_or(raw.len());
+    let (number, unit) = raw.split_at(split);
+    let Ok(count) = number.parse::<i64>() else {
+        return Err(QueryError::new("Invalid date value", at));
+    };
+    let unit_secs: i64 = match unit.to_ascii_lowercase().as_str() {
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        "mo" => 30 * 24 * 60 * 60,
+        "y" => 365 * 24 * 60 * 60,
+        _ => return Err(QueryError::new("Unknown date unit", at + split)),
+    };
+    count
+        .checked_mul(unit_secs)
+        .map(TimePoint::Age)
+        .ok_or_else(|| QueryError::new("Date value is too large", at))
+}
+
+fn point_bounds(point: TimePoint) -> (NaiveDateTime, NaiveDateTime) {
+    match point {
+        TimePoint::Day(day) => {
+            let start = day.and_time(chrono::NaiveTime::MIN);
+            (start, start + Duration::days(1))
+        }
+        TimePoint::Minute(minute) => (minute, minute + Duration::minutes(1)),
+        TimePoint::Age(_) => unreachable!("ages have no absolute bounds"),
+    }
+}
+
+/// Returns the lowercased label when `raw` names a modified-date facet bucket
+/// (`Today`, `Yesterday`, `3 days ago`, `2 weeks ago`, ...).
+pub fn modified_facet_label(raw: &str) -> Option<String> {
+    let label = raw.trim().to_lowercase();
+    if label == "today" || label == "yesterday" {
+        return Some(label);
+    }
+    let rest = label.strip_suffix(" ago")?;
+    let (count, unit) = rest.split_once(' ')?;
+    count.parse::<u32>().ok()?;
+    if matches!(
+        unit,
+        "day" | "days" | "week" | "weeks" | "month" | "months" | "year" | "years"
+    ) {
+        Some(label)
+    } else {
+        None
+    }
+}
+
+pub fn parse_kind(raw: &str, at: usize) -> Result<KindFilter, QueryError> {
+    match raw.to_ascii_lowercase().as_str() {
+        "file" => Ok(KindFilter::File),
+        "dir" | "folder" | "directory" => Ok(KindFilter::Dir),
+        "link" | "symlink" => Ok(KindFilter::Link),
+        "image" => Ok(KindFilter::Image),
+        "video" => Ok(KindFilter::Video),
+        "audio" => Ok(KindFilter::Audio),
+        "pdf" => Ok(KindFilter::Pdf),
+        "archive" => Ok(KindFilter::Archive),
+        _ => Err(QueryError::new("Unknown kind", at)),
+    }
+}
+
+/// Parses `pdf`, `.pdf` or `pdf,docx` into lowercased extensions without dots.
+pub fn parse_ext_list(raw: &str, at: usize) -> Result<Vec<String>, QueryError> {
+    let exts: Vec<String> = raw
+        .split(',')
+        .map(|part| part.trim().trim_start_matches('.').to_lowercase())
+        .filter(|part| !part.is_empty())
+        .collect();
+    if exts.is_empty() {
+        return Err(QueryError::new("Missing field value", at));
+    }
+    Ok(exts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{modified_facet_label, parse_ext_list, parse_size_range, parse_time_range};
+    use crate::commands::search::query::ast::{SizeRange, TimeRange};
+    use chrono::NaiveDateTime;
+
+    fn dt(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").expect("valid test datetime")
+    }
+
+    #[test]
+    fn parses_size_comparisons_and_ranges() {
+        assert_eq!(
+            parse_size_range(">10MB", 0).unwrap(),
+            SizeRange {
+                min: Some(10 * 1024 * 1024 + 1),
+                max: None
+            }
+        );
+        assert_eq!(
+            parse_size_range("1K..5M", 0).unwrap(),
+            SizeRange {
+                min: Some(1024),
+                max: Some(5 * 1024 * 1024)
+            }
+        );
+        assert_eq!(
+            parse_size_range("<=1.5k", 0).unwrap(),
+            SizeRange {
+                min: None,
+                max: Some(1536)
+            }
+        );
+        assert_eq!(
+            parse_size_range("..4K", 0).unwrap(),
+            SizeRange {
+                min: None,
+                max: Some(4096)
+            }
+        );
+    }
+
+    #[test]
+    fn size_errors_point_at_the_bad_part() {
+        let err = parse_size_range(">10XB", 5).unwrap_err();
+        assert!(err.message.contains("Unknown size unit"));
+        assert_eq!(err.at, 8);
+
+        let err = parse_size_range("1K..oops", 5).unwrap_err();
+        assert!(err.message.contains("Invalid size value"));
+        assert_eq!(err.at, 9);
+
+        let err = parse_size_range("5M..1K", 0).unwrap_err();
+        assert!(err.message.contains("reversed"));
+    }
+
+    #[test]
+    fn parses_absolute_dates_as_half_open_ranges() {
+        assert_eq!(
+            parse_time_range("<2025-01-01", 0).unwrap(),
+            TimeRange::Absolute {
+                start: None,
+                end: Some(dt("2025-01-01 00:00"))
+            }
+        );
+        assert_eq!(
+            parse_time_range("2025-01-01", 0).unwrap(),
+            TimeRange::Absolute {
+                start: Some(dt("2025-01-01 00:00")),
+                end: Some(dt("2025-01-02 00:00"))
+            }
+        );
+        assert_eq!(
+            parse_time_range("2025-01-01..2025-01-31", 0).unwrap(),
+            TimeRange::Absolute {
+                start: Some(dt("2025-01-01 00:00")),
+                end: Some(dt("2025-02-01 00:00"))
+            }
+        );
+    }
+
+    #[test]
+    fn parses_ages() {
+        assert_eq!(
+            parse_time_range("7d", 0).unwrap(),
+            TimeRange::Age {
+                min_secs: None,
+                max_secs: Some(7 * 86_400)
+            }
+        );
+        assert_eq!(
+            parse_time_range(">2w", 0).unwrap(),
+            TimeRange::Age {
+                min_secs: Some(14 * 86_400),
+                max_secs: None
+            }
+        );
+        let err = parse_time_range("7d..2025-01-01", 0).unwrap_err();
+        assert!(err.message.contains("mix"));
+        let err = parse_time_range("7x", 3).unwrap_err();
+        assert_eq!(err.at, 4);
+    }
+
+    #[test]
+    fn recognizes_modified_facet_labels() {
+        assert_eq!(modified_facet_label("Today").as_deref(), Some("today"));
+        assert_eq!(
+            modified_facet_label("3 weeks ago").as_deref(),
+            Some("3 weeks ago")
+        );
+        assert_eq!(modified_facet_label("7d"), None);
+    }
+
+    #[test]
+    fn parses_extension_lists() {
+        assert_eq!(
+            parse_ext_list(".PDF,docx", 0).unwrap(),
+            vec!["pdf".to_string(), "docx".to_string()]
+        );
+        assert!(parse_ext_list(",", 0).is_err());
+    }
+}
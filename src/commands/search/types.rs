@@ -10,4 +10,35 @@ pub struct SearchProgress {
     pub error: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub facets: Option<ListingFacets>,
+    /// Why entries in this batch matched a `content:` predicate, keyed by entry path.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub content_matches: Vec<SearchContentMatch>,
+    /// Fuzzy relevance of entries in this batch, keyed by entry path. Entries within a batch
+    /// are already ordered best first.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub scores: Vec<SearchScore>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct SearchContentMatch {
+    pub path: String,
+    pub line: u64,
+    pub snippet: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct SearchScore {
+    pub path: String,
+    pub score: u32,
+}
+
+#[derive(Serialize, Clone)]
+pub struct SearchIndexRootStatus {
+    pub root: String,
+    /// True once a full scan has completed and searches below `root` use the index.
+    pub ready: bool,
+    pub scanning: bool,
+    pub entry_count: u64,
+    /// Seconds since the Unix epoch.
+    pub last_scan_completed: Option<i64>,
 }
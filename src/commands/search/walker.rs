@@ -0,0 +1,424 @@
+//! Work-stealing parallel traversal for `search_stream`.
+//!
+//! Every directory becomes a task on a dedicated rayon pool, so idle threads steal pending
+//! subtrees from busy ones. Matches are evaluated on the walking thread (including content
+//! probes) and handed to the caller over a bounded channel, which keeps batching and event
+//! emission on a single thread.
+
+use super::content::{ContentHit, ContentScan, ContentSearcher, CONTENT_MAX_FILE_BYTES};
+use crate::{
+    commands::decompress::{
+        has_browsable_archive_name, list_archive_members, member_entry, ArchiveScanBudget,
+    },
+    commands::search::query::{fuzzy_rank, matches_query, ContentMatcher, EntryProbe, Expr, Truth},
+    entry::{build_entry, normalize_key_for_db, FsEntry},
+    exclusions::ExclusionRules,
+};
+use chrono::{DateTime, Local, NaiveDateTime};
+use once_cell::sync::Lazy;
+use rayon::{Scope, ThreadPool, ThreadPoolBuilder};
+use std::collections::HashSet;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::mpsc::SyncSender;
+use std::sync::Mutex;
+use tracing::{debug, warn};
+
+const POOL_MIN_THREADS: usize = 2;
+const POOL_MAX_THREADS: usize = 8;
+
+static SEARCH_POOL: Lazy<ThreadPool> = Lazy::new(|| {
+    ThreadPoolBuilder::new()
+        .num_threads(num_cpus::get().clamp(POOL_MIN_THREADS, POOL_MAX_THREADS))
+        .thread_name(|i| format!("search-walk-{i}"))
+        .build()
+        .expect("failed to build search pool")
+});
+
+pub(super) struct WalkHit {
+    pub entry: FsEntry,
+    pub content_hit: Option<ContentHit>,
+    /// Relevance from the query's fuzzy terms, when it has any.
+    pub score: Option<u32>,
+}
+
+/// Read-only inputs shared by every walking task.
+pub(super) struct SearchWalk<'a> {
+    pub query: &'a Expr,
+    pub simple_name_contains_needle_lc: Option<&'a str>,
+    pub content_searcher: Option<&'a ContentSearcher>,
+    /// `None` when the caller asked to include ignored entries.
+    pub exclusions: Option<&'a ExclusionRules>,
+    pub star_set: &'a HashSet<String>,
+    pub now: NaiveDateTime,
+    pub cancel: &'a AtomicBool,
+    /// Returns true once the search is cancelled or the app is shutting down.
+    pub should_stop: &'a (dyn Fn() -> bool + Sync),
+    /// Set when the caller opted into searching archive members. Shared by every archive
+    /// the search opens, so a tree full of archive bombs exhausts it once.
+    pub archive_budget: Option<&'a ArchiveScanBudget>,
+}
+
+struct WalkState<'a> {
+    walk: &'a SearchWalk<'a>,
+    seen: &'a Mutex<HashSet<String>>,
+    tx: SyncSender<WalkHit>,
+}
+
+/// Walks `root` in parallel and sends every matching entry to `tx`. Returns once all tasks
+/// have finished, or early when the receiver is gone or `should_stop` fires.
+pub(super) fn walk_parallel(walk: &SearchWalk<'_>, root: PathBuf, tx: SyncSender<WalkHit>) {
+    walk_parallel_skipping(walk, root, tx, &Mutex::new(HashSet::new()));
+}
+
+/// Like [`walk_parallel`], but leaves out the paths in `seen` and records every path it
+/// evaluates there, so passes sharing one set never send the same entry twice.
+pub(super) fn walk_parallel_skipping(
+    walk: &SearchWalk<'_>,
+    root: PathBuf,
+    tx: SyncSender<WalkHit>,
+    seen: &Mutex<HashSet<String>>,
+) {
+    let state = WalkState { walk, seen, tx };
+    SEARCH_POOL.scope(|scope| visit_dir(scope, &state, root));
+}
+
+fn visit_dir<'s>(scope: &Scope<'s>, state: &'s WalkState<'s>, dir: PathBuf) {
+    let walk = state.walk;
+    if (walk.should_stop)() {
+        return;
+    }
+
+    let iter = match std::fs::read_dir(&dir) {
+        Ok(i) => i,
+        Err(err) => {
+            if err.kind() == std::io::ErrorKind::PermissionDenied {
+                debug!(
+                    "search read_dir permission denied: dir={} err={}",
+                    dir.display(),
+                    err
+                );
+            } else {
+                warn!("search read_dir failed: dir={} err={}", dir.display(), err);
+            }
+            return;
+        }
+    };
+
+    for entry in iter.flatten() {
+        if (walk.should_stop)() {
+            return;
+        }
+
+        let path = entry.path();
+        let file_type = match entry.file_type() {
+            Ok(ft) => ft,
+            Err(_) => continue,
+        };
+        if walk
+            .exclusions
+            .is_some_and(|rules| rules.is_excluded(&path, file_type.is_dir()))
+        {
+            continue;
+        }
+        let descend = file_type.is_dir() && !file_type.is_symlink();
+
+        let name_prefiltered_out = walk.simple_name_contains_needle_lc.is_some_and(|needle| {
+            !entry
+                .file_name()
+                .to_string_lossy()
+                .to_lowercase()
+                .contains(needle)
+        });
+        if !name_prefiltered_out && !evaluate_entry(state, &path) {
+            // The receiver is gone; nobody is listening for further results.
+            return;
+        }
+        if let Some(budget) = walk.archive_budget.filter(|_| file_type.is_file()) {
+            if has_browsable_archive_name(&entry.file_name().to_string_lossy())
+                && !search_archive(state, &path, budget)
+            {
+                return;
+            }
+        }
+
+        if descend {
+            scope.spawn(move |scope| visit_dir(scope, state, path));
+        }
+    }
+}
+
+/// Evaluates one entry and forwards it when it matches. Returns false when the receiver has
+/// been dropped.
+fn evaluate_entry(state: &WalkState<'_>, path: &Path) -> bool {
+    let key = path.to_string_lossy().to_string();
+    let first_visit = match state.seen.lock() {
+        Ok(mut seen) => seen.insert(key),
+        Err(_) => return true,
+    };
+    if !first_visit {
+        return true;
+    }
+    match evaluate_path(state.walk, path) {
+        Some(hit) => state.tx.send(hit).is_ok(),
+        None => true,
+    }
+}
+
+/// Stats `path` and evaluates it against the query, returning the hit when it matches.
+pub(super) fn evaluate_path(walk: &SearchWalk<'_>, path: &Path) -> Option<WalkHit> {
+    let meta = std::fs::symlink_metadata(path).ok()?;
+    let is_link = meta.file_type().is_symlink();
+    let mut item = build_entry(path, &meta, is_link, false);
+    if walk.star_set.contains(&normalize_key_for_db(path)) {
+        item.starred = true;
+    }
+    let mut probe = LocalEntryProbe {
+        path,
+        meta: &meta,
+        content_searcher: walk.content_searcher,
+        cancel: walk.cancel,
+        content_hit: None,
+    };
+    if !matches_query(&item, walk.query, walk.now, &mut probe) {
+        return None;
+    }
+    Some(WalkHit {
+        score: fuzzy_rank(&item, walk.query),
+        entry: item,
+        content_hit: probe.content_hit,
+    })
+}
+
+/// Evaluates the members of the archive at `path` as virtual `archive://` entries. Archives
+/// that cannot be read, or that would overrun the budget, are skipped. Returns false when
+/// the receiver has been dropped.
+fn search_archive(state: &WalkState<'_>, path: &Path, budget: &ArchiveScanBudget) -> bool {
+    let walk = state.walk;
+    let members = match list_archive_members(path, None, budget, Some(walk.cancel)) {
+        Ok(members) => members,
+        Err(error) => {
+            debug!(
+                "search skipped archive: path={} err={}",
+                path.display(),
+                error.message
+            );
+            return true;
+        }
+    };
+    for member in &members {
+        if (walk.should_stop)() {
+            return true;
+        }
+        let entry = member_entry(path, member);
+        if !matches_query(&entry, walk.query, walk.now, &mut NoProbe) {
+            continue;
+        }
+        let hit = WalkHit {
+            score: fuzzy_rank(&entry, walk.query),
+            entry,
+            content_hit: None,
+        };
+        if state.tx.send(hit).is_err() {
+            return false;
+        }
+    }
+    true
+}
+
+/// Answers probe-backed terms as unknown, for entries known only by their metadata.
+pub(super) struct NoProbe;
+
+impl EntryProbe for NoProbe {
+    fn content(&mut self, _matcher: &ContentMatcher) -> Truth {
+        Truth::Unknown
+    }
+
+    fn created(&mut self) -> Option<NaiveDateTime> {
+        None
+    }
+}
+
+/// Answers the probe-backed query terms for one walked entry.
+struct LocalEntryProbe<'a> {
+    path: &'a Path,
+    meta: &'a Metadata,
+    content_searcher: Option<&'a ContentSearcher>,
+    cancel: &'a AtomicBool,
+    content_hit: Option<ContentHit>,
+}
+
+impl EntryProbe for LocalEntryProbe<'_> {
+    fn content(&mut self, matcher: &ContentMatcher) -> Truth {
+        let Some(searcher) = self.content_searcher else {
+            return Truth::Unknown;
+        };
+        if self.meta.len() > CONTENT_MAX_FILE_BYTES {
+            return Truth::Unknown;
+        }
+        match searcher.probe(self.path, matcher, self.cancel) {
+            ContentScan::Found(hit) => {
+                self.content_hit.get_or_insert(hit);
+                Truth::True
+            }
+            ContentScan::NotFound => Truth::False,
+            ContentScan::Unknown => Truth::Unknown,
+        }
+    }
+
+    fn created(&mut self) -> Option<NaiveDateTime> {
+        let created = self.meta.created().ok()?;
+        Some(DateTime::<Local>::from(created).naive_local())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{walk_parallel, NoProbe, SearchWalk};
+    use crate::commands::decompress::ArchiveScanBudget;
+    use crate::commands::search::query::{matches_query, parse_query};
+    use crate::exclusions::ExclusionRules;
+    use crate::fs_utils::unique_temp_dir;
+    use chrono::Local;
+    use std::collections::{BTreeSet, HashSet};
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc;
+
+    fn build_tree(root: &PathBuf) {
+        for dir in 0..6 {
+            for sub in 0..4 {
+                let nested = root.join(format!("dir-{dir}")).join(format!("sub-{sub}"));
+                fs::create_dir_all(&nested).expect("create nested dir");
+                for file in 0..5 {
+                    fs::write(nested.join(format!("note-{file}.txt")), b"x").expect("write");
+                    fs::write(nested.join(format!("image-{file}.png")), b"x").expect("write");
+                }
+            }
+        }
+    }
+
+    fn sequential_matches(root: &PathBuf, query: &str) -> BTreeSet<String> {
+        let expr = parse_query(query).expect("parse");
+        let now = Local::now().naive_local();
+        let mut out = BTreeSet::new();
+        let mut stack = vec![root.clone()];
+        while let Some(dir) = stack.pop() {
+            for entry in fs::read_dir(&dir).expect("read_dir").flatten() {
+                let path = entry.path();
+                let meta = fs::symlink_metadata(&path).expect("meta");
+                let is_link = meta.file_type().is_symlink();
+                let item = crate::entry::build_entry(&path, &meta, is_link, false);
+                if matches_query(&item, &expr, now, &mut NoProbe) {
+                    out.insert(item.path);
+                }
+                if meta.is_dir() && !is_link {
+                    stack.push(path);
+                }
+            }
+        }
+        out
+    }
+
+    fn parallel_matches(root: &PathBuf, query: &str, cancel: &AtomicBool) -> BTreeSet<String> {
+        parallel_matches_with(root, query, cancel, None)
+    }
+
+    fn parallel_matches_with(
+        root: &PathBuf,
+        query: &str,
+        cancel: &AtomicBool,
+        exclusions: Option<&ExclusionRules>,
+    ) -> BTreeSet<String> {
+        parallel_matches_full(root, query, cancel, exclusions, None)
+    }
+
+    fn parallel_matches_full(
+        root: &PathBuf,
+        query: &str,
+        cancel: &AtomicBool,
+        exclusions: Option<&ExclusionRules>,
+        archive_budget: Option<&ArchiveScanBudget>,
+    ) -> BTreeSet<String> {
+        let expr = parse_query(query).expect("parse");
+        let star_set = HashSet::new();
+        let should_stop = || cancel.load(Ordering::Relaxed);
+        let walk = SearchWalk {
+            query: &expr,
+            simple_name_contains_needle_lc: None,
+            content_searcher: None,
+            exclusions,
+            star_set: &star_set,
+            now: Local::now().naive_local(),
+            cancel,
+            should_stop: &should_stop,
+            archive_budget,
+        };
+        let (tx, rx) = mpsc::sync_channel(4);
+        std::thread::scope(|threads| {
+            threads.spawn(|| walk_parallel(&walk, root.clone(), tx));
+            rx.into_iter().map(|hit| hit.entry.path).collect()
+        })
+    }
+
+    #[test]
+    fn parallel_walk_matches_sequential_result_set() {
+        let root = unique_temp_dir("equivalence");
+        build_tree(&root);
+        for query in ["note", "ext:png", "kind:dir OR name:note-3"] {
+            let expected = sequential_matches(&root, query);
+            assert!(!expected.is_empty());
+            let actual = parallel_matches(&root, query, &AtomicBool::new(false));
+            assert_eq!(actual, expected, "query {query}");
+        }
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn cancelled_walk_stops_without_results() {
+        let root = unique_temp_dir("cancelled");
+        build_tree(&root);
+        let actual = parallel_matches(&root, "note", &AtomicBool::new(true));
+        assert!(actual.is_empty());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn exclusion_rules_prune_ignored_subtrees() {
+        let root = unique_temp_dir("exclusions");
+        build_tree(&root);
+        fs::write(root.join(".gitignore"), "dir-1/\nsub-2/\n").expect("write gitignore");
+        let rules = ExclusionRules::new(&root, &[]);
+        let actual = parallel_matches_with(&root, "note-0", &AtomicBool::new(false), Some(&rules));
+        // 6 dirs x 4 subs, minus all of dir-1 and every sub-2.
+        assert_eq!(actual.len(), 5 * 3);
+        assert!(actual
+            .iter()
+            .all(|path| !path.contains("dir-1") && !path.contains("sub-2")));
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn archive_budget_descends_into_zip_members() {
+        let root = unique_temp_dir("archives");
+        let archive = root.join("bundle.zip");
+        let mut zip = zip::ZipWriter::new(fs::File::create(&archive).expect("create zip"));
+        zip.start_file(
+            "docs/report-1.txt",
+            zip::write::SimpleFileOptions::default(),
+        )
+        .expect("start file");
+        std::io::Write::write_all(&mut zip, b"hello").expect("write member");
+        zip.finish().expect("finish zip");
+
+        let cancel = AtomicBool::new(false);
+        assert!(parallel_matches(&root, "report", &cancel).is_empty());
+        let budget = ArchiveScanBudget::new();
+        let actual = parallel_matches_full(&root, "report", &cancel, None, Some(&budget));
+        let expected = format!("archive://{}!/docs/report-1.txt", archive.display());
+        assert_eq!(actual, BTreeSet::from([expected]));
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -1,32 +1,33 @@
 use super::{
-    error::{SearchError, SearchErrorCode},
-    SearchProgress,
+    content::ContentSearcher,
+    error::{SearchError, SearchErrorCode, SearchResult},
+    index::{open_for_target, search_indexed},
+    walker::{walk_parallel, walk_parallel_skipping, SearchWalk, WalkHit},
+    SearchContentMatch, SearchProgress, SearchScore,
 };
 use crate::{
+    commands::decompress::ArchiveScanBudget,
     commands::fs::expand_path,
     commands::listing::{ListingFacetBuilder, ListingFacets},
-    commands::search::query::{matches_query, parse_query, simple_name_contains_needle_lc},
+    commands::search::query::{parse_query, simple_name_contains_needle_lc, Expr},
     db,
-    entry::{normalize_key_for_db, FsEntry},
+    entry::FsEntry,
+    exclusions::ExclusionRules,
     runtime_lifecycle,
     tasks::CancelState,
 };
-use std::collections::HashSet;
-use std::sync::atomic::Ordering;
-use tracing::{debug, warn};
+use chrono::{Local, NaiveDateTime};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::Mutex;
+use tracing::warn;
 
 const SEARCH_BATCH_SIZE: usize = 256;
-
-fn map_db_error(error: db::DbError) -> SearchError {
-    let code = match error.code() {
-        db::DbErrorCode::OpenFailed
-        | db::DbErrorCode::DataDirUnavailable
-        | db::DbErrorCode::PermissionDenied
-        | db::DbErrorCode::ReadOnlyFilesystem => SearchErrorCode::DatabaseOpenFailed,
-        _ => SearchErrorCode::DatabaseReadFailed,
-    };
-    SearchError::new(code, error.to_string())
-}
+/// Bounded so fast walkers cannot queue unbounded results ahead of event emission.
+const SEARCH_CHANNEL_CAPACITY: usize = SEARCH_BATCH_SIZE * 4;
 
 fn error_progress(error: SearchError) -> SearchProgress {
     SearchProgress {
@@ -35,14 +36,182 @@ fn error_progress(error: SearchError) -> SearchProgress {
         error_code: Some(error.code_str_value().to_string()),
         error: Some(error.to_string()),
         facets: Some(ListingFacets::default()),
+        content_matches: Vec::new(),
+        scores: Vec::new(),
+    }
+}
+
+/// Orders a batch best fuzzy match first; entries without a score keep their place after them.
+fn rank_batch(entries: &mut [FsEntry], scores: &[SearchScore]) {
+    if scores.is_empty() {
+        return;
     }
+    let by_path: HashMap<&str, u32> = scores
+        .iter()
+        .map(|score| (score.path.as_str(), score.score))
+        .collect();
+    entries.sort_by_key(|entry| Reverse(by_path.get(entry.path.as_str()).copied()));
 }
 
-fn invalid_query_progress(error: impl ToString) -> SearchProgress {
-    error_progress(SearchError::new(
+fn invalid_query_error(error: impl ToString) -> SearchError {
+    SearchError::new(
         SearchErrorCode::InvalidQuery,
         format!("Invalid search query: {}", error.to_string()),
-    ))
+    )
+}
+
+/// Everything resolved before walking: the parsed query, the start directory and the lookups
+/// shared by every walking task.
+pub(super) struct SearchPlan {
+    query: Expr,
+    simple_name_contains_needle_lc: Option<String>,
+    content_searcher: Option<ContentSearcher>,
+    exclusions: Option<ExclusionRules>,
+    star_set: HashSet<String>,
+    target: PathBuf,
+    now: NaiveDateTime,
+    archive_budget: Option<ArchiveScanBudget>,
+}
+
+/// Parses `query` and resolves the start directory. Returns `Ok(None)` for a blank query.
+pub(super) fn plan_search(
+    path: Option<String>,
+    query: &str,
+    include_ignored: bool,
+    search_archives: bool,
+) -> SearchResult<Option<SearchPlan>> {
+    let needle = query.trim();
+    if needle.is_empty() {
+        return Ok(None);
+    }
+    let parsed_query = parse_query(needle).map_err(invalid_query_error)?;
+    let simple_name_contains_needle_lc = simple_name_contains_needle_lc(&parsed_query);
+    let content_searcher =
+        ContentSearcher::from_query(&parsed_query).map_err(invalid_query_error)?;
+
+    // Only an ad-hoc search without a start directory falls back to home; a root that was
+    // given but is gone must not quietly search somewhere else.
+    let explicit_root = path.is_some();
+    let target = match expand_path(path).map_err(SearchError::from)? {
+        p if p.exists() => p,
+        p if explicit_root => {
+            return Err(SearchError::new(
+                SearchErrorCode::NotFound,
+                format!("Start directory not found: {}", p.display()),
+            ))
+        }
+        _ => dirs_next::home_dir().ok_or_else(|| {
+            SearchError::new(SearchErrorCode::NotFound, "Start directory not found")
+        })?,
+    };
+
+    let star_set = db::open().and_then(|conn| db::starred_set(&conn))?;
+    let exclusions = (!include_ignored).then(|| ExclusionRules::load(&target));
+    Ok(Some(SearchPlan {
+        query: parsed_query,
+        simple_name_contains_needle_lc,
+        content_searcher,
+        exclusions,
+        star_set,
+        target,
+        now: Local::now().naive_local(),
+        archive_budget: search_archives.then(ArchiveScanBudget::new),
+    }))
+}
+
+/// Runs `plan` and hands every hit to `on_hit` on the calling thread. Returns true when the
+/// search stopped early because `should_stop` fired.
+pub(super) fn execute_search(
+    plan: &SearchPlan,
+    cancel: &AtomicBool,
+    should_stop: &(dyn Fn() -> bool + Sync),
+    mut on_hit: impl FnMut(WalkHit),
+) -> bool {
+    let walk = SearchWalk {
+        query: &plan.query,
+        simple_name_contains_needle_lc: plan.simple_name_contains_needle_lc.as_deref(),
+        content_searcher: plan.content_searcher.as_ref(),
+        exclusions: plan.exclusions.as_ref(),
+        star_set: &plan.star_set,
+        now: plan.now,
+        cancel,
+        should_stop,
+        archive_budget: plan.archive_budget.as_ref(),
+    };
+    let target = plan.target.clone();
+    // The index knows archive files but not their members, so archive searches walk the disk.
+    let index = match plan.archive_budget {
+        Some(_) => None,
+        None => open_for_target(&target),
+    };
+    let (tx, rx) = mpsc::sync_channel::<WalkHit>(SEARCH_CHANNEL_CAPACITY);
+    std::thread::scope(|threads| {
+        threads.spawn(|| match index {
+            Some(index) => search_indexed_or_walk(&walk, &target, tx, |tx, seen| {
+                search_indexed(&walk, &index, &target, tx, seen)
+            }),
+            None => walk_parallel(&walk, target.clone(), tx),
+        });
+        for hit in rx {
+            if should_stop() {
+                // Dropping the receiver makes the walking tasks bail out on their next send.
+                return true;
+            }
+            on_hit(hit);
+        }
+        false
+    })
+}
+
+/// Sends the hits below `target` from the `indexed` pass. When that pass fails part-way,
+/// the live walk finishes the answer and skips every path the index already evaluated, so no
+/// hit is sent twice.
+fn search_indexed_or_walk(
+    walk: &SearchWalk<'_>,
+    target: &Path,
+    tx: SyncSender<WalkHit>,
+    indexed: impl FnOnce(&SyncSender<WalkHit>, &Mutex<HashSet<String>>) -> db::DbResult<()>,
+) {
+    let seen = Mutex::new(HashSet::new());
+    if let Err(error) = indexed(&tx, &seen) {
+        warn!(%error, "indexed search failed; walking the disk instead");
+        walk_parallel_skipping(walk, target.to_path_buf(), tx, &seen);
+    }
+}
+
+/// Runs a search to completion and returns every match, for callers that present results as
+/// a listing instead of a stream. A search stopped by `should_stop` fails as cancelled rather
+/// than passing off its partial result as complete.
+pub(super) fn collect_search(
+    path: Option<String>,
+    query: &str,
+    include_ignored: bool,
+    search_archives: bool,
+    cancel: &AtomicBool,
+    should_stop: &(dyn Fn() -> bool + Sync),
+) -> SearchResult<Vec<FsEntry>> {
+    let Some(plan) = plan_search(path, query, include_ignored, search_archives)? else {
+        return Ok(Vec::new());
+    };
+    let mut entries = Vec::new();
+    let mut scores = Vec::new();
+    let stopped = execute_search(&plan, cancel, should_stop, |hit| {
+        if let Some(score) = hit.score {
+            scores.push(SearchScore {
+                path: hit.entry.path.clone(),
+                score,
+            });
+        }
+        entries.push(hit.entry);
+    });
+    if stopped || should_stop() {
+        return Err(SearchError::new(
+            SearchErrorCode::Cancelled,
+            "Search cancelled",
+        ));
+    }
+    rank_batch(&mut entries, &scores);
+    Ok(entries)
 }
 
 pub(super) fn run_search_stream(
@@ -50,33 +219,31 @@ pub(super) fn run_search_stream(
     cancel_state: CancelState,
     path: Option<String>,
     query: String,
+    include_ignored: bool,
+    search_archives: bool,
     progress_event: String,
 ) {
+    let send_payload = |payload: SearchProgress| {
+        let _ = runtime_lifecycle::emit_if_running(&app, &progress_event, payload);
+    };
+
     let send = |entries: Vec<FsEntry>,
                 done: bool,
                 error_code: Option<String>,
                 error: Option<String>,
                 facets: Option<ListingFacets>| {
-        let payload = SearchProgress {
+        send_payload(SearchProgress {
             entries,
             done,
             error_code,
             error,
             facets,
-        };
-        let _ = runtime_lifecycle::emit_if_running(&app, &progress_event, payload);
+            content_matches: Vec::new(),
+            scores: Vec::new(),
+        });
     };
 
-    let send_error = |error: SearchError| {
-        let payload = error_progress(error);
-        send(
-            payload.entries,
-            payload.done,
-            payload.error_code,
-            payload.error,
-            payload.facets,
-        );
-    };
+    let send_error = |error: SearchError| send_payload(error_progress(error));
 
     let cancel_guard = match cancel_state.register(progress_event.clone()) {
         Ok(g) => g,
@@ -87,132 +254,72 @@ pub(super) fn run_search_stream(
     };
     let cancel_token = cancel_guard.token();
 
-    let needle = query.trim();
-    if needle.is_empty() {
-        send(Vec::new(), true, None, None, Some(ListingFacets::default()));
-        return;
-    }
-    let parsed_query = match parse_query(needle) {
-        Ok(q) => q,
-        Err(e) => {
-            let payload = invalid_query_progress(e);
-            send(
-                payload.entries,
-                payload.done,
-                payload.error_code,
-                payload.error,
-                payload.facets,
-            );
-            return;
-        }
-    };
-    let simple_name_contains_needle_lc = simple_name_contains_needle_lc(&parsed_query);
-
-    let target = match expand_path(path) {
-        Ok(p) if p.exists() => p,
-        Ok(_) => match dirs_next::home_dir() {
-            Some(h) => h,
-            None => {
-                send_error(SearchError::new(
-                    SearchErrorCode::NotFound,
-                    "Start directory not found",
-                ));
-                return;
-            }
-        },
-        Err(e) => {
-            send_error(SearchError::from(e));
+    let plan = match plan_search(path, &query, include_ignored, search_archives) {
+        Ok(Some(plan)) => plan,
+        Ok(None) => {
+            send(Vec::new(), true, None, None, Some(ListingFacets::default()));
             return;
         }
-    };
-
-    let star_set = match db::open().and_then(|conn| db::starred_set(&conn)) {
-        Ok(set) => set,
         Err(error) => {
-            send_error(map_db_error(error));
+            send_error(error);
             return;
         }
     };
 
-    let mut stack = vec![target];
-    let mut seen: HashSet<String> = HashSet::new();
     let mut batch: Vec<FsEntry> = Vec::with_capacity(SEARCH_BATCH_SIZE);
+    let mut batch_content_matches: Vec<SearchContentMatch> = Vec::new();
+    let mut batch_scores: Vec<SearchScore> = Vec::new();
     let mut facets = ListingFacetBuilder::default();
+    let send_batch = |mut entries: Vec<FsEntry>,
+                      content_matches: Vec<SearchContentMatch>,
+                      scores: Vec<SearchScore>| {
+        rank_batch(&mut entries, &scores);
+        send_payload(SearchProgress {
+            entries,
+            done: false,
+            error_code: None,
+            error: None,
+            facets: None,
+            content_matches,
+            scores,
+        });
+    };
 
-    while let Some(dir) = stack.pop() {
-        if cancel_token.load(Ordering::Relaxed) || runtime_lifecycle::is_shutting_down(&app) {
-            return;
+    let should_stop =
+        || cancel_token.load(Ordering::Relaxed) || runtime_lifecycle::is_shutting_down(&app);
+    let stopped = execute_search(&plan, &cancel_token, &should_stop, |hit| {
+        if let Some(content_hit) = hit.content_hit {
+            batch_content_matches.push(SearchContentMatch {
+                path: hit.entry.path.clone(),
+                line: content_hit.line,
+                snippet: content_hit.snippet,
+            });
         }
-
-        let iter = match std::fs::read_dir(&dir) {
-            Ok(i) => i,
-            Err(err) => {
-                if err.kind() == std::io::ErrorKind::PermissionDenied {
-                    debug!(
-                        "search read_dir permission denied: dir={} err={}",
-                        dir.display(),
-                        err
-                    );
-                } else {
-                    warn!("search read_dir failed: dir={} err={}", dir.display(), err);
-                }
-                continue;
-            }
-        };
-
-        for entry in iter.flatten() {
-            if cancel_token.load(Ordering::Relaxed) || runtime_lifecycle::is_shutting_down(&app) {
-                return;
-            }
-
-            let path = entry.path();
-            let file_type = match entry.file_type() {
-                Ok(ft) => ft,
-                Err(_) => continue,
-            };
-            let is_link = file_type.is_symlink();
-            let is_dir = file_type.is_dir();
-            if let Some(needle_lc) = simple_name_contains_needle_lc.as_deref() {
-                let name_lc = entry.file_name().to_string_lossy().to_lowercase();
-                if !name_lc.contains(needle_lc) {
-                    if is_dir && !is_link {
-                        stack.push(path);
-                    }
-                    continue;
-                }
-            }
-            {
-                let meta = match std::fs::symlink_metadata(&path) {
-                    Ok(m) => m,
-                    Err(_) => continue,
-                };
-                let key = path.to_string_lossy().to_string();
-                if seen.insert(key) {
-                    let mut item = crate::entry::build_entry(&path, &meta, is_link, false);
-                    if star_set.contains(&normalize_key_for_db(&path)) {
-                        item.starred = true;
-                    }
-                    if matches_query(&item, &parsed_query) {
-                        facets.add(&item);
-                        batch.push(item);
-                        if batch.len() >= SEARCH_BATCH_SIZE {
-                            send(std::mem::take(&mut batch), false, None, None, None);
-                        }
-                    }
-                }
-            }
-
-            if is_dir && !is_link {
-                stack.push(path);
-            }
+        if let Some(score) = hit.score {
+            batch_scores.push(SearchScore {
+                path: hit.entry.path.clone(),
+                score,
+            });
+        }
+        facets.add(&hit.entry);
+        batch.push(hit.entry);
+        if batch.len() >= SEARCH_BATCH_SIZE {
+            send_batch(
+                std::mem::take(&mut batch),
+                std::mem::take(&mut batch_content_matches),
+                std::mem::take(&mut batch_scores),
+            );
         }
+    });
+    if stopped {
+        return;
     }
 
     if !batch.is_empty() {
-        send(batch, false, None, None, None);
+        send_batch(batch, batch_content_matches, batch_scores);
     }
 
-    if cancel_token.load(Ordering::Relaxed) || runtime_lifecycle::is_shutting_down(&app) {
+    if should_stop() {
         return;
     }
     send(Vec::new(), true, None, None, Some(facets.finish()));
@@ -220,11 +327,24 @@ pub(super) fn run_search_stream(
 
 #[cfg(test)]
 mod tests {
-    use super::{invalid_query_progress, SearchError, SearchErrorCode};
+    use super::{
+        error_progress, invalid_query_error, plan_search, rank_batch, search_indexed_or_walk,
+        SearchError, SearchErrorCode, SearchScore,
+    };
+    use crate::commands::search::query::parse_query;
+    use crate::commands::search::walker::{evaluate_path, SearchWalk};
+    use crate::db::{DbError, DbErrorCode};
+    use crate::entry::FsEntry;
+    use crate::fs_utils::unique_temp_dir;
+    use chrono::Local;
+    use std::collections::HashSet;
+    use std::fs;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::mpsc;
 
     #[test]
     fn invalid_query_progress_matches_search_error_payload_shape() {
-        let payload = invalid_query_progress("Unclosed group at position 0");
+        let payload = error_progress(invalid_query_error("Unclosed group at position 0"));
         assert!(payload.done);
         assert!(payload.entries.is_empty());
         assert!(payload.facets.is_some());
@@ -238,11 +358,118 @@ mod tests {
 
     #[test]
     fn error_progress_exposes_search_error_code() {
-        let payload = super::error_progress(SearchError::new(
+        let payload = error_progress(SearchError::new(
             SearchErrorCode::DatabaseOpenFailed,
             "db unavailable",
         ));
         assert_eq!(payload.error_code.as_deref(), Some("database_open_failed"));
         assert_eq!(payload.error.as_deref(), Some("db unavailable"));
     }
+
+    fn entry(path: &str) -> FsEntry {
+        FsEntry {
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            path: path.to_string(),
+            kind: "file".to_string(),
+            ext: None,
+            size: None,
+            items: None,
+            modified: None,
+            original_path: None,
+            trash_id: None,
+            icon_id: 0,
+            starred: false,
+            hidden: false,
+            network: false,
+            read_only: false,
+            read_denied: false,
+            capabilities: None,
+        }
+    }
+
+    #[test]
+    fn rank_batch_orders_scored_entries_first() {
+        let mut entries = vec![entry("/a"), entry("/b"), entry("/c")];
+        let scores = vec![
+            SearchScore {
+                path: "/b".into(),
+                score: 10,
+            },
+            SearchScore {
+                path: "/c".into(),
+                score: 40,
+            },
+        ];
+        rank_batch(&mut entries, &scores);
+        let order: Vec<&str> = entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(order, ["/c", "/b", "/a"]);
+    }
+
+    #[test]
+    fn failed_indexed_pass_falls_back_without_resending_hits() {
+        let root = unique_temp_dir("index-fallback");
+        fs::create_dir_all(root.join("nested")).expect("create dir");
+        for name in ["note-a.txt", "note-b.txt", "note-c.txt", "other.txt"] {
+            fs::write(root.join(name), b"x").expect("write");
+        }
+        fs::write(root.join("nested").join("note-d.txt"), b"x").expect("write");
+
+        let expr = parse_query("note").expect("parse");
+        let star_set = HashSet::new();
+        let cancel = AtomicBool::new(false);
+        let should_stop = || false;
+        let walk = SearchWalk {
+            query: &expr,
+            simple_name_contains_needle_lc: None,
+            content_searcher: None,
+            exclusions: None,
+            star_set: &star_set,
+            now: Local::now().naive_local(),
+            cancel: &cancel,
+            should_stop: &should_stop,
+            archive_budget: None,
+        };
+        let (tx, rx) = mpsc::sync_channel(4);
+        let mut paths: Vec<String> = std::thread::scope(|threads| {
+            threads.spawn(|| {
+                search_indexed_or_walk(&walk, &root, tx, |tx, seen| {
+                    // Send two hits, then fail the way a broken index read would.
+                    for name in ["note-a.txt", "note-b.txt"] {
+                        let path = root.join(name);
+                        seen.lock()
+                            .expect("seen")
+                            .insert(path.to_string_lossy().to_string());
+                        let hit = evaluate_path(&walk, &path).expect("hit");
+                        tx.send(hit).expect("send");
+                    }
+                    Err(DbError::new(DbErrorCode::ReadFailed, "index read failed"))
+                })
+            });
+            rx.into_iter().map(|hit| hit.entry.path).collect()
+        });
+        paths.sort();
+
+        let expected: Vec<String> = [
+            root.join("nested").join("note-d.txt"),
+            root.join("note-a.txt"),
+            root.join("note-b.txt"),
+            root.join("note-c.txt"),
+        ]
+        .iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+        assert_eq!(paths, expected);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn missing_explicit_root_is_not_found() {
+        let dir = unique_temp_dir("missing-root");
+        let missing = dir.join("gone").to_string_lossy().to_string();
+        let error = plan_search(Some(missing), "x", false, false)
+            .err()
+            .expect("missing root fails");
+        assert_eq!(error.code_str_value(), "not_found");
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
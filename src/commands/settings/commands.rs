@@ -1,12 +1,13 @@
 use crate::{
+    commands::compress::ArchiveFormat,
     db::{load_column_widths, save_column_widths},
     errors::api_error::ApiResult,
 };
 
 use super::error::{map_api_result, SettingsError, SettingsErrorCode, SettingsResult};
 use super::persistence::{
-    invalid_input, load_bounded_i64_setting, map_settings_result, normalize_log_level,
-    open_connection,
+    invalid_input, load_bounded_i64_setting, map_settings_result, normalize_exclude_patterns,
+    normalize_log_level, normalize_search_index_roots, open_connection,
 };
 
 #[tauri::command]
@@ -267,6 +268,30 @@ pub fn load_archive_level() -> ApiResult<Option<i64>> {
     })())
 }
 
+#[tauri::command]
+pub fn store_archive_format(value: String) -> ApiResult<()> {
+    map_api_result((|| -> SettingsResult<()> {
+        if ArchiveFormat::from_extension(&value).is_none() {
+            return invalid_input("invalid archive format");
+        }
+        let conn = open_connection()?;
+        map_settings_result(crate::db::set_setting_string(
+            &conn,
+            "archiveFormat",
+            &value,
+        ))
+    })())
+}
+
+#[tauri::command]
+pub fn load_archive_format() -> ApiResult<Option<String>> {
+    map_api_result((|| -> SettingsResult<Option<String>> {
+        let conn = open_connection()?;
+        let value = map_settings_result(crate::db::get_setting_string(&conn, "archiveFormat"))?;
+        Ok(value.filter(|value| ArchiveFormat::from_extension(value).is_some()))
+    })())
+}
+
 #[tauri::command]
 pub fn store_open_dest_after_extract(value: bool) -> ApiResult<()> {
     map_api_result((|| -> SettingsResult<()> {
@@ -354,6 +379,61 @@ pub fn load_mounts_poll_ms() -> ApiResult<Option<i64>> {
     })())
 }
 
+#[tauri::command]
+pub fn store_parallel_jobs_per_device(
+    value: i64,
+    jobs: tauri::State<'_, crate::tasks::JobQueue>,
+) -> ApiResult<()> {
+    map_api_result((|| -> SettingsResult<()> {
+        if !(1..=8).contains(&value) {
+            return invalid_input("parallel jobs per device must be 1-8");
+        }
+        let conn = open_connection()?;
+        map_settings_result(crate::db::set_setting_string(
+            &conn,
+            "parallelJobsPerDevice",
+            &value.to_string(),
+        ))?;
+        jobs.set_per_device(value as usize);
+        Ok(())
+    })())
+}
+
+#[tauri::command]
+pub fn load_parallel_jobs_per_device() -> ApiResult<Option<i64>> {
+    map_api_result((|| -> SettingsResult<Option<i64>> {
+        let conn = open_connection()?;
+        load_bounded_i64_setting(&conn, "parallelJobsPerDevice", 1..=8)
+    })())
+}
+
+/// Stores the global bandwidth limit in bytes per second; `0` turns it off. Running copies pick
+/// up the change on their next chunk.
+#[tauri::command]
+pub fn store_bandwidth_limit(value: i64) -> ApiResult<()> {
+    map_api_result((|| -> SettingsResult<()> {
+        if value < 0 {
+            return invalid_input("bandwidth limit must not be negative");
+        }
+        let conn = open_connection()?;
+        map_settings_result(crate::db::set_setting_string(
+            &conn,
+            "bandwidthLimit",
+            &value.to_string(),
+        ))?;
+        crate::fs_utils::bandwidth::set_global_limit(Some(value as u64));
+        Ok(())
+    })())
+}
+
+#[tauri::command]
+pub fn load_bandwidth_limit() -> ApiResult<Option<i64>> {
+    map_api_result((|| -> SettingsResult<Option<i64>> {
+        let conn = open_connection()?;
+        load_bounded_i64_setting(&conn, "bandwidthLimit", 0..=i64::MAX)
+    })())
+}
+
 #[tauri::command]
 pub fn store_video_thumbs(value: bool) -> ApiResult<()> {
     map_api_result((|| -> SettingsResult<()> {
@@ -523,3 +603,40 @@ pub fn load_double_click_ms() -> ApiResult<Option<i64>> {
         load_bounded_i64_setting(&conn, "doubleClickMs", 150..=600)
     })())
 }
+
+#[tauri::command]
+pub fn store_search_index_roots(roots: Vec<String>) -> ApiResult<()> {
+    map_api_result((|| -> SettingsResult<()> {
+        let roots = normalize_search_index_roots(roots)?;
+        let conn = open_connection()?;
+        map_settings_result(crate::db::save_search_index_roots(&conn, &roots))?;
+        crate::commands::search::notify_search_index_roots_changed();
+        Ok(())
+    })())
+}
+
+#[tauri::command]
+pub fn load_search_index_roots() -> ApiResult<Vec<String>> {
+    map_api_result((|| -> SettingsResult<Vec<String>> {
+        let conn = open_connection()?;
+        map_settings_result(crate::db::load_search_index_roots(&conn))
+    })())
+}
+
+#[tauri::command]
+pub fn store_exclude_patterns(patterns: Vec<String>) -> ApiResult<()> {
+    map_api_result((|| -> SettingsResult<()> {
+        let patterns = normalize_exclude_patterns(patterns);
+        let conn = open_connection()?;
+        map_settings_result(crate::db::save_exclude_patterns(&conn, &patterns))
+    })())
+}
+
+#[tauri::command]
+pub fn load_exclude_patterns() -> ApiResult<Vec<String>> {
+    map_api_result((|| -> SettingsResult<Vec<String>> {
+        let conn = open_connection()?;
+        let stored = map_settings_result(crate::db::load_exclude_patterns(&conn))?;
+        Ok(stored.unwrap_or_else(crate::exclusions::default_patterns))
+    })())
+}
@@ -1,5 +1,6 @@
 use super::error::{self, SettingsError, SettingsResult};
 use std::ops::RangeInclusive;
+use std::path::PathBuf;
 
 pub(super) fn map_db_error(error: crate::db::DbError) -> SettingsError {
     match error.code() {
@@ -70,3 +71,46 @@ pub(super) fn load_bounded_i64_setting(
     }
     Ok(None)
 }
+
+/// Trims and de-duplicates search index roots and drops roots nested inside another one, so
+/// every indexed path belongs to exactly one root.
+pub(super) fn normalize_search_index_roots(roots: Vec<String>) -> SettingsResult<Vec<String>> {
+    let mut cleaned: Vec<PathBuf> = Vec::new();
+    for raw in roots {
+        let trimmed = raw.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let path = PathBuf::from(trimmed);
+        if !path.is_absolute() {
+            return invalid_input("search index roots must be absolute paths");
+        }
+        cleaned.push(path.components().collect());
+    }
+    cleaned.sort();
+    cleaned.dedup();
+    let mut out: Vec<PathBuf> = Vec::new();
+    for path in cleaned {
+        if !out.iter().any(|root| path.starts_with(root)) {
+            out.push(path);
+        }
+    }
+    Ok(out
+        .into_iter()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect())
+}
+
+/// Trims exclude patterns and drops blanks and duplicates, keeping the user's order since
+/// later gitignore-style patterns take precedence.
+pub(super) fn normalize_exclude_patterns(patterns: Vec<String>) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for pattern in patterns {
+        let trimmed = pattern.trim();
+        if trimmed.is_empty() || out.iter().any(|existing| existing == trimmed) {
+            continue;
+        }
+        out.push(trimmed.to_string());
+    }
+    out
+}
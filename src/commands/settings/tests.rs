@@ -1,6 +1,7 @@
 use super::{
-    load_cloud_enabled, load_cloud_thumbs, store_cloud_enabled, store_cloud_thumbs,
-    store_rclone_path,
+    load_cloud_enabled, load_cloud_thumbs, load_exclude_patterns, load_search_index_roots,
+    store_cloud_enabled, store_cloud_thumbs, store_exclude_patterns, store_rclone_path,
+    store_search_index_roots,
 };
 use crate::commands::cloud::{
     cloud_dir_listing_cache_contains_for_tests,
@@ -151,3 +152,66 @@ fn store_cloud_enabled_roundtrip_and_invalidates_cloud_caches() {
         Some(true)
     );
 }
+
+#[test]
+fn store_search_index_roots_normalizes_and_roundtrips() {
+    let _lock = TEST_ENV_LOCK.lock().expect("settings test env lock");
+    let _data_home = temp_data_home_guard();
+
+    assert!(load_search_index_roots()
+        .expect("load default index roots")
+        .is_empty());
+
+    let base = std::env::temp_dir().join("browsey-index-roots");
+    let docs = base.join("docs");
+    let nested = docs.join("nested");
+    let music = base.join("music");
+    store_search_index_roots(vec![
+        format!("  {}  ", music.display()),
+        nested.display().to_string(),
+        docs.display().to_string(),
+        music.display().to_string(),
+        String::new(),
+    ])
+    .expect("store index roots");
+    assert_eq!(
+        load_search_index_roots().expect("load index roots"),
+        vec![docs.display().to_string(), music.display().to_string()]
+    );
+
+    assert!(store_search_index_roots(vec!["relative/dir".to_string()]).is_err());
+    assert_eq!(
+        load_search_index_roots()
+            .expect("load index roots after rejected store")
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn exclude_patterns_default_until_stored() {
+    let _lock = TEST_ENV_LOCK.lock().expect("settings test env lock");
+    let _data_home = temp_data_home_guard();
+
+    assert_eq!(
+        load_exclude_patterns().expect("load default exclude patterns"),
+        crate::exclusions::default_patterns()
+    );
+
+    store_exclude_patterns(vec![
+        " node_modules/ ".to_string(),
+        String::new(),
+        "*.log".to_string(),
+        "node_modules/".to_string(),
+    ])
+    .expect("store exclude patterns");
+    assert_eq!(
+        load_exclude_patterns().expect("load exclude patterns"),
+        vec!["node_modules/".to_string(), "*.log".to_string()]
+    );
+
+    store_exclude_patterns(Vec::new()).expect("store empty exclude patterns");
+    assert!(load_exclude_patterns()
+        .expect("load empty exclude patterns")
+        .is_empty());
+}
@@ -1,10 +1,10 @@
 use super::error::{transfer_err, transfer_err_code as api_err, TransferErrorCode, TransferResult};
 use super::logging::{log_mixed_execute_result, log_mixed_single_execute_result};
 use super::route::{
-    local_leaf_name, mixed_route_hint, validate_mixed_transfer_pair, validate_mixed_transfer_route,
-    LocalOrCloudArg, MixedTransferPair, MixedTransferRoute,
+    is_cloud_path, local_leaf_name, mixed_route_hint, validate_mixed_transfer_pair,
+    validate_mixed_transfer_route, LocalOrCloudArg, MixedTransferPair, MixedTransferRoute,
 };
-use super::{MixedTransferOp, MixedTransferWriteOptions};
+use super::{MixedTransferBatch, MixedTransferOp, MixedTransferWriteOptions};
 use crate::commands::cloud;
 use crate::commands::cloud::path::CloudPath;
 use crate::commands::cloud::provider::CloudProvider;
@@ -13,8 +13,12 @@ use crate::commands::cloud::rclone_cli::{
     RcloneCli, RcloneCliError, RcloneCommandSpec, RcloneSubcommand,
 };
 use crate::commands::cloud::types::{CloudEntryKind, CloudProviderKind};
+use crate::fs_utils::{ConflictPolicies, VerifyMode};
 use crate::runtime_lifecycle;
-use crate::tasks::{CancelGuard, CancelState};
+use crate::tasks::{self, CancelGuard, CancelState};
+use crate::transfer_journal::{
+    RollbackOutcome, TransferJob, TransferJournal, TransferOp, TransferPlan,
+};
 use serde::Serialize;
 use std::fs;
 use std::io::ErrorKind;
@@ -24,8 +28,11 @@ use std::sync::{
 };
 use std::time::Instant;
 
+mod conflicts;
 mod flow;
 mod progress;
+mod rollback;
+mod verify;
 
 #[derive(Clone)]
 struct TransferProgressContext {
@@ -47,18 +54,29 @@ struct RcloneTransferContext<'a> {
     progress: Option<&'a TransferProgressContext>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(super) async fn execute_mixed_entries(
     op: MixedTransferOp,
     sources: Vec<String>,
     dest_dir: String,
     app: tauri::AppHandle,
     options: MixedTransferWriteOptions,
+    policies: Option<ConflictPolicies>,
+    verify: Option<VerifyMode>,
     cancel_state: CancelState,
     progress_event: Option<String>,
-) -> TransferResult<Vec<String>> {
+) -> TransferResult<MixedTransferBatch> {
     let started = Instant::now();
     let source_count = sources.len();
     let route_hint = mixed_route_hint(&sources, &dest_dir);
+    let plan = TransferPlan::Mixed {
+        sources: sources.clone(),
+        dest: dest_dir.clone(),
+        overwrite: options.overwrite,
+        prechecked: options.prechecked,
+        policies: policies.clone(),
+        verify,
+    };
     let route = match validate_mixed_transfer_route(sources, dest_dir).await {
         Ok(route) => route,
         Err(err) => {
@@ -67,23 +85,201 @@ pub(super) async fn execute_mixed_entries(
             return result;
         }
     };
-    let cancel_guard = register_mixed_cancel(&cancel_state, &progress_event)?;
+    let journal = TransferJournal::begin(transfer_op(op), plan);
+    let result = run_mixed_entries(
+        op,
+        route,
+        options,
+        policies,
+        verify,
+        journal,
+        app,
+        &cancel_state,
+        progress_event,
+    )
+    .await;
+    log_mixed_execute_result(op, &result, route_hint, source_count, started);
+    result
+}
+
+/// Runs the rest of an interrupted batch with its original options. The item it was writing
+/// goes to the same target again; a move whose source is already gone only needed its journal
+/// brought up to date.
+pub(super) async fn resume_mixed_entries(
+    job: TransferJob,
+    app: tauri::AppHandle,
+    cancel_state: CancelState,
+    progress_event: Option<String>,
+) -> TransferResult<MixedTransferBatch> {
+    let TransferPlan::Mixed {
+        sources,
+        dest,
+        overwrite,
+        prechecked,
+        policies,
+        verify,
+    } = job.plan.clone()
+    else {
+        return Err(transfer_err(
+            TransferErrorCode::InvalidInput,
+            "Not a mixed transfer",
+        ));
+    };
+    let op = mixed_op(job.op);
+    let mut remaining = sources.get(job.next..).unwrap_or_default().to_vec();
+    let mut moved_entry = None;
+    if let Some(entry) = job.entry.as_ref().filter(|_| op == MixedTransferOp::Move) {
+        if remaining.first() == Some(&entry.src) && !mixed_source_exists(&entry.src).await? {
+            remaining.remove(0);
+            moved_entry = Some(entry.clone());
+        }
+    }
+    let route = if remaining.is_empty() {
+        None
+    } else {
+        Some(validate_mixed_transfer_route(remaining, dest).await?)
+    };
+    let mut created = job.created.clone();
+    let mut skipped = job.skipped.clone();
+    let journal = TransferJournal::resume(job);
+    if let Some(entry) = moved_entry {
+        journal.item_written(&entry.src, &entry.target, entry.fresh);
+        created.push(entry.target);
+    }
+    let Some(route) = route else {
+        journal.finish();
+        return Ok(MixedTransferBatch {
+            created,
+            skipped,
+            verify: None,
+        });
+    };
+    let batch = run_mixed_entries(
+        op,
+        route,
+        MixedTransferWriteOptions {
+            overwrite,
+            prechecked,
+        },
+        policies,
+        verify,
+        journal,
+        app,
+        &cancel_state,
+        progress_event,
+    )
+    .await?;
+    created.extend(batch.created);
+    skipped.extend(batch.skipped);
+    Ok(MixedTransferBatch {
+        created,
+        skipped,
+        verify: batch.verify,
+    })
+}
+
+/// Undoes what an interrupted batch wrote, newest item first.
+pub(super) async fn rollback_mixed_entries(job: TransferJob) -> TransferResult<RollbackOutcome> {
+    let op = mixed_op(job.op);
+    let items = job
+        .entry
+        .into_iter()
+        .chain(job.written.into_iter().rev())
+        .collect::<Vec<_>>();
+    let task = tauri::async_runtime::spawn_blocking(move || {
+        let cli = configured_cli()?;
+        rollback::rollback_mixed_entries_blocking_with_cli(&cli, op, items)
+    });
+    match task.await {
+        Ok(result) => result,
+        Err(error) => Err(api_err(
+            "task_failed",
+            format!("Mixed rollback task failed: {error}"),
+        )),
+    }
+}
+
+/// Runs a validated batch under `journal`. The journal is kept when the app exits mid-batch and
+/// removed in every other case.
+#[allow(clippy::too_many_arguments)]
+async fn run_mixed_entries(
+    op: MixedTransferOp,
+    route: MixedTransferRoute,
+    options: MixedTransferWriteOptions,
+    policies: Option<ConflictPolicies>,
+    verify: Option<VerifyMode>,
+    journal: TransferJournal,
+    app: tauri::AppHandle,
+    cancel_state: &CancelState,
+    progress_event: Option<String>,
+) -> TransferResult<MixedTransferBatch> {
+    let cancel_guard = match register_mixed_cancel(cancel_state, &progress_event) {
+        Ok(guard) => guard,
+        Err(err) => {
+            journal.finish();
+            return Err(err);
+        }
+    };
     let cancel_token = cancel_guard.as_ref().map(|guard| guard.token());
     let progress = progress_event
         .clone()
-        .map(|event_name| TransferProgressContext { app, event_name });
+        .map(|event_name| TransferProgressContext {
+            app: app.clone(),
+            event_name,
+        });
     let task = tauri::async_runtime::spawn_blocking(move || {
-        execute_mixed_entries_blocking(op, route, options, cancel_token, progress)
+        let result = execute_mixed_entries_blocking(
+            op,
+            route,
+            options,
+            policies.as_ref(),
+            verify,
+            &journal,
+            cancel_token,
+            progress,
+        );
+        if result.is_err() && runtime_lifecycle::is_shutting_down(&app) {
+            journal.suspend(&[]);
+        } else {
+            journal.finish();
+        }
+        result
     });
-    let result = match task.await {
+    match task.await {
         Ok(result) => result,
         Err(error) => Err(api_err(
             "task_failed",
             format!("Mixed transfer task failed: {error}"),
         )),
-    };
-    log_mixed_execute_result(op, &result, route_hint, source_count, started);
-    result
+    }
+}
+
+async fn mixed_source_exists(raw: &str) -> TransferResult<bool> {
+    if is_cloud_path(raw) {
+        return Ok(cloud::stat_cloud_entry(raw.to_string()).await?.is_some());
+    }
+    match fs::symlink_metadata(raw) {
+        Ok(_) => Ok(true),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(transfer_err(
+            TransferErrorCode::IoError,
+            format!("Failed to read source metadata: {e}"),
+        )),
+    }
+}
+
+fn transfer_op(op: MixedTransferOp) -> TransferOp {
+    match op {
+        MixedTransferOp::Copy => TransferOp::Copy,
+        MixedTransferOp::Move => TransferOp::Move,
+    }
+}
+
+fn mixed_op(op: TransferOp) -> MixedTransferOp {
+    match op {
+        TransferOp::Copy => MixedTransferOp::Copy,
+        TransferOp::Move => MixedTransferOp::Move,
+    }
 }
 
 pub(super) async fn execute_mixed_entry_to(
@@ -124,24 +320,21 @@ pub(super) async fn execute_mixed_entry_to(
     result
 }
 
+#[allow(clippy::too_many_arguments)]
 fn execute_mixed_entries_blocking(
     op: MixedTransferOp,
     route: MixedTransferRoute,
     options: MixedTransferWriteOptions,
+    policies: Option<&ConflictPolicies>,
+    verify: Option<VerifyMode>,
+    journal: &TransferJournal,
     cancel: Option<Arc<AtomicBool>>,
     progress: Option<TransferProgressContext>,
-) -> TransferResult<Vec<String>> {
-    let cli = cloud::configured_rclone_cli().map_err(|error| {
-        let code = match error.code() {
-            cloud::RclonePathErrorCode::BinaryMissing => TransferErrorCode::BinaryMissing,
-            cloud::RclonePathErrorCode::InvalidBinaryPath => TransferErrorCode::InvalidConfig,
-            cloud::RclonePathErrorCode::DbOpenFailed | cloud::RclonePathErrorCode::DbReadFailed => {
-                TransferErrorCode::TaskFailed
-            }
-        };
-        transfer_err(code, error.message())
-    })?;
-    execute_mixed_entries_blocking_with_cli(&cli, op, route, options, cancel, progress)
+) -> TransferResult<MixedTransferBatch> {
+    let cli = configured_cli()?;
+    execute_mixed_entries_blocking_with_cli(
+        &cli, op, route, options, policies, verify, journal, cancel, progress,
+    )
 }
 
 fn execute_mixed_entry_to_blocking(
@@ -151,7 +344,12 @@ fn execute_mixed_entry_to_blocking(
     cancel: Option<Arc<AtomicBool>>,
     progress: Option<TransferProgressContext>,
 ) -> TransferResult<String> {
-    let cli = cloud::configured_rclone_cli().map_err(|error| {
+    let cli = configured_cli()?;
+    execute_mixed_entry_to_blocking_with_cli(&cli, op, pair, options, cancel, progress)
+}
+
+fn configured_cli() -> TransferResult<RcloneCli> {
+    cloud::configured_rclone_cli().map_err(|error| {
         let code = match error.code() {
             cloud::RclonePathErrorCode::BinaryMissing => TransferErrorCode::BinaryMissing,
             cloud::RclonePathErrorCode::InvalidBinaryPath => TransferErrorCode::InvalidConfig,
@@ -160,8 +358,7 @@ fn execute_mixed_entry_to_blocking(
             }
         };
         transfer_err(code, error.message())
-    })?;
-    execute_mixed_entry_to_blocking_with_cli(&cli, op, pair, options, cancel, progress)
+    })
 }
 
 fn execute_mixed_entry_to_blocking_with_cli(
@@ -175,15 +372,21 @@ fn execute_mixed_entry_to_blocking_with_cli(
     flow::execute_mixed_entry_to_blocking_with_cli(cli, op, pair, options, cancel, progress)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn execute_mixed_entries_blocking_with_cli(
     cli: &RcloneCli,
     op: MixedTransferOp,
     route: MixedTransferRoute,
     options: MixedTransferWriteOptions,
+    policies: Option<&ConflictPolicies>,
+    verify: Option<VerifyMode>,
+    journal: &TransferJournal,
     cancel: Option<Arc<AtomicBool>>,
     progress: Option<TransferProgressContext>,
-) -> TransferResult<Vec<String>> {
-    flow::execute_mixed_entries_blocking_with_cli(cli, op, route, options, cancel, progress)
+) -> TransferResult<MixedTransferBatch> {
+    flow::execute_mixed_entries_blocking_with_cli(
+        cli, op, route, options, policies, verify, journal, cancel, progress,
+    )
 }
 
 fn execute_rclone_transfer(
@@ -234,7 +437,8 @@ fn execute_rclone_transfer(
 
     let spec = RcloneCommandSpec::new(subcommand)
         .arg(src.to_os_arg())
-        .arg(dst.to_os_arg());
+        .arg(dst.to_os_arg())
+        .bwlimit(progress.map(|progress| progress.event_name.as_str()));
 
     cli.run_capture_text_with_cancel(spec, cancel)
         .map_err(|error| map_rclone_cli_error(error, cloud_remote_for_error_mapping))?;
@@ -374,6 +578,13 @@ fn register_mixed_cancel(
         })
 }
 
+/// Holds a transfer between items while its job is paused.
+fn pause_checkpoint(progress: Option<&TransferProgressContext>) {
+    if let Some(progress) = progress {
+        tasks::pause_checkpoint(&progress.app, &progress.event_name);
+    }
+}
+
 fn transfer_cancelled(cancel: Option<&AtomicBool>) -> bool {
     cancel
         .map(|token| token.load(Ordering::SeqCst))
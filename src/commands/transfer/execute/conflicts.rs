@@ -0,0 +1,205 @@
+use super::super::error::TransferError;
+use super::*;
+use crate::commands::cloud::types::CloudEntry;
+use crate::fs_utils::{rename_candidate_name, ConflictPolicies, ConflictPolicy, EntryFacts};
+use chrono::{Local, NaiveDateTime, TimeZone};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const MAX_RENAME_ATTEMPTS: usize = 50;
+
+/// Where a source is written once its conflict is resolved.
+pub(super) struct ResolvedTarget<T> {
+    pub(super) path: T,
+    /// Whether the write replaces an existing item rather than creating a new one.
+    pub(super) replaces: bool,
+}
+
+impl<T> ResolvedTarget<T> {
+    fn fresh(path: T) -> Self {
+        Self {
+            path,
+            replaces: false,
+        }
+    }
+
+    fn replacing(path: T) -> Self {
+        Self {
+            path,
+            replaces: true,
+        }
+    }
+}
+
+/// Where a local source goes below the cloud `dest_dir`, or None when the policy keeps the
+/// existing item. Rclone merges a folder copied onto a folder, so `Overwrite` needs nothing
+/// extra; the conditional policies are decided for the top-level item as a whole.
+pub(super) fn resolve_local_to_cloud_target(
+    cli: &RcloneCli,
+    policies: &ConflictPolicies,
+    src: &Path,
+    target: CloudPath,
+    dest_dir: &CloudPath,
+) -> TransferResult<Option<ResolvedTarget<CloudPath>>> {
+    let provider = mixed_cloud_provider_for_cli(cli);
+    let Some(existing) = provider
+        .stat_path(&target)
+        .map_err(map_cloud_error_to_transfer)?
+    else {
+        return Ok(Some(ResolvedTarget::fresh(target)));
+    };
+    let policy = policies.for_path(src);
+    if policy == ConflictPolicy::Rename {
+        let leaf = local_leaf_name(src)?;
+        for attempt in 1..=MAX_RENAME_ATTEMPTS {
+            let candidate = dest_dir
+                .child_path(&rename_candidate_name(leaf, attempt))
+                .map_err(|e| {
+                    transfer_err(
+                        TransferErrorCode::InvalidPath,
+                        format!("Invalid cloud target path: {e}"),
+                    )
+                })?;
+            let taken = provider
+                .stat_path(&candidate)
+                .map_err(map_cloud_error_to_transfer)?;
+            if taken.is_none() {
+                return Ok(Some(ResolvedTarget::fresh(candidate)));
+            }
+        }
+        return Err(no_free_name(&target.to_string()));
+    }
+    let src_meta = fs::symlink_metadata(src).map_err(|e| {
+        transfer_err(
+            TransferErrorCode::IoError,
+            format!("Failed to read source metadata: {e}"),
+        )
+    })?;
+    let src_facts = to_minutes(EntryFacts::from_metadata(&src_meta));
+    Ok(policy
+        .replaces(&src_facts, &cloud_entry_facts(&existing))
+        .then_some(ResolvedTarget::replacing(target)))
+}
+
+/// Where a cloud source goes in the local folder of `target`, or None when the policy keeps
+/// the existing item.
+pub(super) fn resolve_cloud_to_local_target(
+    cli: &RcloneCli,
+    policies: &ConflictPolicies,
+    src: &CloudPath,
+    target: PathBuf,
+) -> TransferResult<Option<ResolvedTarget<PathBuf>>> {
+    let Some(existing) = local_metadata(&target)? else {
+        return Ok(Some(ResolvedTarget::fresh(target)));
+    };
+    let policy = policies.for_source(&src.to_string());
+    if policy == ConflictPolicy::Rename {
+        let leaf = src.leaf_name().map_err(|e| {
+            transfer_err(
+                TransferErrorCode::InvalidPath,
+                format!("Invalid cloud source path: {e}"),
+            )
+        })?;
+        let parent = target.parent().unwrap_or_else(|| Path::new("."));
+        for attempt in 1..=MAX_RENAME_ATTEMPTS {
+            let candidate = parent.join(rename_candidate_name(leaf, attempt));
+            if local_metadata(&candidate)?.is_none() {
+                return Ok(Some(ResolvedTarget::fresh(candidate)));
+            }
+        }
+        return Err(no_free_name(&target.to_string_lossy()));
+    }
+    let Some(entry) = mixed_cloud_provider_for_cli(cli)
+        .stat_path(src)
+        .map_err(map_cloud_error_to_transfer)?
+    else {
+        return Err(transfer_err(
+            TransferErrorCode::NotFound,
+            "Cloud source was not found",
+        ));
+    };
+    let target_facts = to_minutes(EntryFacts::from_metadata(&existing));
+    if !policy.replaces(&cloud_entry_facts(&entry), &target_facts) {
+        return Ok(None);
+    }
+    if existing.file_type().is_symlink() {
+        return Err(transfer_err(
+            TransferErrorCode::SymlinkUnsupported,
+            "Refusing to overwrite symlinks",
+        ));
+    }
+    Ok(Some(ResolvedTarget::replacing(target)))
+}
+
+fn local_metadata(path: &Path) -> TransferResult<Option<fs::Metadata>> {
+    match fs::symlink_metadata(path) {
+        Ok(meta) => Ok(Some(meta)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(api_err(
+            "io_error",
+            format!("Failed to read destination metadata: {e}"),
+        )),
+    }
+}
+
+fn no_free_name(target: &str) -> TransferError {
+    transfer_err(
+        TransferErrorCode::DestinationExists,
+        format!("No free name for {target}"),
+    )
+}
+
+/// Cloud listings carry modification times to the minute, in local time.
+fn cloud_entry_facts(entry: &CloudEntry) -> EntryFacts {
+    EntryFacts {
+        is_dir: matches!(entry.kind, CloudEntryKind::Dir),
+        size: entry.size.unwrap_or(0),
+        modified: entry
+            .modified
+            .as_deref()
+            .and_then(|raw| NaiveDateTime::parse_from_str(raw, "%Y-%m-%d %H:%M").ok())
+            .and_then(|dt| Local.from_local_datetime(&dt).earliest())
+            .map(SystemTime::from),
+    }
+}
+
+/// Drops the seconds of a local modification time so it compares fairly with cloud times.
+fn to_minutes(facts: EntryFacts) -> EntryFacts {
+    EntryFacts {
+        modified: facts.modified.map(|time| {
+            let secs = time
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            UNIX_EPOCH + Duration::from_secs(secs - secs % 60)
+        }),
+        ..facts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cloud_times_compare_with_local_times_at_minute_precision() {
+        let cloud = CloudEntry {
+            name: "report.txt".to_string(),
+            path: "rclone://work/report.txt".to_string(),
+            kind: CloudEntryKind::File,
+            size: Some(10),
+            modified: Some("2025-03-01 10:15".to_string()),
+            capabilities: crate::commands::cloud::types::CloudCapabilities::v1_core_rw(),
+        };
+        let cloud_facts = cloud_entry_facts(&cloud);
+        let same_minute = to_minutes(EntryFacts {
+            is_dir: false,
+            size: 20,
+            modified: cloud_facts.modified.map(|t| t + Duration::from_secs(42)),
+        });
+
+        assert_eq!(same_minute.modified, cloud_facts.modified);
+        assert!(!ConflictPolicy::OverwriteIfNewer.replaces(&same_minute, &cloud_facts));
+        assert!(ConflictPolicy::OverwriteIfLarger.replaces(&same_minute, &cloud_facts));
+    }
+}
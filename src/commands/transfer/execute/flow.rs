@@ -1,4 +1,12 @@
 use super::*;
+use crate::fs_utils::{ConflictPolicies, FileVerifier, VerifyMode};
+use crate::transfer_journal::TransferJournal;
+
+/// A conflict that was already resolved writes over whatever it decided to replace.
+const RESOLVED_WRITE: MixedTransferWriteOptions = MixedTransferWriteOptions {
+    overwrite: true,
+    prechecked: true,
+};
 
 pub(super) fn execute_mixed_entry_to_blocking_with_cli(
     cli: &RcloneCli,
@@ -51,15 +59,25 @@ pub(super) fn execute_mixed_entry_to_blocking_with_cli(
     Ok(out)
 }
 
+/// Without `policies` an existing target fails the item unless `options` allow overwriting;
+/// with them each conflict is renamed, replaced or skipped before anything is written.
+/// `verify` only applies to copies, since a move leaves no source to compare with. A source
+/// the `journal` was writing when a previous run stopped goes back to the same target.
+#[allow(clippy::too_many_arguments)]
 pub(super) fn execute_mixed_entries_blocking_with_cli(
     cli: &RcloneCli,
     op: MixedTransferOp,
     route: MixedTransferRoute,
     options: MixedTransferWriteOptions,
+    policies: Option<&ConflictPolicies>,
+    verify: Option<VerifyMode>,
+    journal: &TransferJournal,
     cancel: Option<Arc<AtomicBool>>,
     progress: Option<TransferProgressContext>,
-) -> TransferResult<Vec<String>> {
+) -> TransferResult<MixedTransferBatch> {
     let mut created = Vec::new();
+    let mut skipped = Vec::new();
+    let verifier = FileVerifier::new(verify.filter(|_| op == MixedTransferOp::Copy));
     let result = match route {
         MixedTransferRoute::LocalToCloud { sources, dest_dir } => {
             let batch_source_count = sources.len();
@@ -70,13 +88,44 @@ pub(super) fn execute_mixed_entries_blocking_with_cli(
             };
             let mut completed_bytes = 0_u64;
             for (index, src) in sources.into_iter().enumerate() {
+                pause_checkpoint(progress.as_ref());
                 if transfer_cancelled(cancel.as_deref()) {
                     return Err(transfer_err(
                         TransferErrorCode::Cancelled,
                         "Transfer cancelled",
                     ));
                 }
-                let target = local_to_cloud_target_path(&dest_dir, &src)?;
+                let src_label = src.to_string_lossy().to_string();
+                let mut target = local_to_cloud_target_path(&dest_dir, &src)?;
+                let mut options = options;
+                let mut fresh = !options.overwrite && !options.prechecked;
+                if let Some((pending, pending_fresh)) = journal.pending_target(&src_label) {
+                    target = CloudPath::parse(&pending).map_err(|e| {
+                        transfer_err(
+                            TransferErrorCode::InvalidPath,
+                            format!("Invalid cloud target path: {e}"),
+                        )
+                    })?;
+                    options = RESOLVED_WRITE;
+                    fresh = pending_fresh;
+                } else if let Some(policies) = policies {
+                    let resolved = conflicts::resolve_local_to_cloud_target(
+                        cli, policies, &src, target, &dest_dir,
+                    )?;
+                    let Some(resolved) = resolved else {
+                        if let Some(plan) = progress_plan.as_ref() {
+                            completed_bytes =
+                                completed_bytes.saturating_add(plan.file_sizes[index]);
+                        }
+                        journal.item_skipped(&src_label);
+                        skipped.push(src_label);
+                        continue;
+                    };
+                    target = resolved.path;
+                    options = RESOLVED_WRITE;
+                    fresh = !resolved.replaces;
+                }
+                journal.begin_item(&src_label, &target.to_string(), fresh);
                 if let Some(plan) = progress_plan.as_ref() {
                     if !options.overwrite
                         && !options.prechecked
@@ -124,7 +173,16 @@ pub(super) fn execute_mixed_entries_blocking_with_cli(
                         options,
                     )?;
                 }
+                verify::verify_copied_entry(
+                    cli,
+                    &LocalOrCloudArg::Local(src.clone()),
+                    &LocalOrCloudArg::Cloud(target.clone()),
+                    Some(dest_dir.remote()),
+                    &verifier,
+                    cancel.as_deref(),
+                )?;
                 cloud::invalidate_cloud_write_paths(std::slice::from_ref(&target));
+                journal.item_written(&src_label, &target.to_string(), fresh);
                 created.push(target.to_string());
             }
             Ok(())
@@ -138,13 +196,39 @@ pub(super) fn execute_mixed_entries_blocking_with_cli(
             };
             let mut completed_bytes = 0_u64;
             for (index, src) in sources.into_iter().enumerate() {
+                pause_checkpoint(progress.as_ref());
                 if transfer_cancelled(cancel.as_deref()) {
                     return Err(transfer_err(
                         TransferErrorCode::Cancelled,
                         "Transfer cancelled",
                     ));
                 }
-                let target = cloud_to_local_target_path(&dest_dir, &src)?;
+                let src_label = src.to_string();
+                let mut target = cloud_to_local_target_path(&dest_dir, &src)?;
+                let mut options = options;
+                let mut fresh = !options.overwrite && !options.prechecked;
+                if let Some((pending, pending_fresh)) = journal.pending_target(&src_label) {
+                    target = std::path::PathBuf::from(pending);
+                    options = RESOLVED_WRITE;
+                    fresh = pending_fresh;
+                } else if let Some(policies) = policies {
+                    let resolved =
+                        conflicts::resolve_cloud_to_local_target(cli, policies, &src, target)?;
+                    let Some(resolved) = resolved else {
+                        if let Some(plan) = progress_plan.as_ref() {
+                            completed_bytes =
+                                completed_bytes.saturating_add(plan.file_sizes[index]);
+                        }
+                        journal.item_skipped(&src_label);
+                        skipped.push(src_label);
+                        continue;
+                    };
+                    target = resolved.path;
+                    options = RESOLVED_WRITE;
+                    fresh = !resolved.replaces;
+                }
+                let target_label = target.to_string_lossy().to_string();
+                journal.begin_item(&src_label, &target_label, fresh);
                 if let Some(plan) = progress_plan.as_ref() {
                     if !options.overwrite
                         && !options.prechecked
@@ -195,12 +279,25 @@ pub(super) fn execute_mixed_entries_blocking_with_cli(
                 if op == MixedTransferOp::Move {
                     cloud::invalidate_cloud_write_paths(std::slice::from_ref(&src));
                 }
-                created.push(target.to_string_lossy().to_string());
+                verify::verify_copied_entry(
+                    cli,
+                    &LocalOrCloudArg::Cloud(src.clone()),
+                    &LocalOrCloudArg::Local(target.clone()),
+                    Some(src.remote()),
+                    &verifier,
+                    cancel.as_deref(),
+                )?;
+                journal.item_written(&src_label, &target_label, fresh);
+                created.push(target_label);
             }
             Ok(())
         }
     };
-    result.map(|_| created)
+    result.map(|_| MixedTransferBatch {
+        created,
+        skipped,
+        verify: verifier.into_report(),
+    })
 }
 
 fn local_to_cloud_target_path(
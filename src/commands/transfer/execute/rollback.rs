@@ -0,0 +1,122 @@
+use super::*;
+use crate::transfer_journal::JournalEntry;
+use std::path::PathBuf;
+
+/// Putting a moved item back writes over whatever is left of its source.
+const RESTORE_WRITE: MixedTransferWriteOptions = MixedTransferWriteOptions {
+    overwrite: true,
+    prechecked: true,
+};
+
+/// Moves are moved back to their source. Copies are removed when they created their target and
+/// kept when they replaced an existing item, which is gone either way.
+pub(super) fn rollback_mixed_entries_blocking_with_cli(
+    cli: &RcloneCli,
+    op: MixedTransferOp,
+    items: Vec<JournalEntry>,
+) -> TransferResult<RollbackOutcome> {
+    let mut kept = Vec::new();
+    for item in items {
+        let src = journal_arg(&item.src)?;
+        let target = journal_arg(&item.target)?;
+        let cloud_remote = [&src, &target]
+            .into_iter()
+            .find_map(LocalOrCloudArg::cloud_path)
+            .map(|path| path.remote().to_string());
+        if !mixed_target_exists(cli, &target, cloud_remote.as_deref(), None)? {
+            continue;
+        }
+        match op {
+            MixedTransferOp::Move => {
+                execute_rclone_transfer(
+                    RcloneTransferContext {
+                        cli,
+                        cloud_remote_for_error_mapping: cloud_remote.as_deref(),
+                        cancel: None,
+                        progress: None,
+                    },
+                    MixedTransferOp::Move,
+                    target.clone(),
+                    src.clone(),
+                    RESTORE_WRITE,
+                )?;
+                let touched = [&src, &target]
+                    .into_iter()
+                    .filter_map(LocalOrCloudArg::cloud_path)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                cloud::invalidate_cloud_write_paths(&touched);
+            }
+            MixedTransferOp::Copy if !item.fresh => kept.push(item.target),
+            MixedTransferOp::Copy => remove_written_target(cli, &target)?,
+        }
+    }
+    Ok(RollbackOutcome { kept })
+}
+
+fn remove_written_target(cli: &RcloneCli, target: &LocalOrCloudArg) -> TransferResult<()> {
+    match target {
+        LocalOrCloudArg::Local(path) => {
+            let removed = if fs::symlink_metadata(path).is_ok_and(|meta| meta.is_dir()) {
+                fs::remove_dir_all(path)
+            } else {
+                fs::remove_file(path)
+            };
+            match removed {
+                Ok(()) => Ok(()),
+                Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+                Err(e) => Err(transfer_err(
+                    TransferErrorCode::IoError,
+                    format!("Failed to remove {}: {e}", path.display()),
+                )),
+            }
+        }
+        LocalOrCloudArg::Cloud(path) => {
+            let provider = mixed_cloud_provider_for_cli(cli);
+            let removed = match provider
+                .stat_path(path)
+                .map_err(map_cloud_error_to_transfer)?
+            {
+                Some(entry) if matches!(entry.kind, CloudEntryKind::Dir) => {
+                    provider.delete_dir_recursive(path, None)
+                }
+                Some(_) => provider.delete_file(path, None),
+                None => return Ok(()),
+            };
+            removed.map_err(map_cloud_error_to_transfer)?;
+            cloud::invalidate_cloud_write_paths(std::slice::from_ref(path));
+            Ok(())
+        }
+    }
+}
+
+fn journal_arg(raw: &str) -> TransferResult<LocalOrCloudArg> {
+    if !is_cloud_path(raw) {
+        return Ok(LocalOrCloudArg::Local(PathBuf::from(raw)));
+    }
+    CloudPath::parse(raw)
+        .map(LocalOrCloudArg::Cloud)
+        .map_err(|e| {
+            transfer_err(
+                TransferErrorCode::InvalidPath,
+                format!("Invalid cloud path: {e}"),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn journal_args_are_parsed_by_their_scheme() {
+        assert!(matches!(
+            journal_arg("/tmp/report.txt").unwrap(),
+            LocalOrCloudArg::Local(path) if path == PathBuf::from("/tmp/report.txt")
+        ));
+        assert!(matches!(
+            journal_arg("rclone://work/docs/report.txt").unwrap(),
+            LocalOrCloudArg::Cloud(path) if path.to_string() == "rclone://work/docs/report.txt"
+        ));
+    }
+}
@@ -91,6 +91,10 @@ impl FakeRcloneSandbox {
         fs::write(path, content).expect("write remote file");
     }
 
+    fn corrupt_next_file_copy(&self) {
+        fs::write(self.root.join("copyto-corrupt-once"), "").expect("write corrupt marker");
+    }
+
     fn write_local_file(&self, rel: &str, content: &str) -> PathBuf {
         let path = self.local_path(rel);
         if let Some(parent) = path.parent() {
@@ -137,8 +141,12 @@ fn mixed_execute_local_to_cloud_file_copy_and_move_via_fake_rclone() {
         },
         None,
         None,
+        &TransferJournal::disabled(),
+        None,
+        None,
     )
-    .expect("copy local->cloud");
+    .expect("copy local->cloud")
+    .created;
     assert_eq!(copy_out, vec!["rclone://work/dest/copy.txt".to_string()]);
     assert!(copy_src.exists(), "copy should preserve local source");
     assert_eq!(
@@ -161,8 +169,12 @@ fn mixed_execute_local_to_cloud_file_copy_and_move_via_fake_rclone() {
         },
         None,
         None,
+        &TransferJournal::disabled(),
+        None,
+        None,
     )
-    .expect("move local->cloud");
+    .expect("move local->cloud")
+    .created;
     assert_eq!(move_out, vec!["rclone://work/dest/move.txt".to_string()]);
     assert!(!move_src.exists(), "move should remove local source");
     assert_eq!(
@@ -195,8 +207,12 @@ fn mixed_execute_local_to_cloud_multi_file_copy_without_progress_event_succeeds(
         },
         None,
         None,
+        &TransferJournal::disabled(),
+        None,
+        None,
     )
-    .expect("multi-file local->cloud copy");
+    .expect("multi-file local->cloud copy")
+    .created;
 
     assert_eq!(
         out,
@@ -258,6 +274,9 @@ fn mixed_execute_local_to_cloud_batch_invalidates_destination_cloud_cache() {
         },
         None,
         None,
+        &TransferJournal::disabled(),
+        None,
+        None,
     )
     .expect("copy local->cloud should succeed");
 
@@ -344,6 +363,9 @@ fn mixed_execute_local_to_cloud_partial_copy_keeps_source_and_invalidates_cache(
         },
         None,
         None,
+        &TransferJournal::disabled(),
+        None,
+        None,
     )
     .expect_err("second source should fail and produce partial completion");
 
@@ -391,6 +413,9 @@ fn mixed_execute_local_to_cloud_partial_move_removes_first_source() {
         },
         None,
         None,
+        &TransferJournal::disabled(),
+        None,
+        None,
     )
     .expect_err("second source should fail and keep partial move state");
 
@@ -445,6 +470,9 @@ fn mixed_execute_local_to_cloud_partial_directory_move_invalidates_cache_and_kee
         },
         None,
         None,
+        &TransferJournal::disabled(),
+        None,
+        None,
     )
     .expect_err("later missing directory should fail after first success");
 
@@ -493,8 +521,12 @@ fn mixed_execute_local_to_cloud_directory_copy_and_move_via_fake_rclone() {
         },
         None,
         None,
+        &TransferJournal::disabled(),
+        None,
+        None,
     )
-    .expect("copy dir local->cloud");
+    .expect("copy dir local->cloud")
+    .created;
     assert_eq!(copy_out, vec!["rclone://work/dest/folder-copy".to_string()]);
     assert!(copy_dir.exists(), "copy should preserve local source dir");
     assert_eq!(
@@ -520,8 +552,12 @@ fn mixed_execute_local_to_cloud_directory_copy_and_move_via_fake_rclone() {
         },
         None,
         None,
+        &TransferJournal::disabled(),
+        None,
+        None,
     )
-    .expect("move dir local->cloud");
+    .expect("move dir local->cloud")
+    .created;
     assert_eq!(move_out, vec!["rclone://work/dest/folder-move".to_string()]);
     assert!(!move_dir.exists(), "move should remove local source dir");
     assert_eq!(
@@ -556,8 +592,12 @@ fn mixed_execute_cloud_to_local_file_copy_and_move_via_fake_rclone() {
         },
         None,
         None,
+        &TransferJournal::disabled(),
+        None,
+        None,
     )
-    .expect("copy cloud->local");
+    .expect("copy cloud->local")
+    .created;
     assert_eq!(
         copy_out,
         vec![local_dest.join("copy.txt").to_string_lossy().to_string()]
@@ -585,8 +625,12 @@ fn mixed_execute_cloud_to_local_file_copy_and_move_via_fake_rclone() {
         },
         None,
         None,
+        &TransferJournal::disabled(),
+        None,
+        None,
     )
-    .expect("move cloud->local");
+    .expect("move cloud->local")
+    .created;
     assert_eq!(
         move_out,
         vec![local_dest.join("move.txt").to_string_lossy().to_string()]
@@ -629,8 +673,12 @@ fn mixed_execute_cloud_to_local_multi_file_copy_without_progress_event_succeeds(
         },
         None,
         None,
+        &TransferJournal::disabled(),
+        None,
+        None,
     )
-    .expect("multi-file cloud->local copy");
+    .expect("multi-file cloud->local copy")
+    .created;
 
     assert_eq!(
         out,
@@ -674,8 +722,12 @@ fn mixed_execute_cloud_to_local_directory_copy_and_move_via_fake_rclone() {
         },
         None,
         None,
+        &TransferJournal::disabled(),
+        None,
+        None,
     )
-    .expect("copy dir cloud->local");
+    .expect("copy dir cloud->local")
+    .created;
     assert_eq!(
         copy_out,
         vec![local_dest.join("folder-copy").to_string_lossy().to_string()]
@@ -704,8 +756,12 @@ fn mixed_execute_cloud_to_local_directory_copy_and_move_via_fake_rclone() {
         },
         None,
         None,
+        &TransferJournal::disabled(),
+        None,
+        None,
     )
-    .expect("move dir cloud->local");
+    .expect("move dir cloud->local")
+    .created;
     assert_eq!(
         move_out,
         vec![local_dest.join("folder-move").to_string_lossy().to_string()]
@@ -743,6 +799,9 @@ fn mixed_execute_local_to_cloud_returns_cancelled_when_token_is_set_before_start
             overwrite: false,
             prechecked: true,
         },
+        None,
+        None,
+        &TransferJournal::disabled(),
         Some(cancel),
         None,
     )
@@ -783,6 +842,9 @@ fn mixed_execute_local_to_cloud_reports_destination_exists_when_not_prechecked()
         },
         None,
         None,
+        &TransferJournal::disabled(),
+        None,
+        None,
     )
     .expect_err("copy should fail when destination exists and prechecked is false");
 
@@ -899,6 +961,8 @@ fn mixed_execute_uses_invalid_config_for_bad_rclone_path() {
         },
         None,
         None,
+        None,
+        None,
     )
     .expect_err("invalid configured rclone path should fail");
 
@@ -912,3 +976,90 @@ fn mixed_execute_uses_invalid_config_for_bad_rclone_path() {
     set_rclone_path_override_for_tests(None);
     let _ = fs::remove_dir_all(&source_root);
 }
+
+#[cfg(unix)]
+#[test]
+fn mixed_execute_verify_reports_mismatch_and_retry_repairs_it() {
+    let _guard = fake_rclone_test_lock();
+    let sandbox = FakeRcloneSandbox::new();
+    sandbox.mkdir_remote("work", "dest");
+    let cli = sandbox.cli();
+    let src = sandbox.write_local_file("src/report.txt", "archival-payload");
+    let copy = |verify| {
+        execute_mixed_entries_blocking_with_cli(
+            &cli,
+            MixedTransferOp::Copy,
+            MixedTransferRoute::LocalToCloud {
+                sources: vec![src.clone()],
+                dest_dir: sandbox.cloud_path("rclone://work/dest"),
+            },
+            MixedTransferWriteOptions {
+                overwrite: true,
+                prechecked: true,
+            },
+            None,
+            Some(verify),
+            &TransferJournal::disabled(),
+            None,
+            None,
+        )
+        .expect("verified copy")
+    };
+
+    sandbox.corrupt_next_file_copy();
+    let report = copy(VerifyMode::Checksum).verify.expect("verify report");
+    assert_eq!(report.verified, 0);
+    assert_eq!(report.mismatches.len(), 1);
+    assert_eq!(report.mismatches[0].target, "rclone://work/dest/report.txt");
+    assert_eq!(report.mismatches[0].reason, "checksum differs");
+    assert!(!report.mismatches[0].retried);
+
+    sandbox.corrupt_next_file_copy();
+    let report = copy(VerifyMode::ChecksumRetry)
+        .verify
+        .expect("verify report");
+    assert_eq!(report.verified, 1);
+    assert!(report.mismatches.is_empty());
+    assert_eq!(
+        fs::read_to_string(sandbox.remote_path("work", "dest/report.txt")).expect("read remote"),
+        "archival-payload"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn mixed_execute_verify_checks_every_file_of_a_cloud_folder() {
+    let _guard = fake_rclone_test_lock();
+    let sandbox = FakeRcloneSandbox::new();
+    sandbox.write_remote_file("work", "photos/a.jpg", "a");
+    sandbox.write_remote_file("work", "photos/2024/b.jpg", "b");
+    let dest_dir = sandbox.local_path("dest");
+    fs::create_dir_all(&dest_dir).expect("mkdir local dest");
+
+    let batch = execute_mixed_entries_blocking_with_cli(
+        &sandbox.cli(),
+        MixedTransferOp::Copy,
+        MixedTransferRoute::CloudToLocal {
+            sources: vec![sandbox.cloud_path("rclone://work/photos")],
+            dest_dir: dest_dir.clone(),
+        },
+        MixedTransferWriteOptions {
+            overwrite: false,
+            prechecked: true,
+        },
+        None,
+        Some(VerifyMode::Checksum),
+        &TransferJournal::disabled(),
+        None,
+        None,
+    )
+    .expect("verified folder copy");
+
+    let report = batch.verify.expect("verify report");
+    assert_eq!(report.verified, 2);
+    assert!(report.mismatches.is_empty());
+    assert_eq!(
+        fs::read_to_string(dest_dir.join("photos/2024/b.jpg")).expect("read local"),
+        "b"
+    );
+}
@@ -0,0 +1,196 @@
+use super::*;
+use crate::fs_utils::FileVerifier;
+use std::collections::BTreeMap;
+
+/// Hashed on both sides with `rclone hashsum`, so local files and every remote compare the
+/// same way. Remotes that do not store this hash are read back with `--download`.
+const VERIFY_HASH: &str = "MD5";
+
+/// Re-copying a mismatched file writes over the bad copy.
+const RETRY_WRITE: MixedTransferWriteOptions = MixedTransferWriteOptions {
+    overwrite: true,
+    prechecked: true,
+};
+
+/// Checks the finished copy of `src` at `dst` file by file and records the outcome on
+/// `verifier`. A folder is compared by the paths below it; a mismatched file is copied once
+/// more when the mode retries.
+pub(super) fn verify_copied_entry(
+    cli: &RcloneCli,
+    src: &LocalOrCloudArg,
+    dst: &LocalOrCloudArg,
+    cloud_remote: Option<&str>,
+    verifier: &FileVerifier,
+    cancel: Option<&AtomicBool>,
+) -> TransferResult<()> {
+    let Some(mode) = verifier.mode() else {
+        return Ok(());
+    };
+    let is_dir = [src, dst]
+        .into_iter()
+        .find_map(LocalOrCloudArg::local_path)
+        .is_some_and(std::path::Path::is_dir);
+    let src_sums = hashsums(cli, src, is_dir, cloud_remote, cancel)?;
+    let dst_sums = hashsums(cli, dst, is_dir, cloud_remote, cancel)?;
+    for (rel, src_hash) in &src_sums {
+        let Some(reason) = mismatch_reason(src_hash, dst_sums.get(rel)) else {
+            verifier.record_match();
+            continue;
+        };
+        let src_file = child_arg(src, rel)?;
+        let dst_file = child_arg(dst, rel)?;
+        if !mode.retries() {
+            verifier.record_mismatch(&arg_label(&src_file), &arg_label(&dst_file), reason, false);
+            continue;
+        }
+        execute_rclone_transfer(
+            RcloneTransferContext {
+                cli,
+                cloud_remote_for_error_mapping: cloud_remote,
+                cancel,
+                progress: None,
+            },
+            MixedTransferOp::Copy,
+            src_file.clone(),
+            dst_file.clone(),
+            RETRY_WRITE,
+        )?;
+        let retried = hashsums(cli, &dst_file, false, cloud_remote, cancel)?;
+        match mismatch_reason(src_hash, retried.get("")) {
+            None => verifier.record_match(),
+            Some(reason) => {
+                verifier.record_mismatch(&arg_label(&src_file), &arg_label(&dst_file), reason, true)
+            }
+        }
+    }
+    Ok(())
+}
+
+fn mismatch_reason(src_hash: &str, dst_hash: Option<&String>) -> Option<&'static str> {
+    match dst_hash {
+        None => Some("missing at destination"),
+        Some(hash) if hash != src_hash => Some("checksum differs"),
+        Some(_) => None,
+    }
+}
+
+/// Hashes below `arg` keyed by their path relative to it; a single file is keyed by "".
+fn hashsums(
+    cli: &RcloneCli,
+    arg: &LocalOrCloudArg,
+    is_dir: bool,
+    cloud_remote: Option<&str>,
+    cancel: Option<&AtomicBool>,
+) -> TransferResult<BTreeMap<String, String>> {
+    let native = run_hashsum(cli, arg, false, cancel);
+    let stored = match native {
+        Ok(sums) if sums.iter().all(|(hash, _)| has_hash(hash)) => sums,
+        Ok(_) if arg.cloud_path().is_some() => run_hashsum(cli, arg, true, cancel)
+            .map_err(|error| map_rclone_cli_error(error, cloud_remote))?,
+        Err(RcloneCliError::NonZero { stderr, stdout, .. })
+            if arg.cloud_path().is_some() && is_unsupported_hash_text(&stderr, &stdout) =>
+        {
+            run_hashsum(cli, arg, true, cancel)
+                .map_err(|error| map_rclone_cli_error(error, cloud_remote))?
+        }
+        Ok(sums) => sums,
+        Err(error) => return Err(map_rclone_cli_error(error, cloud_remote)),
+    };
+    if is_dir {
+        return Ok(stored.into_iter().map(|(hash, rel)| (rel, hash)).collect());
+    }
+    Ok(stored
+        .into_iter()
+        .next()
+        .map(|(hash, _)| (String::new(), hash))
+        .into_iter()
+        .collect())
+}
+
+fn run_hashsum(
+    cli: &RcloneCli,
+    arg: &LocalOrCloudArg,
+    download: bool,
+    cancel: Option<&AtomicBool>,
+) -> Result<Vec<(String, String)>, RcloneCliError> {
+    let mut spec = RcloneCommandSpec::new(RcloneSubcommand::HashSum)
+        .arg(VERIFY_HASH)
+        .arg(arg.to_os_arg());
+    if download {
+        spec = spec.arg("--download");
+    }
+    let output = cli.run_capture_text_with_cancel(spec, cancel)?;
+    Ok(parse_hashsum_output(&output.stdout))
+}
+
+/// Lines of `rclone hashsum` are `<hash>  <relative path>`.
+fn parse_hashsum_output(stdout: &str) -> Vec<(String, String)> {
+    stdout
+        .lines()
+        .filter_map(|line| line.split_once("  "))
+        .map(|(hash, rel)| (hash.trim().to_ascii_lowercase(), rel.to_string()))
+        .collect()
+}
+
+fn has_hash(hash: &str) -> bool {
+    !hash.is_empty() && !hash.eq_ignore_ascii_case("unsupported")
+}
+
+fn is_unsupported_hash_text(stderr: &str, stdout: &str) -> bool {
+    let lower = format!("{stderr}\n{stdout}").to_ascii_lowercase();
+    lower.contains("unsupported") || lower.contains("not supported")
+}
+
+fn child_arg(arg: &LocalOrCloudArg, rel: &str) -> TransferResult<LocalOrCloudArg> {
+    if rel.is_empty() {
+        return Ok(arg.clone());
+    }
+    match arg {
+        LocalOrCloudArg::Local(path) => Ok(LocalOrCloudArg::Local(path.join(rel))),
+        LocalOrCloudArg::Cloud(path) => rel
+            .split('/')
+            .try_fold(path.clone(), |parent, name| parent.child_path(name))
+            .map(LocalOrCloudArg::Cloud)
+            .map_err(|e| {
+                transfer_err(
+                    TransferErrorCode::InvalidPath,
+                    format!("Invalid cloud target path: {e}"),
+                )
+            }),
+    }
+}
+
+fn arg_label(arg: &LocalOrCloudArg) -> String {
+    match arg {
+        LocalOrCloudArg::Local(path) => path.to_string_lossy().to_string(),
+        LocalOrCloudArg::Cloud(path) => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashsum_output_is_split_into_hashes_and_relative_paths() {
+        let parsed = parse_hashsum_output(
+            "D41D8CD98F00B204E9800998ECF8427E  docs/empty.txt\nUNSUPPORTED  a  b.txt\n",
+        );
+        assert_eq!(
+            parsed,
+            vec![
+                (
+                    "d41d8cd98f00b204e9800998ecf8427e".to_string(),
+                    "docs/empty.txt".to_string()
+                ),
+                ("unsupported".to_string(), "a  b.txt".to_string()),
+            ]
+        );
+        assert!(!has_hash(&parsed[1].0));
+        assert_eq!(
+            mismatch_reason("abc", Some(&"abd".to_string())),
+            Some("checksum differs")
+        );
+        assert_eq!(mismatch_reason("abc", None), Some("missing at destination"));
+    }
+}
@@ -1,6 +1,6 @@
 use super::error::TransferResult;
 use super::route::{route_hint_label, MixedRouteHint};
-use super::{MixedTransferConflictInfo, MixedTransferOp};
+use super::{MixedTransferBatch, MixedTransferConflictInfo, MixedTransferOp};
 use std::time::Instant;
 use tracing::{info, warn};
 
@@ -34,7 +34,7 @@ pub(super) fn log_mixed_preview_result(
 
 pub(super) fn log_mixed_execute_result(
     op: MixedTransferOp,
-    result: &TransferResult<Vec<String>>,
+    result: &TransferResult<MixedTransferBatch>,
     route_hint: MixedRouteHint,
     source_count: usize,
     started: Instant,
@@ -45,11 +45,16 @@ pub(super) fn log_mixed_execute_result(
         MixedTransferOp::Move => "mixed_write_move",
     };
     match result {
-        Ok(created) => info!(
+        Ok(batch) => info!(
             op = op_name,
             route = route_hint_label(route_hint),
             source_count,
-            outputs = created.len(),
+            outputs = batch.created.len(),
+            skipped = batch.skipped.len(),
+            verify_mismatches = batch
+                .verify
+                .as_ref()
+                .map_or(0, |report| report.mismatches.len()),
             elapsed_ms,
             "mixed transfer completed"
         ),
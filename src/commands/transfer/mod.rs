@@ -5,9 +5,18 @@ mod preview;
 mod route;
 
 use crate::errors::api_error::ApiResult;
-use crate::tasks::CancelState;
-use error::map_api_result;
+use crate::fs_utils::{
+    ConflictPolicies, ConflictPolicy, MetadataPreserver, PreserveAttr, PreserveOptions,
+    UnpreservedAttr, VerifyMode, VerifyReport,
+};
+use crate::tasks::{run_job, CancelState, JobKind, JobSpec};
+use crate::transfer_journal::{ResumeOutcome, RollbackOutcome, TransferJob};
+use error::{map_api_result, transfer_err, TransferError, TransferErrorCode, TransferResult};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+const CLOUD_UNPRESERVED_REASON: &str = "cloud storage does not keep them";
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
@@ -25,6 +34,32 @@ pub struct MixedTransferWriteOptions {
     pub prechecked: bool,
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MixedTransferResult {
+    pub created: Vec<String>,
+    /// Sources the conflict policy kept back.
+    pub skipped: Vec<String>,
+    /// Requested attributes the cloud side could not keep.
+    pub unpreserved: Vec<UnpreservedAttr>,
+    /// Checksum results, present when a copy asked for verification.
+    pub verify: Option<VerifyReport>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MixedTransferEntryResult {
+    pub created: String,
+    pub unpreserved: Vec<UnpreservedAttr>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct MixedTransferBatch {
+    pub(super) created: Vec<String>,
+    pub(super) skipped: Vec<String>,
+    pub(super) verify: Option<VerifyReport>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(super) enum MixedTransferOp {
     Copy,
@@ -41,60 +76,100 @@ pub async fn preview_mixed_transfer_conflicts(
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn copy_mixed_entries(
     sources: Vec<String>,
     dest_dir: String,
     app: tauri::AppHandle,
     overwrite: Option<bool>,
     prechecked: Option<bool>,
+    policy: Option<String>,
+    decisions: Option<HashMap<String, String>>,
     cancel: tauri::State<'_, CancelState>,
     progress_event: Option<String>,
-) -> ApiResult<Vec<String>> {
+    preserve: Option<Vec<PreserveAttr>>,
+    verify: Option<VerifyMode>,
+) -> ApiResult<MixedTransferResult> {
+    let policies = match conflict_policies(policy, decisions) {
+        Ok(policies) => policies,
+        Err(err) => return map_api_result(Err(err)),
+    };
+    let unpreserved = cloud_unpreserved(&sources, preserve);
+    let job = transfer_job(&sources, &dest_dir, &progress_event);
+    let work = execute::execute_mixed_entries(
+        MixedTransferOp::Copy,
+        sources,
+        dest_dir,
+        app.clone(),
+        MixedTransferWriteOptions {
+            overwrite: overwrite.unwrap_or(false),
+            prechecked: prechecked.unwrap_or(false),
+        },
+        policies,
+        verify,
+        cancel.inner().clone(),
+        progress_event,
+    );
     map_api_result(
-        execute::execute_mixed_entries(
-            MixedTransferOp::Copy,
-            sources,
-            dest_dir,
-            app,
-            MixedTransferWriteOptions {
-                overwrite: overwrite.unwrap_or(false),
-                prechecked: prechecked.unwrap_or(false),
-            },
-            cancel.inner().clone(),
-            progress_event,
-        )
-        .await,
+        run_job(&app, job, transfer_cancelled, work)
+            .await
+            .map(|batch| MixedTransferResult {
+                created: batch.created,
+                skipped: batch.skipped,
+                unpreserved,
+                verify: batch.verify,
+            }),
     )
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn move_mixed_entries(
     sources: Vec<String>,
     dest_dir: String,
     app: tauri::AppHandle,
     overwrite: Option<bool>,
     prechecked: Option<bool>,
+    policy: Option<String>,
+    decisions: Option<HashMap<String, String>>,
     cancel: tauri::State<'_, CancelState>,
     progress_event: Option<String>,
-) -> ApiResult<Vec<String>> {
+    preserve: Option<Vec<PreserveAttr>>,
+) -> ApiResult<MixedTransferResult> {
+    let policies = match conflict_policies(policy, decisions) {
+        Ok(policies) => policies,
+        Err(err) => return map_api_result(Err(err)),
+    };
+    let unpreserved = cloud_unpreserved(&sources, preserve);
+    let job = transfer_job(&sources, &dest_dir, &progress_event);
+    let work = execute::execute_mixed_entries(
+        MixedTransferOp::Move,
+        sources,
+        dest_dir,
+        app.clone(),
+        MixedTransferWriteOptions {
+            overwrite: overwrite.unwrap_or(false),
+            prechecked: prechecked.unwrap_or(false),
+        },
+        policies,
+        None,
+        cancel.inner().clone(),
+        progress_event,
+    );
     map_api_result(
-        execute::execute_mixed_entries(
-            MixedTransferOp::Move,
-            sources,
-            dest_dir,
-            app,
-            MixedTransferWriteOptions {
-                overwrite: overwrite.unwrap_or(false),
-                prechecked: prechecked.unwrap_or(false),
-            },
-            cancel.inner().clone(),
-            progress_event,
-        )
-        .await,
+        run_job(&app, job, transfer_cancelled, work)
+            .await
+            .map(|batch| MixedTransferResult {
+                created: batch.created,
+                skipped: batch.skipped,
+                unpreserved,
+                verify: batch.verify,
+            }),
     )
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn copy_mixed_entry_to(
     src: String,
     dst: String,
@@ -103,25 +178,34 @@ pub async fn copy_mixed_entry_to(
     prechecked: Option<bool>,
     cancel: tauri::State<'_, CancelState>,
     progress_event: Option<String>,
-) -> ApiResult<String> {
+    preserve: Option<Vec<PreserveAttr>>,
+) -> ApiResult<MixedTransferEntryResult> {
+    let unpreserved = cloud_unpreserved(std::slice::from_ref(&src), preserve);
+    let job = transfer_job(std::slice::from_ref(&src), &dst, &progress_event);
+    let work = execute::execute_mixed_entry_to(
+        MixedTransferOp::Copy,
+        src,
+        dst,
+        app.clone(),
+        MixedTransferWriteOptions {
+            overwrite: overwrite.unwrap_or(false),
+            prechecked: prechecked.unwrap_or(false),
+        },
+        cancel.inner().clone(),
+        progress_event,
+    );
     map_api_result(
-        execute::execute_mixed_entry_to(
-            MixedTransferOp::Copy,
-            src,
-            dst,
-            app,
-            MixedTransferWriteOptions {
-                overwrite: overwrite.unwrap_or(false),
-                prechecked: prechecked.unwrap_or(false),
-            },
-            cancel.inner().clone(),
-            progress_event,
-        )
-        .await,
+        run_job(&app, job, transfer_cancelled, work)
+            .await
+            .map(|created| MixedTransferEntryResult {
+                created,
+                unpreserved,
+            }),
     )
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn move_mixed_entry_to(
     src: String,
     dst: String,
@@ -130,20 +214,158 @@ pub async fn move_mixed_entry_to(
     prechecked: Option<bool>,
     cancel: tauri::State<'_, CancelState>,
     progress_event: Option<String>,
-) -> ApiResult<String> {
+    preserve: Option<Vec<PreserveAttr>>,
+) -> ApiResult<MixedTransferEntryResult> {
+    let unpreserved = cloud_unpreserved(std::slice::from_ref(&src), preserve);
+    let job = transfer_job(std::slice::from_ref(&src), &dst, &progress_event);
+    let work = execute::execute_mixed_entry_to(
+        MixedTransferOp::Move,
+        src,
+        dst,
+        app.clone(),
+        MixedTransferWriteOptions {
+            overwrite: overwrite.unwrap_or(false),
+            prechecked: prechecked.unwrap_or(false),
+        },
+        cancel.inner().clone(),
+        progress_event,
+    );
     map_api_result(
-        execute::execute_mixed_entry_to(
-            MixedTransferOp::Move,
-            src,
-            dst,
-            app,
-            MixedTransferWriteOptions {
-                overwrite: overwrite.unwrap_or(false),
-                prechecked: prechecked.unwrap_or(false),
-            },
-            cancel.inner().clone(),
-            progress_event,
-        )
-        .await,
+        run_job(&app, job, transfer_cancelled, work)
+            .await
+            .map(|created| MixedTransferEntryResult {
+                created,
+                unpreserved,
+            }),
+    )
+}
+
+/// Runs the rest of an interrupted mixed transfer.
+pub(crate) async fn resume_mixed_job(
+    job: TransferJob,
+    app: tauri::AppHandle,
+    cancel_state: CancelState,
+    progress_event: Option<String>,
+) -> ApiResult<ResumeOutcome> {
+    let (sources, dest) = job.plan.paths();
+    let spec = transfer_job(&sources, &dest, &progress_event);
+    let work = execute::resume_mixed_entries(job, app.clone(), cancel_state, progress_event);
+    map_api_result(
+        run_job(&app, spec, transfer_cancelled, work)
+            .await
+            .map(|batch| ResumeOutcome {
+                created: batch.created,
+                skipped: batch.skipped,
+                verify: batch.verify,
+            }),
     )
 }
+
+fn transfer_job(sources: &[String], dest: &str, progress_event: &Option<String>) -> JobSpec {
+    JobSpec::new(
+        JobKind::Transfer,
+        dest,
+        sources.to_vec(),
+        progress_event.clone(),
+    )
+}
+
+fn transfer_cancelled() -> TransferError {
+    transfer_err(TransferErrorCode::Cancelled, "Transfer cancelled")
+}
+
+/// Undoes what an interrupted mixed transfer wrote.
+pub(crate) async fn rollback_mixed_job(job: &TransferJob) -> ApiResult<RollbackOutcome> {
+    map_api_result(execute::rollback_mixed_entries(job.clone()).await)
+}
+
+/// Conflict policies for a batch transfer. Without a `policy` or `decisions` the batch keeps
+/// the `overwrite` flag behaviour, so existing callers are unaffected.
+fn conflict_policies(
+    policy: Option<String>,
+    decisions: Option<HashMap<String, String>>,
+) -> TransferResult<Option<ConflictPolicies>> {
+    if policy.is_none() && decisions.is_none() {
+        return Ok(None);
+    }
+    let parse = |raw: &str| {
+        ConflictPolicy::parse(raw).ok_or_else(|| {
+            transfer_err(
+                TransferErrorCode::InvalidInput,
+                format!("Invalid conflict policy: {raw}"),
+            )
+        })
+    };
+    let default = policy
+        .as_deref()
+        .map(parse)
+        .transpose()?
+        .unwrap_or_default();
+    let items = decisions
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(src, raw)| Ok((src, parse(&raw)?)))
+        .collect::<TransferResult<HashMap<_, _>>>()?;
+    Ok(Some(ConflictPolicies::new(default, items)))
+}
+
+/// Cloud remotes keep modification times but not permission bits, extended attributes or
+/// ACLs, so those are reported for local sources before they are uploaded (or moved away).
+fn cloud_unpreserved(
+    sources: &[String],
+    preserve: Option<Vec<PreserveAttr>>,
+) -> Vec<UnpreservedAttr> {
+    let preserve = MetadataPreserver::new(PreserveOptions::from_request(
+        preserve,
+        PreserveOptions::NONE,
+    ));
+    for source in sources.iter().filter(|s| !route::is_cloud_path(s)) {
+        let path = Path::new(source);
+        preserve.record(path, PreserveAttr::Mode, CLOUD_UNPRESERVED_REASON);
+        preserve.record_unstorable(path, CLOUD_UNPRESERVED_REASON);
+    }
+    preserve.into_report()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cloud_unpreserved_reports_mode_for_local_sources_only() {
+        let report = cloud_unpreserved(
+            &["/tmp/a.txt".to_string(), "rclone://work/b.txt".to_string()],
+            Some(vec![PreserveAttr::Times, PreserveAttr::Mode]),
+        );
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].attribute, PreserveAttr::Mode);
+        assert_eq!(report[0].count, 1);
+        assert_eq!(report[0].path, "/tmp/a.txt");
+
+        assert!(cloud_unpreserved(&["/tmp/a.txt".to_string()], None).is_empty());
+    }
+
+    #[test]
+    fn conflict_policies_are_only_built_when_requested() {
+        assert!(conflict_policies(None, None).unwrap().is_none());
+
+        let policies = conflict_policies(
+            None,
+            Some(HashMap::from([(
+                "rclone://work/a.txt".to_string(),
+                "skip".to_string(),
+            )])),
+        )
+        .unwrap()
+        .expect("decisions imply policies");
+        assert_eq!(
+            policies.for_source("rclone://work/a.txt"),
+            ConflictPolicy::Skip
+        );
+        assert_eq!(
+            policies.for_source("rclone://work/b.txt"),
+            ConflictPolicy::Rename
+        );
+        assert!(conflict_policies(Some("newest".to_string()), None).is_err());
+    }
+}
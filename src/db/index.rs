@@ -0,0 +1,541 @@
+//! Persistent filename index used to answer searches without walking the disk.
+//!
+//! The index lives in its own database (`browsey-index.db`) next to `browsey.db` so that a
+//! large rebuild never contends with the settings/bookmark connection.
+
+use super::{data_dir, map_db_sqlite, DbErrorCode, DbResult};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::path::{Path, MAIN_SEPARATOR};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long a statement waits on a lock held by the other connection before failing. The
+/// background scanner writes while searches read, each through its own connection.
+const INDEX_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedEntry {
+    pub path: String,
+    pub name: String,
+    /// "file", "dir" or "link", matching `FsEntry::kind`.
+    pub kind: String,
+    pub size: Option<u64>,
+    /// Seconds since the Unix epoch.
+    pub mtime: Option<i64>,
+    pub hidden: bool,
+    pub read_only: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexRootStatus {
+    pub root: String,
+    pub last_scan_started: Option<i64>,
+    pub last_scan_completed: Option<i64>,
+    pub entry_count: u64,
+}
+
+impl IndexRootStatus {
+    /// A root is usable for searches once at least one scan has completed.
+    pub fn is_ready(&self) -> bool {
+        self.last_scan_completed.is_some()
+    }
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+fn ensure_index_schema(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS index_roots (
+            root TEXT PRIMARY KEY,
+            scan_id INTEGER NOT NULL DEFAULT 0,
+            last_scan_started INTEGER,
+            last_scan_completed INTEGER,
+            entry_count INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE TABLE IF NOT EXISTS index_entries (
+            path TEXT PRIMARY KEY,
+            root TEXT NOT NULL,
+            name TEXT NOT NULL,
+            name_lc TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            size INTEGER,
+            mtime INTEGER,
+            hidden INTEGER NOT NULL,
+            read_only INTEGER NOT NULL,
+            scan_id INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS index_entries_root ON index_entries (root, scan_id);",
+    )
+    .map_err(map_db_sqlite(DbErrorCode::SchemaInitFailed, || {
+        "Failed to init index schema".to_string()
+    }))?;
+    Ok(())
+}
+
+pub fn open_index() -> DbResult<Connection> {
+    open_index_at(&data_dir()?.join("browsey-index.db"))
+}
+
+pub fn open_index_at(path: &Path) -> DbResult<Connection> {
+    let conn = Connection::open(path).map_err(map_db_sqlite(DbErrorCode::OpenFailed, || {
+        "Failed to open index db".to_string()
+    }))?;
+    // WAL lets searches keep reading while a scan commits its batches.
+    conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))
+        .map_err(map_db_sqlite(DbErrorCode::OpenFailed, || {
+            "Failed to enable WAL for index db".to_string()
+        }))?;
+    conn.busy_timeout(INDEX_BUSY_TIMEOUT)
+        .map_err(map_db_sqlite(DbErrorCode::OpenFailed, || {
+            "Failed to set index db busy timeout".to_string()
+        }))?;
+    ensure_index_schema(&conn)?;
+    Ok(conn)
+}
+
+/// Starts a new scan of `root` and returns its scan id. Rows written by earlier scans stay
+/// searchable until [`finish_scan`] replaces them.
+pub fn begin_scan(conn: &Connection, root: &str) -> DbResult<i64> {
+    conn.execute(
+        "INSERT INTO index_roots (root, scan_id, last_scan_started) VALUES (?1, 1, ?2)
+         ON CONFLICT(root) DO UPDATE SET scan_id = scan_id + 1, last_scan_started = ?2",
+        params![root, now_secs()],
+    )
+    .map_err(map_db_sqlite(DbErrorCode::WriteFailed, || {
+        "Failed to start index scan".to_string()
+    }))?;
+    conn.query_row(
+        "SELECT scan_id FROM index_roots WHERE root = ?1",
+        params![root],
+        |row| row.get(0),
+    )
+    .map_err(map_db_sqlite(DbErrorCode::ReadFailed, || {
+        "Failed to read index scan id".to_string()
+    }))
+}
+
+pub fn upsert_entries(
+    conn: &mut Connection,
+    root: &str,
+    scan_id: i64,
+    entries: &[IndexedEntry],
+) -> DbResult<()> {
+    let tx = conn
+        .transaction()
+        .map_err(map_db_sqlite(DbErrorCode::TransactionFailed, || {
+            "Failed to start transaction".to_string()
+        }))?;
+    {
+        let mut stmt = tx
+            .prepare_cached(
+                "INSERT OR REPLACE INTO index_entries
+                 (path, root, name, name_lc, kind, size, mtime, hidden, read_only, scan_id)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            )
+            .map_err(map_db_sqlite(DbErrorCode::WriteFailed, || {
+                "Failed to prepare index insert".to_string()
+            }))?;
+        for entry in entries {
+            stmt.execute(params![
+                entry.path.as_str(),
+                root,
+                entry.name.as_str(),
+                entry.name.to_lowercase(),
+                entry.kind.as_str(),
+                entry.size.map(|size| size as i64),
+                entry.mtime,
+                entry.hidden,
+                entry.read_only,
+                scan_id,
+            ])
+            .map_err(map_db_sqlite(DbErrorCode::WriteFailed, || {
+                "Failed to store index entry".to_string()
+            }))?;
+        }
+    }
+    tx.commit()
+        .map_err(map_db_sqlite(DbErrorCode::TransactionFailed, || {
+            "Failed to commit index entries".to_string()
+        }))
+}
+
+/// Drops rows that the scan `scan_id` did not see and marks the root as complete.
+pub fn finish_scan(conn: &mut Connection, root: &str, scan_id: i64) -> DbResult<u64> {
+    let tx = conn
+        .transaction()
+        .map_err(map_db_sqlite(DbErrorCode::TransactionFailed, || {
+            "Failed to start transaction".to_string()
+        }))?;
+    tx.execute(
+        "DELETE FROM index_entries WHERE root = ?1 AND scan_id < ?2",
+        params![root, scan_id],
+    )
+    .map_err(map_db_sqlite(DbErrorCode::WriteFailed, || {
+        "Failed to prune index entries".to_string()
+    }))?;
+    let count: i64 = tx
+        .query_row(
+            "SELECT COUNT(*) FROM index_entries WHERE root = ?1",
+            params![root],
+            |row| row.get(0),
+        )
+        .map_err(map_db_sqlite(DbErrorCode::ReadFailed, || {
+            "Failed to count index entries".to_string()
+        }))?;
+    tx.execute(
+        "UPDATE index_roots SET last_scan_completed = ?2, entry_count = ?3 WHERE root = ?1",
+        params![root, now_secs(), count],
+    )
+    .map_err(map_db_sqlite(DbErrorCode::WriteFailed, || {
+        "Failed to update index root".to_string()
+    }))?;
+    tx.commit()
+        .map_err(map_db_sqlite(DbErrorCode::TransactionFailed, || {
+            "Failed to commit index scan".to_string()
+        }))?;
+    Ok(count as u64)
+}
+
+fn root_status_from_row(row: &Row) -> rusqlite::Result<IndexRootStatus> {
+    Ok(IndexRootStatus {
+        root: row.get(0)?,
+        last_scan_started: row.get(1)?,
+        last_scan_completed: row.get(2)?,
+        entry_count: row.get::<_, i64>(3)?.max(0) as u64,
+    })
+}
+
+pub fn root_statuses(conn: &Connection) -> DbResult<Vec<IndexRootStatus>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT root, last_scan_started, last_scan_completed, entry_count
+             FROM index_roots ORDER BY root",
+        )
+        .map_err(map_db_sqlite(DbErrorCode::ReadFailed, || {
+            "Failed to query index roots".to_string()
+        }))?;
+    let rows = stmt
+        .query_map([], root_status_from_row)
+        .map_err(map_db_sqlite(DbErrorCode::ReadFailed, || {
+            "Failed to read index roots".to_string()
+        }))?;
+    Ok(rows.flatten().collect())
+}
+
+pub fn root_status(conn: &Connection, root: &str) -> DbResult<Option<IndexRootStatus>> {
+    conn.query_row(
+        "SELECT root, last_scan_started, last_scan_completed, entry_count
+         FROM index_roots WHERE root = ?1",
+        params![root],
+        root_status_from_row,
+    )
+    .optional()
+    .map_err(map_db_sqlite(DbErrorCode::ReadFailed, || {
+        "Failed to read index root".to_string()
+    }))
+}
+
+/// Removes every indexed root that is not listed in `roots`, along with its entries.
+pub fn retain_roots(conn: &mut Connection, roots: &[String]) -> DbResult<usize> {
+    let stale: Vec<String> = root_statuses(conn)?
+        .into_iter()
+        .map(|status| status.root)
+        .filter(|root| !roots.contains(root))
+        .collect();
+    if stale.is_empty() {
+        return Ok(0);
+    }
+    let tx = conn
+        .transaction()
+        .map_err(map_db_sqlite(DbErrorCode::TransactionFailed, || {
+            "Failed to start transaction".to_string()
+        }))?;
+    for root in &stale {
+        tx.execute("DELETE FROM index_entries WHERE root = ?1", params![root])
+            .map_err(map_db_sqlite(DbErrorCode::WriteFailed, || {
+                "Failed to delete index entries".to_string()
+            }))?;
+        tx.execute("DELETE FROM index_roots WHERE root = ?1", params![root])
+            .map_err(map_db_sqlite(DbErrorCode::WriteFailed, || {
+                "Failed to delete index root".to_string()
+            }))?;
+    }
+    tx.commit()
+        .map_err(map_db_sqlite(DbErrorCode::TransactionFailed, || {
+            "Failed to commit index root removal".to_string()
+        }))?;
+    Ok(stale.len())
+}
+
+/// Half-open key range covering every path strictly below `dir`.
+fn descendant_range(dir: &str) -> (String, String) {
+    let mut lower = dir.to_string();
+    if !lower.ends_with(MAIN_SEPARATOR) {
+        lower.push(MAIN_SEPARATOR);
+    }
+    let mut upper = lower[..lower.len() - MAIN_SEPARATOR.len_utf8()].to_string();
+    upper.push(char::from_u32(MAIN_SEPARATOR as u32 + 1).unwrap_or(char::MAX));
+    (lower, upper)
+}
+
+/// Streams indexed entries below `dir` to `visit`, optionally pre-filtered by a lowercase
+/// name substring. Iteration stops early when `visit` returns false.
+pub fn for_each_entry_under(
+    conn: &Connection,
+    dir: &str,
+    name_contains_lc: Option<&str>,
+    mut visit: impl FnMut(IndexedEntry) -> bool,
+) -> DbResult<()> {
+    let (lower, upper) = descendant_range(dir);
+    let mut stmt = conn
+        .prepare(
+            "SELECT path, name, kind, size, mtime, hidden, read_only FROM index_entries
+             WHERE path >= ?1 AND path < ?2 AND (?3 IS NULL OR instr(name_lc, ?3) > 0)",
+        )
+        .map_err(map_db_sqlite(DbErrorCode::ReadFailed, || {
+            "Failed to prepare index query".to_string()
+        }))?;
+    let rows = stmt
+        .query_map(params![lower, upper, name_contains_lc], |row: &Row| {
+            Ok(IndexedEntry {
+                path: row.get(0)?,
+                name: row.get(1)?,
+                kind: row.get(2)?,
+                size: row.get::<_, Option<i64>>(3)?.map(|size| size.max(0) as u64),
+                mtime: row.get(4)?,
+                hidden: row.get(5)?,
+                read_only: row.get(6)?,
+            })
+        })
+        .map_err(map_db_sqlite(DbErrorCode::ReadFailed, || {
+            "Failed to read index entries".to_string()
+        }))?;
+    for entry in rows.flatten() {
+        if !visit(entry) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Streams the indexed directories below `dir` with their indexed mtimes. Iteration stops
+/// early when `visit` returns false.
+pub fn for_each_dir_under(
+    conn: &Connection,
+    dir: &str,
+    mut visit: impl FnMut(String, Option<i64>) -> bool,
+) -> DbResult<()> {
+    let (lower, upper) = descendant_range(dir);
+    let mut stmt = conn
+        .prepare(
+            "SELECT path, mtime FROM index_entries
+             WHERE path >= ?1 AND path < ?2 AND kind = 'dir'",
+        )
+        .map_err(map_db_sqlite(DbErrorCode::ReadFailed, || {
+            "Failed to prepare index query".to_string()
+        }))?;
+    let rows = stmt
+        .query_map(params![lower, upper], |row: &Row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Option<i64>>(1)?))
+        })
+        .map_err(map_db_sqlite(DbErrorCode::ReadFailed, || {
+            "Failed to read index entries".to_string()
+        }))?;
+    for (path, mtime) in rows.flatten() {
+        if !visit(path, mtime) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+pub fn contains_path(conn: &Connection, path: &str) -> DbResult<bool> {
+    conn.prepare_cached("SELECT 1 FROM index_entries WHERE path = ?1")
+        .and_then(|mut stmt| stmt.exists(params![path]))
+        .map_err(map_db_sqlite(DbErrorCode::ReadFailed, || {
+            "Failed to read index entry".to_string()
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory_index() -> Connection {
+        let conn = Connection::open_in_memory().expect("open in-memory index");
+        ensure_index_schema(&conn).expect("schema");
+        conn
+    }
+
+    fn entry(path: &str, kind: &str) -> IndexedEntry {
+        IndexedEntry {
+            path: path.to_string(),
+            name: path
+                .rsplit(MAIN_SEPARATOR)
+                .next()
+                .unwrap_or(path)
+                .to_string(),
+            kind: kind.to_string(),
+            size: Some(1),
+            mtime: Some(0),
+            hidden: false,
+            read_only: false,
+        }
+    }
+
+    fn paths_under(conn: &Connection, dir: &str, needle: Option<&str>) -> Vec<String> {
+        let mut out = Vec::new();
+        for_each_entry_under(conn, dir, needle, |entry| {
+            out.push(entry.path);
+            true
+        })
+        .expect("query");
+        out.sort();
+        out
+    }
+
+    fn p(parts: &[&str]) -> String {
+        parts.join(std::path::MAIN_SEPARATOR_STR)
+    }
+
+    #[test]
+    fn rescan_prunes_entries_missing_from_latest_scan() {
+        let mut conn = memory_index();
+        let root = p(&["", "data"]);
+        let first = begin_scan(&conn, &root).unwrap();
+        upsert_entries(
+            &mut conn,
+            &root,
+            first,
+            &[
+                entry(&p(&["", "data", "a.txt"]), "file"),
+                entry(&p(&["", "data", "b.txt"]), "file"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(finish_scan(&mut conn, &root, first).unwrap(), 2);
+
+        let second = begin_scan(&conn, &root).unwrap();
+        assert!(second > first);
+        upsert_entries(
+            &mut conn,
+            &root,
+            second,
+            &[entry(&p(&["", "data", "b.txt"]), "file")],
+        )
+        .unwrap();
+        assert_eq!(finish_scan(&mut conn, &root, second).unwrap(), 1);
+
+        let status = root_status(&conn, &root).unwrap().expect("status");
+        assert!(status.is_ready());
+        assert_eq!(status.entry_count, 1);
+        assert_eq!(
+            paths_under(&conn, &root, None),
+            vec![p(&["", "data", "b.txt"])]
+        );
+    }
+
+    #[test]
+    fn descendant_query_excludes_sibling_prefixes() {
+        let mut conn = memory_index();
+        let root = p(&["", "data"]);
+        let scan = begin_scan(&conn, &root).unwrap();
+        upsert_entries(
+            &mut conn,
+            &root,
+            scan,
+            &[
+                entry(&p(&["", "data", "docs"]), "dir"),
+                entry(&p(&["", "data", "docs", "Report.pdf"]), "file"),
+                entry(&p(&["", "data", "docs-old", "report.pdf"]), "file"),
+            ],
+        )
+        .unwrap();
+        finish_scan(&mut conn, &root, scan).unwrap();
+
+        let docs = p(&["", "data", "docs"]);
+        assert_eq!(
+            paths_under(&conn, &docs, None),
+            vec![p(&["", "data", "docs", "Report.pdf"])]
+        );
+        assert_eq!(paths_under(&conn, &root, Some("report")).len(), 2);
+        assert!(paths_under(&conn, &root, Some("missing")).is_empty());
+    }
+
+    #[test]
+    fn dir_query_and_lookup_see_indexed_paths_only() {
+        let mut conn = memory_index();
+        let root = p(&["", "data"]);
+        let scan = begin_scan(&conn, &root).unwrap();
+        upsert_entries(
+            &mut conn,
+            &root,
+            scan,
+            &[
+                entry(&p(&["", "data", "docs"]), "dir"),
+                entry(&p(&["", "data", "docs", "a.txt"]), "file"),
+                entry(&p(&["", "data", "docs", "old"]), "dir"),
+            ],
+        )
+        .unwrap();
+        finish_scan(&mut conn, &root, scan).unwrap();
+
+        let mut dirs = Vec::new();
+        for_each_dir_under(&conn, &root, |path, mtime| {
+            dirs.push((path, mtime));
+            true
+        })
+        .unwrap();
+        dirs.sort();
+        assert_eq!(
+            dirs,
+            vec![
+                (p(&["", "data", "docs"]), Some(0)),
+                (p(&["", "data", "docs", "old"]), Some(0)),
+            ]
+        );
+        assert!(contains_path(&conn, &p(&["", "data", "docs", "a.txt"])).unwrap());
+        assert!(!contains_path(&conn, &p(&["", "data", "docs", "b.txt"])).unwrap());
+    }
+
+    #[test]
+    fn retain_roots_drops_unlisted_roots() {
+        let mut conn = memory_index();
+        for root in ["/a", "/b"] {
+            let scan = begin_scan(&conn, root).unwrap();
+            upsert_entries(
+                &mut conn,
+                root,
+                scan,
+                &[entry(&format!("{root}/x"), "file")],
+            )
+            .unwrap();
+            finish_scan(&mut conn, root, scan).unwrap();
+        }
+        assert_eq!(retain_roots(&mut conn, &["/a".to_string()]).unwrap(), 1);
+        let roots: Vec<String> = root_statuses(&conn)
+            .unwrap()
+            .into_iter()
+            .map(|status| status.root)
+            .collect();
+        assert_eq!(roots, vec!["/a".to_string()]);
+        assert!(paths_under(&conn, "/b", None).is_empty());
+    }
+
+    #[test]
+    fn index_file_opens_in_wal_mode() {
+        let dir = crate::fs_utils::unique_temp_dir("index-wal");
+        let conn = open_index_at(&dir.join("index.db")).expect("open index");
+        let mode: String = conn
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .expect("journal mode");
+        assert_eq!(mode, "wal");
+        drop(conn);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -6,6 +6,7 @@ use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 mod error;
+pub mod index;
 
 pub use error::{DbError, DbErrorCode, DbResult};
 
@@ -25,7 +26,7 @@ fn map_db_sqlite(
     move |error| DbError::from_sqlite_error(fallback, context(), error)
 }
 
-fn db_path() -> DbResult<PathBuf> {
+fn data_dir() -> DbResult<PathBuf> {
     let base = dirs_next::data_dir()
         .ok_or_else(|| {
             DbError::new(
@@ -37,7 +38,11 @@ fn db_path() -> DbResult<PathBuf> {
     std::fs::create_dir_all(&base).map_err(map_db_io(DbErrorCode::DataDirUnavailable, || {
         "Failed to create data dir".to_string()
     }))?;
-    Ok(base.join("browsey.db"))
+    Ok(base)
+}
+
+fn db_path() -> DbResult<PathBuf> {
+    Ok(data_dir()?.join("browsey.db"))
 }
 
 fn ensure_schema(conn: &Connection) -> DbResult<()> {
@@ -58,6 +63,14 @@ fn ensure_schema(conn: &Connection) -> DbResult<()> {
         CREATE TABLE IF NOT EXISTS settings (
             key TEXT PRIMARY KEY,
             value TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS saved_searches (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            query TEXT NOT NULL,
+            root TEXT NOT NULL,
+            options TEXT NOT NULL DEFAULT '{}',
+            created_at INTEGER NOT NULL
         );",
     )
     .map_err(map_db_sqlite(DbErrorCode::SchemaInitFailed, || {
@@ -304,6 +317,102 @@ pub fn delete_bookmark(conn: &Connection, path: &str) -> DbResult<()> {
     Ok(())
 }
 
+/// A stored search. `options` is an opaque JSON object owned by the saved searches commands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedSearchRow {
+    pub id: i64,
+    pub name: String,
+    pub query: String,
+    pub root: String,
+    pub options: String,
+}
+
+fn saved_search_from_row(row: &Row) -> rusqlite::Result<SavedSearchRow> {
+    Ok(SavedSearchRow {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        query: row.get(2)?,
+        root: row.get(3)?,
+        options: row.get(4)?,
+    })
+}
+
+pub fn list_saved_searches(conn: &Connection) -> DbResult<Vec<SavedSearchRow>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name, query, root, options FROM saved_searches
+             ORDER BY name COLLATE NOCASE ASC, id ASC",
+        )
+        .map_err(map_db_sqlite(DbErrorCode::ReadFailed, || {
+            "Failed to prepare saved searches query".to_string()
+        }))?;
+    let rows = stmt
+        .query_map([], saved_search_from_row)
+        .map_err(map_db_sqlite(DbErrorCode::ReadFailed, || {
+            "Failed to read saved searches".to_string()
+        }))?;
+    Ok(rows.flatten().collect())
+}
+
+pub fn get_saved_search(conn: &Connection, id: i64) -> DbResult<Option<SavedSearchRow>> {
+    conn.query_row(
+        "SELECT id, name, query, root, options FROM saved_searches WHERE id = ?1",
+        params![id],
+        saved_search_from_row,
+    )
+    .optional()
+    .map_err(map_db_sqlite(DbErrorCode::ReadFailed, || {
+        "Failed to read saved search".to_string()
+    }))
+}
+
+/// Stores a new saved search and returns its id.
+pub fn insert_saved_search(
+    conn: &Connection,
+    name: &str,
+    query: &str,
+    root: &str,
+    options: &str,
+) -> DbResult<i64> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+    conn.execute(
+        "INSERT INTO saved_searches (name, query, root, options, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![name, query, root, options, now],
+    )
+    .map_err(map_db_sqlite(DbErrorCode::WriteFailed, || {
+        "Failed to store saved search".to_string()
+    }))?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Replaces a saved search in place. Returns false when `id` does not exist.
+pub fn update_saved_search(conn: &Connection, row: &SavedSearchRow) -> DbResult<bool> {
+    let changed = conn
+        .execute(
+            "UPDATE saved_searches SET name = ?2, query = ?3, root = ?4, options = ?5
+             WHERE id = ?1",
+            params![row.id, row.name, row.query, row.root, row.options],
+        )
+        .map_err(map_db_sqlite(DbErrorCode::WriteFailed, || {
+            "Failed to update saved search".to_string()
+        }))?;
+    Ok(changed > 0)
+}
+
+/// Returns false when `id` does not exist.
+pub fn delete_saved_search(conn: &Connection, id: i64) -> DbResult<bool> {
+    let changed = conn
+        .execute("DELETE FROM saved_searches WHERE id = ?1", params![id])
+        .map_err(map_db_sqlite(DbErrorCode::WriteFailed, || {
+            "Failed to delete saved search".to_string()
+        }))?;
+    Ok(changed > 0)
+}
+
 pub fn delete_all_starred(conn: &Connection) -> DbResult<usize> {
     conn.execute("DELETE FROM starred", [])
         .map_err(map_db_sqlite(DbErrorCode::WriteFailed, || {
@@ -367,6 +476,55 @@ pub fn load_column_widths(conn: &Connection) -> DbResult<Option<Vec<f64>>> {
     }
 }
 
+fn set_setting_string_list(
+    conn: &Connection,
+    key: &str,
+    values: &[String],
+    label: &str,
+) -> DbResult<()> {
+    let payload = serde_json::to_string(values).map_err(|e| {
+        DbError::new(
+            DbErrorCode::SerializeFailed,
+            format!("Failed to serialize {label}: {e}"),
+        )
+    })?;
+    set_setting_string(conn, key, &payload)
+}
+
+fn get_setting_string_list(
+    conn: &Connection,
+    key: &str,
+    label: &str,
+) -> DbResult<Option<Vec<String>>> {
+    let Some(json) = get_setting_string(conn, key)? else {
+        return Ok(None);
+    };
+    serde_json::from_str(&json).map(Some).map_err(|e| {
+        DbError::new(
+            DbErrorCode::ParseFailed,
+            format!("Failed to parse {label}: {e}"),
+        )
+    })
+}
+
+pub fn save_search_index_roots(conn: &Connection, roots: &[String]) -> DbResult<()> {
+    set_setting_string_list(conn, "searchIndexRoots", roots, "search index roots")
+}
+
+pub fn load_search_index_roots(conn: &Connection) -> DbResult<Vec<String>> {
+    get_setting_string_list(conn, "searchIndexRoots", "search index roots")
+        .map(Option::unwrap_or_default)
+}
+
+pub fn save_exclude_patterns(conn: &Connection, patterns: &[String]) -> DbResult<()> {
+    set_setting_string_list(conn, "excludePatterns", patterns, "exclude patterns")
+}
+
+/// Returns `None` until the user has saved an exclude list.
+pub fn load_exclude_patterns(conn: &Connection) -> DbResult<Option<Vec<String>>> {
+    get_setting_string_list(conn, "excludePatterns", "exclude patterns")
+}
+
 pub fn set_setting_bool(conn: &Connection, key: &str, value: bool) -> DbResult<()> {
     conn.execute(
         "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
@@ -425,3 +583,44 @@ pub fn get_setting_string(conn: &Connection, key: &str) -> DbResult<Option<Strin
         }))?;
     Ok(val)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory_db() -> Connection {
+        let conn = Connection::open_in_memory().expect("open in-memory db");
+        ensure_schema(&conn).expect("schema");
+        conn
+    }
+
+    #[test]
+    fn saved_search_crud_round_trips() {
+        let conn = memory_db();
+        let id = insert_saved_search(&conn, "Logs", "ext:log", "/var", "{}").expect("insert");
+        let mut row = get_saved_search(&conn, id).expect("get").expect("row");
+        assert_eq!(row.query, "ext:log");
+
+        row.name = "Big logs".to_string();
+        row.query = "ext:log size:>1mb".to_string();
+        assert!(update_saved_search(&conn, &row).expect("update"));
+        assert_eq!(list_saved_searches(&conn).expect("list"), vec![row]);
+
+        assert!(delete_saved_search(&conn, id).expect("delete"));
+        assert!(!delete_saved_search(&conn, id).expect("delete again"));
+        assert!(get_saved_search(&conn, id).expect("get").is_none());
+    }
+
+    #[test]
+    fn update_saved_search_reports_missing_id() {
+        let conn = memory_db();
+        let row = SavedSearchRow {
+            id: 42,
+            name: "Missing".to_string(),
+            query: "a".to_string(),
+            root: "/".to_string(),
+            options: "{}".to_string(),
+        };
+        assert!(!update_saved_search(&conn, &row).expect("update"));
+    }
+}
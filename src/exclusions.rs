@@ -0,0 +1,372 @@
+//! Exclusion rules shared by the recursive walkers (search, duplicate scans, folder sizes).
+//!
+//! Rules come from `.gitignore` and `.ignore` files found in the walked tree (and in the
+//! directories above it up to the enclosing repository) plus the global exclude list stored
+//! in settings. Patterns follow gitignore syntax; the deepest matching file wins, the last
+//! matching line within a file wins, and the global list has the lowest precedence.
+
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+const IGNORE_FILE_NAMES: [&str; 2] = [".gitignore", ".ignore"];
+
+/// Global patterns used until the user saves their own list.
+pub const DEFAULT_EXCLUDE_PATTERNS: [&str; 3] = [".git/", ".hg/", ".svn/"];
+
+struct Rule {
+    regex: Regex,
+    negated: bool,
+    dir_only: bool,
+    /// Anchored rules match the path relative to the rule base; others match the file name.
+    anchored: bool,
+}
+
+struct RuleSet {
+    base: PathBuf,
+    rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    fn parse<'a>(base: PathBuf, lines: impl IntoIterator<Item = &'a str>) -> Self {
+        let rules = lines.into_iter().filter_map(parse_rule).collect();
+        Self { base, rules }
+    }
+
+    /// Returns `Some(true)` when the last matching rule excludes `path`, `Some(false)` when it
+    /// re-includes it and `None` when no rule matches.
+    fn decide(&self, path: &Path, is_dir: bool) -> Option<bool> {
+        let relative = path.strip_prefix(&self.base).ok()?;
+        let mut parts = Vec::new();
+        for component in relative.components() {
+            match component {
+                Component::Normal(part) => parts.push(part.to_string_lossy()),
+                _ => return None,
+            }
+        }
+        let name = parts.last()?.clone();
+        let joined = parts.join("/");
+        self.rules
+            .iter()
+            .rev()
+            .filter(|rule| is_dir || !rule.dir_only)
+            .find(|rule| {
+                let subject: &str = if rule.anchored { &joined } else { &name };
+                rule.regex.is_match(subject)
+            })
+            .map(|rule| !rule.negated)
+    }
+}
+
+fn parse_rule(line: &str) -> Option<Rule> {
+    let line = line.trim_end_matches('\r');
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let mut pattern = trim_unescaped_trailing_spaces(line);
+    let negated = pattern.starts_with('!');
+    if negated {
+        pattern = &pattern[1..];
+    }
+    let (pattern, dir_only) = match pattern.strip_suffix('/') {
+        Some(rest) => (rest, true),
+        None => (pattern, false),
+    };
+    if pattern.is_empty() {
+        return None;
+    }
+    let anchored = pattern.contains('/');
+    let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+    let regex = Regex::new(&glob_to_regex(pattern)).ok()?;
+    Some(Rule {
+        regex,
+        negated,
+        dir_only,
+        anchored,
+    })
+}
+
+fn trim_unescaped_trailing_spaces(line: &str) -> &str {
+    let trimmed = line.trim_end_matches(' ');
+    if trimmed.ends_with('\\') && trimmed.len() < line.len() {
+        &line[..trimmed.len() + 1]
+    } else {
+        trimmed
+    }
+}
+
+/// Translates one gitignore glob into an anchored regex over `/`-separated paths.
+fn glob_to_regex(glob: &str) -> String {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut out = String::from("^");
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                let at_segment_start = i == 0 || chars[i - 1] == '/';
+                match chars.get(i + 2) {
+                    Some('/') if at_segment_start => {
+                        out.push_str("(?:.*/)?");
+                        i += 3;
+                    }
+                    None if at_segment_start => {
+                        out.push_str(".*");
+                        i += 2;
+                    }
+                    _ => {
+                        out.push_str("[^/]*");
+                        i += 2;
+                    }
+                }
+                continue;
+            }
+            '*' => out.push_str("[^/]*"),
+            '?' => out.push_str("[^/]"),
+            '[' => match class_end(&chars, i) {
+                Some(end) => {
+                    out.push('[');
+                    let mut j = i + 1;
+                    if matches!(chars[j], '!' | '^') {
+                        out.push('^');
+                        j += 1;
+                    }
+                    for &c in &chars[j..end] {
+                        if c == '-' || c.is_alphanumeric() {
+                            out.push(c);
+                        } else {
+                            out.push('\\');
+                            out.push(c);
+                        }
+                    }
+                    out.push(']');
+                    i = end;
+                }
+                None => out.push_str("\\["),
+            },
+            '\\' if i + 1 < chars.len() => {
+                i += 1;
+                out.push_str(&regex::escape(&chars[i].to_string()));
+            }
+            c => out.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+    out.push('$');
+    out
+}
+
+/// Index of the `]` closing the class that opens at `start`, if any.
+fn class_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut j = start + 1;
+    if matches!(chars.get(j), Some('!' | '^')) {
+        j += 1;
+    }
+    // A leading `]` is a literal member of the class.
+    if chars.get(j) == Some(&']') {
+        j += 1;
+    }
+    (j..chars.len()).find(|&k| chars[k] == ']')
+}
+
+/// Exclusion rules for one walk rooted at `root`. Ignore files are read lazily per directory
+/// and cached, so one instance can be shared across walking threads.
+pub struct ExclusionRules {
+    root: PathBuf,
+    /// Highest directory whose ignore files apply: the enclosing repository or `root`.
+    top: PathBuf,
+    global: RuleSet,
+    dir_rules: Mutex<HashMap<PathBuf, Option<Arc<RuleSet>>>>,
+}
+
+impl ExclusionRules {
+    pub fn new(root: &Path, global_patterns: &[String]) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            top: repository_top(root).unwrap_or_else(|| root.to_path_buf()),
+            global: RuleSet::parse(
+                root.to_path_buf(),
+                global_patterns.iter().map(String::as_str),
+            ),
+            dir_rules: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Builds the rules for `root` with the global list from settings.
+    pub fn load(root: &Path) -> Self {
+        Self::new(root, &load_global_patterns())
+    }
+
+    /// Whether `path` (inside the walk root) is excluded. Walkers skip excluded directories
+    /// entirely, so parents are not re-checked here.
+    pub fn is_excluded(&self, path: &Path, is_dir: bool) -> bool {
+        if path == self.root {
+            return false;
+        }
+        let mut dir = path.parent();
+        while let Some(current) = dir {
+            if let Some(rules) = self.rules_for_dir(current) {
+                if let Some(excluded) = rules.decide(path, is_dir) {
+                    return excluded;
+                }
+            }
+            if current == self.top {
+                break;
+            }
+            dir = current.parent();
+        }
+        self.global.decide(path, is_dir).unwrap_or(false)
+    }
+
+    /// Like [`Self::is_excluded`], but also checks every directory between the root and
+    /// `path`, for callers that do not walk the tree top-down.
+    pub fn is_excluded_with_ancestors(&self, path: &Path, is_dir: bool) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        let mut current = self.root.clone();
+        let mut components = relative.components().peekable();
+        while let Some(component) = components.next() {
+            current.push(component);
+            let last = components.peek().is_none();
+            if self.is_excluded(&current, !last || is_dir) {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn rules_for_dir(&self, dir: &Path) -> Option<Arc<RuleSet>> {
+        if let Ok(cache) = self.dir_rules.lock() {
+            if let Some(cached) = cache.get(dir) {
+                return cached.clone();
+            }
+        }
+        let loaded = read_ignore_files(dir).map(Arc::new);
+        if let Ok(mut cache) = self.dir_rules.lock() {
+            cache.insert(dir.to_path_buf(), loaded.clone());
+        }
+        loaded
+    }
+}
+
+fn read_ignore_files(dir: &Path) -> Option<RuleSet> {
+    let mut text = String::new();
+    for name in IGNORE_FILE_NAMES {
+        if let Ok(content) = std::fs::read_to_string(dir.join(name)) {
+            text.push_str(&content);
+            text.push('\n');
+        }
+    }
+    if text.is_empty() {
+        return None;
+    }
+    let set = RuleSet::parse(dir.to_path_buf(), text.lines());
+    (!set.rules.is_empty()).then_some(set)
+}
+
+fn repository_top(root: &Path) -> Option<PathBuf> {
+    root.ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Reads the global exclude list from settings, falling back to the defaults.
+pub fn load_global_patterns() -> Vec<String> {
+    let stored = crate::db::open().and_then(|conn| crate::db::load_exclude_patterns(&conn));
+    match stored {
+        Ok(Some(patterns)) => patterns,
+        Ok(None) => default_patterns(),
+        Err(error) => {
+            warn!(%error, "failed to load exclude patterns; using defaults");
+            default_patterns()
+        }
+    }
+}
+
+pub fn default_patterns() -> Vec<String> {
+    DEFAULT_EXCLUDE_PATTERNS
+        .iter()
+        .map(|pattern| pattern.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{glob_to_regex, ExclusionRules, RuleSet};
+    use crate::fs_utils::unique_temp_dir;
+    use regex::Regex;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn glob_matches(glob: &str, subject: &str) -> bool {
+        Regex::new(&glob_to_regex(glob))
+            .expect("regex")
+            .is_match(subject)
+    }
+
+    #[test]
+    fn glob_translation_follows_gitignore_wildcards() {
+        assert!(glob_matches("*.log", "debug.log"));
+        assert!(!glob_matches("*.log", "logs/debug.log"));
+        assert!(glob_matches("**/build", "a/b/build"));
+        assert!(glob_matches("**/build", "build"));
+        assert!(glob_matches("docs/**", "docs/a/b.md"));
+        assert!(glob_matches("a/**/z", "a/z"));
+        assert!(glob_matches("a/**/z", "a/b/c/z"));
+        assert!(glob_matches("file?.[ch]", "file1.c"));
+        assert!(!glob_matches("file?.[!ch]", "file1.c"));
+        assert!(glob_matches("\\#notes", "#notes"));
+        assert!(glob_matches("a+b(1).txt", "a+b(1).txt"));
+    }
+
+    #[test]
+    fn last_matching_rule_wins_and_dir_only_rules_skip_files() {
+        let base = PathBuf::from("/repo");
+        let set = RuleSet::parse(
+            base.clone(),
+            ["# comment", "*.log", "!keep.log", "target/", "/root-only"],
+        );
+        assert_eq!(set.decide(&base.join("a.log"), false), Some(true));
+        assert_eq!(set.decide(&base.join("keep.log"), false), Some(false));
+        assert_eq!(set.decide(&base.join("target"), true), Some(true));
+        assert_eq!(set.decide(&base.join("target"), false), None);
+        assert_eq!(set.decide(&base.join("root-only"), false), Some(true));
+        assert_eq!(set.decide(&base.join("sub").join("root-only"), false), None);
+    }
+
+    #[test]
+    fn nested_ignore_files_override_parents_and_global_list() {
+        let root = unique_temp_dir("nested");
+        fs::create_dir_all(root.join("app").join("target")).expect("create dirs");
+        fs::write(root.join(".gitignore"), "target/\n*.tmp\n").expect("write gitignore");
+        fs::write(root.join("app").join(".ignore"), "!keep.tmp\n").expect("write ignore");
+
+        let rules = ExclusionRules::new(&root, &["node_modules/".to_string()]);
+        let app = root.join("app");
+        assert!(rules.is_excluded(&app.join("target"), true));
+        assert!(rules.is_excluded(&app.join("scratch.tmp"), false));
+        assert!(!rules.is_excluded(&app.join("keep.tmp"), false));
+        assert!(rules.is_excluded(&app.join("node_modules"), true));
+        assert!(!rules.is_excluded(&app.join("src"), true));
+        assert!(!rules.is_excluded(&root, true));
+        assert!(rules
+            .is_excluded_with_ancestors(&app.join("target").join("debug").join("main.rs"), false));
+        assert!(!rules.is_excluded_with_ancestors(&app.join("src").join("main.rs"), false));
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn repository_ignore_files_apply_to_subdirectory_walks() {
+        let repo = unique_temp_dir("repo");
+        fs::create_dir_all(repo.join(".git")).expect("create .git");
+        fs::create_dir_all(repo.join("crates").join("core")).expect("create crates");
+        fs::write(repo.join(".gitignore"), "*.bak\n").expect("write gitignore");
+
+        let crates = repo.join("crates");
+        let rules = ExclusionRules::new(&crates, &[]);
+        assert!(rules.is_excluded(&crates.join("core").join("x.bak"), false));
+        let _ = fs::remove_dir_all(&repo);
+    }
+}
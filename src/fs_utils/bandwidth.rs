@@ -0,0 +1,186 @@
+//! Bandwidth limits for copies and transfers.
+//!
+//! A global limit applies to every copy; a job can get a stricter one of its own, keyed by its
+//! progress event. Limits are in bytes per second and looked up again while a copy runs, so a
+//! change takes effect on the next chunk.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+
+/// `0` means no global limit.
+static GLOBAL_LIMIT: AtomicU64 = AtomicU64::new(0);
+static JOB_LIMITS: Lazy<Mutex<HashMap<String, u64>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+const SLEEP_SLICE: Duration = Duration::from_millis(100);
+/// A copy that fell this far behind its limit, say while the source stalled, starts counting
+/// afresh instead of catching up in one burst.
+const MAX_CATCH_UP: Duration = Duration::from_secs(1);
+
+pub fn set_global_limit(limit: Option<u64>) {
+    GLOBAL_LIMIT.store(limit.unwrap_or(0), Ordering::Relaxed);
+}
+
+/// Sets or clears the limit of the job reporting on `event`.
+pub fn set_job_limit(event: &str, limit: Option<u64>) {
+    let mut limits = JOB_LIMITS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    match limit.filter(|limit| *limit > 0) {
+        Some(limit) => limits.insert(event.to_string(), limit),
+        None => limits.remove(event),
+    };
+}
+
+fn global_limit() -> Option<u64> {
+    Some(GLOBAL_LIMIT.load(Ordering::Relaxed)).filter(|limit| *limit > 0)
+}
+
+/// The limit for a copy reporting on `event`: the stricter of the global and the job limit.
+pub fn limit_for(event: Option<&str>) -> Option<u64> {
+    let job = event.and_then(|event| {
+        JOB_LIMITS
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(event)
+            .copied()
+    });
+    stricter(global_limit(), job)
+}
+
+/// The strictest limit of all running jobs, for a backend that can only hold one limit for
+/// every transfer it runs.
+pub fn shared_limit() -> Option<u64> {
+    let strictest_job = JOB_LIMITS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .values()
+        .copied()
+        .min();
+    stricter(global_limit(), strictest_job)
+}
+
+fn stricter(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// An rclone rate for `--bwlimit` or `core/bwlimit`.
+pub fn rclone_rate(limit: Option<u64>) -> String {
+    match limit {
+        Some(limit) => format!("{limit}B"),
+        None => "off".to_string(),
+    }
+}
+
+/// Paces one copy. It is fed the running byte count and sleeps whenever the copy gets ahead of
+/// its current limit.
+pub struct RateLimiter<'a> {
+    event: Option<&'a str>,
+    limit: Option<u64>,
+    since: Instant,
+    base: u64,
+}
+
+impl<'a> RateLimiter<'a> {
+    pub fn new(event: Option<&'a str>) -> Self {
+        Self {
+            event,
+            limit: limit_for(event),
+            since: Instant::now(),
+            base: 0,
+        }
+    }
+
+    /// How long the copy should wait before `done` bytes fit the limit.
+    pub fn pending(&mut self, done: u64) -> Duration {
+        self.delay(done, Instant::now())
+    }
+
+    /// Waits until `done` bytes fit the limit, in short slices so `cancelled` is honored.
+    pub fn throttle(&mut self, done: u64, cancelled: impl Fn() -> bool) {
+        let mut wait = self.pending(done);
+        while !wait.is_zero() && !cancelled() {
+            let slice = wait.min(SLEEP_SLICE);
+            std::thread::sleep(slice);
+            wait -= slice;
+        }
+    }
+
+    /// How long to wait before `done` bytes fit the limit. A changed limit applies from `now`
+    /// on, to the bytes copied after it changed.
+    fn delay(&mut self, done: u64, now: Instant) -> Duration {
+        let limit = limit_for(self.event);
+        if limit != self.limit {
+            self.restart(limit, done, now);
+        }
+        let Some(limit) = self.limit else {
+            return Duration::ZERO;
+        };
+        let sent = done.saturating_sub(self.base);
+        let due = Duration::from_secs_f64(sent as f64 / limit as f64);
+        let elapsed = now.saturating_duration_since(self.since);
+        if elapsed > due + MAX_CATCH_UP {
+            self.restart(Some(limit), done, now);
+            return Duration::ZERO;
+        }
+        due.saturating_sub(elapsed)
+    }
+
+    fn restart(&mut self, limit: Option<u64>, done: u64, now: Instant) {
+        self.limit = limit;
+        self.since = now;
+        self.base = done;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copies_wait_for_the_stricter_limit_and_pick_up_changes() {
+        let event = "bandwidth-test-job";
+        set_job_limit(event, Some(1000));
+        assert_eq!(limit_for(Some(event)), Some(1000));
+        assert_eq!(rclone_rate(shared_limit()).as_str(), "1000B");
+
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new(Some(event));
+        limiter.since = start;
+        assert_eq!(limiter.delay(500, start), Duration::from_millis(500));
+        assert_eq!(
+            limiter.delay(1000, start + Duration::from_millis(400)),
+            Duration::from_millis(600)
+        );
+
+        set_job_limit(event, Some(2000));
+        let later = start + Duration::from_secs(1);
+        assert_eq!(limiter.delay(1000, later), Duration::ZERO);
+        assert_eq!(limiter.delay(2000, later), Duration::from_millis(500));
+
+        set_job_limit(event, None);
+        assert_eq!(limit_for(Some(event)), None);
+        assert_eq!(limiter.delay(10_000, later), Duration::ZERO);
+        assert_eq!(limiter.limit, None);
+        assert_eq!(rclone_rate(None), "off");
+    }
+
+    #[test]
+    fn a_stalled_copy_does_not_burst_to_catch_up() {
+        let event = "bandwidth-test-stall";
+        set_job_limit(event, Some(1000));
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new(Some(event));
+        limiter.since = start;
+        let stalled = start + Duration::from_secs(10);
+        assert_eq!(limiter.delay(1000, stalled), Duration::ZERO);
+        assert_eq!(limiter.delay(2000, stalled), Duration::from_secs(1));
+        set_job_limit(event, None);
+    }
+}
@@ -0,0 +1,171 @@
+//! Conflict policies shared by local pastes and mixed local/cloud transfers, so both decide the
+//! same way whether an item that already exists at the target is kept, replaced or renamed.
+
+use std::collections::HashMap;
+use std::fs::Metadata;
+use std::path::Path;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// Write under the next free `name-N` name.
+    #[default]
+    Rename,
+    /// Replace the existing item; folders are merged.
+    Overwrite,
+    /// Keep the existing item and leave the source where it is.
+    Skip,
+    /// Replace the existing item only when the source was modified later.
+    OverwriteIfNewer,
+    /// Replace the existing file only when the source file is larger.
+    OverwriteIfLarger,
+}
+
+impl ConflictPolicy {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_lowercase().as_str() {
+            "rename" => Some(Self::Rename),
+            "overwrite" => Some(Self::Overwrite),
+            "skip" => Some(Self::Skip),
+            "overwrite_if_newer" => Some(Self::OverwriteIfNewer),
+            "overwrite_if_larger" => Some(Self::OverwriteIfLarger),
+            _ => None,
+        }
+    }
+
+    /// Whether a folder pasted onto an existing folder is merged into it, with the policy
+    /// applied again to every item inside.
+    pub fn merges_folders(self) -> bool {
+        matches!(
+            self,
+            Self::Overwrite | Self::OverwriteIfNewer | Self::OverwriteIfLarger
+        )
+    }
+
+    /// Whether `target` is replaced by `src`. `Rename` never replaces; callers pick a new
+    /// name instead. Sizes are only compared between two files.
+    pub fn replaces(self, src: &EntryFacts, target: &EntryFacts) -> bool {
+        match self {
+            Self::Rename | Self::Skip => false,
+            Self::Overwrite => true,
+            Self::OverwriteIfNewer => {
+                matches!((src.modified, target.modified), (Some(s), Some(t)) if s > t)
+            }
+            Self::OverwriteIfLarger => !src.is_dir && !target.is_dir && src.size > target.size,
+        }
+    }
+}
+
+/// What a conflict decision looks at on either side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntryFacts {
+    pub is_dir: bool,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+impl EntryFacts {
+    pub fn from_metadata(meta: &Metadata) -> Self {
+        Self {
+            is_dir: meta.is_dir(),
+            size: meta.len(),
+            modified: meta.modified().ok(),
+        }
+    }
+}
+
+/// One policy for a whole operation plus per-item decisions, keyed by the source path as the
+/// conflict preview reported it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConflictPolicies {
+    default: ConflictPolicy,
+    items: HashMap<String, ConflictPolicy>,
+}
+
+impl ConflictPolicies {
+    pub fn new(default: ConflictPolicy, items: HashMap<String, ConflictPolicy>) -> Self {
+        Self { default, items }
+    }
+
+    pub fn for_source(&self, src: &str) -> ConflictPolicy {
+        self.items.get(src).copied().unwrap_or(self.default)
+    }
+
+    /// The decision for `path` or, for items inside a merged folder, for its nearest ancestor
+    /// that has one.
+    pub fn for_path(&self, path: &Path) -> ConflictPolicy {
+        if self.items.is_empty() {
+            return self.default;
+        }
+        path.ancestors()
+            .find_map(|p| self.items.get(p.to_string_lossy().as_ref()).copied())
+            .unwrap_or(self.default)
+    }
+}
+
+/// `name` with `-idx` added before the extension; index 0 is the name itself.
+pub fn rename_candidate_name(name: &str, idx: usize) -> String {
+    if idx == 0 {
+        return name.to_string();
+    }
+    match name.rsplit_once('.') {
+        Some((stem, ext)) => format!("{stem}-{idx}.{ext}"),
+        None => format!("{name}-{idx}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    fn file(size: u64, secs: u64) -> EntryFacts {
+        EntryFacts {
+            is_dir: false,
+            size,
+            modified: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs)),
+        }
+    }
+
+    #[test]
+    fn conditional_policies_compare_times_and_file_sizes() {
+        let old_small = file(10, 100);
+        let new_large = file(20, 200);
+        let dir = EntryFacts {
+            is_dir: true,
+            ..new_large
+        };
+
+        assert!(ConflictPolicy::OverwriteIfNewer.replaces(&new_large, &old_small));
+        assert!(!ConflictPolicy::OverwriteIfNewer.replaces(&old_small, &new_large));
+        assert!(!ConflictPolicy::OverwriteIfNewer.replaces(&old_small, &old_small));
+        assert!(ConflictPolicy::OverwriteIfLarger.replaces(&new_large, &old_small));
+        assert!(!ConflictPolicy::OverwriteIfLarger.replaces(&dir, &old_small));
+        assert!(!ConflictPolicy::Skip.replaces(&new_large, &old_small));
+        assert!(ConflictPolicy::Overwrite.replaces(&old_small, &new_large));
+    }
+
+    #[test]
+    fn per_item_decisions_apply_to_nested_paths() {
+        let src = PathBuf::from("/src/photos");
+        let policies = ConflictPolicies::new(
+            ConflictPolicy::Overwrite,
+            HashMap::from([(src.to_string_lossy().to_string(), ConflictPolicy::Skip)]),
+        );
+
+        assert_eq!(
+            policies.for_path(&src.join("2024/a.jpg")),
+            ConflictPolicy::Skip
+        );
+        assert_eq!(
+            policies.for_path(Path::new("/src/notes.txt")),
+            ConflictPolicy::Overwrite
+        );
+        assert_eq!(rename_candidate_name("a.tar", 2), "a-2.tar");
+        assert_eq!(rename_candidate_name("README", 1), "README-1");
+    }
+}
@@ -0,0 +1,330 @@
+//! Kernel-side file copies. On Linux a copy first tries a `FICLONE` reflink, then
+//! `copy_file_range`, then `sendfile`; the last two only copy the data extents of the source
+//! so holes stay holes. Callers keep their own userspace loop for when none of them applies.
+
+use std::fs::File;
+use std::io;
+
+/// Bytes handed to the kernel per call, so progress and cancellation are checked in between.
+#[cfg(target_os = "linux")]
+const CHUNK: u64 = 8 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FastCopy {
+    /// The source was copied completely; the value is the resulting file size.
+    Done(u64),
+    /// `on_progress` asked to stop after this many bytes of the source were handled.
+    Cancelled(u64),
+    /// No kernel mechanism works for this pair of files and nothing was written. Both file
+    /// positions are where they were, so a userspace loop can take over.
+    Unsupported,
+}
+
+/// Copies `src` into the empty, writable `dest` without going through userspace buffers.
+///
+/// `on_progress` is called with the source offset reached and the data bytes moved so far,
+/// before every chunk and once at the end; returning `false` stops the copy. Holes and reflinked
+/// extents are skipped without moving data, so only the second count tracks actual I/O. Extent probing and `sendfile` move the file positions,
+/// so both are put back before returning. An error after data was written is returned as is,
+/// since falling back at that point would duplicate work on a half-written target.
+pub fn copy_file_fast(
+    src: &File,
+    dest: &File,
+    on_progress: impl FnMut(u64, u64) -> bool,
+) -> io::Result<FastCopy> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::copy(src, dest, on_progress)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (src, dest, on_progress);
+        Ok(FastCopy::Unsupported)
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::{FastCopy, CHUNK};
+    use std::fs::File;
+    use std::io;
+    use std::os::unix::io::{AsRawFd, RawFd};
+
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    pub(super) enum Method {
+        CopyFileRange,
+        Sendfile,
+    }
+
+    pub(super) fn copy(
+        src: &File,
+        dest: &File,
+        on_progress: impl FnMut(u64, u64) -> bool,
+    ) -> io::Result<FastCopy> {
+        copy_with(src, dest, on_progress, true, kernel_copy)
+    }
+
+    /// [`copy`] with the reflink and the range copy swappable, so tests can force a fallback.
+    pub(super) fn copy_with(
+        src: &File,
+        dest: &File,
+        on_progress: impl FnMut(u64, u64) -> bool,
+        reflink: bool,
+        copy_range: impl FnMut(Method, &File, &File, u64, usize) -> io::Result<u64>,
+    ) -> io::Result<FastCopy> {
+        let src_pos = seek(src.as_raw_fd(), 0, libc::SEEK_CUR)?;
+        let dest_pos = seek(dest.as_raw_fd(), 0, libc::SEEK_CUR)?;
+        let outcome = copy_extents(src, dest, on_progress, reflink, copy_range);
+        let restored = seek(src.as_raw_fd(), src_pos, libc::SEEK_SET)
+            .and_then(|_| seek(dest.as_raw_fd(), dest_pos, libc::SEEK_SET));
+        match (outcome, restored) {
+            (Ok(outcome), Ok(_)) => Ok(outcome),
+            (Err(err), _) | (Ok(_), Err(err)) => Err(err),
+        }
+    }
+
+    fn copy_extents(
+        src: &File,
+        dest: &File,
+        mut on_progress: impl FnMut(u64, u64) -> bool,
+        reflink: bool,
+        mut copy_range: impl FnMut(Method, &File, &File, u64, usize) -> io::Result<u64>,
+    ) -> io::Result<FastCopy> {
+        let len = src.metadata()?.len();
+        // Pseudo files (procfs, sysfs) report a zero length but still have content.
+        if len == 0 {
+            return Ok(FastCopy::Unsupported);
+        }
+        if !on_progress(0, 0) {
+            return Ok(FastCopy::Cancelled(0));
+        }
+        if reflink && clone(src, dest) {
+            on_progress(len, 0);
+            return Ok(FastCopy::Done(len));
+        }
+
+        let mut method = Method::CopyFileRange;
+        let mut moved = 0u64;
+        let mut pos = 0u64;
+        while pos < len {
+            let Some((start, end)) = next_data(src, pos, len)? else {
+                break;
+            };
+            let mut offset = start;
+            while offset < end {
+                if !on_progress(offset, moved) {
+                    return Ok(FastCopy::Cancelled(offset));
+                }
+                let chunk = (end - offset).min(CHUNK) as usize;
+                let copied = match copy_range(method, src, dest, offset, chunk) {
+                    Ok(copied) => copied,
+                    Err(err) if moved == 0 && is_unsupported(method, &err) => {
+                        match method {
+                            Method::CopyFileRange => method = Method::Sendfile,
+                            Method::Sendfile => return Ok(FastCopy::Unsupported),
+                        }
+                        continue;
+                    }
+                    Err(err) => return Err(err),
+                };
+                if copied == 0 {
+                    // The source shrank while we were copying it; keep what was there.
+                    dest.set_len(offset)?;
+                    on_progress(offset, moved);
+                    return Ok(FastCopy::Done(offset));
+                }
+                moved += copied;
+                offset += copied;
+            }
+            pos = end;
+        }
+        // Extends the target over a trailing hole without allocating it.
+        dest.set_len(len)?;
+        on_progress(len, moved);
+        Ok(FastCopy::Done(len))
+    }
+
+    /// Shares the source extents with the target on filesystems that support reflinks
+    /// (btrfs, XFS, bcachefs). Any failure just means the next method is tried.
+    fn clone(src: &File, dest: &File) -> bool {
+        unsafe { libc::ioctl(dest.as_raw_fd(), libc::FICLONE as _, src.as_raw_fd()) == 0 }
+    }
+
+    /// The next `[start, end)` range holding data at or after `pos`. Filesystems without
+    /// `SEEK_DATA` support are treated as one data range up to `len`.
+    fn next_data(src: &File, pos: u64, len: u64) -> io::Result<Option<(u64, u64)>> {
+        let fd = src.as_raw_fd();
+        let start = match seek(fd, pos, libc::SEEK_DATA) {
+            Ok(start) => start,
+            Err(err) if err.raw_os_error() == Some(libc::ENXIO) => return Ok(None),
+            Err(err) if err.raw_os_error() == Some(libc::EINVAL) => return Ok(Some((pos, len))),
+            Err(err) => return Err(err),
+        };
+        if start >= len {
+            return Ok(None);
+        }
+        let end = seek(fd, start, libc::SEEK_HOLE).unwrap_or(len).min(len);
+        Ok(Some((start, end)))
+    }
+
+    pub(super) fn seek(fd: RawFd, offset: u64, whence: libc::c_int) -> io::Result<u64> {
+        let res = unsafe { libc::lseek(fd, offset as libc::off_t, whence) };
+        if res < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(res as u64)
+        }
+    }
+
+    fn kernel_copy(
+        method: Method,
+        src: &File,
+        dest: &File,
+        offset: u64,
+        chunk: usize,
+    ) -> io::Result<u64> {
+        let res = match method {
+            Method::CopyFileRange => {
+                let mut off_in = offset as libc::off64_t;
+                let mut off_out = offset as libc::off64_t;
+                unsafe {
+                    libc::copy_file_range(
+                        src.as_raw_fd(),
+                        &mut off_in,
+                        dest.as_raw_fd(),
+                        &mut off_out,
+                        chunk,
+                        0,
+                    )
+                }
+            }
+            Method::Sendfile => {
+                // sendfile writes at the target's file position, so move it past any hole.
+                seek(dest.as_raw_fd(), offset, libc::SEEK_SET)?;
+                let mut off_in = offset as libc::off_t;
+                unsafe { libc::sendfile(dest.as_raw_fd(), src.as_raw_fd(), &mut off_in, chunk) }
+            }
+        };
+        if res < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(res as u64)
+        }
+    }
+
+    fn is_unsupported(method: Method, err: &io::Error) -> bool {
+        let code = err.raw_os_error();
+        match method {
+            // EXDEV on kernels without cross-filesystem support, EPERM from some sandboxes.
+            Method::CopyFileRange => matches!(
+                code,
+                Some(libc::ENOSYS | libc::EXDEV | libc::EOPNOTSUPP | libc::EINVAL | libc::EPERM)
+            ),
+            Method::Sendfile => {
+                matches!(code, Some(libc::ENOSYS | libc::EOPNOTSUPP | libc::EINVAL))
+            }
+        }
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::fs_utils::unique_temp_dir;
+    use std::io::{Read, Seek, SeekFrom, Write};
+    use std::os::unix::fs::MetadataExt;
+
+    #[test]
+    fn copies_sparse_file_without_filling_holes() {
+        let dir = unique_temp_dir("sparse");
+        let src_path = dir.join("sparse.img");
+        let dest_path = dir.join("copy.img");
+        let len = 32 * 1024 * 1024u64;
+        {
+            let mut src = File::create(&src_path).unwrap();
+            src.set_len(len).unwrap();
+            src.seek(SeekFrom::Start(CHUNK + 4096)).unwrap();
+            src.write_all(b"payload").unwrap();
+        }
+        let src = File::open(&src_path).unwrap();
+        let dest = File::create(&dest_path).unwrap();
+        let mut last = (0, 0);
+        let outcome = copy_file_fast(&src, &dest, |offset, moved| {
+            last = (offset, moved);
+            true
+        })
+        .unwrap();
+        drop(dest);
+
+        assert_eq!(outcome, FastCopy::Done(len));
+        assert_eq!(last.0, len);
+        let mut copied = Vec::new();
+        File::open(&dest_path)
+            .unwrap()
+            .read_to_end(&mut copied)
+            .unwrap();
+        assert_eq!(copied.len() as u64, len);
+        let at = (CHUNK + 4096) as usize;
+        assert_eq!(&copied[at..at + 7], b"payload");
+
+        let src_blocks = std::fs::metadata(&src_path).unwrap().blocks();
+        if src_blocks * 512 < len {
+            assert!(std::fs::metadata(&dest_path).unwrap().blocks() * 512 < len);
+            assert!(last.1 < len);
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn falls_back_with_both_positions_where_they_were() {
+        use super::linux::{copy_with, seek, Method};
+        use std::os::unix::io::AsRawFd;
+
+        let dir = unique_temp_dir("fallback");
+        let src_path = dir.join("data.bin");
+        let data = (0..=255u8).cycle().take(64 * 1024).collect::<Vec<_>>();
+        std::fs::write(&src_path, &data).unwrap();
+        let mut src = File::open(&src_path).unwrap();
+        let mut dest = File::create(dir.join("copy.bin")).unwrap();
+
+        // Both methods give up, the way they do on filesystems without support; sendfile
+        // moves the target position first, as the real call does.
+        let outcome = copy_with(
+            &src,
+            &dest,
+            |_, _| true,
+            false,
+            |method, _, dest, offset, _| {
+                if method == Method::Sendfile {
+                    seek(dest.as_raw_fd(), offset + 512, libc::SEEK_SET)?;
+                }
+                Err(io::Error::from_raw_os_error(libc::EINVAL))
+            },
+        )
+        .unwrap();
+
+        assert_eq!(outcome, FastCopy::Unsupported);
+        assert_eq!(dest.stream_position().unwrap(), 0);
+        let mut copied = Vec::new();
+        src.read_to_end(&mut copied).unwrap();
+        assert_eq!(copied, data);
+        dest.write_all(&copied).unwrap();
+        assert_eq!(std::fs::read(dir.join("copy.bin")).unwrap(), data);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn stops_when_progress_asks_to_cancel() {
+        let dir = unique_temp_dir("cancel");
+        let src_path = dir.join("data.bin");
+        std::fs::write(&src_path, vec![7u8; 1024]).unwrap();
+        let src = File::open(&src_path).unwrap();
+        let dest = File::create(dir.join("copy.bin")).unwrap();
+
+        let outcome = copy_file_fast(&src, &dest, |_, _| false).unwrap();
+
+        assert_eq!(outcome, FastCopy::Cancelled(0));
+        assert_eq!(dest.metadata().unwrap().len(), 0);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
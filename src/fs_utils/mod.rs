@@ -5,9 +5,24 @@ use tracing::debug;
 #[cfg(target_os = "windows")]
 use std::path::Prefix;
 
+pub mod bandwidth;
+mod conflict;
 mod error;
+mod fast_copy;
+mod preserve;
+#[cfg(test)]
+mod test_dirs;
+mod verify;
 
+pub use conflict::{rename_candidate_name, ConflictPolicies, ConflictPolicy, EntryFacts};
 pub use error::{FsUtilsError, FsUtilsErrorCode, FsUtilsResult};
+pub use fast_copy::{copy_file_fast, FastCopy};
+pub use preserve::{
+    MetadataPreserver, PreserveAttr, PreserveOptions, StoredMetadata, UnpreservedAttr,
+};
+#[cfg(test)]
+pub use test_dirs::unique_temp_dir;
+pub use verify::{compare_files, FileVerifier, VerifyMode, VerifyReport};
 
 #[cfg(target_os = "windows")]
 fn normalize_drive_root(raw: &str) -> String {
@@ -0,0 +1,610 @@
+//! Carries file metadata (times, mode, extended attributes, POSIX ACLs) over to copies.
+//!
+//! Preservation is best effort: a destination that cannot take an attribute does not fail the
+//! copy. Every attribute that could not be carried over is recorded on the [`MetadataPreserver`]
+//! so the operation can report it.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreserveAttr {
+    Times,
+    Mode,
+    Xattr,
+    Acl,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreserveOptions {
+    pub times: bool,
+    pub mode: bool,
+    pub xattr: bool,
+    pub acl: bool,
+}
+
+impl PreserveOptions {
+    pub const NONE: Self = Self {
+        times: false,
+        mode: false,
+        xattr: false,
+        acl: false,
+    };
+
+    pub const ALL: Self = Self {
+        times: true,
+        mode: true,
+        xattr: true,
+        acl: true,
+    };
+
+    pub fn from_attrs(attrs: &[PreserveAttr]) -> Self {
+        let mut out = Self::NONE;
+        for attr in attrs {
+            match attr {
+                PreserveAttr::Times => out.times = true,
+                PreserveAttr::Mode => out.mode = true,
+                PreserveAttr::Xattr => out.xattr = true,
+                PreserveAttr::Acl => out.acl = true,
+            }
+        }
+        out
+    }
+
+    /// Options for a command's `preserve` argument; `None` keeps the command's own default.
+    pub fn from_request(attrs: Option<Vec<PreserveAttr>>, default: Self) -> Self {
+        attrs.map_or(default, |attrs| Self::from_attrs(&attrs))
+    }
+
+    pub fn is_empty(self) -> bool {
+        self == Self::NONE
+    }
+
+    pub fn contains(self, attr: PreserveAttr) -> bool {
+        match attr {
+            PreserveAttr::Times => self.times,
+            PreserveAttr::Mode => self.mode,
+            PreserveAttr::Xattr => self.xattr,
+            PreserveAttr::Acl => self.acl,
+        }
+    }
+}
+
+/// Attributes that could not be carried over, grouped by attribute and reason.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnpreservedAttr {
+    pub attribute: PreserveAttr,
+    pub reason: String,
+    /// Number of paths affected.
+    pub count: u64,
+    /// First path affected.
+    pub path: String,
+}
+
+/// Metadata read from an archive entry rather than from a file on disk.
+#[derive(Debug, Clone, Default)]
+pub struct StoredMetadata {
+    pub mode: Option<u32>,
+    pub modified: Option<SystemTime>,
+    /// Extended attributes as (name, value), POSIX ACL attributes included.
+    pub xattrs: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+#[derive(Debug)]
+pub struct MetadataPreserver {
+    options: PreserveOptions,
+    unpreserved: Mutex<Vec<UnpreservedAttr>>,
+}
+
+impl MetadataPreserver {
+    pub fn new(options: PreserveOptions) -> Self {
+        Self {
+            options,
+            unpreserved: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self::new(PreserveOptions::NONE)
+    }
+
+    pub fn options(&self) -> PreserveOptions {
+        self.options
+    }
+
+    /// Records that `attr` of `path` was dropped, if the caller asked for it.
+    pub fn record(&self, path: &Path, attr: PreserveAttr, reason: impl Into<String>) {
+        if !self.options.contains(attr) {
+            return;
+        }
+        let reason = reason.into();
+        let Ok(mut list) = self.unpreserved.lock() else {
+            return;
+        };
+        if let Some(existing) = list
+            .iter_mut()
+            .find(|item| item.attribute == attr && item.reason == reason)
+        {
+            existing.count = existing.count.saturating_add(1);
+            return;
+        }
+        list.push(UnpreservedAttr {
+            attribute: attr,
+            reason,
+            count: 1,
+            path: path.to_string_lossy().into_owned(),
+        });
+    }
+
+    pub fn into_report(self) -> Vec<UnpreservedAttr> {
+        self.unpreserved.into_inner().unwrap_or_default()
+    }
+
+    /// Copies the requested metadata of `src` onto `dest`. Directories should be handled after
+    /// their contents are written, or the writes bump the copied times again.
+    pub fn copy_from(&self, src: &Path, dest: &Path) {
+        if self.options.is_empty() {
+            return;
+        }
+        let meta = match fs::symlink_metadata(src) {
+            Ok(meta) => meta,
+            Err(err) => {
+                let reason = format!("failed to read source metadata: {err}");
+                for attr in [
+                    PreserveAttr::Xattr,
+                    PreserveAttr::Mode,
+                    PreserveAttr::Acl,
+                    PreserveAttr::Times,
+                ] {
+                    self.record(dest, attr, reason.clone());
+                }
+                return;
+            }
+        };
+        // Extended attributes go first: writing user attributes needs write access, which a
+        // copied read-only mode would take away.
+        if self.options.xattr {
+            match xattr::list(src) {
+                Ok(names) => {
+                    for name in names.iter().filter(|n| !xattr::is_acl_name(n)) {
+                        if let Err(err) =
+                            xattr::get(src, name).and_then(|v| xattr::set(dest, name, &v))
+                        {
+                            self.record(dest, PreserveAttr::Xattr, io_reason(&err));
+                        }
+                    }
+                }
+                Err(err) => self.record(dest, PreserveAttr::Xattr, io_reason(&err)),
+            }
+        }
+        if self.options.mode {
+            if let Err(err) = fs::set_permissions(dest, meta.permissions()) {
+                self.record(dest, PreserveAttr::Mode, io_reason(&err));
+            }
+        }
+        if self.options.acl {
+            for name in xattr::ACL_NAMES {
+                match xattr::get(src, name.as_bytes()) {
+                    Ok(value) => {
+                        if let Err(err) = xattr::set(dest, name.as_bytes(), &value) {
+                            self.record(dest, PreserveAttr::Acl, io_reason(&err));
+                        }
+                    }
+                    Err(err) if xattr::is_missing(&err) => {}
+                    Err(err) => {
+                        self.record(dest, PreserveAttr::Acl, io_reason(&err));
+                        break;
+                    }
+                }
+            }
+        }
+        if self.options.times {
+            match meta.modified() {
+                Ok(modified) => {
+                    let accessed = meta.accessed().unwrap_or(modified);
+                    if let Err(err) = set_times(dest, accessed, modified) {
+                        self.record(dest, PreserveAttr::Times, io_reason(&err));
+                    }
+                }
+                Err(err) => self.record(dest, PreserveAttr::Times, io_reason(&err)),
+            }
+        }
+    }
+
+    /// Applies metadata stored in an archive entry to the extracted `dest`.
+    pub fn apply_stored(&self, dest: &Path, stored: &StoredMetadata) {
+        if self.options.is_empty() {
+            return;
+        }
+        if self.options.xattr || self.options.acl {
+            for (name, value) in &stored.xattrs {
+                let attr = if xattr::is_acl_name(name) {
+                    PreserveAttr::Acl
+                } else {
+                    PreserveAttr::Xattr
+                };
+                if !self.options.contains(attr) {
+                    continue;
+                }
+                if let Err(err) = xattr::set(dest, name, value) {
+                    self.record(dest, attr, io_reason(&err));
+                }
+            }
+        }
+        if self.options.mode {
+            if let Some(mode) = stored.mode {
+                if let Err(err) = set_mode(dest, mode) {
+                    self.record(dest, PreserveAttr::Mode, io_reason(&err));
+                }
+            }
+        }
+        if self.options.times {
+            if let Some(modified) = stored.modified {
+                if let Err(err) = set_times(dest, modified, modified) {
+                    self.record(dest, PreserveAttr::Times, io_reason(&err));
+                }
+            }
+        }
+    }
+
+    /// Records the extended attributes and ACLs of `src` as dropped, for targets that cannot
+    /// store them at all.
+    pub fn record_unstorable(&self, src: &Path, reason: &str) {
+        if !(self.options.xattr || self.options.acl) {
+            return;
+        }
+        let Ok(names) = xattr::list(src) else {
+            return;
+        };
+        if self.options.xattr && names.iter().any(|n| !xattr::is_acl_name(n)) {
+            self.record(src, PreserveAttr::Xattr, reason);
+        }
+        if self.options.acl && names.iter().any(|n| xattr::is_acl_name(n)) {
+            self.record(src, PreserveAttr::Acl, reason);
+        }
+    }
+
+    /// Reads the requested extended attributes and ACLs of `src` as (name, value), for targets
+    /// that store them next to the data, such as tar archives.
+    pub fn read_xattrs(&self, src: &Path) -> Vec<(Vec<u8>, Vec<u8>)> {
+        if !(self.options.xattr || self.options.acl) {
+            return Vec::new();
+        }
+        let names = match xattr::list(src) {
+            Ok(names) => names,
+            // A filesystem without extended attributes has none to carry over.
+            Err(err) if xattr::is_unsupported(&err) => return Vec::new(),
+            Err(err) => {
+                self.record(src, PreserveAttr::Xattr, io_reason(&err));
+                self.record(src, PreserveAttr::Acl, io_reason(&err));
+                return Vec::new();
+            }
+        };
+        let mut out = Vec::new();
+        for name in names {
+            let attr = if xattr::is_acl_name(&name) {
+                PreserveAttr::Acl
+            } else {
+                PreserveAttr::Xattr
+            };
+            if !self.options.contains(attr) {
+                continue;
+            }
+            match xattr::get(src, &name) {
+                Ok(value) => out.push((name, value)),
+                Err(err) => self.record(src, attr, io_reason(&err)),
+            }
+        }
+        out
+    }
+}
+
+fn io_reason(err: &io::Error) -> String {
+    if xattr::is_unsupported(err) {
+        return "not supported by the destination".to_string();
+    }
+    match err.kind() {
+        io::ErrorKind::PermissionDenied => "permission denied".to_string(),
+        _ => err.to_string(),
+    }
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    // Modes from archives never carry setuid, setgid or sticky bits over.
+    fs::set_permissions(path, fs::Permissions::from_mode(mode & 0o777))
+}
+
+#[cfg(not(unix))]
+fn set_mode(path: &Path, mode: u32) -> io::Result<()> {
+    let mut perms = fs::metadata(path)?.permissions();
+    perms.set_readonly(mode & 0o222 == 0);
+    fs::set_permissions(path, perms)
+}
+
+#[cfg(unix)]
+fn set_times(path: &Path, accessed: SystemTime, modified: SystemTime) -> io::Result<()> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    fn timespec(time: SystemTime) -> libc::timespec {
+        let (secs, nanos) = match time.duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(d) => (d.as_secs() as i64, d.subsec_nanos() as i64),
+            Err(e) => {
+                let d = e.duration();
+                if d.subsec_nanos() == 0 {
+                    (-(d.as_secs() as i64), 0)
+                } else {
+                    (
+                        -(d.as_secs() as i64) - 1,
+                        1_000_000_000 - d.subsec_nanos() as i64,
+                    )
+                }
+            }
+        };
+        libc::timespec {
+            tv_sec: secs as libc::time_t,
+            tv_nsec: nanos as _,
+        }
+    }
+
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "path contains NUL byte"))?;
+    let times = [timespec(accessed), timespec(modified)];
+    let rc = unsafe {
+        libc::utimensat(
+            libc::AT_FDCWD,
+            c_path.as_ptr(),
+            times.as_ptr(),
+            libc::AT_SYMLINK_NOFOLLOW,
+        )
+    };
+    if rc == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(unix))]
+fn set_times(path: &Path, accessed: SystemTime, modified: SystemTime) -> io::Result<()> {
+    use std::os::windows::fs::OpenOptionsExt;
+    // FILE_FLAG_BACKUP_SEMANTICS lets directories be opened as well.
+    const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+    let file = fs::OpenOptions::new()
+        .write(true)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+        .open(path)?;
+    file.set_times(
+        fs::FileTimes::new()
+            .set_accessed(accessed)
+            .set_modified(modified),
+    )
+}
+
+/// Extended attribute access without following symlinks. POSIX ACLs are stored as the
+/// `system.posix_acl_*` attributes on Linux.
+#[cfg(target_os = "linux")]
+mod xattr {
+    use std::ffi::CString;
+    use std::io;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    pub(super) const ACL_NAMES: [&str; 2] = ["system.posix_acl_access", "system.posix_acl_default"];
+
+    pub(super) fn is_acl_name(name: &[u8]) -> bool {
+        ACL_NAMES.iter().any(|acl| acl.as_bytes() == name)
+    }
+
+    pub(super) fn is_missing(err: &io::Error) -> bool {
+        err.raw_os_error() == Some(libc::ENODATA)
+    }
+
+    pub(super) fn is_unsupported(err: &io::Error) -> bool {
+        err.raw_os_error() == Some(libc::ENOTSUP)
+    }
+
+    fn c_path(path: &Path) -> io::Result<CString> {
+        CString::new(path.as_os_str().as_bytes())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "path contains NUL byte"))
+    }
+
+    fn c_name(name: &[u8]) -> io::Result<CString> {
+        CString::new(name).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "attribute name contains NUL byte",
+            )
+        })
+    }
+
+    /// Names of the attributes on `path`, ACL attributes included.
+    pub(super) fn list(path: &Path) -> io::Result<Vec<Vec<u8>>> {
+        let path = c_path(path)?;
+        loop {
+            let size = unsafe { libc::llistxattr(path.as_ptr(), std::ptr::null_mut(), 0) };
+            if size < 0 {
+                let err = io::Error::last_os_error();
+                return if is_unsupported(&err) {
+                    Ok(Vec::new())
+                } else {
+                    Err(err)
+                };
+            }
+            if size == 0 {
+                return Ok(Vec::new());
+            }
+            let mut buf = vec![0u8; size as usize];
+            let read =
+                unsafe { libc::llistxattr(path.as_ptr(), buf.as_mut_ptr().cast(), buf.len()) };
+            if read < 0 {
+                let err = io::Error::last_os_error();
+                // The list grew between the two calls; ask for the size again.
+                if err.raw_os_error() == Some(libc::ERANGE) {
+                    continue;
+                }
+                return Err(err);
+            }
+            buf.truncate(read as usize);
+            return Ok(buf
+                .split(|b| *b == 0)
+                .filter(|name| !name.is_empty())
+                .map(<[u8]>::to_vec)
+                .collect());
+        }
+    }
+
+    pub(super) fn get(path: &Path, name: &[u8]) -> io::Result<Vec<u8>> {
+        let path = c_path(path)?;
+        let name = c_name(name)?;
+        loop {
+            let size =
+                unsafe { libc::lgetxattr(path.as_ptr(), name.as_ptr(), std::ptr::null_mut(), 0) };
+            if size < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let mut buf = vec![0u8; size as usize];
+            let read = unsafe {
+                libc::lgetxattr(
+                    path.as_ptr(),
+                    name.as_ptr(),
+                    buf.as_mut_ptr().cast(),
+                    buf.len(),
+                )
+            };
+            if read < 0 {
+                let err = io::Error::last_os_error();
+                if err.raw_os_error() == Some(libc::ERANGE) {
+                    continue;
+                }
+                return Err(err);
+            }
+            buf.truncate(read as usize);
+            return Ok(buf);
+        }
+    }
+
+    pub(super) fn set(path: &Path, name: &[u8], value: &[u8]) -> io::Result<()> {
+        let path = c_path(path)?;
+        let name = c_name(name)?;
+        let rc = unsafe {
+            libc::lsetxattr(
+                path.as_ptr(),
+                name.as_ptr(),
+                value.as_ptr().cast(),
+                value.len(),
+                0,
+            )
+        };
+        if rc == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+}
+
+/// Platforms without Linux-style attribute calls: nothing to list, and any attribute read
+/// from an archive is reported as unsupported.
+#[cfg(not(target_os = "linux"))]
+mod xattr {
+    use std::io;
+    use std::path::Path;
+
+    pub(super) const ACL_NAMES: [&str; 2] = ["system.posix_acl_access", "system.posix_acl_default"];
+
+    pub(super) fn is_acl_name(name: &[u8]) -> bool {
+        ACL_NAMES.iter().any(|acl| acl.as_bytes() == name)
+    }
+
+    pub(super) fn is_missing(_err: &io::Error) -> bool {
+        false
+    }
+
+    pub(super) fn is_unsupported(_err: &io::Error) -> bool {
+        false
+    }
+
+    fn unsupported() -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "extended attributes are not supported on this platform",
+        )
+    }
+
+    pub(super) fn list(_path: &Path) -> io::Result<Vec<Vec<u8>>> {
+        Err(unsupported())
+    }
+
+    pub(super) fn get(_path: &Path, _name: &[u8]) -> io::Result<Vec<u8>> {
+        Err(unsupported())
+    }
+
+    pub(super) fn set(_path: &Path, _name: &[u8], _value: &[u8]) -> io::Result<()> {
+        Err(unsupported())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs_utils::unique_temp_dir;
+    use std::time::Duration;
+
+    #[test]
+    fn copy_from_carries_times_and_mode() {
+        let dir = unique_temp_dir("times-mode");
+        let src = dir.join("src.txt");
+        let dest = dir.join("dest.txt");
+        fs::write(&src, b"data").unwrap();
+        fs::write(&dest, b"data").unwrap();
+        let old = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        set_times(&src, old, old).unwrap();
+        let mut perms = fs::metadata(&src).unwrap().permissions();
+        perms.set_readonly(true);
+        fs::set_permissions(&src, perms).unwrap();
+
+        let preserver = MetadataPreserver::new(PreserveOptions {
+            times: true,
+            mode: true,
+            ..PreserveOptions::NONE
+        });
+        preserver.copy_from(&src, &dest);
+
+        let meta = fs::metadata(&dest).unwrap();
+        assert_eq!(meta.modified().unwrap(), old);
+        assert!(meta.permissions().readonly());
+        assert!(preserver.into_report().is_empty());
+
+        let mut perms = fs::metadata(&dest).unwrap().permissions();
+        #[allow(clippy::permissions_set_readonly_false)]
+        perms.set_readonly(false);
+        let _ = fs::set_permissions(&dest, perms.clone());
+        let _ = fs::set_permissions(&src, perms);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn record_groups_by_attribute_and_reason_and_skips_unrequested() {
+        let preserver = MetadataPreserver::new(PreserveOptions::from_attrs(&[PreserveAttr::Xattr]));
+        preserver.record(Path::new("/a"), PreserveAttr::Xattr, "not supported");
+        preserver.record(Path::new("/b"), PreserveAttr::Xattr, "not supported");
+        preserver.record(Path::new("/c"), PreserveAttr::Xattr, "permission denied");
+        preserver.record(Path::new("/d"), PreserveAttr::Times, "not supported");
+
+        let report = preserver.into_report();
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0].count, 2);
+        assert_eq!(report[0].path, "/a");
+        assert_eq!(report[1].reason, "permission denied");
+    }
+}
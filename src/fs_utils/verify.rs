@@ -0,0 +1,197 @@
+//! Checksum verification of finished copies.
+//!
+//! A [`FileVerifier`] is created per operation from the caller's `verify` argument. Copy code
+//! reports every verified file to it, so the operation can return how many files were checked
+//! and which ones still differ from their source.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+const HASH_BUF_SIZE: usize = 256 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifyMode {
+    /// Hash source and copy after each file and report mismatches.
+    Checksum,
+    /// Like `Checksum`, but a mismatched file is copied once more before it is reported.
+    ChecksumRetry,
+}
+
+impl VerifyMode {
+    pub fn retries(self) -> bool {
+        self == Self::ChecksumRetry
+    }
+}
+
+/// A copied file whose content still differs from its source.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VerifyMismatch {
+    pub source: String,
+    pub target: String,
+    pub reason: String,
+    /// Whether the file was copied a second time before giving up.
+    pub retried: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct VerifyReport {
+    /// Files whose copy matched the source, after a retry included.
+    pub verified: u64,
+    pub mismatches: Vec<VerifyMismatch>,
+}
+
+#[derive(Debug, Default)]
+pub struct FileVerifier {
+    mode: Option<VerifyMode>,
+    verified: AtomicU64,
+    mismatches: Mutex<Vec<VerifyMismatch>>,
+}
+
+impl FileVerifier {
+    pub fn new(mode: Option<VerifyMode>) -> Self {
+        Self {
+            mode,
+            ..Self::default()
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self::new(None)
+    }
+
+    pub fn mode(&self) -> Option<VerifyMode> {
+        self.mode
+    }
+
+    pub fn record_match(&self) {
+        self.verified.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_mismatch(&self, src: &str, dest: &str, reason: impl Into<String>, retried: bool) {
+        let Ok(mut list) = self.mismatches.lock() else {
+            return;
+        };
+        list.push(VerifyMismatch {
+            source: src.to_string(),
+            target: dest.to_string(),
+            reason: reason.into(),
+            retried,
+        });
+    }
+
+    pub fn mismatch_count(&self) -> usize {
+        self.mismatches.lock().map(|list| list.len()).unwrap_or(0)
+    }
+
+    /// The report for the operation's result; `None` when verification was not requested.
+    pub fn into_report(self) -> Option<VerifyReport> {
+        self.mode?;
+        Some(VerifyReport {
+            verified: self.verified.into_inner(),
+            mismatches: self.mismatches.into_inner().unwrap_or_default(),
+        })
+    }
+}
+
+/// Compares two local files by size and BLAKE3 hash. Returns why they differ, or `None` when
+/// they match. A file that cannot be read counts as a mismatch; only cancellation, reported as
+/// `ErrorKind::Interrupted`, is an error.
+pub fn compare_files(
+    src: &Path,
+    dest: &Path,
+    cancelled: &dyn Fn() -> bool,
+) -> io::Result<Option<String>> {
+    let src_hash = match hash_file(src, cancelled) {
+        Ok(hash) => hash,
+        Err(err) if err.kind() == io::ErrorKind::Interrupted => return Err(err),
+        Err(err) => return Ok(Some(format!("failed to read source: {err}"))),
+    };
+    let dest_hash = match hash_file(dest, cancelled) {
+        Ok(hash) => hash,
+        Err(err) if err.kind() == io::ErrorKind::Interrupted => return Err(err),
+        Err(err) => return Ok(Some(format!("failed to read copy: {err}"))),
+    };
+    if src_hash.1 != dest_hash.1 {
+        return Ok(Some(format!(
+            "size differs ({} bytes, copy has {})",
+            src_hash.1, dest_hash.1
+        )));
+    }
+    if src_hash.0 != dest_hash.0 {
+        return Ok(Some("checksum differs".to_string()));
+    }
+    Ok(None)
+}
+
+/// BLAKE3 hash and length of the file at `path`.
+fn hash_file(path: &Path, cancelled: &dyn Fn() -> bool) -> io::Result<(blake3::Hash, u64)> {
+    let mut reader = File::open(path)?;
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = vec![0u8; HASH_BUF_SIZE];
+    let mut len = 0u64;
+    loop {
+        if cancelled() {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled"));
+        }
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            return Ok((hasher.finalize(), len));
+        }
+        hasher.update(&buf[..n]);
+        len += n as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs_utils::unique_temp_dir;
+
+    #[test]
+    fn compare_reports_size_and_content_differences() {
+        let dir = unique_temp_dir("compare");
+        let src = dir.join("src.bin");
+        let same = dir.join("same.bin");
+        let flipped = dir.join("flipped.bin");
+        let short = dir.join("short.bin");
+        std::fs::write(&src, b"archival bytes").unwrap();
+        std::fs::write(&same, b"archival bytes").unwrap();
+        std::fs::write(&flipped, b"archival bytez").unwrap();
+        std::fs::write(&short, b"archival").unwrap();
+        let never = || false;
+
+        assert_eq!(compare_files(&src, &same, &never).unwrap(), None);
+        assert_eq!(
+            compare_files(&src, &flipped, &never).unwrap().as_deref(),
+            Some("checksum differs")
+        );
+        assert!(compare_files(&src, &short, &never)
+            .unwrap()
+            .is_some_and(|reason| reason.starts_with("size differs")));
+        assert!(compare_files(&src, &dir.join("missing"), &never)
+            .unwrap()
+            .is_some_and(|reason| reason.starts_with("failed to read copy")));
+        let err = compare_files(&src, &same, &|| true).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Interrupted);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn report_is_only_built_when_verification_was_requested() {
+        assert_eq!(FileVerifier::disabled().into_report(), None);
+
+        let verifier = FileVerifier::new(Some(VerifyMode::ChecksumRetry));
+        verifier.record_match();
+        verifier.record_mismatch("/a", "/b", "checksum differs", true);
+        assert_eq!(verifier.mismatch_count(), 1);
+        let report = verifier.into_report().unwrap();
+        assert_eq!(report.verified, 1);
+        assert!(report.mismatches[0].retried);
+    }
+}
@@ -7,6 +7,7 @@ mod context_menu;
 mod db;
 mod entry;
 mod errors;
+mod exclusions;
 mod filter;
 mod fs_utils;
 mod icons;
@@ -18,6 +19,7 @@ mod sorting;
 mod statusbar;
 mod svg_options;
 mod tasks;
+mod transfer_journal;
 mod undo;
 mod watcher;
 
@@ -31,10 +33,18 @@ use fs_utils::debug_log;
 use once_cell::sync::OnceCell;
 use runtime_lifecycle::RuntimeLifecycle;
 use statusbar::dir_sizes;
+use tasks::{JobQueue, DEFAULT_JOBS_PER_DEVICE};
 use tauri::Manager;
 use tracing::{debug, warn};
 use tracing_subscriber::{layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter};
-use undo::{redo_action, undo_action, UndoState};
+use transfer_journal::{
+    discard_interrupted_transfer, list_interrupted_transfers, resume_interrupted_transfer,
+    rollback_interrupted_transfer, TransferJournalState,
+};
+use undo::{
+    list_undo_history, redo_action, redo_to_history_entry, undo_action, undo_history_entry,
+    undo_to_history_entry, UndoState,
+};
 use watcher::WatchState;
 
 const MAX_LOG_BYTES: u64 = 10 * 1024 * 1024; // 10 MiB
@@ -169,6 +179,31 @@ fn resolve_log_level_filter_from_settings() -> EnvFilter {
     build_log_filter(configured.as_deref().unwrap_or("warn"))
 }
 
+fn resolve_jobs_per_device_from_settings() -> usize {
+    db::open()
+        .ok()
+        .and_then(|conn| {
+            db::get_setting_string(&conn, "parallelJobsPerDevice")
+                .ok()
+                .flatten()
+        })
+        .and_then(|value| value.parse::<usize>().ok())
+        .filter(|value| (1..=8).contains(value))
+        .unwrap_or(DEFAULT_JOBS_PER_DEVICE)
+}
+
+fn apply_bandwidth_limit_from_settings() {
+    let limit = db::open()
+        .ok()
+        .and_then(|conn| {
+            db::get_setting_string(&conn, "bandwidthLimit")
+                .ok()
+                .flatten()
+        })
+        .and_then(|value| value.parse::<u64>().ok());
+    fs_utils::bandwidth::set_global_limit(limit);
+}
+
 pub(crate) fn apply_runtime_log_level(value: &str) -> Result<(), String> {
     let level = normalize_log_level(value).ok_or_else(|| "invalid log level".to_string())?;
     let handle = LOG_FILTER_HANDLE
@@ -222,15 +257,25 @@ fn main() {
     }
     init_logging();
     apply_webview_rendering_policy_from_settings();
-    undo::cleanup_stale_backups(None);
+    apply_bandwidth_limit_from_settings();
+    let transfer_journal = TransferJournalState::restore();
+    let undo_state = UndoState::restore(transfer_journal.referenced_backups());
     commands::fs::cleanup_stale_trash_staging();
+    let cancel_state = CancelState::default();
+    let jobs = JobQueue::new(
+        resolve_jobs_per_device_from_settings(),
+        cancel_state.clone(),
+    );
     let app = tauri::Builder::default()
         .plugin(tauri_plugin_drag::init())
         .manage(WatchState::default())
-        .manage(CancelState::default())
-        .manage(UndoState::default())
+        .manage(cancel_state)
+        .manage(jobs)
+        .manage(undo_state)
+        .manage(transfer_journal)
         .manage(RuntimeLifecycle::default())
         .setup(|app| {
+            commands::search::start_search_indexer(app.handle().clone());
             for window in &app.config().app.windows {
                 if window.create {
                     continue;
@@ -266,6 +311,10 @@ fn main() {
             move_mixed_entries,
             copy_mixed_entry_to,
             move_mixed_entry_to,
+            list_interrupted_transfers,
+            resume_interrupted_transfer,
+            rollback_interrupted_transfer,
+            discard_interrupted_transfer,
             normalize_cloud_path,
             list_network_devices,
             list_network_entries,
@@ -274,6 +323,11 @@ fn main() {
             add_bookmark,
             remove_bookmark,
             clear_bookmarks,
+            list_saved_searches,
+            add_saved_search,
+            update_saved_search,
+            remove_saved_search,
+            list_saved_search,
             watch_dir,
             open_entry,
             list_open_with_apps,
@@ -309,6 +363,8 @@ fn main() {
             load_archive_name,
             store_archive_level,
             load_archive_level,
+            store_archive_format,
+            load_archive_format,
             store_open_dest_after_extract,
             load_open_dest_after_extract,
             store_ffmpeg_path,
@@ -317,6 +373,10 @@ fn main() {
             load_thumb_cache_mb,
             store_mounts_poll_ms,
             load_mounts_poll_ms,
+            store_parallel_jobs_per_device,
+            load_parallel_jobs_per_device,
+            store_bandwidth_limit,
+            load_bandwidth_limit,
             store_double_click_ms,
             load_double_click_ms,
             store_log_level,
@@ -335,6 +395,10 @@ fn main() {
             load_rclone_path,
             store_density,
             load_density,
+            store_search_index_roots,
+            load_search_index_roots,
+            store_exclude_patterns,
+            load_exclude_patterns,
             load_shortcuts,
             set_shortcut_binding,
             reset_shortcut_binding,
@@ -361,7 +425,10 @@ fn main() {
             set_hidden,
             can_extract_paths,
             extract_archive,
+            extract_archive_members,
+            extract_archive_with_password,
             extract_archives,
+            list_archive_entries,
             open_console,
             set_clipboard_cmd,
             copy_paths_to_system_clipboard,
@@ -371,13 +438,21 @@ fn main() {
             paste_clipboard_cmd,
             paste_clipboard_preview,
             search_stream,
+            rebuild_search_index,
+            search_index_status,
             restore_trash_items,
             purge_trash_items,
             create_folder,
             compress_entries,
             check_duplicates,
             check_duplicates_stream,
+            find_all_duplicates_stream,
             cancel_task,
+            list_jobs,
+            pause_job,
+            resume_job,
+            move_job,
+            set_job_bandwidth_limit,
             get_permissions,
             get_permissions_batch,
             set_permissions,
@@ -385,6 +460,10 @@ fn main() {
             list_ownership_principals,
             undo_action,
             redo_action,
+            list_undo_history,
+            undo_to_history_entry,
+            redo_to_history_entry,
+            undo_history_entry,
             get_thumbnail,
             clear_thumbnail_cache,
             clear_cloud_open_cache
@@ -19,8 +19,13 @@ fn classify_kind(path: &Path, meta: &fs::Metadata) -> String {
     let ext = path
         .extension()
         .and_then(|e| e.to_str())
-        .unwrap_or_default()
-        .to_ascii_lowercase();
+        .unwrap_or_default();
+    classify_extension(ext).to_string()
+}
+
+/// Classifies a regular file by extension into the kinds used by the metadata providers.
+pub fn classify_extension(ext: &str) -> &'static str {
+    let ext = ext.to_ascii_lowercase();
 
     if matches!(
         ext.as_str(),
@@ -39,22 +44,22 @@ fn classify_kind(path: &Path, meta: &fs::Metadata) -> String {
             | "hdr"
             | "exr"
     ) {
-        return "image".to_string();
+        return "image";
     }
     if ext == "pdf" {
-        return "pdf".to_string();
+        return "pdf";
     }
     if matches!(
         ext.as_str(),
         "mp4" | "mkv" | "webm" | "mov" | "avi" | "wmv" | "m4v" | "mpeg" | "mpg"
     ) {
-        return "video".to_string();
+        return "video";
     }
     if matches!(
         ext.as_str(),
         "mp3" | "flac" | "wav" | "ogg" | "m4a" | "aac" | "opus" | "wma"
     ) {
-        return "audio".to_string();
+        return "audio";
     }
     if matches!(
         ext.as_str(),
@@ -71,10 +76,10 @@ fn classify_kind(path: &Path, meta: &fs::Metadata) -> String {
             | "7z"
             | "rar"
     ) {
-        return "archive".to_string();
+        return "archive";
     }
 
-    "generic".to_string()
+    "generic"
 }
 
 pub fn collect_extra_metadata(path: &Path) -> MetadataResult<ExtraMetadataResult> {
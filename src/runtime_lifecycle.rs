@@ -126,6 +126,7 @@ pub fn emit_if_running<R: tauri::Runtime, S: serde::Serialize + Clone>(
         debug!(event, "dropping runtime event during shutdown");
         return false;
     }
+    crate::tasks::observe_progress(app, event, &payload);
     // Best effort by design: during shutdown or transient frontend teardown we
     // prefer dropping the event over turning coordination helpers into
     // fallible plumbing everywhere.
@@ -8,7 +8,7 @@ use std::sync::atomic::Ordering;
 use std::os::unix::fs::MetadataExt;
 
 use crate::errors::api_error::ApiResult;
-use crate::{commands::CancelState, runtime_lifecycle};
+use crate::{commands::CancelState, exclusions::ExclusionRules, runtime_lifecycle};
 
 mod error;
 use error::{map_api_result, StatusbarError, StatusbarErrorCode, StatusbarResult};
@@ -51,6 +51,7 @@ fn dir_size_recursive<F, S>(
     root: &Path,
     #[cfg_attr(not(unix), allow(unused_variables))] root_dev: Option<u64>,
     pseudo_roots: &HashSet<&str>,
+    exclusions: Option<&ExclusionRules>,
     mut on_progress: F,
     should_stop: S,
 ) -> (u64, u64, bool)
@@ -79,6 +80,9 @@ where
             Ok(m) => m,
             Err(_) => continue,
         };
+        if exclusions.is_some_and(|rules| rules.is_excluded(&path, meta.is_dir())) {
+            continue;
+        }
 
         items = items.saturating_add(1);
         pending_items = pending_items.saturating_add(1);
@@ -143,15 +147,26 @@ pub async fn dir_sizes(
     app: tauri::AppHandle,
     cancel: tauri::State<'_, CancelState>,
     paths: Vec<String>,
+    include_ignored: Option<bool>,
     progress_event: Option<String>,
 ) -> ApiResult<DirSizeResult> {
-    map_api_result(dir_sizes_impl(app, cancel, paths, progress_event).await)
+    map_api_result(
+        dir_sizes_impl(
+            app,
+            cancel,
+            paths,
+            include_ignored.unwrap_or(true),
+            progress_event,
+        )
+        .await,
+    )
 }
 
 async fn dir_sizes_impl(
     app: tauri::AppHandle,
     cancel: tauri::State<'_, CancelState>,
     paths: Vec<String>,
+    include_ignored: bool,
     progress_event: Option<String>,
 ) -> StatusbarResult<DirSizeResult> {
     let cancel_state = cancel.inner().clone();
@@ -197,6 +212,7 @@ async fn dir_sizes_impl(
             #[cfg(not(unix))]
             let root_dev: Option<u64> = None;
 
+            let exclusions = (!include_ignored).then(|| ExclusionRules::load(&path));
             let mut partial: u64 = 0;
             let mut partial_items: u64 = 0;
             let emit_progress =
@@ -218,6 +234,7 @@ async fn dir_sizes_impl(
                 &path,
                 root_dev,
                 &pseudo_roots,
+                exclusions.as_ref(),
                 |delta, items_delta| {
                     partial = partial.saturating_add(delta);
                     partial_items = partial_items.saturating_add(items_delta);
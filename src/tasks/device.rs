@@ -0,0 +1,76 @@
+//! Devices a job reads or writes. Keys compare equal for paths on the same filesystem, so the
+//! queue can limit how many jobs share one disk or one cloud remote.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+const CLOUD_SCHEME: &str = "rclone://";
+
+/// Sorted, de-duplicated keys for `paths`: `cloud:<remote>` for rclone paths and
+/// `dev:<id>` for local ones, taken from the nearest existing ancestor so destinations that do
+/// not exist yet still resolve.
+pub(super) fn device_keys<'a>(paths: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    paths
+        .into_iter()
+        .filter_map(device_key)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+fn device_key(path: &str) -> Option<String> {
+    if let Some(rest) = path.strip_prefix(CLOUD_SCHEME) {
+        let remote = rest.split('/').next().filter(|remote| !remote.is_empty())?;
+        return Some(format!("cloud:{remote}"));
+    }
+    Path::new(path).ancestors().find_map(local_device)
+}
+
+#[cfg(unix)]
+fn local_device(path: &Path) -> Option<String> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata(path)
+        .ok()
+        .map(|meta| format!("dev:{}", meta.dev()))
+}
+
+#[cfg(not(unix))]
+fn local_device(path: &Path) -> Option<String> {
+    use std::path::Component;
+    if !path.exists() {
+        return None;
+    }
+    match path.components().next() {
+        Some(Component::Prefix(prefix)) => Some(format!(
+            "dev:{}",
+            prefix.as_os_str().to_string_lossy().to_uppercase()
+        )),
+        _ => Some("dev:/".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cloud_paths_are_keyed_by_remote_and_missing_paths_by_their_parent() {
+        let tmp = std::env::temp_dir();
+        let missing = tmp.join("browsey-device-test-missing").join("child.txt");
+        let keys = device_keys([
+            "rclone://work/a.txt",
+            "rclone://work/docs/b.txt",
+            "rclone://home",
+            tmp.to_str().unwrap(),
+            missing.to_str().unwrap(),
+        ]);
+        assert_eq!(keys.len(), 3);
+        assert!(keys.contains(&"cloud:work".to_string()));
+        assert!(keys.contains(&"cloud:home".to_string()));
+        assert_eq!(
+            device_key(missing.to_str().unwrap()),
+            device_key(tmp.to_str().unwrap())
+        );
+        assert_eq!(device_key("rclone://"), None);
+    }
+}
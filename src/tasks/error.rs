@@ -8,6 +8,8 @@ use std::fmt;
 pub enum TaskErrorCode {
     RegistryLockFailed,
     TaskNotFound,
+    JobNotFound,
+    InvalidJobState,
 }
 
 impl ErrorCode for TaskErrorCode {
@@ -15,6 +17,8 @@ impl ErrorCode for TaskErrorCode {
         match self {
             Self::RegistryLockFailed => "registry_lock_failed",
             Self::TaskNotFound => "task_not_found",
+            Self::JobNotFound => "job_not_found",
+            Self::InvalidJobState => "invalid_job_state",
         }
     }
 }
@@ -0,0 +1,739 @@
+//! Queue of pastes, mixed transfers, compressions, extractions, trash moves and deletes. A job
+//! starts once every device it touches has a free slot, can be paused, resumed and moved ahead
+//! of other waiting jobs, and all jobs report through one `jobs-changed` event.
+//!
+//! Jobs are tied to the `progress_event` of their command: the progress it emits is folded
+//! into the job, and a paused running job stops at the next [`pause_checkpoint`] of its copy,
+//! extract or compress loop.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::Manager;
+
+use super::device::device_keys;
+use super::error::map_api_result;
+use super::{CancelState, TaskError, TaskErrorCode, TaskResult};
+use crate::errors::api_error::ApiResult;
+use crate::fs_utils::bandwidth;
+use crate::runtime_lifecycle;
+
+pub const JOBS_CHANGED_EVENT: &str = "jobs-changed";
+pub const DEFAULT_JOBS_PER_DEVICE: usize = 2;
+/// Finished jobs stay listed so the UI can show how they ended; older ones are dropped.
+const FINISHED_JOBS_KEPT: usize = 20;
+const WAIT_POLL: Duration = Duration::from_millis(100);
+const PROGRESS_EMIT_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    Paste,
+    Transfer,
+    Compress,
+    Extract,
+    Trash,
+    Delete,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Paused,
+    Failed,
+    Done,
+}
+
+impl JobStatus {
+    fn is_finished(self) -> bool {
+        matches!(self, Self::Failed | Self::Done)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobInfo {
+    pub id: u64,
+    pub kind: JobKind,
+    pub status: JobStatus,
+    /// Where the job writes, or the first item it removes.
+    pub target: String,
+    pub items: usize,
+    pub progress_event: Option<String>,
+    pub devices: Vec<String>,
+    pub bytes: u64,
+    pub total: u64,
+    /// Bytes per second; the global limit still applies when it is stricter.
+    pub bandwidth_limit: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// A job as its command describes it before queueing.
+pub struct JobSpec {
+    kind: JobKind,
+    target: String,
+    sources: Vec<String>,
+    progress_event: Option<String>,
+}
+
+impl JobSpec {
+    /// `sources` and `target` together decide which devices the job occupies.
+    pub fn new(
+        kind: JobKind,
+        target: impl Into<String>,
+        sources: Vec<String>,
+        progress_event: Option<String>,
+    ) -> Self {
+        Self {
+            kind,
+            target: target.into(),
+            sources,
+            progress_event,
+        }
+    }
+}
+
+struct Job {
+    info: JobInfo,
+    /// Holds its device slots; stays set while a running job is paused.
+    started: bool,
+}
+
+struct Jobs {
+    list: Vec<Job>,
+    per_device: usize,
+    next_id: u64,
+    last_progress_emit: Option<Instant>,
+}
+
+#[derive(Clone)]
+pub struct JobQueue {
+    inner: Arc<(Mutex<Jobs>, Condvar)>,
+    cancel: CancelState,
+}
+
+impl JobQueue {
+    /// `per_device` jobs may run at once on each disk or cloud remote. Waiting jobs can be
+    /// cancelled through `cancel`, like running ones.
+    pub fn new(per_device: usize, cancel: CancelState) -> Self {
+        Self {
+            inner: Arc::new((
+                Mutex::new(Jobs {
+                    list: Vec::new(),
+                    per_device: per_device.max(1),
+                    next_id: 1,
+                    last_progress_emit: None,
+                }),
+                Condvar::new(),
+            )),
+            cancel,
+        }
+    }
+
+    pub fn set_per_device(&self, per_device: usize) {
+        self.update(|jobs| jobs.per_device = per_device.max(1));
+    }
+
+    pub fn list(&self) -> Vec<JobInfo> {
+        self.lock()
+            .list
+            .iter()
+            .map(|job| job.info.clone())
+            .collect()
+    }
+
+    pub fn pause(&self, id: u64) -> TaskResult<()> {
+        self.update_job(id, |job| {
+            if matches!(job.info.status, JobStatus::Queued | JobStatus::Running) {
+                job.info.status = JobStatus::Paused;
+            }
+        })
+    }
+
+    pub fn resume(&self, id: u64) -> TaskResult<()> {
+        self.update_job(id, |job| {
+            if job.info.status == JobStatus::Paused {
+                job.info.status = if job.started {
+                    JobStatus::Running
+                } else {
+                    JobStatus::Queued
+                };
+            }
+        })
+    }
+
+    /// Limits an unfinished job to `limit` bytes per second, or lifts its own limit. A running
+    /// copy picks up the change on its next chunk.
+    pub fn set_bandwidth_limit(&self, id: u64, limit: Option<u64>) -> TaskResult<()> {
+        let limit = limit.filter(|limit| *limit > 0);
+        let mut event = None;
+        self.update_job(id, |job| {
+            event = job.info.progress_event.clone();
+            if event.is_some() {
+                job.info.bandwidth_limit = limit;
+            }
+        })?;
+        let event = event.ok_or_else(|| {
+            TaskError::new(
+                TaskErrorCode::InvalidJobState,
+                "Job does not report progress, so its bandwidth cannot be limited",
+            )
+        })?;
+        bandwidth::set_job_limit(&event, limit);
+        Ok(())
+    }
+
+    /// Moves an unfinished job to `position` among the unfinished jobs. Waiting jobs start in
+    /// this order.
+    pub fn move_to(&self, id: u64, position: usize) -> TaskResult<()> {
+        let mut jobs = self.lock();
+        let index = unfinished_index(&jobs, id)?;
+        let job = jobs.list.remove(index);
+        let insert_at = jobs
+            .list
+            .iter()
+            .enumerate()
+            .filter(|(_, job)| !job.info.status.is_finished())
+            .map(|(index, _)| index)
+            .nth(position)
+            .unwrap_or(jobs.list.len());
+        jobs.list.insert(insert_at, job);
+        schedule(&mut jobs);
+        self.inner.1.notify_all();
+        Ok(())
+    }
+
+    fn enqueue(&self, spec: JobSpec) -> u64 {
+        let devices = device_keys(
+            spec.sources
+                .iter()
+                .chain(std::iter::once(&spec.target))
+                .map(String::as_str),
+        );
+        let mut id = 0;
+        self.update(|jobs| {
+            id = jobs.next_id;
+            jobs.next_id += 1;
+            jobs.list.push(Job {
+                info: JobInfo {
+                    id,
+                    kind: spec.kind,
+                    status: JobStatus::Queued,
+                    target: spec.target,
+                    items: spec.sources.len(),
+                    progress_event: spec.progress_event,
+                    devices,
+                    bytes: 0,
+                    total: 0,
+                    bandwidth_limit: None,
+                    error: None,
+                },
+                started: false,
+            });
+        });
+        id
+    }
+
+    /// Blocks until job `id` may start. Returns false when it was cancelled through its
+    /// progress event or `stop` turned true first.
+    fn wait_turn(&self, id: u64, stop: impl Fn() -> bool) -> bool {
+        let event = self
+            .lock()
+            .list
+            .iter()
+            .find(|job| job.info.id == id)
+            .and_then(|job| job.info.progress_event.clone());
+        let cancel_guard = event.and_then(|event| self.cancel.register(event).ok());
+        let cancel_token = cancel_guard.as_ref().map(|guard| guard.token());
+        let mut jobs = self.lock();
+        loop {
+            let Some(job) = jobs.list.iter().find(|job| job.info.id == id) else {
+                return false;
+            };
+            if job.started {
+                return true;
+            }
+            let cancelled = cancel_token
+                .as_ref()
+                .is_some_and(|token| token.load(std::sync::atomic::Ordering::Relaxed));
+            if cancelled || stop() {
+                return false;
+            }
+            jobs = self.wait(jobs);
+        }
+    }
+
+    fn finish(&self, id: u64, error: Option<String>) {
+        self.update(|jobs| {
+            if let Some(job) = jobs.list.iter_mut().find(|job| job.info.id == id) {
+                job.info.status = if error.is_some() {
+                    JobStatus::Failed
+                } else {
+                    JobStatus::Done
+                };
+                job.info.error = error;
+                if job.info.bandwidth_limit.take().is_some() {
+                    if let Some(event) = job.info.progress_event.as_deref() {
+                        bandwidth::set_job_limit(event, None);
+                    }
+                }
+            }
+            let finished = jobs
+                .list
+                .iter()
+                .filter(|job| job.info.status.is_finished())
+                .count();
+            let mut excess = finished.saturating_sub(FINISHED_JOBS_KEPT);
+            jobs.list.retain(|job| {
+                let remove = excess > 0 && job.info.status.is_finished();
+                excess -= usize::from(remove);
+                !remove
+            });
+        });
+    }
+
+    /// Records progress of the running job reporting on `event`. Returns None when no job
+    /// uses the event, otherwise whether a `jobs-changed` event is due.
+    fn record_progress(&self, event: &str, bytes: u64, total: u64) -> Option<bool> {
+        let mut jobs = self.lock();
+        let job = jobs
+            .list
+            .iter_mut()
+            .find(|job| job.started && is_active_on(job, event))?;
+        job.info.bytes = bytes;
+        job.info.total = total;
+        let due = jobs
+            .last_progress_emit
+            .is_none_or(|last| last.elapsed() >= PROGRESS_EMIT_INTERVAL);
+        if due {
+            jobs.last_progress_emit = Some(Instant::now());
+        }
+        Some(due)
+    }
+
+    fn tracks(&self, event: &str) -> bool {
+        self.lock()
+            .list
+            .iter()
+            .any(|job| job.started && is_active_on(job, event))
+    }
+
+    /// Blocks the worker of the job reporting on `event` while that job is paused.
+    fn wait_while_paused(&self, event: &str, stop: impl Fn() -> bool) {
+        let mut jobs = self.lock();
+        loop {
+            let paused = jobs.list.iter().any(|job| {
+                job.info.status == JobStatus::Paused
+                    && job.info.progress_event.as_deref() == Some(event)
+            });
+            if !paused || self.cancel.is_cancelled(event) || stop() {
+                return;
+            }
+            jobs = self.wait(jobs);
+        }
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Jobs)) {
+        let mut jobs = self.lock();
+        f(&mut jobs);
+        schedule(&mut jobs);
+        self.inner.1.notify_all();
+    }
+
+    fn update_job(&self, id: u64, f: impl FnOnce(&mut Job)) -> TaskResult<()> {
+        let mut jobs = self.lock();
+        let index = unfinished_index(&jobs, id)?;
+        f(&mut jobs.list[index]);
+        schedule(&mut jobs);
+        self.inner.1.notify_all();
+        Ok(())
+    }
+
+    /// Job bookkeeping never fails the operation it describes, so a poisoned lock is reused.
+    fn lock(&self) -> MutexGuard<'_, Jobs> {
+        self.inner
+            .0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn wait<'a>(&self, jobs: MutexGuard<'a, Jobs>) -> MutexGuard<'a, Jobs> {
+        match self.inner.1.wait_timeout(jobs, WAIT_POLL) {
+            Ok((jobs, _)) => jobs,
+            Err(poisoned) => poisoned.into_inner().0,
+        }
+    }
+}
+
+fn is_active_on(job: &Job, event: &str) -> bool {
+    !job.info.status.is_finished() && job.info.progress_event.as_deref() == Some(event)
+}
+
+fn unfinished_index(jobs: &Jobs, id: u64) -> TaskResult<usize> {
+    let index = jobs
+        .list
+        .iter()
+        .position(|job| job.info.id == id)
+        .ok_or_else(|| TaskError::new(TaskErrorCode::JobNotFound, "Job not found"))?;
+    if jobs.list[index].info.status.is_finished() {
+        return Err(TaskError::new(
+            TaskErrorCode::InvalidJobState,
+            "Job has already finished",
+        ));
+    }
+    Ok(index)
+}
+
+/// Starts waiting jobs in queue order while their devices have free slots. A job that has to
+/// wait holds back later jobs on the same devices, so reordering decides who goes next.
+fn schedule(jobs: &mut Jobs) {
+    let limit = jobs.per_device;
+    let mut used: HashMap<String, usize> = HashMap::new();
+    for job in jobs
+        .list
+        .iter()
+        .filter(|job| job.started && !job.info.status.is_finished())
+    {
+        for device in &job.info.devices {
+            *used.entry(device.clone()).or_default() += 1;
+        }
+    }
+    for job in jobs.list.iter_mut().filter(|job| !job.started) {
+        if job.info.status != JobStatus::Queued {
+            continue;
+        }
+        let free = job
+            .info
+            .devices
+            .iter()
+            .all(|device| used.get(device).copied().unwrap_or(0) < limit);
+        for device in &job.info.devices {
+            let count = used.entry(device.clone()).or_default();
+            *count = if free {
+                *count + 1
+            } else {
+                (*count).max(limit)
+            };
+        }
+        if free {
+            job.started = true;
+            job.info.status = JobStatus::Running;
+        }
+    }
+}
+
+/// Ends a started job when its command returns, or marks it failed if the command never did.
+struct RunningJob<'a> {
+    queue: &'a JobQueue,
+    id: u64,
+    finished: bool,
+}
+
+impl RunningJob<'_> {
+    fn finish(mut self, error: Option<String>) {
+        self.finished = true;
+        self.queue.finish(self.id, error);
+    }
+}
+
+impl Drop for RunningJob<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.queue
+                .finish(self.id, Some("Job was interrupted".to_string()));
+        }
+    }
+}
+
+/// Runs `work` as a queued job. It waits for a slot on its devices first; when the job is
+/// cancelled or the app exits while it waits, `cancelled()` is returned and `work` never runs.
+pub async fn run_job<T, E, F>(
+    app: &tauri::AppHandle,
+    spec: JobSpec,
+    cancelled: impl FnOnce() -> E,
+    work: F,
+) -> Result<T, E>
+where
+    E: fmt::Display,
+    F: Future<Output = Result<T, E>>,
+{
+    let Some(queue) = app
+        .try_state::<JobQueue>()
+        .map(|state| state.inner().clone())
+    else {
+        return work.await;
+    };
+    let id = queue.enqueue(spec);
+    emit_jobs_changed(app, &queue);
+    let waiter = queue.clone();
+    let waiter_app = app.clone();
+    let started = tauri::async_runtime::spawn_blocking(move || {
+        waiter.wait_turn(id, || runtime_lifecycle::is_shutting_down(&waiter_app))
+    })
+    .await
+    .unwrap_or(false);
+    if !started {
+        queue.finish(id, Some("Cancelled before it started".to_string()));
+        emit_jobs_changed(app, &queue);
+        return Err(cancelled());
+    }
+    emit_jobs_changed(app, &queue);
+    let running = RunningJob {
+        queue: &queue,
+        id,
+        finished: false,
+    };
+    let result = work.await;
+    running.finish(result.as_ref().err().map(ToString::to_string));
+    emit_jobs_changed(app, &queue);
+    result
+}
+
+/// Folds a progress payload (`bytes`, `total`) emitted on `event` into the job reporting on it.
+/// Never blocks: pausing is up to [`pause_checkpoint`].
+pub(crate) fn observe_progress<R: tauri::Runtime, S: Serialize>(
+    app: &tauri::AppHandle<R>,
+    event: &str,
+    payload: &S,
+) {
+    if event == JOBS_CHANGED_EVENT {
+        return;
+    }
+    let Some(queue) = app.try_state::<JobQueue>() else {
+        return;
+    };
+    if !queue.tracks(event) {
+        return;
+    }
+    let Ok(value) = serde_json::to_value(payload) else {
+        return;
+    };
+    let (Some(bytes), Some(total)) = (
+        value.get("bytes").and_then(Value::as_u64),
+        value.get("total").and_then(Value::as_u64),
+    ) else {
+        return;
+    };
+    if queue.record_progress(event, bytes, total) == Some(true) {
+        emit_jobs_changed(app, &queue);
+    }
+}
+
+/// Holds the worker of the job reporting on `event` while that job is paused. Copy, extract
+/// and compress loops call it between chunks and entries, right before checking for a cancel,
+/// which also lets it go.
+pub(crate) fn pause_checkpoint<R: tauri::Runtime>(app: &tauri::AppHandle<R>, event: &str) {
+    if let Some(queue) = app.try_state::<JobQueue>() {
+        queue.wait_while_paused(event, || runtime_lifecycle::is_shutting_down(app));
+    }
+}
+
+fn emit_jobs_changed<R: tauri::Runtime>(app: &tauri::AppHandle<R>, queue: &JobQueue) {
+    let _ = runtime_lifecycle::emit_if_running(app, JOBS_CHANGED_EVENT, queue.list());
+}
+
+#[tauri::command]
+pub fn list_jobs(jobs: tauri::State<'_, JobQueue>) -> ApiResult<Vec<JobInfo>> {
+    Ok(jobs.list())
+}
+
+/// Pauses a job. A waiting job is not started; a running one stops at its next pause
+/// checkpoint and keeps its device slot.
+#[tauri::command]
+pub fn pause_job(
+    app: tauri::AppHandle,
+    jobs: tauri::State<'_, JobQueue>,
+    id: u64,
+) -> ApiResult<()> {
+    map_api_result(jobs.pause(id).map(|()| emit_jobs_changed(&app, &jobs)))
+}
+
+#[tauri::command]
+pub fn resume_job(
+    app: tauri::AppHandle,
+    jobs: tauri::State<'_, JobQueue>,
+    id: u64,
+) -> ApiResult<()> {
+    map_api_result(jobs.resume(id).map(|()| emit_jobs_changed(&app, &jobs)))
+}
+
+/// Sets a job's own bandwidth limit in bytes per second; `None` or `0` lifts it.
+#[tauri::command]
+pub fn set_job_bandwidth_limit(
+    app: tauri::AppHandle,
+    jobs: tauri::State<'_, JobQueue>,
+    id: u64,
+    limit: Option<u64>,
+) -> ApiResult<()> {
+    map_api_result(
+        jobs.set_bandwidth_limit(id, limit)
+            .map(|()| emit_jobs_changed(&app, &jobs)),
+    )
+}
+
+#[tauri::command]
+pub fn move_job(
+    app: tauri::AppHandle,
+    jobs: tauri::State<'_, JobQueue>,
+    id: u64,
+    position: usize,
+) -> ApiResult<()> {
+    map_api_result(
+        jobs.move_to(id, position)
+            .map(|()| emit_jobs_changed(&app, &jobs)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cloud_job(queue: &JobQueue, remote: &str, event: &str) -> u64 {
+        queue.enqueue(JobSpec::new(
+            JobKind::Transfer,
+            format!("rclone://{remote}/dest"),
+            vec![format!("rclone://{remote}/src")],
+            Some(event.to_string()),
+        ))
+    }
+
+    fn statuses(queue: &JobQueue) -> Vec<(u64, JobStatus)> {
+        queue
+            .list()
+            .into_iter()
+            .map(|job| (job.id, job.status))
+            .collect()
+    }
+
+    #[test]
+    fn jobs_start_per_device_in_queue_order() {
+        let queue = JobQueue::new(1, CancelState::default());
+        let a1 = cloud_job(&queue, "a", "a1");
+        let a2 = cloud_job(&queue, "a", "a2");
+        let b1 = cloud_job(&queue, "b", "b1");
+        assert_eq!(
+            statuses(&queue),
+            vec![
+                (a1, JobStatus::Running),
+                (a2, JobStatus::Queued),
+                (b1, JobStatus::Running),
+            ]
+        );
+
+        queue.finish(a1, None);
+        assert!(queue.wait_turn(a2, || false));
+        let done = queue.list().into_iter().find(|job| job.id == a1).unwrap();
+        assert_eq!(done.status, JobStatus::Done);
+        assert_eq!(done.devices, vec!["cloud:a".to_string()]);
+
+        queue.set_per_device(2);
+        let a3 = cloud_job(&queue, "a", "a3");
+        assert!(queue.wait_turn(a3, || false));
+    }
+
+    #[test]
+    fn reordering_and_pausing_decide_which_waiting_job_starts() {
+        let queue = JobQueue::new(1, CancelState::default());
+        let first = cloud_job(&queue, "a", "first");
+        let second = cloud_job(&queue, "a", "second");
+        let third = cloud_job(&queue, "a", "third");
+
+        queue.move_to(third, 1).unwrap();
+        queue.pause(third).unwrap();
+        queue.finish(first, Some("failed".to_string()));
+        assert_eq!(
+            statuses(&queue),
+            vec![
+                (first, JobStatus::Failed),
+                (third, JobStatus::Paused),
+                (second, JobStatus::Running),
+            ]
+        );
+
+        queue.resume(third).unwrap();
+        assert_eq!(queue.list()[1].status, JobStatus::Queued);
+        let err = queue.pause(first).unwrap_err();
+        assert_eq!(err.code(), TaskErrorCode::InvalidJobState);
+        assert_eq!(
+            queue.pause(99).unwrap_err().code(),
+            TaskErrorCode::JobNotFound
+        );
+    }
+
+    #[test]
+    fn progress_is_recorded_and_waiting_jobs_can_be_cancelled() {
+        let cancel = CancelState::default();
+        let queue = JobQueue::new(1, cancel.clone());
+        let running = cloud_job(&queue, "a", "running");
+        let waiting = cloud_job(&queue, "a", "waiting");
+
+        assert_eq!(queue.record_progress("running", 5, 10), Some(true));
+        assert_eq!(queue.record_progress("waiting", 1, 10), None);
+        let info = queue
+            .list()
+            .into_iter()
+            .find(|job| job.id == running)
+            .unwrap();
+        assert_eq!((info.bytes, info.total), (5, 10));
+
+        let waiter = queue.clone();
+        let handle = std::thread::spawn(move || waiter.wait_turn(waiting, || false));
+        while !cancel.cancel("waiting").unwrap() {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(!handle.join().unwrap());
+    }
+
+    #[test]
+    fn a_paused_running_job_is_held_at_its_checkpoint_until_resumed() {
+        let queue = JobQueue::new(1, CancelState::default());
+        let id = cloud_job(&queue, "a", "held");
+        queue.wait_while_paused("held", || false);
+
+        queue.pause(id).unwrap();
+        let worker = queue.clone();
+        let handle = std::thread::spawn(move || worker.wait_while_paused("held", || false));
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(!handle.is_finished());
+        queue.resume(id).unwrap();
+        handle.join().unwrap();
+        assert_eq!(queue.list()[0].status, JobStatus::Running);
+    }
+
+    #[test]
+    fn bandwidth_limits_follow_the_job_until_it_finishes() {
+        let queue = JobQueue::new(1, CancelState::default());
+        let id = cloud_job(&queue, "a", "limited-job");
+        queue.set_bandwidth_limit(id, Some(4096)).unwrap();
+        assert_eq!(queue.list()[0].bandwidth_limit, Some(4096));
+        assert_eq!(bandwidth::limit_for(Some("limited-job")), Some(4096));
+
+        queue.finish(id, None);
+        assert_eq!(bandwidth::limit_for(Some("limited-job")), None);
+        assert_eq!(
+            queue.set_bandwidth_limit(id, Some(1)).unwrap_err().code(),
+            TaskErrorCode::InvalidJobState
+        );
+    }
+
+    #[test]
+    fn only_the_newest_finished_jobs_are_kept() {
+        let queue = JobQueue::new(8, CancelState::default());
+        let ids = (0..FINISHED_JOBS_KEPT + 3)
+            .map(|n| cloud_job(&queue, "a", &format!("job-{n}")))
+            .collect::<Vec<_>>();
+        for id in &ids {
+            queue.finish(*id, None);
+        }
+        let listed = queue.list();
+        assert_eq!(listed.len(), FINISHED_JOBS_KEPT);
+        assert_eq!(listed[0].id, ids[3]);
+    }
+}
@@ -7,11 +7,18 @@ use std::{
 };
 use tracing::warn;
 
+mod device;
 mod error;
+mod jobs;
 
 use crate::errors::api_error::ApiResult;
 use error::map_api_result;
 pub use error::{TaskError, TaskErrorCode, TaskResult};
+pub use jobs::{
+    list_jobs, move_job, pause_job, resume_job, run_job, set_job_bandwidth_limit, JobKind,
+    JobQueue, JobSpec, DEFAULT_JOBS_PER_DEVICE,
+};
+pub(crate) use jobs::{observe_progress, pause_checkpoint};
 
 #[derive(Clone, Default)]
 pub struct CancelState {
@@ -70,6 +77,15 @@ impl CancelState {
         Ok(map.len())
     }
 
+    /// Whether the task registered as `id` was asked to stop.
+    pub fn is_cancelled(&self, id: &str) -> bool {
+        self.inner
+            .lock()
+            .ok()
+            .and_then(|map| map.get(id).map(|flag| flag.load(Ordering::Relaxed)))
+            .unwrap_or(false)
+    }
+
     fn remove(&self, id: &str) {
         match self.inner.lock() {
             Ok(mut map) => {
@@ -0,0 +1,65 @@
+use crate::errors::{
+    api_error::ApiResult,
+    domain::{self, DomainError, ErrorCode},
+};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferJournalErrorCode {
+    StateLockFailed,
+    JobNotFound,
+    TaskFailed,
+}
+
+impl ErrorCode for TransferJournalErrorCode {
+    fn as_code_str(self) -> &'static str {
+        match self {
+            Self::StateLockFailed => "state_lock_failed",
+            Self::JobNotFound => "job_not_found",
+            Self::TaskFailed => "task_failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TransferJournalError {
+    code: TransferJournalErrorCode,
+    message: String,
+}
+
+impl TransferJournalError {
+    pub fn new(code: TransferJournalErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn code(&self) -> TransferJournalErrorCode {
+        self.code
+    }
+}
+
+impl fmt::Display for TransferJournalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for TransferJournalError {}
+
+impl DomainError for TransferJournalError {
+    fn code_str(&self) -> &'static str {
+        self.code.as_code_str()
+    }
+
+    fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+pub type TransferJournalResult<T> = Result<T, TransferJournalError>;
+
+pub fn map_api_result<T>(result: TransferJournalResult<T>) -> ApiResult<T> {
+    domain::map_api_result(result)
+}
@@ -0,0 +1,638 @@
+//! Journal of running copies and moves. A paste or a mixed local/cloud transfer saves its plan,
+//! the items it finished and how far the current file got, so a job cut short by an app exit or
+//! a crash can be resumed or rolled back on the next start.
+
+mod error;
+mod store;
+#[cfg(test)]
+mod tests;
+
+pub use error::{
+    map_api_result, TransferJournalError, TransferJournalErrorCode, TransferJournalResult,
+};
+
+use std::collections::HashSet;
+use std::fs::{self, File, Metadata};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::errors::api_error::ApiResult;
+use crate::fs_utils::{ConflictPolicies, PreserveOptions, VerifyMode, VerifyReport};
+use crate::tasks::CancelState;
+use crate::undo::{backups_of, Action, UndoState};
+
+/// Finished items inside a merged folder are saved at most this often; top-level items and
+/// backups of replaced targets are saved right away.
+const SAVE_INTERVAL: Duration = Duration::from_millis(500);
+/// How often the offset of the file being copied is flushed and saved.
+const PARTIAL_INTERVAL: Duration = Duration::from_secs(2);
+
+static NEXT_JOB: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferOp {
+    Copy,
+    Move,
+}
+
+/// What the job was asked to do, enough to run the rest of it again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TransferPlan {
+    /// A clipboard paste of local items.
+    Paste {
+        sources: Vec<PathBuf>,
+        dest: PathBuf,
+        policies: ConflictPolicies,
+        preserve: PreserveOptions,
+        verify: Option<VerifyMode>,
+    },
+    /// A batch between local folders and a cloud remote.
+    Mixed {
+        sources: Vec<String>,
+        dest: String,
+        overwrite: bool,
+        prechecked: bool,
+        policies: Option<ConflictPolicies>,
+        verify: Option<VerifyMode>,
+    },
+}
+
+impl TransferPlan {
+    /// The sources and destination of the job, as the frontend shows them.
+    pub fn paths(&self) -> (Vec<String>, String) {
+        match self {
+            Self::Paste { sources, dest, .. } => (
+                sources
+                    .iter()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .collect(),
+                dest.to_string_lossy().to_string(),
+            ),
+            Self::Mixed { sources, dest, .. } => (sources.clone(), dest.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub src: String,
+    pub target: String,
+    /// Whether the target did not exist before the job wrote it, so a rollback may remove it.
+    pub fresh: bool,
+    /// For a move, the copy is complete and only the source was still being removed.
+    #[serde(default)]
+    pub copied: bool,
+}
+
+/// How far the file being copied had got. Bytes up to `offset` were flushed to disk before the
+/// journal recorded them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartialFile {
+    pub src: PathBuf,
+    pub dest: PathBuf,
+    pub offset: u64,
+    pub src_len: u64,
+    pub src_modified: Option<SystemTime>,
+}
+
+impl PartialFile {
+    /// Whether the copy can be continued from `offset`: the source still has the size and
+    /// modification time it had, and the copy still holds the recorded bytes.
+    pub fn resumable(&self) -> bool {
+        let (Ok(src), Ok(dest)) = (fs::metadata(&self.src), fs::symlink_metadata(&self.dest))
+        else {
+            return false;
+        };
+        src.len() == self.src_len
+            && src.modified().ok() == self.src_modified
+            && dest.is_file()
+            && dest.len() >= self.offset
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferJob {
+    pub version: u32,
+    pub id: String,
+    pub op: TransferOp,
+    pub started_at: i64,
+    pub plan: TransferPlan,
+    /// Top-level sources finished or skipped, counted from the start of the plan.
+    pub next: usize,
+    /// The item being written; its target may be incomplete.
+    pub entry: Option<JournalEntry>,
+    pub partial: Option<PartialFile>,
+    /// Undo actions of a paste so far; a rollback runs them backwards.
+    #[serde(default)]
+    pub performed: Vec<Action>,
+    /// Items a mixed transfer finished writing.
+    #[serde(default)]
+    pub written: Vec<JournalEntry>,
+    #[serde(default)]
+    pub created: Vec<String>,
+    #[serde(default)]
+    pub skipped: Vec<String>,
+}
+
+/// Write side of a job's journal. A disabled journal, or one whose first save failed, records
+/// nothing, so a transfer never fails because its journal could not be written.
+#[derive(Default)]
+pub struct TransferJournal {
+    writer: Option<Mutex<JournalWriter>>,
+}
+
+struct JournalWriter {
+    path: PathBuf,
+    job: TransferJob,
+    last_save: Instant,
+    last_partial: Instant,
+    /// Items inside merged folders that are finished, including those a resumed paste
+    /// finished before it was interrupted.
+    done_sources: HashSet<PathBuf>,
+}
+
+impl JournalWriter {
+    fn save(&mut self) {
+        if let Err(err) = store::store_job(&self.path, &self.job) {
+            warn!("Failed to save transfer journal {:?}: {}", self.path, err);
+        }
+        self.last_save = Instant::now();
+    }
+
+    fn save_if_due(&mut self) {
+        if self.last_save.elapsed() >= SAVE_INTERVAL {
+            self.save();
+        }
+    }
+
+    fn checkpoint(
+        &mut self,
+        src: &Path,
+        src_meta: &Metadata,
+        dest: &Path,
+        writer: &File,
+        offset: u64,
+    ) -> bool {
+        self.last_partial = Instant::now();
+        if let Err(err) = writer.sync_data() {
+            warn!("Failed to flush partial copy {:?}: {}", dest, err);
+            return false;
+        }
+        self.job.partial = Some(PartialFile {
+            src: src.to_path_buf(),
+            dest: dest.to_path_buf(),
+            offset,
+            src_len: src_meta.len(),
+            src_modified: src_meta.modified().ok(),
+        });
+        self.save();
+        true
+    }
+}
+
+impl TransferJournal {
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    pub fn begin(op: TransferOp, plan: TransferPlan) -> Self {
+        let job = TransferJob {
+            version: store::JOB_VERSION,
+            id: next_job_id(),
+            op,
+            started_at: chrono::Utc::now().timestamp_millis(),
+            plan,
+            next: 0,
+            entry: None,
+            partial: None,
+            performed: Vec::new(),
+            written: Vec::new(),
+            created: Vec::new(),
+            skipped: Vec::new(),
+        };
+        Self::open(&store::journal_dir(), job)
+    }
+
+    /// Reopens the journal of an interrupted job. It keeps its id, so a resume that is cut short
+    /// again is offered from where it stopped.
+    pub fn resume(job: TransferJob) -> Self {
+        Self::open(&store::journal_dir(), job)
+    }
+
+    fn open(dir: &Path, job: TransferJob) -> Self {
+        let path = store::job_path(dir, &job.id);
+        if let Err(err) = store::store_job(&path, &job) {
+            warn!("Transfer continues without a journal {:?}: {}", path, err);
+            return Self::disabled();
+        }
+        let done_sources = job
+            .performed
+            .iter()
+            .filter_map(|action| match action {
+                Action::Copy { from, .. } | Action::Move { from, .. } => Some(from.clone()),
+                _ => None,
+            })
+            .collect();
+        Self {
+            writer: Some(Mutex::new(JournalWriter {
+                path,
+                job,
+                last_save: Instant::now(),
+                last_partial: Instant::now(),
+                done_sources,
+            })),
+        }
+    }
+
+    fn with_writer<T>(&self, f: impl FnOnce(&mut JournalWriter) -> T) -> Option<T> {
+        let mut writer = self.writer.as_ref()?.lock().ok()?;
+        Some(f(&mut writer))
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    /// Whether `src`, inside a folder that a resumed paste merges again, is already finished.
+    pub fn is_done(&self, src: &Path) -> bool {
+        self.with_writer(|w| w.done_sources.contains(src))
+            .unwrap_or(false)
+    }
+
+    /// The target a resumed mixed transfer was writing for `src` when it stopped, and whether
+    /// that target was new.
+    pub fn pending_target(&self, src: &str) -> Option<(String, bool)> {
+        self.with_writer(|w| {
+            w.job
+                .entry
+                .as_ref()
+                .filter(|entry| entry.src == src)
+                .map(|entry| (entry.target.clone(), entry.fresh))
+        })
+        .flatten()
+    }
+
+    /// A top-level item starts writing `target`.
+    pub fn begin_item(&self, src: &str, target: &str, fresh: bool) {
+        self.with_writer(|w| {
+            w.job.entry = Some(JournalEntry {
+                src: src.to_string(),
+                target: target.to_string(),
+                fresh,
+                copied: false,
+            });
+            w.job.partial = None;
+            w.save();
+        });
+    }
+
+    /// An item inside a merged folder starts writing `target`.
+    pub fn begin_entry(&self, src: &Path, target: &Path) {
+        self.with_writer(|w| {
+            w.job.entry = Some(JournalEntry {
+                src: src.to_string_lossy().to_string(),
+                target: target.to_string_lossy().to_string(),
+                fresh: true,
+                copied: false,
+            });
+            w.job.partial = None;
+            w.save_if_due();
+        });
+    }
+
+    /// The item being moved is fully copied; only its source is left to remove.
+    pub fn entry_copied(&self) {
+        self.with_writer(|w| {
+            if let Some(entry) = w.job.entry.as_mut() {
+                entry.copied = true;
+            }
+            w.job.partial = None;
+            w.save();
+        });
+    }
+
+    /// An item inside a merged folder is finished and `performed` holds its undo action.
+    pub fn end_entry(&self, performed: &[Action]) {
+        self.with_writer(|w| {
+            if let Some(entry) = w.job.entry.take() {
+                w.done_sources.insert(PathBuf::from(entry.src));
+            }
+            w.job.partial = None;
+            if w.last_save.elapsed() >= SAVE_INTERVAL {
+                w.job.performed = performed.to_vec();
+                w.save();
+            }
+        });
+    }
+
+    /// Saves `performed` right away; used after an existing target was moved to a backup that
+    /// only these actions point at.
+    pub fn record_actions(&self, performed: &[Action]) {
+        self.with_writer(|w| {
+            w.job.performed = performed.to_vec();
+            w.save();
+        });
+    }
+
+    /// A top-level paste item is finished or skipped.
+    pub fn item_done(&self, performed: &[Action], created: &[String], skipped: &[String]) {
+        self.with_writer(|w| {
+            w.job.next += 1;
+            w.job.entry = None;
+            w.job.partial = None;
+            w.job.performed = performed.to_vec();
+            w.job.created = created.to_vec();
+            w.job.skipped = skipped.to_vec();
+            w.save();
+        });
+    }
+
+    /// A mixed transfer finished writing `src` to `target`.
+    pub fn item_written(&self, src: &str, target: &str, fresh: bool) {
+        self.with_writer(|w| {
+            w.job.next += 1;
+            w.job.entry = None;
+            w.job.written.push(JournalEntry {
+                src: src.to_string(),
+                target: target.to_string(),
+                fresh,
+                copied: true,
+            });
+            w.job.created.push(target.to_string());
+            w.save();
+        });
+    }
+
+    /// A mixed transfer left `src` alone because of its conflict policy.
+    pub fn item_skipped(&self, src: &str) {
+        self.with_writer(|w| {
+            w.job.next += 1;
+            w.job.skipped.push(src.to_string());
+            w.save();
+        });
+    }
+
+    /// Records now and then how far the copy of `src` into `dest` got.
+    pub fn file_progress(
+        &self,
+        src: &Path,
+        src_meta: &Metadata,
+        dest: &Path,
+        writer: &File,
+        offset: u64,
+    ) {
+        self.with_writer(|w| {
+            if w.last_partial.elapsed() >= PARTIAL_INTERVAL {
+                w.checkpoint(src, src_meta, dest, writer, offset);
+            }
+        });
+    }
+
+    /// Records where the copy of `src` stopped. Returns whether the offset was saved, in which
+    /// case the partial copy should be kept for a resume.
+    pub fn file_interrupted(
+        &self,
+        src: &Path,
+        src_meta: &Metadata,
+        dest: &Path,
+        writer: &File,
+        offset: u64,
+    ) -> bool {
+        self.with_writer(|w| w.checkpoint(src, src_meta, dest, writer, offset))
+            .unwrap_or(false)
+    }
+
+    /// Removes the journal of a job that finished, failed or was cancelled by the user.
+    pub fn finish(self) {
+        if let Some(writer) = self.writer.and_then(|w| w.into_inner().ok()) {
+            store::remove_job(&writer.path);
+        }
+    }
+
+    /// Keeps the journal of a job the app interrupted by exiting, with `performed` as its
+    /// latest undo actions.
+    pub fn suspend(self, performed: &[Action]) {
+        self.with_writer(|w| {
+            if matches!(w.job.plan, TransferPlan::Paste { .. }) {
+                w.job.performed = performed.to_vec();
+            }
+            w.save();
+        });
+    }
+}
+
+fn next_job_id() -> String {
+    format!(
+        "{}-{}-{}",
+        chrono::Utc::now().timestamp_millis(),
+        std::process::id(),
+        NEXT_JOB.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// An interrupted job as the startup prompt shows it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InterruptedTransfer {
+    pub id: String,
+    pub op: TransferOp,
+    pub kind: &'static str,
+    pub sources: Vec<String>,
+    pub dest: String,
+    pub started_at: i64,
+    /// Top-level sources already finished or skipped.
+    pub completed: usize,
+    pub total: usize,
+    /// The item that was being written.
+    pub current: Option<String>,
+}
+
+impl From<&TransferJob> for InterruptedTransfer {
+    fn from(job: &TransferJob) -> Self {
+        let kind = match &job.plan {
+            TransferPlan::Paste { .. } => "paste",
+            TransferPlan::Mixed { .. } => "mixed",
+        };
+        let (sources, dest) = job.plan.paths();
+        Self {
+            id: job.id.clone(),
+            op: job.op,
+            kind,
+            total: sources.len(),
+            sources,
+            dest,
+            started_at: job.started_at,
+            completed: job.next,
+            current: job.entry.as_ref().map(|entry| entry.src.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumeOutcome {
+    pub created: Vec<String>,
+    pub skipped: Vec<String>,
+    pub verify: Option<VerifyReport>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RollbackOutcome {
+    /// Written items left in place because undoing them could lose data: they replaced an
+    /// existing item, or a move had already removed part of the source.
+    pub kept: Vec<String>,
+}
+
+/// Jobs a previous run left unfinished. Loaded once at startup, before any new transfer writes
+/// a journal of its own.
+#[derive(Clone, Default)]
+pub struct TransferJournalState {
+    jobs: Arc<Mutex<Vec<TransferJob>>>,
+}
+
+impl TransferJournalState {
+    pub fn restore() -> Self {
+        Self {
+            jobs: Arc::new(Mutex::new(store::load_jobs(&store::journal_dir()))),
+        }
+    }
+
+    /// Undo backups that interrupted pastes still point at; startup cleanup keeps them so a
+    /// rollback can put replaced items back.
+    pub fn referenced_backups(&self) -> HashSet<PathBuf> {
+        self.jobs
+            .lock()
+            .map(|jobs| backups_of(jobs.iter().flat_map(|job| job.performed.iter())))
+            .unwrap_or_default()
+    }
+
+    fn list(&self) -> TransferJournalResult<Vec<InterruptedTransfer>> {
+        let jobs = self.lock()?;
+        Ok(jobs.iter().map(InterruptedTransfer::from).collect())
+    }
+
+    fn take(&self, id: &str) -> TransferJournalResult<TransferJob> {
+        let mut jobs = self.lock()?;
+        let idx = jobs.iter().position(|job| job.id == id).ok_or_else(|| {
+            TransferJournalError::new(
+                TransferJournalErrorCode::JobNotFound,
+                format!("No interrupted transfer with id {id}"),
+            )
+        })?;
+        Ok(jobs.remove(idx))
+    }
+
+    fn put_back(&self, job: TransferJob) {
+        if let Ok(mut jobs) = self.jobs.lock() {
+            jobs.push(job);
+            jobs.sort_by_key(|job| job.started_at);
+        }
+    }
+
+    fn lock(&self) -> TransferJournalResult<std::sync::MutexGuard<'_, Vec<TransferJob>>> {
+        self.jobs.lock().map_err(|_| {
+            TransferJournalError::new(
+                TransferJournalErrorCode::StateLockFailed,
+                "Transfer journal state poisoned",
+            )
+        })
+    }
+}
+
+/// Removes the journal of `job` without touching what it wrote.
+fn discard_job(job: &TransferJob) {
+    store::remove_job(&store::job_path(&store::journal_dir(), &job.id));
+}
+
+#[tauri::command]
+pub fn list_interrupted_transfers(
+    state: tauri::State<'_, TransferJournalState>,
+) -> ApiResult<Vec<InterruptedTransfer>> {
+    map_api_result(state.list())
+}
+
+/// Runs the rest of an interrupted job. Its journal is reopened, so another interruption leaves
+/// it to be offered again on the next start.
+#[tauri::command]
+pub async fn resume_interrupted_transfer(
+    app: tauri::AppHandle,
+    id: String,
+    state: tauri::State<'_, TransferJournalState>,
+    undo: tauri::State<'_, UndoState>,
+    cancel: tauri::State<'_, CancelState>,
+    progress_event: Option<String>,
+) -> ApiResult<ResumeOutcome> {
+    let job = match state.take(&id) {
+        Ok(job) => job,
+        Err(err) => return map_api_result(Err(err)),
+    };
+    let cancel_state = cancel.inner().clone();
+    match job.plan {
+        TransferPlan::Paste { .. } => {
+            crate::clipboard::resume_paste_job(
+                app,
+                job,
+                undo.clone_inner(),
+                cancel_state,
+                progress_event,
+            )
+            .await
+        }
+        TransferPlan::Mixed { .. } => {
+            crate::commands::transfer::resume_mixed_job(job, app, cancel_state, progress_event)
+                .await
+        }
+    }
+}
+
+/// Undoes what an interrupted job wrote. The job is offered again when the rollback fails.
+#[tauri::command]
+pub async fn rollback_interrupted_transfer(
+    id: String,
+    state: tauri::State<'_, TransferJournalState>,
+) -> ApiResult<RollbackOutcome> {
+    let job = match state.take(&id) {
+        Ok(job) => job,
+        Err(err) => return map_api_result(Err(err)),
+    };
+    let result = match &job.plan {
+        TransferPlan::Paste { .. } => {
+            let paste_job = job.clone();
+            match tauri::async_runtime::spawn_blocking(move || {
+                crate::clipboard::rollback_paste_job(&paste_job)
+            })
+            .await
+            {
+                Ok(result) => result,
+                Err(e) => map_api_result(Err(TransferJournalError::new(
+                    TransferJournalErrorCode::TaskFailed,
+                    format!("Rollback task failed: {e}"),
+                ))),
+            }
+        }
+        TransferPlan::Mixed { .. } => crate::commands::transfer::rollback_mixed_job(&job).await,
+    };
+    if result.is_ok() {
+        discard_job(&job);
+    } else {
+        state.put_back(job);
+    }
+    result
+}
+
+/// Forgets an interrupted job and leaves whatever it wrote as it is.
+#[tauri::command]
+pub fn discard_interrupted_transfer(
+    id: String,
+    state: tauri::State<'_, TransferJournalState>,
+) -> ApiResult<()> {
+    map_api_result(state.take(&id).map(|job| discard_job(&job)))
+}
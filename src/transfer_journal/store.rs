@@ -0,0 +1,85 @@
+//! One JSON file per running transfer. Each save goes through a temporary file and a rename,
+//! like the undo journal, so an exit or crash leaves either the previous or the next state.
+//! Saves are not synced to disk: they only have to outlive the app, and a state lost to a power
+//! cut falls back to an earlier one that resume handles the same way.
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use tracing::warn;
+
+use super::TransferJob;
+
+pub(super) const JOB_VERSION: u32 = 1;
+const JOB_EXTENSION: &str = "json";
+
+pub(super) fn journal_dir() -> PathBuf {
+    if let Ok(custom) = std::env::var("BROWSEY_TRANSFER_JOURNAL_DIR") {
+        return PathBuf::from(custom);
+    }
+    dirs_next::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("browsey")
+        .join("transfers")
+}
+
+pub(super) fn job_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{id}.{JOB_EXTENSION}"))
+}
+
+pub(super) fn store_job(path: &Path, job: &TransferJob) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let content = serde_json::to_vec(job)?;
+    let tmp_path = path.with_extension("json.tmp");
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(&content)?;
+    drop(file);
+    fs::rename(&tmp_path, path)
+}
+
+/// Every job journaled in `dir`, oldest first. Unreadable files and other versions are logged
+/// and left on disk untouched.
+pub(super) fn load_jobs(dir: &Path) -> Vec<TransferJob> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Vec::new(),
+        Err(err) => {
+            warn!("Failed to read transfer journal dir {:?}: {}", dir, err);
+            return Vec::new();
+        }
+    };
+    let mut jobs = Vec::new();
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.extension().and_then(|ext| ext.to_str()) != Some(JOB_EXTENSION) {
+            continue;
+        }
+        let content = match fs::read(&path) {
+            Ok(content) => content,
+            Err(err) => {
+                warn!("Failed to read transfer journal {:?}: {}", path, err);
+                continue;
+            }
+        };
+        match serde_json::from_slice::<TransferJob>(&content) {
+            Ok(job) if job.version == JOB_VERSION => jobs.push(job),
+            Ok(job) => warn!(
+                "Ignoring transfer journal {:?} with unsupported version {}",
+                path, job.version
+            ),
+            Err(err) => warn!("Ignoring malformed transfer journal {:?}: {}", path, err),
+        }
+    }
+    jobs.sort_by_key(|job| job.started_at);
+    jobs
+}
+
+pub(super) fn remove_job(path: &Path) {
+    if let Err(err) = fs::remove_file(path) {
+        if err.kind() != std::io::ErrorKind::NotFound {
+            warn!("Failed to remove transfer journal {:?}: {}", path, err);
+        }
+    }
+}
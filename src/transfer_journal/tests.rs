@@ -0,0 +1,158 @@
+use super::*;
+use std::time::{Duration, SystemTime};
+
+fn uniq_path(label: &str) -> PathBuf {
+    let ts = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or(Duration::from_secs(0))
+        .as_nanos();
+    std::env::temp_dir().join(format!("browsey-transfer-journal-test-{label}-{ts}"))
+}
+
+fn mixed_job(id: &str, started_at: i64) -> TransferJob {
+    TransferJob {
+        version: store::JOB_VERSION,
+        id: id.to_string(),
+        op: TransferOp::Copy,
+        started_at,
+        plan: TransferPlan::Mixed {
+            sources: vec![
+                "/tmp/a.txt".to_string(),
+                "/tmp/b.txt".to_string(),
+                "/tmp/c.txt".to_string(),
+            ],
+            dest: "rclone://work/dest".to_string(),
+            overwrite: false,
+            prechecked: false,
+            policies: None,
+            verify: None,
+        },
+        next: 0,
+        entry: None,
+        partial: None,
+        performed: Vec::new(),
+        written: Vec::new(),
+        created: Vec::new(),
+        skipped: Vec::new(),
+    }
+}
+
+#[test]
+fn journal_records_progress_and_is_removed_when_finished() {
+    let dir = uniq_path("progress");
+    let journal = TransferJournal::open(&dir, mixed_job("job-1", 1));
+    assert!(journal.is_active());
+
+    journal.begin_item("/tmp/a.txt", "rclone://work/dest/a.txt", true);
+    journal.item_written("/tmp/a.txt", "rclone://work/dest/a.txt", true);
+    journal.item_skipped("/tmp/b.txt");
+    journal.begin_item("/tmp/c.txt", "rclone://work/dest/c.txt", false);
+
+    let jobs = store::load_jobs(&dir);
+    assert_eq!(jobs.len(), 1);
+    let job = &jobs[0];
+    assert_eq!(job.next, 2);
+    assert_eq!(job.created, vec!["rclone://work/dest/a.txt".to_string()]);
+    assert_eq!(job.skipped, vec!["/tmp/b.txt".to_string()]);
+    assert_eq!(job.written.len(), 1);
+    assert!(job.written[0].fresh);
+    let entry = job.entry.as_ref().expect("entry in progress");
+    assert_eq!(entry.target, "rclone://work/dest/c.txt");
+    assert!(!entry.fresh);
+
+    let summary = InterruptedTransfer::from(job);
+    assert_eq!(summary.kind, "mixed");
+    assert_eq!(summary.completed, 2);
+    assert_eq!(summary.total, 3);
+    assert_eq!(summary.current.as_deref(), Some("/tmp/c.txt"));
+
+    journal.finish();
+    assert!(store::load_jobs(&dir).is_empty());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn reopened_journal_keeps_its_id_and_pending_target() {
+    let dir = uniq_path("reopen");
+    let journal = TransferJournal::open(&dir, mixed_job("job-2", 1));
+    journal.begin_item("/tmp/a.txt", "rclone://work/dest/a (1).txt", true);
+    journal.suspend(&[]);
+
+    let job = store::load_jobs(&dir).pop().expect("suspended job");
+    let reopened = TransferJournal::open(&dir, job);
+    assert_eq!(
+        reopened.pending_target("/tmp/a.txt"),
+        Some(("rclone://work/dest/a (1).txt".to_string(), true))
+    );
+    assert_eq!(reopened.pending_target("/tmp/b.txt"), None);
+    assert_eq!(
+        TransferJournal::disabled().pending_target("/tmp/a.txt"),
+        None
+    );
+
+    reopened.finish();
+    assert!(!store::job_path(&dir, "job-2").exists());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn partial_file_is_only_resumable_while_the_source_is_unchanged() {
+    let dir = uniq_path("partial");
+    fs::create_dir_all(&dir).unwrap();
+    let src = dir.join("src.bin");
+    let dest = dir.join("dest.bin");
+    fs::write(&src, vec![7u8; 64]).unwrap();
+    fs::write(&dest, vec![7u8; 16]).unwrap();
+    let meta = fs::metadata(&src).unwrap();
+    let partial = PartialFile {
+        src: src.clone(),
+        dest: dest.clone(),
+        offset: 16,
+        src_len: meta.len(),
+        src_modified: meta.modified().ok(),
+    };
+    assert!(partial.resumable());
+
+    assert!(!PartialFile {
+        offset: 32,
+        ..partial.clone()
+    }
+    .resumable());
+
+    fs::write(&src, vec![7u8; 65]).unwrap();
+    assert!(!partial.resumable());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn malformed_and_unknown_version_journals_are_ignored() {
+    let dir = uniq_path("load");
+    fs::create_dir_all(&dir).unwrap();
+    store::store_job(&store::job_path(&dir, "newer"), &mixed_job("newer", 20)).unwrap();
+    store::store_job(&store::job_path(&dir, "older"), &mixed_job("older", 10)).unwrap();
+    let mut future = mixed_job("future", 5);
+    future.version = store::JOB_VERSION + 1;
+    store::store_job(&store::job_path(&dir, "future"), &future).unwrap();
+    fs::write(dir.join("broken.json"), b"{not json").unwrap();
+    fs::write(dir.join("older.json.tmp"), b"{}").unwrap();
+
+    let ids = store::load_jobs(&dir)
+        .into_iter()
+        .map(|job| job.id)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec!["older".to_string(), "newer".to_string()]);
+    assert!(dir.join("broken.json").exists());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn unknown_job_ids_are_reported() {
+    let state = TransferJournalState::default();
+    state.put_back(mixed_job("kept", 1));
+
+    let err = state.take("missing").unwrap_err();
+    assert_eq!(err.code(), TransferJournalErrorCode::JobNotFound);
+    assert_eq!(state.list().unwrap().len(), 1);
+    assert_eq!(state.take("kept").unwrap().id, "kept");
+    assert!(state.list().unwrap().is_empty());
+}
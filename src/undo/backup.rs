@@ -1,17 +1,29 @@
 use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
 use std::fs;
 use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tracing::{debug, warn};
 
+use super::journal::JOURNAL_FILE;
 use crate::undo::{UndoError, UndoResult};
 
+/// Unreferenced backups are moved under this directory of the undo directory, one
+/// subdirectory per startup named after its time in milliseconds.
+const RECOVERED_DIR: &str = "recovered";
+/// How long unreferenced backups are kept before they are removed for good.
+const RECOVERED_RETENTION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
 /// Best-effort cleanup of stale `.browsey-undo` directories. Runs at startup to
-/// avoid leaving orphaned backups after a crash or restart (undo history is
-/// in-memory only).
-pub fn cleanup_stale_backups(max_age: Option<Duration>) {
-    let _ = max_age; // keep the signature; we remove everything regardless.
+/// avoid leaving orphaned backups after a crash or restart. Backups in `keep` are
+/// still referenced by the undo journal and survive, as does the journal itself.
+///
+/// An item is moved to its backup before the journal entry pointing at it is written,
+/// so a crash in between leaves the only copy of a deleted item unreferenced. Such
+/// backups are moved under [`RECOVERED_DIR`] instead of being deleted, and only
+/// removed once they are older than [`RECOVERED_RETENTION`].
+pub fn cleanup_stale_backups(keep: &HashSet<PathBuf>) {
     let base = base_undo_dir();
 
     if let Err(e) = validate_undo_dir(&base) {
@@ -20,18 +32,26 @@ pub fn cleanup_stale_backups(max_age: Option<Duration>) {
     }
 
     if base.exists() {
+        let recovered = base.join(RECOVERED_DIR);
+        prune_recovered(&recovered, SystemTime::now());
+        let session = recovered.join(unix_millis(SystemTime::now()).to_string());
+        let journal_tmp = base.join(JOURNAL_FILE).with_extension("json.tmp");
         match fs::read_dir(&base) {
             Ok(entries) => {
                 for entry in entries.flatten() {
                     let path = entry.path();
-                    let res = if path.is_dir() {
-                        fs::remove_dir_all(&path)
-                    } else {
-                        fs::remove_file(&path)
-                    };
-                    if let Err(err) = res {
-                        warn!("Failed to remove {:?}: {}", path, err);
+                    if path == base.join(JOURNAL_FILE) || path == recovered {
+                        continue;
+                    }
+                    if path == journal_tmp {
+                        remove_backup_path(&path);
+                        continue;
                     }
+                    if path.is_dir() && keep.iter().any(|kept| kept.starts_with(&path)) {
+                        quarantine_unreferenced(&base, &session, &path, keep);
+                        continue;
+                    }
+                    quarantine_backup(&base, &session, &path);
                 }
                 debug!("Cleaned contents of backup directory {:?}", base);
             }
@@ -44,6 +64,77 @@ pub fn cleanup_stale_backups(max_age: Option<Duration>) {
     }
 }
 
+/// Quarantines the children of a backup bucket that no journal entry points at.
+fn quarantine_unreferenced(base: &Path, session: &Path, bucket: &Path, keep: &HashSet<PathBuf>) {
+    match fs::read_dir(bucket) {
+        Ok(entries) => {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if !keep.contains(&path) {
+                    quarantine_backup(base, session, &path);
+                }
+            }
+        }
+        Err(e) => warn!("Failed to read backup directory {:?}: {}", bucket, e),
+    }
+}
+
+/// Moves a backup under `session`, at the same path it had below `base`. A backup that
+/// cannot be moved stays where it is rather than being deleted.
+fn quarantine_backup(base: &Path, session: &Path, path: &Path) {
+    let Ok(rel) = path.strip_prefix(base) else {
+        return;
+    };
+    let dest = session.join(rel);
+    let moved = dest
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::rename(path, &dest));
+    match moved {
+        Ok(()) => warn!(
+            "Moved unreferenced undo backup {:?} to {:?}; it is removed after {} days",
+            path,
+            dest,
+            RECOVERED_RETENTION.as_secs() / (24 * 60 * 60)
+        ),
+        Err(e) => warn!("Failed to quarantine undo backup {:?}: {}", path, e),
+    }
+}
+
+/// Removes quarantined backups older than [`RECOVERED_RETENTION`].
+fn prune_recovered(recovered: &Path, now: SystemTime) {
+    let Ok(entries) = fs::read_dir(recovered) else {
+        return;
+    };
+    let cutoff = unix_millis(now).saturating_sub(RECOVERED_RETENTION.as_millis() as u64);
+    for entry in entries.flatten() {
+        let stamp = entry
+            .file_name()
+            .to_str()
+            .and_then(|s| s.parse::<u64>().ok());
+        if stamp.is_some_and(|stamp| stamp < cutoff) {
+            remove_backup_path(&entry.path());
+        }
+    }
+}
+
+fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn remove_backup_path(path: &Path) {
+    let res = if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    };
+    if let Err(err) = res {
+        warn!("Failed to remove {:?}: {}", path, err);
+    }
+}
+
 #[allow(dead_code)]
 pub fn temp_backup_path(original: &Path) -> PathBuf {
     let base = base_undo_dir();
@@ -70,7 +161,19 @@ pub fn temp_backup_path(original: &Path) -> PathBuf {
     candidate
 }
 
-fn base_undo_dir() -> PathBuf {
+/// Total size of the files under a backup path; 0 when it does not exist.
+pub(super) fn backup_size(path: &Path) -> u64 {
+    walkdir::WalkDir::new(path)
+        .follow_links(false)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|meta| meta.is_file())
+        .map(|meta| meta.len())
+        .sum()
+}
+
+pub(super) fn base_undo_dir() -> PathBuf {
     if let Ok(custom) = std::env::var("BROWSEY_UNDO_DIR") {
         return PathBuf::from(custom);
     }
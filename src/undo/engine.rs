@@ -1,5 +1,6 @@
 use super::nofollow::delete_entry_nofollow_io;
 use super::path_ops::{copy_entry, delete_entry_path, move_with_fallback};
+use super::security::{apply_ownership, apply_permissions};
 use super::{Action, Direction};
 use crate::undo::error::UndoErrorCode;
 use crate::undo::{UndoError, UndoResult};
@@ -72,6 +73,22 @@ pub(super) fn execute_action(action: &mut Action, direction: Direction) -> UndoR
             };
             set_windows_hidden_attr(path, next)
         }
+        Action::SetPermissions {
+            path,
+            before,
+            after,
+        } => match direction {
+            Direction::Forward => apply_permissions(path, after),
+            Direction::Backward => apply_permissions(path, before),
+        },
+        Action::SetOwnership {
+            path,
+            before,
+            after,
+        } => match direction {
+            Direction::Forward => apply_ownership(path, after),
+            Direction::Backward => apply_ownership(path, before),
+        },
         Action::CreateFolder { path } => match direction {
             Direction::Forward => Ok(fs::create_dir(&*path).map_err(|e| {
                 UndoError::from_io_error(
@@ -19,6 +19,7 @@ pub enum UndoErrorCode {
     SnapshotMismatch,
     UndoUnavailable,
     RedoUnavailable,
+    HistoryConflict,
     LockFailed,
     IoError,
 }
@@ -37,6 +38,7 @@ impl ErrorCode for UndoErrorCode {
             Self::SnapshotMismatch => "snapshot_mismatch",
             Self::UndoUnavailable => "undo_unavailable",
             Self::RedoUnavailable => "redo_unavailable",
+            Self::HistoryConflict => "history_conflict",
             Self::LockFailed => "lock_failed",
             Self::IoError => "io_error",
         }
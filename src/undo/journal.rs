@@ -0,0 +1,170 @@
+//! On-disk undo history. The journal lives next to the backups it references and is rewritten
+//! through a temporary file and a rename, so a crash leaves either the old or the new history.
+
+use std::collections::{HashSet, VecDeque};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::backup::base_undo_dir;
+use super::types::HistoryEntry;
+use super::Action;
+use crate::undo::{UndoError, UndoResult};
+
+pub(super) const JOURNAL_FILE: &str = "history.json";
+const JOURNAL_VERSION: u32 = 1;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(super) struct JournalData {
+    pub version: u32,
+    pub next_id: u64,
+    /// Oldest first; the last entry is undone next.
+    pub undo: VecDeque<HistoryEntry>,
+    /// The last entry is redone next.
+    pub redo: VecDeque<HistoryEntry>,
+}
+
+#[derive(Serialize)]
+struct JournalView<'a> {
+    version: u32,
+    next_id: u64,
+    undo: &'a VecDeque<HistoryEntry>,
+    redo: &'a VecDeque<HistoryEntry>,
+}
+
+pub(super) fn journal_path() -> PathBuf {
+    base_undo_dir().join(JOURNAL_FILE)
+}
+
+/// Reads the journal at `path`. A missing journal is an empty history; an unreadable one is
+/// logged and treated the same, since the backups it described cannot be trusted either.
+pub(super) fn load_journal(path: &Path) -> JournalData {
+    let content = match fs::read(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return JournalData::default(),
+        Err(err) => {
+            warn!("Failed to read undo journal {:?}: {}", path, err);
+            return JournalData::default();
+        }
+    };
+    match serde_json::from_slice::<JournalData>(&content) {
+        Ok(data) if data.version == JOURNAL_VERSION => data,
+        Ok(data) => {
+            warn!(
+                "Ignoring undo journal {:?} with unsupported version {}",
+                path, data.version
+            );
+            JournalData::default()
+        }
+        Err(err) => {
+            warn!("Ignoring malformed undo journal {:?}: {}", path, err);
+            JournalData::default()
+        }
+    }
+}
+
+pub(super) fn store_journal(
+    path: &Path,
+    next_id: u64,
+    undo: &VecDeque<HistoryEntry>,
+    redo: &VecDeque<HistoryEntry>,
+) -> UndoResult<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| {
+            UndoError::from_io_error(
+                format!(
+                    "Failed to create undo journal directory {}",
+                    parent.display()
+                ),
+                e,
+            )
+        })?;
+    }
+    let content = serde_json::to_vec(&JournalView {
+        version: JOURNAL_VERSION,
+        next_id,
+        undo,
+        redo,
+    })
+    .map_err(|e| UndoError::invalid_input(format!("Failed to encode undo journal: {e}")))?;
+
+    let tmp_path = path.with_extension("json.tmp");
+    let write_tmp = || -> std::io::Result<()> {
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(&content)?;
+        file.sync_all()
+    };
+    write_tmp().map_err(|e| {
+        UndoError::from_io_error(
+            format!(
+                "Failed to write temporary undo journal {}",
+                tmp_path.display()
+            ),
+            e,
+        )
+    })?;
+    fs::rename(&tmp_path, path).map_err(|e| {
+        UndoError::from_io_error(
+            format!("Failed to finalize undo journal {}", path.display()),
+            e,
+        )
+    })
+}
+
+/// Drops entries that can no longer be replayed because a backup they move back from is gone.
+pub(super) fn retain_recoverable(data: &mut JournalData) {
+    let before = data.undo.len() + data.redo.len();
+    data.undo
+        .retain(|entry| backups_present(&entry.action, BackupSide::Undo));
+    data.redo
+        .retain(|entry| backups_present(&entry.action, BackupSide::Redo));
+    let dropped = before - data.undo.len() - data.redo.len();
+    if dropped > 0 {
+        warn!("Dropped {dropped} undo history entries with missing backups");
+    }
+}
+
+/// Backup paths that the retained history still points at, whether or not they exist yet.
+pub(super) fn referenced_backups<'a>(
+    entries: impl IntoIterator<Item = &'a HistoryEntry>,
+) -> HashSet<PathBuf> {
+    backups_of(entries.into_iter().map(|entry| &entry.action))
+}
+
+/// Backup paths that `actions` move items to or back from.
+pub(crate) fn backups_of<'a>(actions: impl IntoIterator<Item = &'a Action>) -> HashSet<PathBuf> {
+    let mut out = HashSet::new();
+    for action in actions {
+        collect_backups(action, &mut out);
+    }
+    out
+}
+
+#[derive(Clone, Copy)]
+enum BackupSide {
+    Undo,
+    Redo,
+}
+
+fn backups_present(action: &Action, side: BackupSide) -> bool {
+    match (action, side) {
+        (Action::Batch(actions), _) => actions.iter().all(|a| backups_present(a, side)),
+        // Undoing a delete and redoing a create both move the backup back into place.
+        (Action::Delete { backup, .. }, BackupSide::Undo)
+        | (Action::Create { backup, .. }, BackupSide::Redo) => fs::symlink_metadata(backup).is_ok(),
+        _ => true,
+    }
+}
+
+fn collect_backups(action: &Action, out: &mut HashSet<PathBuf>) {
+    match action {
+        Action::Batch(actions) => actions.iter().for_each(|a| collect_backups(a, out)),
+        Action::Create { backup, .. } | Action::Delete { backup, .. } => {
+            out.insert(backup.clone());
+        }
+        _ => {}
+    }
+}
@@ -1,6 +1,7 @@
 mod backup;
 mod engine;
 mod error;
+mod journal;
 mod nofollow;
 mod path_checks;
 mod path_ops;
@@ -20,10 +21,12 @@ pub(crate) use security::{apply_ownership, apply_permissions, set_ownership_nofo
 pub use security::{ownership_snapshot, permissions_snapshot};
 pub(crate) use types::PathSnapshot;
 pub use types::{
-    Action, Direction, OwnershipSnapshot, PermissionsSnapshot, UndoManager, UndoState,
+    Action, Direction, OwnershipSnapshot, PermissionsSnapshot, UndoHistoryItem, UndoManager,
+    UndoState,
 };
 
 pub(crate) use engine::run_actions;
+pub(crate) use journal::backups_of;
 pub(crate) use path_checks::{assert_path_snapshot, snapshot_existing_path};
 pub(crate) use path_ops::{copy_entry, delete_entry_path, is_destination_exists_error};
 
@@ -39,3 +42,23 @@ pub fn undo_action(state: tauri::State<'_, UndoState>) -> ApiResult<()> {
 pub fn redo_action(state: tauri::State<'_, UndoState>) -> ApiResult<()> {
     error::map_api_result(state.redo())
 }
+
+#[tauri::command]
+pub fn list_undo_history(state: tauri::State<'_, UndoState>) -> ApiResult<Vec<UndoHistoryItem>> {
+    error::map_api_result(state.history())
+}
+
+#[tauri::command]
+pub fn undo_to_history_entry(state: tauri::State<'_, UndoState>, id: u64) -> ApiResult<usize> {
+    error::map_api_result(state.undo_to(id))
+}
+
+#[tauri::command]
+pub fn redo_to_history_entry(state: tauri::State<'_, UndoState>, id: u64) -> ApiResult<usize> {
+    error::map_api_result(state.redo_to(id))
+}
+
+#[tauri::command]
+pub fn undo_history_entry(state: tauri::State<'_, UndoState>, id: u64) -> ApiResult<()> {
+    error::map_api_result(state.undo_entry(id))
+}
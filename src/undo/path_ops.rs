@@ -2,6 +2,9 @@ use std::fs;
 use std::io::{self, ErrorKind};
 use std::path::Path;
 
+use tracing::warn;
+
+use crate::fs_utils::{MetadataPreserver, PreserveOptions};
 use crate::undo::error::UndoErrorCode;
 use crate::undo::{UndoError, UndoResult};
 
@@ -12,7 +15,25 @@ use super::path_checks::{
 };
 use super::types::{self, PathSnapshot};
 
+/// Copies `src` to `dest`, carrying times, mode, extended attributes and ACLs over. Attributes
+/// the destination cannot take are logged rather than failing the copy.
 pub(crate) fn copy_entry(src: &Path, dest: &Path) -> UndoResult<()> {
+    let preserve = MetadataPreserver::new(PreserveOptions::ALL);
+    let result = copy_entry_preserving(src, dest, &preserve);
+    for item in preserve.into_report() {
+        warn!(
+            "Could not preserve {:?} for {} path(s) while copying {} (first: {}): {}",
+            item.attribute,
+            item.count,
+            src.display(),
+            item.path,
+            item.reason
+        );
+    }
+    result
+}
+
+fn copy_entry_preserving(src: &Path, dest: &Path, preserve: &MetadataPreserver) -> UndoResult<()> {
     let meta = ensure_existing_path_nonsymlink(src)?;
     let src_snapshot = types::path_snapshot_from_meta(&meta);
     if let Some(parent) = dest.parent() {
@@ -20,17 +41,17 @@ pub(crate) fn copy_entry(src: &Path, dest: &Path) -> UndoResult<()> {
     }
     if meta.is_dir() {
         assert_path_snapshot(src, &src_snapshot)?;
-        copy_dir(src, dest)
+        copy_dir(src, dest, preserve)
     } else {
         if let Some(parent) = dest.parent() {
             ensure_existing_dir_nonsymlink(parent)?;
         }
         assert_path_snapshot(src, &src_snapshot)?;
-        copy_file_noreplace(src, dest)
+        copy_file_noreplace(src, dest, preserve)
     }
 }
 
-fn copy_file_noreplace(src: &Path, dest: &Path) -> UndoResult<()> {
+fn copy_file_noreplace(src: &Path, dest: &Path, preserve: &MetadataPreserver) -> UndoResult<()> {
     let mut src_file = fs::File::open(src).map_err(|e| {
         UndoError::from_io_error(format!("Failed to open source file {}", src.display()), e)
     })?;
@@ -58,24 +79,12 @@ fn copy_file_noreplace(src: &Path, dest: &Path) -> UndoResult<()> {
             e,
         )
     })?;
-    let perms = src_file
-        .metadata()
-        .map_err(|e| {
-            UndoError::from_io_error(
-                format!("Failed to read source permissions {}", src.display()),
-                e,
-            )
-        })?
-        .permissions();
-    fs::set_permissions(dest, perms).map_err(|e| {
-        UndoError::from_io_error(
-            format!("Failed to set permissions on {}", dest.display()),
-            e,
-        )
-    })
+    drop(dst_file);
+    preserve.copy_from(src, dest);
+    Ok(())
 }
 
-fn copy_dir(src: &Path, dest: &Path) -> UndoResult<()> {
+fn copy_dir(src: &Path, dest: &Path, preserve: &MetadataPreserver) -> UndoResult<()> {
     let src_snapshot = snapshot_existing_path(src)?;
     if let Some(parent) = dest.parent() {
         ensure_existing_dir_nonsymlink(parent)?;
@@ -98,12 +107,13 @@ fn copy_dir(src: &Path, dest: &Path) -> UndoResult<()> {
         let target = dest.join(entry.file_name());
         if meta.is_dir() {
             assert_path_snapshot(&path, &child_snapshot)?;
-            copy_dir(&path, &target)?;
+            copy_dir(&path, &target, preserve)?;
         } else {
             assert_path_snapshot(&path, &child_snapshot)?;
-            copy_file_noreplace(&path, &target)?;
+            copy_file_noreplace(&path, &target, preserve)?;
         }
     }
+    preserve.copy_from(src, dest);
     Ok(())
 }
 
@@ -344,13 +344,111 @@ fn cleanup_prunes_stale_backup_dirs() {
     let base = test_undo_dir();
     let target = base.join("dummy");
     fs::create_dir_all(&target).unwrap();
+    let kept = base.join("bucket-kept").join("kept.txt");
+    let stale = base.join("bucket-kept").join("stale.txt");
+    write_file(&kept, b"kept");
+    write_file(&stale, b"stale");
 
-    cleanup_stale_backups(Some(Duration::from_secs(0)));
+    cleanup_stale_backups(&std::collections::HashSet::from([kept.clone()]));
 
     assert!(
         !target.exists(),
         "backup base contents should be removed during cleanup"
     );
+    assert!(kept.exists(), "journaled backups should survive cleanup");
+    assert!(!stale.exists());
+    let quarantined = |rel: &Path| {
+        fs::read_dir(base.join("recovered"))
+            .unwrap()
+            .flatten()
+            .any(|session| session.path().join(rel).exists())
+    };
+    assert!(
+        quarantined(Path::new("dummy")),
+        "unreferenced backups should be quarantined, not deleted"
+    );
+    assert!(quarantined(&Path::new("bucket-kept").join("stale.txt")));
+    let _ = fs::remove_dir_all(base.join("bucket-kept"));
+}
+
+#[test]
+fn cleanup_removes_quarantined_backups_only_after_retention() {
+    let base = test_undo_dir();
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+    let day = 24 * 60 * 60 * 1000;
+    let expired = base
+        .join("recovered")
+        .join((now - 31 * day).to_string())
+        .join("old.txt");
+    let recent = base
+        .join("recovered")
+        .join((now - day).to_string())
+        .join("recent.txt");
+    write_file(&expired, b"old");
+    write_file(&recent, b"recent");
+
+    cleanup_stale_backups(&std::collections::HashSet::new());
+
+    assert!(!expired.exists(), "expired quarantine should be removed");
+    assert!(recent.exists(), "recent quarantine should be kept");
+    let _ = fs::remove_dir_all(recent.parent().unwrap());
+}
+
+#[test]
+fn journal_restores_history_and_drops_entries_with_missing_backups() {
+    let dir = uniq_path("journal");
+    let _ = fs::create_dir_all(&dir);
+    let journal = dir.join("history.json");
+    let from = dir.join("a.txt");
+    let to = dir.join("b.txt");
+    write_file(&from, b"content");
+
+    let mut mgr = UndoManager::with_journal(journal.clone());
+    mgr.apply(Action::Rename {
+        from: from.clone(),
+        to: to.clone(),
+    })
+    .expect("rename should apply");
+    mgr.record_applied(Action::Delete {
+        path: dir.join("gone.txt"),
+        backup: dir.join("missing-backup"),
+    });
+    mgr.record_applied(Action::Batch(vec![
+        Action::CreateFolder {
+            path: dir.join("one"),
+        },
+        Action::CreateFolder {
+            path: dir.join("two"),
+        },
+    ]));
+    mgr.undo().expect("undo batch");
+    drop(mgr);
+
+    let mut restored = UndoManager::with_journal(journal.clone());
+    let history = restored.history();
+    assert_eq!(history.len(), 2, "delete without backup should be dropped");
+    assert_eq!(history[0].kind, "rename");
+    assert_eq!(
+        history[0].paths,
+        vec![
+            from.to_string_lossy().into_owned(),
+            to.to_string_lossy().into_owned()
+        ]
+    );
+    assert!(!history[0].undone);
+    assert_eq!(history[1].kind, "batch");
+    assert_eq!(history[1].action_count, 2);
+    assert!(history[1].undone);
+    assert!(history[1].id > history[0].id);
+
+    restored.undo().expect("restored rename should undo");
+    assert!(from.exists());
+    assert!(!to.exists());
+
+    let _ = fs::remove_dir_all(&dir);
 }
 
 #[test]
@@ -386,3 +484,128 @@ fn path_snapshot_detects_replaced_path() {
 
     let _ = fs::remove_dir_all(&dir);
 }
+
+#[test]
+fn undo_to_and_redo_to_step_through_several_entries() {
+    let dir = uniq_path("undo-to");
+    let _ = fs::create_dir_all(&dir);
+    let mut mgr = UndoManager::new();
+    for name in ["one", "two", "three"] {
+        mgr.apply(Action::CreateFolder {
+            path: dir.join(name),
+        })
+        .expect("create folder");
+    }
+    let ids: Vec<u64> = mgr.history().iter().map(|item| item.id).collect();
+
+    assert_eq!(mgr.undo_to(ids[1]).expect("undo to second entry"), 2);
+    assert!(dir.join("one").exists());
+    assert!(!dir.join("two").exists());
+    assert!(!dir.join("three").exists());
+
+    let err = mgr.undo_to(ids[2]).expect_err("entry is on the redo stack");
+    assert_eq!(err.code(), UndoErrorCode::NotFound);
+
+    assert_eq!(mgr.redo_to(ids[2]).expect("redo to last entry"), 2);
+    assert!(dir.join("three").exists());
+    assert!(mgr.history().iter().all(|item| !item.undone));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn undo_entry_undoes_independent_entry_below_the_top() {
+    let dir = uniq_path("undo-entry");
+    let _ = fs::create_dir_all(&dir);
+    let from = dir.join("a.txt");
+    let to = dir.join("b.txt");
+    write_file(&from, b"content");
+
+    let mut mgr = UndoManager::new();
+    mgr.apply(Action::Rename {
+        from: from.clone(),
+        to: to.clone(),
+    })
+    .expect("rename");
+    mgr.apply(Action::CreateFolder {
+        path: dir.join("later"),
+    })
+    .expect("create folder");
+    let ids: Vec<u64> = mgr.history().iter().map(|item| item.id).collect();
+
+    mgr.undo_entry(ids[0])
+        .expect("independent entry should undo");
+    assert!(from.exists());
+    assert!(!to.exists());
+    assert!(dir.join("later").exists(), "later entry stays applied");
+
+    let history = mgr.history();
+    assert!(
+        !history
+            .iter()
+            .find(|item| item.id == ids[1])
+            .unwrap()
+            .undone
+    );
+    assert!(
+        history
+            .iter()
+            .find(|item| item.id == ids[0])
+            .unwrap()
+            .undone
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn undo_entry_refuses_conflicting_or_replaced_paths() {
+    let dir = uniq_path("undo-entry-conflict");
+    let _ = fs::create_dir_all(&dir);
+    let a = dir.join("a.txt");
+    let b = dir.join("b.txt");
+    let c = dir.join("c.txt");
+    write_file(&a, b"content");
+
+    let mut mgr = UndoManager::new();
+    mgr.apply(Action::Rename {
+        from: a.clone(),
+        to: b.clone(),
+    })
+    .expect("rename a to b");
+    mgr.apply(Action::Rename {
+        from: b.clone(),
+        to: c.clone(),
+    })
+    .expect("rename b to c");
+    let ids: Vec<u64> = mgr.history().iter().map(|item| item.id).collect();
+
+    let err = mgr.undo_entry(ids[0]).expect_err("later rename touches b");
+    assert_eq!(err.code(), UndoErrorCode::HistoryConflict);
+    assert!(c.exists());
+
+    let moved = dir.join("moved.txt");
+    let other = dir.join("other");
+    write_file(&dir.join("src.txt"), b"x");
+    mgr.apply(Action::Rename {
+        from: dir.join("src.txt"),
+        to: moved.clone(),
+    })
+    .expect("rename src");
+    mgr.apply(Action::CreateFolder {
+        path: other.clone(),
+    })
+    .expect("create folder");
+    let moved_id = mgr.history()[2].id;
+
+    // Replace the rename result behind the manager's back.
+    fs::remove_file(&moved).unwrap();
+    fs::create_dir(&moved).unwrap();
+    let err = mgr
+        .undo_entry(moved_id)
+        .expect_err("result path was replaced");
+    assert_eq!(err.code(), UndoErrorCode::SnapshotMismatch);
+    assert!(moved.is_dir());
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -1,15 +1,23 @@
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
 use std::fs;
 #[cfg(unix)]
 use std::os::unix::fs::MetadataExt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
-use crate::undo::{UndoError, UndoResult};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::backup::backup_size;
+use super::journal;
+use super::path_checks::{assert_path_snapshot, snapshot_existing_path};
+use crate::undo::{cleanup_stale_backups, UndoError, UndoErrorCode, UndoResult};
 
 const MAX_HISTORY: usize = 50;
+const MAX_HISTORY_ENTRY_PATHS: usize = 20;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "data", rename_all = "snake_case")]
 #[allow(dead_code)]
 pub enum Action {
     Rename {
@@ -43,9 +51,165 @@ pub enum Action {
     CreateFolder {
         path: PathBuf,
     },
+    /// Undo restores `before` exactly; redo re-applies `after`.
+    SetPermissions {
+        path: PathBuf,
+        before: PermissionsSnapshot,
+        after: PermissionsSnapshot,
+    },
+    SetOwnership {
+        path: PathBuf,
+        before: OwnershipSnapshot,
+        after: OwnershipSnapshot,
+    },
     Batch(Vec<Action>),
 }
 
+impl Action {
+    fn kind(&self) -> &'static str {
+        match self {
+            Action::Rename { .. } => "rename",
+            Action::Move { .. } => "move",
+            Action::Copy { .. } => "copy",
+            Action::Create { .. } => "create",
+            Action::Delete { .. } => "delete",
+            #[cfg(target_os = "windows")]
+            Action::SetHidden { .. } => "set_hidden",
+            Action::CreateFolder { .. } => "create_folder",
+            Action::SetPermissions { .. } => "set_permissions",
+            Action::SetOwnership { .. } => "set_ownership",
+            Action::Batch(_) => "batch",
+        }
+    }
+
+    /// Number of non-batch actions, counting nested batches.
+    fn leaf_count(&self) -> usize {
+        match self {
+            Action::Batch(actions) => actions.iter().map(Action::leaf_count).sum(),
+            _ => 1,
+        }
+    }
+
+    /// Every path the action touches, in order, excluding backups.
+    fn paths(&self) -> Vec<&Path> {
+        let mut out = Vec::new();
+        self.visit_paths(&mut |path| out.push(path));
+        out
+    }
+
+    fn visit_paths<'a>(&'a self, visit: &mut impl FnMut(&'a Path)) {
+        match self {
+            Action::Batch(actions) => actions.iter().for_each(|a| a.visit_paths(visit)),
+            Action::Rename { from, to } | Action::Move { from, to } | Action::Copy { from, to } => {
+                visit(from);
+                visit(to);
+            }
+            Action::Create { path, .. }
+            | Action::Delete { path, .. }
+            | Action::CreateFolder { path }
+            | Action::SetPermissions { path, .. }
+            | Action::SetOwnership { path, .. } => visit(path),
+            #[cfg(target_os = "windows")]
+            Action::SetHidden { path, .. } => visit(path),
+        }
+    }
+
+    /// Paths the action leaves behind when applied forward; undoing it starts from these.
+    fn result_paths<'a>(&'a self, out: &mut Vec<&'a Path>) {
+        match self {
+            Action::Batch(actions) => actions.iter().for_each(|a| a.result_paths(out)),
+            Action::Rename { to, .. } | Action::Move { to, .. } | Action::Copy { to, .. } => {
+                out.push(to)
+            }
+            Action::Delete { backup, .. } => out.push(backup),
+            Action::Create { path, .. }
+            | Action::CreateFolder { path }
+            | Action::SetPermissions { path, .. }
+            | Action::SetOwnership { path, .. } => out.push(path),
+            #[cfg(target_os = "windows")]
+            Action::SetHidden { path, .. } => out.push(path),
+        }
+    }
+}
+
+/// Identity of a path an action left behind. `None` when it could not be captured, for
+/// example because the result is a symlink.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ResultSnapshot {
+    pub path: PathBuf,
+    pub snapshot: Option<PathSnapshot>,
+}
+
+fn capture_results(action: &Action) -> Vec<ResultSnapshot> {
+    let mut paths = Vec::new();
+    action.result_paths(&mut paths);
+    paths
+        .into_iter()
+        .map(|path| ResultSnapshot {
+            path: path.to_path_buf(),
+            snapshot: snapshot_existing_path(path).ok(),
+        })
+        .collect()
+}
+
+fn paths_overlap(a: &Path, b: &Path) -> bool {
+    a.starts_with(b) || b.starts_with(a)
+}
+
+/// An action on the undo or redo stack, as stored in the journal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct HistoryEntry {
+    pub id: u64,
+    /// Unix time in milliseconds.
+    pub recorded_at: i64,
+    pub action: Action,
+    /// Result paths as they were after the action was last applied forward.
+    #[serde(default)]
+    pub results: Vec<ResultSnapshot>,
+}
+
+/// One row of `list_undo_history`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoHistoryItem {
+    pub id: u64,
+    pub kind: &'static str,
+    pub recorded_at: i64,
+    /// Affected paths, truncated for large batches.
+    pub paths: Vec<String>,
+    pub action_count: usize,
+    /// Bytes held in backups for this entry, e.g. the data of deleted files.
+    pub backup_bytes: u64,
+    /// True for entries that were undone and can be redone.
+    pub undone: bool,
+    #[serde(skip)]
+    backups: Vec<PathBuf>,
+}
+
+impl UndoHistoryItem {
+    fn from_entry(entry: &HistoryEntry, undone: bool) -> Self {
+        let mut paths = entry.action.paths();
+        paths.truncate(MAX_HISTORY_ENTRY_PATHS);
+        Self {
+            id: entry.id,
+            kind: entry.action.kind(),
+            recorded_at: entry.recorded_at,
+            paths: paths
+                .into_iter()
+                .map(|p| p.to_string_lossy().into_owned())
+                .collect(),
+            action_count: entry.action.leaf_count(),
+            backup_bytes: 0,
+            undone,
+            backups: journal::referenced_backups([entry]).into_iter().collect(),
+        }
+    }
+
+    fn measure_backups(&mut self) {
+        self.backup_bytes = self.backups.iter().map(|path| backup_size(path)).sum();
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 #[allow(dead_code)]
 pub enum Direction {
@@ -53,7 +217,7 @@ pub enum Direction {
     Backward,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct PermissionsSnapshot {
     pub readonly: bool,
@@ -63,7 +227,7 @@ pub struct PermissionsSnapshot {
     pub dacl: Option<Vec<u8>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct OwnershipSnapshot {
     #[cfg(unix)]
@@ -75,17 +239,62 @@ pub struct OwnershipSnapshot {
 #[derive(Default)]
 #[allow(dead_code)]
 pub struct UndoManager {
-    undo_stack: VecDeque<Action>,
-    redo_stack: VecDeque<Action>,
+    undo_stack: VecDeque<HistoryEntry>,
+    redo_stack: VecDeque<HistoryEntry>,
+    next_id: u64,
+    /// Where the history is persisted after every change; in-memory only when None.
+    journal: Option<PathBuf>,
 }
 
 impl UndoManager {
     #[allow(dead_code)]
     pub fn new() -> Self {
-        Self {
-            undo_stack: VecDeque::new(),
-            redo_stack: VecDeque::new(),
-        }
+        Self::default()
+    }
+
+    /// Loads the history persisted at `journal` and keeps it updated from now on. Entries
+    /// whose backups are gone are dropped.
+    pub fn with_journal(journal: PathBuf) -> Self {
+        let mut data = journal::load_journal(&journal);
+        journal::retain_recoverable(&mut data);
+        let next_id = data
+            .undo
+            .iter()
+            .chain(data.redo.iter())
+            .map(|entry| entry.id + 1)
+            .max()
+            .unwrap_or(0)
+            .max(data.next_id);
+        let mgr = Self {
+            undo_stack: data.undo,
+            redo_stack: data.redo,
+            next_id,
+            journal: Some(journal),
+        };
+        mgr.persist();
+        mgr
+    }
+
+    /// Undo history in chronological order: done actions oldest first, then undone ones in
+    /// the order they would be redone.
+    pub fn history(&self) -> Vec<UndoHistoryItem> {
+        let mut items = self.history_items();
+        items.iter_mut().for_each(UndoHistoryItem::measure_backups);
+        items
+    }
+
+    /// Like [`Self::history`], without sizing backups.
+    fn history_items(&self) -> Vec<UndoHistoryItem> {
+        self.undo_stack
+            .iter()
+            .map(|entry| UndoHistoryItem::from_entry(entry, false))
+            .chain(
+                self.redo_stack
+                    .iter()
+                    .rev()
+                    .map(|entry| UndoHistoryItem::from_entry(entry, true)),
+            )
+            .collect()
     }
 
     #[allow(dead_code)]
@@ -102,57 +311,173 @@ impl UndoManager {
     #[allow(dead_code)]
     pub fn apply(&mut self, mut action: Action) -> UndoResult<()> {
         super::engine::execute_action(&mut action, Direction::Forward)?;
-        self.undo_stack.push_back(action);
-        self.redo_stack.clear();
-        self.trim();
+        self.record_applied(action);
         Ok(())
     }
 
     pub fn undo(&mut self) -> UndoResult<()> {
-        let mut action = self
+        let mut entry = self
             .undo_stack
             .pop_back()
             .ok_or_else(UndoError::undo_unavailable)?;
-        match super::engine::execute_action(&mut action, Direction::Backward) {
+        match super::engine::execute_action(&mut entry.action, Direction::Backward) {
             Ok(_) => {
-                self.redo_stack.push_back(action);
+                self.redo_stack.push_back(entry);
+                self.persist();
                 Ok(())
             }
             Err(err) => {
-                self.undo_stack.push_back(action);
+                self.undo_stack.push_back(entry);
                 Err(err)
             }
         }
     }
 
     pub fn redo(&mut self) -> UndoResult<()> {
-        let mut action = self
+        let mut entry = self
             .redo_stack
             .pop_back()
             .ok_or_else(UndoError::redo_unavailable)?;
-        match super::engine::execute_action(&mut action, Direction::Forward) {
+        match super::engine::execute_action(&mut entry.action, Direction::Forward) {
             Ok(_) => {
-                self.undo_stack.push_back(action);
+                entry.results = capture_results(&entry.action);
+                self.undo_stack.push_back(entry);
                 self.trim();
+                self.persist();
                 Ok(())
             }
             Err(err) => {
-                self.redo_stack.push_back(action);
+                self.redo_stack.push_back(entry);
                 Err(err)
             }
         }
     }
 
+    /// Undoes entries from the top of the undo stack down to and including `id`. Stops at
+    /// the first failure; entries undone before it stay undone. Returns how many were undone.
+    pub fn undo_to(&mut self, id: u64) -> UndoResult<usize> {
+        let pos = self.undo_position(id)?;
+        let total = self.undo_stack.len() - pos;
+        for done in 0..total {
+            self.undo()
+                .map_err(|err| err.with_context(format!("Undid {done} of {total} entries")))?;
+        }
+        Ok(total)
+    }
+
+    /// Redoes entries from the top of the redo stack up to and including `id`.
+    pub fn redo_to(&mut self, id: u64) -> UndoResult<usize> {
+        let pos = self
+            .redo_stack
+            .iter()
+            .position(|entry| entry.id == id)
+            .ok_or_else(|| {
+                UndoError::not_found(format!("History entry {id} is not on the redo stack"))
+            })?;
+        let total = self.redo_stack.len() - pos;
+        for done in 0..total {
+            self.redo()
+                .map_err(|err| err.with_context(format!("Redid {done} of {total} entries")))?;
+        }
+        Ok(total)
+    }
+
+    /// Undoes a single entry that need not be on top. Refused when a later entry touches an
+    /// overlapping path, or when a path the entry left behind was replaced since.
+    pub fn undo_entry(&mut self, id: u64) -> UndoResult<()> {
+        let pos = self.undo_position(id)?;
+        if pos + 1 == self.undo_stack.len() {
+            return self.undo();
+        }
+        let entry = &self.undo_stack[pos];
+        let paths = entry.action.paths();
+        for later in self.undo_stack.iter().skip(pos + 1) {
+            let later_paths = later.action.paths();
+            let conflict = paths
+                .iter()
+                .find(|path| later_paths.iter().any(|other| paths_overlap(path, other)));
+            if let Some(path) = conflict {
+                return Err(UndoError::new(
+                    UndoErrorCode::HistoryConflict,
+                    format!(
+                        "History entry {} also changed {}; undo it first",
+                        later.id,
+                        path.display()
+                    ),
+                ));
+            }
+        }
+        for result in &entry.results {
+            let Some(snapshot) = &result.snapshot else {
+                return Err(UndoError::new(
+                    UndoErrorCode::HistoryConflict,
+                    format!(
+                        "Cannot verify {} is unchanged; undo later entries first",
+                        result.path.display()
+                    ),
+                ));
+            };
+            assert_path_snapshot(&result.path, snapshot)?;
+        }
+
+        let mut entry = self
+            .undo_stack
+            .remove(pos)
+            .ok_or_else(UndoError::undo_unavailable)?;
+        match super::engine::execute_action(&mut entry.action, Direction::Backward) {
+            Ok(_) => {
+                self.redo_stack.push_back(entry);
+                self.persist();
+                Ok(())
+            }
+            Err(err) => {
+                self.undo_stack.insert(pos, entry);
+                Err(err)
+            }
+        }
+    }
+
+    fn undo_position(&self, id: u64) -> UndoResult<usize> {
+        self.undo_stack
+            .iter()
+            .position(|entry| entry.id == id)
+            .ok_or_else(|| {
+                UndoError::not_found(format!("History entry {id} is not on the undo stack"))
+            })
+    }
+
     #[allow(dead_code)]
     pub fn clear(&mut self) {
         self.undo_stack.clear();
         self.redo_stack.clear();
+        self.persist();
     }
 
     pub fn record_applied(&mut self, action: Action) {
-        self.undo_stack.push_back(action);
+        let entry = HistoryEntry {
+            id: self.next_id,
+            recorded_at: chrono::Utc::now().timestamp_millis(),
+            results: capture_results(&action),
+            action,
+        };
+        self.next_id += 1;
+        self.undo_stack.push_back(entry);
         self.redo_stack.clear();
         self.trim();
+        self.persist();
+    }
+
+    /// Writes the journal. The filesystem change already happened, so a failed write is
+    /// logged rather than reported; only history after a crash is affected.
+    fn persist(&self) {
+        let Some(path) = &self.journal else {
+            return;
+        };
+        if let Err(err) =
+            journal::store_journal(path, self.next_id, &self.undo_stack, &self.redo_stack)
+        {
+            warn!("Failed to persist undo history: {err}");
+        }
     }
 
     fn trim(&mut self) {
@@ -168,6 +493,19 @@ pub struct UndoState {
 }
 
 impl UndoState {
+    /// Restores the history journaled by a previous run and removes backups neither it nor
+    /// `keep` references. Called once at startup, before any file operation can create backups.
+    pub fn restore(keep: HashSet<PathBuf>) -> Self {
+        let mgr = UndoManager::with_journal(journal::journal_path());
+        let mut referenced =
+            journal::referenced_backups(mgr.undo_stack.iter().chain(mgr.redo_stack.iter()));
+        referenced.extend(keep);
+        cleanup_stale_backups(&referenced);
+        Self {
+            inner: Arc::new(Mutex::new(mgr)),
+        }
+    }
+
     pub fn clone_inner(&self) -> Arc<Mutex<UndoManager>> {
         self.inner.clone()
     }
@@ -206,16 +544,53 @@ impl UndoState {
             .map_err(|_| UndoError::lock_failed("Undo manager poisoned"))?;
         mgr.redo()
     }
+
+    /// Backups are sized after the lock is released, since they can be large trees.
+    pub fn history(&self) -> UndoResult<Vec<UndoHistoryItem>> {
+        let mut items = {
+            let mgr = self
+                .inner
+                .lock()
+                .map_err(|_| UndoError::lock_failed("Undo manager poisoned"))?;
+            mgr.history_items()
+        };
+        items.iter_mut().for_each(UndoHistoryItem::measure_backups);
+        Ok(items)
+    }
+
+    pub fn undo_to(&self, id: u64) -> UndoResult<usize> {
+        let mut mgr = self
+            .inner
+            .lock()
+            .map_err(|_| UndoError::lock_failed("Undo manager poisoned"))?;
+        mgr.undo_to(id)
+    }
+
+    pub fn redo_to(&self, id: u64) -> UndoResult<usize> {
+        let mut mgr = self
+            .inner
+            .lock()
+            .map_err(|_| UndoError::lock_failed("Undo manager poisoned"))?;
+        mgr.redo_to(id)
+    }
+
+    pub fn undo_entry(&self, id: u64) -> UndoResult<()> {
+        let mut mgr = self
+            .inner
+            .lock()
+            .map_err(|_| UndoError::lock_failed("Undo manager poisoned"))?;
+        mgr.undo_entry(id)
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 enum PathKind {
     File,
     Dir,
     Other,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub(crate) struct PathSnapshot {
     kind: PathKind,
     #[cfg(unix)]
@@ -8,6 +8,7 @@ log_file="$script_dir/fake-rclone.log"
 mkdir_destination_exists_once_file="$script_dir/mkdir-destination-exists-once"
 mkdir_destination_exists_always_file="$script_dir/mkdir-destination-exists-always"
 config_dump_fail_file="$script_dir/config-dump-fail"
+copyto_corrupt_once_file="$script_dir/copyto-corrupt-once"
 mkdir -p "$state_root" "$provider_types_root"
 
 printf '%s\n' "$*" >> "$log_file"
@@ -265,12 +266,37 @@ case "$subcmd" in
         cp -R -- "$src" "$dst"
       else
         cp -f -- "$src" "$dst"
+        if [[ -f "$copyto_corrupt_once_file" ]]; then
+          rm -f -- "$copyto_corrupt_once_file"
+          printf 'corrupted' > "$dst"
+        fi
       fi
     else
       rm -rf -- "$dst"
       mv -- "$src" "$dst"
     fi
     ;;
+  hashsum)
+    if (( idx + 1 >= ${#args[@]} )); then
+      echo "missing hash/path for hashsum" >&2
+      exit 2
+    fi
+    target="$(map_spec_path "${args[$idx + 1]}")"
+    if [[ -d "$target" ]]; then
+      (
+        cd -- "$target"
+        find . -type f | LC_ALL=C sort | while IFS= read -r rel; do
+          rel="${rel#./}"
+          printf '%s  %s\n' "$(md5sum < "$rel" | cut -d' ' -f1)" "$rel"
+        done
+      )
+    elif [[ -f "$target" ]]; then
+      printf '%s  %s\n' "$(md5sum < "$target" | cut -d' ' -f1)" "$(basename -- "$target")"
+    else
+      echo "object not found" >&2
+      exit 3
+    fi
+    ;;
   *)
     echo "unsupported fake-rclone subcommand: $subcmd" >&2
     exit 2